@@ -0,0 +1,17 @@
+-- Add up migration script here
+
+-- Scores are now computed in Rust, according to the configured scoring policy. This table stores a fingerprint
+-- of the policy that was used to compute the scores currently cached in the "players", "nationalities" and
+-- "subdivisions" tables, so that we know to recompute them whenever the policy changes. It contains at most one row.
+CREATE TABLE scoring_policy (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    fingerprint BIGINT NOT NULL
+);
+
+DROP FUNCTION recompute_player_scores();
+DROP FUNCTION recompute_nation_scores();
+DROP FUNCTION recompute_subdivision_scores();
+DROP FUNCTION score_of_player(INTEGER);
+DROP FUNCTION score_of_nation(VARCHAR(2));
+DROP FUNCTION score_of_subdivision(VARCHAR(2), VARCHAR(3));
+DROP FUNCTION record_score(FLOAT, FLOAT, FLOAT, FLOAT);
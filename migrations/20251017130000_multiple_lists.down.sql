@@ -0,0 +1,93 @@
+-- Add down migration script here
+
+-- Only the default list can be represented in the old schema
+DELETE FROM records USING demons WHERE records.demon = demons.id AND demons.list <> 1;
+DELETE FROM creators USING demons WHERE creators.demon = demons.id AND demons.list <> 1;
+DELETE FROM demons WHERE list <> 1;
+
+ALTER TABLE players ADD COLUMN score DOUBLE PRECISION DEFAULT 0 NOT NULL;
+ALTER TABLE nationalities ADD COLUMN score DOUBLE PRECISION NOT NULL DEFAULT 0.0;
+ALTER TABLE subdivisions ADD COLUMN score DOUBLE PRECISION NOT NULL DEFAULT 0.0;
+
+UPDATE players SET score = player_scores.score FROM player_scores WHERE player_scores.player = players.id AND player_scores.list = 1;
+UPDATE nationalities SET score = nation_scores.score FROM nation_scores WHERE nation_scores.nation = nationalities.iso_country_code AND nation_scores.list = 1;
+UPDATE subdivisions SET score = subdivision_scores.score FROM subdivision_scores
+    WHERE subdivision_scores.nation = subdivisions.nation AND subdivision_scores.subdivision = subdivisions.iso_code AND subdivision_scores.list = 1;
+
+DROP VIEW ranked_nations;
+DROP VIEW ranked_players;
+DROP MATERIALIZED VIEW player_ranks;
+
+DROP TABLE subdivision_scores;
+DROP TABLE nation_scores;
+DROP TABLE player_scores;
+
+CREATE MATERIALIZED VIEW player_ranks AS
+       SELECT
+           RANK() OVER (ORDER BY score DESC) as rank,
+           id
+       FROM players
+       WHERE
+           score != 0 AND NOT banned;
+
+CREATE UNIQUE INDEX player_ranks_id_idx ON player_ranks(id);
+
+CREATE VIEW ranked_players AS
+SELECT
+    ROW_NUMBER() OVER(ORDER BY rank, id) AS index,
+    rank,
+    id, name, players.score, subdivision,
+    nationalities.iso_country_code,
+    nationalities.nation,
+    nationalities.continent
+FROM players
+LEFT OUTER JOIN nationalities
+             ON players.nationality = nationalities.iso_country_code
+NATURAL JOIN player_ranks;
+
+CREATE VIEW ranked_nations AS
+    SELECT
+        ROW_NUMBER() OVER(ORDER BY score DESC, iso_country_code) AS index,
+        RANK() OVER(ORDER BY score DESC) AS rank,
+        score,
+        iso_country_code,
+        nation,
+        continent
+    FROM nationalities
+    WHERE score > 0.0;
+
+DROP VIEW score_giving;
+CREATE VIEW score_giving AS
+    SELECT records.progress, demons.position, demons.requirement, records.player
+    FROM records
+    INNER JOIN demons
+    ON demons.id = records.demon
+    WHERE records.status_ = 'APPROVED' AND (demons.position <= 75 OR records.progress = 100)
+
+    UNION
+
+    SELECT 100, demons.position, demons.requirement, demons.verifier
+    FROM demons;
+
+DROP FUNCTION list_at(TIMESTAMP WITHOUT TIME ZONE);
+CREATE FUNCTION list_at(TIMESTAMP WITHOUT TIME ZONE)
+RETURNS TABLE (name CITEXT, position_ SMALLINT, requirement SMALLINT, video VARCHAR(200), thumbnail TEXT, verifier INTEGER, publisher INTEGER, id INTEGER, level_id BIGINT, current_position SMALLINT)
+AS $$
+SELECT name, CASE WHEN t.position IS NULL THEN demons.position ELSE t.position END, requirement, video, thumbnail, verifier, publisher, demons.id, level_id, demons.position AS current_position
+FROM demons
+         LEFT OUTER JOIN (
+    SELECT DISTINCT ON (id) id, position
+    FROM demon_modifications
+    WHERE time >= $1 AND position != -1
+    ORDER BY id, time
+) t
+                         ON demons.id = t.id
+WHERE NOT EXISTS (SELECT 1 FROM demon_additions WHERE demon_additions.id = demons.id AND time >= $1)
+$$
+LANGUAGE SQL STABLE;
+
+ALTER TABLE demons DROP CONSTRAINT unique_position;
+ALTER TABLE demons ADD CONSTRAINT unique_position UNIQUE (position) DEFERRABLE;
+ALTER TABLE demons DROP COLUMN list;
+
+DROP TABLE lists;
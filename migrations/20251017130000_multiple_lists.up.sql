@@ -0,0 +1,132 @@
+-- Add up migration script here
+
+CREATE TABLE lists (
+    id SERIAL PRIMARY KEY,
+    name CITEXT NOT NULL UNIQUE,
+    list_size SMALLINT NOT NULL DEFAULT 75 CHECK (list_size > 0),
+    extended_list_size SMALLINT NOT NULL DEFAULT 150,
+    CHECK (extended_list_size >= list_size)
+);
+
+-- All existing demons belong to the default list, whose ID is hardcoded as 1
+INSERT INTO lists (id, name) VALUES (1, 'demonlist');
+SELECT setval('lists_id_seq', 1);
+
+ALTER TABLE demons ADD COLUMN list INTEGER NOT NULL DEFAULT 1 REFERENCES lists(id) ON DELETE RESTRICT;
+ALTER TABLE demons ALTER COLUMN list DROP DEFAULT;
+
+-- Positions are only unique within a list
+ALTER TABLE demons DROP CONSTRAINT unique_position;
+ALTER TABLE demons ADD CONSTRAINT unique_position UNIQUE (list, position) DEFERRABLE;
+
+DROP FUNCTION list_at(TIMESTAMP WITHOUT TIME ZONE);
+CREATE FUNCTION list_at(TIMESTAMP WITHOUT TIME ZONE)
+RETURNS TABLE (name CITEXT, position_ SMALLINT, requirement SMALLINT, video VARCHAR(200), thumbnail TEXT, verifier INTEGER, publisher INTEGER, id INTEGER, level_id BIGINT, current_position SMALLINT, list INTEGER)
+AS $$
+SELECT name, CASE WHEN t.position IS NULL THEN demons.position ELSE t.position END, requirement, video, thumbnail, verifier, publisher, demons.id, level_id, demons.position AS current_position, demons.list
+FROM demons
+         LEFT OUTER JOIN (
+    SELECT DISTINCT ON (id) id, position
+    FROM demon_modifications
+    WHERE time >= $1 AND position != -1
+    ORDER BY id, time
+) t
+                         ON demons.id = t.id
+WHERE NOT EXISTS (SELECT 1 FROM demon_additions WHERE demon_additions.id = demons.id AND time >= $1)
+$$
+LANGUAGE SQL STABLE;
+
+-- Progress records on demons outside of a list's main list give no points
+DROP VIEW score_giving;
+CREATE VIEW score_giving AS
+    SELECT records.progress, demons.position, demons.requirement, records.player, demons.list
+    FROM records
+    INNER JOIN demons
+    ON demons.id = records.demon
+    INNER JOIN lists
+    ON lists.id = demons.list
+    WHERE records.status_ = 'APPROVED' AND (demons.position <= lists.list_size OR records.progress = 100)
+
+    UNION
+
+    SELECT 100, demons.position, demons.requirement, demons.verifier, demons.list
+    FROM demons;
+
+-- Scores are now tracked per list
+CREATE TABLE player_scores (
+    player INTEGER NOT NULL REFERENCES players(id) ON DELETE CASCADE ON UPDATE CASCADE,
+    list INTEGER NOT NULL REFERENCES lists(id) ON DELETE CASCADE,
+    score DOUBLE PRECISION NOT NULL,
+    PRIMARY KEY (list, player)
+);
+
+CREATE TABLE nation_scores (
+    nation VARCHAR(2) NOT NULL REFERENCES nationalities(iso_country_code) ON DELETE CASCADE,
+    list INTEGER NOT NULL REFERENCES lists(id) ON DELETE CASCADE,
+    score DOUBLE PRECISION NOT NULL,
+    PRIMARY KEY (list, nation)
+);
+
+CREATE TABLE subdivision_scores (
+    nation VARCHAR(2) NOT NULL,
+    subdivision VARCHAR(3) NOT NULL,
+    list INTEGER NOT NULL REFERENCES lists(id) ON DELETE CASCADE,
+    score DOUBLE PRECISION NOT NULL,
+    PRIMARY KEY (list, nation, subdivision),
+    FOREIGN KEY (nation, subdivision) REFERENCES subdivisions(nation, iso_code) ON DELETE CASCADE
+);
+
+INSERT INTO player_scores (player, list, score) SELECT id, 1, score FROM players WHERE score <> 0;
+INSERT INTO nation_scores (nation, list, score) SELECT iso_country_code, 1, score FROM nationalities WHERE score <> 0;
+INSERT INTO subdivision_scores (nation, subdivision, list, score) SELECT nation, iso_code, 1, score FROM subdivisions WHERE score <> 0;
+
+DROP VIEW ranked_players;
+DROP VIEW ranked_nations;
+DROP MATERIALIZED VIEW player_ranks;
+
+ALTER TABLE players DROP COLUMN score;
+ALTER TABLE nationalities DROP COLUMN score;
+ALTER TABLE subdivisions DROP COLUMN score;
+
+CREATE MATERIALIZED VIEW player_ranks AS
+       SELECT
+           player_scores.list,
+           RANK() OVER (PARTITION BY player_scores.list ORDER BY score DESC) as rank,
+           id
+       FROM player_scores
+       INNER JOIN players
+               ON players.id = player_scores.player
+       WHERE
+           score != 0 AND NOT banned;
+
+CREATE UNIQUE INDEX player_ranks_list_id_idx ON player_ranks(list, id);
+
+CREATE VIEW ranked_players AS
+SELECT
+    player_ranks.list,
+    ROW_NUMBER() OVER(PARTITION BY player_ranks.list ORDER BY rank, id) AS index,
+    rank,
+    id, name, player_scores.score, subdivision,
+    nationalities.iso_country_code,
+    nationalities.nation,
+    nationalities.continent
+FROM players
+LEFT OUTER JOIN nationalities
+             ON players.nationality = nationalities.iso_country_code
+NATURAL JOIN player_ranks
+INNER JOIN player_scores
+        ON player_scores.player = player_ranks.id AND player_scores.list = player_ranks.list;
+
+CREATE VIEW ranked_nations AS
+    SELECT
+        nation_scores.list,
+        ROW_NUMBER() OVER(PARTITION BY nation_scores.list ORDER BY score DESC, iso_country_code) AS index,
+        RANK() OVER(PARTITION BY nation_scores.list ORDER BY score DESC) AS rank,
+        score,
+        iso_country_code,
+        nationalities.nation,
+        continent
+    FROM nation_scores
+    INNER JOIN nationalities
+            ON nationalities.iso_country_code = nation_scores.nation
+    WHERE score > 0.0;
@@ -0,0 +1,5 @@
+-- Add down migration script here
+
+DROP TRIGGER demon_deletion_trigger ON demons;
+DROP FUNCTION audit_demon_deletion();
+DROP TABLE demon_deletions;
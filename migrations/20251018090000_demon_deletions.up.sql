@@ -0,0 +1,21 @@
+-- Add up migration script here
+
+-- See handling of record_deletions
+CREATE TABLE demon_deletions (
+    id INTEGER NOT NULL -- REFERENCES demons(id)
+) INHERITS (audit_log2);
+
+CREATE FUNCTION audit_demon_deletion() RETURNS trigger AS $demon_deletion_trigger$
+    BEGIN
+        INSERT INTO demon_modifications (userid, name, position, requirement, video, verifier, publisher, thumbnail, id)
+            (SELECT id, OLD.name, OLD.position, OLD.requirement, OLD.video, OLD.verifier, OLD.publisher, OLD.thumbnail, OLD.id
+            FROM active_user LIMIT 1);
+
+        INSERT INTO demon_deletions (userid, id)
+            (SELECT id, OLD.id FROM active_user LIMIT 1);
+
+        RETURN NULL;
+    END;
+$demon_deletion_trigger$ LANGUAGE plpgsql;
+
+CREATE TRIGGER demon_deletion_trigger AFTER DELETE ON demons FOR EACH ROW EXECUTE PROCEDURE audit_demon_deletion();
@@ -0,0 +1,8 @@
+-- Add up migration script here
+
+-- Merging two players deletes one of them, which on its own would only show up as a deletion in the audit log
+CREATE TABLE player_merges (
+    id INTEGER NOT NULL, -- REFERENCES players(id), the player that was merged into
+    merged INTEGER NOT NULL, -- the player that was merged (and deleted)
+    merged_name CITEXT NOT NULL
+) INHERITS (audit_log2);
@@ -0,0 +1,6 @@
+-- Add down migration script here
+
+DROP TABLE webhook_deliveries;
+DROP TABLE webhook_targets;
+DROP TYPE WEBHOOK_FORMAT;
+DROP TYPE WEBHOOK_EVENT;
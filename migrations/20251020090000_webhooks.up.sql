@@ -0,0 +1,36 @@
+-- Add up migration script here
+
+CREATE TYPE WEBHOOK_EVENT AS ENUM (
+    'RECORD_SUBMITTED',
+    'RECORD_APPROVED',
+    'RECORD_REJECTED',
+    'DEMON_ADDED',
+    'DEMON_MOVED',
+    'CLAIM_VERIFIED',
+    'PLAYER_BANNED'
+);
+
+CREATE TYPE WEBHOOK_FORMAT AS ENUM ('JSON', 'DISCORD', 'SLACK');
+
+CREATE TABLE webhook_targets (
+    id SERIAL PRIMARY KEY,
+    url TEXT NOT NULL,
+    format WEBHOOK_FORMAT NOT NULL DEFAULT 'JSON',
+    events WEBHOOK_EVENT[] NOT NULL,
+    -- key used to compute the HMAC signature of each delivery
+    secret TEXT NOT NULL
+);
+
+CREATE TABLE webhook_deliveries (
+    id SERIAL PRIMARY KEY,
+    target INTEGER NOT NULL REFERENCES webhook_targets(id) ON DELETE CASCADE,
+    event WEBHOOK_EVENT NOT NULL,
+    attempt SMALLINT NOT NULL,
+    time TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc'),
+    -- NULL if no response was received at all
+    status_code SMALLINT,
+    error TEXT,
+    payload TEXT NOT NULL
+);
+
+CREATE INDEX webhook_deliveries_target_idx ON webhook_deliveries(target);
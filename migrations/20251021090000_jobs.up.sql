@@ -0,0 +1,26 @@
+-- Add up migration script here
+
+CREATE TYPE JOB_STATUS AS ENUM ('PENDING', 'RUNNING', 'SUCCEEDED', 'FAILED');
+
+CREATE TABLE jobs (
+    id SERIAL PRIMARY KEY,
+    kind TEXT NOT NULL,
+    -- JSON serialization of the job
+    payload TEXT NOT NULL,
+    -- at most one unfinished job per (kind, dedup_key) can exist
+    dedup_key TEXT,
+    status JOB_STATUS NOT NULL DEFAULT 'PENDING',
+    attempts SMALLINT NOT NULL DEFAULT 0,
+    max_attempts SMALLINT NOT NULL,
+    -- the job will not be picked up before this point in time
+    scheduled_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc'),
+    -- while a job is running, its lock expires at this point. Jobs whose lock expired are assumed to
+    -- have been abandoned by a crashed worker and are picked up again
+    locked_until TIMESTAMP WITHOUT TIME ZONE,
+    last_error TEXT,
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc'),
+    finished_at TIMESTAMP WITHOUT TIME ZONE
+);
+
+CREATE UNIQUE INDEX jobs_dedup_key_idx ON jobs(kind, dedup_key) WHERE dedup_key IS NOT NULL AND status IN ('PENDING', 'RUNNING');
+CREATE INDEX jobs_scheduled_at_idx ON jobs(scheduled_at) WHERE status IN ('PENDING', 'RUNNING');
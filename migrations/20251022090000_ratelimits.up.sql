@@ -0,0 +1,10 @@
+-- Add up migration script here
+
+CREATE TABLE ratelimits (
+    name TEXT NOT NULL,
+    key TEXT NOT NULL,
+    -- "theoretical arrival time" of the GCRA algorithm, in seconds since the unix epoch. The limit is
+    -- fully replenished once this point in time is reached
+    tat DOUBLE PRECISION NOT NULL,
+    PRIMARY KEY (name, key)
+);
@@ -0,0 +1,5 @@
+-- Add down migration script here
+
+DROP TABLE totp_recovery_codes;
+
+ALTER TABLE members DROP COLUMN totp_secret;
@@ -0,0 +1,11 @@
+-- Add up migration script here
+
+-- base32 encoded TOTP secret. NULL if the user did not enable two-factor authentication
+ALTER TABLE members ADD COLUMN totp_secret TEXT;
+
+CREATE TABLE totp_recovery_codes (
+    member_id INTEGER NOT NULL REFERENCES members(member_id) ON DELETE CASCADE,
+    -- hex encoded SHA-256 hash of the recovery code. Each code can only be used once
+    code_hash TEXT NOT NULL,
+    PRIMARY KEY (member_id, code_hash)
+);
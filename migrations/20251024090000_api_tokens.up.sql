@@ -0,0 +1,14 @@
+-- Add up migration script here
+
+-- Named, individually revocable access tokens. A token is only valid while its row exists and has
+-- not expired. Scopes have the form "<resource>:<access>", see pointercrate_user::auth::token::Scope
+CREATE TABLE api_tokens (
+    id SERIAL PRIMARY KEY,
+    member_id INTEGER NOT NULL REFERENCES members(member_id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    scopes TEXT[] NOT NULL,
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc'),
+    expires_at TIMESTAMP WITHOUT TIME ZONE NOT NULL,
+    last_used_at TIMESTAMP WITHOUT TIME ZONE,
+    UNIQUE (member_id, name)
+);
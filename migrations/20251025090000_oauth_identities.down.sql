@@ -0,0 +1,10 @@
+-- Add down migration script here
+
+ALTER TABLE members ADD COLUMN google_account_id VARCHAR(256) UNIQUE;
+
+-- Identities at providers other than google are lost
+UPDATE members SET google_account_id = oauth_identities.subject
+FROM oauth_identities
+WHERE oauth_identities.member_id = members.member_id AND oauth_identities.provider = 'google';
+
+DROP TABLE oauth_identities;
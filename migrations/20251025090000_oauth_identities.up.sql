@@ -0,0 +1,20 @@
+-- Add up migration script here
+
+-- Identities at external OAuth2/OpenID Connect providers that can be used to sign in to a
+-- pointercrate account. "provider" is the id of a configured provider (e.g. "google"), and
+-- "subject" the provider's stable identifier for the account (the "sub" claim for OpenID Connect).
+-- Accounts can link at most one identity per provider.
+CREATE TABLE oauth_identities (
+    id SERIAL PRIMARY KEY,
+    member_id INTEGER NOT NULL REFERENCES members(member_id) ON DELETE CASCADE,
+    provider TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    linked_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc'),
+    UNIQUE (provider, subject),
+    UNIQUE (member_id, provider)
+);
+
+INSERT INTO oauth_identities (member_id, provider, subject)
+SELECT member_id, 'google', google_account_id FROM members WHERE google_account_id IS NOT NULL;
+
+ALTER TABLE members DROP COLUMN google_account_id;
@@ -0,0 +1,14 @@
+-- Add up migration script here
+
+-- Initial thumbnails are now derived from the verification video by whichever video host it is on
+-- (see pointercrate_demonlist::video::VideoHost), and passed along when inserting the demon. The
+-- trigger only takes care of falling back to the default thumbnail if there is none, or if the
+-- verifier is link banned.
+CREATE OR REPLACE FUNCTION set_initial_thumbnail() RETURNS trigger AS $$
+BEGIN
+    IF NEW.thumbnail IS NULL OR EXISTS(SELECT 1 FROM players WHERE players.id=NEW.verifier AND players.link_banned) THEN
+        NEW.thumbnail := 'https://i.ytimg.com/vi/zebrafishes/mqdefault.jpg';
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,19 @@
+-- Add up migration script here
+
+-- Runtime maintenance mode settings. There is only ever a single row in this table (enforced by the
+-- check constraint on the primary key), which the maintenance fairing periodically reloads.
+CREATE TABLE maintenance (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    enabled BOOLEAN NOT NULL DEFAULT FALSE,
+    starts_at TIMESTAMP WITHOUT TIME ZONE,
+    ends_at TIMESTAMP WITHOUT TIME ZONE,
+    allowlist TEXT[] NOT NULL DEFAULT '{}'
+);
+
+INSERT INTO maintenance DEFAULT VALUES;
+
+-- The banner message shown on every page while maintenance is scheduled or active, per language
+CREATE TABLE maintenance_messages (
+    language TEXT PRIMARY KEY,
+    message TEXT NOT NULL
+);
@@ -0,0 +1,4 @@
+-- Add down migration script here
+
+DROP VIEW submitter_trust;
+DROP TABLE record_claims;
@@ -0,0 +1,24 @@
+-- Add up migration script here
+
+-- Records reviewers have claimed, so that no two reviewers work on the same submission at once.
+-- Claims expire, after which anyone can claim the record again. Expired claims are simply ignored
+-- (and overwritten by the next claim).
+CREATE TABLE record_claims (
+    record INTEGER PRIMARY KEY REFERENCES records(id) ON DELETE CASCADE,
+    member_id INTEGER NOT NULL REFERENCES members(member_id) ON DELETE CASCADE,
+    claimed_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc'),
+    expires_at TIMESTAMP WITHOUT TIME ZONE NOT NULL
+);
+
+CREATE INDEX record_claims_member_id_idx ON record_claims(member_id);
+
+-- How trustworthy a submitter's submissions are, as the share of their reviewed submissions that
+-- got approved. Smoothed, so that submitters none of whose submissions were reviewed yet have a
+-- trust of 0.5.
+CREATE VIEW submitter_trust AS
+    SELECT submitter_id,
+           (COUNT(records.id) FILTER (WHERE status_ = 'APPROVED') + 1)::DOUBLE PRECISION
+               / (COUNT(records.id) FILTER (WHERE status_ IN ('APPROVED', 'REJECTED')) + 2) AS trust
+    FROM submitters
+    LEFT OUTER JOIN records ON records.submitter = submitters.submitter_id
+    GROUP BY submitter_id;
@@ -0,0 +1,11 @@
+-- Add up migration script here
+
+-- The reason a rejected record was rejected for, as a key into the catalogue of rejection reasons
+-- configured in the [demonlist] configuration table. Not every rejected record has a reason (if
+-- reasons are optional, or for records rejected before reasons were introduced).
+CREATE TABLE record_rejections (
+    record INTEGER PRIMARY KEY REFERENCES records(id) ON DELETE CASCADE,
+    reason TEXT NOT NULL
+);
+
+CREATE INDEX record_rejections_reason_idx ON record_rejections(reason);
@@ -0,0 +1,8 @@
+-- Add up migration script here
+
+-- Unguessable tokens handed out to submitters when they submit a record, allowing them to look up
+-- the state of their submission without an account.
+CREATE TABLE submission_tracking (
+    record INTEGER PRIMARY KEY REFERENCES records(id) ON DELETE CASCADE,
+    token TEXT NOT NULL UNIQUE
+);
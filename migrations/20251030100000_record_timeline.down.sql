@@ -0,0 +1,8 @@
+-- Add down migration script here
+
+DROP VIEW record_timeline;
+
+DROP INDEX record_modifications_id_idx;
+DROP INDEX record_additions_id_idx;
+
+ALTER TABLE records DROP COLUMN achieved_on;
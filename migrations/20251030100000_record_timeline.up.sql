@@ -0,0 +1,24 @@
+-- Add up migration script here
+
+-- The day a record was achieved on, as stated by its submitter (or corrected by a list moderator). Optional,
+-- since not every player knows when exactly they beat a level.
+ALTER TABLE records ADD COLUMN achieved_on DATE NULL;
+
+-- The audit log is queried per record to derive when records were submitted and approved
+CREATE INDEX record_additions_id_idx ON record_additions(id);
+CREATE INDEX record_modifications_id_idx ON record_modifications(id);
+
+-- When each record was submitted and, if it is approved, when it got approved, as derived from the audit log.
+-- Both are NULL for records older than the audit log.
+CREATE VIEW record_timeline AS
+    SELECT records.id,
+           additions.submitted_at,
+           CASE WHEN records.status_ = 'APPROVED' THEN
+               -- Every status change logs the previous status, so the most recent one is the change to 'approved'. Records
+               -- without status changes were approved right away.
+               COALESCE((SELECT MAX(time) FROM record_modifications WHERE record_modifications.id = records.id AND
+                         record_modifications.status_ IS NOT NULL), additions.submitted_at)
+           END AS approved_at
+    FROM records
+    -- Importing an archive adds a second addition entry for every record, the archived one is the earlier one
+    CROSS JOIN LATERAL (SELECT MIN(time) AS submitted_at FROM record_additions WHERE record_additions.id = records.id) AS additions;
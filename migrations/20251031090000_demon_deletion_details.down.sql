@@ -0,0 +1,21 @@
+-- Add down migration script here
+
+DROP INDEX demon_deletions_list_idx;
+
+CREATE OR REPLACE FUNCTION audit_demon_deletion() RETURNS trigger AS $demon_deletion_trigger$
+    BEGIN
+        INSERT INTO demon_modifications (userid, name, position, requirement, video, verifier, publisher, thumbnail, id)
+            (SELECT id, OLD.name, OLD.position, OLD.requirement, OLD.video, OLD.verifier, OLD.publisher, OLD.thumbnail, OLD.id
+            FROM active_user LIMIT 1);
+
+        INSERT INTO demon_deletions (userid, id)
+            (SELECT id, OLD.id FROM active_user LIMIT 1);
+
+        RETURN NULL;
+    END;
+$demon_deletion_trigger$ LANGUAGE plpgsql;
+
+INSERT INTO demon_modifications (time, userid, name, position, id)
+    SELECT time, userid, name, position, id FROM demon_deletions;
+
+ALTER TABLE demon_deletions DROP COLUMN name, DROP COLUMN position, DROP COLUMN list;
@@ -0,0 +1,31 @@
+-- Add up migration script here
+
+-- Deleted demons used to be logged by copying them into demon_modifications, which made deletions
+-- show up as modifications. Instead, keep everything needed to describe a deletion in
+-- demon_deletions itself.
+ALTER TABLE demon_deletions ADD COLUMN name CITEXT NULL, ADD COLUMN position SMALLINT NULL, ADD COLUMN list INTEGER NULL;
+
+UPDATE demon_deletions
+   SET name = demon_modifications.name, position = demon_modifications.position
+  FROM demon_modifications
+ WHERE demon_modifications.id = demon_deletions.id AND demon_modifications.time = demon_deletions.time;
+
+-- The demons below a deleted demon were moved up in the same transaction, so they tell us which list it was on
+UPDATE demon_deletions
+   SET list = (SELECT demons.list FROM demon_modifications INNER JOIN demons ON demons.id = demon_modifications.id
+                WHERE demon_modifications.time = demon_deletions.time LIMIT 1);
+
+DELETE FROM demon_modifications
+ USING demon_deletions
+ WHERE demon_modifications.id = demon_deletions.id AND demon_modifications.time = demon_deletions.time;
+
+CREATE OR REPLACE FUNCTION audit_demon_deletion() RETURNS trigger AS $demon_deletion_trigger$
+    BEGIN
+        INSERT INTO demon_deletions (userid, id, name, position, list)
+            (SELECT id, OLD.id, OLD.name, OLD.position, OLD.list FROM active_user LIMIT 1);
+
+        RETURN NULL;
+    END;
+$demon_deletion_trigger$ LANGUAGE plpgsql;
+
+CREATE INDEX demon_deletions_list_idx ON demon_deletions(list);
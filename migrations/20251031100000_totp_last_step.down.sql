@@ -0,0 +1,3 @@
+-- Add down migration script here
+
+ALTER TABLE members DROP COLUMN totp_last_step;
@@ -0,0 +1,5 @@
+-- Add up migration script here
+
+-- The TOTP time step (unix time divided by 30 seconds) of the last code accepted for this user. Codes
+-- from this step or earlier ones are rejected, so that an intercepted code cannot be replayed.
+ALTER TABLE members ADD COLUMN totp_last_step BIGINT;
@@ -0,0 +1,7 @@
+-- Add down migration script here
+
+-- The original tokens cannot be recovered from their hashes, so all existing ones are invalidated
+DELETE FROM submission_tracking;
+
+ALTER TABLE submission_tracking DROP COLUMN token_hash;
+ALTER TABLE submission_tracking ADD COLUMN token TEXT NOT NULL UNIQUE;
@@ -0,0 +1,10 @@
+-- Add up migration script here
+
+-- Tracking tokens are bearer credentials, so only their hex encoded SHA-256 hash is stored
+ALTER TABLE submission_tracking ADD COLUMN token_hash TEXT;
+
+UPDATE submission_tracking SET token_hash = encode(sha256(convert_to(token, 'UTF8')), 'hex');
+
+ALTER TABLE submission_tracking ALTER COLUMN token_hash SET NOT NULL;
+ALTER TABLE submission_tracking ADD UNIQUE (token_hash);
+ALTER TABLE submission_tracking DROP COLUMN token;
@@ -0,0 +1,347 @@
+//! Command line tool for administrating a pointercrate instance
+//!
+//! Connects to the database configured in `pointercrate.toml` (or via the `DATABASE_URL`
+//! environment variable), exactly like the server does. All changes are attributed to
+//! [`SYSTEM_USER_ID`](pointercrate_core::pool::SYSTEM_USER_ID) in the audit logs, and running servers are notified so that they do not
+//! serve stale cached responses.
+//!
+//! Unlike the server, this tool does not apply pending migrations implicitly. Use the `migrate`
+//! command for that.
+
+use futures::StreamExt;
+use pointercrate_core::{
+    error::{CoreError, PointercrateError},
+    job,
+    permission::Permission,
+    pool::PointercratePool,
+    util::csprng_u64,
+};
+use pointercrate_core_api::cache::ResponseCache;
+use pointercrate_demonlist::{
+    archive::{self, ArchiveImporter},
+    demon::Demon,
+    error::DemonlistError,
+    player::{DatabasePlayer, Player},
+    score::RecomputeScores,
+    LIST_ADMINISTRATOR, LIST_HELPER, LIST_MODERATOR,
+};
+use pointercrate_demonlist_api::cache;
+use pointercrate_integrate::gd::GeometryDashConnector;
+use pointercrate_user::{auth::AuthenticatedUser, error::UserError, User, ADMINISTRATOR, MODERATOR};
+use std::{fmt::Display, process::ExitCode};
+use tokio::{
+    fs::File,
+    io::{AsyncBufReadExt, AsyncWrite, AsyncWriteExt, BufReader, BufWriter},
+};
+
+const USAGE: &str = "\
+Usage: pointercrate-admin <command>
+
+Commands:
+    migrate                               Applies all pending database migrations
+    user grant <user> <permission>...     Grants the given permissions to the user with the given name
+    user revoke <user> <permission>...    Revokes the given permissions from the user with the given name
+    user reset-password <user>            Sets a new, randomly generated password for the user with the given name
+    user logout <user>                    Invalidates all access tokens of the user with the given name
+    scores recompute                      Schedules a recomputation of the scores of all players on all lists
+    player merge <player> <other>         Merges the player with ID <other> into the player with ID <player>
+    gd refresh <demon>                    Reloads the Geometry Dash data of the demon with the given ID
+    export [<file>]                       Writes an archive of all demonlist data to the given file (or stdout)
+    import <file>                         Imports an archive into this instance, which must not have any players, demons or records yet.
+                                          Scores are computed once the server's job worker picks up the scheduled recomputation
+
+Permissions are given either by name (administrator, moderator, list-helper, list-moderator,
+list-administrator) or by their bit (such as 0x10, for dedicated list permissions).";
+
+/// The permissions known to this tool, which are given by their text IDs without the common prefix
+const PERMISSIONS: [Permission; 5] = [ADMINISTRATOR, MODERATOR, LIST_ADMINISTRATOR, LIST_MODERATOR, LIST_HELPER];
+
+enum Error {
+    Usage,
+    Invalid(String),
+    Io(std::io::Error),
+    Pointercrate { code: u16, details: String },
+}
+
+impl Error {
+    fn pointercrate(err: impl PointercrateError) -> Self {
+        // Error messages are localized, which is only possible while handling requests
+        Error::Pointercrate {
+            code: err.error_code(),
+            details: format!("{:?}", err),
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<CoreError> for Error {
+    fn from(err: CoreError) -> Self {
+        Error::pointercrate(err)
+    }
+}
+
+impl From<DemonlistError> for Error {
+    fn from(err: DemonlistError) -> Self {
+        Error::pointercrate(err)
+    }
+}
+
+impl From<UserError> for Error {
+    fn from(err: UserError) -> Self {
+        Error::pointercrate(err)
+    }
+}
+
+impl From<sqlx::Error> for Error {
+    fn from(err: sqlx::Error) -> Self {
+        Error::pointercrate(CoreError::from(err))
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Usage => write!(f, "{}", USAGE),
+            Error::Invalid(message) => write!(f, "{}", message),
+            Error::Io(err) => write!(f, "I/O error: {}", err),
+            Error::Pointercrate { code, details } => write!(f, "{} (error code {})", details, code),
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let _ = dotenv::dotenv();
+
+    if let Err(err) = pointercrate_core::config::init()
+        .and_then(|_| pointercrate_demonlist::config::init())
+        .and_then(|_| pointercrate_demonlist_api::config::init())
+        .and_then(|_| pointercrate_user::config::init())
+    {
+        eprintln!("Invalid configuration: {}", err);
+
+        return ExitCode::FAILURE;
+    }
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    match run(&args.iter().map(String::as_str).collect::<Vec<_>>()).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}", err);
+
+            ExitCode::FAILURE
+        },
+    }
+}
+
+async fn run(args: &[&str]) -> Result<(), Error> {
+    // Check the arguments before connecting to the database
+    let valid = matches!(
+        args,
+        ["migrate"]
+            | ["user", "grant" | "revoke", _, _, ..]
+            | ["user", "reset-password" | "logout", _]
+            | ["scores", "recompute"]
+            | ["player", "merge", _, _]
+            | ["gd", "refresh", _]
+            | ["export"]
+            | ["export", _]
+            | ["import", _]
+    );
+
+    if !valid {
+        return Err(Error::Usage);
+    }
+
+    let pool = PointercratePool::connect().await;
+
+    match *args {
+        ["migrate"] => {
+            pool.run_migrations().await;
+
+            println!("Database schema is up to date");
+
+            Ok(())
+        },
+        ["user", "grant", user, ref permissions @ ..] => set_permissions(&pool, user, parse_permissions(permissions)?, true).await,
+        ["user", "revoke", user, ref permissions @ ..] => set_permissions(&pool, user, parse_permissions(permissions)?, false).await,
+        ["user", "reset-password", user] => reset_password(&pool, user).await,
+        ["user", "logout", user] => logout(&pool, user).await,
+        ["scores", "recompute"] => recompute(&pool).await,
+        ["player", "merge", player, other] => merge_players(&pool, parse_id(player)?, parse_id(other)?).await,
+        ["gd", "refresh", demon] => refresh_demon(&pool, parse_id(demon)?).await,
+        ["export"] => export(&pool, BufWriter::new(tokio::io::stdout())).await,
+        ["export", path] => export(&pool, BufWriter::new(File::create(path).await?)).await,
+        ["import", path] => import(&pool, path).await,
+        _ => Err(Error::Usage),
+    }
+}
+
+fn parse_id(id: &str) -> Result<i32, Error> {
+    id.parse().map_err(|_| Error::Invalid(format!("'{}' is not a valid ID", id)))
+}
+
+/// Parses permissions given by name or bit into the bit mask of all of them
+fn parse_permissions(permissions: &[&str]) -> Result<u16, Error> {
+    permissions.iter().try_fold(0, |mask, &permission| {
+        let bit = match permission.strip_prefix("0x") {
+            Some(hex) => u16::from_str_radix(hex, 16).ok().filter(|bit| bit.is_power_of_two()),
+            None => PERMISSIONS
+                .iter()
+                .find(|known| known.text_id().trim_start_matches("user-permissions.") == permission)
+                .map(Permission::bit),
+        };
+
+        bit.map(|bit| mask | bit)
+            .ok_or_else(|| Error::Invalid(format!("Unknown permission '{}'", permission)))
+    })
+}
+
+async fn set_permissions(pool: &PointercratePool, name: &str, permissions: u16, grant: bool) -> Result<(), Error> {
+    let mut transaction = pool.transaction().await?;
+    let mut user = User::by_name(name, &mut transaction).await?;
+
+    let new_permissions = match grant {
+        true => user.permissions | permissions,
+        false => user.permissions & !permissions,
+    };
+
+    user.set_permissions(new_permissions, &mut transaction).await?;
+    transaction.commit().await?;
+
+    println!("Permissions of {} are now {:#06x}", user, new_permissions);
+
+    Ok(())
+}
+
+async fn reset_password(pool: &PointercratePool, name: &str) -> Result<(), Error> {
+    let password = format!("{:016x}{:016x}", csprng_u64()?, csprng_u64()?);
+
+    let mut transaction = pool.transaction().await?;
+    let user = User::by_name(name, &mut transaction).await?;
+
+    AuthenticatedUser::by_id(user.id, &mut transaction)
+        .await?
+        .reset_password(password.clone(), &mut transaction)
+        .await?;
+    transaction.commit().await?;
+
+    println!("The new password of {} is {}", user, password);
+
+    Ok(())
+}
+
+async fn logout(pool: &PointercratePool, name: &str) -> Result<(), Error> {
+    let mut transaction = pool.transaction().await?;
+    let user = User::by_name(name, &mut transaction).await?;
+
+    AuthenticatedUser::by_id(user.id, &mut transaction)
+        .await?
+        .revoke_all_tokens(&mut transaction)
+        .await?;
+    transaction.commit().await?;
+
+    println!("Invalidated all access tokens of {}", user);
+
+    Ok(())
+}
+
+async fn recompute(pool: &PointercratePool) -> Result<(), Error> {
+    // The scoring policy is registered in code by the server binary, so this tool cannot know it.
+    // Instead, the server's job worker recomputes the scores using the configured policy and
+    // stores its fingerprint alongside them.
+    let mut connection = pool.connection().await?;
+
+    match job::enqueue(&RecomputeScores, &mut connection).await? {
+        Some(_) => println!("Scheduled a recomputation of the scores of all players, which the server will perform"),
+        None => println!("A recomputation of the scores of all players is already scheduled"),
+    }
+
+    Ok(())
+}
+
+async fn merge_players(pool: &PointercratePool, player_id: i32, other_id: i32) -> Result<(), Error> {
+    if player_id == other_id {
+        return Err(Error::Invalid("Cannot merge a player with themselves".to_string()));
+    }
+
+    let mut transaction = pool.transaction().await?;
+    let mut player = Player::by_id(player_id, &mut transaction).await?.upgrade(&mut transaction).await?;
+    let other = DatabasePlayer::by_id(other_id, &mut transaction).await?;
+
+    println!("Merging {} into {}", other, player.player.base);
+
+    player.merge(other, &mut transaction).await?;
+
+    ResponseCache::notify(&[cache::DEMONS, cache::RECORDS, cache::PLAYERS], &mut transaction).await?;
+    transaction.commit().await?;
+
+    Ok(())
+}
+
+async fn refresh_demon(pool: &PointercratePool, demon_id: i32) -> Result<(), Error> {
+    let mut connection = pool.connection().await?;
+    let demon = Demon::by_id(demon_id, &mut connection).await?;
+
+    if let Some(endpoint) = pointercrate_demonlist_api::config::gd_connector_endpoint().map_err(|err| Error::Invalid(err.to_string()))? {
+        pointercrate_integrate::set_gd_connector_endpoint(endpoint.to_string());
+    }
+
+    // Changes made by the connector are attributed to SYSTEM_USER_ID via the global fallback of the audit log
+    GeometryDashConnector::new(pool.clone_inner())
+        .refresh_demon_data(demon.base.name.clone(), demon.base.id, demon.level_id)
+        .await
+        .map_err(|err| Error::Invalid(format!("Failed to refresh data of {}: {}", demon.base, err)))?;
+
+    ResponseCache::notify(&[cache::DEMONS], &mut connection).await?;
+
+    println!("Refreshed Geometry Dash data of {}", demon.base);
+
+    Ok(())
+}
+
+async fn export(pool: &PointercratePool, mut output: impl AsyncWrite + Unpin) -> Result<(), Error> {
+    let mut connection = pool.read_connection().await?;
+    let mut entries = std::pin::pin!(archive::export(&mut connection));
+
+    while let Some(entry) = entries.next().await {
+        let line = serde_json::to_string(&entry?).expect("archive entries to be serializable");
+
+        output.write_all(line.as_bytes()).await?;
+        output.write_all(b"\n").await?;
+    }
+
+    Ok(output.flush().await?)
+}
+
+async fn import(pool: &PointercratePool, path: &str) -> Result<(), Error> {
+    let mut lines = BufReader::new(File::open(path).await?).lines();
+    let mut transaction = pool.transaction().await?;
+    let mut importer = ArchiveImporter::new(&mut transaction).await?;
+
+    while let Some(line) = lines.next_line().await? {
+        importer.import_line(&line).await?;
+    }
+
+    // Like for `scores recompute`, the server's job worker computes the scores using the configured
+    // scoring policy, which this tool does not know
+    let summary = importer.finish_without_scores().await?;
+
+    job::enqueue(&RecomputeScores, &mut transaction).await?;
+    ResponseCache::notify(&[cache::DEMONS, cache::RECORDS, cache::PLAYERS], &mut transaction).await?;
+    transaction.commit().await?;
+
+    println!(
+        "Imported {} lists, {} players, {} demons, {} creators, {} records, {} notes and {} audit log entries",
+        summary.lists, summary.players, summary.demons, summary.creators, summary.records, summary.notes, summary.audit_entries
+    );
+    println!("Scheduled a recomputation of the scores of all players, which the server will perform");
+
+    Ok(())
+}
@@ -0,0 +1,286 @@
+//! Server-side cache for the results of expensive, frequently requested endpoints
+//!
+//! Entries are keyed by the route and query string of the request (see [`CacheKey`]), and carry a
+//! set of tags describing which data they were computed from. Handlers that modify such data
+//! invalidate all entries with the respective tags via [`ResponseCache::invalidate`]. Invalidations
+//! are additionally broadcast via postgres' `NOTIFY`, so that they reach the caches of all
+//! pointercrate instances connected to the same database.
+//!
+//! Unlike [ETags](crate::etag), which only save bandwidth, cache hits do not touch the database at
+//! all.
+
+use pointercrate_core::{config, error::CoreError, metrics, pool::PointercratePool};
+use rocket::{
+    fairing::{Fairing, Info, Kind},
+    request::{FromRequest, Outcome},
+    Build, Request, Rocket,
+};
+use sqlx::{postgres::PgListener, PgConnection, Pool, Postgres};
+use std::{
+    any::Any,
+    collections::HashMap,
+    future::Future,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+    time::{Duration, Instant},
+};
+
+/// The postgres channel on which invalidated tags are broadcast
+const CHANNEL: &str = "pointercrate_cache";
+
+/// Upper bound on the number of cached entries, to bound memory usage in case of many distinct
+/// query strings
+const MAX_ENTRIES: usize = 10_000;
+
+/// How long to wait before listening for invalidations again after the listener failed
+const LISTENER_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Rocket fairing setting up the [`ResponseCache`], and the listener receiving invalidations from
+/// other pointercrate instances
+///
+/// The lifetime of cache entries is configured via `response_cache_ttl` in the `[core]`
+/// configuration table. Setting it to `0` disables caching.
+pub struct ResponseCacheFairing;
+
+#[rocket::async_trait]
+impl Fairing for ResponseCacheFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "Response Cache",
+            kind: Kind::Ignite | Kind::Singleton,
+        }
+    }
+
+    async fn on_ignite(&self, rocket: Rocket<Build>) -> rocket::fairing::Result {
+        let Some(pool) = rocket.state::<PointercratePool>() else {
+            log::error!("No database pool registered, cannot set up response cache");
+
+            return Err(rocket);
+        };
+
+        let ttl = match config::core() {
+            Ok(config) => config.response_cache_ttl,
+            Err(err) => {
+                log::error!("Cannot set up response cache: {}", err);
+
+                return Err(rocket);
+            },
+        };
+
+        let cache = ResponseCache::new(Duration::from_secs(ttl));
+
+        if !cache.is_disabled() {
+            rocket::tokio::spawn(cache.clone().listen(pool.clone_inner()));
+        }
+
+        Ok(rocket.manage(cache))
+    }
+}
+
+/// The key under which a response is cached, consisting of the route and query string of the
+/// request it was computed for
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey(String);
+
+impl From<String> for CacheKey {
+    fn from(key: String) -> Self {
+        CacheKey(key)
+    }
+}
+
+impl From<&str> for CacheKey {
+    fn from(key: &str) -> Self {
+        CacheKey(key.to_string())
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for CacheKey {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(CacheKey(request.uri().to_string()))
+    }
+}
+
+struct CacheEntry {
+    value: Arc<dyn Any + Send + Sync>,
+    tags: &'static [&'static str],
+    expires_at: Instant,
+}
+
+#[derive(Clone)]
+pub struct ResponseCache(Arc<CacheInner>);
+
+struct CacheInner {
+    ttl: Duration,
+    entries: RwLock<HashMap<CacheKey, CacheEntry>>,
+
+    /// Incremented on every invalidation, to detect values that were computed concurrently to an
+    /// invalidation (and might thus be stale)
+    generation: AtomicU64,
+}
+
+impl ResponseCache {
+    /// Constructs a new cache whose entries expire after the given duration, even if they are never
+    /// invalidated. A duration of zero disables caching.
+    pub fn new(ttl: Duration) -> Self {
+        ResponseCache(Arc::new(CacheInner {
+            ttl,
+            entries: RwLock::new(HashMap::new()),
+            generation: AtomicU64::new(0),
+        }))
+    }
+
+    fn is_disabled(&self) -> bool {
+        self.0.ttl.is_zero()
+    }
+
+    /// Gets the value cached under the given key, or computes and caches it if there is none
+    ///
+    /// `tags` are the tags under which the computed value is cached. Errors are not cached.
+    ///
+    /// Invalidations happen on writes to the primary database, so `compute` should read from
+    /// [`PointercratePool::connection`] rather than a replica, which might still serve the data
+    /// from before the invalidation and keep it cached until the TTL runs out.
+    pub async fn get_or_insert<T, E, F>(&self, key: impl Into<CacheKey>, tags: &'static [&'static str], compute: F) -> Result<T, E>
+    where
+        T: Clone + Send + Sync + 'static,
+        F: Future<Output = Result<T, E>>,
+    {
+        if self.is_disabled() {
+            return compute.await;
+        }
+
+        let key = key.into();
+
+        if let Some(value) = self.get(&key) {
+            metrics::record_cache_lookup("hit");
+
+            return Ok(value);
+        }
+
+        metrics::record_cache_lookup("miss");
+
+        let generation = self.0.generation.load(Ordering::Acquire);
+        let value = compute.await?;
+
+        self.insert(key, tags, generation, value.clone());
+
+        Ok(value)
+    }
+
+    fn get<T: Clone + 'static>(&self, key: &CacheKey) -> Option<T> {
+        let entries = self.0.entries.read().unwrap();
+        let entry = entries.get(key).filter(|entry| entry.expires_at > Instant::now())?;
+
+        entry.value.downcast_ref::<T>().cloned()
+    }
+
+    fn insert<T: Send + Sync + 'static>(&self, key: CacheKey, tags: &'static [&'static str], generation: u64, value: T) {
+        let mut entries = self.0.entries.write().unwrap();
+
+        // Checked while holding the lock, so no invalidation can happen between the check and the insertion
+        if self.0.generation.load(Ordering::Acquire) != generation {
+            return;
+        }
+
+        if entries.len() >= MAX_ENTRIES {
+            let now = Instant::now();
+
+            entries.retain(|_, entry| entry.expires_at > now);
+
+            if entries.len() >= MAX_ENTRIES {
+                return;
+            }
+        }
+
+        entries.insert(
+            key,
+            CacheEntry {
+                value: Arc::new(value),
+                tags,
+                expires_at: Instant::now() + self.0.ttl,
+            },
+        );
+    }
+
+    /// Invalidates all entries with any of the given tags, on this and all other pointercrate
+    /// instances
+    ///
+    /// If `connection` is inside a transaction, other instances only receive the invalidation once
+    /// the transaction commits (at which point this instance invalidates the entries a second
+    /// time, so that no entries computed from data predating the transaction survive).
+    pub async fn invalidate(&self, tags: &[&str], connection: &mut PgConnection) -> Result<(), CoreError> {
+        self.invalidate_locally(tags);
+
+        ResponseCache::notify(tags, connection).await
+    }
+
+    /// Broadcasts an invalidation of all entries with any of the given tags to all pointercrate
+    /// instances (including this one), for use where the [`ResponseCache`] itself is not available
+    ///
+    /// The notification is only sent once the transaction `connection` is in (if any) commits.
+    pub async fn notify(tags: &[&str], connection: &mut PgConnection) -> Result<(), CoreError> {
+        sqlx::query!("SELECT pg_notify($1, $2)", CHANNEL, tags.join(","))
+            .execute(connection)
+            .await?;
+
+        Ok(())
+    }
+
+    fn invalidate_locally(&self, tags: &[&str]) {
+        let mut entries = self.0.entries.write().unwrap();
+
+        self.0.generation.fetch_add(1, Ordering::AcqRel);
+
+        entries.retain(|_, entry| !entry.tags.iter().any(|tag| tags.contains(tag)));
+    }
+
+    /// Removes all entries from the cache
+    fn clear(&self) {
+        let mut entries = self.0.entries.write().unwrap();
+
+        self.0.generation.fetch_add(1, Ordering::AcqRel);
+
+        entries.clear();
+    }
+
+    /// Applies invalidations broadcast by other pointercrate instances (see
+    /// [`ResponseCache::invalidate`]) until the given pool is closed
+    pub async fn listen(self, pool: Pool<Postgres>) {
+        loop {
+            let result: Result<(), sqlx::Error> = async {
+                let mut listener = PgListener::connect_with(&pool).await?;
+
+                listener.listen(CHANNEL).await?;
+
+                loop {
+                    match listener.try_recv().await? {
+                        Some(notification) => self.invalidate_locally(&notification.payload().split(',').collect::<Vec<_>>()),
+                        None => {
+                            // We might have missed invalidations while the connection was lost
+                            log::warn!("Lost connection while listening for cache invalidations, clearing cache");
+
+                            self.clear();
+                        },
+                    }
+                }
+            }
+            .await;
+
+            match result {
+                Err(sqlx::Error::PoolClosed) => return,
+                Err(err) => log::error!("Failed to listen for cache invalidations: {:?}", err),
+                Ok(()) => (),
+            }
+
+            // Same as above, we cannot know what we missed
+            self.clear();
+
+            rocket::tokio::time::sleep(LISTENER_RETRY_INTERVAL).await;
+        }
+    }
+}
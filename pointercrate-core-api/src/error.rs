@@ -4,6 +4,7 @@ use crate::response::Page;
 use log::info;
 use pointercrate_core::error::PointercrateError;
 use pointercrate_core::localization::{LocaleConfiguration, LANGUAGE};
+use pointercrate_core::metrics;
 use pointercrate_core_pages::error::ErrorFragment;
 use pointercrate_core_pages::PageFragment;
 use rocket::outcome::Outcome;
@@ -26,10 +27,14 @@ pub struct ErrorResponder {
     #[serde(rename = "code")]
     error_code: u16,
     data: Value,
+    #[serde(skip)]
+    headers: Vec<(&'static str, String)>,
 }
 
 impl<'r> Responder<'r, 'static> for ErrorResponder {
-    fn respond_to(self, request: &'r Request<'_>) -> rocket::response::Result<'static> {
+    fn respond_to(mut self, request: &'r Request<'_>) -> rocket::response::Result<'static> {
+        metrics::record_error(self.error_code);
+
         let accept = match request.accept() {
             None => {
                 info!("No ACCEPT header set, assuming application/json");
@@ -40,8 +45,9 @@ impl<'r> Responder<'r, 'static> for ErrorResponder {
         };
 
         let status = Status::from_code(self.error_code / 100).unwrap_or(Status::InternalServerError);
+        let headers = std::mem::take(&mut self.headers);
 
-        if *accept == MediaType::HTML {
+        let mut response = if *accept == MediaType::HTML {
             let preference_manager = request.rocket().state::<PreferenceManager>().ok_or(Status::InternalServerError)?;
             let preferences = ClientPreferences::from_cookies(request.cookies(), preference_manager);
 
@@ -62,10 +68,16 @@ impl<'r> Responder<'r, 'static> for ErrorResponder {
                 })
             });
 
-            Response::build_from(Page::new(fragment).respond_to(request)?).status(status).ok()
+            Response::build_from(Page::new(fragment).respond_to(request)?)
         } else {
-            Response::build_from(Json(self).respond_to(request)?).status(status).ok()
+            Response::build_from(Json(self).respond_to(request)?)
+        };
+
+        for (name, value) in headers {
+            response.raw_header(name, value);
         }
+
+        response.status(status).ok()
     }
 }
 
@@ -74,6 +86,7 @@ impl<E: PointercrateError> From<E> for ErrorResponder {
         ErrorResponder {
             message: error.to_string(),
             error_code: error.error_code(),
+            headers: error.headers(),
             data: serde_json::to_value(error).expect("failed to serialize error to json"),
         }
     }
@@ -14,6 +14,12 @@ pub struct Tagged<T: Taggable>(pub T);
 pub struct Precondition(Vec<String> /* ensure private constructor for type level proof of header */);
 
 impl Precondition {
+    /// Constructs a precondition from an `If-Match` value that was not received as a header, such
+    /// as those of the individual operations of a batch request
+    pub fn from_if_match(if_match: &str) -> Self {
+        Precondition(if_match.split(',').map(ToString::to_string).collect())
+    }
+
     pub fn require_etag_match<T: Taggable>(&self, taggable: &T) -> Result<(), CoreError> {
         let patch_etag = taggable.patch_part().to_string();
 
@@ -39,7 +45,7 @@ impl<'r> FromRequest<'r> for Precondition {
         request
             .headers()
             .get_one("if-match")
-            .map(|if_match| Precondition(if_match.split(',').map(ToString::to_string).collect()))
+            .map(Precondition::from_if_match)
             .ok_or(CoreError::PreconditionFailed)
             .into_outcome()
     }
@@ -1,7 +1,9 @@
+pub mod cache;
 pub mod error;
 pub mod etag;
 pub mod localization;
 pub mod maintenance;
+pub mod metrics;
 pub mod pagination;
 pub mod preferences;
 pub mod query;
@@ -1,29 +1,134 @@
 //! Module providing a "maintenance mode" fairing (middleware)
 
 use crate::{error::Result, localization::ClientLocale};
-use pointercrate_core::{error::CoreError, localization::LANGUAGE};
+use pointercrate_core::{error::CoreError, localization::LANGUAGE, maintenance::MaintenanceSettings, pool::PointercratePool};
 use rocket::{
     fairing::{Fairing, Info, Kind},
     http::Method,
     routes, uri, Build, Data, Request, Rocket,
 };
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+use std::{
+    sync::RwLock,
+    time::{Duration, Instant},
+};
+
+/// How long the maintenance settings are cached before being reloaded from the database
+///
+/// This bounds how long it takes for changes made on one server instance to propagate to all
+/// others (changes made on this instance are picked up immediately, see [`MaintenanceMode::update`]).
+const REFRESH_INTERVAL: Duration = Duration::from_secs(10);
 
-/// Rocket fairing that causes all mutating requests (aka non-GET requests) to return 503 SERVICE UNAVAILABLE if `.0` is `true`.
+/// Rocket fairing that causes all mutating requests (aka non-GET requests) to return 503 SERVICE UNAVAILABLE while
+/// maintenance mode is active.
+///
+/// Maintenance mode is active if either `.0` is `true`, or if it was switched on (or scheduled) at runtime via the
+/// [`MaintenanceSettings`] stored in the database. Requests that a registered [`MaintenanceExemption`] deems exempt
+/// are let through regardless.
 ///
 /// Works in a very hacky way, as rocket does not allow fairing to terminate requests. Thus we instead rewrite the
 /// request on the fly to be a GET /maintenance, which is an endpoint that unconditionally returns a 503 response.
-/// This endpoint only exists when maintainence mode is active (it is dynamically mounted in `on_ignite`).
 ///
 /// Idea taken from https://stackoverflow.com/questions/70011965/global-authentication-authorization-in-rocket-based-on-a-header
 #[derive(Default)]
 pub struct MaintenanceFairing(bool);
 
 impl MaintenanceFairing {
+    /// Constructs a new maintenance fairing. If `read_only` is `true`, maintenance mode is
+    /// unconditionally active and cannot be switched off at runtime.
     pub fn new(read_only: bool) -> Self {
         MaintenanceFairing(read_only)
     }
 }
 
+/// Decides whether a mutating request may go through despite maintenance mode being active
+#[rocket::async_trait]
+pub trait MaintenanceExemption: Send + Sync {
+    /// Whether the given request is exempt from maintenance mode
+    ///
+    /// Only called for mutating requests while maintenance mode is active.
+    async fn is_exempt(&self, request: &Request<'_>, settings: &MaintenanceSettings) -> bool;
+}
+
+/// Rocket state holding the current maintenance settings, managed by the [`MaintenanceFairing`]
+pub struct MaintenanceMode {
+    pool: Pool<Postgres>,
+    forced: bool,
+    cached: RwLock<(Instant, MaintenanceSettings)>,
+}
+
+/// The maintenance settings, together with whether maintenance mode is currently in effect
+#[derive(Serialize, Debug)]
+pub struct MaintenanceStatus {
+    #[serde(flatten)]
+    pub settings: MaintenanceSettings,
+
+    /// Whether maintenance mode is currently active
+    pub active: bool,
+
+    /// Whether maintenance mode was activated at startup, in which case it cannot be switched off
+    /// at runtime
+    pub forced: bool,
+}
+
+impl MaintenanceMode {
+    async fn load(pool: Pool<Postgres>, forced: bool) -> pointercrate_core::error::Result<Self> {
+        let settings = MaintenanceSettings::load(&mut *pool.acquire().await?).await?;
+
+        Ok(MaintenanceMode {
+            pool,
+            forced,
+            cached: RwLock::new((Instant::now(), settings)),
+        })
+    }
+
+    /// The current maintenance settings, as last loaded from the database
+    pub fn settings(&self) -> MaintenanceSettings {
+        self.cached.read().unwrap().1.clone()
+    }
+
+    /// Whether maintenance mode is currently in effect
+    pub fn is_active(&self) -> bool {
+        self.forced || self.cached.read().unwrap().1.is_active()
+    }
+
+    pub fn status(&self) -> MaintenanceStatus {
+        let settings = self.settings();
+
+        MaintenanceStatus {
+            active: self.forced || settings.is_active(),
+            forced: self.forced,
+            settings,
+        }
+    }
+
+    /// Replaces the cached settings. To be called after changes to the settings have been
+    /// committed to the database.
+    pub fn update(&self, settings: MaintenanceSettings) {
+        *self.cached.write().unwrap() = (Instant::now(), settings);
+    }
+
+    /// Reloads the settings from the database if the cached ones are older than [`REFRESH_INTERVAL`]
+    ///
+    /// Failures are logged, and the stale settings kept.
+    async fn refresh(&self) {
+        if self.cached.read().unwrap().0.elapsed() < REFRESH_INTERVAL {
+            return;
+        }
+
+        let settings = match self.pool.acquire().await {
+            Ok(mut connection) => MaintenanceSettings::load(&mut connection).await,
+            Err(err) => Err(err.into()),
+        };
+
+        match settings {
+            Ok(settings) => self.update(settings),
+            Err(err) => log::error!("Failed to reload maintenance settings: {:?}", err),
+        }
+    }
+}
+
 #[rocket::async_trait]
 impl Fairing for MaintenanceFairing {
     fn info(&self) -> Info {
@@ -33,19 +138,48 @@ impl Fairing for MaintenanceFairing {
         }
     }
 
-    async fn on_ignite(&self, mut rocket: Rocket<Build>) -> rocket::fairing::Result {
-        if self.0 {
+    async fn on_ignite(&self, rocket: Rocket<Build>) -> rocket::fairing::Result {
+        let Some(pool) = rocket.state::<PointercratePool>() else {
+            log::error!("No database pool registered, cannot set up maintenance mode");
+
+            return Err(rocket);
+        };
+
+        let Ok(mode) = MaintenanceMode::load(pool.clone_inner(), self.0).await else {
+            log::error!("Failed to load maintenance settings");
+
+            return Err(rocket);
+        };
+
+        if mode.is_active() {
             log::warn!("Maintenance mode activated! All non-GET requests will receive a 503 response!");
-            rocket = rocket.mount("/", routes![maintenance]);
         }
-        Ok(rocket)
+
+        Ok(rocket.manage(mode).mount("/", routes![maintenance]))
     }
 
     async fn on_request(&self, request: &mut Request<'_>, _: &mut Data<'_>) {
-        if self.0 && request.method() != Method::Get {
-            request.set_uri(uri!("/maintenance"));
-            request.set_method(Method::Get);
+        let Some(mode) = request.rocket().state::<MaintenanceMode>() else {
+            return;
+        };
+
+        // Refresh even for GET requests, so that pages show up-to-date maintenance banners
+        mode.refresh().await;
+
+        if request.method() == Method::Get || !mode.is_active() {
+            return;
         }
+
+        if !mode.forced {
+            if let Some(exemption) = request.rocket().state::<Box<dyn MaintenanceExemption>>() {
+                if exemption.is_exempt(request, &mode.settings()).await {
+                    return;
+                }
+            }
+        }
+
+        request.set_uri(uri!("/maintenance"));
+        request.set_method(Method::Get);
     }
 }
 
@@ -0,0 +1,86 @@
+//! Module providing a fairing recording request metrics, and the `/metrics` endpoint exposing them
+//! to prometheus
+
+use crate::{error::Result, localization::ClientLocale};
+use pointercrate_core::{config, error::CoreError, localization::LANGUAGE, metrics, pool::PointercratePool};
+use rocket::{
+    fairing::{Fairing, Info, Kind},
+    http::ContentType,
+    request::{FromRequest, Outcome},
+    routes, Build, Data, Request, Response, Rocket, State,
+};
+use std::time::Instant;
+
+/// Rocket fairing that records the duration of every request in the per-route histogram of
+/// [`metrics::observe_request`], and mounts the `/metrics` endpoint
+///
+/// If the `METRICS_TOKEN` environment variable is set, the endpoint requires it to be passed as
+/// bearer token.
+pub struct MetricsFairing;
+
+/// When the current request was received, stored in the request local cache
+struct RequestStart(Instant);
+
+#[rocket::async_trait]
+impl Fairing for MetricsFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "Metrics",
+            kind: Kind::Ignite | Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_ignite(&self, rocket: Rocket<Build>) -> rocket::fairing::Result {
+        Ok(rocket.mount("/", routes![serve_metrics]))
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, _: &mut Data<'_>) {
+        request.local_cache(|| RequestStart(Instant::now()));
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        let start = request.local_cache(|| RequestStart(Instant::now()));
+
+        // Label by route instead of path, as paths contain IDs and would cause an unbounded number of time series
+        let route = request.route().map(|route| route.uri.as_str()).unwrap_or("<unmatched>");
+
+        metrics::observe_request(request.method().as_str(), route, response.status().code, start.0.elapsed());
+    }
+}
+
+/// The bearer token passed in the `Authorization` header, if any
+struct BearerToken(Option<String>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for BearerToken {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let token = request
+            .headers()
+            .get_one("Authorization")
+            .and_then(|authorization| authorization.strip_prefix("Bearer "))
+            .map(ToString::to_string);
+
+        Outcome::Success(BearerToken(token))
+    }
+}
+
+// we can't use the #[localized] proc-macro here due to issues related to imports
+// (https://github.com/stadust/pointercrate/pull/232#discussion_r2118293806)
+#[rocket::get("/metrics")]
+async fn serve_metrics(locale: ClientLocale, token: BearerToken, pool: &State<PointercratePool>) -> Result<(ContentType, String)> {
+    LANGUAGE
+        .scope(locale.0, async {
+            if let Some(expected) = &config::core().map_err(CoreError::from)?.metrics_token {
+                if token.0.as_deref() != Some(expected.as_str()) {
+                    return Err(CoreError::Unauthorized.into());
+                }
+            }
+
+            pool.record_metrics();
+
+            Ok((ContentType::Plain, metrics::render()))
+        })
+        .await
+}
@@ -2,23 +2,36 @@ use std::collections::BTreeMap;
 
 use pointercrate_core::{
     error::CoreError,
-    pagination::{Paginatable, PaginationParameters, PaginationQuery},
+    pagination::{Paginatable, PaginationParameters, PaginationQuery, SortParameters, Sortable, SortedPaginationQuery},
 };
 use rocket::serde::json::Json;
+use serde::Serialize;
 use sqlx::PgConnection;
 
 use crate::response::Response2;
 
+#[cfg(feature = "testing")]
+pub mod testing;
+
+#[derive(Debug)]
+enum Rel {
+    /// The link points to the base query with its pagination parameters replaced
+    Parameters(PaginationParameters),
+
+    /// The link points to the given, already serialized, query string
+    Query(String),
+}
+
 #[derive(Debug)]
 pub struct LinksBuilder {
-    endpoint: &'static str,
-    rels: BTreeMap<&'static str, PaginationParameters>,
+    endpoint: String,
+    rels: BTreeMap<&'static str, Rel>,
 }
 
 impl LinksBuilder {
-    pub fn new(endpoint: &'static str) -> Self {
+    pub fn new(endpoint: impl Into<String>) -> Self {
         LinksBuilder {
-            endpoint,
+            endpoint: endpoint.into(),
             rels: BTreeMap::new(),
         }
     }
@@ -26,11 +39,11 @@ impl LinksBuilder {
     pub fn with_first(mut self, id_before_first: i32) -> Self {
         self.rels.insert(
             "first",
-            PaginationParameters {
+            Rel::Parameters(PaginationParameters {
                 after: Some(id_before_first),
                 before: None,
                 ..Default::default()
-            },
+            }),
         );
         self
     }
@@ -38,11 +51,11 @@ impl LinksBuilder {
     pub fn with_last(mut self, id_after_last: i32) -> Self {
         self.rels.insert(
             "last",
-            PaginationParameters {
+            Rel::Parameters(PaginationParameters {
                 after: None,
                 before: Some(id_after_last),
                 ..Default::default()
-            },
+            }),
         );
         self
     }
@@ -50,11 +63,11 @@ impl LinksBuilder {
     pub fn with_next(mut self, after: i32) -> Self {
         self.rels.insert(
             "next",
-            PaginationParameters {
+            Rel::Parameters(PaginationParameters {
                 after: Some(after),
                 before: None,
                 ..Default::default()
-            },
+            }),
         );
         self
     }
@@ -62,34 +75,54 @@ impl LinksBuilder {
     pub fn with_previous(mut self, before: i32) -> Self {
         self.rels.insert(
             "prev",
-            PaginationParameters {
+            Rel::Parameters(PaginationParameters {
                 after: None,
                 before: Some(before),
                 ..Default::default()
-            },
+            }),
         );
         self
     }
 
+    /// Adds a link with the given relation pointing to the given query. Unlike for the other
+    /// relations, the query is used as-is, e.g. its `limit` is not overwritten by
+    /// [`LinksBuilder::generate`].
+    pub fn with_query(mut self, rel: &'static str, query: &impl Serialize) -> Result<Self, CoreError> {
+        let query_string = serde_urlencoded::to_string(query).map_err(|err| {
+            CoreError::internal_server_error(format!(
+                "Failed to serialize pagination query string: {:?}. Builder: {:?}, Current Rel: {}",
+                err, self, rel
+            ))
+        })?;
+
+        self.rels.insert(rel, Rel::Query(query_string));
+
+        Ok(self)
+    }
+
     pub fn generate<P: PaginationQuery>(&self, base: &P) -> Result<String, CoreError> {
         let mut buf = String::new();
         let mut is_first = true;
-        // The build functions set a default value for "limit" - copy the actual value from the given base here
-        let limit = base.parameters().limit;
+        // The build functions set default values for "limit" and "count" - copy the actual values from the given base here
+        let PaginationParameters { limit, count, .. } = base.parameters();
 
-        for (rel, param) in &self.rels {
+        for (rel, target) in &self.rels {
             if !is_first {
                 buf.push(',');
             }
             is_first = false;
 
-            let query_string =
-                serde_urlencoded::to_string(base.with_parameters(PaginationParameters { limit, ..*param })).map_err(|err| {
-                    CoreError::internal_server_error(format!(
-                        "Failed to serialize pagination query string: {:?}. Base: {:?}, Builder: {:?}, Current Rel: {}",
-                        err, base, self, rel
-                    ))
-                })?;
+            let query_string = match target {
+                Rel::Parameters(param) => {
+                    serde_urlencoded::to_string(base.with_parameters(PaginationParameters { limit, count, ..*param })).map_err(|err| {
+                        CoreError::internal_server_error(format!(
+                            "Failed to serialize pagination query string: {:?}. Base: {:?}, Builder: {:?}, Current Rel: {}",
+                            err, base, self, rel
+                        ))
+                    })?
+                },
+                Rel::Query(query_string) => query_string.clone(),
+            };
 
             buf += &format!("<{}?{}>; rel={}", self.endpoint, query_string, rel);
         }
@@ -99,7 +132,7 @@ impl LinksBuilder {
 }
 
 pub async fn pagination_response<Q: PaginationQuery, P: Paginatable<Q>>(
-    endpoint: &'static str, query: Q, connection: &mut PgConnection,
+    endpoint: &str, query: Q, connection: &mut PgConnection,
 ) -> Result<Response2<Json<Vec<P>>>, CoreError> {
     let parameters = query.parameters();
 
@@ -109,7 +142,7 @@ pub async fn pagination_response<Q: PaginationQuery, P: Paginatable<Q>>(
 
     let mut links = LinksBuilder::new(endpoint);
 
-    if let Some((min_id, max_id)) = P::first_and_last(connection).await? {
+    if let Some((min_id, max_id)) = P::first_and_last(&query, connection).await? {
         links = links.with_first(min_id - 1).with_last(max_id + 1);
     }
 
@@ -128,9 +161,8 @@ pub async fn pagination_response<Q: PaginationQuery, P: Paginatable<Q>>(
             },
         };
 
-        // TODO: Figure out the case where both `before` and `after` are set
         // If `before` is set on this request, then we _could_ support one-way pagination up to `before` by preserving the "before" value here.
-        // Currently, this scenario cannot happen, as the documentation of `Pagination::page` we treat these pages as "standalone".
+        // However, the next page is meant to continue with all objects matching the query's conditions, which `before` is not part of.
         links = links.with_next(after);
     }
 
@@ -152,7 +184,98 @@ pub async fn pagination_response<Q: PaginationQuery, P: Paginatable<Q>>(
         links = links.with_previous(before);
     };
 
-    Ok(Response2::json(objects).with_header("Links", links.generate(&query)?))
+    let response = Response2::json(objects).with_header("Links", links.generate(&query)?);
+
+    with_total_count::<Q, P>(response, &query, connection).await
+}
+
+/// Adds the `X-Total-Count` header to the given response if requested via the `count` parameter
+async fn with_total_count<Q: PaginationQuery, P: Paginatable<Q>>(
+    response: Response2<Json<Vec<P>>>, query: &Q, connection: &mut PgConnection,
+) -> Result<Response2<Json<Vec<P>>>, CoreError> {
+    if !query.parameters().count {
+        return Ok(response);
+    }
+
+    Ok(match P::count(query, connection).await? {
+        Some(count) => response.with_header("X-Total-Count", count.to_string()),
+        None => response,
+    })
+}
+
+/// Like [`pagination_response`], but for queries that can be sorted by arbitrary [sort
+/// keys](pointercrate_core::pagination::SortKey)
+///
+/// If the query is [sorted](SortParameters::is_sorted), the generated links page through the
+/// objects via cursors instead of ids. Since the position of the last object in the sort order is
+/// not known without querying it, no `last` link is generated in that case.
+pub async fn sorted_pagination_response<Q: SortedPaginationQuery, P: Sortable<Q>>(
+    endpoint: &str, query: Q, connection: &mut PgConnection,
+) -> Result<Response2<Json<Vec<P>>>, CoreError> {
+    let sorting = query.sorting().clone();
+
+    if !sorting.is_sorted() {
+        return pagination_response(endpoint, query, connection).await;
+    }
+
+    let parameters = query.parameters();
+
+    parameters.validate()?;
+    sorting.validate(&parameters)?;
+
+    let (objects, context) = P::page(&query, &mut *connection).await?;
+
+    let page = |before_cursor, after_cursor| {
+        query.with_sorting(SortParameters {
+            before_cursor,
+            after_cursor,
+            ..sorting.clone()
+        })
+    };
+
+    let mut links = LinksBuilder::new(endpoint).with_query("first", &page(None, None))?;
+
+    if context.has_next() {
+        let after = match objects.last() {
+            Some(obj) => obj.cursor(sorting.sort),
+            // Analogous to `pagination_response`, an empty page with a next page must be the page before `before_cursor`.
+            // The next page starts with the object at `before_cursor` (if it still exists), so we step back by one.
+            None => sorting
+                .before_cursor
+                .clone()
+                .ok_or_else(|| {
+                    CoreError::internal_server_error(format!(
+                        "Empty page claims next page exists, yet `before_cursor` not set on current request. Caused by {:?}",
+                        query
+                    ))
+                })?
+                .offset(-sorting.step()),
+        };
+
+        links = links.with_query("next", &page(None, Some(after)))?;
+    }
+
+    if context.has_previous() {
+        let before = match objects.first() {
+            Some(obj) => obj.cursor(sorting.sort),
+            None => sorting
+                .after_cursor
+                .clone()
+                .ok_or_else(|| {
+                    CoreError::internal_server_error(format!(
+                        "Empty page claims previous page exists, yet `after_cursor` not set on current request. Caused by {:?}",
+                        query
+                    ))
+                })?
+                .offset(sorting.step()),
+        };
+
+        links = links.with_query("prev", &page(Some(before), None))?;
+    }
+
+    let response = Response2::json(objects).with_header("Links", links.generate(&query)?);
+
+    with_total_count::<Q, P>(response, &query, connection).await
 }
 
 #[cfg(test)]
@@ -190,4 +313,26 @@ mod tests {
             "</dummies?after=0>; rel=first,</dummies?before=1971>; rel=last,</dummies?after=2>; rel=next,</dummies?before=100>; rel=prev"
         );
     }
+
+    #[test]
+    fn test_links_builder_with_query() {
+        let links_header = LinksBuilder::new("/dummies")
+            .with_first(0)
+            .with_query(
+                "next",
+                &DummyQuery(PaginationParameters {
+                    limit: 10,
+                    ..Default::default()
+                }),
+            )
+            .unwrap()
+            .generate(&DummyQuery(PaginationParameters {
+                limit: 20,
+                ..Default::default()
+            }))
+            .unwrap();
+
+        // Links given via `with_query` keep their own limit
+        assert_eq!(links_header, "</dummies?after=0&limit=20>; rel=first,</dummies?limit=10>; rel=next");
+    }
 }
@@ -0,0 +1,264 @@
+//! Generic checks that the responses generated for a [`Paginatable`] uphold the invariants
+//! documented on [`Paginatable::fetch`] and [`Paginatable::page`]
+//!
+//! Only available with the `testing` feature, as these are meant to be run by the crates
+//! implementing [`Paginatable`], for each of their implementations.
+
+use std::{future::Future, pin::Pin};
+
+use pointercrate_core::{
+    error::CoreError,
+    pagination::{Paginatable, PaginationParameters, PaginationQuery, SortParameters, Sortable, SortedPaginationQuery, ENTRIES_PER_PAGE},
+};
+use rocket::serde::json::Json;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use sqlx::PgConnection;
+
+use crate::{
+    pagination::{pagination_response, sorted_pagination_response},
+    response::Response2,
+};
+
+/// Only the query strings of the generated links are of interest, so the endpoint does not matter
+const ENDPOINT: &str = "/";
+
+type PageFuture<'c, P> = Pin<Box<dyn Future<Output = Result<Response2<Json<Vec<P>>>, CoreError>> + 'c>>;
+
+/// Pages through all objects matching the given query one by one, first forwards and then
+/// backwards, asserting that
+/// - a `prev`/`next` link is present if and only if there are objects before/after the current
+///   page,
+/// - following these links yields the same objects as requesting all of them at once,
+/// - the `first` and `last` links (if present) lead to the pages at either end, and
+/// - `X-Total-Count` (if supported) is the number of all objects, regardless of which page is
+///   requested
+///
+/// The query must match at least two, but less than [`ENTRIES_PER_PAGE`], objects.
+pub async fn assert_pagination_invariants<Q, P>(query: Q, connection: &mut PgConnection)
+where
+    Q: PaginationQuery + Clone + 'static,
+    P: Paginatable<Q> + 'static,
+{
+    walk(
+        query,
+        connection,
+        |query, connection| Box::pin(pagination_response::<Q, P>(ENDPOINT, query, connection)),
+        |query, link| query.with_parameters(parse(link)),
+    )
+    .await
+}
+
+/// Like [`assert_pagination_invariants`], but for [sorted](SortParameters::is_sorted) queries
+pub async fn assert_sorted_pagination_invariants<Q, P>(query: Q, connection: &mut PgConnection)
+where
+    Q: SortedPaginationQuery + Clone + 'static,
+    P: Sortable<Q> + 'static,
+{
+    walk(
+        query,
+        connection,
+        |query, connection| Box::pin(sorted_pagination_response::<Q, P>(ENDPOINT, query, connection)),
+        |query, link| {
+            query
+                .with_parameters(parse(link))
+                .with_sorting(parse::<SortParameters<Q::Key>>(link))
+        },
+    )
+    .await
+}
+
+struct Page {
+    objects: Vec<Value>,
+    links: String,
+    total_count: Option<String>,
+}
+
+async fn walk<Q, P, R, F>(query: Q, connection: &mut PgConnection, respond: R, follow: F)
+where
+    Q: PaginationQuery + Clone,
+    P: Serialize,
+    R: for<'c> Fn(Q, &'c mut PgConnection) -> PageFuture<'c, P>,
+    F: Fn(&Q, &str) -> Q,
+{
+    let single_objects = query.with_parameters(PaginationParameters {
+        limit: 1,
+        count: true,
+        ..Default::default()
+    });
+    let all = fetch_page(
+        &respond,
+        query.with_parameters(PaginationParameters {
+            limit: ENTRIES_PER_PAGE,
+            count: true,
+            ..Default::default()
+        }),
+        connection,
+    )
+    .await;
+    let total = all.objects.len();
+
+    assert!(total >= 2, "{:?}: too few objects to test pagination", query);
+    assert!(
+        total < ENTRIES_PER_PAGE as usize,
+        "{:?}: too many objects to test pagination",
+        query
+    );
+    assert_eq!(link(&all.links, "prev"), None, "{:?}", query);
+    assert_eq!(link(&all.links, "next"), None, "{:?}", query);
+
+    if let Some(ref count) = all.total_count {
+        assert_eq!(*count, total.to_string(), "{:?}", query);
+    }
+
+    let mut forwards = Vec::new();
+    let mut next = Some(single_objects);
+    let mut last_page = None;
+
+    while let Some(page_query) = next {
+        let page = fetch_page(&respond, page_query.clone(), connection).await;
+
+        assert_eq!(page.objects.len(), 1, "{:?}", page_query);
+        assert_eq!(link(&page.links, "prev").is_some(), !forwards.is_empty(), "{:?}", page_query);
+        assert_eq!(page.total_count, all.total_count, "{:?}", page_query);
+
+        forwards.extend(page.objects);
+
+        assert!(forwards.len() <= total, "{:?}: 'next' links do not end", query);
+
+        next = link(&page.links, "next").map(|link| follow(&query, &link));
+        last_page = Some((page_query, page.links));
+    }
+
+    assert_eq!(forwards, all.objects, "{:?}: paging forwards", query);
+
+    let (last_query, last_links) = last_page.expect("at least one page to be requested");
+
+    let mut backwards = Vec::new();
+    let mut previous = Some(last_query);
+
+    while let Some(page_query) = previous {
+        let page = fetch_page(&respond, page_query.clone(), connection).await;
+
+        assert_eq!(page.objects.len(), 1, "{:?}", page_query);
+        assert_eq!(link(&page.links, "next").is_some(), !backwards.is_empty(), "{:?}", page_query);
+
+        backwards.extend(page.objects);
+
+        assert!(backwards.len() <= total, "{:?}: 'prev' links do not end", query);
+
+        previous = link(&page.links, "prev").map(|link| follow(&query, &link));
+    }
+
+    backwards.reverse();
+
+    assert_eq!(backwards, all.objects, "{:?}: paging backwards", query);
+
+    for (rel, expected) in [("first", &forwards[..1]), ("last", &forwards[total - 1..])] {
+        if let Some(link) = link(&last_links, rel) {
+            let page = fetch_page(&respond, follow(&query, &link), connection).await;
+
+            assert_eq!(page.objects, expected, "{:?}: following '{}' link", query, rel);
+        }
+    }
+}
+
+async fn fetch_page<Q, P, R>(respond: &R, query: Q, connection: &mut PgConnection) -> Page
+where
+    Q: PaginationQuery,
+    P: Serialize,
+    R: for<'c> Fn(Q, &'c mut PgConnection) -> PageFuture<'c, P>,
+{
+    let description = format!("{:?}", query);
+    let response = respond(query, connection)
+        .await
+        .unwrap_or_else(|err| panic!("{}: {:?}", description, err));
+
+    Page {
+        objects: response
+            .content()
+            .iter()
+            .map(|object| serde_json::to_value(object).unwrap())
+            .collect(),
+        links: response.header("Links").expect("'Links' header to be set").to_string(),
+        total_count: response.header("X-Total-Count").map(str::to_string),
+    }
+}
+
+/// The query string of the link with the given relation in the given `Links` header
+fn link(links_header: &str, rel: &str) -> Option<String> {
+    links_header.split(',').find_map(|link| {
+        let (url, link_rel) = link.split_once("; rel=")?;
+        let (_, query_string) = url.trim_start_matches('<').trim_end_matches('>').split_once('?')?;
+
+        (link_rel == rel).then(|| query_string.to_string())
+    })
+}
+
+fn parse<T: DeserializeOwned>(query_string: &str) -> T {
+    serde_urlencoded::from_str(query_string).unwrap_or_else(|err| panic!("Malformed link '{}': {}", query_string, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use pointercrate_core::pagination::{__pagination_compat, PageContext, Paginatable, PaginationParameters, PaginationQuery};
+    use serde::Serialize;
+    use sqlx::{PgConnection, Pool, Postgres};
+
+    use super::assert_pagination_invariants;
+
+    /// Paginates the even numbers from 2 to 20
+    #[derive(Debug, Default, Clone, Serialize)]
+    struct EvenNumbers(PaginationParameters);
+
+    impl PaginationQuery for EvenNumbers {
+        fn parameters(&self) -> PaginationParameters {
+            self.0
+        }
+
+        fn with_parameters(&self, parameters: PaginationParameters) -> Self {
+            EvenNumbers(parameters)
+        }
+    }
+
+    #[derive(Serialize)]
+    struct Number(i32);
+
+    impl Paginatable<EvenNumbers> for Number {
+        async fn fetch(query: &EvenNumbers, connection: &mut PgConnection) -> Result<(Vec<Number>, PageContext), sqlx::Error> {
+            let sql_query = format!(
+                "SELECT n FROM generate_series(2, 20, 2) AS n WHERE (n < $1 OR $1 IS NULL) AND (n > $2 OR $2 IS NULL) ORDER BY n {} \
+                 LIMIT $3",
+                query.0.order()
+            );
+
+            let numbers: Vec<i32> = sqlx::query_scalar(&sql_query)
+                .bind(query.0.before)
+                .bind(query.0.after)
+                .bind(query.0.limit + 1)
+                .fetch_all(connection)
+                .await?;
+
+            Ok(__pagination_compat(&query.0, numbers.into_iter().map(Number).collect()))
+        }
+
+        async fn count(_query: &EvenNumbers, _connection: &mut PgConnection) -> Result<Option<i64>, sqlx::Error> {
+            Ok(Some(10))
+        }
+
+        async fn first_and_last(_query: &EvenNumbers, _connection: &mut PgConnection) -> Result<Option<(i32, i32)>, sqlx::Error> {
+            Ok(Some((2, 20)))
+        }
+
+        fn pagination_id(&self) -> i32 {
+            self.0
+        }
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_pagination_invariants(pool: Pool<Postgres>) {
+        let mut connection = pool.acquire().await.unwrap();
+
+        assert_pagination_invariants::<_, Number>(EvenNumbers::default(), &mut connection).await;
+    }
+}
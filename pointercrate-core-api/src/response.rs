@@ -1,11 +1,12 @@
 use crate::localization::LOCALE_COOKIE_NAME;
 use crate::{
     etag::Tagged,
+    maintenance::MaintenanceMode,
     preferences::{ClientPreferences, PreferenceManager},
 };
 use maud::{html, Render, DOCTYPE};
 use pointercrate_core::localization::LocaleConfiguration;
-use pointercrate_core::{etag::Taggable, localization::LANGUAGE};
+use pointercrate_core::{error::CoreError, etag::Taggable, localization::LANGUAGE};
 use pointercrate_core_pages::{
     head::{Head, HeadLike},
     PageConfiguration, PageFragment,
@@ -14,12 +15,12 @@ use rocket::tokio::runtime::Handle;
 use rocket::tokio::task::block_in_place;
 use rocket::{
     http::{ContentType, Header, Status},
-    response::Responder,
+    response::{content::RawJson, Responder},
     serde::json::Json,
     Request, Response,
 };
 use serde::Serialize;
-use std::{borrow::Cow, io::Cursor};
+use std::{borrow::Cow, io::Cursor, sync::Arc};
 
 pub struct Page(PageFragment);
 
@@ -63,6 +64,17 @@ impl<'r, 'o: 'r> Responder<'r, 'o> for Page {
 
         let fragment = self.0;
 
+        // Announce scheduled maintenance ahead of time, and explain why things are read-only while it is ongoing
+        let maintenance_message = request.rocket().state::<MaintenanceMode>().and_then(|mode| {
+            let settings = mode.settings();
+
+            if !mode.is_active() && !settings.is_upcoming() {
+                return None;
+            }
+
+            settings.message(lang_id.language.as_str()).map(ToString::to_string)
+        });
+
         let is_uk = request.headers().get_one("CF-IPCountry").map(|c| c == "GB").unwrap_or(false);
 
         let rendered_fragment = html! {
@@ -75,6 +87,11 @@ impl<'r, 'o: 'r> Responder<'r, 'o> for Page {
                 body {
                     div.content {
                         (nav_bar)
+                        @if let Some(message) = maintenance_message {
+                            nav.blue #maintenance-banner style="height:auto; font-weight: bolder; position: relative; z-index: 100; justify-content: center" {
+                                (message)
+                            }
+                        }
                         @if is_uk {
                             nav.red style="height:auto; font-weight: bolder; position: relative; z-index: 100" {
                                 marquee scrolldelay = "60" {
@@ -99,6 +116,7 @@ impl<'r, 'o: 'r> Responder<'r, 'o> for Page {
     }
 }
 
+#[derive(Clone)]
 pub struct Response2<T> {
     content: T,
     status: Status,
@@ -111,6 +129,20 @@ impl<T: Serialize> Response2<Json<T>> {
     }
 }
 
+impl<T: Serialize> Response2<Json<T>> {
+    /// Serializes the content ahead of time, so that the response can be stored in the
+    /// [`ResponseCache`](crate::cache::ResponseCache)
+    pub fn prerender(self) -> Result<Response2<RawJson<Arc<str>>>, CoreError> {
+        let content = serde_json::to_string(&self.content.0).map_err(|err| CoreError::internal_server_error(err.to_string()))?;
+
+        Ok(Response2 {
+            content: RawJson(content.into()),
+            status: self.status,
+            headers: self.headers,
+        })
+    }
+}
+
 impl<T: Taggable> Response2<Tagged<T>> {
     pub fn tagged(content: T) -> Self {
         Response2::new(Tagged(content))
@@ -135,6 +167,18 @@ impl<T> Response2<T> {
         self.status = status;
         self
     }
+
+    pub fn content(&self) -> &T {
+        &self.content
+    }
+
+    /// The value of the first header with the given name set on this response, if any
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|header| header.name() == name)
+            .map(|header| header.value())
+    }
 }
 
 impl<'r, 'o: 'r, T: Responder<'r, 'o>> Responder<'r, 'o> for Response2<T> {
@@ -1,6 +1,6 @@
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse2, parse_macro_input, parse_quote, ItemFn};
+use syn::{parse2, parse_macro_input, parse_quote, FnArg, Ident, ItemFn, Pat, PatType, Type};
 
 /// A procedural macro for automatically wrapping a request handler inside a tokio::task_local!
 /// [`LocalKey`] scope for `LANGUAGE`, with the value of the [`ClientLocale`] request guard.
@@ -37,18 +37,28 @@ pub fn localized(_: TokenStream, input: TokenStream) -> TokenStream {
 }
 
 /// Identical behaviour to `#[localized]`, but modified to support error catchers.
+///
+/// If the catcher already takes the [`Request`](rocket::Request) as an argument, the locale is
+/// extracted from that one. Otherwise, a hidden `&Request` argument is added.
 #[proc_macro_attribute]
 pub fn localized_catcher(_: TokenStream, input: TokenStream) -> TokenStream {
     let mut f = parse_macro_input!(input as ItemFn);
 
-    f.sig.inputs.push(parse_quote! { __request: &rocket::Request<'_> });
+    let request = match request_argument(&f) {
+        Some(ident) => ident,
+        None => {
+            f.sig.inputs.push(parse_quote! { __request: &rocket::Request<'_> });
+
+            parse_quote! { __request }
+        },
+    };
 
     let block = &f.block;
     let block = quote! {
         {
             use rocket::request::FromRequest;
 
-            let __locale = match pointercrate_core_api::localization::ClientLocale::from_request(__request).await {
+            let __locale = match pointercrate_core_api::localization::ClientLocale::from_request(#request).await {
                 rocket::request::Outcome::Success(locale) => locale,
                 _ => return pointercrate_core_api::error::ErrorResponder::from(pointercrate_core::error::CoreError::internal_server_error("An error occurred while trying to extract requested locale. Check your locale fallbacks!")),
             };
@@ -63,3 +73,17 @@ pub fn localized_catcher(_: TokenStream, input: TokenStream) -> TokenStream {
 
     TokenStream::from(quote!(#f))
 }
+
+/// Finds the argument of a catcher that holds the [`Request`](rocket::Request), if any
+///
+/// Catchers can take at most a [`Status`](rocket::http::Status) and a `&Request`, so any reference
+/// argument is the request.
+fn request_argument(f: &ItemFn) -> Option<Ident> {
+    f.sig.inputs.iter().find_map(|arg| match arg {
+        FnArg::Typed(PatType { pat, ty, .. }) => match (&**pat, &**ty) {
+            (Pat::Ident(pat), Type::Reference(_)) => Some(pat.ident.clone()),
+            _ => None,
+        },
+        _ => None,
+    })
+}
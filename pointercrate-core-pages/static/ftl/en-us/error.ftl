@@ -4,8 +4,11 @@ error-contact = Believe we've made a mistake in showing you this error?
 error-core-badrequest = The browser (or proxy) sent a request that this server could not understand.
 error-core-invalidheadervalue = The value for the header '{ $header }' could not be processed.
 error-core-unauthorized = The server could not verify that you are authorized to access the URL requested. You either supplied the wrong credentials (e.g. a bad password) or your browser doesn't understand how to supply the credentials required.
+error-core-twofactorrequired = This account has two-factor authentication enabled. Please supply a code from your authenticator app or one of your recovery codes.
 error-core-forbidden = You don't have the permission to access the requested resource. It is either read-protected or not readable by the server.
 error-core-missingpermissions = You do not have the pointercrate permissions to perform this request. Required is: { $required-permission }, which isn't contained in any of your permissions.
+error-core-twofactornotenabled = Your permission { $required-permission } may only be used with two-factor authentication enabled. Please enable it on your profile first.
+error-core-missingscope = The access token used to authenticate this request does not grant access to this resource. Required scope: { $required-scope }
 error-core-notfound = The requested URL was not found on the server. If you entered the URL manually please check your spelling and try again.
 error-core-methodnotallowed = The method is not allowed for the requested URL.
 error-core-conflict = A conflict happened while processing the request. The resource might have been modified while the request was being processed.
@@ -20,6 +23,9 @@ error-core-urlauthenticated = The provided URL contains authentication informati
 error-core-invalidurlformat = The given URL does not lead to a video. The URL format for the given host has to be '{ $expected-format }'.
 error-core-aftersmallerbefore = The 'after' value provided for pagination is smaller than the 'before' value. This would result in an empty response and is most likely a bug.
 error-core-mutuallyexclusive = Your request contains mutually exclusive fields. Please restrict yourself to one of them.
+error-core-maintenanceendbeforestart = The scheduled maintenance window must end after it starts.
+error-core-invalidmaintenanceroute = The route '{ $route }' is not an absolute path. Allowlisted routes must start with a '/'.
+error-core-invalidpaginationcursor = The given pagination cursor does not belong to the requested sort order. Cursors can only be reused with the 'sort' parameter they were obtained for.
 error-core-preconditionrequired = This request is required to be conditional; try using "If-Match".
 error-core-ratelimited = { $message } Try again in { $remaining-duration }.
 error-core-internalservererror = The server encountered an internal error and was unable to complete your request. Either the server is overloaded or there is an error in the application. Please notify a server administrator and have them look at the server logs!
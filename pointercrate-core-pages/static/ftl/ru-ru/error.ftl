@@ -4,8 +4,11 @@ error-contact = Думаете, что этой ошибки здесь быть
 error-core-badrequest = Браузер (либо прокси) отправил запрос, который не смог быть обработан сервером.
 error-core-invalidheadervalue = Значение заголовка '{ $header }' не прошло проверку.
 error-core-unauthorized = Сервер не смог подтвердить разрешение для доступа к запрашиваемой вами ссылке. Либо вы используете неверные учетные данные (напр. неверный пароль), либо ваш браузер не понимает, как работать с требуемыми учетными данными.
+error-core-twofactorrequired = Для этого аккаунта включена двухфакторная аутентификация. Пожалуйста, введите код из приложения-аутентификатора или один из ваших кодов восстановления.
 error-core-forbidden = У вас нет разрешения на доступ к запрашиваемому ресурсу. Либо он доступен только для чтения, либо не может быть прочитан сервером.
 error-core-missingpermissions = У вас нет нужных прав pointercrate для выполнения данного запроса. Требуется: { $required-permission }, чего нет в имеющихся у вас правах.
+error-core-twofactornotenabled = Ваше право { $required-permission } можно использовать только с включенной двухфакторной аутентификацией. Пожалуйста, сначала включите её в своем профиле.
+error-core-missingscope = Токен доступа, использованный для аутентификации этого запроса, не дает доступа к данному ресурсу. Необходимая область доступа: { $required-scope }
 error-core-notfound = Запрашиваемая ссылка не была найдена на сервере. Если вы ввели ссылку вручную, проверьте ее написание и попробуйте еще раз.
 error-core-methodnotallowed = Данный метод не разрешен для запрашиваемой ссылки.
 error-core-conflict = При обработке запроса случился конфликт данных. Ресурс мог быть изменен во время обработки запроса.
@@ -20,6 +23,9 @@ error-core-urlauthenticated = Переданная ссылка содержит
 error-core-invalidurlformat = Данная ссылка не перенаправляет на видео. Формат ссылки для данного хоста должен быть '{ $expected-format }'.
 error-core-aftersmallerbefore = Значение 'after', переданное для пагинации, меньше, чем значение 'before'. Это приведет к пустому запросу и, скорее всего, является багом.
 error-core-mutuallyexclusive = Ваш запрос содержит взаимоисключающие поля. Пожалуйста, используйте лишь одним из них.
+error-core-maintenanceendbeforestart = Запланированное техническое обслуживание должно заканчиваться после своего начала.
+error-core-invalidmaintenanceroute = Путь '{ $route }' не является абсолютным. Пути в списке разрешённых должны начинаться с '/'.
+error-core-invalidpaginationcursor = Переданный курсор пагинации не относится к запрошенному порядку сортировки. Курсоры можно использовать только с тем параметром 'sort', для которого они были получены.
 error-core-preconditionrequired = Этот запрос требует предварительного условия; попробуйте использовать "If-Match".
 error-core-ratelimited = { $message } Попробуйте еще раз через { $remaining-duration }.
 error-core-internalservererror = Сервер наткнулся на внутреннюю ошибку и не смог обработать ваш запрос. Либо сервер перегружен, либо в приложении содержится ошибка. Пожалуйста, свяжитесь с серверным администратором и попросите его просмотреть логи сервера!
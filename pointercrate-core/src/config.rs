@@ -1,3 +1,421 @@
-pub fn database_url() -> String {
-    std::env::var("DATABASE_URL").expect("DATABASE_URL is not set")
+//! Typed, validated configuration
+//!
+//! Configuration is read from a TOML file, `pointercrate.toml` in the working directory (or
+//! whichever file the `POINTERCRATE_CONFIG` environment variable points to). Each crate
+//! contributes its own table to this file, described by a type implementing [`ConfigSection`].
+//! Individual settings can be overwritten via environment variables, which take precedence over the
+//! file.
+//!
+//! Every crate exposes an `init` function in its `config` module, which should be called at startup
+//! (after [`init`] from this module, which loads the file), so that invalid configuration is
+//! reported right away instead of when a setting is first used.
+
+use serde::{de::DeserializeOwned, Deserialize, Deserializer};
+use std::{collections::HashMap, fmt::Display, io::ErrorKind, path::PathBuf, str::FromStr, sync::OnceLock};
+
+/// The configuration file, if it was loaded
+static FILE: OnceLock<toml::Table> = OnceLock::new();
+
+static CORE: Section<CoreConfig> = Section::new();
+
+#[derive(thiserror::Error, Debug)]
+pub enum ConfigError {
+    #[error("Failed to read configuration file {}: {source}", path.display())]
+    Io { path: PathBuf, source: std::io::Error },
+
+    #[error("Failed to parse configuration file {}: {source}", path.display())]
+    Parse { path: PathBuf, source: toml::de::Error },
+
+    #[error("Invalid [{section}] table in configuration file: {source}")]
+    Section { section: &'static str, source: toml::de::Error },
+
+    #[error("Invalid value '{value}' for environment variable {variable}: {message}")]
+    Env { variable: String, value: String, message: String },
+
+    #[error("Missing required setting '{setting}' in [{section}] table (or environment variable {variable})")]
+    Missing {
+        section: &'static str,
+        setting: &'static str,
+        variable: &'static str,
+    },
+
+    #[error("Invalid setting '{setting}' in [{section}] table: {message}")]
+    Invalid {
+        section: &'static str,
+        setting: &'static str,
+        message: String,
+    },
+}
+
+/// A table of the configuration file, belonging to a single crate
+///
+/// Implementors should be annotated with `#[serde(default, deny_unknown_fields)]`, so that
+/// settings can be omitted, and typos in setting names are caught.
+pub trait ConfigSection: DeserializeOwned + Default + Send + Sync + 'static {
+    /// The name of the table holding this section
+    const NAME: &'static str;
+
+    /// Overwrites settings with the values of the corresponding environment variables, if set
+    fn apply_env(&mut self) -> Result<(), ConfigError>;
+
+    /// Checks that the settings are consistent
+    fn validate(&self) -> Result<(), ConfigError> {
+        Ok(())
+    }
+}
+
+/// Lazily loaded [`ConfigSection`], meant to be stored in a `static`
+pub struct Section<S>(OnceLock<S>);
+
+impl<S> Default for Section<S> {
+    fn default() -> Self {
+        Section::new()
+    }
+}
+
+impl<S> Section<S> {
+    pub const fn new() -> Self {
+        Section(OnceLock::new())
+    }
+}
+
+impl<S: ConfigSection> Section<S> {
+    /// Loads and validates this section, unless that already happened
+    pub fn init(&self) -> Result<&S, ConfigError> {
+        if let Some(section) = self.0.get() {
+            return Ok(section);
+        }
+
+        let section = load_section(FILE.get())?;
+
+        Ok(self.0.get_or_init(|| section))
+    }
+
+    /// Gets this section, loading it if needed
+    ///
+    /// Fails if the section is invalid, which can only happen if it was not initialized at startup
+    /// via [`Section::init`] (which would have reported the error right away).
+    pub fn get(&self) -> Result<&S, ConfigError> {
+        self.init()
+    }
+}
+
+fn load_section<S: ConfigSection>(file: Option<&toml::Table>) -> Result<S, ConfigError> {
+    let mut section = match file.and_then(|file| file.get(S::NAME)) {
+        Some(table) => table
+            .clone()
+            .try_into()
+            .map_err(|source| ConfigError::Section { section: S::NAME, source })?,
+        None => S::default(),
+    };
+
+    section.apply_env()?;
+    section.validate()?;
+
+    Ok(section)
+}
+
+/// Reads and parses the given environment variable, if it is set
+pub fn env<T: FromStr>(variable: &str) -> Result<Option<T>, ConfigError>
+where
+    T::Err: Display,
+{
+    match std::env::var(variable) {
+        Ok(value) => value.parse().map(Some).map_err(|err: T::Err| ConfigError::Env {
+            variable: variable.to_string(),
+            message: err.to_string(),
+            value,
+        }),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Overwrites `setting` with the value of the given environment variable, if it is set
+pub fn override_from_env<T: FromStr>(setting: &mut T, variable: &str) -> Result<(), ConfigError>
+where
+    T::Err: Display,
+{
+    if let Some(value) = env(variable)? {
+        *setting = value;
+    }
+
+    Ok(())
+}
+
+/// Loads the configuration file and the [`CoreConfig`]
+///
+/// A missing `pointercrate.toml` is not an error (in which case only environment variables and
+/// defaults are used), but a missing file explicitly selected via `POINTERCRATE_CONFIG` is.
+pub fn init() -> Result<(), ConfigError> {
+    let (path, required) = match std::env::var("POINTERCRATE_CONFIG") {
+        Ok(path) => (PathBuf::from(path), true),
+        Err(_) => (PathBuf::from("pointercrate.toml"), false),
+    };
+
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => Some(contents),
+        Err(err) if err.kind() == ErrorKind::NotFound && !required => None,
+        Err(source) => return Err(ConfigError::Io { path, source }),
+    };
+
+    if let Some(contents) = contents {
+        let table = toml::from_str(&contents).map_err(|source| ConfigError::Parse { path, source })?;
+
+        // Sections loaded before this point would not have seen the file
+        if FILE.set(table).is_err() {
+            log::warn!("Configuration file loaded twice, ignoring second load");
+        }
+    }
+
+    CORE.init().map(|_| ())
+}
+
+/// The configuration of pointercrate's core components
+pub fn core() -> Result<&'static CoreConfig, ConfigError> {
+    CORE.get()
+}
+
+/// The `[core]` configuration table
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct CoreConfig {
+    /// Connection string for the database (environment variable `DATABASE_URL`)
+    pub database_url: String,
+
+    /// Connection string for a read-only replica of the database, which serves queries of
+    /// read-only requests if set (environment variable `DATABASE_REPLICA_URL`)
+    pub replica_url: Option<String>,
+
+    /// The maximal number of connections in the database pool, and in the replica's pool
+    /// (environment variable `DATABASE_POOL_SIZE`)
+    pub pool_size: u32,
+
+    /// Where ratelimits are tracked (environment variable `RATELIMIT_BACKEND`)
+    pub ratelimit_backend: RatelimitBackend,
+
+    /// Overwritten quotas for individual ratelimits, by ratelimit name (environment variables
+    /// `RATELIMIT_<NAME>`)
+    pub ratelimits: HashMap<String, QuotaOverride>,
+
+    /// How long responses stay in the response cache (in seconds) if they are not invalidated
+    /// earlier. `0` disables the cache (environment variable `RESPONSE_CACHE_TTL`).
+    pub response_cache_ttl: u64,
+
+    /// Bearer token required to access the `/metrics` endpoint. If unset, metrics are publicly
+    /// accessible (environment variable `METRICS_TOKEN`).
+    pub metrics_token: Option<String>,
+}
+
+impl Default for CoreConfig {
+    fn default() -> Self {
+        CoreConfig {
+            database_url: String::new(),
+            replica_url: None,
+            pool_size: 20,
+            ratelimit_backend: RatelimitBackend::default(),
+            ratelimits: HashMap::new(),
+            response_cache_ttl: 300,
+            metrics_token: None,
+        }
+    }
+}
+
+impl ConfigSection for CoreConfig {
+    const NAME: &'static str = "core";
+
+    fn apply_env(&mut self) -> Result<(), ConfigError> {
+        override_from_env(&mut self.database_url, "DATABASE_URL")?;
+        override_from_env(&mut self.pool_size, "DATABASE_POOL_SIZE")?;
+
+        if let Some(replica_url) = env("DATABASE_REPLICA_URL")? {
+            self.replica_url = Some(replica_url);
+        }
+
+        override_from_env(&mut self.ratelimit_backend, "RATELIMIT_BACKEND")?;
+        override_from_env(&mut self.response_cache_ttl, "RESPONSE_CACHE_TTL")?;
+
+        if let Some(token) = env("METRICS_TOKEN")? {
+            self.metrics_token = Some(token);
+        }
+
+        for (variable, _) in std::env::vars() {
+            if let Some(name) = variable.strip_prefix("RATELIMIT_").filter(|&name| name != "BACKEND") {
+                if let Some(quota) = env(&variable)? {
+                    self.ratelimits.insert(name.to_lowercase(), quota);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.database_url.is_empty() {
+            return Err(ConfigError::Missing {
+                section: Self::NAME,
+                setting: "database_url",
+                variable: "DATABASE_URL",
+            });
+        }
+
+        if self.pool_size == 0 {
+            return Err(ConfigError::Invalid {
+                section: Self::NAME,
+                setting: "pool_size",
+                message: "must be positive".to_string(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Which [`RatelimitStore`](crate::ratelimits::RatelimitStore) to use
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RatelimitBackend {
+    /// Ratelimits are tracked in memory, and reset on restart
+    #[default]
+    Memory,
+
+    /// Ratelimits are tracked in the database, and shared between all instances using it
+    Postgres,
+}
+
+impl FromStr for RatelimitBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "memory" => Ok(RatelimitBackend::Memory),
+            "postgres" => Ok(RatelimitBackend::Postgres),
+            _ => Err("expected 'memory' or 'postgres'".to_string()),
+        }
+    }
+}
+
+/// An overwritten ratelimit quota, of the form `<capacity>/<seconds>`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuotaOverride {
+    pub capacity: u32,
+    pub seconds: u64,
+}
+
+impl FromStr for QuotaOverride {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split_once('/')
+            .and_then(|(capacity, seconds)| Some((capacity.trim().parse().ok()?, seconds.trim().parse().ok()?)))
+            .filter(|&(capacity, seconds)| capacity > 0 && seconds > 0)
+            .map(|(capacity, seconds)| QuotaOverride { capacity, seconds })
+            .ok_or_else(|| "expected <capacity>/<seconds> with both being positive integers".to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for QuotaOverride {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Overwritten quota for the ratelimit with the given name, as `(capacity, seconds)`
+pub fn ratelimit_override(name: &str) -> Result<Option<(u32, u64)>, ConfigError> {
+    Ok(core()?
+        .ratelimits
+        .get(&name.to_lowercase())
+        .map(|quota| (quota.capacity, quota.seconds)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{load_section, ConfigError, ConfigSection, QuotaOverride, Section};
+    use serde::Deserialize;
+
+    #[derive(Deserialize, Debug, Default)]
+    #[serde(default, deny_unknown_fields)]
+    struct TestConfig {
+        size: i16,
+        quota: Option<QuotaOverride>,
+    }
+
+    impl ConfigSection for TestConfig {
+        const NAME: &'static str = "test";
+
+        fn apply_env(&mut self) -> Result<(), ConfigError> {
+            Ok(())
+        }
+
+        fn validate(&self) -> Result<(), ConfigError> {
+            match self.size {
+                0.. => Ok(()),
+                _ => Err(ConfigError::Invalid {
+                    section: Self::NAME,
+                    setting: "size",
+                    message: "must not be negative".to_string(),
+                }),
+            }
+        }
+    }
+
+    fn load(file: &str) -> Result<TestConfig, ConfigError> {
+        load_section(Some(&toml::from_str(file).unwrap()))
+    }
+
+    #[test]
+    fn test_load_section() {
+        let config = load("[test]\nsize = 5\nquota = \"3/1800\"").unwrap();
+
+        assert_eq!(config.size, 5);
+        assert_eq!(
+            config.quota,
+            Some(QuotaOverride {
+                capacity: 3,
+                seconds: 1800
+            })
+        );
+
+        // Missing tables fall back to defaults
+        assert_eq!(load("[other]\nsize = 5").unwrap().size, 0);
+    }
+
+    #[test]
+    fn test_invalid_section() {
+        assert!(matches!(load("[test]\nsiez = 5"), Err(ConfigError::Section { .. })));
+        assert!(matches!(load("[test]\nsize = \"five\""), Err(ConfigError::Section { .. })));
+        assert!(matches!(load("[test]\nquota = \"3/0\""), Err(ConfigError::Section { .. })));
+        assert!(matches!(load("[test]\nsize = -1"), Err(ConfigError::Invalid { .. })));
+    }
+
+    #[test]
+    fn test_get_invalid_section() {
+        #[derive(Deserialize, Debug, Default)]
+        struct RequiredConfig {
+            required: Option<String>,
+        }
+
+        impl ConfigSection for RequiredConfig {
+            const NAME: &'static str = "required";
+
+            fn apply_env(&mut self) -> Result<(), ConfigError> {
+                Ok(())
+            }
+
+            fn validate(&self) -> Result<(), ConfigError> {
+                match self.required {
+                    Some(_) => Ok(()),
+                    None => Err(ConfigError::Missing {
+                        section: Self::NAME,
+                        setting: "required",
+                        variable: "REQUIRED",
+                    }),
+                }
+            }
+        }
+
+        static REQUIRED: Section<RequiredConfig> = Section::new();
+
+        // Not initialized at startup, and invalid when loaded lazily
+        assert!(matches!(REQUIRED.get(), Err(ConfigError::Missing { .. })));
+    }
 }
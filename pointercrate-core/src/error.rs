@@ -1,4 +1,4 @@
-use crate::{localization::tr, permission::Permission, trp};
+use crate::{config::ConfigError, localization::tr, permission::Permission, trp};
 use log::error;
 use serde::Serialize;
 use std::{error::Error, fmt::Display, time::Duration};
@@ -10,6 +10,11 @@ pub trait PointercrateError: Error + Serialize + From<CoreError> {
     fn status_code(&self) -> u16 {
         self.error_code() / 100
     }
+
+    /// Additional headers to set on responses reporting this error
+    fn headers(&self) -> Vec<(&'static str, String)> {
+        Vec::new()
+    }
 }
 
 #[derive(Serialize, Debug, Eq, PartialEq, Clone)]
@@ -33,6 +38,12 @@ pub enum CoreError {
     /// Error code 40100
     Unauthorized,
 
+    /// `401 UNAUTHORIZED` error returned if the supplied password was correct, but the account has
+    /// two-factor authentication enabled and no (or no valid) second factor was supplied
+    ///
+    /// Error Code `40101`
+    TwoFactorRequired,
+
     /// `403 FORBIDDEN`
     ///
     /// Error Code `40300`
@@ -47,6 +58,24 @@ pub enum CoreError {
         required: Permission,
     },
 
+    /// `403 FORBIDDEN` error returned if the client holds a permission that may only be used with
+    /// two-factor authentication enabled, but has not enabled it for their account
+    ///
+    /// Error Code `40309`
+    TwoFactorNotEnabled {
+        /// The held permission that requires two-factor authentication
+        required: Permission,
+    },
+
+    /// `403 FORBIDDEN` error returned if the client authenticated using an access token whose
+    /// scopes do not cover the request
+    ///
+    /// Error Code `40310`
+    MissingScope {
+        /// A scope that would allow the request, in the form `<resource>:<access>`
+        required: String,
+    },
+
     /// `404 NOT FOUND`
     ///
     /// Error Code `40400`
@@ -128,6 +157,27 @@ pub enum CoreError {
     /// Error Code `42229`
     MutuallyExclusive,
 
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a pagination cursor was obtained for a
+    /// different sort key than the one requested
+    ///
+    /// Error Code `42244`
+    InvalidPaginationCursor,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a scheduled maintenance window would end
+    /// before it starts
+    ///
+    /// Error Code `42242`
+    MaintenanceEndBeforeStart,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if an entry of the maintenance allowlist is not
+    /// an absolute path
+    ///
+    /// Error Code `42243`
+    InvalidMaintenanceRoute {
+        /// The offending allowlist entry
+        route: String,
+    },
+
     /// `428 PRECONDITION REQUIRED`
     ///
     /// Error Code `42800`
@@ -141,6 +191,9 @@ pub enum CoreError {
         message: String,
 
         remaining: Duration,
+
+        /// The number of requests allowed by the exhausted ratelimit per period
+        limit: u32,
     },
 
     /// `500 INTERNAL SERVER ERROR`
@@ -192,8 +245,11 @@ impl PointercrateError for CoreError {
             CoreError::BadRequest => 40000,
             CoreError::InvalidHeaderValue { .. } => 40002,
             CoreError::Unauthorized => 40100,
+            CoreError::TwoFactorRequired => 40101,
             CoreError::Forbidden => 40300,
             CoreError::MissingPermissions { .. } => 40301,
+            CoreError::TwoFactorNotEnabled { .. } => 40309,
+            CoreError::MissingScope { .. } => 40310,
             CoreError::NotFound => 40400,
             CoreError::MethodNotAllowed => 40500,
             CoreError::Conflict => 40900,
@@ -208,6 +264,9 @@ impl PointercrateError for CoreError {
             CoreError::InvalidUrlFormat { .. } => 42225,
             CoreError::AfterSmallerBefore => 42227,
             CoreError::MutuallyExclusive => 42229,
+            CoreError::MaintenanceEndBeforeStart => 42242,
+            CoreError::InvalidMaintenanceRoute { .. } => 42243,
+            CoreError::InvalidPaginationCursor => 42244,
             CoreError::PreconditionRequired => 42800,
             CoreError::Ratelimited { .. } => 42900,
             CoreError::InternalServerError => 50000,
@@ -217,6 +276,23 @@ impl PointercrateError for CoreError {
             CoreError::ReadOnlyMaintenance => 50301,
         }
     }
+
+    fn headers(&self) -> Vec<(&'static str, String)> {
+        match self {
+            CoreError::Ratelimited { remaining, limit, .. } => {
+                // Round up, so that clients waiting for the given number of seconds never get ratelimited again
+                let seconds = remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0);
+
+                vec![
+                    ("Retry-After", seconds.to_string()),
+                    ("RateLimit-Limit", limit.to_string()),
+                    ("RateLimit-Remaining", "0".to_string()),
+                    ("RateLimit-Reset", seconds.to_string()),
+                ]
+            },
+            _ => Vec::new(),
+        }
+    }
 }
 
 impl Display for CoreError {
@@ -228,9 +304,13 @@ impl Display for CoreError {
                 CoreError::BadRequest => tr("error-core-badrequest"),
                 CoreError::InvalidHeaderValue { header } => trp!("error-core-badrequest", "header" = header),
                 CoreError::Unauthorized => tr("error-core-unauthorized"),
+                CoreError::TwoFactorRequired => tr("error-core-twofactorrequired"),
                 CoreError::Forbidden => tr("error-core-badrequest"),
                 CoreError::MissingPermissions { required } =>
                     trp!("error-core-missingpermissions", "required-permission" = tr(required.text_id())),
+                CoreError::TwoFactorNotEnabled { required } =>
+                    trp!("error-core-twofactornotenabled", "required-permission" = tr(required.text_id())),
+                CoreError::MissingScope { required } => trp!("error-core-missingscope", "required-scope" = required),
                 CoreError::NotFound => tr("error-core-notfound"),
                 CoreError::MethodNotAllowed => tr("error-core-methodnotallowed"),
                 CoreError::Conflict => tr("error-core-conflict"),
@@ -245,8 +325,11 @@ impl Display for CoreError {
                 CoreError::InvalidUrlFormat { expected } => trp!("error-core-invalidurlformat", "expected-format" = expected),
                 CoreError::AfterSmallerBefore => tr("error-core-aftersmallerbefore"),
                 CoreError::MutuallyExclusive => tr("error-core-mutuallyexclusive"),
+                CoreError::MaintenanceEndBeforeStart => tr("error-core-maintenanceendbeforestart"),
+                CoreError::InvalidMaintenanceRoute { route } => trp!("error-core-invalidmaintenanceroute", "route" = route),
+                CoreError::InvalidPaginationCursor => tr("error-core-invalidpaginationcursor"),
                 CoreError::PreconditionRequired => tr("error-core-preconditionrequired"),
-                CoreError::Ratelimited { message, remaining } => trp!(
+                CoreError::Ratelimited { message, remaining, .. } => trp!(
                     "error-core-ratelimited",
                     "message" = message,
                     "remaining-duration" = format!("{:.2?}", remaining)
@@ -288,3 +371,9 @@ impl From<sqlx::Error> for CoreError {
         }
     }
 }
+
+impl From<ConfigError> for CoreError {
+    fn from(error: ConfigError) -> Self {
+        CoreError::internal_server_error(format!("Invalid configuration: {}", error))
+    }
+}
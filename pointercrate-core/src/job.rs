@@ -0,0 +1,444 @@
+//! Durable, postgres-backed background jobs
+//!
+//! Work that should not happen as part of handling a request (for instance because it talks to
+//! external services) is described by a type implementing [`Job`], which is [`enqueue`]d into the
+//! `jobs` table, ideally as part of the same transaction that caused the work to become necessary.
+//! A [`JobQueue`] worker then picks up due jobs, and hands them to the handler registered for
+//! their [`Job::KIND`]. Failing jobs are retried with exponential backoff, until
+//! [`Job::max_attempts`] is reached.
+
+use crate::{
+    error::{CoreError, Result},
+    first_and_last, metrics,
+    pagination::{__pagination_compat, PageContext, Paginatable, PaginationParameters, PaginationQuery},
+    util::non_nullable,
+};
+use chrono::NaiveDateTime;
+use log::{debug, error, info, warn};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sqlx::{PgConnection, Pool, Postgres, Row};
+use std::{collections::HashMap, fmt::Display, future::Future, pin::Pin, sync::Arc, time::Duration};
+use tokio::sync::Semaphore;
+
+/// How often a job is attempted before giving up on it, unless overwritten by [`Job::max_attempts`]
+pub const DEFAULT_MAX_ATTEMPTS: i16 = 5;
+
+/// How long to wait before the first retry of a failed job. Doubles with each further attempt.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(30);
+
+/// How long a worker may take to run a job before the job is assumed to be abandoned and picked up
+/// again
+const LOCK_DURATION: Duration = Duration::from_secs(600);
+
+/// How long the worker waits before checking for new jobs if none were due
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The maximal number of jobs a single worker runs concurrently
+const MAX_CONCURRENT_JOBS: usize = 8;
+
+/// A unit of background work
+///
+/// Jobs are stored as JSON in the database, meaning they should only contain the data needed to
+/// look up everything else once they run (e.g. IDs instead of full objects).
+pub trait Job: Serialize + DeserializeOwned + Send + 'static {
+    /// Uniquely identifies this type of job. Used to find the handler for a job once it is due.
+    const KIND: &'static str;
+
+    /// A key for deduplicating jobs of this kind
+    ///
+    /// At most one unfinished (e.g. pending or running) job of this kind can exist per key.
+    /// Enqueueing a job while another one with the same key is still unfinished does nothing.
+    fn dedup_key(&self) -> Option<String> {
+        None
+    }
+
+    /// How often this job should be attempted before it is marked as failed
+    fn max_attempts(&self) -> i16 {
+        DEFAULT_MAX_ATTEMPTS
+    }
+}
+
+/// Enqueues the given job to run as soon as possible
+///
+/// Returns the id of the new job, or `None` if an unfinished job with the same
+/// [`Job::dedup_key`] already exists. If the given connection is inside a transaction, the job
+/// will only be visible to workers once the transaction is committed.
+pub async fn enqueue<J: Job>(job: &J, connection: &mut PgConnection) -> Result<Option<i32>> {
+    enqueue_at(job, chrono::Utc::now().naive_utc(), connection).await
+}
+
+/// Enqueues the given job to run no earlier than `scheduled_at`
+///
+/// See [`enqueue`].
+pub async fn enqueue_at<J: Job>(job: &J, scheduled_at: NaiveDateTime, connection: &mut PgConnection) -> Result<Option<i32>> {
+    let payload = serde_json::to_string(job)
+        .map_err(|err| CoreError::internal_server_error(format!("Failed to serialize {} job: {:?}", J::KIND, err)))?;
+
+    let id = sqlx::query_scalar!(
+        "INSERT INTO jobs (kind, payload, dedup_key, max_attempts, scheduled_at) VALUES ($1, $2, $3, $4, $5) ON CONFLICT (kind, \
+         dedup_key) WHERE dedup_key IS NOT NULL AND status IN ('PENDING', 'RUNNING') DO NOTHING RETURNING id",
+        J::KIND,
+        payload,
+        job.dedup_key(),
+        job.max_attempts(),
+        scheduled_at
+    )
+    .fetch_optional(connection)
+    .await?;
+
+    match id {
+        Some(id) => debug!("Enqueued {} job {} scheduled at {}", J::KIND, id, scheduled_at),
+        None => debug!(
+            "Not enqueueing {} job with key {:?}, as an unfinished one exists",
+            J::KIND,
+            job.dedup_key()
+        ),
+    }
+
+    Ok(id)
+}
+
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    /// The job is waiting to be picked up by a worker
+    Pending,
+
+    /// The job is currently being run by a worker
+    Running,
+
+    Succeeded,
+
+    /// All attempts at running this job failed
+    Failed,
+}
+
+impl JobStatus {
+    pub fn to_sql(self) -> String {
+        match self {
+            JobStatus::Pending => "PENDING",
+            JobStatus::Running => "RUNNING",
+            JobStatus::Succeeded => "SUCCEEDED",
+            JobStatus::Failed => "FAILED",
+        }
+        .to_owned()
+    }
+
+    fn from_sql(sql: &str) -> Self {
+        match sql {
+            "PENDING" => JobStatus::Pending,
+            "RUNNING" => JobStatus::Running,
+            "SUCCEEDED" => JobStatus::Succeeded,
+            "FAILED" => JobStatus::Failed,
+            _ => panic!("invalid job status: {}", sql),
+        }
+    }
+}
+
+/// A job as stored in the database
+#[derive(Debug, Serialize)]
+pub struct QueuedJob {
+    pub id: i32,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    pub dedup_key: Option<String>,
+    pub status: JobStatus,
+
+    /// How often this job has been attempted so far
+    pub attempts: i16,
+    pub max_attempts: i16,
+    pub scheduled_at: NaiveDateTime,
+
+    /// The error reported by the most recent failed attempt, if any
+    pub last_error: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub finished_at: Option<NaiveDateTime>,
+}
+
+#[derive(Deserialize, Debug, Clone, Serialize)]
+pub struct JobPagination {
+    #[serde(flatten)]
+    pub params: PaginationParameters,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub kind: Option<String>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub status: Option<JobStatus>,
+}
+
+impl PaginationQuery for JobPagination {
+    fn parameters(&self) -> PaginationParameters {
+        self.params
+    }
+
+    fn with_parameters(&self, parameters: PaginationParameters) -> Self {
+        Self {
+            params: parameters,
+            ..self.clone()
+        }
+    }
+}
+
+impl Paginatable<JobPagination> for QueuedJob {
+    first_and_last!(JobPagination, "jobs");
+
+    async fn fetch(
+        query: &JobPagination, connection: &mut PgConnection,
+    ) -> std::result::Result<(Vec<QueuedJob>, PageContext), sqlx::Error> {
+        let order = query.params.order();
+
+        let sql_query = format!(
+            "SELECT id, kind, payload, dedup_key, status::TEXT, attempts, max_attempts, scheduled_at, last_error, created_at, \
+             finished_at FROM jobs WHERE (id < $1 OR $1 IS NULL) AND (id > $2 OR $2 IS NULL) AND (kind = $3 OR $3 IS NULL) AND \
+             (status::TEXT = $4 OR $4 IS NULL) ORDER BY id {} LIMIT $5",
+            order
+        );
+
+        let rows = sqlx::query(&sql_query)
+            .bind(query.params.before)
+            .bind(query.params.after)
+            .bind(&query.kind)
+            .bind(query.status.map(JobStatus::to_sql))
+            .bind(query.params.limit + 1)
+            .fetch_all(connection)
+            .await?;
+
+        let jobs = rows
+            .into_iter()
+            .map(|row| {
+                let payload: String = row.get("payload");
+
+                QueuedJob {
+                    id: row.get("id"),
+                    kind: row.get("kind"),
+                    payload: serde_json::from_str(&payload).unwrap_or(serde_json::Value::String(payload)),
+                    dedup_key: row.get("dedup_key"),
+                    status: JobStatus::from_sql(row.get("status")),
+                    attempts: row.get("attempts"),
+                    max_attempts: row.get("max_attempts"),
+                    scheduled_at: row.get("scheduled_at"),
+                    last_error: row.get("last_error"),
+                    created_at: row.get("created_at"),
+                    finished_at: row.get("finished_at"),
+                }
+            })
+            .collect();
+
+        Ok(__pagination_compat(&query.params, jobs))
+    }
+
+    fn pagination_id(&self) -> i32 {
+        self.id
+    }
+}
+
+type HandlerFuture = Pin<Box<dyn Future<Output = std::result::Result<(), String>> + Send>>;
+type Handler = Box<dyn Fn(&str, i16) -> HandlerFuture + Send + Sync>;
+
+/// A worker running the jobs stored in the database
+///
+/// Only picks up jobs whose kind has a handler registered via [`JobQueue::register`]. Multiple
+/// workers (e.g. in different processes) can safely operate on the same database.
+pub struct JobQueue {
+    pool: Pool<Postgres>,
+    handlers: HashMap<&'static str, Handler>,
+}
+
+/// A job claimed by a worker
+struct ClaimedJob {
+    id: i32,
+    kind: String,
+    payload: String,
+    attempts: i16,
+    max_attempts: i16,
+}
+
+impl JobQueue {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        JobQueue {
+            pool,
+            handlers: HashMap::new(),
+        }
+    }
+
+    /// Registers the function that runs jobs of type `J`
+    ///
+    /// The handler is given the deserialized job and the number of the current attempt, starting
+    /// at 1. Returning an error causes the job to be retried later, unless it ran out of attempts.
+    pub fn register<J, F, Fut, E>(mut self, handler: F) -> Self
+    where
+        J: Job,
+        F: Fn(J, i16) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = std::result::Result<(), E>> + Send + 'static,
+        E: Display,
+    {
+        let handler = Arc::new(handler);
+
+        self.handlers.insert(
+            J::KIND,
+            Box::new(move |payload, attempt| {
+                let job = serde_json::from_str::<J>(payload);
+                let handler = Arc::clone(&handler);
+
+                Box::pin(async move {
+                    let job = job.map_err(|err| format!("Malformed payload: {}", err))?;
+
+                    handler(job, attempt).await.map_err(|err| err.to_string())
+                })
+            }),
+        );
+        self
+    }
+
+    /// Runs this worker until the process exits
+    pub async fn run(self) {
+        let queue = Arc::new(self);
+        let permits = Arc::new(Semaphore::new(MAX_CONCURRENT_JOBS));
+
+        info!("Starting job worker for {:?}", queue.handlers.keys());
+
+        loop {
+            let Ok(permit) = Arc::clone(&permits).acquire_owned().await else {
+                return;
+            };
+
+            match queue.claim().await {
+                Ok(Some(job)) => {
+                    let queue = Arc::clone(&queue);
+
+                    tokio::spawn(async move {
+                        queue.execute(job).await;
+
+                        drop(permit)
+                    });
+                },
+                Ok(None) => tokio::time::sleep(POLL_INTERVAL).await,
+                Err(err) => {
+                    error!("Failed to claim job: {:?}", err);
+
+                    tokio::time::sleep(POLL_INTERVAL).await
+                },
+            }
+        }
+    }
+
+    /// Runs the next due job, if any, waiting for it to finish
+    ///
+    /// Returns whether a job was run.
+    pub async fn run_next(&self) -> Result<bool> {
+        match self.claim().await? {
+            Some(job) => {
+                self.execute(job).await;
+
+                Ok(true)
+            },
+            None => Ok(false),
+        }
+    }
+
+    /// Marks the next due job as running, and returns it
+    ///
+    /// Jobs left in the running state after their lock expired (e.g. because the worker running
+    /// them crashed) are considered due again.
+    async fn claim(&self) -> Result<Option<ClaimedJob>> {
+        let kinds = self.handlers.keys().map(|kind| kind.to_string()).collect::<Vec<_>>();
+
+        let row = sqlx::query!(
+            r#"UPDATE jobs SET status = 'RUNNING', attempts = attempts + 1, locked_until = (NOW() AT TIME ZONE 'utc') + make_interval(secs => $2)
+               WHERE id = (
+                   SELECT id FROM jobs
+                   WHERE kind = ANY($1)
+                     AND scheduled_at <= (NOW() AT TIME ZONE 'utc')
+                     AND (status = 'PENDING' OR (status = 'RUNNING' AND locked_until < (NOW() AT TIME ZONE 'utc')))
+                   ORDER BY scheduled_at
+                   LIMIT 1
+                   FOR UPDATE SKIP LOCKED
+               )
+               RETURNING id, kind, payload, attempts, max_attempts"#,
+            &kinds,
+            LOCK_DURATION.as_secs_f64()
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|row| ClaimedJob {
+            id: row.id,
+            kind: row.kind,
+            payload: row.payload,
+            attempts: row.attempts,
+            max_attempts: row.max_attempts,
+        }))
+    }
+
+    async fn execute(&self, job: ClaimedJob) {
+        let result = if job.attempts > job.max_attempts {
+            // Only happens if the lock on the final attempt expired
+            Err("Job timed out".to_string())
+        } else {
+            match self.handlers.get(job.kind.as_str()) {
+                Some(handler) => match tokio::time::timeout(LOCK_DURATION, handler(&job.payload, job.attempts)).await {
+                    Ok(result) => result,
+                    Err(_) => Err("Job timed out".to_string()),
+                },
+                None => Err(format!("No handler registered for job kind {}", job.kind)),
+            }
+        };
+
+        let finished = match result {
+            Ok(()) => {
+                debug!("Successfully ran {} job {}", job.kind, job.id);
+                metrics::record_job(&job.kind, "succeeded");
+
+                sqlx::query!(
+                    "UPDATE jobs SET status = 'SUCCEEDED', locked_until = NULL, finished_at = (NOW() AT TIME ZONE 'utc') WHERE id = $1",
+                    job.id
+                )
+                .execute(&self.pool)
+                .await
+            },
+            Err(err) if job.attempts >= job.max_attempts => {
+                error!(
+                    "Giving up on {} job {} after {} attempts, last error: {}",
+                    job.kind, job.id, job.attempts, err
+                );
+                metrics::record_job(&job.kind, "failed");
+
+                sqlx::query!(
+                    "UPDATE jobs SET status = 'FAILED', locked_until = NULL, last_error = $2, finished_at = (NOW() AT TIME ZONE 'utc') WHERE \
+                     id = $1",
+                    job.id,
+                    err
+                )
+                .execute(&self.pool)
+                .await
+            },
+            Err(err) => {
+                let backoff = INITIAL_BACKOFF * 2u32.pow(job.attempts.clamp(1, 10) as u32 - 1);
+
+                warn!(
+                    "{} job {} failed (attempt {}/{}), retrying in {:?}: {}",
+                    job.kind, job.id, job.attempts, job.max_attempts, backoff, err
+                );
+                metrics::record_job(&job.kind, "retried");
+
+                sqlx::query!(
+                    "UPDATE jobs SET status = 'PENDING', locked_until = NULL, last_error = $2, scheduled_at = (NOW() AT TIME ZONE 'utc') + \
+                     make_interval(secs => $3) WHERE id = $1",
+                    job.id,
+                    err,
+                    backoff.as_secs_f64()
+                )
+                .execute(&self.pool)
+                .await
+            },
+        };
+
+        if let Err(err) = finished {
+            error!(
+                "INTERNAL SERVER ERROR: Failed to update state of {} job {}: {:?}",
+                job.kind, job.id, err
+            );
+        }
+    }
+}
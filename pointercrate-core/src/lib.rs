@@ -2,7 +2,10 @@ pub mod audit;
 pub mod config;
 pub mod error;
 pub mod etag;
+pub mod job;
 pub mod localization;
+pub mod maintenance;
+pub mod metrics;
 pub mod pagination;
 pub mod permission;
 pub mod pool;
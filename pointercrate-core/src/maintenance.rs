@@ -0,0 +1,248 @@
+//! Runtime configurable maintenance mode
+//!
+//! While maintenance mode is active, all mutating (e.g. non-GET) requests are rejected with a `503
+//! SERVICE UNAVAILABLE` response, except for requests by administrators to routes on the
+//! [`MaintenanceSettings::allowlist`]. Maintenance can either be switched on manually, or scheduled
+//! for a specific time window. The settings are stored in the database, so that they can be changed
+//! without restarting the server (see `pointercrate_core_api::maintenance` for how they are
+//! enforced).
+
+use crate::{
+    error::{CoreError, Result},
+    localization::LocaleConfiguration,
+    util::{non_nullable, nullable},
+};
+use chrono::{NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct MaintenanceSettings {
+    /// Whether maintenance mode has been switched on manually
+    pub enabled: bool,
+
+    /// The (UTC) time at which maintenance mode automatically starts, if scheduled
+    pub starts_at: Option<NaiveDateTime>,
+
+    /// The (UTC) time at which maintenance mode automatically ends, if scheduled. Also ends
+    /// maintenance mode that was [`enabled`](MaintenanceSettings::enabled) manually.
+    pub ends_at: Option<NaiveDateTime>,
+
+    /// The banner message to display on every page while maintenance is scheduled or active, indexed
+    /// by language code
+    pub messages: HashMap<String, String>,
+
+    /// Routes that administrators can still make mutating requests to while maintenance mode is
+    /// active
+    ///
+    /// Entries ending in `*` match all routes starting with the given prefix.
+    pub allowlist: Vec<String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct PatchMaintenanceSettings {
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub enabled: Option<bool>,
+
+    #[serde(default, deserialize_with = "nullable")]
+    #[allow(clippy::option_option)]
+    pub starts_at: Option<Option<NaiveDateTime>>,
+
+    #[serde(default, deserialize_with = "nullable")]
+    #[allow(clippy::option_option)]
+    pub ends_at: Option<Option<NaiveDateTime>>,
+
+    /// Replaces all banner messages. Empty messages are dropped.
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub messages: Option<HashMap<String, String>>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub allowlist: Option<Vec<String>>,
+}
+
+impl MaintenanceSettings {
+    /// Whether maintenance mode is currently in effect
+    pub fn is_active(&self) -> bool {
+        self.is_active_at(Utc::now().naive_utc())
+    }
+
+    /// Whether maintenance mode is scheduled to start at some point in the future
+    pub fn is_upcoming(&self) -> bool {
+        self.is_upcoming_at(Utc::now().naive_utc())
+    }
+
+    /// Whether maintenance mode is in effect at the given (UTC) time
+    pub fn is_active_at(&self, now: NaiveDateTime) -> bool {
+        let started = self.enabled || self.starts_at.is_some_and(|starts_at| starts_at <= now);
+        let ended = self.ends_at.is_some_and(|ends_at| ends_at <= now);
+
+        started && !ended
+    }
+
+    /// Whether maintenance mode is scheduled to start after the given (UTC) time
+    pub fn is_upcoming_at(&self, now: NaiveDateTime) -> bool {
+        !self.is_active_at(now)
+            && self.starts_at.is_some_and(|starts_at| starts_at > now)
+            && self.ends_at.is_none_or(|ends_at| ends_at > now)
+    }
+
+    /// Whether administrators may still make mutating requests to the given path while maintenance
+    /// mode is active
+    pub fn allows(&self, path: &str) -> bool {
+        // Routes are matched irrespective of whether they are written with a trailing slash
+        let path = format!("{}/", path.trim_end_matches('/'));
+
+        self.allowlist.iter().any(|route| match route.strip_suffix('*') {
+            Some(prefix) => path.starts_with(prefix),
+            None => path == format!("{}/", route.trim_end_matches('/')),
+        })
+    }
+
+    /// The banner message for the given language, falling back to the message for the default
+    /// language if there is none
+    pub fn message(&self, language: &str) -> Option<&str> {
+        self.messages
+            .get(language)
+            .or_else(|| self.messages.get(LocaleConfiguration::get().fallback.as_str()))
+            .map(String::as_str)
+    }
+
+    pub async fn load(connection: &mut PgConnection) -> Result<MaintenanceSettings> {
+        let row = sqlx::query!("SELECT enabled, starts_at, ends_at, allowlist FROM maintenance")
+            .fetch_optional(&mut *connection)
+            .await?;
+
+        let Some(row) = row else {
+            return Ok(MaintenanceSettings::default());
+        };
+
+        let messages = sqlx::query!("SELECT language, message FROM maintenance_messages")
+            .fetch_all(&mut *connection)
+            .await?
+            .into_iter()
+            .map(|row| (row.language, row.message))
+            .collect();
+
+        Ok(MaintenanceSettings {
+            enabled: row.enabled,
+            starts_at: row.starts_at,
+            ends_at: row.ends_at,
+            messages,
+            allowlist: row.allowlist,
+        })
+    }
+
+    pub async fn apply_patch(mut self, patch: PatchMaintenanceSettings, connection: &mut PgConnection) -> Result<MaintenanceSettings> {
+        if let Some(enabled) = patch.enabled {
+            self.enabled = enabled;
+        }
+
+        if let Some(starts_at) = patch.starts_at {
+            self.starts_at = starts_at;
+        }
+
+        if let Some(ends_at) = patch.ends_at {
+            self.ends_at = ends_at;
+        }
+
+        if let (Some(starts_at), Some(ends_at)) = (self.starts_at, self.ends_at) {
+            if ends_at <= starts_at {
+                return Err(CoreError::MaintenanceEndBeforeStart);
+            }
+        }
+
+        if let Some(allowlist) = patch.allowlist {
+            if let Some(route) = allowlist.iter().find(|route| !route.starts_with('/')) {
+                return Err(CoreError::InvalidMaintenanceRoute { route: route.clone() });
+            }
+
+            self.allowlist = allowlist;
+        }
+
+        sqlx::query!(
+            "INSERT INTO maintenance (enabled, starts_at, ends_at, allowlist) VALUES ($1, $2, $3, $4) ON CONFLICT (id) DO UPDATE SET \
+             enabled = EXCLUDED.enabled, starts_at = EXCLUDED.starts_at, ends_at = EXCLUDED.ends_at, allowlist = EXCLUDED.allowlist",
+            self.enabled,
+            self.starts_at,
+            self.ends_at,
+            &self.allowlist
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        if let Some(messages) = patch.messages {
+            self.messages = messages.into_iter().filter(|(_, message)| !message.trim().is_empty()).collect();
+
+            sqlx::query!("DELETE FROM maintenance_messages").execute(&mut *connection).await?;
+
+            for (language, message) in &self.messages {
+                sqlx::query!(
+                    "INSERT INTO maintenance_messages (language, message) VALUES ($1, $2)",
+                    language,
+                    message
+                )
+                .execute(&mut *connection)
+                .await?;
+            }
+        }
+
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MaintenanceSettings;
+    use chrono::{NaiveDate, NaiveDateTime};
+
+    fn at(hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 10, 27).unwrap().and_hms_opt(hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_manual_maintenance() {
+        let mut settings = MaintenanceSettings::default();
+
+        assert!(!settings.is_active_at(at(12)));
+
+        settings.enabled = true;
+        assert!(settings.is_active_at(at(12)));
+
+        settings.ends_at = Some(at(13));
+        assert!(settings.is_active_at(at(12)));
+        assert!(!settings.is_active_at(at(13)));
+    }
+
+    #[test]
+    fn test_scheduled_maintenance() {
+        let settings = MaintenanceSettings {
+            starts_at: Some(at(10)),
+            ends_at: Some(at(12)),
+            ..Default::default()
+        };
+
+        assert!(!settings.is_active_at(at(9)));
+        assert!(settings.is_upcoming_at(at(9)));
+        assert!(settings.is_active_at(at(10)));
+        assert!(!settings.is_upcoming_at(at(10)));
+        assert!(settings.is_active_at(at(11)));
+        assert!(!settings.is_active_at(at(12)));
+        assert!(!settings.is_upcoming_at(at(12)));
+    }
+
+    #[test]
+    fn test_allowlist() {
+        let settings = MaintenanceSettings {
+            allowlist: vec!["/login/".to_string(), "/api/v1/auth/*".to_string()],
+            ..Default::default()
+        };
+
+        assert!(settings.allows("/login/"));
+        assert!(settings.allows("/login"));
+        assert!(!settings.allows("/login/other/"));
+        assert!(settings.allows("/api/v1/auth/"));
+        assert!(settings.allows("/api/v1/auth/me/"));
+        assert!(!settings.allows("/api/v1/users/"));
+    }
+}
@@ -0,0 +1,164 @@
+//! Prometheus metrics
+//!
+//! All metrics live in a single process-wide registry, and are recorded via the free functions in
+//! this module (so that crates without access to rocket state, such as the Geometry Dash
+//! integration, can record metrics too). [`render`] encodes the current values in the prometheus
+//! text exposition format, see `pointercrate_core_api::metrics` for the endpoint serving it.
+
+use log::error;
+use prometheus::{Encoder, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGaugeVec, Opts, Registry, TextEncoder};
+use sqlx::{Pool, Postgres};
+use std::{sync::LazyLock, time::Duration};
+
+/// Prefix of the names of all metrics
+const NAMESPACE: &str = "pointercrate";
+
+static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+struct Metrics {
+    registry: Registry,
+    request_duration: HistogramVec,
+    errors: IntCounterVec,
+    ratelimit_hits: IntCounterVec,
+    pool_connections: IntGaugeVec,
+    pool_max_connections: IntGaugeVec,
+    replica_fallbacks: IntCounter,
+    gd_requests: IntCounterVec,
+    cache_lookups: IntCounterVec,
+    jobs: IntCounterVec,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new_custom(Some(NAMESPACE.to_string()), None).expect("valid metrics namespace");
+
+        // The metric definitions below are static, so registering them can only fail due to a bug
+        macro_rules! register {
+            ($metric: expr) => {{
+                let metric = $metric.expect("valid metric definition");
+                registry.register(Box::new(metric.clone())).expect("unique metric name");
+                metric
+            }};
+        }
+
+        Metrics {
+            request_duration: register!(HistogramVec::new(
+                HistogramOpts::new("http_request_duration_seconds", "Time taken to handle HTTP requests, by route"),
+                &["method", "route", "status"]
+            )),
+            errors: register!(IntCounterVec::new(
+                Opts::new("http_errors_total", "Number of error responses, by pointercrate error code"),
+                &["code"]
+            )),
+            ratelimit_hits: register!(IntCounterVec::new(
+                Opts::new("ratelimit_hits_total", "Number of requests rejected due to an exhausted ratelimit"),
+                &["ratelimit"]
+            )),
+            pool_connections: register!(IntGaugeVec::new(
+                Opts::new(
+                    "db_pool_connections",
+                    "Number of connections currently in the database pools, by pool and state"
+                ),
+                &["pool", "state"]
+            )),
+            pool_max_connections: register!(IntGaugeVec::new(
+                Opts::new("db_pool_max_connections", "Maximal number of connections the database pools open"),
+                &["pool"]
+            )),
+            replica_fallbacks: register!(IntCounter::new(
+                "db_replica_fallbacks_total",
+                "Number of times the read replica was found unhealthy, and reads were routed to the primary"
+            )),
+            gd_requests: register!(IntCounterVec::new(
+                Opts::new(
+                    "gd_requests_total",
+                    "Number of requests made to the Geometry Dash servers, by outcome"
+                ),
+                &["endpoint", "outcome"]
+            )),
+            cache_lookups: register!(IntCounterVec::new(
+                Opts::new("response_cache_lookups_total", "Number of response cache lookups, by outcome"),
+                &["outcome"]
+            )),
+            jobs: register!(IntCounterVec::new(
+                Opts::new("jobs_total", "Number of background job attempts, by outcome"),
+                &["kind", "outcome"]
+            )),
+            registry,
+        }
+    }
+}
+
+/// Records how long handling a request to the given route took
+///
+/// `route` should be the route's URI template (e.g. `/api/v1/players/<player_id>/`), not the
+/// actual path requested, to keep the number of time series bounded.
+pub fn observe_request(method: &str, route: &str, status: u16, duration: Duration) {
+    METRICS
+        .request_duration
+        .with_label_values(&[method, route, &status.to_string()])
+        .observe(duration.as_secs_f64());
+}
+
+/// Records that a response reporting the error with the given code was sent
+pub fn record_error(code: u16) {
+    METRICS.errors.with_label_values(&[&code.to_string()]).inc();
+}
+
+/// Records that a request was rejected by the ratelimit with the given name
+pub fn record_ratelimit_hit(ratelimit: &str) {
+    METRICS.ratelimit_hits.with_label_values(&[ratelimit]).inc();
+}
+
+/// Records the outcome of a request to the given endpoint of the Geometry Dash servers
+///
+/// The outcome is one of `success`, `request_error` (the servers could not be reached) or
+/// `parse_error` (the response could not be understood).
+pub fn record_gd_request(endpoint: &str, outcome: &str) {
+    METRICS.gd_requests.with_label_values(&[endpoint, outcome]).inc();
+}
+
+/// Records the outcome of a lookup in the response cache, either `hit` or `miss`
+pub fn record_cache_lookup(outcome: &str) {
+    METRICS.cache_lookups.with_label_values(&[outcome]).inc();
+}
+
+/// Records the outcome of an attempt at running a background job of the given kind
+///
+/// The outcome is one of `succeeded`, `retried` or `failed`.
+pub fn record_job(kind: &str, outcome: &str) {
+    METRICS.jobs.with_label_values(&[kind, outcome]).inc();
+}
+
+/// Updates the database pool gauges with the current state of the given pool
+///
+/// `name` is either `primary` or `replica`.
+pub fn record_pool(name: &str, pool: &Pool<Postgres>) {
+    let idle = pool.num_idle() as i64;
+
+    METRICS.pool_connections.with_label_values(&[name, "idle"]).set(idle);
+    METRICS
+        .pool_connections
+        .with_label_values(&[name, "in_use"])
+        .set(pool.size() as i64 - idle);
+    METRICS
+        .pool_max_connections
+        .with_label_values(&[name])
+        .set(pool.options().get_max_connections() as i64);
+}
+
+/// Records that reads were routed to the primary database because the read replica was unhealthy
+pub fn record_replica_fallback() {
+    METRICS.replica_fallbacks.inc();
+}
+
+/// Encodes the current values of all metrics in the prometheus text format
+pub fn render() -> String {
+    let mut buffer = Vec::new();
+
+    if let Err(err) = TextEncoder::new().encode(&METRICS.registry.gather(), &mut buffer) {
+        error!("Failed to encode metrics: {:?}", err);
+    }
+
+    String::from_utf8(buffer).unwrap_or_default()
+}
@@ -1,8 +1,12 @@
-use std::fmt::{Debug, Display};
+use std::{
+    fmt::{Debug, Display},
+    str::FromStr,
+};
 
 use crate::{error::CoreError, util::non_nullable};
-use serde::{de::Error, Deserialize, Serialize};
-use sqlx::PgConnection;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::{de::DeserializeOwned, de::Error, Deserialize, Serialize, Serializer};
+use sqlx::{postgres::PgArguments, query::Query, PgConnection, Postgres};
 
 /// The maximal number of entries that can be requested per page via the `limit` parameter.
 pub const ENTRIES_PER_PAGE: i32 = 100;
@@ -26,6 +30,11 @@ pub struct PaginationParameters {
         skip_serializing_if = "is_default_entries_per_page"
     )]
     pub limit: i32,
+
+    /// Whether the total number of objects matching the query should be returned in the
+    /// `X-Total-Count` header, see [`Paginatable::count`]
+    #[serde(default, deserialize_with = "from_str", skip_serializing_if = "is_default")]
+    pub count: bool,
 }
 
 impl Default for PaginationParameters {
@@ -34,6 +43,7 @@ impl Default for PaginationParameters {
             before: None,
             after: None,
             limit: DEFAULT_ENTRIES_PER_PAGE,
+            count: false,
         }
     }
 }
@@ -99,6 +109,15 @@ pub enum PageContext {
 }
 
 impl PageContext {
+    pub fn new(has_previous: bool, has_next: bool) -> Self {
+        match (has_previous, has_next) {
+            (false, false) => PageContext::Standalone,
+            (true, false) => PageContext::HasPrevious,
+            (false, true) => PageContext::HasNext,
+            (true, true) => PageContext::HasPreviousAndNext,
+        }
+    }
+
     pub fn has_next(&self) -> bool {
         matches!(self, PageContext::HasNext | PageContext::HasPreviousAndNext)
     }
@@ -108,17 +127,61 @@ impl PageContext {
     }
 }
 
-pub trait PaginationQuery: Serialize + Debug {
+pub trait PaginationQuery: Serialize + Debug + Sized {
     fn parameters(&self) -> PaginationParameters;
     fn with_parameters(&self, parameters: PaginationParameters) -> Self;
+
+    /// A query for the objects that match this query, but lie at or before its lower bound (e.g.
+    /// `after`). If it has no lower bound, no such objects exist and `None` is returned.
+    ///
+    /// A previous page exists if and only if this query matches any objects.
+    fn preceding(&self) -> Option<Self> {
+        preceding_by_id(self)
+    }
+
+    /// A query for the objects that match this query, but lie at or after its upper bound (e.g.
+    /// `before`). If it has no upper bound, no such objects exist and `None` is returned.
+    ///
+    /// A next page exists if and only if this query matches any objects.
+    fn following(&self) -> Option<Self> {
+        following_by_id(self)
+    }
+}
+
+fn preceding_by_id<Q: PaginationQuery>(query: &Q) -> Option<Q> {
+    let parameters = query.parameters();
+
+    parameters.after.map(|after| {
+        query.with_parameters(PaginationParameters {
+            before: Some(after + 1),
+            after: None,
+            limit: 1,
+            count: false,
+        })
+    })
+}
+
+fn following_by_id<Q: PaginationQuery>(query: &Q) -> Option<Q> {
+    let parameters = query.parameters();
+
+    parameters.before.map(|before| {
+        query.with_parameters(PaginationParameters {
+            before: None,
+            after: Some(before - 1),
+            limit: 1,
+            count: false,
+        })
+    })
 }
 
 #[allow(async_fn_in_trait)]
 pub trait Paginatable<Q: PaginationQuery>: Serialize + Sized {
-    /// Returns a page of objects matching the query described by tthe given [`PaginationQuery`].
+    /// Retrieves a page of objects matching the query described by the given [`PaginationQuery`].
     ///
     /// The returned list of objects must have the following properties:
-    /// - They are sorted in ascending order according to the value of [`pagination_id`].
+    /// - They are sorted in ascending order according to the value of [`pagination_id`] (or the
+    ///   requested sort order, for [sorted](SortParameters::is_sorted) queries, in which case the
+    ///   conditions below apply to the cursors instead of the ids).
     /// - Their ids are consecutive, meaning if the object at index `i` in the list has ID `a`, and
     ///   the object at index `i + 1` has id `b`, then there exists no object also matching all conditions
     ///   of this `Pagination` in the _database_ with an ID `c` such that `a < c < b`.
@@ -129,18 +192,347 @@ pub trait Paginatable<Q: PaginationQuery>: Serialize + Sized {
     ///   list must have the greatest ID out of all objects matching the given query smaller than
     ///   `before`.
     ///
-    /// The returned [`PageContext`] should describe whether more pages surrounding this page exist which
-    /// match all conditions of this [`Pagination`] object, with the exception of the `before` and `after` fields!
-    /// HOWEVER, if both `before` and `after` are set, then it should be [`PageContext::Standalone`].
+    /// The returned [`PageContext`] only needs to describe whether more objects exist in the
+    /// direction in which objects were retrieved from the database (e.g. whether there are more
+    /// objects between the page and `before` if only `before` is set). Objects beyond the bounds of
+    /// the query are detected by [`Paginatable::page`].
     ///
     /// The number of items in the returned `Vec` must not exceed [`PaginationParameters::limit`].
-    async fn page(query: &Q, connection: &mut PgConnection) -> Result<(Vec<Self>, PageContext), sqlx::Error>;
+    async fn fetch(query: &Q, connection: &mut PgConnection) -> Result<(Vec<Self>, PageContext), sqlx::Error>;
+
+    /// Returns a page of objects matching the query described by the given [`PaginationQuery`]
+    /// (see [`Paginatable::fetch`]), together with a [`PageContext`] describing whether more
+    /// objects matching all conditions of the query, except for its bounds, exist before and after
+    /// that page.
+    async fn page(query: &Q, connection: &mut PgConnection) -> Result<(Vec<Self>, PageContext), sqlx::Error> {
+        let (objects, context) = Self::fetch(query, &mut *connection).await?;
+
+        let has_previous = match query.preceding() {
+            Some(preceding) if !context.has_previous() => !Self::fetch(&preceding, &mut *connection).await?.0.is_empty(),
+            _ => context.has_previous(),
+        };
+
+        let has_next = match query.following() {
+            Some(following) if !context.has_next() => !Self::fetch(&following, &mut *connection).await?.0.is_empty(),
+            _ => context.has_next(),
+        };
+
+        Ok((objects, PageContext::new(has_previous, has_next)))
+    }
 
-    async fn first_and_last(connection: &mut PgConnection) -> Result<Option<(i32, i32)>, sqlx::Error>;
+    /// Counts the objects matching all conditions of the given query, except for its bounds and
+    /// limit
+    ///
+    /// Returns `None` if counting is not supported for these objects, in which case no
+    /// `X-Total-Count` header is sent even if requested.
+    async fn count(_query: &Q, _connection: &mut PgConnection) -> Result<Option<i64>, sqlx::Error> {
+        Ok(None)
+    }
+
+    /// Retrieves the smallest and largest [`pagination_id`](Paginatable::pagination_id) of the
+    /// objects the given query pages through, which bound the `first` and `last` links
+    ///
+    /// Only needs to take the query into account if the pagination ids are not unique across all
+    /// objects, e.g. if they are indices into a ranking of a single list.
+    async fn first_and_last(query: &Q, connection: &mut PgConnection) -> Result<Option<(i32, i32)>, sqlx::Error>;
 
     fn pagination_id(&self) -> i32;
 }
 
+/// A column by which the objects returned by a [`SortedPaginationQuery`] can be sorted
+///
+/// Ties are always broken by the objects' [`Paginatable::pagination_id`], so that every object
+/// has a unique position in the sort order. The [`Default`] key must sort by the pagination id
+/// itself.
+pub trait SortKey: Copy + Default + PartialEq + Debug + Serialize + DeserializeOwned {
+    /// The name of this key in query strings and [`Cursor`]s
+    fn name(&self) -> &'static str;
+
+    /// The SQL expression whose value objects are sorted by
+    fn column(&self) -> &'static str;
+
+    /// The SQL type of [`SortKey::column`], used to cast cursor values before comparing them
+    fn sql_type(&self) -> &'static str;
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    fn reversed(self) -> SortOrder {
+        match self {
+            SortOrder::Asc => SortOrder::Desc,
+            SortOrder::Desc => SortOrder::Asc,
+        }
+    }
+
+    fn sql(self) -> &'static str {
+        match self {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        }
+    }
+}
+
+/// The value of a [`SortKey`] for some object
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(untagged)]
+pub enum SortValue {
+    Integer(i64),
+    Float(f64),
+    Text(String),
+}
+
+/// The position of an object in the sort order of some [`SortKey`], consisting of the object's
+/// value for that key and its pagination id
+///
+/// Cursors are handed to clients as opaque tokens, and thus can only be obtained from the `Links`
+/// header of a previous response.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Cursor {
+    key: String,
+    value: SortValue,
+    id: i32,
+}
+
+impl Cursor {
+    pub fn new<K: SortKey>(key: K, value: SortValue, id: i32) -> Self {
+        Cursor {
+            key: key.name().to_string(),
+            value,
+            id,
+        }
+    }
+
+    /// Moves this cursor by the given amount along the pagination ids. As pagination ids are unique
+    /// integers, this is the same as moving it by that many (potential) objects.
+    pub fn offset(self, by: i32) -> Self {
+        Cursor { id: self.id + by, ..self }
+    }
+}
+
+impl Display for Cursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let json = serde_json::to_string(&(&self.key, &self.value, self.id)).map_err(|_| std::fmt::Error)?;
+
+        write!(f, "{}", URL_SAFE_NO_PAD.encode(json))
+    }
+}
+
+impl FromStr for Cursor {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let json = URL_SAFE_NO_PAD.decode(s).map_err(|_| "malformed pagination cursor")?;
+        let (key, value, id) = serde_json::from_slice(&json).map_err(|_| "malformed pagination cursor")?;
+
+        Ok(Cursor { key, value, id })
+    }
+}
+
+impl Serialize for Cursor {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// The parameters describing how a [`SortedPaginationQuery`] is sorted, and which part of the
+/// sorted objects it requests
+///
+/// These supersede the id-based `before` and `after` of [`PaginationParameters`], which cannot be
+/// combined with them. If all of these are left at their defaults, the query is paginated
+/// exactly like a plain [`PaginationQuery`].
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+#[serde(bound = "K: SortKey")]
+pub struct SortParameters<K: SortKey> {
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub sort: K,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub order: SortOrder,
+
+    /// Only objects sorted before this cursor are requested
+    #[serde(default, deserialize_with = "from_str_non_nullable", skip_serializing_if = "Option::is_none")]
+    pub before_cursor: Option<Cursor>,
+
+    /// Only objects sorted after this cursor are requested
+    #[serde(default, deserialize_with = "from_str_non_nullable", skip_serializing_if = "Option::is_none")]
+    pub after_cursor: Option<Cursor>,
+}
+
+impl<K: SortKey> SortParameters<K> {
+    /// Whether any of these parameters was set, meaning pagination should be done via [`Cursor`]s
+    pub fn is_sorted(&self) -> bool {
+        self.sort != K::default() || self.order != SortOrder::Asc || self.before_cursor.is_some() || self.after_cursor.is_some()
+    }
+
+    pub fn validate(&self, parameters: &PaginationParameters) -> Result<(), CoreError> {
+        if self.is_sorted() && (parameters.before.is_some() || parameters.after.is_some()) {
+            return Err(CoreError::MutuallyExclusive);
+        }
+
+        for cursor in self.before_cursor.iter().chain(&self.after_cursor) {
+            if cursor.key != self.sort.name() {
+                return Err(CoreError::InvalidPaginationCursor);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The amount by which a [`Cursor`] needs to be [offset](Cursor::offset) to move it one
+    /// (potential) object forward in the requested order
+    pub fn step(&self) -> i32 {
+        match self.order {
+            SortOrder::Asc => 1,
+            SortOrder::Desc => -1,
+        }
+    }
+
+    /// The order in which objects should be retrieved from the database. This is the reverse of
+    /// the requested order if only `before_cursor` is set, as we then need the objects closest to
+    /// that cursor.
+    fn retrieval_order(&self) -> SortOrder {
+        if self.before_cursor.is_some() && self.after_cursor.is_none() {
+            self.order.reversed()
+        } else {
+            self.order
+        }
+    }
+
+    /// Generates the contents of the `ORDER BY` clause for paginating a table whose pagination id
+    /// is stored in `id_column`
+    ///
+    /// If these parameters are not [sorted](SortParameters::is_sorted), this is simply
+    /// `id_column` in [`PaginationParameters::order`].
+    pub fn order_by(&self, parameters: &PaginationParameters, id_column: &str) -> String {
+        if !self.is_sorted() {
+            return format!("{} {}", id_column, parameters.order());
+        }
+
+        let order = self.retrieval_order().sql();
+
+        if self.sort.column() == id_column {
+            format!("{} {}", id_column, order)
+        } else {
+            format!("{} {}, {} {}", self.sort.column(), order, id_column, order)
+        }
+    }
+
+    /// Generates SQL conditions (each prefixed with `AND`) restricting the query to objects between
+    /// the cursors, referring to the cursors' values as bind parameters starting at
+    /// `$first_parameter`. These must then be bound via [`SortParameters::bind_cursors`].
+    pub fn cursor_conditions(&self, id_column: &str, first_parameter: usize) -> String {
+        let (after, before) = match self.order {
+            SortOrder::Asc => (">", "<"),
+            SortOrder::Desc => ("<", ">"),
+        };
+
+        let mut conditions = String::new();
+        let mut parameter = first_parameter;
+
+        for (cursor, operator) in [(&self.after_cursor, after), (&self.before_cursor, before)] {
+            if cursor.is_some() {
+                conditions += &format!(
+                    " AND ({}, {}) {} (CAST(${} AS {}), ${})",
+                    self.sort.column(),
+                    id_column,
+                    operator,
+                    parameter,
+                    self.sort.sql_type(),
+                    parameter + 1
+                );
+                parameter += 2;
+            }
+        }
+
+        conditions
+    }
+
+    pub fn bind_cursors<'q>(&self, mut query: Query<'q, Postgres, PgArguments>) -> Query<'q, Postgres, PgArguments> {
+        for cursor in [&self.after_cursor, &self.before_cursor].into_iter().flatten() {
+            query = match cursor.value.clone() {
+                SortValue::Integer(value) => query.bind(value),
+                SortValue::Float(value) => query.bind(value),
+                SortValue::Text(value) => query.bind(value),
+            }
+            .bind(cursor.id);
+        }
+
+        query
+    }
+}
+
+/// A [`PaginationQuery`] that can additionally be sorted by columns other than the pagination id,
+/// see [`SortParameters`]
+///
+/// Implementors should implement [`PaginationQuery::preceding`] and
+/// [`PaginationQuery::following`] via [`SortedPaginationQuery::sorted_preceding`] and
+/// [`SortedPaginationQuery::sorted_following`].
+pub trait SortedPaginationQuery: PaginationQuery {
+    type Key: SortKey;
+
+    fn sorting(&self) -> &SortParameters<Self::Key>;
+    fn with_sorting(&self, sorting: SortParameters<Self::Key>) -> Self;
+
+    /// Like [`PaginationQuery::preceding`], but for sorted queries bounded by cursors
+    fn sorted_preceding(&self) -> Option<Self> {
+        let sorting = self.sorting();
+
+        if !sorting.is_sorted() {
+            return preceding_by_id(self);
+        }
+
+        let after = sorting.after_cursor.clone()?;
+
+        Some(self.with_neighbour_sorting(SortParameters {
+            before_cursor: Some(after.offset(sorting.step())),
+            after_cursor: None,
+            ..sorting.clone()
+        }))
+    }
+
+    /// Like [`PaginationQuery::following`], but for sorted queries bounded by cursors
+    fn sorted_following(&self) -> Option<Self> {
+        let sorting = self.sorting();
+
+        if !sorting.is_sorted() {
+            return following_by_id(self);
+        }
+
+        let before = sorting.before_cursor.clone()?;
+
+        Some(self.with_neighbour_sorting(SortParameters {
+            before_cursor: None,
+            after_cursor: Some(before.offset(-sorting.step())),
+            ..sorting.clone()
+        }))
+    }
+
+    #[doc(hidden)]
+    fn with_neighbour_sorting(&self, sorting: SortParameters<Self::Key>) -> Self {
+        self.with_sorting(sorting).with_parameters(PaginationParameters {
+            limit: 1,
+            ..Default::default()
+        })
+    }
+}
+
+pub trait Sortable<Q: SortedPaginationQuery>: Paginatable<Q> {
+    /// The value of the given key for this object
+    fn sort_value(&self, key: Q::Key) -> SortValue;
+
+    /// The position of this object in the sort order of the given key
+    fn cursor(&self, key: Q::Key) -> Cursor {
+        Cursor::new(key, self.sort_value(key), self.pagination_id())
+    }
+}
+
 /// Historically, pointercrate has been determining whether a new page exists by simply incrementing the "limit" parameter
 /// by one, and seeing if we can get one extra object from the database. This object was then popped from the results,
 /// and its presence indicated that "another page in the same direction" existed - e.g. if `after` was specified, it
@@ -148,58 +540,70 @@ pub trait Paginatable<Q: PaginationQuery>: Serialize + Sized {
 /// be generated. While this logic is correct, it should never have leaked outside of the `page` implementation and into
 /// the actual pagination API.
 ///
-/// Additionally, pointercrate assumes that a previous page exists whenever "after" is set, and that a next page exists
-/// whenever "previous" is set (but that we have a standalong page if _both_ are set). This is not sound (we should really be
-/// trying to find an "extra" object at the other end of the list), but fixing this would require a bigger refractor than I am
-/// willing to do at the time of writing this.
+/// Additionally, pointercrate used to assume that a previous page exists whenever "after" is set, and that a next page exists
+/// whenever "before" is set. This was not sound, and is now checked by [`Paginatable::page`] querying for objects beyond these
+/// bounds instead.
 ///
 /// Lastly, pointercrate used to return the object list in reverse if `before` but not `after` was set, and left it up
 /// to the caller to reverse it. That, too, is an implementation detail that should never become API.
 ///
-/// This compat function tries to fix these up as best as it can - it reverses the given list of objects if needed, and translates
-/// the "extra" object into a `PageContext`. It doesn't solve the second point though.
+/// This compat function fixes these up - it reverses the given list of objects if needed, and translates the "extra" object
+/// into a `PageContext` (which, as required by [`Paginatable::fetch`], only describes the direction the objects were
+/// retrieved in).
+#[doc(hidden)]
+pub fn __pagination_compat<T>(params: &PaginationParameters, objects: Vec<T>) -> (Vec<T>, PageContext) {
+    page_context(params.limit, params.before.is_some(), params.after.is_some(), objects)
+}
+
+/// Like [`__pagination_compat`], but for pages of a [`SortedPaginationQuery`], which are
+/// delimited by cursors instead of ids if [sorted](SortParameters::is_sorted).
 #[doc(hidden)]
-pub fn __pagination_compat<T>(params: &PaginationParameters, mut objects: Vec<T>) -> (Vec<T>, PageContext) {
-    let has_followup_page = objects.len() > params.limit as usize;
+pub fn __sorted_pagination_compat<K: SortKey, T>(
+    params: &PaginationParameters, sorting: &SortParameters<K>, objects: Vec<T>,
+) -> (Vec<T>, PageContext) {
+    if !sorting.is_sorted() {
+        return __pagination_compat(params, objects);
+    }
+
+    page_context(
+        params.limit,
+        sorting.before_cursor.is_some(),
+        sorting.after_cursor.is_some(),
+        objects,
+    )
+}
+
+fn page_context<T>(limit: i32, before: bool, after: bool, mut objects: Vec<T>) -> (Vec<T>, PageContext) {
+    let has_followup_page = objects.len() > limit as usize;
 
     if has_followup_page {
         objects.pop();
     }
 
-    let ctx = match (params.before, params.after) {
-        (Some(_), None) => {
-            objects.reverse();
+    // Only if `before` but not `after` is set are objects retrieved in descending order, meaning the "extra" object indicates
+    // a previous page
+    let ctx = if before && !after {
+        objects.reverse();
 
-            if has_followup_page {
-                PageContext::HasPreviousAndNext
-            } else {
-                PageContext::HasNext
-            }
-        },
-        (None, Some(_)) => {
-            if has_followup_page {
-                PageContext::HasPreviousAndNext
-            } else {
-                PageContext::HasPrevious
-            }
-        },
-        (Some(_), Some(_)) => PageContext::Standalone,
-        (None, None) => {
-            if has_followup_page {
-                PageContext::HasNext
-            } else {
-                PageContext::Standalone
-            }
-        },
+        PageContext::new(has_followup_page, false)
+    } else {
+        PageContext::new(false, has_followup_page)
     };
 
     (objects, ctx)
 }
 
+/// Wraps a query retrieving pages of objects into one counting all the objects it matches, for
+/// implementing [`Paginatable::count`]. The limit of the wrapped query must be bound to `NULL`.
+pub fn count_query(sql: &str) -> String {
+    // The newline ensures a trailing line comment in `sql` does not swallow the closing parenthesis
+    format!("SELECT COUNT(*) FROM ({}\n) AS matching", sql)
+}
+
 #[macro_export]
 macro_rules! first_and_last {
-    ($table_name: expr, $id_column: expr) => {
-        async fn first_and_last(connection: &mut PgConnection) -> std::result::Result<Option<(i32, i32)>, sqlx::Error> {
+    ($query: ty, $table_name: expr, $id_column: expr) => {
+        async fn first_and_last(_query: &$query, connection: &mut PgConnection) -> std::result::Result<Option<(i32, i32)>, sqlx::Error> {
             let row = sqlx::query!(
                 "SELECT CAST(MIN(" + $id_column + ") AS INTEGER), CAST(MAX(" + $id_column + ") AS INTEGER) FROM " + $table_name
             )
@@ -209,8 +613,8 @@ macro_rules! first_and_last {
             Ok(row.min.zip(row.max))
         }
     };
-    ($table_name: expr) => {
-        first_and_last!($table_name, "id");
+    ($query: ty, $table_name: expr) => {
+        first_and_last!($query, $table_name, "id");
     };
 }
 
@@ -224,6 +628,10 @@ const fn is_default_entries_per_page(limit: &i32) -> bool {
     *limit == DEFAULT_ENTRIES_PER_PAGE
 }
 
+fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
 // Helper function needed because serde's flatten attribute does not work with non-self describing data formats (such as url-encoding) - it thinks everything is a string.
 // See also https://github.com/nox/serde_urlencoded/issues/33
 fn from_str<'de, D, S>(deserializer: D) -> Result<S, D::Error>
@@ -246,3 +654,26 @@ where
         .map(|s| S::from_str(s).map_err(|err| D::Error::custom(err.to_string())))
         .transpose()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Cursor, SortValue};
+
+    #[test]
+    fn test_cursor_roundtrip() {
+        for value in [
+            SortValue::Integer(-3),
+            SortValue::Float(10.0 / 11.0),
+            SortValue::Text("stardust1971".to_string()),
+        ] {
+            let cursor = Cursor {
+                key: "key".to_string(),
+                value,
+                id: 1971,
+            };
+
+            // Values are compared for equality in SQL, so even floats must not lose precision
+            assert_eq!(cursor.to_string().parse::<Cursor>(), Ok(cursor));
+        }
+    }
+}
@@ -87,11 +87,20 @@ impl From<Permission> for u16 {
 ///
 /// Then, user `Z` will be able to perform the same operations as user `X`
 /// (w.r.t. assigning permissions and accessing users).
+///
+/// ## Two-factor authentication
+///
+/// Permissions can additionally be marked as requiring two-factor authentication
+/// via [`PermissionsManager::requires_two_factor`]. Users holding such a
+/// permission (either directly or via implication) cannot perform any
+/// permission-gated requests until they enabled a second factor for their
+/// account.
 #[derive(Clone)]
 pub struct PermissionsManager {
     permissions: HashSet<Permission>,
     implication_map: HashMap<Permission, HashSet<Permission>>,
     assignable_map: HashMap<Permission, HashSet<Permission>>,
+    two_factor_required: HashSet<Permission>,
 }
 
 impl PermissionsManager {
@@ -106,6 +115,7 @@ impl PermissionsManager {
             permissions: permission_set,
             implication_map: HashMap::new(),
             assignable_map: HashMap::new(),
+            two_factor_required: HashSet::new(),
         }
     }
 
@@ -124,8 +134,16 @@ impl PermissionsManager {
         }
 
         self.permissions.extend(other.permissions);
-        self.implication_map.extend(other.implication_map);
-        self.assignable_map.extend(other.assignable_map);
+
+        // Both managers might define relations for the same permission, so merge the sets instead of
+        // overwriting them
+        for (permission, implied) in other.implication_map {
+            self.implication_map.entry(permission).or_default().extend(implied);
+        }
+        for (permission, assignable) in other.assignable_map {
+            self.assignable_map.entry(permission).or_default().extend(assignable);
+        }
+        self.two_factor_required.extend(other.two_factor_required);
     }
 
     // we should probably verify that added permissions are all part of what was in
@@ -140,6 +158,19 @@ impl PermissionsManager {
         self
     }
 
+    pub fn requires_two_factor(mut self, permission: Permission) -> Self {
+        self.two_factor_required.insert(permission);
+        self
+    }
+
+    /// Returns some permission held by a user with the given permission bits that may only be
+    /// used with two-factor authentication enabled, if any
+    pub fn two_factor_required_by_bits(&self, permission_bits: u16) -> Option<Permission> {
+        self.implied_by_bits(permission_bits)
+            .into_iter()
+            .find(|perm| self.two_factor_required.contains(perm))
+    }
+
     pub fn implied_by(&self, permission: Permission) -> HashSet<Permission> {
         let mut implied = HashSet::new();
         implied.insert(permission);
@@ -259,6 +290,24 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_merge_keeps_relations() {
+        let mut manager = permission_manager();
+        manager.merge_with(PermissionsManager::new(vec![PERM6]).implies(PERM1, PERM6).assigns(PERM4, PERM6));
+
+        assert_eq!(manager.implied_by(PERM1), set![PERM1, PERM2, PERM3, PERM6]);
+        assert_eq!(manager.assignable_by(PERM4), set![PERM2, PERM5, PERM6]);
+    }
+
+    #[test]
+    fn test_two_factor_requirement_is_implied() {
+        let manager = permission_manager().requires_two_factor(PERM2);
+
+        assert_eq!(manager.two_factor_required_by_bits(0x1), Some(PERM2));
+        assert_eq!(manager.two_factor_required_by_bits(0x2), Some(PERM2));
+        assert_eq!(manager.two_factor_required_by_bits(0x4 | 0x8), None);
+    }
+
     #[test]
     fn test_assignment() {
         assert_eq!(permission_manager().assignable_by(PERM4), set![PERM2, PERM5, PERM6]);
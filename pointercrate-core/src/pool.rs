@@ -1,9 +1,62 @@
-use crate::{config, error::Result};
-use log::trace;
+use crate::{config, error::Result, metrics};
+use log::{error, info, trace};
 use sqlx::{pool::PoolConnection, postgres::PgPoolOptions, PgConnection, Pool, Postgres, Transaction};
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// How long to wait for a connection from the read replica before considering it unhealthy
+const REPLICA_ACQUIRE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How long to route all reads to the primary after the read replica was found to be unhealthy
+const REPLICA_RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The user ID actions not performed by any actual user (such as background jobs or administrative
+/// commands) are attributed to in audit logs
+///
+/// No account with this ID exists.
+pub const SYSTEM_USER_ID: i32 = 0;
 
 pub struct PointercratePool {
     connection_pool: Pool<Postgres>,
+    replica: Option<Replica>,
+}
+
+/// A read-only replica of the primary database
+struct Replica {
+    pool: Pool<Postgres>,
+
+    /// If the replica was found to be unhealthy, the time at which it should be tried again
+    unhealthy_until: Mutex<Option<Instant>>,
+}
+
+impl Replica {
+    fn new(pool: Pool<Postgres>) -> Self {
+        Replica {
+            pool,
+            unhealthy_until: Mutex::new(None),
+        }
+    }
+
+    fn is_healthy(&self) -> bool {
+        let mut unhealthy_until = self.unhealthy_until.lock().unwrap();
+
+        match *unhealthy_until {
+            Some(until) if until > Instant::now() => false,
+            Some(_) => {
+                info!("Retrying read replica");
+
+                *unhealthy_until = None;
+                true
+            },
+            None => true,
+        }
+    }
+
+    fn mark_unhealthy(&self) {
+        *self.unhealthy_until.lock().unwrap() = Some(Instant::now() + REPLICA_RETRY_INTERVAL);
+    }
 }
 
 impl PointercratePool {
@@ -11,21 +64,49 @@ impl PointercratePool {
         self.connection_pool.clone()
     }
 
+    /// Connects to the configured database, bringing its schema up to date
     pub async fn init() -> Self {
-        let pool = PointercratePool {
-            connection_pool: PgPoolOptions::default()
-                .max_connections(20)
-                .connect(&config::database_url())
-                .await
-                .expect("Failed to connect to pointercrate database"),
-        };
+        let pool = PointercratePool::connect().await;
 
         pool.run_migrations().await;
+        pool
+    }
+
+    /// Connects to the configured database, without checking whether its schema is up to date
+    pub async fn connect() -> Self {
+        let config = config::core().unwrap_or_else(|err| panic!("Invalid configuration: {}", err));
+        let mut pool = PointercratePool::from(
+            PgPoolOptions::default()
+                .max_connections(config.pool_size)
+                .connect(&config.database_url)
+                .await
+                .expect("Failed to connect to pointercrate database"),
+        );
+
+        if let Some(replica_url) = &config.replica_url {
+            // Connect lazily, so that an unavailable replica does not prevent startup
+            pool = pool.with_replica(
+                PgPoolOptions::default()
+                    .max_connections(config.pool_size)
+                    .acquire_timeout(REPLICA_ACQUIRE_TIMEOUT)
+                    .connect_lazy(replica_url)
+                    .expect("Invalid read replica connection string"),
+            );
+        }
 
         pool
     }
 
-    async fn run_migrations(&self) {
+    /// Routes read-only connections (see [`PointercratePool::read_connection`]) to the given replica
+    pub fn with_replica(self, replica: Pool<Postgres>) -> Self {
+        PointercratePool {
+            replica: Some(Replica::new(replica)),
+            ..self
+        }
+    }
+
+    /// Applies all migrations that have not been applied to the database yet
+    pub async fn run_migrations(&self) {
         let row = sqlx::query!(
             r#"
 SELECT EXISTS (
@@ -54,11 +135,45 @@ AND NOT EXISTS (
             .expect("Failed to run migrations");
     }
 
-    /// Gets a connection from the connection pool
+    /// Updates the database pool metrics (see [`metrics::record_pool`])
+    pub fn record_metrics(&self) {
+        metrics::record_pool("primary", &self.connection_pool);
+
+        if let Some(replica) = &self.replica {
+            metrics::record_pool("replica", &replica.pool);
+        }
+    }
+
+    /// Gets a connection for read-only queries
+    ///
+    /// If a read replica is configured, the connection is to the replica, unless it was recently
+    /// found to be unhealthy, in which case this falls back to [`PointercratePool::connection`].
+    /// Since the replica might lag behind the primary, this should only be used by handlers that do
+    /// not write to the database, and do not need to see their own writes.
+    pub async fn read_connection(&self) -> Result<PoolConnection<Postgres>> {
+        if let Some(replica) = self.replica.as_ref().filter(|replica| replica.is_healthy()) {
+            match replica.pool.acquire().await {
+                Ok(connection) => return Ok(connection),
+                Err(err) => {
+                    error!(
+                        "Failed to acquire connection to read replica, falling back to primary for {:?}: {:?}",
+                        REPLICA_RETRY_INTERVAL, err
+                    );
+
+                    metrics::record_replica_fallback();
+                    replica.mark_unhealthy();
+                },
+            }
+        }
+
+        self.connection().await
+    }
+
+    /// Gets a connection to the primary database from the connection pool
     pub async fn connection(&self) -> Result<PoolConnection<Postgres>> {
         let mut connection = self.connection_pool.acquire().await?;
 
-        audit_connection(&mut connection, 0).await?;
+        audit_connection(&mut connection, SYSTEM_USER_ID).await?;
 
         Ok(connection)
     }
@@ -66,7 +181,7 @@ AND NOT EXISTS (
     pub async fn transaction(&self) -> Result<Transaction<'static, Postgres>> {
         let mut connection = self.connection_pool.begin().await?;
 
-        audit_connection(&mut connection, 0).await?;
+        audit_connection(&mut connection, SYSTEM_USER_ID).await?;
 
         Ok(connection)
     }
@@ -75,7 +190,10 @@ AND NOT EXISTS (
 // Used for integration tests, when sqlx::test sets up a pool for us
 impl From<Pool<Postgres>> for PointercratePool {
     fn from(connection_pool: Pool<Postgres>) -> Self {
-        PointercratePool { connection_pool }
+        PointercratePool {
+            connection_pool,
+            replica: None,
+        }
     }
 }
 
@@ -1,3 +1,210 @@
+//! Module for ratelimiting requests
+//!
+//! Ratelimits are declared via the [`ratelimits!`](crate::ratelimits!) macro, which generates a
+//! struct with one async method per limit. The state of each limit is kept in a
+//! [`RatelimitStore`]. By default, this is an [`InMemoryStore`], meaning limits reset on restart and
+//! are not shared between multiple pointercrate instances. Setting `ratelimit_backend = "postgres"`
+//! in the `[core]` configuration table (or `RATELIMIT_BACKEND=postgres`) selects the
+//! [`PostgresStore`] instead (see [`configured_store`]).
+//!
+//! The quotas given in the macro invocation are defaults, which can be overwritten in the
+//! `[core.ratelimits]` configuration table, or by setting the `RATELIMIT_<NAME>` environment
+//! variable to `<capacity>/<seconds>`. For example, `RATELIMIT_LOGIN_ATTEMPTS=5/600` allows 5 login
+//! attempts per 10 minutes.
+
+use crate::config::{self, ConfigError, RatelimitBackend};
+use governor::{
+    clock::{Clock, DefaultClock},
+    DefaultKeyedRateLimiter,
+};
+use log::error;
+use sqlx::{Pool, Postgres};
+use std::{
+    collections::HashMap,
+    future::Future,
+    num::NonZeroU32,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+/// How many checks the [`PostgresStore`] performs between removing expired entries
+const CLEANUP_INTERVAL: u32 = 1000;
+
+/// Allows `capacity` requests in bursts, with one request being replenished every
+/// `period / capacity`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quota {
+    pub capacity: u32,
+    pub period: Duration,
+}
+
+impl Quota {
+    pub const fn new(capacity: u32, seconds: u64) -> Self {
+        Quota {
+            capacity,
+            period: Duration::from_secs(seconds),
+        }
+    }
+
+    /// Gets the quota for the ratelimit with the given name, which is the given default unless
+    /// overwritten in the configuration (see [`CoreConfig::ratelimits`](config::CoreConfig::ratelimits))
+    ///
+    /// Falls back to the default if the configuration is invalid (which is reported at startup).
+    pub fn configured(name: &str, capacity: u32, seconds: u64) -> Self {
+        match config::ratelimit_override(name) {
+            Ok(Some((capacity, seconds))) => Quota::new(capacity, seconds),
+            Ok(None) => Quota::new(capacity, seconds),
+            Err(err) => {
+                error!("Not applying configured quota for ratelimit {}: {}", name, err);
+
+                Quota::new(capacity, seconds)
+            },
+        }
+    }
+
+    /// How long it takes for a single request to be replenished
+    fn emission_interval(&self) -> Duration {
+        self.period / self.capacity
+    }
+}
+
+/// Result of a [`RatelimitStore::check`] that found the limit exhausted, containing the time until
+/// the next request would be allowed
+pub type RetryAfter = Duration;
+
+/// A backend keeping track of how many requests were made against each ratelimit
+pub trait RatelimitStore: Send + Sync {
+    /// Counts a request with the given key against the ratelimit with the given name, unless the
+    /// limit is already exhausted
+    ///
+    /// Unkeyed limits use an empty key. The quota of a limit is assumed to never change during the
+    /// lifetime of a store.
+    fn check<'a>(
+        &'a self, limit: &'static str, key: String, quota: Quota,
+    ) -> Pin<Box<dyn Future<Output = Result<(), RetryAfter>> + Send + 'a>>;
+}
+
+/// A [`RatelimitStore`] keeping track of ratelimits in the memory of the current process
+#[derive(Default)]
+pub struct InMemoryStore {
+    limiters: Mutex<HashMap<&'static str, Arc<DefaultKeyedRateLimiter<String>>>>,
+}
+
+impl RatelimitStore for InMemoryStore {
+    fn check<'a>(
+        &'a self, limit: &'static str, key: String, quota: Quota,
+    ) -> Pin<Box<dyn Future<Output = Result<(), RetryAfter>> + Send + 'a>> {
+        let limiter = self
+            .limiters
+            .lock()
+            .unwrap()
+            .entry(limit)
+            .or_insert_with(|| {
+                let quota = governor::Quota::with_period(quota.emission_interval())
+                    .expect("ratelimit quota to have non-zero period")
+                    .allow_burst(NonZeroU32::new(quota.capacity).expect("ratelimit quota to have non-zero capacity"));
+
+                Arc::new(governor::RateLimiter::keyed(quota))
+            })
+            .clone();
+
+        let result = limiter
+            .check_key(&key)
+            .map_err(|not_until| not_until.wait_time_from(DefaultClock::default().now()));
+
+        Box::pin(std::future::ready(result))
+    }
+}
+
+/// A [`RatelimitStore`] keeping track of ratelimits in the `ratelimits` table, allowing them to
+/// persist across restarts and to be shared between all instances using the same database
+///
+/// Implements the same algorithm as the [`InMemoryStore`] (GCRA, see
+/// <https://brandur.org/rate-limiting>). If the database cannot be reached, all requests are allowed.
+pub struct PostgresStore {
+    pool: Pool<Postgres>,
+    checks: AtomicU32,
+}
+
+impl PostgresStore {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        PostgresStore {
+            pool,
+            checks: AtomicU32::new(0),
+        }
+    }
+
+    async fn check_quota(&self, limit: &'static str, key: &str, quota: Quota) -> Result<Result<(), RetryAfter>, sqlx::Error> {
+        if self.checks.fetch_add(1, Ordering::Relaxed).is_multiple_of(CLEANUP_INTERVAL) {
+            // Entries whose theoretical arrival time lies in the past are equivalent to no entry at all
+            sqlx::query!("DELETE FROM ratelimits WHERE tat < EXTRACT(EPOCH FROM clock_timestamp())::FLOAT8")
+                .execute(&self.pool)
+                .await?;
+        }
+
+        let interval = quota.emission_interval().as_secs_f64();
+        let period = quota.period.as_secs_f64();
+
+        // The excluded tat is "now + interval", which is the new tat if the old one already passed
+        let allowed = sqlx::query_scalar!(
+            "INSERT INTO ratelimits (name, key, tat) VALUES ($1, $2, EXTRACT(EPOCH FROM clock_timestamp())::FLOAT8 + $3::FLOAT8) ON \
+             CONFLICT (name, key) DO UPDATE SET tat = GREATEST(ratelimits.tat + $3, EXCLUDED.tat) WHERE GREATEST(ratelimits.tat + $3, \
+             EXCLUDED.tat) - EXCLUDED.tat + $3 <= $4::FLOAT8 RETURNING tat",
+            limit,
+            key,
+            interval,
+            period
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        if allowed.is_some() {
+            return Ok(Ok(()));
+        }
+
+        let retry_after = sqlx::query_scalar!(
+            r#"SELECT GREATEST(tat, EXTRACT(EPOCH FROM clock_timestamp())::FLOAT8) + $3::FLOAT8 - EXTRACT(EPOCH FROM clock_timestamp())::FLOAT8 - $4::FLOAT8 AS "retry_after!" FROM ratelimits WHERE name = $1 AND key = $2"#,
+            limit,
+            key,
+            interval,
+            period
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(Err(Duration::from_secs_f64(retry_after.max(0.0))))
+    }
+}
+
+impl RatelimitStore for PostgresStore {
+    fn check<'a>(
+        &'a self, limit: &'static str, key: String, quota: Quota,
+    ) -> Pin<Box<dyn Future<Output = Result<(), RetryAfter>> + Send + 'a>> {
+        Box::pin(async move {
+            match self.check_quota(limit, &key, quota).await {
+                Ok(result) => result,
+                Err(err) => {
+                    error!("Failed to check ratelimit '{}', allowing request: {:?}", limit, err);
+
+                    Ok(())
+                },
+            }
+        })
+    }
+}
+
+/// Creates the [`RatelimitStore`] selected via [`CoreConfig::ratelimit_backend`](config::CoreConfig::ratelimit_backend)
+pub fn configured_store(pool: Pool<Postgres>) -> Result<Arc<dyn RatelimitStore>, ConfigError> {
+    Ok(match config::core()?.ratelimit_backend {
+        RatelimitBackend::Memory => Arc::new(InMemoryStore::default()),
+        RatelimitBackend::Postgres => Arc::new(PostgresStore::new(pool)),
+    })
+}
+
 #[macro_export]
 macro_rules! ratelimits {
     ($struct_name: ident {$($tokens:tt)*}) => {
@@ -8,36 +215,25 @@ macro_rules! ratelimits {
         }
     };
 
-    // Accumulates the fields of the struct as a comma separated list of the form "name: type | initializer_expression"
-    (@struct@ $struct_name: ident [$($field: ident: $type: ty | $init: expr),*] $name: ident[$capacity: tt per $seconds: tt] => $message: expr, $($remaining: tt)*) => {
+    // Accumulates the fields of the struct as a comma separated list of the form "name | capacity | seconds"
+    (@struct@ $struct_name: ident [$($field: ident | $cap: tt | $secs: tt),*] $name: ident[$capacity: tt per $seconds: tt $(per $key_type: ty)?] => $message: expr, $($remaining: tt)*) => {
         ratelimits!(@struct@
             $struct_name [
-                $($field: $type | $init,)*  // already processed fields
-                $name: governor::RateLimiter<governor::state::direct::NotKeyed, governor::state::InMemoryState, governor::clock::DefaultClock> | governor::RateLimiter::direct(governor::Quota::new(nonzero_ext::nonzero!($capacity), std::time::Duration::from_secs($seconds)).unwrap())  // new field
+                $($field | $cap | $secs,)*  // already processed fields
+                $name | $capacity | $seconds  // new field
             ] $($remaining)*);  // remaining, unprocessed fields as token stream
     };
 
-    (@struct@ $struct_name: ident [$($field: ident: $type: ty | $init: expr),*] $name: ident[$capacity: tt per $seconds: tt per $key_type: ty] => $message: expr, $($remaining: tt)*) => {
-        ratelimits!(@struct@
-            $struct_name [
-                $($field: $type | $init,)*
-                $name: governor::RateLimiter<$key_type, governor::state::keyed::DefaultKeyedStateStore<$key_type>, governor::clock::DefaultClock> | governor::RateLimiter::keyed(governor::Quota::new(nonzero_ext::nonzero!($capacity), std::time::Duration::from_secs($seconds)).unwrap())
-            ] $($remaining)*);
-    };
-
     (@method@ $name: ident[$capacity: tt per $seconds: tt] => $message: expr, $($remaining: tt)*) => {
-        pub(crate) fn $name(&self) -> Result<(), pointercrate_core::error::CoreError> {
-            use governor::clock::{Clock, Reference};
-
-            let now = governor::clock::DefaultClock::default().now();
-
-            self.$name.check().map_err(|too_early| {
-                let remaining: std::time::Duration = too_early.earliest_possible().duration_since(now).into();
+        pub(crate) async fn $name(&self) -> Result<(), pointercrate_core::error::CoreError> {
+            self.store.check(stringify!($name), String::new(), self.$name).await.map_err(|remaining| {
                 log::debug!("Triggered ratelimit '{}'. Cooldown: {}s", stringify!($name), remaining.as_secs());
+                pointercrate_core::metrics::record_ratelimit_hit(stringify!($name));
 
                 pointercrate_core::error::CoreError::Ratelimited {
                     message: $message.to_string(),
                     remaining,
+                    limit: self.$name.capacity,
                 }
             })
         }
@@ -45,37 +241,42 @@ macro_rules! ratelimits {
     };
 
     (@method@ $name: ident[$capacity: tt per $seconds: tt per $key_type: ty] => $message: expr, $($remaining: tt)*) => {
-        pub(crate) fn $name(&self, ip: $key_type) -> Result<(), pointercrate_core::error::CoreError> {
-            use governor::clock::{Clock, Reference};
-
-            let now = governor::clock::DefaultClock::default().now();
-
-            self.$name.check_key(&ip).map_err(|too_early| {
-                let remaining: std::time::Duration = too_early.earliest_possible().duration_since(now).into();
-                log::debug!("Triggered ratelimit '{}' on key '{}'. Cooldown: {}s", stringify!($name), ip, remaining.as_secs());
+        pub(crate) async fn $name(&self, key: $key_type) -> Result<(), pointercrate_core::error::CoreError> {
+            self.store.check(stringify!($name), key.to_string(), self.$name).await.map_err(|remaining| {
+                log::debug!("Triggered ratelimit '{}' on key '{}'. Cooldown: {}s", stringify!($name), key, remaining.as_secs());
+                pointercrate_core::metrics::record_ratelimit_hit(stringify!($name));
 
                 pointercrate_core::error::CoreError::Ratelimited {
                     message: $message.to_string(),
                     remaining,
+                    limit: self.$name.capacity,
                 }
             })
         }
         ratelimits!(@method@  $($remaining)*);
     };
 
-    (@struct@ $struct_name: ident [$($field: ident: $type: ty | $init: expr),*]) => {
+    (@struct@ $struct_name: ident [$($field: ident | $capacity: tt | $seconds: tt),*]) => {
         pub struct $struct_name {
+            store: std::sync::Arc<dyn pointercrate_core::ratelimits::RatelimitStore>,
             $(
-                $field: $type,
+                $field: pointercrate_core::ratelimits::Quota,
             )*
         }
 
         impl $struct_name {
+            /// Creates a new instance keeping track of its limits in memory
+            #[allow(dead_code)]
             pub(crate) fn new() -> Self {
-                #[allow(deprecated)] // the governor API mentions that using Quota::new() is fine since our ratelimits are given as "burst per duration"
+                Self::with_store(std::sync::Arc::new(pointercrate_core::ratelimits::InMemoryStore::default()))
+            }
+
+            #[allow(dead_code)]
+            pub(crate) fn with_store(store: std::sync::Arc<dyn pointercrate_core::ratelimits::RatelimitStore>) -> Self {
                 $struct_name {
+                    store,
                     $(
-                        $field: $init,
+                        $field: pointercrate_core::ratelimits::Quota::configured(stringify!($field), $capacity, $seconds),
                     )*
                 }
             }
@@ -0,0 +1,14 @@
+//! Tags of the demonlist's entries in the [`ResponseCache`](pointercrate_core_api::cache::ResponseCache)
+//!
+//! Every cached entry is tagged with the kinds of objects it was computed from, and every handler
+//! modifying such objects invalidates the respective tag.
+
+pub const DEMONS: &str = "demons";
+pub const RECORDS: &str = "records";
+pub const PLAYERS: &str = "players";
+
+/// Tags of the list itself (which contains the names of verifiers and publishers)
+pub const LIST: &[&str] = &[DEMONS, PLAYERS];
+
+/// Tags of everything derived from scores, such as rankings and the heatmap
+pub const SCORES: &[&str] = &[DEMONS, RECORDS, PLAYERS];
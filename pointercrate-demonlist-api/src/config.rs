@@ -1,7 +1,69 @@
-pub fn submission_webhook() -> Option<String> {
-    std::env::var("DISCORD_WEBHOOK").ok()
+use pointercrate_core::config::{env, ConfigError, ConfigSection, Section};
+use reqwest::Url;
+use serde::Deserialize;
+
+static INTEGRATIONS: Section<IntegrationsConfig> = Section::new();
+
+/// The `[integrations]` configuration table
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct IntegrationsConfig {
+    /// Discord webhook to which notifications about new submissions are sent (environment variable
+    /// `DISCORD_WEBHOOK`)
+    pub discord_webhook: Option<String>,
+
+    /// Base URL of a proxy to use instead of contacting the Geometry Dash servers directly
+    /// (environment variable `GD_CONNECTOR_ENDPOINT`)
+    pub gd_connector_endpoint: Option<String>,
+}
+
+impl ConfigSection for IntegrationsConfig {
+    const NAME: &'static str = "integrations";
+
+    fn apply_env(&mut self) -> Result<(), ConfigError> {
+        if let Some(webhook) = env("DISCORD_WEBHOOK")? {
+            self.discord_webhook = Some(webhook);
+        }
+
+        if let Some(endpoint) = env("GD_CONNECTOR_ENDPOINT")? {
+            self.gd_connector_endpoint = Some(endpoint);
+        }
+
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        validate_url("discord_webhook", self.discord_webhook.as_deref())?;
+        validate_url("gd_connector_endpoint", self.gd_connector_endpoint.as_deref())
+    }
+}
+
+fn validate_url(setting: &'static str, url: Option<&str>) -> Result<(), ConfigError> {
+    let Some(url) = url else { return Ok(()) };
+
+    let invalid = |message: String| ConfigError::Invalid {
+        section: IntegrationsConfig::NAME,
+        setting,
+        message,
+    };
+
+    let url = Url::parse(url).map_err(|err| invalid(format!("'{}' is not a valid URL: {}", url, err)))?;
+
+    match url.scheme() {
+        "http" | "https" => Ok(()),
+        scheme => Err(invalid(format!("expected an http(s) URL, got scheme '{}'", scheme))),
+    }
+}
+
+/// Loads and validates the `[integrations]` configuration table
+pub fn init() -> Result<(), ConfigError> {
+    INTEGRATIONS.init().map(|_| ())
+}
+
+pub(crate) fn submission_webhook() -> Result<Option<&'static str>, ConfigError> {
+    Ok(INTEGRATIONS.get()?.discord_webhook.as_deref())
 }
 
-pub fn gd_connector_endpoint() -> Option<String> {
-    std::env::var("GD_CONNECTOR_ENDPOINT").ok()
+pub fn gd_connector_endpoint() -> Result<Option<&'static str>, ConfigError> {
+    Ok(INTEGRATIONS.get()?.gd_connector_endpoint.as_deref())
 }
@@ -0,0 +1,85 @@
+use crate::cache;
+use log::error;
+use pointercrate_core::{error::CoreError, pool::PointercratePool};
+use pointercrate_core_api::{cache::ResponseCache, error::Result};
+use pointercrate_core_macros::localized;
+use pointercrate_demonlist::{
+    archive::{self, ArchiveImporter, ImportSummary},
+    LIST_ADMINISTRATOR,
+};
+use pointercrate_user::auth::ApiToken;
+use pointercrate_user_api::auth::Auth;
+use rocket::{
+    data::{Limits, ToByteUnit},
+    futures::StreamExt,
+    http::ContentType,
+    response::stream::TextStream,
+    serde::json::Json,
+    tokio::io::{AsyncBufReadExt, BufReader},
+    Data, State,
+};
+
+/// Archives are usually larger than what rocket accepts by default. Can be configured via the
+/// `archive` limit.
+const DEFAULT_ARCHIVE_LIMIT: u64 = 256 * 1024 * 1024;
+
+fn ndjson() -> ContentType {
+    ContentType::new("application", "x-ndjson")
+}
+
+/// Streams an archive of all demonlist data of this instance
+///
+/// Since the response status has already been sent by the time a database error could occur,
+/// such errors simply end the archive early. Importers detect this by the missing end entry.
+#[localized]
+#[rocket::get("/")]
+pub async fn export(auth: Auth<ApiToken>, pool: &State<PointercratePool>) -> Result<(ContentType, TextStream![String])> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    let mut connection = pool.read_connection().await?;
+
+    Ok((
+        ndjson(),
+        TextStream! {
+            let mut entries = std::pin::pin!(archive::export(&mut connection));
+
+            while let Some(entry) = entries.next().await {
+                match entry {
+                    Ok(entry) => yield serde_json::to_string(&entry).unwrap_or_default() + "\n",
+                    Err(err) => {
+                        error!("Failed to export archive: {:?}", err);
+
+                        break;
+                    },
+                }
+            }
+        },
+    ))
+}
+
+/// Imports an archive into this instance, which must not have any players, demons or records yet
+#[localized]
+#[rocket::post("/", data = "<archive>")]
+pub async fn import(
+    mut auth: Auth<ApiToken>, archive: Data<'_>, limits: &Limits, cache: &State<ResponseCache>,
+) -> Result<Json<ImportSummary>> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    let limit = limits.get("archive").unwrap_or(DEFAULT_ARCHIVE_LIMIT.bytes());
+    let mut lines = BufReader::new(archive.open(limit)).lines();
+    let mut importer = ArchiveImporter::new(&mut auth.connection).await?;
+
+    // Archives exceeding the limit are cut off, which the importer detects by the missing end entry
+    while let Some(line) = lines.next_line().await.map_err(|_| CoreError::BadRequest)? {
+        importer.import_line(&line).await?;
+    }
+
+    let summary = importer.finish().await?;
+
+    cache
+        .invalidate(&[cache::DEMONS, cache::RECORDS, cache::PLAYERS], &mut auth.connection)
+        .await?;
+    auth.commit().await?;
+
+    Ok(Json(summary))
+}
@@ -1,6 +1,7 @@
-use crate::ratelimits::DemonlistRatelimits;
+use crate::{cache, lists::MountedList, ratelimits::DemonlistRatelimits, webhooks::EventBus};
 use pointercrate_core::{audit::AuditLogEntry, pool::PointercratePool};
 use pointercrate_core_api::{
+    cache::{CacheKey, ResponseCache},
     error::Result,
     etag::{Precondition, TaggableExt, Tagged},
     pagination::pagination_response,
@@ -12,40 +13,63 @@ use pointercrate_demonlist::{
     creator::{Creator, PostCreator},
     demon::{
         audit::{DemonModificationData, MovementLogEntry},
-        Demon, DemonIdPagination, DemonPositionPagination, FullDemon, PatchDemon, PostDemon,
+        Demon, DemonIdPagination, DemonPositionPagination, FullDemon, MinimalDemon, PatchDemon, PostDemon,
     },
     error::DemonlistError,
     player::DatabasePlayer,
-    LIST_ADMINISTRATOR, LIST_MODERATOR,
+    video::VideoHosts,
+    webhook::Event,
 };
 use pointercrate_user::auth::ApiToken;
 use pointercrate_user_api::auth::Auth;
-use rocket::{http::Status, serde::json::Json, State};
+use rocket::{http::Status, response::content::RawJson, serde::json::Json, State};
+use sqlx::PgConnection;
+use std::sync::Arc;
 
 #[localized]
 #[rocket::get("/")]
-pub async fn paginate(pool: &State<PointercratePool>, pagination: Query<DemonIdPagination>) -> Result<Response2<Json<Vec<Demon>>>> {
-    Ok(pagination_response("/api/v2/demons/", pagination.0, &mut *pool.connection().await?).await?)
+pub async fn paginate(
+    pool: &State<PointercratePool>, list: &MountedList, mut pagination: Query<DemonIdPagination>,
+) -> Result<Response2<Json<Vec<Demon>>>> {
+    pagination.0.list = Some(list.id());
+
+    Ok(pagination_response(list.api_base, pagination.0, &mut *pool.read_connection().await?).await?)
 }
 
 #[localized]
 #[rocket::get("/listed/")]
 pub async fn paginate_listed(
-    pool: &State<PointercratePool>, pagination: Query<DemonPositionPagination>,
-) -> Result<Response2<Json<Vec<Demon>>>> {
-    Ok(pagination_response("/api/v2/demons/listed/", pagination.0, &mut *pool.connection().await?).await?)
+    pool: &State<PointercratePool>, list: &MountedList, mut pagination: Query<DemonPositionPagination>, cache: &State<ResponseCache>,
+    key: CacheKey,
+) -> Result<Response2<RawJson<Arc<str>>>> {
+    pagination.0.list = Some(list.id());
+
+    cache
+        .get_or_insert(key, cache::LIST, async {
+            let response =
+                pagination_response::<_, Demon>(&format!("{}listed/", list.api_base), pagination.0, &mut *pool.connection().await?).await?;
+
+            Ok(response.prerender()?)
+        })
+        .await
 }
 
 #[localized]
 #[rocket::get("/<demon_id>/")]
-pub async fn get(demon_id: i32, pool: &State<PointercratePool>) -> Result<Tagged<FullDemon>> {
-    Ok(Tagged(FullDemon::by_id(demon_id, &mut *pool.connection().await?).await?))
+pub async fn get(demon_id: i32, list: &MountedList, pool: &State<PointercratePool>) -> Result<Tagged<FullDemon>> {
+    let demon = FullDemon::by_id(demon_id, &mut *pool.read_connection().await?).await?;
+
+    require_on_list(&demon.demon.base, list)?;
+
+    Ok(Tagged(demon))
 }
 
 #[localized]
 #[rocket::get("/<demon_id>/audit/")]
-pub async fn audit(demon_id: i32, mut auth: Auth<ApiToken>) -> Result<Json<Vec<AuditLogEntry<DemonModificationData>>>> {
-    auth.require_permission(LIST_ADMINISTRATOR)?;
+pub async fn audit(demon_id: i32, list: &MountedList, mut auth: Auth<ApiToken>) -> Result<Json<Vec<AuditLogEntry<DemonModificationData>>>> {
+    auth.require_permission(list.permissions.administrator)?;
+
+    require_on_list_if_exists(demon_id, list, &mut auth.connection).await?;
 
     let log = pointercrate_demonlist::demon::audit::audit_log_for_demon(demon_id, &mut auth.connection).await?;
 
@@ -58,8 +82,12 @@ pub async fn audit(demon_id: i32, mut auth: Auth<ApiToken>) -> Result<Json<Vec<A
 
 #[localized]
 #[rocket::get("/<demon_id>/audit/movement/")]
-pub async fn movement_log(demon_id: i32, pool: &State<PointercratePool>) -> Result<Json<Vec<MovementLogEntry>>> {
-    let log = pointercrate_demonlist::demon::audit::movement_log_for_demon(demon_id, &mut *pool.connection().await?).await?;
+pub async fn movement_log(demon_id: i32, list: &MountedList, pool: &State<PointercratePool>) -> Result<Json<Vec<MovementLogEntry>>> {
+    let mut connection = pool.read_connection().await?;
+
+    require_on_list_if_exists(demon_id, list, &mut connection).await?;
+
+    let log = pointercrate_demonlist::demon::audit::movement_log_for_demon(demon_id, &mut connection).await?;
 
     if log.is_empty() {
         return Err(DemonlistError::DemonNotFound { demon_id }.into());
@@ -68,68 +96,113 @@ pub async fn movement_log(demon_id: i32, pool: &State<PointercratePool>) -> Resu
     Ok(Json(log))
 }
 
+#[allow(clippy::too_many_arguments)]
 #[localized]
 #[rocket::post("/", data = "<data>")]
 pub async fn post(
-    mut auth: Auth<ApiToken>, data: Json<PostDemon>, ratelimits: &State<DemonlistRatelimits>,
+    mut auth: Auth<ApiToken>, list: &MountedList, data: Json<PostDemon>, ratelimits: &State<DemonlistRatelimits>, events: &State<EventBus>,
+    video_hosts: &State<VideoHosts>, cache: &State<ResponseCache>,
 ) -> Result<Response2<Tagged<FullDemon>>> {
-    auth.require_permission(LIST_MODERATOR)?;
+    auth.require_permission(list.permissions.moderator)?;
 
-    ratelimits.add_demon()?;
+    ratelimits.add_demon().await?;
 
-    let demon = FullDemon::create_from(data.0, &mut auth.connection).await?;
+    let demon = FullDemon::create_from(data.0, list.id(), video_hosts, &mut auth.connection).await?;
 
+    cache.invalidate(&[cache::DEMONS], &mut auth.connection).await?;
     auth.commit().await?;
 
+    events.publish(Event::DemonAdded(&demon));
+
     let demon_id = demon.demon.base.id;
 
     Ok(Response2::tagged(demon)
         .status(Status::Created)
-        .with_header("Location", format!("/api/v2/demons/{}/", demon_id)))
+        .with_header("Location", format!("{}{}/", list.api_base, demon_id)))
 }
 
+#[allow(clippy::too_many_arguments)]
 #[localized]
 #[rocket::patch("/<demon_id>/", data = "<patch>")]
 pub async fn patch(
-    demon_id: i32, mut auth: Auth<ApiToken>, precondition: Precondition, patch: Json<PatchDemon>,
+    demon_id: i32, list: &MountedList, mut auth: Auth<ApiToken>, precondition: Precondition, patch: Json<PatchDemon>,
+    events: &State<EventBus>, video_hosts: &State<VideoHosts>, cache: &State<ResponseCache>,
 ) -> Result<Tagged<FullDemon>> {
-    auth.require_permission(LIST_MODERATOR)?;
+    auth.require_permission(list.permissions.moderator)?;
 
-    let demon = FullDemon::by_id(demon_id, &mut auth.connection)
-        .await?
+    let demon = FullDemon::by_id(demon_id, &mut auth.connection).await?;
+
+    require_on_list(&demon.demon.base, list)?;
+
+    let from = demon.demon.base.position;
+    let demon = demon
         .require_match(precondition)?
-        .apply_patch(patch.0, &mut auth.connection)
+        .apply_patch(patch.0, video_hosts, &mut auth.connection)
         .await?;
 
+    cache.invalidate(&[cache::DEMONS], &mut auth.connection).await?;
     auth.commit().await?;
 
+    if demon.demon.base.position != from {
+        events.publish(Event::DemonMoved { demon: &demon, from });
+    }
+
     Ok(Tagged(demon))
 }
 
+#[localized]
+#[rocket::delete("/<demon_id>/")]
+pub async fn delete(
+    demon_id: i32, list: &MountedList, mut auth: Auth<ApiToken>, precondition: Precondition, cache: &State<ResponseCache>,
+) -> Result<Status> {
+    auth.require_permission(list.permissions.administrator)?;
+
+    let demon = FullDemon::by_id(demon_id, &mut auth.connection).await?;
+
+    require_on_list(&demon.demon.base, list)?;
+    precondition.require_etag_match(&demon)?;
+
+    demon.delete(&mut auth.connection).await?;
+
+    cache.invalidate(&[cache::DEMONS], &mut auth.connection).await?;
+    auth.commit().await?;
+
+    Ok(Status::NoContent)
+}
+
 #[localized]
 #[rocket::post("/<demon_id>/creators/", data = "<creator>")]
-pub async fn post_creator(demon_id: i32, mut auth: Auth<ApiToken>, creator: Json<PostCreator>) -> Result<Response2<Json<()>>> {
-    auth.require_permission(LIST_MODERATOR)?;
+pub async fn post_creator(
+    demon_id: i32, list: &MountedList, mut auth: Auth<ApiToken>, creator: Json<PostCreator>, cache: &State<ResponseCache>,
+) -> Result<Response2<Json<()>>> {
+    auth.require_permission(list.permissions.moderator)?;
 
     let demon = Demon::by_id(demon_id, &mut auth.connection).await?;
+
+    require_on_list(&demon.base, list)?;
     let player = DatabasePlayer::by_name_or_create(&creator.creator, &mut auth.connection).await?;
 
     Creator::insert(&demon.base, &player, &mut auth.connection).await?;
 
+    cache.invalidate(&[cache::DEMONS], &mut auth.connection).await?;
     auth.commit().await?;
 
     Ok(Response2::json(()).status(Status::Created).with_header(
         "Location",
-        format!("/api/v2/demons/{}/creators/{}/", demon.base.position, player.id),
+        format!("{}{}/creators/{}/", list.api_base, demon.base.position, player.id),
     ))
 }
 
 #[localized]
 #[rocket::delete("/<demon_id>/creators/<player_id>/")]
-pub async fn delete_creator(demon_id: i32, player_id: i32, mut auth: Auth<ApiToken>) -> Result<Status> {
-    auth.require_permission(LIST_MODERATOR)?;
+pub async fn delete_creator(
+    demon_id: i32, player_id: i32, list: &MountedList, mut auth: Auth<ApiToken>, cache: &State<ResponseCache>,
+) -> Result<Status> {
+    auth.require_permission(list.permissions.moderator)?;
 
     let demon = Demon::by_id(demon_id, &mut auth.connection).await?;
+
+    require_on_list(&demon.base, list)?;
     let player = DatabasePlayer::by_id(player_id, &mut auth.connection).await?;
 
     Creator::get(&demon.base, &player, &mut auth.connection)
@@ -137,7 +210,29 @@ pub async fn delete_creator(demon_id: i32, player_id: i32, mut auth: Auth<ApiTok
         .delete(&mut auth.connection)
         .await?;
 
+    cache.invalidate(&[cache::DEMONS], &mut auth.connection).await?;
     auth.commit().await?;
 
     Ok(Status::NoContent)
 }
+
+/// Demons are only accessible via the endpoints of the list they are on
+fn require_on_list(demon: &MinimalDemon, list: &MountedList) -> std::result::Result<(), DemonlistError> {
+    if demon.list != list.id() {
+        return Err(DemonlistError::DemonNotFound { demon_id: demon.id });
+    }
+
+    Ok(())
+}
+
+/// Like [`require_on_list`], but also succeeds if the demon does not exist (anymore). Used for
+/// audit logs, which outlive the demons they are about.
+async fn require_on_list_if_exists(
+    demon_id: i32, list: &MountedList, connection: &mut PgConnection,
+) -> std::result::Result<(), DemonlistError> {
+    match MinimalDemon::by_id(demon_id, connection).await {
+        Ok(demon) => require_on_list(&demon, list),
+        Err(DemonlistError::DemonNotFound { .. }) => Ok(()),
+        Err(err) => Err(err),
+    }
+}
@@ -0,0 +1,15 @@
+use pointercrate_core::job::{JobPagination, QueuedJob};
+use pointercrate_core_api::{error::Result, pagination::pagination_response, query::Query, response::Response2};
+use pointercrate_core_macros::localized;
+use pointercrate_demonlist::LIST_ADMINISTRATOR;
+use pointercrate_user::auth::ApiToken;
+use pointercrate_user_api::auth::Auth;
+use rocket::serde::json::Json;
+
+#[localized]
+#[rocket::get("/")]
+pub async fn paginate(mut auth: Auth<ApiToken>, pagination: Query<JobPagination>) -> Result<Response2<Json<Vec<QueuedJob>>>> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    Ok(pagination_response("/api/v1/jobs/", pagination.0, &mut auth.connection).await?)
+}
@@ -1,15 +1,22 @@
-use pointercrate_demonlist::config;
-use rocket::serde::json::Json;
+use pointercrate_core::pool::PointercratePool;
+use pointercrate_core_api::error::Result;
+use pointercrate_core_macros::localized;
+use pointercrate_demonlist::list::{List, DEFAULT_LIST};
+use rocket::{serde::json::Json, State};
 use serde_json::{json, Value};
 
-#[rocket::get("/")]
-pub fn list_information() -> Json<Value> {
+#[localized]
+#[rocket::get("/?<list>")]
+pub async fn list_information(list: Option<i32>, pool: &State<PointercratePool>) -> Result<Json<Value>> {
+    let list = List::by_id(list.unwrap_or(DEFAULT_LIST), &mut *pool.read_connection().await?).await?;
+
     let data = json! {
         {
-            "list_size": config::list_size(),
-            "extended_list_size": config::extended_list_size()
+            "name": list.name,
+            "list_size": list.list_size,
+            "extended_list_size": list.extended_list_size
         }
     };
 
-    Json(data)
+    Ok(Json(data))
 }
@@ -1,6 +1,10 @@
+pub(crate) mod archive;
 pub(crate) mod demon;
+pub(crate) mod job;
 pub(crate) mod misc;
 pub(crate) mod nationality;
 pub(crate) mod player;
 pub(crate) mod record;
+pub(crate) mod review;
 pub(crate) mod submitter;
+pub(crate) mod webhook;
@@ -1,13 +1,25 @@
+use crate::cache;
 use pointercrate_core::pool::PointercratePool;
-use pointercrate_core_api::{error::Result, etag::Tagged, query::Query};
+use pointercrate_core_api::{
+    cache::{CacheKey, ResponseCache},
+    error::Result,
+    etag::Tagged,
+    pagination::pagination_response,
+    query::Query,
+    response::Response2,
+};
 use pointercrate_core_macros::localized;
-use pointercrate_demonlist::nationality::{Nationality, NationalityRankingPagination, NationalityRecord, RankedNation, Subdivision};
-use rocket::{serde::json::Json, State};
+use pointercrate_demonlist::{
+    list::{List, DEFAULT_LIST},
+    nationality::{Nationality, NationalityRankingPagination, NationalityRecord, RankedNation, Subdivision},
+};
+use rocket::{response::content::RawJson, serde::json::Json, State};
+use std::sync::Arc;
 
 #[localized]
 #[rocket::get("/<iso_code>/subdivisions/")]
 pub async fn subdivisions(pool: &State<PointercratePool>, iso_code: String) -> Result<Json<Vec<Subdivision>>> {
-    let mut connection = pool.connection().await?;
+    let mut connection = pool.read_connection().await?;
 
     // good code
     let nationality = Nationality::by_country_code_or_name(iso_code.to_uppercase().as_ref(), &mut connection).await?;
@@ -17,17 +29,29 @@ pub async fn subdivisions(pool: &State<PointercratePool>, iso_code: String) -> R
 
 #[localized]
 #[rocket::get("/ranking/")]
-pub async fn ranking(pool: &State<PointercratePool>, pagination: Query<NationalityRankingPagination>) -> Result<Json<Vec<RankedNation>>> {
-    Ok(Json(pagination.0.page(&mut *pool.connection().await?).await?))
+pub async fn ranking(
+    pool: &State<PointercratePool>, pagination: Query<NationalityRankingPagination>, cache: &State<ResponseCache>, key: CacheKey,
+) -> Result<Response2<RawJson<Arc<str>>>> {
+    cache
+        .get_or_insert(key, cache::SCORES, async {
+            let response =
+                pagination_response::<_, RankedNation>("/api/v1/nationalities/ranking/", pagination.0, &mut *pool.connection().await?)
+                    .await?;
+
+            Ok(response.prerender()?)
+        })
+        .await
 }
 
 #[localized]
-#[rocket::get("/<iso_code>/")]
-pub async fn nation(pool: &State<PointercratePool>, iso_code: String) -> Result<Tagged<NationalityRecord>> {
-    let mut connection = pool.connection().await?;
+#[rocket::get("/<iso_code>/?<list>")]
+pub async fn nation(pool: &State<PointercratePool>, iso_code: String, list: Option<i32>) -> Result<Tagged<NationalityRecord>> {
+    let mut connection = pool.read_connection().await?;
 
     // good code
     let nationality = Nationality::by_country_code_or_name(iso_code.to_uppercase().as_ref(), &mut connection).await?;
 
-    Ok(Tagged(nationality.upgrade(&mut connection).await?))
+    let list = List::by_id(list.unwrap_or(DEFAULT_LIST), &mut connection).await?;
+
+    Ok(Tagged(nationality.upgrade(&list, &mut connection).await?))
 }
@@ -1,87 +1,149 @@
-use crate::claims::AuthWithClaim;
-use pointercrate_core::pool::PointercratePool;
+use crate::{cache, claims::AuthWithClaim, lists::ListRegistry, webhooks::EventBus};
+use pointercrate_core::{audit::AuditLogEntry, pool::PointercratePool};
 use pointercrate_core_api::{
+    cache::{CacheKey, ResponseCache},
     error::Result,
     etag::{Precondition, TaggableExt, Tagged},
-    pagination::pagination_response,
+    pagination::{pagination_response, sorted_pagination_response},
     query::Query,
     response::Response2,
 };
 use pointercrate_core_macros::localized;
 use pointercrate_demonlist::{
     error::DemonlistError,
+    list::DEFAULT_LIST,
     player::{
+        audit::PlayerModificationData,
         claim::{ListedClaim, PatchPlayerClaim, PlayerClaim, PlayerClaimPagination},
         DatabasePlayer, FullPlayer, PatchPlayer, Player, PlayerPagination, RankedPlayer, RankingPagination,
     },
-    LIST_HELPER, LIST_MODERATOR,
+    webhook::Event,
+    LIST_ADMINISTRATOR, LIST_MODERATOR,
 };
 use pointercrate_user::{auth::ApiToken, MODERATOR};
 use pointercrate_user_api::auth::Auth;
-use rocket::{http::Status, serde::json::Json, State};
+use rocket::{http::Status, response::content::RawJson, serde::json::Json, State};
+use std::sync::Arc;
 
 #[localized]
 #[rocket::get("/")]
 pub async fn paginate(
-    pool: &State<PointercratePool>, query: Query<PlayerPagination>, auth: Option<Auth<ApiToken>>,
+    pool: &State<PointercratePool>, query: Query<PlayerPagination>, auth: Option<Auth<ApiToken>>, lists: &State<ListRegistry>,
 ) -> Result<Response2<Json<Vec<Player>>>> {
     let mut pagination = query.0;
 
-    if let Some(auth) = auth {
-        if !auth.has_permission(LIST_HELPER) {
-            pagination.banned = Some(false);
-        }
-    } else {
+    // Banned players are only visible to helpers (of the list filtered by)
+    let helper = lists.permissions(pagination.list)?.helper;
+
+    if !auth.is_some_and(|auth| auth.has_permission(helper)) {
         pagination.banned = Some(false);
     }
 
-    Ok(pagination_response("/api/v1/players/", pagination, &mut *pool.connection().await?).await?)
+    Ok(sorted_pagination_response("/api/v1/players/", pagination, &mut *pool.read_connection().await?).await?)
 }
 
 #[localized]
 #[rocket::get("/ranking/")]
-pub async fn ranking(pool: &State<PointercratePool>, query: Query<RankingPagination>) -> Result<Response2<Json<Vec<RankedPlayer>>>> {
-    Ok(pagination_response("/api/v1/players/ranking/", query.0, &mut *pool.connection().await?).await?)
+pub async fn ranking(
+    pool: &State<PointercratePool>, query: Query<RankingPagination>, cache: &State<ResponseCache>, key: CacheKey,
+) -> Result<Response2<RawJson<Arc<str>>>> {
+    cache
+        .get_or_insert(key, cache::SCORES, async {
+            let response =
+                pagination_response::<_, RankedPlayer>("/api/v1/players/ranking/", query.0, &mut *pool.connection().await?).await?;
+
+            Ok(response.prerender()?)
+        })
+        .await
 }
 
 #[localized]
-#[rocket::get("/me/", rank = 0)]
-pub async fn get_me(auth: AuthWithClaim<ApiToken, false>) -> Result<Tagged<FullPlayer>> {
+#[rocket::get("/me/?<list>", rank = 0)]
+pub async fn get_me(auth: AuthWithClaim<ApiToken, false>, list: Option<i32>) -> Result<Tagged<FullPlayer>> {
     let AuthWithClaim(mut auth, claim) = auth;
 
-    let player = Player::by_id(claim.player.id, &mut auth.connection).await?;
+    let player = Player::by_id_on_list(claim.player.id, list.unwrap_or(DEFAULT_LIST), &mut auth.connection).await?;
     let full_player = player.upgrade(&mut auth.connection).await?;
 
     Ok(Tagged(full_player))
 }
 
 #[localized]
-#[rocket::get("/<player_id>/")]
-pub async fn get(player_id: i32, pool: &State<PointercratePool>) -> Result<Tagged<FullPlayer>> {
-    let mut connection = pool.connection().await?;
+#[rocket::get("/<player_id>/?<list>")]
+pub async fn get(player_id: i32, list: Option<i32>, pool: &State<PointercratePool>) -> Result<Tagged<FullPlayer>> {
+    let mut connection = pool.read_connection().await?;
 
     Ok(Tagged(
-        Player::by_id(player_id, &mut connection).await?.upgrade(&mut connection).await?,
+        Player::by_id_on_list(player_id, list.unwrap_or(DEFAULT_LIST), &mut connection)
+            .await?
+            .upgrade(&mut connection)
+            .await?,
     ))
 }
 
+/// Modifies a player
+///
+/// Players (and their bans) are shared between all lists, so this requires the global
+/// `LIST_MODERATOR` permission rather than moderator permissions for any single list.
 #[localized]
 #[rocket::patch("/<player_id>/", data = "<patch>")]
 pub async fn patch(
-    player_id: i32, mut auth: Auth<ApiToken>, precondition: Precondition, patch: Json<PatchPlayer>,
+    player_id: i32, mut auth: Auth<ApiToken>, precondition: Precondition, patch: Json<PatchPlayer>, events: &State<EventBus>,
+    cache: &State<ResponseCache>,
 ) -> Result<Tagged<FullPlayer>> {
     auth.require_permission(LIST_MODERATOR)?;
     let player = Player::by_id(player_id, &mut auth.connection)
+        .await?
+        .upgrade(&mut auth.connection)
+        .await?
+        .require_match(precondition)?;
+
+    let was_banned = player.player.base.banned;
+    let player = player.apply_patch(patch.0, &mut auth.connection).await?;
+
+    cache.invalidate(&[cache::PLAYERS], &mut auth.connection).await?;
+    auth.commit().await?;
+
+    if player.player.base.banned && !was_banned {
+        events.publish(Event::PlayerBanned(&player.player.base));
+    }
+
+    Ok(Tagged(player))
+}
+
+#[localized]
+#[rocket::delete("/<player_id>/")]
+pub async fn delete(player_id: i32, mut auth: Auth<ApiToken>, precondition: Precondition, cache: &State<ResponseCache>) -> Result<Status> {
+    auth.require_permission(LIST_MODERATOR)?;
+
+    Player::by_id(player_id, &mut auth.connection)
         .await?
         .upgrade(&mut auth.connection)
         .await?
         .require_match(precondition)?
-        .apply_patch(patch.0, &mut auth.connection)
+        .player
+        .base
+        .delete(&mut auth.connection)
         .await?;
 
+    cache.invalidate(&[cache::PLAYERS], &mut auth.connection).await?;
     auth.commit().await?;
 
-    Ok(Tagged(player))
+    Ok(Status::NoContent)
+}
+
+#[localized]
+#[rocket::get("/<player_id>/audit/")]
+pub async fn audit(player_id: i32, mut auth: Auth<ApiToken>) -> Result<Json<Vec<AuditLogEntry<PlayerModificationData>>>> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    let log = pointercrate_demonlist::player::audit::audit_log_for_player(player_id, &mut auth.connection).await?;
+
+    if log.is_empty() {
+        return Err(DemonlistError::PlayerNotFound { player_id }.into());
+    }
+
+    Ok(Json(log))
 }
 
 #[localized]
@@ -104,7 +166,7 @@ pub async fn put_claim(player_id: i32, mut auth: Auth<ApiToken>) -> Result<Respo
 #[localized]
 #[rocket::patch("/<player_id>/claims/<user_id>/", data = "<data>")]
 pub async fn patch_claim(
-    player_id: i32, user_id: i32, mut auth: Auth<ApiToken>, data: Json<PatchPlayerClaim>,
+    player_id: i32, user_id: i32, mut auth: Auth<ApiToken>, data: Json<PatchPlayerClaim>, events: &State<EventBus>,
 ) -> Result<Json<PlayerClaim>> {
     let claim = PlayerClaim::get(user_id, player_id, &mut auth.connection).await;
 
@@ -138,10 +200,20 @@ pub async fn patch_claim(
         },
     };
 
+    let was_verified = claim.verified;
     let claim = claim.apply_patch(data.0, &mut auth.connection).await?;
 
+    let newly_verified_player = match claim.verified && !was_verified {
+        true => Some(DatabasePlayer::by_id(claim.player_id, &mut auth.connection).await?),
+        false => None,
+    };
+
     auth.commit().await?;
 
+    if let Some(ref player) = newly_verified_player {
+        events.publish(Event::ClaimVerified { claim: &claim, player });
+    }
+
     Ok(Json(claim))
 }
 
@@ -175,6 +247,7 @@ pub async fn geolocate_nationality(
     // This is ugly, but there is no other way to trigger our custom error responders from FromRequest impls :/
     auth: std::result::Result<AuthWithClaim<ApiToken, true>, DemonlistError>,
     location: std::result::Result<crate::geolocate::GeolocatedNationality, DemonlistError>,
+    cache: &State<ResponseCache>,
 ) -> Result<Json<pointercrate_demonlist::nationality::Nationality>> {
     let AuthWithClaim(mut auth, claim) = auth?;
     let location = location?;
@@ -183,6 +256,7 @@ pub async fn geolocate_nationality(
 
     player.set_nationality(Some(location.0), &mut auth.connection).await?;
 
+    cache.invalidate(&[cache::PLAYERS], &mut auth.connection).await?;
     auth.commit().await?;
 
     Ok(Json(player.nationality.unwrap()))
@@ -1,62 +1,74 @@
-use crate::ratelimits::DemonlistRatelimits;
-use log::{debug, error, warn};
-use pointercrate_core::{audit::AuditLogEntry, error::CoreError, pool::PointercratePool};
+use crate::{cache, jobs::ValidateSubmission, lists::ListRegistry, ratelimits::DemonlistRatelimits, webhooks::EventBus};
+use pointercrate_core::{audit::AuditLogEntry, error::CoreError, job, pool::PointercratePool};
 use pointercrate_core_api::{
+    cache::ResponseCache,
     error::Result,
     etag::{Precondition, TaggableExt, Tagged},
-    pagination::pagination_response,
+    pagination::sorted_pagination_response,
     query::Query,
     response::Response2,
 };
 use pointercrate_core_macros::localized;
 use pointercrate_demonlist::{
+    demon::MinimalDemon,
     error::DemonlistError,
-    player::claim::PlayerClaim,
+    player::{claim::PlayerClaim, ScoreUpdates},
     record::{
         audit::RecordModificationData,
         note::{notes_on, NewNote, Note, PatchNote},
-        submission_count, FullRecord, MinimalRecordPD, PatchRecord, RecordPagination, RecordStatus, Submission,
+        submission_count, FullRecord, MinimalRecordPD, PatchRecord, RecordOperation, RecordOperationResult, RecordPagination, RecordStatus,
+        Submission, TrackedSubmission,
     },
+    review::RecordClaim,
     submitter::Submitter,
-    LIST_ADMINISTRATOR, LIST_HELPER, LIST_MODERATOR,
+    video::VideoHosts,
+    webhook::Event,
+    LIST_ADMINISTRATOR,
 };
 use pointercrate_user::auth::ApiToken;
 use pointercrate_user_api::auth::Auth;
-use rocket::{http::Status, serde::json::Json, tokio, State};
-use sqlx::{pool::PoolConnection, Postgres};
+use rocket::{http::Status, serde::json::Json, State};
 use std::net::IpAddr;
 
 /// Pagination endpoint for records in case authentication is provided
 ///
 /// Subject to the following constraints
-/// + Only users with `LIST_MODERATOR` permissions can filter by submitter.
-/// + Only users with `LIST_HELPER` permissions can filter by record status. For all other users,
-/// the `status` property defaults to `APPROVED` (although explicitly setting the status to
-/// `APPROVED` is allowed, UNLESS we also filter by player and the player we filter by match a
-/// verified claim of the user making the request, in which case access to all records is allowed
-/// (the `status` property does not get defaulted, and filtering on it is allowed)
+/// + Only users with `LIST_MODERATOR` permissions (or moderator permissions for the list filtered
+/// by) can filter by submitter.
+/// + Only users with `LIST_HELPER` permissions (or helper permissions for the list filtered by) can
+/// filter by record status. For all other users, the `status` property defaults to `APPROVED`
+/// (although explicitly setting the status to `APPROVED` is allowed, UNLESS we also filter by
+/// player and the player we filter by match a verified claim of the user making the request, in
+/// which case access to all records is allowed (the `status` property does not get defaulted, and
+/// filtering on it is allowed)
 #[localized]
 #[rocket::get("/")]
-pub async fn paginate(mut auth: Auth<ApiToken>, query: Query<RecordPagination>) -> Result<Response2<Json<Vec<MinimalRecordPD>>>> {
+pub async fn paginate(
+    mut auth: Auth<ApiToken>, query: Query<RecordPagination>, lists: &State<ListRegistry>,
+) -> Result<Response2<Json<Vec<MinimalRecordPD>>>> {
     let mut pagination = query.0;
+    let permissions = lists.permissions(pagination.list)?;
 
     if pagination.submitter.is_some() {
-        auth.require_permission(LIST_MODERATOR)?;
+        auth.require_permission(permissions.moderator)?;
     }
 
     let claim = PlayerClaim::by_user(auth.user.user().id, &mut auth.connection)
         .await?
         .filter(|c| c.verified);
 
-    if (claim.is_none() || claim.map(|c| c.player.id) != pagination.player) && !auth.has_permission(LIST_HELPER) {
+    if (claim.is_none() || claim.map(|c| c.player.id) != pagination.player) && !auth.has_permission(permissions.helper) {
         if pagination.status.is_some() && pagination.status != Some(RecordStatus::Approved) {
-            return Err(CoreError::MissingPermissions { required: LIST_HELPER }.into());
+            return Err(CoreError::MissingPermissions {
+                required: permissions.helper,
+            }
+            .into());
         }
 
         pagination.status = Some(RecordStatus::Approved);
     }
 
-    Ok(pagination_response("/api/v1/records/", pagination, &mut auth.connection).await?)
+    Ok(sorted_pagination_response("/api/v1/records/", pagination, &mut auth.connection).await?)
 }
 
 #[localized]
@@ -64,7 +76,7 @@ pub async fn paginate(mut auth: Auth<ApiToken>, query: Query<RecordPagination>)
 pub async fn unauthed_pagination(
     pool: &State<PointercratePool>, query: Query<RecordPagination>,
 ) -> Result<Response2<Json<Vec<MinimalRecordPD>>>> {
-    let mut connection = pool.connection().await?;
+    let mut connection = pool.read_connection().await?;
     let mut pagination = query.0;
 
     if pagination.submitter.is_some() {
@@ -77,29 +89,34 @@ pub async fn unauthed_pagination(
 
     pagination.status = Some(RecordStatus::Approved);
 
-    Ok(pagination_response("/api/v1/records/", pagination, &mut connection).await?)
+    Ok(sorted_pagination_response("/api/v1/records/", pagination, &mut connection).await?)
 }
 
+#[allow(clippy::too_many_arguments)]
 #[localized]
 #[rocket::post("/", data = "<submission>")]
 pub async fn submit(
-    ip: IpAddr, auth: Option<Auth<ApiToken>>, submission: Json<Submission>, pool: &State<PointercratePool>,
-    ratelimits: &State<DemonlistRatelimits>,
+    ip: IpAddr, mut auth: Option<Auth<ApiToken>>, submission: Json<Submission>, pool: &State<PointercratePool>,
+    lists: &State<ListRegistry>, ratelimits: &State<DemonlistRatelimits>, events: &State<EventBus>, video_hosts: &State<VideoHosts>,
+    cache: &State<ResponseCache>,
 ) -> Result<Response2<Tagged<FullRecord>>> {
     let submission = submission.0;
     let status_is_submitted = submission.status() == RecordStatus::Submitted;
     let (is_team_member, user_id) = match auth {
-        Some(ref auth) => (auth.has_permission(LIST_HELPER), Some(auth.user.user().id)),
+        Some(ref mut auth) => {
+            let demon = MinimalDemon::by_id(submission.demon_id(), &mut auth.connection).await?;
+            let helper = lists.permissions(Some(demon.list))?.helper;
+
+            if !status_is_submitted || !submission.has_video() {
+                auth.require_permission(helper)?;
+            }
+
+            (auth.has_permission(helper), Some(auth.user.user().id))
+        },
+        None if !status_is_submitted || !submission.has_video() => return Err(CoreError::Unauthorized.into()),
         None => (false, None),
     };
 
-    if !status_is_submitted || !submission.has_video() {
-        match auth {
-            Some(ref auth) => auth.require_permission(LIST_HELPER)?,
-            None => return Err(CoreError::Unauthorized.into()),
-        }
-    }
-
     let mut connection = match auth {
         Some(auth) => auth.connection,
         None => pool.transaction().await?,
@@ -108,7 +125,7 @@ pub async fn submit(
     let submitter = match Submitter::by_ip(ip, &mut connection).await? {
         Some(submitter) => submitter,
         None => {
-            ratelimits.new_submitters()?;
+            ratelimits.new_submitters().await?;
 
             Submitter::create_submitter(ip, &mut connection).await?
         },
@@ -119,7 +136,7 @@ pub async fn submit(
         return Err(DemonlistError::BannedFromSubmissions.into());
     }
 
-    let normalized = submission.normalize(&mut connection).await?;
+    let normalized = submission.normalize(video_hosts, &mut connection).await?;
 
     // check if the player is claimed with submissions locked
     if let Some(claim) = normalized.verified_player_claim(&mut connection).await? {
@@ -138,26 +155,41 @@ pub async fn submit(
         // easier.
 
         // Also check the local ratelimit first since that one expires earlier
-        ratelimits.record_submission(ip)?;
-        ratelimits.record_submission_global()?;
+        ratelimits.record_submission(ip).await?;
+        ratelimits.record_submission_global().await?;
     }
 
     let mut record = validated.create(submitter, &mut connection).await?;
 
-    connection.commit().await.map_err(DemonlistError::from)?;
+    // Allow the submitter to follow up on their submission without needing an account
+    let tracking_token = if status_is_submitted {
+        Some(record.create_tracking_token(&mut connection).await?)
+    } else {
+        None
+    };
 
-    // FIXME: This is fucking stupid
     if status_is_submitted {
         if let Some(ref video) = record.video {
-            tokio::spawn(validate(
-                record.id,
-                video.to_string(),
-                webhook_embed(&record),
-                pool.connection().await?,
-            ));
+            let validation = ValidateSubmission {
+                record_id: record.id,
+                video: video.to_string(),
+            };
+
+            job::enqueue(&validation, &mut connection).await?;
         }
     }
 
+    // Submissions are not visible anywhere cached until they are approved
+    if record.status == RecordStatus::Approved {
+        cache.invalidate(&[cache::RECORDS], &mut connection).await?;
+    }
+
+    connection.commit().await.map_err(DemonlistError::from)?;
+
+    if record.status == RecordStatus::Approved {
+        events.publish(Event::RecordApproved(&record));
+    }
+
     if !is_team_member {
         record.submitter = None;
     }
@@ -171,25 +203,53 @@ pub async fn submit(
         );
     }
 
+    if let Some(token) = tracking_token {
+        response = response.with_header("X-TRACKING-TOKEN", token);
+    }
+
     Ok(response)
 }
 
+/// Retrieves the publicly visible state of a submission via the tracking token handed out when
+/// it was submitted
 #[localized]
-#[rocket::get("/<record_id>/")]
-pub async fn get(record_id: i32, auth: Option<Auth<ApiToken>>, pool: &State<PointercratePool>) -> Result<Tagged<FullRecord>> {
-    let is_helper = auth.as_ref().is_some_and(|auth| auth.has_permission(LIST_HELPER));
+#[rocket::get("/track/<token>/", rank = 0)]
+pub async fn track(token: &str, pool: &State<PointercratePool>) -> Result<Json<TrackedSubmission>> {
+    let mut connection = pool.connection().await?;
 
-    let mut connection = match auth {
-        Some(auth) => auth.connection,
-        None => pool.transaction().await?,
+    Ok(Json(TrackedSubmission::by_token(token, &mut connection).await?))
+}
+
+/// Retrieves a single record
+///
+/// Records that are not approved are only visible to helpers of the record's list, and to the user
+/// holding a verified claim on the record's player.
+#[localized]
+#[rocket::get("/<record_id>/")]
+pub async fn get(
+    record_id: i32, mut auth: Option<Auth<ApiToken>>, pool: &State<PointercratePool>, lists: &State<ListRegistry>,
+) -> Result<Tagged<FullRecord>> {
+    let mut record = match auth {
+        Some(ref mut auth) => FullRecord::by_id(record_id, &mut auth.connection).await?,
+        None => FullRecord::by_id(record_id, &mut *pool.connection().await?).await?,
     };
 
-    let mut record = FullRecord::by_id(record_id, &mut connection).await?;
+    let helper = lists.permissions(Some(record.demon.list))?.helper;
 
-    // TODO: allow access if auth is provided and a verified claim on the record's player is given
-    if !is_helper {
+    if !auth.as_ref().is_some_and(|auth| auth.has_permission(helper)) {
         if record.status != RecordStatus::Approved {
-            return Err(DemonlistError::RecordNotFound { record_id }.into());
+            let is_claimant = match auth {
+                Some(ref mut auth) => match PlayerClaim::get(auth.user.user().id, record.player.id, &mut auth.connection).await {
+                    Ok(claim) => claim.verified,
+                    Err(DemonlistError::ClaimNotFound { .. }) => false,
+                    Err(err) => return Err(err.into()),
+                },
+                None => false,
+            };
+
+            if !is_claimant {
+                return Err(DemonlistError::RecordNotFound { record_id }.into());
+            }
         }
         record.submitter = None;
         record.raw_footage = None;
@@ -212,43 +272,173 @@ pub async fn audit(record_id: i32, mut auth: Auth<ApiToken>) -> Result<Json<Vec<
     Ok(Json(log))
 }
 
+#[allow(clippy::too_many_arguments)]
 #[localized]
 #[rocket::patch("/<record_id>/", data = "<patch>")]
 pub async fn patch(
-    record_id: i32, mut auth: Auth<ApiToken>, precondition: Precondition, patch: Json<PatchRecord>,
+    record_id: i32, mut auth: Auth<ApiToken>, precondition: Precondition, patch: Json<PatchRecord>, lists: &State<ListRegistry>,
+    events: &State<EventBus>, video_hosts: &State<VideoHosts>, cache: &State<ResponseCache>,
 ) -> Result<Tagged<FullRecord>> {
     let record = FullRecord::by_id(record_id, &mut auth.connection).await?;
 
-    if record.demon.position > pointercrate_demonlist::config::extended_list_size() {
-        auth.require_permission(LIST_MODERATOR)?;
-    } else {
-        auth.require_permission(LIST_HELPER)?;
-    }
+    require_reviewer(&record, &auth, lists)?;
+
+    RecordClaim::require_unclaimed_by_others(record_id, auth.user.user().id, &mut auth.connection).await?;
 
+    let old_status = record.status;
     let record = record
         .require_match(precondition)?
-        .apply_patch(patch.0, &mut auth.connection)
+        .apply_patch(patch.0, video_hosts, &mut auth.connection)
         .await?;
 
+    cache.invalidate(&[cache::RECORDS], &mut auth.connection).await?;
     auth.commit().await?;
 
+    publish_review(&record, old_status, events);
+
     Ok(Tagged(record))
 }
 
+/// Applies a list of record modifications and deletions in a single transaction
+///
+/// Each operation is subject to the same permission checks and preconditions as the corresponding
+/// `PATCH` or `DELETE` request. If any operation fails, none of them are applied. The scores of
+/// all players affected by the batch are only recomputed once all operations have been applied.
+///
+/// A batch can contain at most [`max_batch_size`](pointercrate_demonlist::config::max_batch_size)
+/// operations, and at most one operation per record.
 #[localized]
-#[rocket::delete("/<record_id>/")]
-pub async fn delete(record_id: i32, mut auth: Auth<ApiToken>, precondition: Precondition) -> Result<Status> {
-    let record = FullRecord::by_id(record_id, &mut auth.connection).await?;
+#[rocket::post("/batch/", data = "<operations>")]
+pub async fn batch(
+    mut auth: Auth<ApiToken>, operations: Json<Vec<RecordOperation>>, lists: &State<ListRegistry>, events: &State<EventBus>,
+    video_hosts: &State<VideoHosts>, cache: &State<ResponseCache>,
+) -> Result<Json<Vec<RecordOperationResult>>> {
+    RecordOperation::validate_batch(&operations.0)?;
+
+    let mut scores = ScoreUpdates::default();
+    let mut results = Vec::new();
+
+    for (index, operation) in operations.0.into_iter().enumerate() {
+        let result = apply_operation(operation, &mut auth, lists, video_hosts, &mut scores)
+            .await
+            .map_err(|error| DemonlistError::BatchOperationFailed {
+                index,
+                error: Box::new(error),
+            })?;
+
+        results.push(result);
+    }
+
+    scores.apply(&mut auth.connection).await.map_err(DemonlistError::from)?;
+
+    cache.invalidate(&[cache::RECORDS], &mut auth.connection).await?;
+    auth.commit().await?;
+
+    for (result, old_status) in &results {
+        if let (RecordOperationResult::Patch { record, .. }, Some(old_status)) = (result, old_status) {
+            publish_review(record, *old_status, events);
+        }
+    }
+
+    Ok(Json(results.into_iter().map(|(result, _)| result).collect()))
+}
+
+/// Applies a single operation of a batch, returning its result and, for patches, the status the
+/// record had before
+async fn apply_operation(
+    operation: RecordOperation, auth: &mut Auth<ApiToken>, lists: &ListRegistry, video_hosts: &VideoHosts, scores: &mut ScoreUpdates,
+) -> pointercrate_demonlist::error::Result<(RecordOperationResult, Option<RecordStatus>)> {
+    match operation {
+        RecordOperation::Patch { id, if_match, data } => {
+            let record = FullRecord::by_id(id, &mut auth.connection).await?;
+
+            require_reviewer(&record, auth, lists)?;
+            Precondition::from_if_match(&if_match).require_etag_match(&record)?;
+            RecordClaim::require_unclaimed_by_others(id, auth.user.user().id, &mut auth.connection).await?;
+
+            let old_status = record.status;
+            let record = record.apply_patch_deferred(data, video_hosts, scores, &mut auth.connection).await?;
+
+            Ok((RecordOperationResult::patched(record), Some(old_status)))
+        },
+        RecordOperation::Delete { id, if_match } => {
+            let record = FullRecord::by_id(id, &mut auth.connection).await?;
+
+            require_deleter(&record, auth, lists).await?;
+            Precondition::from_if_match(&if_match).require_etag_match(&record)?;
+            RecordClaim::require_unclaimed_by_others(id, auth.user.user().id, &mut auth.connection).await?;
+
+            record.delete_deferred(scores, &mut auth.connection).await?;
+
+            Ok((RecordOperationResult::Delete { id }, None))
+        },
+    }
+}
+
+/// Notifies webhooks if the given record was approved or rejected
+fn publish_review(record: &FullRecord, old_status: RecordStatus, events: &EventBus) {
+    if record.status != old_status {
+        match record.status {
+            RecordStatus::Approved => events.publish(Event::RecordApproved(record)),
+            RecordStatus::Rejected => events.publish(Event::RecordRejected(record)),
+            _ => (),
+        }
+    }
+}
+
+/// Ensures the authenticated user can review the given record, e.g. is a helper of the record's list
+/// (or a moderator, if the record is for a demon beyond the extended list)
+pub(crate) fn require_reviewer(
+    record: &FullRecord, auth: &Auth<ApiToken>, lists: &ListRegistry,
+) -> pointercrate_demonlist::error::Result<()> {
+    let list = lists.by_id(record.demon.list).ok_or(DemonlistError::ListNotFound {
+        list_id: record.demon.list,
+    })?;
+
+    if record.demon.position > list.list.extended_list_size {
+        auth.require_permission(list.permissions.moderator)?;
+    } else {
+        auth.require_permission(list.permissions.helper)?;
+    }
+
+    Ok(())
+}
+
+/// Ensures the authenticated user can delete the given record
+///
+/// Helpers can only delete submissions that no one has touched yet, everything else requires
+/// moderator permissions for the record's list.
+async fn require_deleter(
+    record: &FullRecord, auth: &mut Auth<ApiToken>, lists: &ListRegistry,
+) -> pointercrate_demonlist::error::Result<()> {
+    let list = lists.by_id(record.demon.list).ok_or(DemonlistError::ListNotFound {
+        list_id: record.demon.list,
+    })?;
 
     if record.status == RecordStatus::Submitted && !record.was_modified(&mut auth.connection).await? {
-        auth.require_permission(LIST_HELPER)?;
+        auth.require_permission(list.permissions.helper)?;
     } else {
-        auth.require_permission(LIST_MODERATOR)?;
+        auth.require_permission(list.permissions.moderator)?;
     }
 
+    Ok(())
+}
+
+#[localized]
+#[rocket::delete("/<record_id>/")]
+pub async fn delete(
+    record_id: i32, mut auth: Auth<ApiToken>, precondition: Precondition, lists: &State<ListRegistry>, cache: &State<ResponseCache>,
+) -> Result<Status> {
+    let record = FullRecord::by_id(record_id, &mut auth.connection).await?;
+
+    require_deleter(&record, &mut auth, lists).await?;
+
     precondition.require_etag_match(&record)?;
 
+    RecordClaim::require_unclaimed_by_others(record_id, auth.user.user().id, &mut auth.connection).await?;
+
     record.delete(&mut auth.connection).await?;
+    cache.invalidate(&[cache::RECORDS], &mut auth.connection).await?;
     auth.commit().await?;
 
     Ok(Status::NoContent)
@@ -256,23 +446,13 @@ pub async fn delete(record_id: i32, mut auth: Auth<ApiToken>, precondition: Prec
 
 #[localized]
 #[rocket::get("/<record_id>/notes/")]
-pub async fn get_notes(record_id: i32, mut auth: Auth<ApiToken>) -> Result<Response2<Json<Vec<Note>>>> {
-    let record_holder_id = sqlx::query!("SELECT player FROM records WHERE id = $1", record_id)
-        .fetch_one(&mut *auth.connection)
-        .await
-        .map_err(|err| {
-            if let sqlx::Error::RowNotFound = err {
-                DemonlistError::RecordNotFound { record_id }
-            } else {
-                err.into()
-            }
-        })?
-        .player;
+pub async fn get_notes(record_id: i32, mut auth: Auth<ApiToken>, lists: &State<ListRegistry>) -> Result<Response2<Json<Vec<Note>>>> {
+    let record = FullRecord::by_id(record_id, &mut auth.connection).await?;
 
-    let notes = if auth.has_permission(LIST_HELPER) {
+    let notes = if auth.has_permission(lists.permissions(Some(record.demon.list))?.helper) {
         notes_on(record_id, false, &mut auth.connection).await?
     } else {
-        match PlayerClaim::get(auth.user.user().id, record_holder_id, &mut auth.connection).await {
+        match PlayerClaim::get(auth.user.user().id, record.player.id, &mut auth.connection).await {
             Ok(claim) if claim.verified => notes_on(record_id, true, &mut auth.connection).await?,
             Ok(_) | Err(DemonlistError::ClaimNotFound { .. }) => return Err(DemonlistError::RecordNotFound { record_id }.into()),
             Err(err) => return Err(err.into()),
@@ -284,11 +464,13 @@ pub async fn get_notes(record_id: i32, mut auth: Auth<ApiToken>) -> Result<Respo
 
 #[localized]
 #[rocket::post("/<record_id>/notes/", data = "<data>")]
-pub async fn add_note(record_id: i32, mut auth: Auth<ApiToken>, data: Json<NewNote>) -> Result<Response2<Tagged<Note>>> {
-    auth.require_permission(LIST_HELPER)?;
-
+pub async fn add_note(
+    record_id: i32, mut auth: Auth<ApiToken>, data: Json<NewNote>, lists: &State<ListRegistry>,
+) -> Result<Response2<Tagged<Note>>> {
     let record = FullRecord::by_id(record_id, &mut auth.connection).await?;
 
+    auth.require_permission(lists.permissions(Some(record.demon.list))?.helper)?;
+
     let mut note = Note::create_on(&record, data.0, &mut auth.connection).await?;
 
     note.author = Some(auth.user.into_user().name);
@@ -304,14 +486,12 @@ pub async fn add_note(record_id: i32, mut auth: Auth<ApiToken>, data: Json<NewNo
 
 #[localized]
 #[rocket::patch("/<record_id>/notes/<note_id>/", data = "<patch>")]
-pub async fn patch_note(record_id: i32, note_id: i32, mut auth: Auth<ApiToken>, patch: Json<PatchNote>) -> Result<Tagged<Note>> {
+pub async fn patch_note(
+    record_id: i32, note_id: i32, mut auth: Auth<ApiToken>, patch: Json<PatchNote>, lists: &State<ListRegistry>,
+) -> Result<Tagged<Note>> {
     let note = Note::by_id(record_id, note_id, &mut auth.connection).await?;
 
-    if note.author.as_ref() != Some(&auth.user.user().name) {
-        auth.require_permission(LIST_ADMINISTRATOR)?;
-    } else {
-        auth.require_permission(LIST_HELPER)?;
-    }
+    require_note_editor(&note, &mut auth, lists).await?;
 
     let note = note.apply_patch(patch.0, &mut auth.connection).await?;
 
@@ -322,14 +502,10 @@ pub async fn patch_note(record_id: i32, note_id: i32, mut auth: Auth<ApiToken>,
 
 #[localized]
 #[rocket::delete("/<record_id>/notes/<note_id>/")]
-pub async fn delete_note(record_id: i32, note_id: i32, mut auth: Auth<ApiToken>) -> Result<Status> {
+pub async fn delete_note(record_id: i32, note_id: i32, mut auth: Auth<ApiToken>, lists: &State<ListRegistry>) -> Result<Status> {
     let note = Note::by_id(record_id, note_id, &mut auth.connection).await?;
 
-    if note.author.as_ref() != Some(&auth.user.user().name) {
-        auth.require_permission(LIST_ADMINISTRATOR)?;
-    } else {
-        auth.require_permission(LIST_HELPER)?;
-    }
+    require_note_editor(&note, &mut auth, lists).await?;
 
     note.delete(&mut auth.connection).await?;
 
@@ -338,87 +514,19 @@ pub async fn delete_note(record_id: i32, note_id: i32, mut auth: Auth<ApiToken>)
     Ok(Status::NoContent)
 }
 
-async fn validate(record_id: i32, video: String, body: serde_json::Value, mut connection: PoolConnection<Postgres>) {
-    debug!("Verifying that submission {} with video {} actually is valid", record_id, video);
-
-    match reqwest::get(&video).await {
-        Ok(response) => {
-            let status = response.status().as_u16();
-
-            if (200..400).contains(&status) {
-                debug!("GET request yielded some sort of successful response, executing webhook");
-
-                execute_webhook(body).await;
-            } else {
-                warn!("Server response to 'GET {}' was {:?}, deleting submission!", video, response);
-
-                match FullRecord::delete_by_id(record_id, &mut connection).await {
-                    Ok(_) => (),
-                    Err(error) => error!("INTERNAL SERVER ERROR: Failure to delete record - {:?}!", error),
-                }
-            }
-        },
-        Err(error) => {
-            error!(
-                "INTERNAL SERVER ERROR: GET request to verify video failed: {:?}. Deleting submission",
-                error
-            );
-
-            match FullRecord::delete_by_id(record_id, &mut connection).await {
-                Ok(_) => (),
-                Err(error) => error!("INTERNAL SERVER ERROR: Failure to delete record - {:?}!", error),
-            }
-        },
-    }
-}
+/// Ensures the authenticated user can modify the given note
+///
+/// Helpers of the record's list can modify their own notes, everything else requires administrator
+/// permissions for the record's list.
+async fn require_note_editor(note: &Note, auth: &mut Auth<ApiToken>, lists: &ListRegistry) -> pointercrate_demonlist::error::Result<()> {
+    let record = FullRecord::by_id(note.record, &mut auth.connection).await?;
+    let permissions = lists.permissions(Some(record.demon.list))?;
 
-async fn execute_webhook(body: serde_json::Value) {
-    if let Some(ref webhook_url) = crate::config::submission_webhook() {
-        match reqwest::Client::new()
-            .post(webhook_url)
-            .header("Content-Type", "application/json")
-            .body(body.to_string())
-            .send()
-            .await
-        {
-            Err(error) => error!("INTERNAL SERVER ERROR: Failure to execute discord webhook: {:?}", error),
-            Ok(_) => debug!("Successfully executed discord webhook"),
-        }
+    if note.author.as_ref() != Some(&auth.user.user().name) {
+        auth.require_permission(permissions.administrator)?;
     } else {
-        warn!("Trying to execute webhook, though no link was configured!");
-    }
-}
-
-fn webhook_embed(record: &FullRecord) -> serde_json::Value {
-    let mut payload = serde_json::json!({
-        "content": format!("**New record submitted! ID: {}**", record.id),
-        "embeds": [
-            {
-                "type": "rich",
-                "title": format!("{}% on {}", record.progress, record.demon.name),
-                "description": format!("{} just got {}% on {}! Go add their record!", record.player.name, record.progress, record.demon.name),
-                "footer": {
-                    "text": format!("This record has been submitted by submitter #{}", record.submitter.map(|s|s.id).unwrap_or(1))
-                },
-                "author": {
-                    "name": format!("{} (ID: {})", record.player.name, record.player.id),
-                    "url": record.video
-                },
-                "thumbnail": {
-                    "url": "https://cdn.discordapp.com/avatars/277391246035648512/b03c85d94dc02084c413a7fdbe2cea79.webp?size=1024"
-                },
-            }
-        ]
-    });
-
-    if let Some(ref video) = record.video {
-        payload["embeds"][0]["fields"] = serde_json::json! {
-            [{
-                "name": "Video Proof:",
-                "value": video
-            }]
-        };
+        auth.require_permission(permissions.helper)?;
     }
 
-    payload
+    Ok(())
 }
@@ -0,0 +1,114 @@
+use crate::{endpoints::record::require_reviewer, lists::ListRegistry};
+use pointercrate_core_api::{error::Result, pagination::sorted_pagination_response, query::Query, response::Response2};
+use pointercrate_core_macros::localized;
+use pointercrate_demonlist::{
+    error::DemonlistError,
+    record::{rejection_reason_stats, FullRecord, RejectionReason, RejectionReasonCount},
+    review::{reviewer_stats, QueuedRecord, RecordClaim, ReviewQueuePagination, ReviewerStats},
+};
+use pointercrate_user::auth::ApiToken;
+use pointercrate_user_api::auth::Auth;
+use rocket::{http::Status, serde::json::Json, State};
+
+/// Pagination endpoint for the review queue, e.g. all records awaiting review
+///
+/// Requires `LIST_HELPER` permissions, or helper permissions for the list filtered by.
+#[localized]
+#[rocket::get("/queue/")]
+pub async fn queue(
+    mut auth: Auth<ApiToken>, query: Query<ReviewQueuePagination>, lists: &State<ListRegistry>,
+) -> Result<Response2<Json<Vec<QueuedRecord>>>> {
+    let pagination = query.0;
+
+    auth.require_permission(lists.permissions(pagination.list)?.helper)?;
+
+    Ok(sorted_pagination_response("/api/v1/records/queue/", pagination, &mut auth.connection).await?)
+}
+
+/// Recent activity of all reviewers, optionally restricted to a single list
+///
+/// Requires `LIST_HELPER` permissions, or helper permissions for the list filtered by.
+#[localized]
+#[rocket::get("/queue/reviewers/?<list>")]
+pub async fn reviewers(list: Option<i32>, mut auth: Auth<ApiToken>, lists: &State<ListRegistry>) -> Result<Json<Vec<ReviewerStats>>> {
+    auth.require_permission(lists.permissions(list)?.helper)?;
+
+    Ok(Json(reviewer_stats(list, &mut auth.connection).await?))
+}
+
+/// The catalogue of reasons reviewers can give when rejecting a record
+#[localized]
+#[rocket::get("/rejection-reasons/")]
+pub async fn rejection_reasons() -> Result<Json<Vec<RejectionReason>>> {
+    Ok(Json(RejectionReason::all()?))
+}
+
+/// How often each rejection reason was given, optionally restricted to a single list
+///
+/// Requires `LIST_HELPER` permissions, or helper permissions for the list filtered by.
+#[localized]
+#[rocket::get("/rejection-reasons/stats/?<list>")]
+pub async fn rejection_stats(
+    list: Option<i32>, mut auth: Auth<ApiToken>, lists: &State<ListRegistry>,
+) -> Result<Json<Vec<RejectionReasonCount>>> {
+    auth.require_permission(lists.permissions(list)?.helper)?;
+
+    Ok(Json(rejection_reason_stats(list, &mut auth.connection).await?))
+}
+
+#[localized]
+#[rocket::get("/<record_id>/claim/")]
+pub async fn get_claim(record_id: i32, mut auth: Auth<ApiToken>, lists: &State<ListRegistry>) -> Result<Json<RecordClaim>> {
+    let record = FullRecord::by_id(record_id, &mut auth.connection).await?;
+
+    auth.require_permission(lists.permissions(Some(record.demon.list))?.helper)?;
+
+    match RecordClaim::active_on(record_id, &mut auth.connection).await? {
+        Some(claim) => Ok(Json(claim)),
+        None => Err(DemonlistError::RecordNotClaimed { record_id }.into()),
+    }
+}
+
+/// Claims a record for review by the authenticated user, or extends their existing claim
+///
+/// Requires the same permissions as modifying the record.
+#[localized]
+#[rocket::put("/<record_id>/claim/")]
+pub async fn put_claim(record_id: i32, mut auth: Auth<ApiToken>, lists: &State<ListRegistry>) -> Result<Json<RecordClaim>> {
+    let record = FullRecord::by_id(record_id, &mut auth.connection).await?;
+
+    require_reviewer(&record, &auth, lists)?;
+
+    let claim = record.claim_for_review(auth.user.user(), &mut auth.connection).await?;
+
+    auth.commit().await?;
+
+    Ok(Json(claim))
+}
+
+/// Releases the active claim on a record
+///
+/// Only the claimant can release their claim, unless the authenticated user has moderator
+/// permissions for the record's list.
+#[localized]
+#[rocket::delete("/<record_id>/claim/")]
+pub async fn delete_claim(record_id: i32, mut auth: Auth<ApiToken>, lists: &State<ListRegistry>) -> Result<Status> {
+    let record = FullRecord::by_id(record_id, &mut auth.connection).await?;
+    let permissions = lists.permissions(Some(record.demon.list))?;
+
+    auth.require_permission(permissions.helper)?;
+
+    let claim = RecordClaim::active_on(record_id, &mut auth.connection)
+        .await?
+        .ok_or(DemonlistError::RecordNotClaimed { record_id })?;
+
+    if claim.claimed_by.id != auth.user.user().id {
+        auth.require_permission(permissions.moderator)?;
+    }
+
+    claim.release(&mut auth.connection).await?;
+
+    auth.commit().await?;
+
+    Ok(Status::NoContent)
+}
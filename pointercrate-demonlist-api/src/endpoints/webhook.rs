@@ -0,0 +1,110 @@
+use pointercrate_core_api::{
+    error::Result,
+    etag::{Precondition, TaggableExt, Tagged},
+    pagination::pagination_response,
+    query::Query,
+    response::Response2,
+};
+use pointercrate_core_macros::localized;
+use pointercrate_demonlist::{
+    webhook::{PatchWebhookTarget, PostWebhookTarget, WebhookDelivery, WebhookDeliveryPagination, WebhookTarget},
+    LIST_ADMINISTRATOR,
+};
+use pointercrate_user::auth::ApiToken;
+use pointercrate_user_api::auth::Auth;
+use rocket::{http::Status, serde::json::Json};
+use serde::Serialize;
+
+/// Webhook targets are only returned together with their secret right after creation
+#[derive(Serialize)]
+pub struct CreatedWebhookTarget {
+    #[serde(flatten)]
+    target: WebhookTarget,
+    secret: String,
+}
+
+#[localized]
+#[rocket::get("/")]
+pub async fn list(mut auth: Auth<ApiToken>) -> Result<Json<Vec<WebhookTarget>>> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    Ok(Json(WebhookTarget::all(&mut auth.connection).await?))
+}
+
+#[localized]
+#[rocket::post("/", data = "<data>")]
+pub async fn post(mut auth: Auth<ApiToken>, data: Json<PostWebhookTarget>) -> Result<Response2<Json<CreatedWebhookTarget>>> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    let mut target = WebhookTarget::create_from(data.0, &mut auth.connection).await?;
+
+    auth.commit().await?;
+
+    let target_id = target.id;
+    let secret = std::mem::take(&mut target.secret);
+
+    Ok(Response2::json(CreatedWebhookTarget { target, secret })
+        .status(Status::Created)
+        .with_header("Location", format!("/api/v1/webhooks/{}/", target_id)))
+}
+
+#[localized]
+#[rocket::get("/<target_id>/")]
+pub async fn get(target_id: i32, mut auth: Auth<ApiToken>) -> Result<Tagged<WebhookTarget>> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    Ok(Tagged(WebhookTarget::by_id(target_id, &mut auth.connection).await?))
+}
+
+#[localized]
+#[rocket::patch("/<target_id>/", data = "<patch>")]
+pub async fn patch(
+    target_id: i32, mut auth: Auth<ApiToken>, precondition: Precondition, patch: Json<PatchWebhookTarget>,
+) -> Result<Tagged<WebhookTarget>> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    let target = WebhookTarget::by_id(target_id, &mut auth.connection)
+        .await?
+        .require_match(precondition)?
+        .apply_patch(patch.0, &mut auth.connection)
+        .await?;
+
+    auth.commit().await?;
+
+    Ok(Tagged(target))
+}
+
+#[localized]
+#[rocket::delete("/<target_id>/")]
+pub async fn delete(target_id: i32, mut auth: Auth<ApiToken>, precondition: Precondition) -> Result<Status> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    let target = WebhookTarget::by_id(target_id, &mut auth.connection).await?;
+
+    precondition.require_etag_match(&target)?;
+
+    target.delete(&mut auth.connection).await?;
+    auth.commit().await?;
+
+    Ok(Status::NoContent)
+}
+
+/// The delivery attempts made to the given webhook target
+#[localized]
+#[rocket::get("/<target_id>/deliveries/")]
+pub async fn deliveries(
+    target_id: i32, mut pagination: Query<WebhookDeliveryPagination>, mut auth: Auth<ApiToken>,
+) -> Result<Response2<Json<Vec<WebhookDelivery>>>> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    let target = WebhookTarget::by_id(target_id, &mut auth.connection).await?;
+
+    pagination.0.target = target.id;
+
+    Ok(pagination_response(
+        &format!("/api/v1/webhooks/{}/deliveries/", target.id),
+        pagination.0,
+        &mut auth.connection,
+    )
+    .await?)
+}
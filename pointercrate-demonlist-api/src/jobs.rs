@@ -0,0 +1,116 @@
+//! The background jobs run by the demonlist, and the worker running them
+
+use crate::{
+    cache,
+    webhooks::{self, DeliverWebhook, EventBus},
+};
+use log::{debug, warn};
+use pointercrate_core::{
+    job::{Job, JobQueue},
+    pool::{audit_connection, SYSTEM_USER_ID},
+};
+use pointercrate_core_api::cache::ResponseCache;
+use pointercrate_demonlist::{
+    error::DemonlistError,
+    player::recompute_scores,
+    record::{FullRecord, RecordStatus},
+    score::RecomputeScores,
+    webhook::Event,
+};
+use pointercrate_integrate::gd::{GeometryDashConnector, RefreshDemonData};
+use rocket::{fairing::AdHoc, tokio};
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Postgres};
+use std::error::Error;
+
+/// Job for checking that the video of a newly submitted record actually exists
+///
+/// Submissions whose video link is dead are deleted, all others are announced to the webhook
+/// targets subscribed to record submissions.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct ValidateSubmission {
+    pub record_id: i32,
+    pub video: String,
+}
+
+impl Job for ValidateSubmission {
+    const KIND: &'static str = "validate_submission";
+
+    fn dedup_key(&self) -> Option<String> {
+        Some(self.record_id.to_string())
+    }
+}
+
+type JobResult = Result<(), Box<dyn Error + Send + Sync>>;
+
+async fn validate_submission(job: ValidateSubmission, pool: Pool<Postgres>, events: EventBus) -> JobResult {
+    let mut connection = pool.acquire().await?;
+
+    audit_connection(&mut connection, SYSTEM_USER_ID).await?;
+
+    // The submission might have been handled by a moderator while this job was waiting for a retry
+    let record = match FullRecord::by_id(job.record_id, &mut connection).await {
+        Ok(record) if record.status == RecordStatus::Submitted => record,
+        Ok(_) | Err(DemonlistError::RecordNotFound { .. }) => return Ok(()),
+        Err(err) => return Err(err.into()),
+    };
+
+    debug!(
+        "Verifying that submission {} with video {} actually is valid",
+        job.record_id, job.video
+    );
+
+    // Network errors are propagated so that the check is retried later
+    let response = reqwest::get(&job.video).await?;
+
+    let status = response.status().as_u16();
+
+    if (200..400).contains(&status) {
+        debug!("GET request yielded some sort of successful response, notifying webhook targets");
+
+        events.publish(Event::RecordSubmitted(&record));
+    } else {
+        warn!("Server response to 'GET {}' was {:?}, deleting submission!", job.video, response);
+
+        FullRecord::delete_by_id(job.record_id, &mut connection).await?;
+    }
+
+    Ok(())
+}
+
+async fn recompute_all_scores(pool: Pool<Postgres>) -> JobResult {
+    let mut transaction = pool.begin().await?;
+
+    audit_connection(&mut transaction, SYSTEM_USER_ID).await?;
+    recompute_scores(&mut transaction).await?;
+    ResponseCache::notify(cache::SCORES, &mut transaction).await?;
+    transaction.commit().await?;
+
+    Ok(())
+}
+
+/// Sets up a [`JobQueue`] handling all jobs of the demonlist, which is started once the rocket
+/// launches
+pub(crate) fn worker(pool: Pool<Postgres>, gd: GeometryDashConnector, events: EventBus) -> AdHoc {
+    let client = reqwest::Client::new();
+
+    let queue = JobQueue::new(pool.clone())
+        .register({
+            let pool = pool.clone();
+
+            move |job: ValidateSubmission, _| validate_submission(job, pool.clone(), events.clone())
+        })
+        .register({
+            let pool = pool.clone();
+
+            move |job: DeliverWebhook, attempt| webhooks::deliver(job, attempt, pool.clone(), client.clone())
+        })
+        .register(move |job: RefreshDemonData, _| gd.clone().refresh_demon_data(job.name, job.demon_id, job.level_id))
+        .register(move |_: RecomputeScores, _| recompute_all_scores(pool.clone()));
+
+    AdHoc::on_liftoff("Job Worker", |_| {
+        Box::pin(async move {
+            tokio::spawn(queue.run());
+        })
+    })
+}
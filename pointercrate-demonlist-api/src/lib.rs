@@ -1,25 +1,36 @@
-use crate::{endpoints::misc, ratelimits::DemonlistRatelimits};
-use pointercrate_core::pool::PointercratePool;
+use crate::{endpoints::misc, lists::ListRegistry, ratelimits::DemonlistRatelimits, webhooks::EventBus};
+use pointercrate_core::{config::ConfigError, error::CoreError, pool::PointercratePool, ratelimits::configured_store};
+use pointercrate_core_api::cache::ResponseCacheFairing;
+use pointercrate_demonlist::{list::DEFAULT_LIST, video::VideoHosts};
 use pointercrate_integrate::gd::GeometryDashConnector;
-use rocket::{Build, Rocket};
+use rocket::{fairing::AdHoc, Build, Rocket};
 
+pub mod cache;
 pub(crate) mod claims;
-pub(crate) mod config;
+pub mod config;
 mod endpoints;
 #[cfg(feature = "geolocation")]
 mod geolocate;
+pub(crate) mod jobs;
+pub(crate) mod lists;
 pub(crate) mod pages;
 pub(crate) mod ratelimits;
+pub(crate) mod webhooks;
 
 #[cfg(feature = "geolocation")]
 pub use geolocate::GeolocationProvider;
+pub use lists::{ListSetup, MountedList};
 
 pub fn setup(rocket: Rocket<Build>) -> Rocket<Build> {
-    let ratelimits = DemonlistRatelimits::new();
-    let dash_rs = GeometryDashConnector::new(rocket.state::<PointercratePool>().unwrap().clone_inner());
+    let pool = rocket.state::<PointercratePool>().unwrap().clone_inner();
+    let ratelimits = DemonlistRatelimits::with_store(expect_valid(configured_store(pool.clone())));
+    let dash_rs = GeometryDashConnector::new(pool.clone());
+    let events = EventBus::new(pool.clone());
+    let worker = jobs::worker(pool, dash_rs.clone(), events.clone());
+    let default_list = expect_valid(ListSetup::default_list());
 
-    if let Some(endpoint) = config::gd_connector_endpoint() {
-        pointercrate_integrate::set_gd_connector_endpoint(endpoint);
+    if let Some(endpoint) = expect_valid(config::gd_connector_endpoint()) {
+        pointercrate_integrate::set_gd_connector_endpoint(endpoint.to_string());
     }
 
     #[cfg_attr(not(feature = "geolocation"), allow(unused_mut))]
@@ -28,6 +39,8 @@ pub fn setup(rocket: Rocket<Build>) -> Rocket<Build> {
         endpoints::player::get_me,
         endpoints::player::paginate,
         endpoints::player::patch,
+        endpoints::player::delete,
+        endpoints::player::audit,
         endpoints::player::ranking,
         endpoints::player::put_claim,
         endpoints::player::patch_claim,
@@ -38,9 +51,10 @@ pub fn setup(rocket: Rocket<Build>) -> Rocket<Build> {
     #[cfg(feature = "geolocation")]
     player_routes.extend(rocket::routes![endpoints::player::geolocate_nationality]);
 
-    rocket
+    let rocket = rocket
         .manage(ratelimits)
         .manage(dash_rs)
+        .manage(events)
         .mount("/api/v1/list_information/", rocket::routes![misc::list_information])
         .mount(
             "/api/v1/submitters/",
@@ -56,6 +70,7 @@ pub fn setup(rocket: Rocket<Build>) -> Rocket<Build> {
                 endpoints::record::get_notes,
                 endpoints::record::add_note,
                 endpoints::record::audit,
+                endpoints::record::batch,
                 endpoints::record::delete,
                 endpoints::record::delete_note,
                 endpoints::record::get,
@@ -63,10 +78,34 @@ pub fn setup(rocket: Rocket<Build>) -> Rocket<Build> {
                 endpoints::record::unauthed_pagination,
                 endpoints::record::patch,
                 endpoints::record::patch_note,
-                endpoints::record::submit
+                endpoints::record::submit,
+                endpoints::record::track,
+                endpoints::review::queue,
+                endpoints::review::reviewers,
+                endpoints::review::get_claim,
+                endpoints::review::put_claim,
+                endpoints::review::delete_claim,
+                endpoints::review::rejection_reasons,
+                endpoints::review::rejection_stats
             ],
         )
         .mount("/api/v1/players/", player_routes)
+        .mount(
+            "/api/v1/webhooks/",
+            rocket::routes![
+                endpoints::webhook::list,
+                endpoints::webhook::post,
+                endpoints::webhook::get,
+                endpoints::webhook::patch,
+                endpoints::webhook::delete,
+                endpoints::webhook::deliveries
+            ],
+        )
+        .mount("/api/v1/jobs/", rocket::routes![endpoints::job::paginate])
+        .mount(
+            "/api/v1/archive/",
+            rocket::routes![endpoints::archive::export, endpoints::archive::import],
+        )
         .mount(
             "/api/v1/nationalities/",
             rocket::routes![
@@ -75,8 +114,44 @@ pub fn setup(rocket: Rocket<Build>) -> Rocket<Build> {
                 endpoints::nationality::nation
             ],
         )
+        .attach(AdHoc::try_on_ignite("Lists", lists::register_lists))
+        .attach(AdHoc::try_on_ignite("Scoring Policy", sync_scoring_policy))
+        .attach(AdHoc::try_on_ignite("Discord Webhook", webhooks::register_legacy_webhook))
+        .attach(AdHoc::on_ignite("Video Hosts", setup_video_hosts))
+        .attach(worker)
+        .attach(ResponseCacheFairing)
+        .manage(ListRegistry::default());
+
+    mount(rocket, Some(DEFAULT_LIST), default_list)
+}
+
+/// Invalid configuration is reported by the `config::init` functions at startup already, so this
+/// only panics if they were not called
+fn expect_valid<T>(setting: Result<T, ConfigError>) -> T {
+    setting.unwrap_or_else(|err| panic!("Invalid configuration: {}", err))
+}
+
+/// Hosts an additional list on this pointercrate instance
+///
+/// Mounts the demon endpoints at [`ListSetup::api_base`] and the demonlist pages at
+/// [`ListSetup::pages_base`]. The list is created in the database on launch, if it does not exist
+/// yet. Must be called after [`setup`], which already mounts the default list at `/api/v2/demons/`
+/// and `/demonlist/`.
+pub fn mount_list(rocket: Rocket<Build>, setup: ListSetup) -> Rocket<Build> {
+    mount(rocket, None, setup)
+}
+
+fn mount(rocket: Rocket<Build>, id: Option<i32>, setup: ListSetup) -> Rocket<Build> {
+    let (api_base, pages_base) = (setup.api_base, setup.pages_base);
+
+    rocket
+        .state::<ListRegistry>()
+        .expect("pointercrate_demonlist_api::setup to be called before mounting lists")
+        .add(id, setup);
+
+    rocket
         .mount(
-            "/api/v2/demons/",
+            api_base,
             rocket::routes![
                 endpoints::demon::get,
                 endpoints::demon::paginate,
@@ -84,13 +159,14 @@ pub fn setup(rocket: Rocket<Build>) -> Rocket<Build> {
                 endpoints::demon::audit,
                 endpoints::demon::movement_log,
                 endpoints::demon::patch,
+                endpoints::demon::delete,
                 endpoints::demon::post,
                 endpoints::demon::post_creator,
                 endpoints::demon::delete_creator
             ],
         )
         .mount(
-            "/demonlist/",
+            pages_base,
             rocket::routes![
                 pages::overview,
                 pages::stats_viewer,
@@ -101,3 +177,43 @@ pub fn setup(rocket: Rocket<Build>) -> Rocket<Build> {
             ],
         )
 }
+
+/// Manages the default [`VideoHosts`], unless a registry with custom hosts was managed explicitly
+async fn setup_video_hosts(rocket: Rocket<Build>) -> Rocket<Build> {
+    if rocket.state::<VideoHosts>().is_some() {
+        return rocket;
+    }
+
+    rocket.manage(VideoHosts::default())
+}
+
+/// Makes sure the scores cached in the database were computed using the configured
+/// [`ScoringPolicy`](pointercrate_demonlist::score::ScoringPolicy), scheduling their recomputation if not.
+async fn sync_scoring_policy(rocket: Rocket<Build>) -> rocket::fairing::Result {
+    let Some(pool) = rocket.state::<PointercratePool>() else {
+        log::error!("No database pool registered, cannot synchronize scoring policy");
+
+        return Err(rocket);
+    };
+
+    let result: Result<bool, CoreError> = async {
+        let mut transaction = pool.transaction().await?;
+        let recomputed = pointercrate_demonlist::score::sync_scoring_policy(&mut transaction).await?;
+        transaction.commit().await?;
+
+        Ok(recomputed)
+    }
+    .await;
+
+    match result {
+        Ok(true) => log::info!("Scheduled recomputation of all scores according to the configured scoring policy"),
+        Ok(false) => (),
+        Err(err) => {
+            log::error!("Failed to synchronize scoring policy: {:?}", err);
+
+            return Err(rocket);
+        },
+    }
+
+    Ok(rocket)
+}
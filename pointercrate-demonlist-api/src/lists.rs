@@ -0,0 +1,217 @@
+//! Support for mounting the demon endpoints and demonlist pages once per [`List`]
+//!
+//! Every list hosted by a pointercrate instance gets its own copy of the `/api/v2/demons/` endpoints
+//! and `/demonlist/` pages, mounted at different base paths. Handlers figure out which list they
+//! are serving via the [`MountedList`] request guard, which resolves the base path of the route
+//! that matched the request.
+
+use log::{error, info};
+use pointercrate_core::{config::ConfigError, error::CoreError, job, pool::PointercratePool};
+use pointercrate_core_api::{tryo_result, tryo_state};
+use pointercrate_demonlist::{
+    config,
+    error::DemonlistError,
+    list::{List, ListPermissions},
+    score::RecomputeScores,
+};
+use pointercrate_demonlist_pages::ListContext;
+use rocket::{
+    request::{FromRequest, Outcome},
+    Build, Request, Rocket,
+};
+use std::sync::{Mutex, OnceLock};
+
+/// Description of a list to be hosted by this pointercrate instance. See [`mount_list`](crate::mount_list).
+#[derive(Debug, Clone)]
+pub struct ListSetup {
+    /// The list's name. Lists are identified by name across restarts, so changing this creates a new
+    /// list.
+    pub name: &'static str,
+
+    /// Where to mount the list's demon endpoints (e.g. `/api/v2/challenges/`)
+    pub api_base: &'static str,
+
+    /// Where to mount the list's pages (e.g. `/challengelist/`)
+    pub pages_base: &'static str,
+
+    pub list_size: i16,
+    pub extended_list_size: i16,
+
+    /// The permissions required to manage this list. Remember to merge
+    /// [`ListPermissions::permissions_manager`] into your instance's permissions manager!
+    pub permissions: ListPermissions,
+}
+
+impl ListSetup {
+    /// The setup of the default list, whose sizes are configured via the `LIST_SIZE` and
+    /// `EXTENDED_LIST_SIZE` environment variables
+    pub(crate) fn default_list() -> Result<Self, ConfigError> {
+        Ok(ListSetup {
+            name: "demonlist",
+            api_base: "/api/v2/demons/",
+            pages_base: "/demonlist/",
+            list_size: config::list_size()?,
+            extended_list_size: config::extended_list_size()?,
+            permissions: ListPermissions::default(),
+        })
+    }
+}
+
+/// A list that has been mounted onto the rocket, and registered in the database
+#[derive(Debug)]
+pub struct MountedList {
+    pub list: List,
+    pub permissions: ListPermissions,
+    pub api_base: &'static str,
+    pub pages_base: &'static str,
+}
+
+impl MountedList {
+    pub fn id(&self) -> i32 {
+        self.list.id
+    }
+
+    /// The information about this list needed to render its pages
+    pub fn context(&self) -> ListContext {
+        ListContext {
+            list: self.list.clone(),
+            pages_base: self.pages_base,
+            api_base: self.api_base,
+        }
+    }
+}
+
+/// Managed state keeping track of all mounted lists
+#[derive(Default)]
+pub(crate) struct ListRegistry {
+    /// Lists that were mounted, but not yet registered in the database, together with their id if
+    /// it is fixed. Registration happens on ignite.
+    pending: Mutex<Vec<(Option<i32>, ListSetup)>>,
+    mounted: OnceLock<Vec<MountedList>>,
+}
+
+impl ListRegistry {
+    /// Adds a list to be registered on ignite. If `id` is set, the list with that id is used instead
+    /// of looking it up by name.
+    pub(crate) fn add(&self, id: Option<i32>, setup: ListSetup) {
+        // Mutex poisoning can only happen if some other thread panicked while mounting lists, at which point the rocket will never launch anyway
+        let mut pending = self.pending.lock().unwrap();
+
+        if pending.iter().any(|(_, other)| other.name == setup.name) {
+            panic!("List '{}' mounted twice", setup.name)
+        }
+
+        pending.push((id, setup))
+    }
+
+    /// Finds the list whose endpoints or pages are mounted at the given base path
+    fn by_base(&self, base: &str) -> Option<&MountedList> {
+        let base = base.trim_end_matches('/');
+
+        self.mounted
+            .get()?
+            .iter()
+            .find(|mounted| mounted.api_base.trim_end_matches('/') == base || mounted.pages_base.trim_end_matches('/') == base)
+    }
+
+    /// Finds the mounted list with the given id
+    pub(crate) fn by_id(&self, list_id: i32) -> Option<&MountedList> {
+        self.mounted.get()?.iter().find(|mounted| mounted.list.id == list_id)
+    }
+
+    /// The permissions required to manage the list with the given id, or the global demonlist
+    /// permissions if no list is given
+    pub(crate) fn permissions(&self, list_id: Option<i32>) -> Result<ListPermissions, DemonlistError> {
+        match list_id {
+            Some(list_id) => self
+                .by_id(list_id)
+                .map(|mounted| mounted.permissions)
+                .ok_or(DemonlistError::ListNotFound { list_id }),
+            None => Ok(ListPermissions::default()),
+        }
+    }
+}
+
+/// Registers all mounted lists in the database, creating them if they do not exist yet
+pub(crate) async fn register_lists(rocket: Rocket<Build>) -> rocket::fairing::Result {
+    let (Some(pool), Some(registry)) = (rocket.state::<PointercratePool>(), rocket.state::<ListRegistry>()) else {
+        error!("No database pool or list registry, cannot register lists");
+
+        return Err(rocket);
+    };
+
+    let pending = std::mem::take(&mut *registry.pending.lock().unwrap());
+
+    let result: Result<Vec<MountedList>, DemonlistError> = async {
+        let mut transaction = pool.transaction().await?;
+        let mut mounted = Vec::new();
+
+        let mut sizes_changed = false;
+
+        for (id, setup) in pending {
+            let mut list = match id {
+                Some(id) => List::by_id(id, &mut transaction).await?,
+                None => List::register(setup.name, setup.list_size, setup.extended_list_size, &mut transaction).await?,
+            };
+
+            if (list.list_size, list.extended_list_size) != (setup.list_size, setup.extended_list_size) {
+                info!(
+                    "Resizing list {} to list size {} and extended list size {}",
+                    list, setup.list_size, setup.extended_list_size
+                );
+
+                list.set_sizes(setup.list_size, setup.extended_list_size, &mut transaction).await?;
+                sizes_changed = true;
+            }
+
+            info!("Mounted list {} at {} and {}", list, setup.api_base, setup.pages_base);
+
+            mounted.push(MountedList {
+                list,
+                permissions: setup.permissions,
+                api_base: setup.api_base,
+                pages_base: setup.pages_base,
+            });
+        }
+
+        // Which progress records give points depends on the list size
+        if sizes_changed {
+            job::enqueue(&RecomputeScores, &mut transaction).await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(mounted)
+    }
+    .await;
+
+    match result {
+        Ok(mounted) => {
+            let _ = registry.mounted.set(mounted);
+
+            Ok(rocket)
+        },
+        Err(err) => {
+            error!("Failed to register lists: {:?}", err);
+
+            Err(rocket)
+        },
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for &'r MountedList {
+    type Error = CoreError;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let registry = tryo_state!(request, ListRegistry);
+        let base = tryo_result!(request
+            .route()
+            .map(|route| route.uri.base())
+            .ok_or_else(|| CoreError::internal_server_error("MountedList guard used outside of a route")));
+
+        Outcome::Success(tryo_result!(registry
+            .by_base(base)
+            .ok_or_else(|| { CoreError::internal_server_error(format!("No list mounted at {}", base)) })))
+    }
+}
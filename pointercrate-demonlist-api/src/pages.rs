@@ -6,16 +6,17 @@ use rocket::{response::Redirect, State};
 use chrono::{DateTime, FixedOffset, NaiveDate, Utc};
 use pointercrate_core::{audit::AuditLogEntryType, pool::PointercratePool};
 use pointercrate_core_api::{
+    cache::{CacheKey, ResponseCache},
     error::Result,
     response::{Page, Response2},
 };
 use pointercrate_demonlist::player::claim::PlayerClaim;
 use pointercrate_demonlist::player::{FullPlayer, Player};
 use pointercrate_demonlist::{
-    demon::{audit::audit_log_for_demon, current_list, list_at, FullDemon, MinimalDemon},
+    demon::{audit::audit_log_for_demon, current_list, list_at, Demon, FullDemon, MinimalDemon},
     error::DemonlistError,
     nationality::Nationality,
-    LIST_ADMINISTRATOR, LIST_HELPER, LIST_MODERATOR,
+    video::VideoHosts,
 };
 use pointercrate_demonlist_pages::{
     components::{team::Team, time_machine::Tardis},
@@ -30,18 +31,21 @@ use pointercrate_user_api::auth::Auth;
 use rocket::{futures::StreamExt, http::CookieJar};
 use sqlx::PgConnection;
 
+use crate::{cache, lists::MountedList};
+
+#[allow(clippy::too_many_arguments)]
 #[localized]
 #[rocket::get("/?<timemachine>&<submitter>")]
 pub async fn overview(
     pool: &State<PointercratePool>, timemachine: Option<bool>, submitter: Option<bool>, cookies: &CookieJar<'_>,
-    auth: Option<Auth<NonMutating>>,
+    auth: Option<Auth<NonMutating>>, list: &MountedList, cache: &State<ResponseCache>,
 ) -> Result<Page> {
     // A few months before pointercrate first went live - definitely the oldest data we have
     let beginning_of_time = NaiveDate::from_ymd_opt(2017, 1, 4).unwrap().and_hms_opt(0, 0, 0).unwrap();
 
-    let mut connection = pool.connection().await?;
+    let mut connection = pool.read_connection().await?;
 
-    let demonlist = current_list(&mut connection).await?;
+    let demonlist = cached_list(list, cache, pool).await?;
 
     let specified_when = cookies
         .get("when")
@@ -57,48 +61,69 @@ pub async fn overview(
     let mut tardis = Tardis::new(timemachine.unwrap_or(false));
 
     if let Some(destination) = specified_when {
-        let demons_then = list_at(&mut connection, destination.naive_utc()).await?;
+        let demons_then = list_at(list.id(), &mut connection, destination.naive_utc()).await?;
         tardis.activate(destination, demons_then, true)
     }
 
     Ok(Page::new(OverviewPage {
-        team: Team {
-            admins: User::by_permission(LIST_ADMINISTRATOR, &mut connection).await?,
-            moderators: User::by_permission(LIST_MODERATOR, &mut connection).await?,
-            helpers: User::by_permission(LIST_HELPER, &mut connection).await?,
-        },
+        list: list.context(),
+        team: team(list, &mut connection).await?,
         demonlist,
         time_machine: tardis,
         submitter_initially_visible: submitter.unwrap_or(false),
         claimed_player: match auth {
-            Some(auth) => claimed_full_player(auth.user.user(), &mut connection).await,
+            Some(auth) => claimed_full_player(auth.user.user(), list, &mut connection).await,
             None => None,
         },
     }))
 }
 
-async fn claimed_full_player(user: &User, connection: &mut PgConnection) -> Option<FullPlayer> {
+/// The current state of the given list, from the [`ResponseCache`] if possible
+async fn cached_list(list: &MountedList, cache: &ResponseCache, pool: &PointercratePool) -> Result<Vec<Demon>> {
+    cache
+        .get_or_insert(format!("current_list/{}", list.id()), cache::LIST, async {
+            Ok(current_list(list.id(), &mut *pool.connection().await?).await?)
+        })
+        .await
+}
+
+async fn team(list: &MountedList, connection: &mut PgConnection) -> Result<Team> {
+    Ok(Team {
+        admins: User::by_permission(list.permissions.administrator, connection).await?,
+        moderators: User::by_permission(list.permissions.moderator, connection).await?,
+        helpers: User::by_permission(list.permissions.helper, connection).await?,
+    })
+}
+
+async fn claimed_full_player(user: &User, list: &MountedList, connection: &mut PgConnection) -> Option<FullPlayer> {
     let claim = PlayerClaim::by_user(user.id, connection).await.ok().flatten()?;
-    let player = Player::by_id(claim.player.id, connection).await.ok()?;
+    let player = Player::by_id_on_list(claim.player.id, list.id(), connection).await.ok()?;
 
     player.upgrade(connection).await.ok()
 }
 
 #[rocket::get("/permalink/<demon_id>/")]
-pub async fn demon_permalink(demon_id: i32, pool: &State<PointercratePool>) -> Result<Redirect> {
-    let mut connection = pool.connection().await?;
+pub async fn demon_permalink(demon_id: i32, pool: &State<PointercratePool>, list: &MountedList) -> Result<Redirect> {
+    let mut connection = pool.read_connection().await?;
 
-    let position = MinimalDemon::by_id(demon_id, &mut connection).await?.position;
+    let demon = MinimalDemon::by_id(demon_id, &mut connection).await?;
 
-    Ok(Redirect::to(rocket::uri!("/demonlist", demon_page(position))))
+    if demon.list != list.id() {
+        return Err(DemonlistError::DemonNotFound { demon_id }.into());
+    }
+
+    Ok(Redirect::to(format!("{}{}/", list.pages_base, demon.position)))
 }
 
 #[localized]
 #[rocket::get("/<position>/")]
-pub async fn demon_page(position: i16, pool: &State<PointercratePool>, gd: &State<GeometryDashConnector>) -> Result<Page> {
-    let mut connection = pool.connection().await?;
+pub async fn demon_page(
+    position: i16, pool: &State<PointercratePool>, gd: &State<GeometryDashConnector>, list: &MountedList, video_hosts: &State<VideoHosts>,
+    cache: &State<ResponseCache>,
+) -> Result<Page> {
+    let mut connection = pool.read_connection().await?;
 
-    let full_demon = FullDemon::by_position(position, &mut connection).await?;
+    let full_demon = FullDemon::by_position(position, list.id(), &mut connection).await?;
 
     let audit_log = audit_log_for_demon(full_demon.demon.base.id, &mut connection).await?;
 
@@ -137,42 +162,53 @@ pub async fn demon_page(position: i16, pool: &State<PointercratePool>, gd: &Stat
     }
 
     Ok(Page::new(DemonPage {
-        team: Team {
-            admins: User::by_permission(LIST_ADMINISTRATOR, &mut connection).await?,
-            moderators: User::by_permission(LIST_MODERATOR, &mut connection).await?,
-            helpers: User::by_permission(LIST_HELPER, &mut connection).await?,
-        },
-        demonlist: current_list(&mut connection).await?,
+        list: list.context(),
+        team: team(list, &mut connection).await?,
+        demonlist: cached_list(list, cache, pool).await?,
         movements: modifications,
         integration: gd.load_level_for_demon(&full_demon.demon).await,
         data: full_demon,
+        video_hosts,
     }))
 }
 
 #[localized]
 #[rocket::get("/statsviewer/")]
-pub async fn stats_viewer(pool: &State<PointercratePool>) -> Result<Page> {
-    let mut connection = pool.connection().await?;
+pub async fn stats_viewer(pool: &State<PointercratePool>, list: &MountedList) -> Result<Page> {
+    let mut connection = pool.read_connection().await?;
 
     Ok(Page::new(IndividualStatsViewer {
+        list: list.context(),
         nationalities_in_use: Nationality::used(&mut connection).await?,
     }))
 }
 
 #[localized]
 #[rocket::get("/statsviewer/nations/")]
-pub async fn nation_stats_viewer() -> Page {
-    Page::new(pointercrate_demonlist_pages::statsviewer::national::nation_based_stats_viewer())
+pub async fn nation_stats_viewer(list: &MountedList) -> Page {
+    Page::new(pointercrate_demonlist_pages::statsviewer::national::nation_based_stats_viewer(
+        &list.context(),
+    ))
 }
 
 #[localized]
 #[rocket::get("/statsviewer/heatmap.css")]
-pub async fn heatmap_css(pool: &State<PointercratePool>) -> Result<Response2<String>> {
+pub async fn heatmap_css(
+    pool: &State<PointercratePool>, list: &MountedList, cache: &State<ResponseCache>, key: CacheKey,
+) -> Result<Response2<String>> {
+    cache.get_or_insert(key, cache::SCORES, heatmap(pool, list)).await
+}
+
+async fn heatmap(pool: &PointercratePool, list: &MountedList) -> Result<Response2<String>> {
     let mut connection = pool.connection().await?;
     let mut css = String::new();
 
     let mut nation_scores = HashMap::new();
-    let mut nations_stream = sqlx::query!("SELECT iso_country_code, score FROM nationalities WHERE score > 0.0").fetch(&mut *connection);
+    let mut nations_stream = sqlx::query!(
+        "SELECT nation AS iso_country_code, score FROM nation_scores WHERE list = $1 AND score > 0.0",
+        list.id()
+    )
+    .fetch(&mut *connection);
 
     while let Some(row) = nations_stream.next().await {
         let row = row.map_err(DemonlistError::from)?;
@@ -192,8 +228,11 @@ pub async fn heatmap_css(pool: &State<PointercratePool>) -> Result<Response2<Str
     // un-borrow `connection`
     drop(nations_stream);
 
-    let mut subdivisions_stream =
-        sqlx::query!("SELECT nation, iso_code, score FROM subdivisions WHERE score > 0.0").fetch(&mut *connection);
+    let mut subdivisions_stream = sqlx::query!(
+        "SELECT nation, subdivision AS iso_code, score FROM subdivision_scores WHERE list = $1 AND score > 0.0",
+        list.id()
+    )
+    .fetch(&mut *connection);
 
     while let Some(row) = subdivisions_stream.next().await {
         let row = row.map_err(DemonlistError::from)?;
@@ -0,0 +1,321 @@
+//! Delivery of [`Event`]s to the registered webhook targets
+//!
+//! Every delivery is a `POST` request whose body is signed using HMAC-SHA256 with the target's
+//! secret. The hex-encoded signature is sent in the `X-Pointercrate-Signature` header (prefixed with
+//! `sha256=`), and the type of the event in the `X-Pointercrate-Event` header. Each delivery is a
+//! [`DeliverWebhook`] job, meaning failed deliveries are retried with exponential backoff. Every
+//! attempt is logged in the `webhook_deliveries` table.
+
+use hmac::{Hmac, Mac};
+use log::{debug, error, info, warn};
+use pointercrate_core::{
+    job::{self, Job},
+    pool::PointercratePool,
+};
+use pointercrate_demonlist::{
+    error::DemonlistError,
+    webhook::{Event, EventType, PayloadFormat, PostWebhookTarget, WebhookDelivery, WebhookTarget},
+};
+use rocket::{tokio, Build, Rocket};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::Sha256;
+use sqlx::{Pool, Postgres};
+
+/// How often delivery of an event to a target is attempted before giving up
+const MAX_ATTEMPTS: i16 = 5;
+
+/// Managed state through which request handlers notify webhook targets about [`Event`]s
+#[derive(Clone)]
+pub(crate) struct EventBus {
+    pool: Pool<Postgres>,
+}
+
+impl EventBus {
+    pub(crate) fn new(pool: Pool<Postgres>) -> Self {
+        EventBus { pool }
+    }
+
+    /// Notifies all webhook targets subscribed to the given event. Delivery happens in the
+    /// background, so this should only be called after the transaction causing the event has been
+    /// committed.
+    pub(crate) fn publish(&self, event: Event<'_>) {
+        let rendered = match RenderedEvent::render(&event) {
+            Ok(rendered) => rendered,
+            Err(err) => return error!("INTERNAL SERVER ERROR: Failed to serialize {} event: {:?}", event.event_type(), err),
+        };
+
+        let pool = self.pool.clone();
+
+        tokio::spawn(async move {
+            if let Err(err) = rendered.enqueue_deliveries(&pool).await {
+                error!(
+                    "INTERNAL SERVER ERROR: Failed to enqueue deliveries of {} event: {:?}",
+                    rendered.event_type, err
+                )
+            }
+        });
+    }
+}
+
+/// Job for delivering an event to a single webhook target
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct DeliverWebhook {
+    target: i32,
+    event: EventType,
+    payload: String,
+}
+
+impl Job for DeliverWebhook {
+    const KIND: &'static str = "deliver_webhook";
+
+    fn max_attempts(&self) -> i16 {
+        MAX_ATTEMPTS
+    }
+}
+
+/// An event, already rendered into all supported [`PayloadFormat`]s
+struct RenderedEvent {
+    event_type: EventType,
+    json: String,
+    discord: String,
+    slack: String,
+}
+
+impl RenderedEvent {
+    fn render(event: &Event<'_>) -> serde_json::Result<Self> {
+        let (title, description, url) = summarize(event);
+
+        let slack_text = match url {
+            Some(ref url) => format!("*{}*\n{}\n<{}>", title, description, url),
+            None => format!("*{}*\n{}", title, description),
+        };
+
+        Ok(RenderedEvent {
+            event_type: event.event_type(),
+            json: serde_json::to_string(event)?,
+            discord: json!({
+                "embeds": [{
+                    "type": "rich",
+                    "title": title,
+                    "description": description,
+                    "url": url,
+                }]
+            })
+            .to_string(),
+            slack: json!({ "text": slack_text }).to_string(),
+        })
+    }
+
+    fn payload(&self, format: PayloadFormat) -> &str {
+        match format {
+            PayloadFormat::Json => &self.json,
+            PayloadFormat::Discord => &self.discord,
+            PayloadFormat::Slack => &self.slack,
+        }
+    }
+
+    async fn enqueue_deliveries(&self, pool: &Pool<Postgres>) -> Result<(), DemonlistError> {
+        let mut connection = pool.acquire().await?;
+
+        for target in WebhookTarget::subscribed_to(self.event_type, &mut connection).await? {
+            let delivery = DeliverWebhook {
+                target: target.id,
+                event: self.event_type,
+                payload: self.payload(target.format).to_string(),
+            };
+
+            job::enqueue(&delivery, &mut connection).await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A human readable title, description and optional link for the given event, used by the chat
+/// message formats
+fn summarize(event: &Event<'_>) -> (String, String, Option<String>) {
+    match event {
+        Event::RecordSubmitted(record) => (
+            format!("New record submitted! ID: {}", record.id),
+            format!(
+                "{} just got {}% on {}! Go add their record!",
+                record.player.name, record.progress, record.demon.name
+            ),
+            record.video.clone(),
+        ),
+        Event::RecordApproved(record) => (
+            format!("Record approved! ID: {}", record.id),
+            format!(
+                "{}'s {}% record on {} has been approved",
+                record.player.name, record.progress, record.demon.name
+            ),
+            record.video.clone(),
+        ),
+        Event::RecordRejected(record) => (
+            format!("Record rejected! ID: {}", record.id),
+            format!(
+                "{}'s {}% record on {} has been rejected",
+                record.player.name, record.progress, record.demon.name
+            ),
+            record.video.clone(),
+        ),
+        Event::DemonAdded(demon) => (
+            format!("{} added!", demon.demon.base.name),
+            format!(
+                "{} has been placed at position {}, verified by {} and published by {}",
+                demon.demon.base.name, demon.demon.base.position, demon.demon.verifier.name, demon.demon.publisher.name
+            ),
+            demon.demon.video.clone(),
+        ),
+        Event::DemonMoved { demon, from } => (
+            format!("{} moved!", demon.demon.base.name),
+            format!(
+                "{} has been moved from position {} to position {}",
+                demon.demon.base.name, from, demon.demon.base.position
+            ),
+            demon.demon.video.clone(),
+        ),
+        Event::ClaimVerified { claim, player } => (
+            format!("Claim on {} verified!", player.name),
+            format!("The claim of user #{} on player {} has been verified", claim.user_id, player),
+            None,
+        ),
+        Event::PlayerBanned(player) => (format!("{} banned!", player.name), format!("{} has been banned", player), None),
+    }
+}
+
+fn sign(secret: &str, payload: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC can take keys of any size");
+
+    mac.update(payload.as_bytes());
+
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Runs a [`DeliverWebhook`] job, failing if the target did not respond with a success status code
+pub(crate) async fn deliver(job: DeliverWebhook, attempt: i16, pool: Pool<Postgres>, client: reqwest::Client) -> Result<(), String> {
+    let mut connection = pool.acquire().await.map_err(|err| err.to_string())?;
+
+    let target = match WebhookTarget::by_id(job.target, &mut connection).await {
+        Ok(target) => target,
+        // The target was deleted after the event was published
+        Err(DemonlistError::WebhookTargetNotFound { .. }) => return Ok(()),
+        Err(err) => return Err(err.to_string()),
+    };
+
+    let result = client
+        .post(&target.url)
+        .header("Content-Type", "application/json")
+        .header("X-Pointercrate-Event", job.event.to_string())
+        .header("X-Pointercrate-Signature", format!("sha256={}", sign(&target.secret, &job.payload)))
+        .body(job.payload.clone())
+        .send()
+        .await;
+
+    let (status_code, error) = match result {
+        Ok(response) if response.status().is_success() => (Some(response.status().as_u16() as i16), None),
+        Ok(response) => (
+            Some(response.status().as_u16() as i16),
+            Some(format!("Webhook target responded with {}", response.status())),
+        ),
+        Err(err) => (None, Some(err.to_string())),
+    };
+
+    if let Some(ref error) = error {
+        warn!(
+            "Failed to deliver {} event to webhook target {} (attempt {}/{}): {}",
+            job.event, target, attempt, MAX_ATTEMPTS, error
+        );
+    }
+
+    if let Err(err) = WebhookDelivery::log(
+        target.id,
+        job.event,
+        attempt,
+        status_code,
+        error.clone(),
+        &job.payload,
+        &mut connection,
+    )
+    .await
+    {
+        error!("INTERNAL SERVER ERROR: Failed to log webhook delivery: {:?}", err);
+    }
+
+    match error {
+        Some(error) => Err(error),
+        None => {
+            debug!("Successfully delivered {} event to webhook target {}", job.event, target);
+
+            Ok(())
+        },
+    }
+}
+
+/// Registers the Discord webhook configured via `discord_webhook` in the `[integrations]` table (if any)
+/// as a webhook target for record submissions, so that setups predating webhook targets keep working
+pub(crate) async fn register_legacy_webhook(rocket: Rocket<Build>) -> rocket::fairing::Result {
+    let url = match crate::config::submission_webhook() {
+        Ok(Some(url)) => url,
+        Ok(None) => return Ok(rocket),
+        Err(err) => {
+            error!("Cannot register discord webhook: {}", err);
+
+            return Err(rocket);
+        },
+    };
+
+    let Some(pool) = rocket.state::<PointercratePool>() else {
+        error!("No database pool, cannot register discord webhook");
+
+        return Err(rocket);
+    };
+
+    let result: Result<(), DemonlistError> = async {
+        let mut connection = pool.connection().await?;
+
+        if WebhookTarget::all(&mut connection).await?.iter().any(|target| target.url == url) {
+            return Ok(());
+        }
+
+        let target = WebhookTarget::create_from(
+            PostWebhookTarget {
+                url: url.to_string(),
+                format: PayloadFormat::Discord,
+                events: vec![EventType::RecordSubmitted],
+                secret: None,
+            },
+            &mut connection,
+        )
+        .await?;
+
+        info!("Registered DISCORD_WEBHOOK as webhook target {}", target);
+
+        Ok(())
+    }
+    .await;
+
+    match result {
+        Ok(()) => Ok(rocket),
+        Err(err) => {
+            error!("Failed to register discord webhook: {:?}", err);
+
+            Err(rocket)
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::sign;
+
+    #[test]
+    fn test_signature() {
+        // Test case 2 from RFC 4231
+        assert_eq!(
+            sign("Jefe", "what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+}
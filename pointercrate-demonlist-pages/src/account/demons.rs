@@ -3,17 +3,38 @@ use maud::{html, Markup, PreEscaped};
 use pointercrate_core::{localization::tr, permission::PermissionsManager};
 use pointercrate_core_pages::trp_html;
 use pointercrate_core_pages::util::filtered_paginator;
-use pointercrate_demonlist::LIST_MODERATOR;
+use pointercrate_demonlist::list::ListPermissions;
 use pointercrate_user::auth::{AuthenticatedUser, NonMutating};
 use pointercrate_user_pages::account::AccountPageTab;
 use sqlx::PgConnection;
 
-pub struct DemonsTab;
+/// Tab for managing the demons of a single list, displayed to that list's moderators
+pub struct DemonsTab {
+    /// Where the list's demon endpoints are mounted
+    api_base: &'static str,
+    permissions: ListPermissions,
+}
+
+impl DemonsTab {
+    /// Constructs a tab managing the demons of the list whose endpoints are mounted at the given base
+    /// path
+    pub fn for_list(api_base: &'static str, permissions: ListPermissions) -> Self {
+        DemonsTab { api_base, permissions }
+    }
+}
+
+impl Default for DemonsTab {
+    fn default() -> Self {
+        DemonsTab::for_list("/api/v2/demons/", ListPermissions::default())
+    }
+}
 
 #[async_trait::async_trait]
 impl AccountPageTab for DemonsTab {
     fn should_display_for(&self, permissions_we_have: u16, permissions: &PermissionsManager) -> bool {
-        permissions.require_permission(permissions_we_have, LIST_MODERATOR).is_ok()
+        permissions
+            .require_permission(permissions_we_have, self.permissions.moderator)
+            .is_ok()
     }
 
     fn initialization_script(&self) -> String {
@@ -40,12 +61,12 @@ impl AccountPageTab for DemonsTab {
         html! {
             div.left {
                 (demon_submitter())
-                div.panel.fade {
+                div.panel.fade #demon-manager data-api-base = (self.api_base) {
                     h2.underlined.pad {
                         (tr("demon-manager"))
                     }
                     div.flex.viewer {
-                        (filtered_paginator("demon-pagination", "/api/v2/demons/listed/"))
+                        (filtered_paginator("demon-pagination", &format!("{}listed/", self.api_base)))
                         p.viewer-welcome {
                             (tr("demon-viewer.welcome"))
                         }
@@ -6,22 +6,41 @@ use maud::{html, Markup, PreEscaped};
 use pointercrate_core::{error::PointercrateError, localization::tr, permission::PermissionsManager, trp};
 use pointercrate_core_pages::{
     error::ErrorFragment,
-    util::{dropdown, paginator},
+    util::{dropdown, paginator, simple_dropdown},
 };
 use pointercrate_demonlist::{
     demon::{current_list, Demon},
-    LIST_HELPER,
+    error::DemonlistError,
+    list::{List, ListPermissions, DEFAULT_LIST},
+    record::RejectionReason,
 };
 use pointercrate_user::auth::{AuthenticatedUser, NonMutating};
 use pointercrate_user_pages::account::AccountPageTab;
 use sqlx::PgConnection;
 
-pub struct RecordsPage;
+/// Tab for reviewing and managing the records of a single list, displayed to that list's helpers
+pub struct RecordsPage {
+    list: i32,
+    permissions: ListPermissions,
+}
+
+impl RecordsPage {
+    /// Constructs a tab managing the records of the list with the given id and permissions
+    pub fn for_list(list: i32, permissions: ListPermissions) -> Self {
+        RecordsPage { list, permissions }
+    }
+}
+
+impl Default for RecordsPage {
+    fn default() -> Self {
+        RecordsPage::for_list(DEFAULT_LIST, ListPermissions::default())
+    }
+}
 
 #[async_trait::async_trait]
 impl AccountPageTab for RecordsPage {
     fn should_display_for(&self, permissions_we_have: u16, permissions: &PermissionsManager) -> bool {
-        permissions.require_permission(permissions_we_have, LIST_HELPER).is_ok()
+        permissions.require_permission(permissions_we_have, self.permissions.helper).is_ok()
     }
 
     fn initialization_script(&self) -> String {
@@ -45,8 +64,15 @@ impl AccountPageTab for RecordsPage {
     async fn content(
         &self, _user: &AuthenticatedUser<NonMutating>, _permissions: &PermissionsManager, connection: &mut PgConnection,
     ) -> Markup {
-        let demons = match current_list(connection).await {
-            Ok(demons) => demons,
+        let list_and_demons = async {
+            Ok::<_, DemonlistError>((
+                List::by_id(self.list, &mut *connection).await?,
+                current_list(self.list, &mut *connection).await?,
+            ))
+        };
+
+        let (list, demons) = match list_and_demons.await {
+            Ok(list_and_demons) => list_and_demons,
             Err(err) => {
                 return ErrorFragment {
                     status: err.status_code(),
@@ -59,8 +85,8 @@ impl AccountPageTab for RecordsPage {
 
         html! {
             div.left {
-                (RecordSubmitter::new(false, &demons[..]))
-                (record_manager(&demons[..]))
+                (RecordSubmitter::new(false, &list, &demons[..]))
+                (record_manager(list.id, &demons[..]))
                 (note_adder())
                 div.panel.fade #record-notes-container style = "display:none" {
                     div.white.hover.clickable #add-record-note-open {
@@ -71,6 +97,7 @@ impl AccountPageTab for RecordsPage {
                 (manager_help())
             }
             div.right {
+                (review_queue())
                 (status_selector())
                 (record_selector())
                 (player_selector())
@@ -78,15 +105,17 @@ impl AccountPageTab for RecordsPage {
             }
             (change_progress_dialog())
             (change_video_dialog())
+            (change_achieved_on_dialog())
             (change_holder_dialog())
             (change_demon_dialog(&demons[..]))
+            (rejection_reason_dialog())
         }
     }
 }
 
-fn record_manager(demons: &[Demon]) -> Markup {
+fn record_manager(list_id: i32, demons: &[Demon]) -> Markup {
     html! {
-        div.panel.fade #record-manager {
+        div.panel.fade #record-manager data-list = (list_id) {
             h2.underlined.pad {
                 (tr("record-manager")) " - "
                 (dropdown("All", html! {
@@ -172,6 +201,51 @@ fn record_manager(demons: &[Demon]) -> Markup {
                                 span #record-submitter {}
                             }
                         }
+                        div.stats-container.flex.space {
+                            span {
+                                b {
+                                    (tr("record-claim"))
+                                }
+                                br;
+                                span #record-claim {}
+                            }
+                            span {
+                                span.button.blue.hover.small #record-claim-button {(tr("record-claim.claim"))}
+                                span.button.red.hover.small #record-release-button {(tr("record-claim.release"))}
+                            }
+                        }
+                        div.stats-container.flex.space {
+                            span {
+                                b {
+                                    i.fa.fa-pencil-alt.clickable #record-achieved-on-pen aria-hidden = "true" {} " " (tr("record-achieved-on"))
+                                }
+                                br;
+                                span #record-achieved-on {}
+                            }
+                            span {
+                                b {
+                                    (tr("record-submitted-at"))
+                                }
+                                br;
+                                span #record-submitted-at {}
+                            }
+                        }
+                        div.stats-container.flex.space {
+                            span {
+                                b {
+                                    i.fa.fa-pencil-alt.clickable #record-rejection-reason-pen aria-hidden = "true" {} " " (tr("record-rejection-reason"))
+                                }
+                                br;
+                                span #record-rejection-reason {}
+                            }
+                            span {
+                                b {
+                                    (tr("record-approved-at"))
+                                }
+                                br;
+                                span #record-approved-at {}
+                            }
+                        }
                         span.button.red.hover #record-delete style = "margin: 15px auto 0px" {(tr("record-viewer.delete"))};
                     }
                 }
@@ -219,6 +293,37 @@ fn manager_help() -> Markup {
     }
 }
 
+fn review_queue() -> Markup {
+    html! {
+        div.panel.fade #review-queue-panel style = "overflow: visible" {
+            h2.underlined.pad {
+                (tr("review-queue-panel"))
+            }
+            p {
+                (tr("review-queue-panel.info"))
+            }
+            (simple_dropdown(
+                "review-queue-sort",
+                Some(("age", tr("review-queue-panel.sort-age"))),
+                vec![
+                    ("position", tr("review-queue-panel.sort-position")),
+                    ("trust", tr("review-queue-panel.sort-trust")),
+                ]
+                .into_iter()
+            ))
+            (paginator("review-queue-pagination", "/api/v1/records/queue/"))
+            h3 {
+                (tr("review-queue-panel.reviewers"))
+            }
+            ul #review-queue-reviewers {} // populated by javascript
+            h3 {
+                (tr("record-rejection-reason.stats"))
+            }
+            ul #rejection-reason-stats {} // populated by javascript
+        }
+    }
+}
+
 fn status_selector() -> Markup {
     // FIXME: no vec
     let dropdown_items = vec![
@@ -378,6 +483,32 @@ fn change_video_dialog() -> Markup {
     }
 }
 
+fn change_achieved_on_dialog() -> Markup {
+    html! {
+        div.overlay.closable {
+            div.dialog #record-achieved-on-dialog {
+                span.plus.cross.hover {}
+                h2.underlined.pad {
+                    (tr("record-achieved-on-dialog"))
+                }
+                p style = "max-width: 400px"{
+                    (tr("record-achieved-on-dialog.info"))
+                }
+                form.flex.col novalidate = "" {
+                    p.info-red.output {}
+                    p.info-green.output {}
+                    span.form-input #record-achieved-on-edit {
+                        label for = "achieved_on" {(tr("record-achieved-on-dialog.achieved-on-field")) }
+                        input name = "achieved_on" type = "date";
+                        p.error {}
+                    }
+                    input.button.blue.hover type = "submit" style = "margin: 15px auto 0px;" value = (tr("record-achieved-on-dialog.submit"));
+                }
+            }
+        }
+    }
+}
+
 fn change_holder_dialog() -> Markup {
     player_selection_dialog(
         "record-holder-dialog",
@@ -407,3 +538,27 @@ fn change_demon_dialog(demons: &[Demon]) -> Markup {
         }
     }
 }
+
+fn rejection_reason_dialog() -> Markup {
+    html! {
+        div.overlay.closable {
+            div.dialog #record-rejection-reason-dialog style="overflow: initial;" {
+                span.plus.cross.hover {}
+                h2.underlined.pad {
+                    (tr("record-rejection-reason-dialog"))
+                }
+                div.flex.col {
+                    p {
+                        (tr("record-rejection-reason-dialog.info"))
+                    }
+                    (simple_dropdown(
+                        "edit-rejection-reason-record",
+                        None,
+                        // A configuration error was already logged when converting it to a DemonlistError
+                        RejectionReason::all().unwrap_or_default().into_iter().map(|reason| (reason.key, reason.description))
+                    ))
+                }
+            }
+        }
+    }
+}
@@ -2,17 +2,19 @@ use crate::components::{demon_dropdown, player_selection_dropdown};
 use maud::{html, Markup, Render};
 use pointercrate_core::{localization::tr, trp};
 use pointercrate_core_pages::trp_html;
-use pointercrate_demonlist::{config, demon::Demon};
+use pointercrate_demonlist::{demon::Demon, list::List};
 
 pub struct RecordSubmitter<'a> {
     initially_visible: bool,
+    list: &'a List,
     demons: &'a [Demon],
 }
 
 impl<'a> RecordSubmitter<'a> {
-    pub fn new(visible: bool, demons: &'a [Demon]) -> RecordSubmitter<'a> {
+    pub fn new(visible: bool, list: &'a List, demons: &'a [Demon]) -> RecordSubmitter<'a> {
         RecordSubmitter {
             initially_visible: visible,
+            list,
             demons,
         }
     }
@@ -33,10 +35,10 @@ impl Render for RecordSubmitter<'_> {
                         (tr("record-submission.demon"))
                     }
                     p {
-                        (trp!("record-submission.demon-info", "list-size" = config::extended_list_size()))
+                        (trp!("record-submission.demon-info", "list-size" = self.list.extended_list_size))
                     }
                     span.form-input data-type = "dropdown" {
-                        (demon_dropdown("id_demon", self.demons.iter().filter(|demon| demon.base.position <= config::extended_list_size())))
+                        (demon_dropdown("id_demon", self.demons.iter().filter(|demon| demon.base.position <= self.list.extended_list_size)))
                         p.error {}
                     }
                     h3 {
@@ -59,6 +61,16 @@ impl Render for RecordSubmitter<'_> {
                         input type = "number" name = "progress" required="" placeholder = (tr("record-submission.progress-placeholder")) min="0" max="100";
                         p.error {}
                     }
+                    h3 {
+                        (tr("record-submission.achieved-on"))
+                    }
+                    p {
+                        (tr("record-submission.achieved-on-info"))
+                    }
+                    span.form-input.flex.col #id_achieved_on {
+                        input type = "date" name = "achieved_on";
+                        p.error {}
+                    }
                     h3 {
                         (tr("record-submission.video"))
                     }
@@ -109,6 +121,15 @@ impl Render for RecordSubmitter<'_> {
                     }
                     input.button.blue.hover type = "submit" style = "margin: 15px auto 0px;" value=(tr("record-submission.submit"));
                 }
+                div #tracked-submissions style = "display: none" {
+                    h3 {
+                        (tr("record-submission-tracked"))
+                    }
+                    p {
+                        (tr("record-submission-tracked.info"))
+                    }
+                    ul #tracked-submissions-list {}
+                }
             }
         }
     }
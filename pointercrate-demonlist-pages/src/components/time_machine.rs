@@ -63,7 +63,7 @@ impl Render for Tardis {
                                 }
                             }
                         }
-                        a.white.button href = "/demonlist/" onclick=r#"document.cookie = "when=""# style = "margin-left: 15px"{ b{ (tr("time-machine.return")) }}
+                        a.white.button href = "." onclick=r#"document.cookie = "when=""# style = "margin-left: 15px"{ b{ (tr("time-machine.return")) }}
                     }
                 },
                 _ => {}
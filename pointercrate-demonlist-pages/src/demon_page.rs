@@ -5,17 +5,17 @@ use crate::{
         team::Team,
     },
     statsviewer::stats_viewer_panel,
+    ListContext,
 };
 use chrono::NaiveDateTime;
 use maud::{html, Markup, PreEscaped};
 use pointercrate_core::{localization::tr, trp};
 use pointercrate_core_pages::{head::HeadLike, trp_html, PageFragment};
 use pointercrate_demonlist::{
-    config::{self as list_config, extended_list_size},
     demon::{Demon, FullDemon},
+    video::VideoHosts,
 };
 use pointercrate_integrate::gd::{DemonRating, IntegrationLevel, LevelRating, Thunk};
-use url::Url;
 
 #[derive(Debug)]
 pub struct DemonMovement {
@@ -23,16 +23,18 @@ pub struct DemonMovement {
     pub at: NaiveDateTime,
 }
 
-pub struct DemonPage {
+pub struct DemonPage<'a> {
+    pub list: ListContext,
     pub team: Team,
     pub demonlist: Vec<Demon>,
     pub data: FullDemon,
     pub movements: Vec<DemonMovement>,
     pub integration: Option<IntegrationLevel>,
+    pub video_hosts: &'a VideoHosts,
 }
 
-impl From<DemonPage> for PageFragment {
-    fn from(page: DemonPage) -> Self {
+impl From<DemonPage<'_>> for PageFragment {
+    fn from(page: DemonPage<'_>) -> Self {
         PageFragment::new(page.title(), page.description())
             .script("https://cdn.jsdelivr.net/chartist.js/latest/chartist.min.js")
             .module("/static/core/js/modules/form.js")
@@ -46,14 +48,14 @@ impl From<DemonPage> for PageFragment {
     }
 }
 
-impl DemonPage {
+impl DemonPage<'_> {
     fn title(&self) -> String {
         let mut title = format!(
             "{} - Geometry Dash Demonlist",
             self.data.demon.base.name // FIXME: flatten the structs, holy shit
         );
 
-        if self.data.demon.base.position <= extended_list_size() {
+        if self.data.demon.base.position <= self.list.list.extended_list_size {
             title = format!("#{} - {}", self.data.demon.base.position, title);
         }
 
@@ -107,18 +109,17 @@ impl DemonPage {
                 }
                 </script>
             "##))
+            (self.list.script())
             (PreEscaped(format!("
                 <script>
-                    window.list_length = {0};
-                    window.extended_list_length = {1};
-                    window.demon_id = {2};
-                </script>", list_config::list_size(), list_config::extended_list_size(), self.data.demon.base.id
+                    window.demon_id = {0};
+                </script>", self.data.demon.base.id
             )))
         }
     }
 
     fn body(&self) -> Markup {
-        let dropdowns = super::dropdowns(&self.demonlist.iter().collect::<Vec<_>>()[..], Some(&self.data.demon));
+        let dropdowns = super::dropdowns(&self.list, &self.demonlist.iter().collect::<Vec<_>>()[..], Some(&self.data.demon));
 
         let mut labels = Vec::new();
 
@@ -150,7 +151,7 @@ impl DemonPage {
 
             div.flex.m-center.container {
                 main.left {
-                    (RecordSubmitter::new(false, &self.demonlist))
+                    (RecordSubmitter::new(false, &self.list.list, &self.demonlist))
                     (self.demon_panel())
                     div.panel.fade.js-scroll-anim.js-collapse data-anim = "fade" {
                         h2.underlined.pad {
@@ -194,7 +195,7 @@ impl DemonPage {
                     (self.team)
                     (super::rules_panel())
                     (submit_panel())
-                    (stats_viewer_panel())
+                    (stats_viewer_panel(&self.list))
                     (super::discord_panel())
                 }
             }
@@ -229,22 +230,22 @@ impl DemonPage {
                 div.underlined {
                     h1 #demon-heading style = "overflow: hidden"{
                         @if self.data.demon.base.position != 1 {
-                            a href=(format!("/demonlist/{:?}", self.data.demon.base.position - 1)) {
+                            a href=(format!("{}{:?}", self.list.pages_base, self.data.demon.base.position - 1)) {
                                 i class="fa fa-chevron-left" style="padding-right: 5%" {}
                             }
                         }
                         (name)
                         @if position as usize != self.demonlist.len() {
-                            a href=(format!("/demonlist/{:?}", position + 1)) {
+                            a href=(format!("{}{:?}", self.list.pages_base, position + 1)) {
                                 i class="fa fa-chevron-right" style="padding-left: 5%" {}
                             }
                         }
                     }
                     (PreEscaped(format!(r#"
                     <script>
-                    document.getElementById("demon-heading").addEventListener('click', () => navigator.clipboard.writeText('https://pointercrate.com/demonlist/permalink/{}/?redirect'))
+                    document.getElementById("demon-heading").addEventListener('click', () => navigator.clipboard.writeText('https://pointercrate.com{}permalink/{}/?redirect'))
                     </script>
-                    "#, self.data.demon.base.id)))
+                    "#, self.list.pages_base, self.data.demon.base.id)))
                     h3 {
                         @match &self.data.creators[..] {
                             [] => { (trp_html!(
@@ -313,7 +314,7 @@ impl DemonPage {
                     }
                 }
                 @if let Some(ref video) = self.data.demon.video {
-                    @if let Some(embedded_video) = embed(video) {
+                    @if let Some(embedded_video) = self.video_hosts.embed_url(video) {
                         iframe."ratio-16-9"."js-delay-attr" style="width:90%; margin: 15px 5%" allowfullscreen="" data-attr = "src" data-attr-value = (embedded_video) {"Verification Video"}
                     }
                 }
@@ -398,7 +399,7 @@ impl DemonPage {
                             }
                         }
                     }
-                    @if position <= list_config::extended_list_size() {
+                    @if position <= self.list.list.extended_list_size {
                         span {
                             b {
                                 (trp!("demon-score", "percent" = 100.0))
@@ -407,7 +408,7 @@ impl DemonPage {
                             (format!("{:.2}", score100))
                         }
                     }
-                    @if position <= list_config::list_size(){
+                    @if position <= self.list.list.list_size{
                         span {
                             b {
                                 (trp!("demon-score", "percent" = self.data.demon.requirement))
@@ -426,18 +427,18 @@ impl DemonPage {
         let _name = &self.data.demon.base.name;
 
         html! {
-            @if !self.data.records.is_empty() || position <= list_config::extended_list_size() {
+            @if !self.data.records.is_empty() || position <= self.list.list.extended_list_size {
                 section.records.panel.fade.js-scroll-anim data-anim = "fade" {
                     div.underlined.pad {
                         h2 {
                             (tr("demon-records"))
                         }
-                        @if position <= list_config::list_size() {
+                        @if position <= self.list.list.list_size {
                             h3 {
                                 (trp!("demon-records-qualify", "percent" = self.data.demon.requirement))
                             }
                         }
-                        @else if position <= list_config::extended_list_size() {
+                        @else if position <= self.list.list.extended_list_size {
                             h3 {
                                 (trp!("demon-records-qualify", "percent" = 100.0))
                             }
@@ -451,7 +452,7 @@ impl DemonPage {
                     }
                     @if self.data.records.is_empty() {
                         h3 {
-                            @if position > list_config::extended_list_size() {
+                            @if position > self.list.list.extended_list_size {
                                 (tr("demon-records.none"))
                             }
                             @else {
@@ -496,7 +497,7 @@ impl DemonPage {
                                         td.video-link {
                                             @if let Some(ref video) = record.video {
                                                  a.link href = (video) target = "_blank"{
-                                                     (host(video))
+                                                     (self.video_hosts.host_name(video))
                                                  }
                                             }
                                         }
@@ -510,38 +511,3 @@ impl DemonPage {
         }
     }
 }
-
-fn host(video: &str) -> &str {
-    match Url::parse(video).unwrap().domain().unwrap() {
-        "www.youtube.com" => "YouTube",
-        "www.twitch.tv" => "Twitch",
-        "everyplay.com" => "Everyplay",
-        "www.bilibili.com" => "Bilibili",
-        "vimeo.com" => "Vimeo",
-        host => panic!("{}", host),
-    }
-}
-
-fn embed(video: &str) -> Option<String> {
-    // Video URLs need to be wellformed once we get here!
-    let url = Url::parse(video).unwrap();
-
-    match url.domain()? {
-        "www.youtube.com" => {
-            let video_id = url
-                .query_pairs()
-                .find_map(|(key, value)| if key == "v" { Some(value) } else { None })?;
-
-            Some(format!("https://www.youtube.com/embed/{}", video_id))
-        },
-        "www.twitch.tv" => {
-            // per validation always of the form 'https://www.twitch.tv/videos/[video id]/'
-            let mut url_segment = url.path_segments()?;
-            url_segment.next()?;
-            let video_id = url_segment.next()?;
-
-            Some(format!("https://player.twitch.tv/?video={}&autoplay=false", video_id))
-        },
-        _ => None,
-    }
-}
@@ -1,7 +1,7 @@
-use maud::{html, Markup};
+use maud::{html, Markup, PreEscaped};
 
 use pointercrate_core::localization::tr;
-use pointercrate_demonlist::{config, demon::Demon};
+use pointercrate_demonlist::{demon::Demon, list::List};
 
 pub mod account;
 pub mod components;
@@ -9,6 +9,36 @@ pub mod demon_page;
 pub mod overview;
 pub mod statsviewer;
 
+/// The list whose pages are being rendered, together with the paths its pages and demon endpoints
+/// are mounted at
+#[derive(Debug, Clone)]
+pub struct ListContext {
+    pub list: List,
+
+    /// Base path of the list's pages, e.g. `/demonlist/`
+    pub pages_base: &'static str,
+
+    /// Base path of the list's demon endpoints, e.g. `/api/v2/demons/`
+    pub api_base: &'static str,
+}
+
+impl ListContext {
+    /// Script tag telling the frontend which list it is displaying
+    fn script(&self) -> Markup {
+        PreEscaped(format!(
+            r#"
+                <script>
+                    window.list_id = {};
+                    window.list_base = "{}";
+                    window.list_api_base = "{}";
+                    window.list_length = {};
+                    window.extended_list_length = {};
+                </script>"#,
+            self.list.id, self.pages_base, self.api_base, self.list.list_size, self.list.extended_list_size
+        ))
+    }
+}
+
 struct ListSection {
     name: String,
     description: String,
@@ -16,38 +46,38 @@ struct ListSection {
     numbered: bool,
 }
 
-fn dropdowns(all_demons: &[&Demon], current: Option<&Demon>) -> Markup {
-    let (main, extended, legacy) = if all_demons.len() < config::list_size() as usize {
+fn dropdowns(list: &ListContext, all_demons: &[&Demon], current: Option<&Demon>) -> Markup {
+    let list_size = list.list.list_size as usize;
+    let extended_list_size = list.list.extended_list_size as usize;
+
+    let (main, extended, legacy) = if all_demons.len() < list_size {
         (all_demons, Default::default(), Default::default())
     } else {
-        let (extended, legacy) = if all_demons.len() < config::extended_list_size() as usize {
-            (&all_demons[config::list_size() as usize..], Default::default())
+        let (extended, legacy) = if all_demons.len() < extended_list_size {
+            (&all_demons[list_size..], Default::default())
         } else {
-            (
-                &all_demons[config::list_size() as usize..config::extended_list_size() as usize],
-                &all_demons[config::extended_list_size() as usize..],
-            )
+            (&all_demons[list_size..extended_list_size], &all_demons[extended_list_size..])
         };
 
-        (&all_demons[..config::list_size() as usize], extended, legacy)
+        (&all_demons[..list_size], extended, legacy)
     };
 
     html! {
         nav.flex.wrap.m-center.fade #lists style="text-align: center;" {
             // The drop down for the main list:
-            (dropdown(&ListSection { name: tr("main-list"), description: tr("main-list.info"), id: "mainlist", numbered: true }, main, current))
+            (dropdown(list, &ListSection { name: tr("main-list"), description: tr("main-list.info"), id: "mainlist", numbered: true }, main, current))
             // The drop down for the extended list:
-            (dropdown(&ListSection { name: tr("extended-list"), description: tr("extended-list.info"), id: "extended", numbered: true }, extended, current))
+            (dropdown(list, &ListSection { name: tr("extended-list"), description: tr("extended-list.info"), id: "extended", numbered: true }, extended, current))
             // The drop down for the legacy list:
-            (dropdown(&ListSection { name: tr("legacy-list"), description: tr("legacy-list.info"), id: "legacy", numbered: false }, legacy, current))
+            (dropdown(list, &ListSection { name: tr("legacy-list"), description: tr("legacy-list.info"), id: "legacy", numbered: false }, legacy, current))
         }
     }
 }
 
-fn dropdown(section: &ListSection, demons: &[&Demon], current: Option<&Demon>) -> Markup {
+fn dropdown(list: &ListContext, section: &ListSection, demons: &[&Demon], current: Option<&Demon>) -> Markup {
     let format = |demon: &Demon| -> Markup {
         html! {
-            a href = {"/demonlist/permalink/" (demon.base.id) "/"} {
+            a href = {(list.pages_base) "permalink/" (demon.base.id) "/"} {
                 @if section.numbered {
                     {"#" (demon.base.position) " - " (demon.base.name)}
                     br ;
@@ -6,17 +6,16 @@ use crate::{
         time_machine::Tardis,
     },
     statsviewer::stats_viewer_panel,
+    ListContext,
 };
 use maud::{html, Markup, PreEscaped};
 use pointercrate_core::{localization::tr, trp};
 use pointercrate_core_pages::{head::HeadLike, trp_html, PageFragment};
+use pointercrate_demonlist::demon::{Demon, TimeShiftedDemon};
 use pointercrate_demonlist::player::FullPlayer;
-use pointercrate_demonlist::{
-    config as list_config, config,
-    demon::{Demon, TimeShiftedDemon},
-};
 
 pub struct OverviewPage {
+    pub list: ListContext,
     pub team: Team,
     pub demonlist: Vec<Demon>,
     pub time_machine: Tardis,
@@ -72,12 +71,7 @@ impl OverviewPage {
                 }
                 </script>
             "#))
-            (PreEscaped(format!("
-                <script>
-                    window.list_length = {0};
-                    window.extended_list_length = {1}
-                </script>", list_config::list_size(), list_config::extended_list_size())
-            ))
+            (self.list.script())
             // FIXME: abstract away
             link ref = "canonical" href = "https://pointercrate.com/demonlist/";
         }
@@ -89,7 +83,7 @@ impl OverviewPage {
             _ => self.demonlist.iter().collect(),
         };
 
-        let dropdowns = super::dropdowns(&demons_for_dropdown[..], None);
+        let dropdowns = super::dropdowns(&self.list, &demons_for_dropdown[..], None);
 
         html! {
             (dropdowns)
@@ -97,19 +91,19 @@ impl OverviewPage {
             div.flex.m-center.container {
                 main.left {
                     (self.time_machine)
-                    (RecordSubmitter::new(self.submitter_initially_visible, &self.demonlist))
+                    (RecordSubmitter::new(self.submitter_initially_visible, &self.list.list, &self.demonlist))
 
                     @match &self.time_machine {
                         Tardis::Activated { demons, ..} => {
                             @for TimeShiftedDemon {current_demon, position_now} in demons {
-                                @if current_demon.base.position <= list_config::extended_list_size() {
+                                @if current_demon.base.position <= self.list.list.extended_list_size {
                                     (self.demon_panel(current_demon, Some(*position_now)))
                                 }
                             }
                         },
                         _ => {
                             @for demon in &self.demonlist {
-                                @if demon.base.position <= list_config::extended_list_size() {
+                                @if demon.base.position <= self.list.list.extended_list_size {
                                     (self.demon_panel(demon, None))
                                 }
                             }
@@ -121,7 +115,7 @@ impl OverviewPage {
                     (self.team)
                     (super::rules_panel())
                     (submit_panel())
-                    (stats_viewer_panel())
+                    (stats_viewer_panel(&self.list))
                     (super::discord_panel())
                 }
             }
@@ -153,7 +147,7 @@ impl OverviewPage {
                  div.flex.demon-info style = "align-items: center" {
                      div.demon-byline {
                          h2 style = "text-align: left; margin-bottom: 0px" {
-                             a href = {"/demonlist/permalink/" (demon.base.id) "/"} {
+                             a href = {(self.list.pages_base) "permalink/" (demon.base.id) "/"} {
                                  "#" (demon.base.position) (PreEscaped(" &#8211; ")) (demon.base.name)
                              }
                          }
@@ -165,7 +159,7 @@ impl OverviewPage {
                          }
                          div style="text-align: left; font-size: 0.8em" {
                             @if let Some(current_position) = current_position {
-                                 @if current_position > list_config::extended_list_size() {
+                                 @if current_position > self.list.list.extended_list_size {
                                      (tr("time-machine.active-position-legacy"))
                                  }
                                  @else {
@@ -176,7 +170,7 @@ impl OverviewPage {
                                  }
                             }
                             @else {
-                                @if demon.base.position > config::list_size() {
+                                @if demon.base.position > self.list.list.list_size {
                                     (trp!(
                                         "demon-info.score-short",
                                         "score" = total_score
@@ -1,4 +1,4 @@
-use crate::statsviewer::stats_viewer_html;
+use crate::{statsviewer::stats_viewer_html, ListContext};
 use maud::{html, Markup};
 use pointercrate_core::localization::tr;
 use pointercrate_core_pages::{head::HeadLike, trp_html, PageFragment};
@@ -6,6 +6,7 @@ use pointercrate_demonlist::nationality::Nationality;
 
 #[derive(Debug)]
 pub struct IndividualStatsViewer {
+    pub list: ListContext,
     pub nationalities_in_use: Vec<Nationality>,
 }
 
@@ -20,6 +21,7 @@ impl From<IndividualStatsViewer> for PageFragment {
         .module("/static/demonlist/js/statsviewer/individual.js")
         .stylesheet("/static/demonlist/css/statsviewer.css")
         .stylesheet("/static/core/css/sidebar.css")
+        .head(stats_viewer.list.script())
         .body(stats_viewer.body())
     }
 }
@@ -28,10 +30,10 @@ impl IndividualStatsViewer {
     fn body(&self) -> Markup {
         html! {
             nav.flex.wrap.m-center.fade #statsviewers style="text-align: center; z-index: 1" {
-                a.button.white.hover.no-shadow href="/demonlist/statsviewer/"{
+                a.button.white.hover.no-shadow href={(self.list.pages_base) "statsviewer/"} {
                     b {(tr("statsviewer-individual"))}
                 }
-                a.button.white.hover.no-shadow href="/demonlist/statsviewer/nations/" {
+                a.button.white.hover.no-shadow href={(self.list.pages_base) "statsviewer/nations/"} {
                     b {(tr("statsviewer-nation"))}
                 }
             }
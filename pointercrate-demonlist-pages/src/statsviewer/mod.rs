@@ -3,10 +3,12 @@ use pointercrate_core::localization::tr;
 use pointercrate_core_pages::util::{dropdown, filtered_paginator, simple_dropdown};
 use pointercrate_demonlist::nationality::Nationality;
 
+use crate::ListContext;
+
 pub mod individual;
 pub mod national;
 
-pub(crate) fn stats_viewer_panel() -> Markup {
+pub(crate) fn stats_viewer_panel(list: &ListContext) -> Markup {
     html! {
         section #stats.panel.fade.js-scroll-anim data-anim = "fade" {
             div.underlined {
@@ -17,7 +19,7 @@ pub(crate) fn stats_viewer_panel() -> Markup {
             p {
                 (tr("statsviewer-panel.info"))
             }
-            a.blue.hover.button #show-stats-viewer href = "/demonlist/statsviewer/ "{
+            a.blue.hover.button #show-stats-viewer href = {(list.pages_base) "statsviewer/"} {
                 (tr("statsviewer-panel.button"))
             }
         }
@@ -1,9 +1,12 @@
-use crate::statsviewer::{stats_viewer_html, StatsViewerRow};
+use crate::{
+    statsviewer::{stats_viewer_html, StatsViewerRow},
+    ListContext,
+};
 use maud::{html, Markup};
 use pointercrate_core::localization::tr;
 use pointercrate_core_pages::{head::HeadLike, PageFragment};
 
-pub fn nation_based_stats_viewer() -> PageFragment {
+pub fn nation_based_stats_viewer(list: &ListContext) -> PageFragment {
     PageFragment::new(
         "Nation Stats Viewer",
         "The pointercrate nation stats viewer, ranking how well each nation's players are doing in their quest to collectively complete \
@@ -13,10 +16,11 @@ pub fn nation_based_stats_viewer() -> PageFragment {
     .module("/static/demonlist/js/statsviewer/nation.js")
     .stylesheet("/static/demonlist/css/statsviewer.css")
     .stylesheet("/static/core/css/sidebar.css")
-    .body(nation_based_stats_viewer_html())
+    .head(list.script())
+    .body(nation_based_stats_viewer_html(list))
 }
 
-fn nation_based_stats_viewer_html() -> Markup {
+fn nation_based_stats_viewer_html(list: &ListContext) -> Markup {
     let mut rows = super::standard_stats_viewer_rows();
 
     rows[0].0.insert(1, (tr("statsviewer-nation.players"), "players"));
@@ -24,10 +28,10 @@ fn nation_based_stats_viewer_html() -> Markup {
 
     html! {
         nav.flex.wrap.m-center.fade #statsviewers style="text-align: center; z-index: 1" {
-            a.button.white.hover.no-shadow href="/demonlist/statsviewer/"{
+            a.button.white.hover.no-shadow href={(list.pages_base) "statsviewer/"} {
                 b {(tr("statsviewer-individual"))}
             }
-            a.button.white.hover.no-shadow href="/demonlist/statsviewer/nations/" {
+            a.button.white.hover.no-shadow href={(list.pages_base) "statsviewer/nations/"} {
                 b {(tr("statsviewer-nation"))}
             }
         }
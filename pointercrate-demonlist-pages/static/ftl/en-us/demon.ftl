@@ -86,6 +86,7 @@ movements-reason = Reason
     .moved = Moved
     .movedabove = { $demon } was moved up past this demon
     .movedbelow = { $demon } was moved down past this demon
+    .deletedabove = { $demon } was removed from the list
 
 ## Records table
 demon-records = Records
@@ -1,3 +1,4 @@
+error-demonlist-batchoperationfailed = Operation #{ $index } failed: { $error }
 error-demonlist-geolocationfailed = Geolocation failed!
 error-demonlist-malformedvideourl = Malformed video URL
 error-demonlist-bannedfromsubmissions = You are banned from submitting records to the demonlist!
@@ -16,10 +17,17 @@ error-demonlist-demonnotfoundname = No demon with name { $demon-name } found
 error-demonlist-demonnotfoundposition = No demon at position { $demon-position } found
 error-demonlist-recordnotfound = No record with id { $record-id } found
 error-demonlist-claimnotfound = No claim by user { $member-id } on player { $player-id } found
+error-demonlist-listnotfound = No list with id { $list-id } found
+error-demonlist-webhooktargetnotfound = No webhook target with id { $id } found
+error-demonlist-recordnotclaimed = No one is currently reviewing record #{ $record-id }
+error-demonlist-trackedsubmissionnotfound = No submission is tracked by the given token
 error-demonlist-creatorexists = This player is already registered as a creator on this demon
 error-demonlist-duplicatevideo = This video is already used by record #{ $record-id }
 error-demonlist-nonationset = Attempt to set subdivision without nation
 error-demonlist-conflictingclaims = The players '{ $player-1 }' and '{ $player-2 }' have verified claims by different pointercrate users
+error-demonlist-playerinuse = This player still has records, creator credits, or verified/published demons and cannot be deleted. Merge them into another player instead
+error-demonlist-importtargetnotempty = Archives can only be imported into an instance without any players, demons or records
+error-demonlist-recordclaimedbyother = Record #{ $record-id } is currently being reviewed by { $claimed-by }
 error-demonlist-invalidrequirement = Record requirement needs to be greater than -1 and smaller than 101
 error-demonlist-invalidposition = Demon position needs to be greater than or equal to 1 and smaller than or equal to { $maximal }
 error-demonlist-invalidprogress = Record progress must lie between { $requirement } and 100%!
@@ -34,6 +42,19 @@ error-demonlist-alreadyclaimed = This player already has a verified claim associ
 error-demonlist-rawrequired = Raw footage much be provided to submit this record
 error-demonlist-malformedrawurl = Raw footage needs to be a valid URL
 error-demonlist-invalidlevelid = Level ID needs to be positive
+error-demonlist-invalidwebhookurl = Webhook URL needs to be a valid http or https URL
+error-demonlist-malformedarchive = Line { $line } of the archive is not a valid archive entry, or the archive is incomplete
+error-demonlist-unsupportedarchiveversion = Archives of version { $version } are not supported
+error-demonlist-inconsistentarchive = The entry on line { $line } of the archive duplicates or references objects inconsistently
+error-demonlist-invalidarchivepositions = The demons of list { $list-id } in the archive do not have consecutive positions starting at 1
+error-demonlist-archivescoremismatch = The archived score of player { $player-id } on list { $list-id } does not match the archived records
+error-demonlist-recordalreadyreviewed = This record has already been reviewed
+error-demonlist-unknownrejectionreason = Unknown rejection reason "{ $reason }"
+error-demonlist-rejectionreasonrequired = A reason needs to be given when rejecting a record
+error-demonlist-recordnotrejected = Only rejected records can have a rejection reason
+error-demonlist-achievedinfuture = A record cannot have been achieved in the future
+error-demonlist-batchtoolarge = A batch can contain at most { $max-size } operations
+error-demonlist-duplicatebatchrecord = A batch can contain at most one operation on record { $record-id }
 
 error-demonlist-ratelimit-record-submit = You're submitting too many records too fast!
 error-demonlist-ratelimit-record-submit-global = Too many records are being submitted right now!
@@ -63,6 +63,7 @@ claim-records = Your claimed player's records
 
     .record-notes = Notes for record { $record-id }:
     .record-notes-none = No public notes on this record!
+    .rejection-reason = Rejection reason: { $reason }
 
 claim-manager = Manage Claims
     .info-a = Manage claims using the interface below. The list can be filtered by player and user using the panels on the right. Invalid claims should be deleted using the trash icon.
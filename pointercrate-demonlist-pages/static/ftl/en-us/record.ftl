@@ -12,6 +12,13 @@ record-holder = Record Holder
 record-progress = Progress
 record-submitter = Submitter ID
 
+# Descriptions of the default rejection reasons (see the rejection_reasons setting)
+rejection-reason-no-clicks = The clicks cannot be heard in the video
+rejection-reason-cheat-indicator = The video shows signs of hacks or other cheats being used
+rejection-reason-wrong-level-version = The record was achieved on a different version of the level than the one on the list
+rejection-reason-unclear-video = The video does not clearly show the completion
+rejection-reason-duplicate = This record was already submitted
+
 ## Records tab (user area)
 records = Records
 record-manager = Record Manager
@@ -44,6 +51,39 @@ record-note-listed = Record Note #{ $note-id }
     .transferred = This note was not originally left on this record.
     .public = This note is public.
 
+record-claim = Claimed by
+    .claim = Claim
+    .release = Release
+    .unclaimed = No one is reviewing this record
+    .claimed-by = { $reviewer } (until { $until })
+
+record-rejection-reason = Rejection Reason
+    .none = None given
+    .stats = Rejection Reasons
+    .stats-entry = { $reason }: { $count }
+
+record-rejection-reason-dialog = Reject record
+    .info = Select why this record is being rejected. If the record is not rejected yet, this also rejects it.
+
+record-achieved-on = Achieved On
+    .unknown = Unknown
+
+record-submitted-at = Submitted At
+    .unknown = Unknown
+
+record-approved-at = Approved At
+    .not-approved = Not approved
+    .unknown = Unknown
+
+review-queue-panel = Review Queue
+    .info = Submissions awaiting review. Claim a record before reviewing it, so that no one else reviews it at the same time. Claims expire automatically.
+    .sort-age = Oldest first
+    .sort-position = Hardest demon first
+    .sort-trust = Least trusted submitter first
+    .reviewers = Reviewers
+    .reviewer-stats = { $reviewer }: { $claimed } claimed, { $reviewed } reviewed in the last 30 days
+    .claimed-by = Claimed by { $reviewer }
+
 record-status-filter-panel = Filter
     .info = Filter by record status
 
@@ -75,6 +115,12 @@ record-videolink-dialog = Change video link
 
     .videolink-validator-typemismatch = Please enter a valid URL
 
+record-achieved-on-dialog = Change completion date
+    .info = Change the day this record was achieved on. Leave the field empty if the day is not known.
+    .achieved-on-field = Completion date:
+
+    .submit = Edit
+
 record-demon-dialog = Change record demon
     .info = Change the demon associated with this record. Search up the demon this record should be associated with below. Then click it to modify the record
 
@@ -38,6 +38,9 @@ record-submission = Record Submission
     .progress-validator-badinput = Record progress must be a valid integer
     .progress-validator-stepmismatch = Record progress mustn't be a decimal
 
+    .achieved-on = Completion date
+    .achieved-on-info = The day the record was achieved on. Optional, leave this empty if you do not know it.
+
     .video = Video
     .video-info = A proof video of the legitimacy of the given record. If the record was achieved on stream, but wasn't uploaded anywhere else, please provide a twitch link to that stream.
     .video-note = Please pay attention to only submit well-formed URLs!
@@ -65,6 +68,13 @@ record-submission = Record Submission
     .submission-success = Record successfully submitted.
     .submission-success-queue = Record successfully submitted. It is { $queue-position } in the queue!
 
+# Submissions made from this browser, remembered via the tracking token
+# handed out on submission
+record-submission-tracked = Your Submissions
+    .info = The records you submitted from this browser. Each entry shows the current status of the submission, as well as the reason it was rejected for and any public notes a list moderator left on it. The background color tells you whether the record is approved, submitted, rejected or under consideration.
+
+    .rejection-reason = Rejection reason: { $reason }
+
 ## Submitters tab
 submitters = Submitters
 
@@ -86,6 +86,7 @@ movements-reason = Причина
     .moved = Перемещён
     .movedabove = { $demon } был перемещён выше этого демона
     .movedbelow = { $demon } был перемещён ниже этого демона
+    .deletedabove = { $demon } был удалён из листа
 
 ## Records table
 demon-records = Рекорды
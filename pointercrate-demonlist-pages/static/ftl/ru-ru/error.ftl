@@ -1,3 +1,4 @@
+error-demonlist-batchoperationfailed = Операция #{ $index } не удалась: { $error }
 error-demonlist-malformedvideourl = Неправильная ссылка на видео
 error-demonlist-bannedfromsubmissions = Вы забанены в демонлисте!
 error-demonlist-claimunverified = Ваш запрос на присвоение профиля не подтвержден
@@ -15,10 +16,17 @@ error-demonlist-demonnotfoundname = Демон с именем { $demon-name } 
 error-demonlist-demonnotfoundposition = Демон на позиции { $demon-position } не был найден
 error-demonlist-recordnotfound = Рекорд с id { $record-id } не был найден
 error-demonlist-claimnotfound = Запрос пользователем { $member-id } на присвоение профиля { $player-id } не был найден
+error-demonlist-listnotfound = Список с id { $list-id } не был найден
+error-demonlist-webhooktargetnotfound = Вебхук с id { $id } не был найден
+error-demonlist-recordnotclaimed = Рекорд #{ $record-id } сейчас никто не проверяет
+error-demonlist-trackedsubmissionnotfound = Нет заявки, отслеживаемой по данному токену
 error-demonlist-creatorexists = Этот игрок уже указан как креатор на этом демоне
 error-demonlist-duplicatevideo = Это видео уже используется рекордом #{ $record-id }
 error-demonlist-nonationset = Попытка установить регион без страны
 error-demonlist-conflictingclaims = Игроки '{ $player-1 }' и '{ $player-2 }' имеют подтвержденные присвоения разными пользователями pointercrate
+error-demonlist-playerinuse = У этого игрока всё ещё есть рекорды, креаторства или верифицированные/опубликованные демоны, поэтому его нельзя удалить. Вместо этого объедините его с другим игроком
+error-demonlist-importtargetnotempty = Архивы можно импортировать только в экземпляр без игроков, демонов и рекордов
+error-demonlist-recordclaimedbyother = Рекорд #{ $record-id } сейчас проверяет { $claimed-by }
 error-demonlist-invalidrequirement = Требование к рекорду должно быть больше -1 и меньше 101
 error-demonlist-invalidposition = Позиция демона должна быть между 1 и { $maximal }
 error-demonlist-invalidprogress = Прогресс на рекорде должен находиться между { $requirement } и 100%!
@@ -33,6 +41,19 @@ error-demonlist-alreadyclaimed = Этот игрок уже имеет связ
 error-demonlist-rawrequired = Для отправки рекорда необходимо предоставить необработанную запись
 error-demonlist-malformedrawurl = Необработанная запись должна быть в виде правильно оформленной ссылки
 error-demonlist-invalidlevelid = ID уровня должен быть положительным
+error-demonlist-invalidwebhookurl = URL вебхука должен быть корректным http или https URL
+error-demonlist-malformedarchive = Строка { $line } архива не является корректной записью архива, или архив неполный
+error-demonlist-unsupportedarchiveversion = Архивы версии { $version } не поддерживаются
+error-demonlist-inconsistentarchive = Запись в строке { $line } архива дублирует объекты или ссылается на них некорректно
+error-demonlist-invalidarchivepositions = Демоны списка { $list-id } в архиве не имеют последовательных позиций, начиная с 1
+error-demonlist-archivescoremismatch = Сохранённые в архиве очки игрока { $player-id } в списке { $list-id } не соответствуют рекордам архива
+error-demonlist-recordalreadyreviewed = Этот рекорд уже был проверен
+error-demonlist-unknownrejectionreason = Неизвестная причина отклонения "{ $reason }"
+error-demonlist-rejectionreasonrequired = При отклонении рекорда необходимо указать причину
+error-demonlist-recordnotrejected = Причина отклонения может быть только у отклонённых рекордов
+error-demonlist-achievedinfuture = Рекорд не может быть достигнут в будущем
+error-demonlist-batchtoolarge = Пакет может содержать не более { $max-size } операций
+error-demonlist-duplicatebatchrecord = Пакет может содержать не более одной операции над рекордом { $record-id }
 
 error-demonlist-ratelimit-record-submit = Вы отправляете слишком много рекордов слишком часто!
 error-demonlist-ratelimit-record-submit-global = Слишком много рекордов отправляется на данный момент!
@@ -61,6 +61,7 @@ claim-records = Рекорды на вашем профиле
 
     .record-notes = Заметки для рекорда { $record-id }:
     .record-notes-none = На данном рекорде отсутствуют публичные заметки!
+    .rejection-reason = Причина отклонения: { $reason }
 
 claim-manager = Менеджер присвоения
     .info-a = Здесь проходит работа с присвоением профилей через интерфейс ниже. Список сортируется по профилям и пользователям через панели справа. Неправильные запросы на присвоение должны удаляться через кнопку с мусоркой.
@@ -12,6 +12,13 @@ record-holder = Владелец рекорда
 record-progress = Прогресс
 record-submitter = ID отправителя
 
+# Описания стандартных причин отклонения (см. настройку rejection_reasons)
+rejection-reason-no-clicks = На видео не слышно кликов
+rejection-reason-cheat-indicator = На видео видны признаки использования читов
+rejection-reason-wrong-level-version = Рекорд был поставлен на другой версии уровня, чем та, что в листе
+rejection-reason-unclear-video = Видео не показывает прохождение достаточно чётко
+rejection-reason-duplicate = Этот рекорд уже был отправлен
+
 ## Records tab (user area)
 records = Рекорды
 record-manager = Менеджер рекордов
@@ -44,6 +51,39 @@ record-note-listed = Заметка #{ $note-id }
     .transferred = Эта заметка изначально не принадлежит этому рекорду.
     .public = Эта заметка является публичной.
 
+record-claim = Проверяет
+    .claim = Взять на проверку
+    .release = Освободить
+    .unclaimed = Этот рекорд сейчас никто не проверяет
+    .claimed-by = { $reviewer } (до { $until })
+
+record-rejection-reason = Причина отклонения
+    .none = Не указана
+    .stats = Причины отклонения
+    .stats-entry = { $reason }: { $count }
+
+record-rejection-reason-dialog = Отклонить рекорд
+    .info = Выберите, почему этот рекорд отклоняется. Если рекорд ещё не отклонён, он также будет отклонён.
+
+record-achieved-on = Дата прохождения
+    .unknown = Неизвестна
+
+record-submitted-at = Отправлен
+    .unknown = Неизвестно
+
+record-approved-at = Одобрен
+    .not-approved = Не одобрен
+    .unknown = Неизвестно
+
+review-queue-panel = Очередь проверки
+    .info = Рекорды, ожидающие проверки. Возьмите рекорд на проверку перед тем, как проверять его, чтобы никто другой не проверял его одновременно с вами. Взятие на проверку истекает автоматически.
+    .sort-age = Сначала старые
+    .sort-position = Сначала сложные демоны
+    .sort-trust = Сначала наименее надёжные отправители
+    .reviewers = Проверяющие
+    .reviewer-stats = { $reviewer }: взято { $claimed }, проверено { $reviewed } за последние 30 дней
+    .claimed-by = Проверяет { $reviewer }
+
 record-status-filter-panel = Фильтрация
     .info = Фильтрация по статусу рекордов
 
@@ -75,6 +115,12 @@ record-videolink-dialog = Изменение ссылки на видео
 
     .videolink-validator-typemismatch = Пожалуйста, введите правильную ссылку
 
+record-achieved-on-dialog = Изменение даты прохождения
+    .info = Здесь проходит изменение дня, в который был достигнут этот рекорд. Оставьте поле пустым, если день неизвестен.
+    .achieved-on-field = Дата прохождения:
+
+    .submit = Изменить
+
 record-demon-dialog = Изменение демона в рекорде
     .info = Здесь проходит изменение демона, связанного с данным рекордом. Ниже можно найти демон, с которым должен быть этот рекорд. После этого нажмите на него для изменения рекорда
 
@@ -38,6 +38,9 @@ record-submission = Отправление рекордов
     .progress-validator-badinput = Значение прогресса должно быть целым числом
     .progress-validator-stepmismatch = Значение прогресса не должно быть дробным
 
+    .achieved-on = Дата прохождения
+    .achieved-on-info = День, в который был достигнут рекорд. Необязательно, оставьте поле пустым, если вы его не знаете.
+
     .video = Видео
     .video-info = Видео-доказательства честности данного рекорда. Если рекорд был поставлен на стриме, но не был опубликован где-то еще, предоставьте Twitch-ссылку на этот стрим.
     .video-note = Внимательно вводите ссылки и соблюдайте их правильность!
@@ -65,6 +68,13 @@ record-submission = Отправление рекордов
     .submission-success = Рекорд успешно отправлен.
     .submission-success-queue = Рекорд успешно отправлен. Он находится на { $queue-position } в очереди!
 
+# Submissions made from this browser, remembered via the tracking token
+# handed out on submission
+record-submission-tracked = Ваши заявки
+    .info = Рекорды, отправленные вами с этого браузера. Для каждого рекорда показан текущий статус заявки, а также причина отклонения и публичные записки, оставленные модератором списка. Цвет фона показывает, одобрен ли рекорд, отправлен, отклонён или находится на рассмотрении.
+
+    .rejection-reason = Причина отклонения: { $reason }
+
 ## Submitters tab
 submitters = Отправители
 
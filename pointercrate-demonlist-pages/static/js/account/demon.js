@@ -25,6 +25,13 @@ import { loadResource, tr } from "/static/core/js/modules/localization.js";
 
 export let demonManager;
 
+/**
+ * The base path of the demon endpoints of the list this tab manages
+ */
+function apiBase() {
+  return document.getElementById("demon-manager").dataset.apiBase;
+}
+
 export class DemonManager extends FilteredPaginator {
   constructor() {
     super("demon-pagination", generateDemon, "name_contains");
@@ -34,7 +41,7 @@ export class DemonManager extends FilteredPaginator {
       this
     );
 
-    this.retrievalEndpoint = "/api/v2/demons/";
+    this.retrievalEndpoint = apiBase();
 
     this._id = document.getElementById("demon-demon-id");
     this._name = document.getElementById("demon-demon-name");
@@ -208,7 +215,7 @@ export class DemonManager extends FilteredPaginator {
     let html = insertCreatorInto(creator, this._creators);
     html.children[0].addEventListener("click", () => {
       del(
-        "/api/v2/demons/" +
+        apiBase() +
           this.currentObject.id +
           "/creators/" +
           creator.id +
@@ -328,7 +335,7 @@ function setupDemonAdditionForm() {
 
     data["creators"] = form.creators;
 
-    post("/api/v2/demons/", {}, data)
+    post(apiBase(), {}, data)
       .then(() => {
         form.setSuccess("Successfully added demon!");
         demonManager.refresh();
@@ -355,7 +362,7 @@ export function initialize() {
   button1.addEventListener("click", () => {
     creatorFormDialog.submissionPredicateFactory = (data) => {
       return post(
-        "/api/v2/demons/" + demonManager.currentObject.id + "/creators/",
+        apiBase() + demonManager.currentObject.id + "/creators/",
         {},
         data
       )
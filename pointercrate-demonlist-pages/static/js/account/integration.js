@@ -13,7 +13,10 @@ import {
   generatePlayer,
 } from "/static/demonlist/js/modules/demonlist.js";
 import { Paginator } from "/static/core/js/modules/form.js";
-import { generateRecord } from "/static/demonlist/js/modules/demonlist.js";
+import {
+  generateRecord,
+  getRejectionReasons,
+} from "/static/demonlist/js/modules/demonlist.js";
 import { loadResource, tr, trp } from "/static/core/js/modules/localization.js";
 
 export let claimManager;
@@ -163,8 +166,26 @@ class ClaimedPlayerRecordPaginator extends Paginator {
   onSelect(selected) {
     let recordId = selected.dataset.id;
 
-    get("/api/v1/records/" + recordId + "/notes/")
-      .then((response) => {
+    Promise.all([
+      get("/api/v1/records/" + recordId + "/"),
+      get("/api/v1/records/" + recordId + "/notes/"),
+      getRejectionReasons(),
+    ])
+      .then(([recordResponse, response, reasons]) => {
+        let reason = recordResponse.data.data.rejection_reason;
+        let rejection = null;
+
+        if (reason !== null && reason !== undefined) {
+          rejection = trp(
+            "demonlist",
+            "player",
+            "claim-records.rejection-reason",
+            {
+              ["reason"]: reasons.get(reason) ?? reason,
+            }
+          );
+        }
+
         if (Array.isArray(response.data) && response.data.length > 0) {
           this.setSuccess(null);
 
@@ -172,6 +193,11 @@ class ClaimedPlayerRecordPaginator extends Paginator {
             this.successOutput.removeChild(this.successOutput.lastChild);
           }
 
+          if (rejection !== null) {
+            this.successOutput.appendChild(document.createTextNode(rejection));
+            this.successOutput.appendChild(document.createElement("br"));
+          }
+
           let title = document.createElement("b");
           title.innerText = trp(
             "demonlist",
@@ -196,6 +222,8 @@ class ClaimedPlayerRecordPaginator extends Paginator {
           }
 
           this.successOutput.style.display = "block";
+        } else if (rejection !== null) {
+          this.setSuccess(rejection);
         } else {
           this.setSuccess(
             tr("demonlist", "player", "claim-records.record-notes-none")
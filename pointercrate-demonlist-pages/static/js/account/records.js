@@ -1,5 +1,6 @@
 import {
   post,
+  put,
   del,
   get,
   displayError,
@@ -25,14 +26,23 @@ import {
   initializeRecordSubmitter,
   generateRecord,
   embedVideo,
+  getRejectionReasons,
 } from "/static/demonlist/js/modules/demonlist.js";
 import { tr, trp } from "/static/core/js/modules/localization.js";
 
 export let recordManager;
+export let reviewQueue;
+
+/**
+ * The id of the list whose records this tab manages
+ */
+function managedList() {
+  return document.getElementById("record-manager").dataset.list;
+}
 
 class RecordManager extends Paginator {
   constructor() {
-    super("record-pagination", {}, generateRecord);
+    super("record-pagination", { list: managedList() }, generateRecord);
 
     var manager = document.getElementById("record-manager");
 
@@ -50,6 +60,11 @@ class RecordManager extends Paginator {
     this._progress = document.getElementById("record-progress");
     this._submitter = document.getElementById("record-submitter");
     this._notes = document.getElementById("record-notes");
+    this._claim = document.getElementById("record-claim");
+    this._rejectionReason = document.getElementById("record-rejection-reason");
+    this._achievedOn = document.getElementById("record-achieved-on");
+    this._submittedAt = document.getElementById("record-submitted-at");
+    this._approvedAt = document.getElementById("record-approved-at");
 
     this.dropdown = new Dropdown(
       document
@@ -77,6 +92,7 @@ class RecordManager extends Paginator {
 
     this.initProgressDialog();
     this.initVideoDialog();
+    this.initAchievedOnDialog();
     setupEditorDialog(
       new FormDialog("record-holder-dialog"),
       "record-holder-pen",
@@ -84,6 +100,16 @@ class RecordManager extends Paginator {
       this.output
     );
     this.initDemonDialog();
+    setupEditorDialog(
+      new DropdownDialog(
+        "record-rejection-reason-dialog",
+        "edit-rejection-reason-record"
+      ),
+      "record-rejection-reason-pen",
+      new PaginatorEditorBackend(this, true),
+      this.output,
+      (reason) => ({ status: "rejected", rejection_reason: reason })
+    );
 
     document
       .getElementById("record-copy-info")
@@ -173,6 +199,17 @@ class RecordManager extends Paginator {
     }
   }
 
+  initAchievedOnDialog() {
+    let form = setupFormDialogEditor(
+      new PaginatorEditorBackend(this, false),
+      "record-achieved-on-dialog",
+      "record-achieved-on-pen",
+      this.output
+    );
+
+    form.addErrorOverride(42254, "record-achieved-on-edit");
+  }
+
   initDemonDialog() {
     setupEditorDialog(
       new DropdownDialog("record-demon-dialog", "edit-demon-record"),
@@ -227,6 +264,10 @@ class RecordManager extends Paginator {
     this._progress.innerText = this.currentObject.progress + "%";
     this._submitter.innerText = this.currentObject.submitter.id;
 
+    this.loadClaim();
+    this.displayRejectionReason();
+    this.displayTimeline();
+
     // this is introducing race conditions. Oh well.
     return get("/api/v1/records/" + this.currentObject.id + "/notes/").then(
       (response) => {
@@ -243,6 +284,189 @@ class RecordManager extends Paginator {
       }
     );
   }
+
+  loadClaim() {
+    return get("/api/v1/records/" + this.currentObject.id + "/claim/")
+      .then((response) => this.displayClaim(response.data))
+      .catch((response) => {
+        if (response.status === 404) this.displayClaim(null);
+        else displayError(this.output)(response);
+      });
+  }
+
+  displayRejectionReason() {
+    let reason = this.currentObject.rejection_reason;
+
+    if (reason === null || reason === undefined) {
+      this._rejectionReason.innerText = tr(
+        "demonlist",
+        "record",
+        "record-rejection-reason.none"
+      );
+    } else {
+      getRejectionReasons().then((reasons) => {
+        this._rejectionReason.innerText = reasons.get(reason) ?? reason;
+      });
+    }
+  }
+
+  displayTimeline() {
+    let record = this.currentObject;
+
+    this._achievedOn.innerText =
+      record.achieved_on ??
+      tr("demonlist", "record", "record-achieved-on.unknown");
+
+    // Audit log times are in UTC
+    this._submittedAt.innerText = record.submitted_at
+      ? new Date(record.submitted_at + "Z").toLocaleString()
+      : tr("demonlist", "record", "record-submitted-at.unknown");
+
+    if (record.status !== "approved") {
+      this._approvedAt.innerText = tr(
+        "demonlist",
+        "record",
+        "record-approved-at.not-approved"
+      );
+    } else {
+      this._approvedAt.innerText = record.approved_at
+        ? new Date(record.approved_at + "Z").toLocaleString()
+        : tr("demonlist", "record", "record-approved-at.unknown");
+    }
+  }
+
+  displayClaim(claim) {
+    if (claim === null) {
+      this._claim.innerText = tr(
+        "demonlist",
+        "record",
+        "record-claim.unclaimed"
+      );
+    } else {
+      this._claim.innerText = trp(
+        "demonlist",
+        "record",
+        "record-claim.claimed-by",
+        {
+          ["reviewer"]: claim.claimed_by.name,
+          // Claim times are in UTC
+          ["until"]: new Date(claim.expires_at + "Z").toLocaleTimeString(),
+        }
+      );
+    }
+  }
+}
+
+class ReviewQueue extends Paginator {
+  constructor() {
+    super(
+      "review-queue-pagination",
+      { list: managedList() },
+      generateQueuedRecord
+    );
+
+    this._reviewers = document.getElementById("review-queue-reviewers");
+    this._rejectionStats = document.getElementById("rejection-reason-stats");
+
+    // All sort keys put the records that should be reviewed first at the
+    // start when sorting in ascending order, so we never change the order
+    new Dropdown(
+      document.getElementById("review-queue-sort")
+    ).addEventListener((selected) => {
+      if (selected === "age") this.updateQueryData("sort", undefined);
+      else this.updateQueryData("sort", selected);
+    });
+  }
+
+  // Queued records are viewed (and edited) in the record manager
+  onSelect(selected) {
+    this.currentlySelected = selected;
+
+    return recordManager
+      .selectArbitrary(parseInt(selected.dataset.id))
+      .catch(displayError(this));
+  }
+
+  refresh() {
+    this.loadReviewers();
+    this.loadRejectionStats();
+
+    return super.refresh();
+  }
+
+  loadReviewers() {
+    return get(
+      "/api/v1/records/queue/reviewers/?list=" + managedList()
+    ).then((response) => {
+      while (this._reviewers.firstChild) {
+        this._reviewers.removeChild(this._reviewers.firstChild);
+      }
+
+      for (let stats of response.data) {
+        let li = document.createElement("li");
+
+        li.innerText = trp(
+          "demonlist",
+          "record",
+          "review-queue-panel.reviewer-stats",
+          {
+            ["reviewer"]: stats.reviewer.name,
+            ["claimed"]: stats.claimed,
+            ["reviewed"]: stats.reviewed,
+          }
+        );
+
+        this._reviewers.appendChild(li);
+      }
+    });
+  }
+
+  loadRejectionStats() {
+    return Promise.all([
+      get("/api/v1/records/rejection-reasons/stats/?list=" + managedList()),
+      getRejectionReasons(),
+    ]).then(([response, reasons]) => {
+      while (this._rejectionStats.firstChild) {
+        this._rejectionStats.removeChild(this._rejectionStats.firstChild);
+      }
+
+      for (let stats of response.data) {
+        let li = document.createElement("li");
+        let reason =
+          stats.reason === null
+            ? tr("demonlist", "record", "record-rejection-reason.none")
+            : reasons.get(stats.reason) ?? stats.reason;
+
+        li.innerText = trp(
+          "demonlist",
+          "record",
+          "record-rejection-reason.stats-entry",
+          {
+            ["reason"]: reason,
+            ["count"]: stats.count,
+          }
+        );
+
+        this._rejectionStats.appendChild(li);
+      }
+    });
+  }
+}
+
+function generateQueuedRecord(record) {
+  let li = generateRecord(record);
+
+  if (record.claim) {
+    let i = document.createElement("i");
+
+    i.innerText = trp("demonlist", "record", "review-queue-panel.claimed-by", {
+      ["reviewer"]: record.claim.claimed_by.name,
+    });
+
+    li.appendChild(i);
+  }
+
+  return li;
 }
 
 function createNoteHtml(note) {
@@ -441,6 +665,30 @@ function setupEditRecordForm() {
   });
 }
 
+function setupClaimButtons() {
+  document
+    .getElementById("record-claim-button")
+    .addEventListener("click", () => {
+      put("/api/v1/records/" + recordManager.currentObject.id + "/claim/")
+        .then((response) => {
+          recordManager.displayClaim(response.data);
+          reviewQueue.refresh();
+        })
+        .catch(displayError(recordManager.output));
+    });
+
+  document
+    .getElementById("record-release-button")
+    .addEventListener("click", () => {
+      del("/api/v1/records/" + recordManager.currentObject.id + "/claim/")
+        .then(() => {
+          recordManager.displayClaim(null);
+          reviewQueue.refresh();
+        })
+        .catch(displayError(recordManager.output));
+    });
+}
+
 export function initialize() {
   setupRecordFilterPlayerIdForm();
   setupRecordFilterPlayerNameForm();
@@ -450,6 +698,10 @@ export function initialize() {
 
   initializeRecordSubmitter(true);
 
+  setupClaimButtons();
+
   recordManager = new RecordManager();
-  return recordManager.initialize();
+  reviewQueue = new ReviewQueue();
+
+  return Promise.all([recordManager.initialize(), reviewQueue.initialize()]);
 }
@@ -16,7 +16,7 @@ $(window).on("load", function () {
 });
 
 function initializeHistoryTable() {
-  get("/api/v2/demons/" + window.demon_id + "/audit/movement/").then(
+  get(window.list_api_base + window.demon_id + "/audit/movement/").then(
     (response) => {
       let data = response.data;
       let tableBody = document.getElementById("history-table-body");
@@ -101,6 +101,18 @@ function initializeHistoryTable() {
                 : trp("demonlist", "demon", "movements-reason.movedabove", {
                     ["demon"]: name,
                   });
+          } else if (entry["reason"]["OtherDeletedAbove"] !== undefined) {
+            let other = entry["reason"]["OtherDeletedAbove"]["other"];
+            let name = other.name === null ? "A demon" : other["name"];
+
+            reason = trp(
+              "demonlist",
+              "demon",
+              "movements-reason.deletedabove",
+              {
+                ["demon"]: name,
+              }
+            );
           }
         }
 
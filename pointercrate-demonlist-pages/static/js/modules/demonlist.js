@@ -86,7 +86,7 @@ export function initializeTimeMachine() {
 
     document.cookie = "when=" + when;
 
-    window.location = "/demonlist/";
+    window.location = window.list_base;
   });
 }
 
@@ -98,6 +98,7 @@ export function initializeRecordSubmitter(submitApproved = false) {
   var progress = submissionForm.input("id_progress");
   var video = submissionForm.input("id_video");
   var rawFootage = submissionForm.input("submit-raw-footage");
+  var achievedOn = submissionForm.input("id_achieved_on");
 
   demon.addValidator(
     (input) => input.dropdown.selected !== undefined,
@@ -220,6 +221,13 @@ export function initializeRecordSubmitter(submitApproved = false) {
             tr("demonlist", "submitter", "record-submission.submission-success")
           );
         submissionForm.clear();
+
+        let trackingToken = response.headers["x-tracking-token"];
+
+        if (trackingToken && !submitApproved) {
+          trackSubmission(trackingToken);
+          displayTrackedSubmissions();
+        }
       })
       .catch((response) => {
         switch (response.data.code) {
@@ -243,11 +251,114 @@ export function initializeRecordSubmitter(submitApproved = false) {
           case 42233:
             rawFootage.errorText = response.data.message;
             break;
+          case 42254:
+            achievedOn.errorText = response.data.message;
+            break;
           default:
             submissionForm.setError(response.data.message);
         }
       }); // TODO: maybe specially handle some error codes
   });
+
+  if (!submitApproved) displayTrackedSubmissions();
+}
+
+const TRACKED_SUBMISSIONS_KEY = "tracked_submissions";
+const MAX_TRACKED_SUBMISSIONS = 25;
+
+function getTrackingTokens() {
+  try {
+    let tokens = JSON.parse(
+      window.localStorage.getItem(TRACKED_SUBMISSIONS_KEY)
+    );
+
+    return Array.isArray(tokens) ? tokens : [];
+  } catch (e) {
+    return [];
+  }
+}
+
+function setTrackingTokens(tokens) {
+  window.localStorage.setItem(
+    TRACKED_SUBMISSIONS_KEY,
+    JSON.stringify(tokens.slice(0, MAX_TRACKED_SUBMISSIONS))
+  );
+}
+
+// Remembers the tracking token of a submission made from this browser, most
+// recent first
+function trackSubmission(token) {
+  setTrackingTokens([token, ...getTrackingTokens()]);
+}
+
+// Lists the current state of all submissions made from this browser. Tokens
+// of submissions that no longer exist (for instance because the record got
+// deleted) are forgotten.
+function displayTrackedSubmissions() {
+  let container = document.getElementById("tracked-submissions");
+  let list = document.getElementById("tracked-submissions-list");
+
+  if (!container || !list) return;
+
+  let tokens = getTrackingTokens();
+
+  Promise.all([
+    Promise.allSettled(
+      tokens.map((token) => get("/api/v1/records/track/" + token + "/"))
+    ),
+    getRejectionReasons().catch(() => new Map()),
+  ]).then(([results, reasons]) => {
+    let stillTracked = tokens.filter(
+      (_, index) =>
+        results[index].status === "fulfilled" ||
+        results[index].reason.status !== 404
+    );
+
+    if (stillTracked.length !== tokens.length) setTrackingTokens(stillTracked);
+
+    list.replaceChildren(
+      ...results
+        .filter((result) => result.status === "fulfilled")
+        .map((result) => generateTrackedSubmission(result.value.data, reasons))
+    );
+
+    container.style.display = list.childElementCount ? "block" : "none";
+  });
+}
+
+function generateTrackedSubmission(submission, reasons) {
+  let li = generateRecord(submission);
+  let reason = submission.rejection_reason;
+
+  if (reason !== null && reason !== undefined) {
+    li.appendChild(
+      document.createTextNode(
+        trp(
+          "demonlist",
+          "submitter",
+          "record-submission-tracked.rejection-reason",
+          {
+            ["reason"]: reasons.get(reason) ?? reason,
+          }
+        )
+      )
+    );
+    li.appendChild(document.createElement("br"));
+  }
+
+  for (let note of submission.notes) {
+    // Notes without author are the ones the submitter provided themselves
+    if (note.author) {
+      let noteAuthor = document.createElement("i");
+      noteAuthor.innerText = "(" + note.author + ") ";
+
+      li.appendChild(noteAuthor);
+    }
+    li.appendChild(document.createTextNode(note.content));
+    li.appendChild(document.createElement("br"));
+  }
+
+  return li;
 }
 
 export function getCountryFlag(title, countryCode) {
@@ -363,6 +474,21 @@ export function generateDemon(demon) {
   return li;
 }
 
+let rejectionReasons;
+
+// Resolves to a map from the keys of all rejection reasons to their
+// descriptions. The catalogue is only retrieved once per page load.
+export function getRejectionReasons() {
+  if (rejectionReasons === undefined) {
+    rejectionReasons = get("/api/v1/records/rejection-reasons/").then(
+      (response) =>
+        new Map(response.data.map((reason) => [reason.key, reason.description]))
+    );
+  }
+
+  return rejectionReasons;
+}
+
 export function generateRecord(record) {
   var li = document.createElement("li");
   var recordId = document.createElement("b");
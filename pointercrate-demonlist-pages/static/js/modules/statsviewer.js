@@ -23,7 +23,8 @@ export class StatsViewer extends FilteredPaginator {
     super(
       "stats-viewer-pagination",
       statsviewerdata.entryGenerator,
-      "name_contains"
+      "name_contains",
+      { list: window.list_id }
     );
 
     this.endpoint = statsviewerdata.rankingEndpoint;
@@ -94,13 +95,24 @@ export class StatsViewer extends FilteredPaginator {
     ); // default to alphabetical
   }
 
+  selectArbitrary(id) {
+    return get(this.retrievalEndpoint + id + "/?list=" + window.list_id).then(
+      (response) => {
+        this.setError(null);
+        this.onReceive(response);
+      }
+    );
+  }
+
   initialize() {
-    return get("/api/v1/list_information/").then((data) => {
-      this.list_size = data.data["list_size"];
-      this.extended_list_size = data.data["extended_list_size"];
+    return get("/api/v1/list_information/?list=" + window.list_id).then(
+      (data) => {
+        this.list_size = data.data["list_size"];
+        this.extended_list_size = data.data["extended_list_size"];
 
-      super.initialize();
-    });
+        super.initialize();
+      }
+    );
   }
 
   setName(name, nationality) {
@@ -177,7 +189,7 @@ export class StatsViewer extends FilteredPaginator {
     }
 
     let a = document.createElement("a");
-    a.href = link ?? "/demonlist/permalink/" + demon.id + "/";
+    a.href = link ?? window.list_base + "permalink/" + demon.id + "/";
     a.textContent = demon.name;
 
     element.appendChild(a);
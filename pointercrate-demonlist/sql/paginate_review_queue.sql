@@ -0,0 +1,20 @@
+SELECT records.id, progress, CASE WHEN players.link_banned THEN NULL ELSE records.video::text END AS video, status_::text AS status,
+       players.id AS player_id, players.name::text AS player_name, players.banned AS player_banned,
+       demons.id AS demon_id, demons.name::text AS demon_name, demons.position, demons.list,
+       records.submitter, submitter_trust.trust,
+       record_claims.member_id AS claimed_by, members.name AS claimed_by_name, record_claims.claimed_at, record_claims.expires_at
+FROM records
+INNER JOIN players ON records.player = players.id
+INNER JOIN demons ON records.demon = demons.id
+INNER JOIN submitter_trust ON records.submitter = submitter_trust.submitter_id
+LEFT OUTER JOIN record_claims ON record_claims.record = records.id AND record_claims.expires_at > (NOW() AT TIME ZONE 'utc')
+LEFT OUTER JOIN members ON record_claims.member_id = members.member_id
+WHERE status_ IN ('SUBMITTED', 'UNDER_CONSIDERATION')
+  AND (records.id < $1 OR $1 IS NULL)
+  AND (records.id > $2 OR $2 IS NULL)
+  AND (status_ = CAST($3::TEXT AS record_status) OR $3 IS NULL)
+  AND (demons.list = $4 OR $4 IS NULL)
+  AND ((record_claims.record IS NOT NULL) = $5 OR $5 IS NULL)
+  AND (record_claims.member_id = $6 OR $6 IS NULL){cursor_conditions}
+ORDER BY {order_by}
+LIMIT $7
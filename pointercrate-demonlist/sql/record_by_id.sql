@@ -3,10 +3,14 @@ SELECT progress,
        CASE WHEN players.link_banned THEN NULL ELSE records.raw_footage::text END,
        status_::text AS "status!: String" ,
        players.id AS player_id, players.name AS "player_name: String", players.banned AS player_banned,
-       demons.id AS demon_id, demons.name AS "demon_name: String", demons.position,
-       submitters.submitter_id AS submitter_id, submitters.banned AS submitter_banned
+       demons.id AS demon_id, demons.name AS "demon_name: String", demons.position, demons.list,
+       submitters.submitter_id AS submitter_id, submitters.banned AS submitter_banned,
+       record_rejections.reason AS "rejection_reason?",
+       records.achieved_on, record_timeline.submitted_at AS "submitted_at?", record_timeline.approved_at AS "approved_at?"
 FROM records
 INNER JOIN players ON records.player = players.id
 INNER JOIN demons ON records.demon = demons.id
 INNER JOIN submitters ON records.submitter = submitters.submitter_id
+LEFT OUTER JOIN record_rejections ON record_rejections.record = records.id
+INNER JOIN record_timeline ON record_timeline.id = records.id
 WHERE records.id = $1
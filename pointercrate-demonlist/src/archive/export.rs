@@ -0,0 +1,236 @@
+use crate::{
+    archive::{
+        ArchiveEnd, ArchiveEntry, ArchiveHeader, ArchivedAuditEntry, ArchivedCreator, ArchivedDemon, ArchivedList, ArchivedNationality,
+        ArchivedNote, ArchivedPlayer, ArchivedRecord, ArchivedScore, ArchivedSubdivision, AuditTable, ARCHIVE_VERSION,
+    },
+    error::{DemonlistError, Result},
+};
+use async_stream::try_stream;
+use chrono::Utc;
+use futures::{Stream, StreamExt};
+use pointercrate_core::error::CoreError;
+use sqlx::{Connection, PgConnection};
+use std::collections::HashMap;
+
+/// Exports all data of this instance that is part of archives, in the order described in the
+/// [module level documentation](crate::archive)
+///
+/// All data is read from a single snapshot of the database, so the archive is consistent even if
+/// the data is modified during the export. For this, `connection` must not be inside a
+/// transaction.
+pub fn export(connection: &mut PgConnection) -> impl Stream<Item = Result<ArchiveEntry>> + '_ {
+    try_stream! {
+        let mut transaction = connection.begin().await?;
+
+        sqlx::query!("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+            .execute(&mut *transaction)
+            .await?;
+
+        let scoring_fingerprint = sqlx::query_scalar!("SELECT fingerprint FROM scoring_policy")
+            .fetch_optional(&mut *transaction)
+            .await?;
+
+        yield ArchiveEntry::Header(ArchiveHeader {
+            version: ARCHIVE_VERSION,
+            exported_at: Utc::now().naive_utc(),
+            scoring_fingerprint,
+        });
+
+        let mut entries = 0;
+
+        let mut lists = sqlx::query!(r#"SELECT id, name::TEXT AS "name!", list_size, extended_list_size FROM lists ORDER BY id"#)
+            .fetch(&mut *transaction);
+
+        while let Some(row) = lists.next().await {
+            let row = row?;
+
+            entries += 1;
+
+            yield ArchiveEntry::List(ArchivedList {
+                id: row.id,
+                name: row.name,
+                list_size: row.list_size,
+                extended_list_size: row.extended_list_size,
+            });
+        }
+
+        drop(lists);
+
+        let mut nationalities = sqlx::query!(
+            r#"SELECT iso_country_code, nation::TEXT AS "nation!", continent::TEXT AS "continent!" FROM nationalities ORDER BY iso_country_code"#
+        )
+        .fetch(&mut *transaction);
+
+        while let Some(row) = nationalities.next().await {
+            let row = row?;
+
+            entries += 1;
+
+            yield ArchiveEntry::Nationality(ArchivedNationality {
+                iso_country_code: row.iso_country_code,
+                nation: row.nation,
+                continent: row.continent,
+            });
+        }
+
+        drop(nationalities);
+
+        let mut subdivisions = sqlx::query!(r#"SELECT iso_code, name::TEXT AS "name!", nation FROM subdivisions ORDER BY nation, iso_code"#)
+            .fetch(&mut *transaction);
+
+        while let Some(row) = subdivisions.next().await {
+            let row = row?;
+
+            entries += 1;
+
+            yield ArchiveEntry::Subdivision(ArchivedSubdivision {
+                iso_code: row.iso_code,
+                name: row.name,
+                nation: row.nation,
+            });
+        }
+
+        drop(subdivisions);
+
+        let mut scores = HashMap::<i32, Vec<ArchivedScore>>::new();
+
+        for row in sqlx::query!("SELECT player, list, score FROM player_scores ORDER BY list")
+            .fetch_all(&mut *transaction)
+            .await?
+        {
+            scores.entry(row.player).or_default().push(ArchivedScore {
+                list: row.list,
+                score: row.score,
+            });
+        }
+
+        let mut players = sqlx::query!(
+            r#"SELECT id, name::TEXT AS "name!", banned, COALESCE(link_banned, FALSE) AS "link_banned!", nationality, subdivision FROM players ORDER BY id"#
+        )
+        .fetch(&mut *transaction);
+
+        while let Some(row) = players.next().await {
+            let row = row?;
+
+            entries += 1;
+
+            yield ArchiveEntry::Player(ArchivedPlayer {
+                id: row.id,
+                name: row.name,
+                banned: row.banned,
+                link_banned: row.link_banned,
+                nationality: row.nationality,
+                subdivision: row.subdivision,
+                scores: scores.remove(&row.id).unwrap_or_default(),
+            });
+        }
+
+        drop(players);
+
+        let mut demons = sqlx::query!(
+            r#"SELECT id, list, name::TEXT AS "name!", position, requirement, video::TEXT, thumbnail, verifier, publisher, level_id FROM demons ORDER BY list, position"#
+        )
+        .fetch(&mut *transaction);
+
+        while let Some(row) = demons.next().await {
+            let row = row?;
+
+            entries += 1;
+
+            yield ArchiveEntry::Demon(ArchivedDemon {
+                id: row.id,
+                list: row.list,
+                name: row.name,
+                position: row.position,
+                requirement: row.requirement,
+                video: row.video,
+                thumbnail: row.thumbnail,
+                verifier: row.verifier,
+                publisher: row.publisher,
+                level_id: row.level_id,
+            });
+        }
+
+        drop(demons);
+
+        let mut creators = sqlx::query!("SELECT demon, creator FROM creators ORDER BY demon, creator").fetch(&mut *transaction);
+
+        while let Some(row) = creators.next().await {
+            let row = row?;
+
+            entries += 1;
+
+            yield ArchiveEntry::Creator(ArchivedCreator {
+                demon: row.demon,
+                player: row.creator,
+            });
+        }
+
+        drop(creators);
+
+        let mut records = sqlx::query!(
+            "SELECT id, progress, video::TEXT, raw_footage, player, demon, achieved_on FROM records WHERE status_ = 'APPROVED' ORDER BY id"
+        )
+        .fetch(&mut *transaction);
+
+        while let Some(row) = records.next().await {
+            let row = row?;
+
+            entries += 1;
+
+            yield ArchiveEntry::Record(ArchivedRecord {
+                id: row.id,
+                progress: row.progress,
+                video: row.video,
+                raw_footage: row.raw_footage,
+                player: row.player,
+                demon: row.demon,
+                achieved_on: row.achieved_on,
+            });
+        }
+
+        drop(records);
+
+        let mut notes = sqlx::query!(
+            "SELECT record_notes.id, record, content, is_public FROM record_notes INNER JOIN records ON records.id = record_notes.record WHERE \
+             records.status_ = 'APPROVED' ORDER BY record_notes.id"
+        )
+        .fetch(&mut *transaction);
+
+        while let Some(row) = notes.next().await {
+            let row = row?;
+
+            entries += 1;
+
+            yield ArchiveEntry::Note(ArchivedNote {
+                id: row.id,
+                record: row.record,
+                content: row.content,
+                is_public: row.is_public,
+            });
+        }
+
+        drop(notes);
+
+        for table in AuditTable::ALL {
+            // Having postgres serialize the rows means we do not have to keep the archive format in sync with the columns of
+            // every audit log table
+            let sql = format!(
+                "SELECT row_to_json({0})::TEXT FROM {0} {1} ORDER BY audit_id",
+                table.table_name(),
+                table.export_filter()
+            );
+            let mut rows = sqlx::query_scalar::<_, String>(&sql).fetch(&mut *transaction);
+
+            while let Some(row) = rows.next().await {
+                let entry = serde_json::from_str(&row?).map_err(|_| DemonlistError::Core(CoreError::InternalServerError))?;
+
+                entries += 1;
+
+                yield ArchiveEntry::Audit(ArchivedAuditEntry { table, entry });
+            }
+        }
+
+        yield ArchiveEntry::End(ArchiveEnd { entries });
+    }
+}
@@ -0,0 +1,493 @@
+use crate::{
+    archive::{
+        ArchiveEnd, ArchiveEntry, ArchiveHeader, ArchivedAuditEntry, ArchivedCreator, ArchivedDemon, ArchivedList, ArchivedNationality,
+        ArchivedNote, ArchivedPlayer, ArchivedRecord, ArchivedScore, ArchivedSubdivision, ARCHIVE_VERSION,
+    },
+    error::{DemonlistError, Result},
+    player::recompute_scores,
+    score::scoring_fingerprint,
+};
+use serde::Serialize;
+use sqlx::PgConnection;
+use std::collections::{HashMap, HashSet};
+
+/// Scores recomputed after an import may differ from the archived ones by this much due to
+/// floating point inaccuracies
+const SCORE_TOLERANCE: f64 = 1e-6;
+
+/// The number of objects of each kind created by an import
+#[derive(Debug, Serialize, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ImportSummary {
+    pub lists: usize,
+    pub players: usize,
+    pub demons: usize,
+    pub creators: usize,
+    pub records: usize,
+    pub notes: usize,
+    pub audit_entries: usize,
+}
+
+/// Imports an archive entry by entry, validating that it is consistent
+///
+/// Imports can only happen into instances that do not have any players, demons or records yet
+/// (lists of the archive replace existing lists with the same ID). Since entries are written to
+/// the database as soon as they are imported, but the archive as a whole is only validated by
+/// [`ArchiveImporter::finish`], imports should happen inside a transaction that is rolled back on
+/// error.
+///
+/// Entities created by the import are attributed to the user `connection` is audited as (in
+/// addition to the archived audit log entries about them).
+pub struct ArchiveImporter<'c> {
+    connection: &'c mut PgConnection,
+
+    /// The line of the archive currently being imported, for error reporting
+    line: usize,
+
+    header: Option<ArchiveHeader>,
+    end: Option<ArchiveEnd>,
+    last_section: u8,
+
+    /// The number of entries between header and end imported so far
+    entries: usize,
+
+    /// The submitter all imported records are attributed to, created on demand
+    submitter: Option<i32>,
+
+    lists: HashSet<i32>,
+    nationalities: HashSet<String>,
+    subdivisions: HashSet<(String, String)>,
+    players: HashSet<i32>,
+    positions: HashMap<i32, HashSet<i16>>,
+    demons: HashSet<i32>,
+    creators: HashSet<(i32, i32)>,
+    records: HashSet<i32>,
+    notes: HashSet<i32>,
+    scores: Vec<(i32, ArchivedScore)>,
+
+    summary: ImportSummary,
+}
+
+impl<'c> ArchiveImporter<'c> {
+    pub async fn new(connection: &'c mut PgConnection) -> Result<Self> {
+        let not_empty = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM players) OR EXISTS(SELECT 1 FROM demons) OR EXISTS(SELECT 1 FROM records) AS "not_empty!""#
+        )
+        .fetch_one(&mut *connection)
+        .await?;
+
+        if not_empty {
+            return Err(DemonlistError::ImportTargetNotEmpty);
+        }
+
+        Ok(ArchiveImporter {
+            connection,
+            line: 0,
+            header: None,
+            end: None,
+            last_section: 0,
+            entries: 0,
+            submitter: None,
+            lists: HashSet::new(),
+            nationalities: HashSet::new(),
+            subdivisions: HashSet::new(),
+            players: HashSet::new(),
+            positions: HashMap::new(),
+            demons: HashSet::new(),
+            creators: HashSet::new(),
+            records: HashSet::new(),
+            notes: HashSet::new(),
+            scores: Vec::new(),
+            summary: ImportSummary::default(),
+        })
+    }
+
+    /// Parses and imports the next line of the archive. Empty lines are skipped.
+    pub async fn import_line(&mut self, line: &str) -> Result<()> {
+        if line.trim().is_empty() {
+            self.line += 1;
+
+            return Ok(());
+        }
+
+        let entry = serde_json::from_str(line).map_err(|_| DemonlistError::MalformedArchive { line: self.line + 1 })?;
+
+        self.import(entry).await
+    }
+
+    /// Imports the next entry of the archive
+    pub async fn import(&mut self, entry: ArchiveEntry) -> Result<()> {
+        self.line += 1;
+
+        let section = entry.section();
+
+        // The header has to come first, nothing may come after the end, and everything else has to be in order
+        if (section == 0) == self.header.is_some() || self.end.is_some() || section < self.last_section {
+            return Err(self.malformed());
+        }
+
+        self.last_section = section;
+
+        if !matches!(entry, ArchiveEntry::Header(_) | ArchiveEntry::End(_)) {
+            self.entries += 1;
+        }
+
+        match entry {
+            ArchiveEntry::Header(header) => self.import_header(header),
+            ArchiveEntry::List(list) => self.import_list(list).await,
+            ArchiveEntry::Nationality(nationality) => self.import_nationality(nationality).await,
+            ArchiveEntry::Subdivision(subdivision) => self.import_subdivision(subdivision).await,
+            ArchiveEntry::Player(player) => self.import_player(player).await,
+            ArchiveEntry::Demon(demon) => self.import_demon(demon).await,
+            ArchiveEntry::Creator(creator) => self.import_creator(creator).await,
+            ArchiveEntry::Record(record) => self.import_record(record).await,
+            ArchiveEntry::Note(note) => self.import_note(note).await,
+            ArchiveEntry::Audit(entry) => self.import_audit_entry(entry).await,
+            ArchiveEntry::End(end) => self.import_end(end),
+        }
+    }
+
+    /// Validates the list positions and scores of the imported archive
+    ///
+    /// Recomputes all scores using this instance's [`ScoringPolicy`](crate::score::ScoringPolicy).
+    /// The recomputed scores are only compared against the archived ones if the archive was
+    /// exported with the same scoring policy.
+    pub async fn finish(mut self) -> Result<ImportSummary> {
+        let header = self.finish_entries().await?;
+
+        recompute_scores(&mut *self.connection).await?;
+
+        if header.scoring_fingerprint == Some(scoring_fingerprint()) {
+            let mut recomputed: HashMap<(i32, i32), f64> = sqlx::query!("SELECT player, list, score FROM player_scores")
+                .fetch_all(&mut *self.connection)
+                .await?
+                .into_iter()
+                .map(|row| ((row.player, row.list), row.score))
+                .collect();
+
+            for (player_id, archived) in self.scores {
+                let score = recomputed.remove(&(player_id, archived.list)).unwrap_or(0.0);
+
+                if (score - archived.score).abs() > SCORE_TOLERANCE {
+                    return Err(DemonlistError::ArchiveScoreMismatch {
+                        player_id,
+                        list_id: archived.list,
+                    });
+                }
+            }
+
+            // Players with a score that the archive does not know about
+            if let Some((&(player_id, list_id), _)) = recomputed.iter().find(|(_, &score)| score.abs() > SCORE_TOLERANCE) {
+                return Err(DemonlistError::ArchiveScoreMismatch { player_id, list_id });
+            }
+        }
+
+        Ok(self.summary)
+    }
+
+    /// Like [`ArchiveImporter::finish`], but neither recomputes nor validates any scores
+    ///
+    /// For processes that do not know this instance's [`ScoringPolicy`](crate::score::ScoringPolicy),
+    /// which is registered by the server binary. These should schedule a
+    /// [`RecomputeScores`](crate::score::RecomputeScores) job instead, until which the imported
+    /// players have no scores.
+    pub async fn finish_without_scores(mut self) -> Result<ImportSummary> {
+        self.finish_entries().await?;
+
+        Ok(self.summary)
+    }
+
+    /// Validates that the archive was complete and its list positions are consistent, returning its
+    /// header
+    async fn finish_entries(&mut self) -> Result<ArchiveHeader> {
+        let Some(header) = self.header.take().filter(|_| self.end.is_some()) else {
+            return Err(DemonlistError::MalformedArchive { line: self.line + 1 });
+        };
+
+        for (&list_id, positions) in &self.positions {
+            if (1..=positions.len() as i16).any(|position| !positions.contains(&position)) {
+                return Err(DemonlistError::InvalidArchivePositions { list_id });
+            }
+        }
+
+        // Objects created after the import should not collide with imported ones
+        for table in ["lists", "players", "demons", "records", "record_notes"] {
+            sqlx::query(&format!(
+                "SELECT setval(pg_get_serial_sequence('{0}', 'id'), MAX(id)) FROM {0}",
+                table
+            ))
+            .execute(&mut *self.connection)
+            .await?;
+        }
+
+        Ok(header)
+    }
+
+    fn malformed(&self) -> DemonlistError {
+        DemonlistError::MalformedArchive { line: self.line }
+    }
+
+    fn inconsistent(&self) -> DemonlistError {
+        DemonlistError::InconsistentArchive { line: self.line }
+    }
+
+    fn import_header(&mut self, header: ArchiveHeader) -> Result<()> {
+        if header.version != ARCHIVE_VERSION {
+            return Err(DemonlistError::UnsupportedArchiveVersion { version: header.version });
+        }
+
+        self.header = Some(header);
+
+        Ok(())
+    }
+
+    fn import_end(&mut self, end: ArchiveEnd) -> Result<()> {
+        if end.entries != self.entries {
+            return Err(self.malformed());
+        }
+
+        self.end = Some(end);
+
+        Ok(())
+    }
+
+    async fn import_list(&mut self, list: ArchivedList) -> Result<()> {
+        let name_taken = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM lists WHERE name = $1::TEXT AND id <> $2) AS "taken!""#,
+            list.name,
+            list.id
+        )
+        .fetch_one(&mut *self.connection)
+        .await?;
+
+        if name_taken || list.list_size < 1 || list.extended_list_size < list.list_size || !self.lists.insert(list.id) {
+            return Err(self.inconsistent());
+        }
+
+        sqlx::query!(
+            "INSERT INTO lists (id, name, list_size, extended_list_size) VALUES ($1, $2::TEXT, $3, $4) ON CONFLICT (id) DO UPDATE SET name = \
+             EXCLUDED.name, list_size = EXCLUDED.list_size, extended_list_size = EXCLUDED.extended_list_size",
+            list.id,
+            list.name,
+            list.list_size,
+            list.extended_list_size
+        )
+        .execute(&mut *self.connection)
+        .await?;
+
+        self.summary.lists += 1;
+
+        Ok(())
+    }
+
+    /// Nationalities (and subdivisions) are predefined, so ones already known to this instance are
+    /// left unchanged
+    async fn import_nationality(&mut self, nationality: ArchivedNationality) -> Result<()> {
+        if !self.nationalities.insert(nationality.iso_country_code.clone()) {
+            return Err(self.inconsistent());
+        }
+
+        sqlx::query!(
+            "INSERT INTO nationalities (iso_country_code, nation, continent) VALUES ($1, $2::TEXT, CAST($3::TEXT AS continent)) ON \
+             CONFLICT DO NOTHING",
+            nationality.iso_country_code,
+            nationality.nation,
+            nationality.continent
+        )
+        .execute(&mut *self.connection)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn import_subdivision(&mut self, subdivision: ArchivedSubdivision) -> Result<()> {
+        if !self.nationalities.contains(&subdivision.nation)
+            || !self.subdivisions.insert((subdivision.nation.clone(), subdivision.iso_code.clone()))
+        {
+            return Err(self.inconsistent());
+        }
+
+        sqlx::query!(
+            "INSERT INTO subdivisions (iso_code, name, nation) VALUES ($1, $2::TEXT, $3) ON CONFLICT DO NOTHING",
+            subdivision.iso_code,
+            subdivision.name,
+            subdivision.nation
+        )
+        .execute(&mut *self.connection)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn import_player(&mut self, player: ArchivedPlayer) -> Result<()> {
+        let valid_nationality = match (&player.nationality, &player.subdivision) {
+            (None, None) => true,
+            (Some(nation), None) => self.nationalities.contains(nation),
+            (Some(nation), Some(subdivision)) => self.subdivisions.contains(&(nation.clone(), subdivision.clone())),
+            (None, Some(_)) => false,
+        };
+
+        if !valid_nationality || player.scores.iter().any(|score| !self.lists.contains(&score.list)) || !self.players.insert(player.id) {
+            return Err(self.inconsistent());
+        }
+
+        sqlx::query!(
+            "INSERT INTO players (id, name, banned, link_banned, nationality, subdivision) VALUES ($1, $2::TEXT, $3, $4, $5, $6)",
+            player.id,
+            player.name,
+            player.banned,
+            player.link_banned,
+            player.nationality,
+            player.subdivision
+        )
+        .execute(&mut *self.connection)
+        .await?;
+
+        self.scores.extend(player.scores.into_iter().map(|score| (player.id, score)));
+        self.summary.players += 1;
+
+        Ok(())
+    }
+
+    async fn import_demon(&mut self, demon: ArchivedDemon) -> Result<()> {
+        let valid = self.lists.contains(&demon.list)
+            && self.players.contains(&demon.verifier)
+            && self.players.contains(&demon.publisher)
+            && demon.position >= 1
+            && (0..=100).contains(&demon.requirement)
+            && self.positions.entry(demon.list).or_default().insert(demon.position);
+
+        if !valid || !self.demons.insert(demon.id) {
+            return Err(self.inconsistent());
+        }
+
+        sqlx::query!(
+            "INSERT INTO demons (id, list, name, position, requirement, video, thumbnail, verifier, publisher, level_id) VALUES ($1, $2, \
+             $3::TEXT, $4, $5, $6, $7, $8, $9, $10)",
+            demon.id,
+            demon.list,
+            demon.name,
+            demon.position,
+            demon.requirement,
+            demon.video,
+            demon.thumbnail,
+            demon.verifier,
+            demon.publisher,
+            demon.level_id
+        )
+        .execute(&mut *self.connection)
+        .await?;
+
+        self.summary.demons += 1;
+
+        Ok(())
+    }
+
+    async fn import_creator(&mut self, creator: ArchivedCreator) -> Result<()> {
+        if !self.demons.contains(&creator.demon)
+            || !self.players.contains(&creator.player)
+            || !self.creators.insert((creator.demon, creator.player))
+        {
+            return Err(self.inconsistent());
+        }
+
+        sqlx::query!(
+            "INSERT INTO creators (demon, creator) VALUES ($1, $2)",
+            creator.demon,
+            creator.player
+        )
+        .execute(&mut *self.connection)
+        .await?;
+
+        self.summary.creators += 1;
+
+        Ok(())
+    }
+
+    async fn import_record(&mut self, record: ArchivedRecord) -> Result<()> {
+        if !self.demons.contains(&record.demon)
+            || !self.players.contains(&record.player)
+            || !(0..=100).contains(&record.progress)
+            || !self.records.insert(record.id)
+        {
+            return Err(self.inconsistent());
+        }
+
+        let submitter = match self.submitter {
+            Some(submitter) => submitter,
+            None => {
+                // Archives do not contain submitters, so attribute all records to a placeholder that no actual request can
+                // come from
+                let submitter = sqlx::query_scalar!("INSERT INTO submitters (ip_address) VALUES ('0.0.0.0') RETURNING submitter_id")
+                    .fetch_one(&mut *self.connection)
+                    .await?;
+
+                *self.submitter.insert(submitter)
+            },
+        };
+
+        sqlx::query!(
+            "INSERT INTO records (id, progress, video, raw_footage, status_, player, submitter, demon, achieved_on) VALUES ($1, $2, $3, \
+             $4, 'APPROVED', $5, $6, $7, $8)",
+            record.id,
+            record.progress,
+            record.video,
+            record.raw_footage,
+            record.player,
+            submitter,
+            record.demon,
+            record.achieved_on
+        )
+        .execute(&mut *self.connection)
+        .await?;
+
+        self.summary.records += 1;
+
+        Ok(())
+    }
+
+    async fn import_note(&mut self, note: ArchivedNote) -> Result<()> {
+        if !self.records.contains(&note.record) || note.content.trim().is_empty() || !self.notes.insert(note.id) {
+            return Err(self.inconsistent());
+        }
+
+        sqlx::query!(
+            "INSERT INTO record_notes (id, record, content, is_public) VALUES ($1, $2, $3, $4)",
+            note.id,
+            note.record,
+            note.content,
+            note.is_public
+        )
+        .execute(&mut *self.connection)
+        .await?;
+
+        self.summary.notes += 1;
+
+        Ok(())
+    }
+
+    async fn import_audit_entry(&mut self, audit: ArchivedAuditEntry) -> Result<()> {
+        // Columns missing from the entry would be set to NULL, which is only acceptable for the values of modifications
+        if ["time", "userid"]
+            .iter()
+            .any(|column| audit.entry.get(*column).is_none_or(|value| value.is_null()))
+        {
+            return Err(self.malformed());
+        }
+
+        // Audit log entries get new IDs, as they share a sequence with the audit log entries of users, which are not archived
+        let sql = format!(
+            "INSERT INTO {0} SELECT * FROM jsonb_populate_record(NULL::{0}, $1::JSONB || jsonb_build_object('audit_id', \
+             nextval('audit_log2_audit_id_seq')))",
+            audit.table.table_name()
+        );
+
+        sqlx::query(&sql)
+            .bind(serde_json::to_string(&audit.entry).map_err(|_| self.malformed())?)
+            .execute(&mut *self.connection)
+            .await?;
+
+        self.summary.audit_entries += 1;
+
+        Ok(())
+    }
+}
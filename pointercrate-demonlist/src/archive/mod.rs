@@ -0,0 +1,276 @@
+//! Module for exporting all demonlist data of a pointercrate instance into a portable archive, and
+//! for importing such archives into a fresh instance
+//!
+//! # Archive format
+//!
+//! Archives are [newline delimited JSON](https://github.com/ndjson/ndjson-spec): every line is a
+//! JSON object describing one [`ArchiveEntry`], whose kind is given by its `type` field. Entries
+//! appear in the following order:
+//!
+//! 1. A single `header` entry, stating the [`ARCHIVE_VERSION`] of the archive
+//! 2. `list` entries, one per list hosted on the instance
+//! 3. `nationality` and `subdivision` entries
+//! 4. `player` entries, including the players' scores at the time of export
+//! 5. `demon` entries
+//! 6. `creator` entries
+//! 7. `record` entries (only approved records are exported)
+//! 8. `note` entries of the exported records
+//! 9. `audit` entries, each being a row of one of the [`AuditTable`]s
+//! 10. A single `end` entry, stating the number of entries between header and end
+//!
+//! For example:
+//!
+//! ```text
+//! {"type":"header","version":1,"exported_at":"2025-10-28T09:00:00","scoring_fingerprint":123}
+//! {"type":"list","id":1,"name":"demonlist","list_size":75,"extended_list_size":150}
+//! {"type":"player","id":1,"name":"stardust1971","banned":false,"link_banned":false,"nationality":null,"subdivision":null,"scores":[{"list":1,"score":350.0}]}
+//! {"type":"demon","id":1,"list":1,"name":"Bloodbath","position":1,"requirement":50,"video":null,"thumbnail":"https://i.ytimg.com/vi/zebrafishes/mqdefault.jpg","verifier":1,"publisher":1,"level_id":null}
+//! {"type":"record","id":1,"progress":100,"video":null,"raw_footage":null,"player":1,"demon":1,"achieved_on":null}
+//! {"type":"audit","table":"demon_additions","entry":{"time":"2025-10-27T12:00:00","audit_id":1,"userid":1,"id":1}}
+//! {"type":"end","entries":4}
+//! ```
+//!
+//! Objects keep their IDs across export and import, and every entry may only reference objects
+//! that appeared earlier in the archive. Audit entries are the exception, as they may describe
+//! objects that have since been deleted. The `userid` of audit entries refers to the users of the
+//! exporting instance, since users are not part of archives.
+
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+pub use export::export;
+pub use import::{ArchiveImporter, ImportSummary};
+
+mod export;
+mod import;
+
+/// The version of the archive format produced by this version of pointercrate
+///
+/// Bumped whenever the format changes in a way older versions cannot import.
+pub const ARCHIVE_VERSION: u32 = 1;
+
+/// A single line of an archive
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ArchiveEntry {
+    Header(ArchiveHeader),
+    List(ArchivedList),
+    Nationality(ArchivedNationality),
+    Subdivision(ArchivedSubdivision),
+    Player(ArchivedPlayer),
+    Demon(ArchivedDemon),
+    Creator(ArchivedCreator),
+    Record(ArchivedRecord),
+    Note(ArchivedNote),
+    Audit(ArchivedAuditEntry),
+    End(ArchiveEnd),
+}
+
+impl ArchiveEntry {
+    /// The position of this kind of entry in the order given in the [module level
+    /// documentation](self)
+    fn section(&self) -> u8 {
+        match self {
+            ArchiveEntry::Header(_) => 0,
+            ArchiveEntry::List(_) => 1,
+            ArchiveEntry::Nationality(_) => 2,
+            ArchiveEntry::Subdivision(_) => 3,
+            ArchiveEntry::Player(_) => 4,
+            ArchiveEntry::Demon(_) => 5,
+            ArchiveEntry::Creator(_) => 6,
+            ArchiveEntry::Record(_) => 7,
+            ArchiveEntry::Note(_) => 8,
+            ArchiveEntry::Audit(_) => 9,
+            ArchiveEntry::End(_) => 10,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ArchiveHeader {
+    pub version: u32,
+
+    /// The (UTC) time at which the export was started
+    pub exported_at: NaiveDateTime,
+
+    /// The fingerprint of the [`ScoringPolicy`](crate::score::ScoringPolicy) the exported scores
+    /// were computed with, if known
+    pub scoring_fingerprint: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ArchivedList {
+    pub id: i32,
+    pub name: String,
+    pub list_size: i16,
+    pub extended_list_size: i16,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ArchivedNationality {
+    pub iso_country_code: String,
+    pub nation: String,
+    pub continent: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ArchivedSubdivision {
+    pub iso_code: String,
+    pub name: String,
+    pub nation: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ArchivedPlayer {
+    pub id: i32,
+    pub name: String,
+    pub banned: bool,
+    pub link_banned: bool,
+    pub nationality: Option<String>,
+    pub subdivision: Option<String>,
+
+    /// The player's score on every list they have score giving records on
+    pub scores: Vec<ArchivedScore>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct ArchivedScore {
+    pub list: i32,
+    pub score: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ArchivedDemon {
+    pub id: i32,
+    pub list: i32,
+    pub name: String,
+    pub position: i16,
+    pub requirement: i16,
+    pub video: Option<String>,
+    pub thumbnail: String,
+    pub verifier: i32,
+    pub publisher: i32,
+    pub level_id: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct ArchivedCreator {
+    pub demon: i32,
+    pub player: i32,
+}
+
+/// An approved record
+///
+/// Submitters are not part of archives, as they are only identified by their IP address.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ArchivedRecord {
+    pub id: i32,
+    pub progress: i16,
+    pub video: Option<String>,
+    pub raw_footage: Option<String>,
+    pub player: i32,
+    pub demon: i32,
+
+    /// Not present in archives exported before records had achievement dates
+    #[serde(default)]
+    pub achieved_on: Option<NaiveDate>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ArchivedNote {
+    pub id: i32,
+    pub record: i32,
+    pub content: String,
+    pub is_public: bool,
+}
+
+/// A row of one of the audit log tables, exactly as stored in the database
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ArchivedAuditEntry {
+    pub table: AuditTable,
+    pub entry: Map<String, Value>,
+}
+
+/// The audit log tables that are part of archives
+///
+/// The audit logs of users and submitters are not archived, since neither users nor submitters
+/// are.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditTable {
+    DemonAdditions,
+    DemonModifications,
+    DemonDeletions,
+    CreatorAdditions,
+    CreatorDeletions,
+    PlayerAdditions,
+    PlayerModifications,
+    PlayerDeletions,
+    PlayerMerges,
+    RecordAdditions,
+    RecordModifications,
+    RecordNotesAdditions,
+    RecordNotesModifications,
+}
+
+impl AuditTable {
+    pub const ALL: [AuditTable; 13] = [
+        AuditTable::DemonAdditions,
+        AuditTable::DemonModifications,
+        AuditTable::DemonDeletions,
+        AuditTable::CreatorAdditions,
+        AuditTable::CreatorDeletions,
+        AuditTable::PlayerAdditions,
+        AuditTable::PlayerModifications,
+        AuditTable::PlayerDeletions,
+        AuditTable::PlayerMerges,
+        AuditTable::RecordAdditions,
+        AuditTable::RecordModifications,
+        AuditTable::RecordNotesAdditions,
+        AuditTable::RecordNotesModifications,
+    ];
+
+    pub fn table_name(self) -> &'static str {
+        match self {
+            AuditTable::DemonAdditions => "demon_additions",
+            AuditTable::DemonModifications => "demon_modifications",
+            AuditTable::DemonDeletions => "demon_deletions",
+            AuditTable::CreatorAdditions => "creator_additions",
+            AuditTable::CreatorDeletions => "creator_deletions",
+            AuditTable::PlayerAdditions => "player_additions",
+            AuditTable::PlayerModifications => "player_modifications",
+            AuditTable::PlayerDeletions => "player_deletions",
+            AuditTable::PlayerMerges => "player_merges",
+            AuditTable::RecordAdditions => "record_additions",
+            AuditTable::RecordModifications => "record_modifications",
+            AuditTable::RecordNotesAdditions => "record_notes_additions",
+            AuditTable::RecordNotesModifications => "record_notes_modifications",
+        }
+    }
+
+    /// Condition restricting the exported rows of this table to those about exported objects
+    ///
+    /// Only relevant for records and notes, since not all of them are exported. Since the audit
+    /// logs of deleted records and notes would thus be incomplete anyway, they are not exported at
+    /// all.
+    fn export_filter(self) -> &'static str {
+        match self {
+            AuditTable::RecordAdditions | AuditTable::RecordModifications => {
+                "WHERE id IN (SELECT id FROM records WHERE status_ = 'APPROVED')"
+            },
+            AuditTable::RecordNotesAdditions | AuditTable::RecordNotesModifications => {
+                "WHERE id IN (SELECT record_notes.id FROM record_notes INNER JOIN records ON records.id = record_notes.record WHERE \
+                 records.status_ = 'APPROVED')"
+            },
+            _ => "",
+        }
+    }
+}
+
+/// The last line of an archive, used to detect truncated archives
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct ArchiveEnd {
+    /// The number of entries between the header and this entry
+    pub entries: usize,
+}
@@ -1,9 +1,150 @@
-use pointercrate_core::util::from_env_or_default;
+use pointercrate_core::config::{override_from_env, ConfigError, ConfigSection, Section};
+use serde::Deserialize;
 
-pub fn list_size() -> i16 {
-    from_env_or_default("LIST_SIZE", 50)
+static DEMONLIST: Section<DemonlistConfig> = Section::new();
+
+/// The `[demonlist]` configuration table
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct DemonlistConfig {
+    /// The number of demons on the main list (environment variable `LIST_SIZE`)
+    pub list_size: i16,
+
+    /// The number of demons on the main and extended list combined (environment variable
+    /// `EXTENDED_LIST_SIZE`)
+    pub extended_list_size: i16,
+
+    /// For how many minutes reviewers can claim a submission before it can be claimed by someone
+    /// else (environment variable `RECORD_CLAIM_MINUTES`)
+    pub record_claim_minutes: i32,
+
+    /// The keys of the reasons reviewers can give when rejecting a record. The description of each
+    /// reason is the localized text with id `rejection-reason-<key>`.
+    pub rejection_reasons: Vec<String>,
+
+    /// Whether reviewers have to give one of the `rejection_reasons` when rejecting a record
+    /// (environment variable `REQUIRE_REJECTION_REASON`)
+    pub require_rejection_reason: bool,
+
+    /// The maximum number of operations a single batch request can contain (environment variable
+    /// `MAX_BATCH_SIZE`)
+    pub max_batch_size: usize,
+}
+
+impl Default for DemonlistConfig {
+    fn default() -> Self {
+        DemonlistConfig {
+            list_size: 50,
+            extended_list_size: 100,
+            record_claim_minutes: 30,
+            rejection_reasons: ["no-clicks", "cheat-indicator", "wrong-level-version", "unclear-video", "duplicate"]
+                .map(ToString::to_string)
+                .to_vec(),
+            require_rejection_reason: false,
+            max_batch_size: 50,
+        }
+    }
+}
+
+impl ConfigSection for DemonlistConfig {
+    const NAME: &'static str = "demonlist";
+
+    fn apply_env(&mut self) -> Result<(), ConfigError> {
+        override_from_env(&mut self.list_size, "LIST_SIZE")?;
+        override_from_env(&mut self.extended_list_size, "EXTENDED_LIST_SIZE")?;
+        override_from_env(&mut self.record_claim_minutes, "RECORD_CLAIM_MINUTES")?;
+        override_from_env(&mut self.require_rejection_reason, "REQUIRE_REJECTION_REASON")?;
+        override_from_env(&mut self.max_batch_size, "MAX_BATCH_SIZE")
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.list_size < 1 {
+            return Err(ConfigError::Invalid {
+                section: Self::NAME,
+                setting: "list_size",
+                message: "must be positive".to_string(),
+            });
+        }
+
+        if self.extended_list_size < self.list_size {
+            return Err(ConfigError::Invalid {
+                section: Self::NAME,
+                setting: "extended_list_size",
+                message: format!("must be at least list_size ({})", self.list_size),
+            });
+        }
+
+        if self.record_claim_minutes < 1 {
+            return Err(ConfigError::Invalid {
+                section: Self::NAME,
+                setting: "record_claim_minutes",
+                message: "must be positive".to_string(),
+            });
+        }
+
+        for (index, reason) in self.rejection_reasons.iter().enumerate() {
+            if reason.is_empty() || !reason.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-') {
+                return Err(ConfigError::Invalid {
+                    section: Self::NAME,
+                    setting: "rejection_reasons",
+                    message: format!("'{}' must only consist of lowercase letters, digits and dashes", reason),
+                });
+            }
+
+            if self.rejection_reasons[..index].contains(reason) {
+                return Err(ConfigError::Invalid {
+                    section: Self::NAME,
+                    setting: "rejection_reasons",
+                    message: format!("'{}' is listed more than once", reason),
+                });
+            }
+        }
+
+        if self.max_batch_size < 1 {
+            return Err(ConfigError::Invalid {
+                section: Self::NAME,
+                setting: "max_batch_size",
+                message: "must be positive".to_string(),
+            });
+        }
+
+        if self.require_rejection_reason && self.rejection_reasons.is_empty() {
+            return Err(ConfigError::Invalid {
+                section: Self::NAME,
+                setting: "require_rejection_reason",
+                message: "requires at least one rejection reason to be configured".to_string(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Loads and validates the `[demonlist]` configuration table
+pub fn init() -> Result<(), ConfigError> {
+    DEMONLIST.init().map(|_| ())
+}
+
+pub fn list_size() -> Result<i16, ConfigError> {
+    Ok(DEMONLIST.get()?.list_size)
+}
+
+pub fn extended_list_size() -> Result<i16, ConfigError> {
+    Ok(DEMONLIST.get()?.extended_list_size)
+}
+
+pub fn record_claim_minutes() -> Result<i32, ConfigError> {
+    Ok(DEMONLIST.get()?.record_claim_minutes)
+}
+
+pub fn rejection_reasons() -> Result<&'static [String], ConfigError> {
+    Ok(&DEMONLIST.get()?.rejection_reasons)
+}
+
+pub fn require_rejection_reason() -> Result<bool, ConfigError> {
+    Ok(DEMONLIST.get()?.require_rejection_reason)
 }
 
-pub fn extended_list_size() -> i16 {
-    from_env_or_default("EXTENDED_LIST_SIZE", 100)
+pub fn max_batch_size() -> Result<usize, ConfigError> {
+    Ok(DEMONLIST.get()?.max_batch_size)
 }
@@ -1,4 +1,4 @@
-use crate::error::Result;
+use crate::error::{DemonlistError, Result};
 
 use crate::demon::MinimalDemon;
 use chrono::{NaiveDateTime, NaiveTime};
@@ -24,6 +24,7 @@ pub enum MovementReason {
     Moved,
     OtherAddedAbove { other: NamedId },
     OtherMoved { other: NamedId },
+    OtherDeletedAbove { other: NamedId },
     Unknown,
 }
 
@@ -44,6 +45,8 @@ pub async fn movement_log_for_demon(demon_id: i32, connection: &mut PgConnection
     let mut additions = HashMap::new();
     // map time -> NamedId keeping track when movements to -1 happened
     let mut all_moves = HashMap::new();
+    // map time -> NamedId keeping track of all deletions
+    let mut deletions = HashMap::new();
 
     {
         // non-lexical lifetimes working amazingly I see >.>
@@ -84,6 +87,29 @@ pub async fn movement_log_for_demon(demon_id: i32, connection: &mut PgConnection
         }
     }
 
+    {
+        // Only deletions of demons above this one (on the same list) can have moved it
+        let mut deletion_stream = sqlx::query!(
+            r#"SELECT time, id, name::TEXT FROM demon_deletions
+               WHERE list = (SELECT list FROM demons WHERE id = $1 UNION ALL SELECT list FROM demon_deletions WHERE id = $1 LIMIT 1)
+               AND position < (SELECT MAX(position) FROM (SELECT position FROM demons WHERE id = $1
+                                                          UNION ALL SELECT position FROM demon_modifications WHERE id = $1) AS positions)"#,
+            demon_id
+        )
+        .fetch(&mut *connection);
+
+        while let Some(row) = deletion_stream.next().await {
+            let row = row?;
+            deletions.insert(
+                row.time,
+                NamedId {
+                    id: row.id,
+                    name: row.name,
+                },
+            );
+        }
+    }
+
     for log_entry in audit_log {
         let time = log_entry.time;
 
@@ -135,16 +161,24 @@ pub async fn movement_log_for_demon(demon_id: i32, connection: &mut PgConnection
                         }),
                         None => {
                             let added_demon = additions.get(&time);
+                            let deleted_demon = deletions.get(&time);
 
-                            match added_demon {
-                                Some(added_demon) => movement_log.push(MovementLogEntry {
+                            match (added_demon, deleted_demon) {
+                                (Some(added_demon), _) => movement_log.push(MovementLogEntry {
                                     reason: MovementReason::OtherAddedAbove {
                                         other: added_demon.clone(),
                                     },
                                     new_position: None,
                                     time,
                                 }),
-                                None => movement_log.push(MovementLogEntry {
+                                (None, Some(deleted_demon)) => movement_log.push(MovementLogEntry {
+                                    reason: MovementReason::OtherDeletedAbove {
+                                        other: deleted_demon.clone(),
+                                    },
+                                    new_position: None,
+                                    time,
+                                }),
+                                (None, None) => movement_log.push(MovementLogEntry {
                                     reason: MovementReason::Unknown,
                                     new_position: None,
                                     time,
@@ -165,16 +199,21 @@ pub async fn movement_log_for_demon(demon_id: i32, connection: &mut PgConnection
                     // audit logs accurately) :(
                 }
             },
-            AuditLogEntryType::Deletion => unreachable!(),
+            // Deletion is always the last entry, and there is no position after it
+            AuditLogEntryType::Deletion => (),
         }
     }
 
-    // update the last entry with the current position
-    MinimalDemon::by_id(demon_id, &mut *connection).await.map(|minimal_demon| {
-        if let Some(entry) = movement_log.last_mut() {
-            entry.new_position = Some(minimal_demon.position)
-        }
-    })?;
+    // update the last entry with the current position, unless the demon has been deleted
+    match MinimalDemon::by_id(demon_id, &mut *connection).await {
+        Ok(minimal_demon) => {
+            if let Some(entry) = movement_log.last_mut() {
+                entry.new_position = Some(minimal_demon.position)
+            }
+        },
+        Err(DemonlistError::DemonNotFound { .. }) => (),
+        Err(err) => return Err(err),
+    }
 
     Ok(movement_log)
 }
@@ -227,7 +266,7 @@ pub async fn audit_log_for_demon(demon_id: i32, connection: &mut PgConnection) -
                 "#,
         demon_id
     )
-    .fetch(connection);
+    .fetch(&mut *connection);
 
     while let Some(modification) = modification_stream.next().await {
         let row = modification?;
@@ -263,5 +302,31 @@ pub async fn audit_log_for_demon(demon_id: i32, connection: &mut PgConnection) -
         })
     }
 
+    // un-borrow `connection`
+    drop(modification_stream);
+
+    let deletion_row = sqlx::query!(
+        r#"SELECT time, audit_id, 
+                  userid,
+                  members.name AS "name?"
+                  FROM demon_deletions LEFT OUTER JOIN members ON members.member_id = userid WHERE id = $1"#,
+        demon_id
+    )
+    .fetch_optional(&mut *connection)
+    .await?;
+
+    if let Some(deletion) = deletion_row {
+        entries.push(AuditLogEntry {
+            time: deletion.time,
+            entry_id: deletion.audit_id,
+            id: demon_id,
+            user: NamedId {
+                name: deletion.name,
+                id: deletion.userid,
+            },
+            r#type: AuditLogEntryType::Deletion,
+        });
+    }
+
     Ok(entries)
 }
@@ -0,0 +1,42 @@
+use crate::{demon::FullDemon, error::Result, player::recompute_scores};
+use log::info;
+use sqlx::PgConnection;
+
+impl FullDemon {
+    /// Deletes this demon together with all its records and creators, and closes the gap it leaves
+    /// in its list by moving all demons below it up by one position.
+    ///
+    /// Nothing is lost for good: the audit log triggers archive a copy of every deleted record,
+    /// creator and of the demon itself.
+    pub async fn delete(self, connection: &mut PgConnection) -> Result<()> {
+        info!("Deleting demon {}", self);
+
+        // Associated notes get deleted due to the ON DELETE CASCADE on record_notes.record
+        let records = sqlx::query!("DELETE FROM records WHERE demon = $1", self.demon.base.id)
+            .execute(&mut *connection)
+            .await?;
+
+        info!("Deleted {} records on demon {}", records.rows_affected(), self);
+
+        sqlx::query!("DELETE FROM creators WHERE demon = $1", self.demon.base.id)
+            .execute(&mut *connection)
+            .await?;
+
+        sqlx::query!("DELETE FROM demons WHERE id = $1", self.demon.base.id)
+            .execute(&mut *connection)
+            .await?;
+
+        sqlx::query!(
+            "UPDATE demons SET position = position - 1 WHERE position > $1 AND list = $2",
+            self.demon.base.position,
+            self.demon.base.list
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        // Every demon below the deleted one moved, and the records on the deleted demon no longer count
+        recompute_scores(connection).await?;
+
+        Ok(())
+    }
+}
@@ -8,6 +8,7 @@ use crate::{
     error::{DemonlistError, Result},
     player::DatabasePlayer,
     record::MinimalRecordP,
+    score::scoring_policy,
 };
 use derive_more::Display;
 use log::info;
@@ -19,6 +20,7 @@ use std::{
     hash::{Hash, Hasher},
 };
 
+mod delete;
 #[macro_use]
 mod get;
 pub mod audit;
@@ -32,7 +34,7 @@ pub struct TimeShiftedDemon {
 }
 
 /// Struct modelling a demon. These objects are returned from the paginating `/demons/` endpoint
-#[derive(Debug, Deserialize, Serialize, Hash, Display, Eq, PartialEq)]
+#[derive(Debug, Deserialize, Serialize, Hash, Display, Eq, PartialEq, Clone)]
 #[display("{}", base)]
 pub struct Demon {
     #[serde(flatten)]
@@ -75,6 +77,9 @@ pub struct MinimalDemon {
     ///
     /// Note that the name doesn't need to be unique!
     pub name: String,
+
+    /// The id of the [`List`](crate::list::List) this [`Demon`] is on
+    pub list: i32,
 }
 
 /// Struct modelling the "full" version of a demon.
@@ -136,10 +141,10 @@ impl Demon {
         Ok(level_id as u64)
     }
 
-    pub async fn validate_position(position: i16, connection: &mut PgConnection) -> Result<()> {
+    pub async fn validate_position(position: i16, list: i32, connection: &mut PgConnection) -> Result<()> {
         // To prevent holes from being created in the list, the new position must lie between 1 and (current
         // last position + 1), inclusive
-        let maximal_position = Demon::max_position(connection).await? + 1;
+        let maximal_position = Demon::max_position(list, connection).await? + 1;
 
         if position > maximal_position || position < 1 {
             return Err(DemonlistError::InvalidPosition { maximal: maximal_position });
@@ -148,48 +153,37 @@ impl Demon {
         Ok(())
     }
 
-    /// Increments the position of all demons with positions equal to or greater than the given one,
-    /// by one.
-    async fn shift_down(starting_at: i16, connection: &mut PgConnection) -> Result<()> {
-        info!("Shifting down all demons, starting at {}", starting_at);
+    /// Increments the position of all demons on the given list with positions equal to or greater
+    /// than the given one, by one.
+    async fn shift_down(starting_at: i16, list: i32, connection: &mut PgConnection) -> Result<()> {
+        info!("Shifting down all demons on list {}, starting at {}", list, starting_at);
 
-        sqlx::query!("UPDATE demons SET position = position + 1 WHERE position >= $1", starting_at)
-            .execute(connection)
-            .await?;
+        sqlx::query!(
+            "UPDATE demons SET position = position + 1 WHERE position >= $1 AND list = $2",
+            starting_at,
+            list
+        )
+        .execute(connection)
+        .await?;
 
         Ok(())
     }
 
-    /// Gets the current max position a demon has, or `0` if there are no demons
-    /// in the database
-    pub async fn max_position(connection: &mut PgConnection) -> Result<i16> {
-        Ok(sqlx::query!("SELECT MAX(position) as max_position FROM demons")
-            .fetch_one(connection)
-            .await?
-            .max_position
-            .unwrap_or(0))
+    /// Gets the current max position a demon on the given list has, or `0` if there are no demons
+    /// on that list
+    pub async fn max_position(list: i32, connection: &mut PgConnection) -> Result<i16> {
+        Ok(
+            sqlx::query!("SELECT MAX(position) as max_position FROM demons WHERE list = $1", list)
+                .fetch_one(connection)
+                .await?
+                .max_position
+                .unwrap_or(0),
+        )
     }
 
+    /// The score a record with the given progress on this demon is worth, according to the
+    /// configured [`ScoringPolicy`](crate::score::ScoringPolicy)
     pub fn score(&self, progress: i16) -> f64 {
-        if progress < self.requirement {
-            return 0.0;
-        }
-
-        let position = self.base.position;
-
-        let beaten_score = match position {
-            56..=150 => 1.039035131_f64 * ((185.7_f64 * (-0.02715_f64 * position as f64).exp()) + 14.84_f64),
-            36..=55 => 1.0371139743_f64 * ((212.61_f64 * 1.036_f64.powf(1_f64 - position as f64)) + 25.071_f64),
-            21..=35 => ((250_f64 - 83.389_f64) * (1.0099685_f64.powf(2_f64 - position as f64)) - 31.152_f64) * 1.0371139743_f64,
-            4..=20 => ((326.1_f64 * (-0.0871_f64 * position as f64).exp()) + 51.09_f64) * 1.037117142_f64,
-            1..=3 => (-18.2899079915_f64 * position as f64) + 368.2899079915_f64,
-            _ => 0_f64,
-        };
-
-        if progress != 100 {
-            (beaten_score * (5f64.powf((progress - self.requirement) as f64 / (100f64 - self.requirement as f64)))) / 10f64
-        } else {
-            beaten_score
-        }
+        scoring_policy(self.base.list).score(self.base.position, self.requirement, progress)
     }
 }
@@ -5,11 +5,15 @@ use crate::{
 use futures::stream::StreamExt;
 use pointercrate_core::{
     first_and_last,
-    pagination::{PageContext, Paginatable, PaginationParameters, PaginationQuery, __pagination_compat},
+    pagination::{__pagination_compat, count_query, PageContext, Paginatable, PaginationParameters, PaginationQuery},
     util::non_nullable,
 };
 use serde::{Deserialize, Serialize};
-use sqlx::{PgConnection, Row};
+use sqlx::{
+    postgres::{PgArguments, PgConnection},
+    query::Query,
+    Postgres, Row,
+};
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct DemonIdPagination {
@@ -45,6 +49,11 @@ pub struct DemonIdPagination {
     #[serde(default, deserialize_with = "non_nullable")]
     #[serde(rename = "requirement__lt")]
     requirement_lt: Option<i16>,
+
+    /// The list to paginate. Not part of the query string, but determined by where the endpoint
+    /// was mounted.
+    #[serde(skip)]
+    pub list: Option<i32>,
 }
 
 impl PaginationQuery for DemonIdPagination {
@@ -60,30 +69,41 @@ impl PaginationQuery for DemonIdPagination {
     }
 }
 
-impl Paginatable<DemonIdPagination> for Demon {
-    first_and_last!("demons");
-
-    async fn page(query: &DemonIdPagination, connection: &mut PgConnection) -> Result<(Vec<Demon>, PageContext), sqlx::Error> {
-        let order = query.params.order();
+impl DemonIdPagination {
+    fn sql(&self) -> String {
+        let order = self.params.order();
 
-        let sql_query = format!(include_str!("../../sql/paginate_demons_by_id.sql"), order);
+        format!(include_str!("../../sql/paginate_demons_by_id.sql"), order)
+    }
 
+    /// Binds the parameters of the given query, which is (derived from) the one returned by
+    /// [`DemonIdPagination::sql`], returning at most `limit` rows (if given)
+    fn sql_query<'q>(&'q self, sql: &'q str, limit: Option<i32>) -> Query<'q, Postgres, PgArguments> {
         // FIXME(sqlx) once CITEXT is supported
-        let mut stream = sqlx::query(&sql_query)
-            .bind(query.params.before)
-            .bind(query.params.after)
-            .bind(query.name.as_deref())
-            .bind(query.requirement)
-            .bind(query.requirement_lt)
-            .bind(query.requirement_gt)
-            .bind(query.verifier_id)
-            .bind(query.verifier_name.as_deref())
-            .bind(query.publisher_id)
-            .bind(query.publisher_name.as_deref())
-            .bind(query.name_contains.as_deref())
-            .bind(query.level_id)
-            .bind(query.params.limit + 1)
-            .fetch(connection);
+        sqlx::query(sql)
+            .bind(self.params.before)
+            .bind(self.params.after)
+            .bind(self.name.as_deref())
+            .bind(self.requirement)
+            .bind(self.requirement_lt)
+            .bind(self.requirement_gt)
+            .bind(self.verifier_id)
+            .bind(self.verifier_name.as_deref())
+            .bind(self.publisher_id)
+            .bind(self.publisher_name.as_deref())
+            .bind(self.name_contains.as_deref())
+            .bind(self.level_id)
+            .bind(limit)
+            .bind(self.list)
+    }
+}
+
+impl Paginatable<DemonIdPagination> for Demon {
+    first_and_last!(DemonIdPagination, "demons");
+
+    async fn fetch(query: &DemonIdPagination, connection: &mut PgConnection) -> Result<(Vec<Demon>, PageContext), sqlx::Error> {
+        let sql = query.sql();
+        let mut stream = query.sql_query(&sql, Some(query.params.limit + 1)).fetch(connection);
 
         let mut demons = Vec::new();
 
@@ -97,6 +117,7 @@ impl Paginatable<DemonIdPagination> for Demon {
                     id: row.get("demon_id"),
                     name: row.get("demon_name"),
                     position: row.get("position"),
+                    list: row.get("list"),
                 },
                 requirement: row.get("requirement"),
                 video,
@@ -118,6 +139,13 @@ impl Paginatable<DemonIdPagination> for Demon {
         Ok(__pagination_compat(&query.params, demons))
     }
 
+    async fn count(query: &DemonIdPagination, connection: &mut PgConnection) -> Result<Option<i64>, sqlx::Error> {
+        let query = query.with_parameters(PaginationParameters::default());
+        let sql = count_query(&query.sql());
+
+        Ok(Some(query.sql_query(&sql, None).fetch_one(connection).await?.try_get(0)?))
+    }
+
     fn pagination_id(&self) -> i32 {
         self.base.id
     }
@@ -157,6 +185,11 @@ pub struct DemonPositionPagination {
     #[serde(default, deserialize_with = "non_nullable")]
     #[serde(rename = "requirement__lt")]
     pub requirement_lt: Option<i16>,
+
+    /// The list to paginate. Not part of the query string, but determined by where the endpoint
+    /// was mounted.
+    #[serde(skip)]
+    pub list: Option<i32>,
 }
 
 impl PaginationQuery for DemonPositionPagination {
@@ -172,30 +205,41 @@ impl PaginationQuery for DemonPositionPagination {
     }
 }
 
-impl Paginatable<DemonPositionPagination> for Demon {
-    first_and_last!("demons", "position");
-
-    async fn page(query: &DemonPositionPagination, connection: &mut PgConnection) -> Result<(Vec<Demon>, PageContext), sqlx::Error> {
-        let order = query.params.order();
+impl DemonPositionPagination {
+    fn sql(&self) -> String {
+        let order = self.params.order();
 
-        let sql_query = format!(include_str!("../../sql/paginate_demons_by_position.sql"), order);
+        format!(include_str!("../../sql/paginate_demons_by_position.sql"), order)
+    }
 
+    /// Binds the parameters of the given query, which is (derived from) the one returned by
+    /// [`DemonPositionPagination::sql`], returning at most `limit` rows (if given)
+    fn sql_query<'q>(&'q self, sql: &'q str, limit: Option<i32>) -> Query<'q, Postgres, PgArguments> {
         // FIXME(sqlx) once CITEXT is supported
-        let mut stream = sqlx::query(&sql_query)
-            .bind(query.params.before)
-            .bind(query.params.after)
-            .bind(query.name.as_deref())
-            .bind(query.requirement)
-            .bind(query.requirement_lt)
-            .bind(query.requirement_gt)
-            .bind(query.verifier_id)
-            .bind(query.verifier_name.as_deref())
-            .bind(query.publisher_id)
-            .bind(query.publisher_name.as_deref())
-            .bind(query.name_contains.as_deref())
-            .bind(query.level_id)
-            .bind(query.params.limit + 1)
-            .fetch(connection);
+        sqlx::query(sql)
+            .bind(self.params.before)
+            .bind(self.params.after)
+            .bind(self.name.as_deref())
+            .bind(self.requirement)
+            .bind(self.requirement_lt)
+            .bind(self.requirement_gt)
+            .bind(self.verifier_id)
+            .bind(self.verifier_name.as_deref())
+            .bind(self.publisher_id)
+            .bind(self.publisher_name.as_deref())
+            .bind(self.name_contains.as_deref())
+            .bind(self.level_id)
+            .bind(limit)
+            .bind(self.list)
+    }
+}
+
+impl Paginatable<DemonPositionPagination> for Demon {
+    first_and_last!(DemonPositionPagination, "demons", "position");
+
+    async fn fetch(query: &DemonPositionPagination, connection: &mut PgConnection) -> Result<(Vec<Demon>, PageContext), sqlx::Error> {
+        let sql = query.sql();
+        let mut stream = query.sql_query(&sql, Some(query.params.limit + 1)).fetch(connection);
 
         let mut demons = Vec::new();
 
@@ -209,6 +253,7 @@ impl Paginatable<DemonPositionPagination> for Demon {
                     id: row.get("demon_id"),
                     name: row.get("demon_name"),
                     position: row.get("position"),
+                    list: row.get("list"),
                 },
                 requirement: row.get("requirement"),
                 video,
@@ -230,6 +275,13 @@ impl Paginatable<DemonPositionPagination> for Demon {
         Ok(__pagination_compat(&query.params, demons))
     }
 
+    async fn count(query: &DemonPositionPagination, connection: &mut PgConnection) -> Result<Option<i64>, sqlx::Error> {
+        let query = query.with_parameters(PaginationParameters::default());
+        let sql = count_query(&query.sql());
+
+        Ok(Some(query.sql_query(&sql, None).fetch_one(connection).await?.try_get(0)?))
+    }
+
     fn pagination_id(&self) -> i32 {
         self.base.position as i32
     }
@@ -2,6 +2,7 @@ use crate::{
     demon::{Demon, FullDemon, MinimalDemon},
     error::{DemonlistError, Result},
     player::{recompute_scores, DatabasePlayer},
+    video::VideoHosts,
 };
 use log::{debug, info, warn};
 use pointercrate_core::util::{non_nullable, nullable};
@@ -33,10 +34,10 @@ pub struct PatchDemon {
 }
 
 impl FullDemon {
-    pub async fn apply_patch(mut self, patch: PatchDemon, connection: &mut PgConnection) -> Result<Self> {
+    pub async fn apply_patch(mut self, patch: PatchDemon, video_hosts: &VideoHosts, connection: &mut PgConnection) -> Result<Self> {
         let changes_requirement = patch.requirement.is_some();
 
-        let updated_demon = self.demon.apply_patch(patch, connection).await?;
+        let updated_demon = self.demon.apply_patch(patch, video_hosts, connection).await?;
 
         if changes_requirement {
             self.records.retain(|record| record.progress >= updated_demon.requirement);
@@ -51,7 +52,7 @@ impl FullDemon {
 
 impl Demon {
     /// Must run inside a transaction!
-    pub async fn apply_patch(mut self, patch: PatchDemon, connection: &mut PgConnection) -> Result<Self> {
+    pub async fn apply_patch(mut self, patch: PatchDemon, video_hosts: &VideoHosts, connection: &mut PgConnection) -> Result<Self> {
         // duplicate names are OK nowadays
 
         if let Some(position) = patch.position {
@@ -65,7 +66,7 @@ impl Demon {
         if let Some(video) = patch.video {
             match video {
                 None => self.remove_video(connection).await?,
-                Some(video) => self.set_video(video, connection).await?,
+                Some(video) => self.set_video(video, video_hosts, connection).await?,
             }
         }
 
@@ -138,8 +139,8 @@ impl Demon {
         Ok(())
     }
 
-    pub async fn set_video(&mut self, video: String, connection: &mut PgConnection) -> Result<()> {
-        let video = crate::video::validate(&video)?;
+    pub async fn set_video(&mut self, video: String, video_hosts: &VideoHosts, connection: &mut PgConnection) -> Result<()> {
+        let video = video_hosts.validate(&video)?;
 
         sqlx::query!("UPDATE demons SET video = $1::text WHERE id = $2", video, self.base.id)
             .execute(connection)
@@ -184,13 +185,13 @@ impl MinimalDemon {
         Ok(())
     }
 
-    /// Moves this demon to the specified position
+    /// Moves this demon to the specified position on its list
     ///
     /// Validates that `to` is `> 0` and less than or equal to the currently highest position on the
     /// list (to preven "holes")
     pub async fn mv(&mut self, to: i16, connection: &mut PgConnection) -> Result<()> {
         // This returns 0 if the list is empty, but if the list is empty then there is no demon for us to do a move with, so we will never get here anyway.
-        let maximal_position = Demon::max_position(connection).await?;
+        let maximal_position = Demon::max_position(self.list, connection).await?;
 
         if to > maximal_position || to < 1 {
             return Err(DemonlistError::InvalidPosition { maximal: maximal_position });
@@ -216,9 +217,10 @@ impl MinimalDemon {
             );
 
             sqlx::query!(
-                "UPDATE demons SET position = position - 1 WHERE position > $1 AND position <= $2",
+                "UPDATE demons SET position = position - 1 WHERE position > $1 AND position <= $2 AND list = $3",
                 self.position,
-                to
+                to,
+                self.list
             )
             .execute(&mut *connection)
             .await?;
@@ -229,9 +231,10 @@ impl MinimalDemon {
             );
 
             sqlx::query!(
-                "UPDATE demons SET position = position + 1 WHERE position >= $1 AND position < $2",
+                "UPDATE demons SET position = position + 1 WHERE position >= $1 AND position < $2 AND list = $3",
                 to,
-                self.position
+                self.position,
+                self.list
             )
             .execute(&mut *connection)
             .await?;
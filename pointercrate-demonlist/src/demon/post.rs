@@ -3,6 +3,7 @@ use crate::{
     demon::{Demon, FullDemon, MinimalDemon},
     error::Result,
     player::{recompute_scores, DatabasePlayer},
+    video::VideoHosts,
 };
 use log::info;
 use serde::Deserialize;
@@ -21,35 +22,40 @@ pub struct PostDemon {
 }
 
 impl FullDemon {
+    /// Creates a new demon on the list with the given id
+    ///
     /// Must be run within a transaction!
-    pub async fn create_from(data: PostDemon, connection: &mut PgConnection) -> Result<FullDemon> {
-        info!("Creating new demon from {:?}", data);
+    pub async fn create_from(data: PostDemon, list: i32, video_hosts: &VideoHosts, connection: &mut PgConnection) -> Result<FullDemon> {
+        info!("Creating new demon on list {} from {:?}", list, data);
 
         Demon::validate_requirement(data.requirement)?;
         let level_id = data.level_id.map(Demon::validate_level_id).transpose()?;
 
         let video = match data.video {
-            Some(ref video) => Some(crate::video::validate(video)?),
+            Some(ref video) => Some(video_hosts.validate(video)?),
             None => None,
         };
+        let thumbnail = video.as_deref().and_then(|video| video_hosts.thumbnail_url(video));
 
-        Demon::validate_position(data.position, connection).await?;
+        Demon::validate_position(data.position, list, connection).await?;
 
         let publisher = DatabasePlayer::by_name_or_create(data.publisher.as_ref(), connection).await?;
         let verifier = DatabasePlayer::by_name_or_create(data.verifier.as_ref(), connection).await?;
 
-        Demon::shift_down(data.position, connection).await?;
+        Demon::shift_down(data.position, list, connection).await?;
 
         let created = sqlx::query!(
-            "INSERT INTO demons (name, position, requirement, video, verifier, publisher, level_id) VALUES ($1::text,$2,$3,$4::text,$5,$6, $7) \
-             RETURNING id, thumbnail",
+            "INSERT INTO demons (name, position, requirement, video, verifier, publisher, level_id, list, thumbnail) VALUES \
+             ($1::text,$2,$3,$4::text,$5,$6, $7, $8, $9::text) RETURNING id, thumbnail",
             data.name.to_string(),
             data.position,
             data.requirement,
             video.as_ref(),
             verifier.id,
             publisher.id,
-            data.level_id
+            data.level_id,
+            list,
+            thumbnail
         )
         .fetch_one(&mut *connection)
         .await?;
@@ -59,6 +65,7 @@ impl FullDemon {
                 id: created.id,
                 position: data.position,
                 name: data.name,
+                list,
             },
             requirement: data.requirement,
             video,
@@ -92,8 +99,10 @@ mod tests {
     use sqlx::{pool::PoolConnection, Postgres};
 
     use crate::{
-        demon::{FullDemon, PostDemon},
+        demon::{Demon, FullDemon, PostDemon},
         error::DemonlistError,
+        list::{List, DEFAULT_LIST},
+        video::VideoHosts,
     };
 
     const DEFAULT_THUMBNAIL: &str = "https://i.ytimg.com/vi/zebrafishes/mqdefault.jpg";
@@ -111,6 +120,8 @@ mod tests {
                 video: None,
                 level_id: None,
             },
+            DEFAULT_LIST,
+            &VideoHosts::default(),
             &mut conn,
         )
         .await
@@ -137,6 +148,8 @@ mod tests {
                 video: Some("https://www.youtube.com/watch?v=dQw4w9WgXcQ".to_owned()),
                 level_id: None,
             },
+            DEFAULT_LIST,
+            &VideoHosts::default(),
             &mut conn,
         )
         .await
@@ -158,6 +171,8 @@ mod tests {
                 video: Some("https://www.youtube.com/watch?v=dQw4w9WgXcQ".to_owned()),
                 level_id: None,
             },
+            DEFAULT_LIST,
+            &VideoHosts::default(),
             &mut conn,
         )
         .await
@@ -179,6 +194,8 @@ mod tests {
                 video: None,
                 level_id: Some(-1),
             },
+            DEFAULT_LIST,
+            &VideoHosts::default(),
             &mut conn,
         )
         .await
@@ -186,4 +203,51 @@ mod tests {
 
         assert_eq!(error, DemonlistError::InvalidLevelId);
     }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_positions_per_list(mut conn: PoolConnection<Postgres>) {
+        let challenge_list = List::register("challengelist", 10, 20, &mut conn).await.unwrap();
+
+        let bloodbath = FullDemon::create_from(
+            PostDemon {
+                name: "Bloodbath".to_owned(),
+                position: 1,
+                requirement: 90,
+                verifier: "Riot".to_owned(),
+                publisher: "Riot".to_owned(),
+                creators: Vec::new(),
+                video: None,
+                level_id: None,
+            },
+            DEFAULT_LIST,
+            &VideoHosts::default(),
+            &mut conn,
+        )
+        .await
+        .unwrap();
+
+        let challenge = FullDemon::create_from(
+            PostDemon {
+                name: "Dollar Tree".to_owned(),
+                position: 1,
+                requirement: 100,
+                verifier: "Riot".to_owned(),
+                publisher: "Riot".to_owned(),
+                creators: Vec::new(),
+                video: None,
+                level_id: None,
+            },
+            challenge_list.id,
+            &VideoHosts::default(),
+            &mut conn,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(bloodbath.position(), 1);
+        assert_eq!(challenge.position(), 1);
+        assert_eq!(challenge.demon.base.list, challenge_list.id);
+        assert_eq!(Demon::max_position(DEFAULT_LIST, &mut conn).await.unwrap(), 1);
+        assert_eq!(Demon::max_position(challenge_list.id, &mut conn).await.unwrap(), 1);
+    }
 }
@@ -3,6 +3,7 @@ use std::fmt::Display;
 use crate::{demon::MinimalDemon, record::RecordStatus};
 
 use pointercrate_core::{
+    config::ConfigError,
     error::{CoreError, PointercrateError},
     localization::tr,
     trp,
@@ -16,6 +17,15 @@ pub type Result<T> = std::result::Result<T, DemonlistError>;
 pub enum DemonlistError {
     Core(CoreError),
 
+    /// Error returned if one operation of a batch request failed, in which case none of the
+    /// batch's operations are applied
+    ///
+    /// Has the same status and error code as the error that made the operation fail.
+    BatchOperationFailed {
+        index: usize,
+        error: Box<DemonlistError>,
+    },
+
     /// `400 BAD REQUEST` error returned if a request could not be geolocated
     ///
     /// Wikipedia says 400 is appropriate for "deceptive request routine", so it seems to fit the
@@ -95,6 +105,27 @@ pub enum DemonlistError {
         player_id: i32,
     },
 
+    ListNotFound {
+        list_id: i32,
+    },
+
+    WebhookTargetNotFound {
+        id: i32,
+    },
+
+    /// `404 NOT FOUND` variant returned when trying to release a claim on a record no one is
+    /// currently reviewing
+    ///
+    /// Error Code `40401`
+    RecordNotClaimed {
+        record_id: i32,
+    },
+
+    /// `404 NOT FOUND` variant returned when no submission is tracked by the given tracking token
+    ///
+    /// Error Code `40401`
+    TrackedSubmissionNotFound,
+
     CreatorExists,
 
     /// `409 CONFLICT` variant
@@ -114,6 +145,27 @@ pub enum DemonlistError {
         player2: String,
     },
 
+    /// `409 CONFLICT` variant returned when trying to delete a player that still has records,
+    /// creator credits, or verified/published demons
+    ///
+    /// Error Code `40909`
+    PlayerInUse,
+
+    /// `409 CONFLICT` variant returned when trying to import an archive into an instance that
+    /// already has players, demons or records
+    ///
+    /// Error Code `40913`
+    ImportTargetNotEmpty,
+
+    /// `409 CONFLICT` variant returned when trying to claim or modify a record that another
+    /// reviewer has currently claimed
+    ///
+    /// Error Code `40914`
+    RecordClaimedByOther {
+        record_id: i32,
+        claimed_by: String,
+    },
+
     /// `422 UNPROCESSABLE ENTITY` variant returned if attempted to create a demon with a record
     /// requirements outside of [0, 100]
     ///
@@ -198,6 +250,100 @@ pub enum DemonlistError {
     ///
     /// Error Code `42235`
     InvalidLevelId,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a webhook target URL is not an HTTP(S) URL
+    ///
+    /// Error Code `42236`
+    InvalidWebhookUrl,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a line of an archive is not a valid archive
+    /// entry, or appears out of order, or if the archive is truncated
+    ///
+    /// Error Code `42245`
+    MalformedArchive {
+        line: usize,
+    },
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if an archive was exported by a version of
+    /// pointercrate using a different archive format
+    ///
+    /// Error Code `42246`
+    UnsupportedArchiveVersion {
+        version: u32,
+    },
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if an entry of an archive duplicates or
+    /// references an object in a way that is inconsistent with the rest of the archive
+    ///
+    /// Error Code `42247`
+    InconsistentArchive {
+        line: usize,
+    },
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if the positions of the demons on a list in an
+    /// archive leave "holes" in the list
+    ///
+    /// Error Code `42248`
+    InvalidArchivePositions {
+        list_id: i32,
+    },
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a player's score stored in an archive does not
+    /// match the score computed from the archived records
+    ///
+    /// Error Code `42249`
+    ArchiveScoreMismatch {
+        player_id: i32,
+        list_id: i32,
+    },
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned when trying to claim a record that has already
+    /// been approved or rejected
+    ///
+    /// Error Code `42250`
+    RecordAlreadyReviewed,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned when trying to reject a record for a reason that
+    /// is not in the configured catalogue of rejection reasons
+    ///
+    /// Error Code `42251`
+    UnknownRejectionReason {
+        reason: String,
+    },
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned when trying to reject a record without giving a
+    /// reason, if rejection reasons are required
+    ///
+    /// Error Code `42252`
+    RejectionReasonRequired,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned when trying to give a rejection reason for a
+    /// record that is not rejected
+    ///
+    /// Error Code `42253`
+    RecordNotRejected,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned when the date a record was achieved on lies in
+    /// the future
+    ///
+    /// Error Code `42254`
+    AchievedInFuture,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned when a batch request contains more operations
+    /// than the configured maximum
+    ///
+    /// Error Code `42255`
+    BatchTooLarge {
+        max_size: usize,
+    },
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned when a batch request contains more than one
+    /// operation on the same record
+    ///
+    /// Error Code `42256`
+    DuplicateBatchRecord {
+        record_id: i32,
+    },
 }
 
 impl std::error::Error for DemonlistError {}
@@ -208,6 +354,7 @@ impl PointercrateError for DemonlistError {
 
         match self {
             Core(core) => core.error_code(),
+            BatchOperationFailed { error, .. } => error.error_code(),
             GeolocationFailed => 40003,
             SubmitterNotFound { .. } => 40401,
             NoteNotFound { .. } => 40401,
@@ -230,9 +377,16 @@ impl PointercrateError for DemonlistError {
             DemonNotFoundPosition { .. } => 40401,
             RecordNotFound { .. } => 40401,
             ClaimNotFound { .. } => 40401,
+            ListNotFound { .. } => 40401,
+            WebhookTargetNotFound { .. } => 40401,
+            RecordNotClaimed { .. } => 40401,
+            TrackedSubmissionNotFound => 40401,
             DuplicateVideo { .. } => 40906,
             NoNationSet => 40907,
             ConflictingClaims { .. } => 40908,
+            PlayerInUse => 40909,
+            ImportTargetNotEmpty => 40913,
+            RecordClaimedByOther { .. } => 40914,
             InvalidProgress { .. } => 42215,
             SubmissionExists { .. } => 42217,
             PlayerBanned => 42218,
@@ -244,6 +398,27 @@ impl PointercrateError for DemonlistError {
             RawRequired => 42232,
             MalformedRawUrl => 42233,
             InvalidLevelId => 42235,
+            InvalidWebhookUrl => 42236,
+            MalformedArchive { .. } => 42245,
+            UnsupportedArchiveVersion { .. } => 42246,
+            InconsistentArchive { .. } => 42247,
+            InvalidArchivePositions { .. } => 42248,
+            ArchiveScoreMismatch { .. } => 42249,
+            RecordAlreadyReviewed => 42250,
+            UnknownRejectionReason { .. } => 42251,
+            RejectionReasonRequired => 42252,
+            RecordNotRejected => 42253,
+            AchievedInFuture => 42254,
+            BatchTooLarge { .. } => 42255,
+            DuplicateBatchRecord { .. } => 42256,
+        }
+    }
+
+    fn headers(&self) -> Vec<(&'static str, String)> {
+        match self {
+            DemonlistError::Core(core) => core.headers(),
+            DemonlistError::BatchOperationFailed { error, .. } => error.headers(),
+            _ => Vec::new(),
         }
     }
 }
@@ -257,6 +432,8 @@ impl Display for DemonlistError {
                 DemonlistError::Core(core) => {
                     return core.fmt(f);
                 },
+                DemonlistError::BatchOperationFailed { index, error } =>
+                    trp!("error-demonlist-batchoperationfailed", "index" = index, "error" = error.to_string()),
                 DemonlistError::GeolocationFailed => tr("error-demonlist-geolocationfailed"),
                 DemonlistError::MalformedVideoUrl => tr("error-demonlist-malformedvideourl"),
                 DemonlistError::BannedFromSubmissions => tr("error-demonlist-bannedfromsubmissions"),
@@ -289,11 +466,22 @@ impl Display for DemonlistError {
                 DemonlistError::RecordNotFound { record_id } => trp!("error-demonlist-recordnotfound", "record-id" = record_id),
                 DemonlistError::ClaimNotFound { member_id, player_id } =>
                     trp!("error-demonlist-claimnotfound", "member-id" = member_id, "player-id" = player_id),
+                DemonlistError::ListNotFound { list_id } => trp!("error-demonlist-listnotfound", "list-id" = list_id),
+                DemonlistError::WebhookTargetNotFound { id } => trp!("error-demonlist-webhooktargetnotfound", "id" = id),
+                DemonlistError::RecordNotClaimed { record_id } => trp!("error-demonlist-recordnotclaimed", "record-id" = record_id),
+                DemonlistError::TrackedSubmissionNotFound => tr("error-demonlist-trackedsubmissionnotfound"),
                 DemonlistError::CreatorExists => tr("error-demonlist-creatorexists"),
                 DemonlistError::DuplicateVideo { id } => trp!("error-demonlist-duplicatevideo", "record-id" = id),
                 DemonlistError::NoNationSet => tr("error-demonlist-nonationset"),
                 DemonlistError::ConflictingClaims { player1, player2 } =>
                     trp!("error-demonlist-conflictingclaims", "player-1" = player1, "player-2" = player2),
+                DemonlistError::PlayerInUse => tr("error-demonlist-playerinuse"),
+                DemonlistError::ImportTargetNotEmpty => tr("error-demonlist-importtargetnotempty"),
+                DemonlistError::RecordClaimedByOther { record_id, claimed_by } => trp!(
+                    "error-demonlist-recordclaimedbyother",
+                    "record-id" = record_id,
+                    "claimed-by" = claimed_by
+                ),
                 DemonlistError::InvalidRequirement => tr("error-demonlist-invalidrequirement"),
                 DemonlistError::InvalidPosition { maximal } => trp!("error-demonlist-invalidposition", "maximal" = maximal),
                 DemonlistError::InvalidProgress { requirement } => trp!("error-demonlist-invalidprogress", "requirement" = requirement),
@@ -312,6 +500,21 @@ impl Display for DemonlistError {
                 DemonlistError::RawRequired => tr("error-demonlist-rawrequired"),
                 DemonlistError::MalformedRawUrl => tr("error-demonlist-malformedrawurl"),
                 DemonlistError::InvalidLevelId => tr("error-demonlist-invalidlevelid"),
+                DemonlistError::InvalidWebhookUrl => tr("error-demonlist-invalidwebhookurl"),
+                DemonlistError::MalformedArchive { line } => trp!("error-demonlist-malformedarchive", "line" = line),
+                DemonlistError::UnsupportedArchiveVersion { version } =>
+                    trp!("error-demonlist-unsupportedarchiveversion", "version" = version),
+                DemonlistError::InconsistentArchive { line } => trp!("error-demonlist-inconsistentarchive", "line" = line),
+                DemonlistError::InvalidArchivePositions { list_id } => trp!("error-demonlist-invalidarchivepositions", "list-id" = list_id),
+                DemonlistError::ArchiveScoreMismatch { player_id, list_id } =>
+                    trp!("error-demonlist-archivescoremismatch", "player-id" = player_id, "list-id" = list_id),
+                DemonlistError::RecordAlreadyReviewed => tr("error-demonlist-recordalreadyreviewed"),
+                DemonlistError::UnknownRejectionReason { reason } => trp!("error-demonlist-unknownrejectionreason", "reason" = reason),
+                DemonlistError::RejectionReasonRequired => tr("error-demonlist-rejectionreasonrequired"),
+                DemonlistError::RecordNotRejected => tr("error-demonlist-recordnotrejected"),
+                DemonlistError::AchievedInFuture => tr("error-demonlist-achievedinfuture"),
+                DemonlistError::BatchTooLarge { max_size } => trp!("error-demonlist-batchtoolarge", "max-size" = max_size),
+                DemonlistError::DuplicateBatchRecord { record_id } => trp!("error-demonlist-duplicatebatchrecord", "record-id" = record_id),
             }
         )
     }
@@ -323,6 +526,12 @@ impl From<CoreError> for DemonlistError {
     }
 }
 
+impl From<ConfigError> for DemonlistError {
+    fn from(error: ConfigError) -> Self {
+        DemonlistError::Core(error.into())
+    }
+}
+
 impl From<sqlx::Error> for DemonlistError {
     fn from(error: sqlx::Error) -> Self {
         DemonlistError::Core(error.into())
@@ -1,16 +1,21 @@
 use pointercrate_core::permission::{Permission, PermissionsManager};
 use pointercrate_user::ADMINISTRATOR;
 
+pub mod archive;
 #[macro_use]
 pub mod demon;
 pub mod config;
 pub mod creator;
 pub mod error;
+pub mod list;
 pub mod nationality;
 pub mod player;
 pub mod record;
+pub mod review;
+pub mod score;
 pub mod submitter;
-mod video;
+pub mod video;
+pub mod webhook;
 
 pub const LIST_HELPER: Permission = Permission::new("user-permissions.list-helper", 0x2);
 pub const LIST_MODERATOR: Permission = Permission::new("user-permissions.list-moderator", 0x4);
@@ -0,0 +1,32 @@
+use crate::{
+    error::{DemonlistError, Result},
+    list::List,
+};
+use sqlx::{Error, PgConnection};
+
+impl List {
+    pub async fn by_id(id: i32, connection: &mut PgConnection) -> Result<List> {
+        let result = sqlx::query_as!(
+            List,
+            r#"SELECT id, name::TEXT AS "name!", list_size, extended_list_size FROM lists WHERE id = $1"#,
+            id
+        )
+        .fetch_one(connection)
+        .await;
+
+        match result {
+            Ok(list) => Ok(list),
+            Err(Error::RowNotFound) => Err(DemonlistError::ListNotFound { list_id: id }),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub async fn all(connection: &mut PgConnection) -> Result<Vec<List>> {
+        Ok(sqlx::query_as!(
+            List,
+            r#"SELECT id, name::TEXT AS "name!", list_size, extended_list_size FROM lists ORDER BY id"#
+        )
+        .fetch_all(connection)
+        .await?)
+    }
+}
@@ -0,0 +1,126 @@
+//! Module containing the [`List`] entity
+//!
+//! A single pointercrate instance can host multiple independent lists (for example a main list and
+//! a challenge list). Each demon belongs to exactly one list, and positions, list sizes, scores and
+//! rankings are all scoped to a list. User accounts and players are shared between all lists.
+//!
+//! The list with id [`DEFAULT_LIST`] always exists, and is the list all demons created before
+//! multi-list support belong to.
+
+use crate::{LIST_ADMINISTRATOR, LIST_HELPER, LIST_MODERATOR};
+use derive_more::Display;
+use pointercrate_core::permission::{Permission, PermissionsManager};
+use pointercrate_user::ADMINISTRATOR;
+use serde::Serialize;
+
+mod get;
+mod patch;
+mod post;
+
+/// The id of the list every pointercrate instance has
+pub const DEFAULT_LIST: i32 = 1;
+
+#[derive(Debug, Serialize, Display, PartialEq, Eq, Hash, Clone)]
+#[display("{} (ID: {})", name, id)]
+pub struct List {
+    pub id: i32,
+    pub name: String,
+
+    /// The number of demons on the main list. Only progress records on these demons give points.
+    pub list_size: i16,
+
+    /// The number of demons on the main and extended list combined. Demons past this position are
+    /// on the legacy list and do not accept records.
+    pub extended_list_size: i16,
+}
+
+/// The permissions required to manage a specific [`List`]
+///
+/// The global [`LIST_HELPER`], [`LIST_MODERATOR`] and [`LIST_ADMINISTRATOR`] permissions apply to
+/// all lists. Additional lists can use dedicated permissions to give their staff access to only
+/// that list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ListPermissions {
+    pub helper: Permission,
+    pub moderator: Permission,
+    pub administrator: Permission,
+}
+
+impl Default for ListPermissions {
+    fn default() -> Self {
+        ListPermissions {
+            helper: LIST_HELPER,
+            moderator: LIST_MODERATOR,
+            administrator: LIST_ADMINISTRATOR,
+        }
+    }
+}
+
+impl ListPermissions {
+    /// Constructs a [`PermissionsManager`] describing these permissions, to be merged into the
+    /// instance's permission manager.
+    ///
+    /// The dedicated permissions are set up analogously to the global ones, and are implied by
+    /// their global counterparts.
+    pub fn permissions_manager(&self) -> PermissionsManager {
+        let mut manager = PermissionsManager::new(vec![ADMINISTRATOR, self.helper, self.moderator, self.administrator])
+            .assigns(ADMINISTRATOR, self.administrator)
+            .assigns(ADMINISTRATOR, self.moderator)
+            .assigns(ADMINISTRATOR, self.helper)
+            .assigns(self.administrator, self.moderator)
+            .assigns(self.administrator, self.helper)
+            .implies(self.administrator, self.moderator)
+            .implies(self.moderator, self.helper);
+
+        // Implication cycles would cause infinite recursion when resolving permissions
+        for (global, dedicated) in [
+            (LIST_ADMINISTRATOR, self.administrator),
+            (LIST_MODERATOR, self.moderator),
+            (LIST_HELPER, self.helper),
+        ] {
+            if global != dedicated {
+                manager = manager.implies(global, dedicated);
+            }
+        }
+
+        manager
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{list::ListPermissions, LIST_ADMINISTRATOR, LIST_HELPER, LIST_MODERATOR};
+    use pointercrate_core::permission::Permission;
+
+    const CHALLENGE_HELPER: Permission = Permission::new("challenge-helper", 0x10);
+    const CHALLENGE_MODERATOR: Permission = Permission::new("challenge-moderator", 0x20);
+    const CHALLENGE_ADMINISTRATOR: Permission = Permission::new("challenge-administrator", 0x40);
+
+    #[test]
+    fn test_dedicated_permissions() {
+        let mut manager = crate::default_permissions_manager();
+        manager.merge_with(
+            ListPermissions {
+                helper: CHALLENGE_HELPER,
+                moderator: CHALLENGE_MODERATOR,
+                administrator: CHALLENGE_ADMINISTRATOR,
+            }
+            .permissions_manager(),
+        );
+
+        // Global list permissions carry over to the dedicated ones
+        assert!(manager.implied_by(LIST_MODERATOR).contains(&CHALLENGE_HELPER));
+        assert!(manager.implied_by(LIST_ADMINISTRATOR).contains(&CHALLENGE_ADMINISTRATOR));
+        // ...but not the other way around
+        assert!(!manager.implied_by(CHALLENGE_ADMINISTRATOR).contains(&LIST_HELPER));
+        assert!(manager.implied_by(CHALLENGE_MODERATOR).contains(&CHALLENGE_HELPER));
+    }
+
+    #[test]
+    fn test_default_permissions() {
+        let mut manager = crate::default_permissions_manager();
+        manager.merge_with(ListPermissions::default().permissions_manager());
+
+        assert!(manager.implied_by(LIST_ADMINISTRATOR).contains(&LIST_HELPER));
+    }
+}
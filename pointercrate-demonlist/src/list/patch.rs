@@ -0,0 +1,21 @@
+use crate::{error::Result, list::List};
+use sqlx::PgConnection;
+
+impl List {
+    /// Updates the main and extended list sizes of this list
+    pub async fn set_sizes(&mut self, list_size: i16, extended_list_size: i16, connection: &mut PgConnection) -> Result<()> {
+        sqlx::query!(
+            "UPDATE lists SET list_size = $2, extended_list_size = $3 WHERE id = $1",
+            self.id,
+            list_size,
+            extended_list_size
+        )
+        .execute(connection)
+        .await?;
+
+        self.list_size = list_size;
+        self.extended_list_size = extended_list_size;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,34 @@
+use crate::{error::Result, list::List};
+use log::info;
+use sqlx::PgConnection;
+
+impl List {
+    /// Makes sure a list with the given name exists, creating it with the given sizes if not
+    ///
+    /// Lists are configured in code (see `pointercrate_demonlist_api::mount_list`), so this is
+    /// called on every launch. Lists are identified by their name, meaning a list that is renamed
+    /// in the configuration will be created anew. The sizes of already existing lists are not
+    /// changed, use [`List::set_sizes`] for that.
+    pub async fn register(name: &str, list_size: i16, extended_list_size: i16, connection: &mut PgConnection) -> Result<List> {
+        let created = sqlx::query!(
+            "INSERT INTO lists (name, list_size, extended_list_size) VALUES ($1::TEXT, $2, $3) ON CONFLICT (name) DO NOTHING",
+            name,
+            list_size,
+            extended_list_size
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        if created.rows_affected() > 0 {
+            info!("Created new list {}", name);
+        }
+
+        Ok(sqlx::query_as!(
+            List,
+            r#"SELECT id, name::TEXT AS "name!", list_size, extended_list_size FROM lists WHERE name = $1::TEXT"#,
+            name
+        )
+        .fetch_one(connection)
+        .await?)
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{
     demon::MinimalDemon,
     error::{DemonlistError, Result},
+    list::List,
     nationality::{BestRecord, MiniDemonWithPlayers, Nationality, NationalityRecord, Subdivision},
 };
 use futures::stream::StreamExt;
@@ -113,24 +114,26 @@ impl Nationality {
         Ok(nationalities)
     }
 
-    pub async fn upgrade(self, connection: &mut PgConnection) -> Result<NationalityRecord> {
+    /// Collects this nation's statistics on the given [`List`]
+    pub async fn upgrade(self, list: &List, connection: &mut PgConnection) -> Result<NationalityRecord> {
         Ok(NationalityRecord {
-            best_records: best_records_in(&self, connection).await?,
-            created: created_in(&self, connection).await?,
-            verified: verified_in(&self, connection).await?,
-            published: published_in(&self, connection).await?,
-            unbeaten: unbeaten_in(&self, connection).await?,
+            best_records: best_records_in(&self, list.id, connection).await?,
+            created: created_in(&self, list.id, connection).await?,
+            verified: verified_in(&self, list.id, connection).await?,
+            published: published_in(&self, list.id, connection).await?,
+            unbeaten: unbeaten_in(&self, list, connection).await?,
             nation: self,
         })
     }
 }
 
-pub async fn unbeaten_in(nation: &Nationality, connection: &mut PgConnection) -> Result<Vec<MinimalDemon>> {
+pub async fn unbeaten_in(nation: &Nationality, list: &List, connection: &mut PgConnection) -> Result<Vec<MinimalDemon>> {
     let mut stream = sqlx::query!(
-        r#"select name::text as "name!", id as "id!", position as "position!" from demons where position <= $1 except (select demons.name, demons.id, position from records inner join players on 
-         players.id=records.player inner join demons on demons.id=records.demon where status_='APPROVED' and nationality=$2 and progress=100 union select demons.name, demons.id, demons.position from demons inner join players on players.id=verifier where players.nationality=$2)"#,
-        crate::config::extended_list_size(),
-        nation.iso_country_code
+        r#"select name::text as "name!", id as "id!", position as "position!", list as "list!" from demons where position <= $1 and list = $3 except (select demons.name, demons.id, position, list from records inner join players on 
+         players.id=records.player inner join demons on demons.id=records.demon where status_='APPROVED' and nationality=$2 and progress=100 union select demons.name, demons.id, demons.position, demons.list from demons inner join players on players.id=verifier where players.nationality=$2)"#,
+        list.extended_list_size,
+        nation.iso_country_code,
+        list.id
     )
     .fetch(connection);
 
@@ -143,14 +146,15 @@ pub async fn unbeaten_in(nation: &Nationality, connection: &mut PgConnection) ->
             id: row.id,
             position: row.position,
             name: row.name,
+            list: row.list,
         });
     }
 
     Ok(unbeaten)
 }
 
-pub async fn created_in(nation: &Nationality, connection: &mut PgConnection) -> Result<Vec<MiniDemonWithPlayers>> {
-    let mut stream = sqlx::query!( r#"select demon, demons.name::text as "demon_name!", demons.position, players.name::text as "player_name!" from creators inner join demons on demons.id=demon inner join players on players.id=creator where nationality=$1 order by demon"#, nation.iso_country_code).fetch(connection);
+pub async fn created_in(nation: &Nationality, list: i32, connection: &mut PgConnection) -> Result<Vec<MiniDemonWithPlayers>> {
+    let mut stream = sqlx::query!( r#"select demon, demons.name::text as "demon_name!", demons.position, players.name::text as "player_name!" from creators inner join demons on demons.id=demon inner join players on players.id=creator where nationality=$1 and demons.list=$2 order by demon"#, nation.iso_country_code, list).fetch(connection);
 
     let mut creations = Vec::<MiniDemonWithPlayers>::new();
 
@@ -164,6 +168,7 @@ pub async fn created_in(nation: &Nationality, connection: &mut PgConnection) ->
                     id: row.demon,
                     name: row.demon_name,
                     position: row.position,
+                    list,
                 },
                 players: vec![row.player_name],
             }),
@@ -173,9 +178,9 @@ pub async fn created_in(nation: &Nationality, connection: &mut PgConnection) ->
     Ok(creations)
 }
 
-pub async fn verified_in(nation: &Nationality, connection: &mut PgConnection) -> Result<Vec<MiniDemonWithPlayers>> {
+pub async fn verified_in(nation: &Nationality, list: i32, connection: &mut PgConnection) -> Result<Vec<MiniDemonWithPlayers>> {
     let mut stream = sqlx::query!(
-        r#"select demons.id as demon, demons.name::text as "demon_name!", demons.position, players.name::text as "player_name!" from demons inner join players on players.id=verifier where nationality=$1"#, nation.iso_country_code).fetch(connection);
+        r#"select demons.id as demon, demons.name::text as "demon_name!", demons.position, players.name::text as "player_name!" from demons inner join players on players.id=verifier where nationality=$1 and demons.list=$2"#, nation.iso_country_code, list).fetch(connection);
 
     let mut demons = Vec::new();
 
@@ -187,6 +192,7 @@ pub async fn verified_in(nation: &Nationality, connection: &mut PgConnection) ->
                 id: row.demon,
                 name: row.demon_name,
                 position: row.position,
+                list,
             },
             players: vec![row.player_name],
         });
@@ -195,9 +201,9 @@ pub async fn verified_in(nation: &Nationality, connection: &mut PgConnection) ->
     Ok(demons)
 }
 
-pub async fn published_in(nation: &Nationality, connection: &mut PgConnection) -> Result<Vec<MiniDemonWithPlayers>> {
+pub async fn published_in(nation: &Nationality, list: i32, connection: &mut PgConnection) -> Result<Vec<MiniDemonWithPlayers>> {
     let mut stream = sqlx::query!(
-        r#"select demons.id as demon, demons.name::text as "demon_name!", demons.position, players.name::text as "player_name!" from demons inner join players on players.id=publisher where nationality=$1"#, nation.iso_country_code).fetch(connection);
+        r#"select demons.id as demon, demons.name::text as "demon_name!", demons.position, players.name::text as "player_name!" from demons inner join players on players.id=publisher where nationality=$1 and demons.list=$2"#, nation.iso_country_code, list).fetch(connection);
 
     let mut demons = Vec::new();
 
@@ -209,6 +215,7 @@ pub async fn published_in(nation: &Nationality, connection: &mut PgConnection) -
                 id: row.demon,
                 name: row.demon_name,
                 position: row.position,
+                list,
             },
             players: vec![row.player_name],
         });
@@ -217,10 +224,11 @@ pub async fn published_in(nation: &Nationality, connection: &mut PgConnection) -
     Ok(demons)
 }
 
-pub async fn best_records_in(nation: &Nationality, connection: &mut PgConnection) -> Result<Vec<BestRecord>> {
+pub async fn best_records_in(nation: &Nationality, list: i32, connection: &mut PgConnection) -> Result<Vec<BestRecord>> {
     let mut stream = sqlx::query!(
-        r#"SELECT progress as "progress!", demons.id AS "demon_id!", demons.name as "demon_name!: String", demons.position as "position!", players.name as "player_name!: String" FROM best_records_in($1) as records INNER JOIN demons ON records.demon = demons.id INNER JOIN players ON players.id = records.player"#,
-        nation.iso_country_code
+        r#"SELECT progress as "progress!", demons.id AS "demon_id!", demons.name as "demon_name!: String", demons.position as "position!", players.name as "player_name!: String" FROM best_records_in($1) as records INNER JOIN demons ON records.demon = demons.id INNER JOIN players ON players.id = records.player WHERE demons.list = $2"#,
+        nation.iso_country_code,
+        list
     )
         .fetch(connection);
 
@@ -236,6 +244,7 @@ pub async fn best_records_in(nation: &Nationality, connection: &mut PgConnection
                     id: row.demon_id,
                     name: row.demon_name,
                     position: row.position,
+                    list,
                 },
                 progress: row.progress,
                 players: vec![row.player_name],
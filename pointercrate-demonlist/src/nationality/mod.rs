@@ -1,4 +1,7 @@
-use crate::demon::MinimalDemon;
+use crate::{
+    demon::MinimalDemon,
+    score::{update_nation_scores, update_subdivision_scores},
+};
 pub use paginate::{NationalityRankingPagination, RankedNation};
 use pointercrate_core::etag::Taggable;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
@@ -122,22 +125,13 @@ impl Nationality {
         self.iso_country_code == other.iso_country_code
     }
 
-    /// Updates the score for this [`Nationality`] and contained [`Subdivision`] (if set).
+    /// Updates the scores for this [`Nationality`] and contained [`Subdivision`] (if set) on all
+    /// lists.
     pub async fn update_nation_score(&self, connection: &mut PgConnection) -> Result<(), sqlx::Error> {
-        sqlx::query!(
-            "UPDATE nationalities SET score = coalesce(score_of_nation($1), 0) WHERE iso_country_code = $1",
-            self.iso_country_code
-        )
-        .execute(&mut *connection)
-        .await?;
+        update_nation_scores(&self.iso_country_code, &mut *connection).await?;
+
         if let Some(ref subdivision) = self.subdivision {
-            sqlx::query!(
-                "UPDATE subdivisions SET score = coalesce(score_of_subdivision($1, $2), 0) WHERE nation = $1 AND iso_code = $2",
-                self.iso_country_code,
-                subdivision.iso_code
-            )
-            .execute(&mut *connection)
-            .await?;
+            update_subdivision_scores(&self.iso_country_code, &subdivision.iso_code, &mut *connection).await?;
         }
 
         Ok(())
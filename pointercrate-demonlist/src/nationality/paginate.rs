@@ -1,38 +1,89 @@
 use crate::{
-    error::Result,
+    list::DEFAULT_LIST,
     nationality::{Continent, Nationality},
 };
 use futures::StreamExt;
-use pointercrate_core::util::non_nullable;
+use pointercrate_core::{
+    pagination::{__pagination_compat, PageContext, Paginatable, PaginationParameters, PaginationQuery},
+    util::non_nullable,
+};
 use serde::{Deserialize, Serialize};
-use sqlx::PgConnection;
+use sqlx::{PgConnection, Row};
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct NationalityRankingPagination {
+    #[serde(flatten)]
+    pub params: PaginationParameters,
+
     #[serde(default, deserialize_with = "non_nullable")]
     continent: Option<Continent>,
 
     #[serde(default, deserialize_with = "non_nullable")]
     name_contains: Option<String>,
+
+    /// The list whose ranking to retrieve, defaulting to the [default list](DEFAULT_LIST)
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub list: Option<i32>,
+}
+
+impl PaginationQuery for NationalityRankingPagination {
+    fn parameters(&self) -> PaginationParameters {
+        self.params
+    }
+
+    fn with_parameters(&self, parameters: PaginationParameters) -> Self {
+        Self {
+            params: parameters,
+            ..self.clone()
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct RankedNation {
+    #[serde(skip)]
+    index: i64,
     pub rank: i64,
     pub score: f64,
     #[serde(flatten)]
     pub nationality: Nationality,
 }
 
-impl NationalityRankingPagination {
-    pub async fn page(&self, connection: &mut PgConnection) -> Result<Vec<RankedNation>> {
-        let mut stream = sqlx::query!(
-            r#"SELECT rank as "rank!", score as "score!", nation as "nation!", iso_country_code as "iso_country_code!" FROM ranked_nations WHERE (STRPOS(nation, $1::CITEXT) > 
-             0 OR $1 is NULL) AND (continent::text = $2 OR $2 IS NULL)"#,
-            self.name_contains,
-            self.continent.map(|c| c.to_sql())
+impl Paginatable<NationalityRankingPagination> for RankedNation {
+    async fn first_and_last(
+        query: &NationalityRankingPagination, connection: &mut PgConnection,
+    ) -> Result<Option<(i32, i32)>, sqlx::Error> {
+        // Analogous to the player ranking, the indices are only unique within a list's ranking
+        Ok(sqlx::query!(
+            "SELECT COUNT(*) FROM ranked_nations WHERE list = $1",
+            query.list.unwrap_or(DEFAULT_LIST)
         )
-        .fetch(connection);
+        .fetch_one(connection)
+        .await?
+        .count
+        .map(|max| (1, max as i32)))
+    }
+
+    async fn fetch(
+        query: &NationalityRankingPagination, connection: &mut PgConnection,
+    ) -> Result<(Vec<RankedNation>, PageContext), sqlx::Error> {
+        let order = query.params.order();
+
+        let sql_query = format!(
+            "SELECT index, rank, score, nation, iso_country_code FROM ranked_nations WHERE (index < $1 OR $1 IS NULL) AND (index > $2 \
+             OR $2 IS NULL) AND (STRPOS(nation, $3::CITEXT) > 0 OR $3 is NULL) AND (continent::text = $4 OR $4 IS NULL) AND list = $5 \
+             ORDER BY index {} LIMIT $6",
+            order
+        );
+
+        let mut stream = sqlx::query(&sql_query)
+            .bind(query.params.before)
+            .bind(query.params.after)
+            .bind(query.name_contains.as_deref())
+            .bind(query.continent.as_ref().map(|c| c.to_sql()))
+            .bind(query.list.unwrap_or(DEFAULT_LIST))
+            .bind(query.params.limit + 1)
+            .fetch(connection);
 
         let mut nations = Vec::new();
 
@@ -40,16 +91,21 @@ impl NationalityRankingPagination {
             let row = row?;
 
             nations.push(RankedNation {
-                rank: row.rank,
-                score: row.score,
+                index: row.get("index"),
+                rank: row.get("rank"),
+                score: row.get("score"),
                 nationality: Nationality {
-                    iso_country_code: row.iso_country_code,
-                    nation: row.nation,
+                    iso_country_code: row.get("iso_country_code"),
+                    nation: row.get("nation"),
                     subdivision: None,
                 },
             })
         }
 
-        Ok(nations)
+        Ok(__pagination_compat(&query.params, nations))
+    }
+
+    fn pagination_id(&self) -> i32 {
+        self.index as i32
     }
 }
@@ -0,0 +1,173 @@
+use crate::error::Result;
+
+use futures::StreamExt;
+use pointercrate_core::audit::{AuditLogEntry, AuditLogEntryType, NamedId};
+use serde::Serialize;
+use sqlx::PgConnection;
+
+#[derive(Serialize, Default)]
+pub struct PlayerModificationData {
+    name: Option<String>,
+    banned: Option<bool>,
+    nationality: Option<String>,
+    subdivision: Option<String>,
+
+    /// Set if the given player was merged into this one
+    merged: Option<NamedId>,
+
+    /// Set if this player was merged into the given player
+    merged_into: Option<NamedId>,
+}
+
+/// Gets all audit log entries for the given player, in chronological order
+///
+/// Banning and unbanning a player shows up as a modification of the `banned` field. Merges show
+/// up as modifications on both players, with the merged player additionally being deleted.
+pub async fn audit_log_for_player(player_id: i32, connection: &mut PgConnection) -> Result<Vec<AuditLogEntry<PlayerModificationData>>> {
+    let mut entries = Vec::new();
+
+    let addition_row = sqlx::query!(
+        r#"SELECT time, audit_id,
+                  userid,
+                  members.name AS "name?"
+                  FROM player_additions LEFT OUTER JOIN members ON members.member_id = userid WHERE id = $1"#,
+        player_id
+    )
+    .fetch_optional(&mut *connection)
+    .await?;
+
+    if let Some(addition) = addition_row {
+        entries.push(AuditLogEntry {
+            time: addition.time,
+            entry_id: addition.audit_id,
+            id: player_id,
+            user: NamedId {
+                name: addition.name,
+                id: addition.userid,
+            },
+            r#type: AuditLogEntryType::Addition,
+        });
+    }
+
+    let mut modification_stream = sqlx::query!(
+        r#"SELECT time,
+                  audit_id,
+                  members.name AS "username?",
+                  userid,
+                  player_modifications.name::TEXT,
+                  banned,
+                  player_modifications.nationality,
+                  player_modifications.subdivision
+                  FROM player_modifications
+                  LEFT OUTER JOIN members ON members.member_id = userid
+                  WHERE id = $1
+                  ORDER BY time"#,
+        player_id
+    )
+    .fetch(&mut *connection);
+
+    while let Some(modification) = modification_stream.next().await {
+        let modification = modification?;
+
+        entries.push(AuditLogEntry {
+            time: modification.time,
+            entry_id: modification.audit_id,
+            id: player_id,
+            r#type: AuditLogEntryType::Modification(PlayerModificationData {
+                name: modification.name,
+                banned: modification.banned,
+                nationality: modification.nationality,
+                subdivision: modification.subdivision,
+                ..Default::default()
+            }),
+            user: NamedId {
+                name: modification.username,
+                id: modification.userid,
+            },
+        })
+    }
+
+    // un-borrow `connection`
+    drop(modification_stream);
+
+    let mut merge_stream = sqlx::query!(
+        r#"SELECT time,
+                  audit_id,
+                  members.name AS "username?",
+                  userid,
+                  player_merges.id,
+                  players.name::TEXT AS "name?",
+                  merged,
+                  merged_name::TEXT AS "merged_name!"
+                  FROM player_merges
+                  LEFT OUTER JOIN members ON members.member_id = userid
+                  LEFT OUTER JOIN players ON players.id = player_merges.id
+                  WHERE player_merges.id = $1 OR merged = $1
+                  ORDER BY time"#,
+        player_id
+    )
+    .fetch(&mut *connection);
+
+    while let Some(merge) = merge_stream.next().await {
+        let merge = merge?;
+
+        let data = if merge.id == player_id {
+            PlayerModificationData {
+                merged: Some(NamedId {
+                    id: merge.merged,
+                    name: Some(merge.merged_name),
+                }),
+                ..Default::default()
+            }
+        } else {
+            PlayerModificationData {
+                merged_into: Some(NamedId {
+                    id: merge.id,
+                    name: merge.name,
+                }),
+                ..Default::default()
+            }
+        };
+
+        entries.push(AuditLogEntry {
+            time: merge.time,
+            entry_id: merge.audit_id,
+            id: player_id,
+            r#type: AuditLogEntryType::Modification(data),
+            user: NamedId {
+                name: merge.username,
+                id: merge.userid,
+            },
+        })
+    }
+
+    drop(merge_stream);
+
+    let deletion_row = sqlx::query!(
+        r#"SELECT time, audit_id,
+                  userid,
+                  members.name AS "name?"
+                  FROM player_deletions LEFT OUTER JOIN members ON members.member_id = userid WHERE id = $1"#,
+        player_id
+    )
+    .fetch_optional(&mut *connection)
+    .await?;
+
+    if let Some(deletion) = deletion_row {
+        entries.push(AuditLogEntry {
+            time: deletion.time,
+            entry_id: deletion.audit_id,
+            id: player_id,
+            user: NamedId {
+                name: deletion.name,
+                id: deletion.userid,
+            },
+            r#type: AuditLogEntryType::Deletion,
+        });
+    }
+
+    // Merges are interleaved with the other modifications
+    entries.sort_by_key(|entry| (entry.time, entry.entry_id));
+
+    Ok(entries)
+}
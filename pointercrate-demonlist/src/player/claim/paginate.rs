@@ -2,7 +2,7 @@ use futures::StreamExt;
 use pointercrate_core::{
     audit::NamedId,
     first_and_last,
-    pagination::{PageContext, Paginatable, PaginationParameters, PaginationQuery, __pagination_compat},
+    pagination::{__pagination_compat, PageContext, Paginatable, PaginationParameters, PaginationQuery},
     util::non_nullable,
 };
 use serde::{Deserialize, Serialize};
@@ -43,9 +43,9 @@ impl PaginationQuery for PlayerClaimPagination {
 }
 
 impl Paginatable<PlayerClaimPagination> for ListedClaim {
-    first_and_last!("player_claims");
+    first_and_last!(PlayerClaimPagination, "player_claims");
 
-    async fn page(query: &PlayerClaimPagination, connection: &mut PgConnection) -> Result<(Vec<ListedClaim>, PageContext), sqlx::Error> {
+    async fn fetch(query: &PlayerClaimPagination, connection: &mut PgConnection) -> Result<(Vec<ListedClaim>, PageContext), sqlx::Error> {
         let order = query.params.order();
 
         let sql_query = format!(include_str!("../../../sql/paginate_claims.sql"), order);
@@ -0,0 +1,43 @@
+use crate::{
+    error::{DemonlistError, Result},
+    player::DatabasePlayer,
+};
+use log::info;
+use sqlx::PgConnection;
+
+impl DatabasePlayer {
+    /// Deletes this player, together with any claims on it
+    ///
+    /// Only players that are not referenced anywhere on any list can be deleted, meaning they must
+    /// have no records (of any status), no creator credits, and must not be the verifier or publisher
+    /// of any demon. Players that are still referenced can only be removed by merging them into
+    /// another player.
+    pub async fn delete(self, connection: &mut PgConnection) -> Result<()> {
+        let in_use = sqlx::query_scalar!(
+            r#"SELECT EXISTS (SELECT 1 FROM records WHERE player = $1)
+                   OR EXISTS (SELECT 1 FROM creators WHERE creator = $1)
+                   OR EXISTS (SELECT 1 FROM demons WHERE verifier = $1 OR publisher = $1) AS "in_use!""#,
+            self.id
+        )
+        .fetch_one(&mut *connection)
+        .await?;
+
+        if in_use {
+            return Err(DemonlistError::PlayerInUse);
+        }
+
+        info!("Deleting player {}", self);
+
+        let deleted = sqlx::query!("DELETE FROM player_claims WHERE player_id = $1", self.id)
+            .execute(&mut *connection)
+            .await?;
+
+        info!("Deleted {} claims on {}", deleted.rows_affected(), self);
+
+        sqlx::query!("DELETE FROM players WHERE id = $1", self.id)
+            .execute(connection)
+            .await?;
+
+        Ok(())
+    }
+}
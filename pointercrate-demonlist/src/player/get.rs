@@ -2,6 +2,7 @@ use crate::{
     creator::created_by,
     demon::{published_by, verified_by},
     error::{DemonlistError, Result},
+    list::DEFAULT_LIST,
     nationality::{Nationality, Subdivision},
     player::{DatabasePlayer, FullPlayer, Player},
     record::approved_records_by,
@@ -10,10 +11,16 @@ use sqlx::{Error, PgConnection};
 
 impl Player {
     pub async fn upgrade(self, connection: &mut PgConnection) -> Result<FullPlayer> {
-        let records = approved_records_by(&self.base, connection).await?;
-        let published = published_by(&self.base, connection).await?;
-        let verified = verified_by(&self.base, connection).await?;
-        let created = created_by(self.base.id, connection).await?;
+        let mut records = approved_records_by(&self.base, connection).await?;
+        let mut published = published_by(&self.base, connection).await?;
+        let mut verified = verified_by(&self.base, connection).await?;
+        let mut created = created_by(self.base.id, connection).await?;
+
+        // Only show the player's achievements on the list their score and rank belong to
+        records.retain(|record| record.demon.list == self.list);
+        published.retain(|demon| demon.list == self.list);
+        verified.retain(|demon| demon.list == self.list);
+        created.retain(|demon| demon.list == self.list);
 
         Ok(FullPlayer {
             player: self,
@@ -24,11 +31,19 @@ impl Player {
         })
     }
 
+    /// Retrieves the player with the given id, with score and rank on the [default list](DEFAULT_LIST)
     pub async fn by_id(id: i32, connection: &mut PgConnection) -> Result<Player> {
+        Player::by_id_on_list(id, DEFAULT_LIST, connection).await
+    }
+
+    /// Retrieves the player with the given id, with score and rank on the given list
+    pub async fn by_id_on_list(id: i32, list: i32, connection: &mut PgConnection) -> Result<Player> {
         let result = sqlx::query!(
-            r#"SELECT players.id, players.name, banned, players.score, nationalities.nation::text, iso_country_code::text, iso_code::text as subdivision_code, subdivisions.name::text as subdivision_name, player_ranks.rank FROM players LEFT OUTER JOIN nationalities ON 
-             players.nationality = nationalities.iso_country_code LEFT OUTER JOIN subdivisions ON players.subdivision = subdivisions.iso_code LEFT OUTER JOIN player_ranks ON player_ranks.id = players.id WHERE players.id = $1 AND (subdivisions.nation=nationalities.iso_country_code or players.subdivision is null)"#,
-            id
+            r#"SELECT players.id, players.name, banned, COALESCE(player_scores.score, 0) AS "score!", nationalities.nation::text, iso_country_code::text, iso_code::text as subdivision_code, subdivisions.name::text as subdivision_name, player_ranks.rank FROM players LEFT OUTER JOIN nationalities ON 
+             players.nationality = nationalities.iso_country_code LEFT OUTER JOIN subdivisions ON players.subdivision = subdivisions.iso_code LEFT OUTER JOIN player_ranks ON player_ranks.id = players.id AND player_ranks.list = $2 LEFT OUTER JOIN player_scores ON player_scores.player = players.id AND player_scores.list = $2
+             WHERE players.id = $1 AND (subdivisions.nation=nationalities.iso_country_code or players.subdivision is null)"#,
+            id,
+            list
         )
         .fetch_one(connection)
         .await;
@@ -60,6 +75,7 @@ impl Player {
                     score: row.score,
                     rank: row.rank,
                     nationality,
+                    list,
                 })
             },
             Err(Error::RowNotFound) => Err(DemonlistError::PlayerNotFound { player_id: id }),
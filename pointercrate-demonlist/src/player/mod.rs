@@ -1,18 +1,28 @@
 pub use self::{
-    paginate::{PlayerPagination, RankedPlayer, RankingPagination},
+    paginate::{PlayerPagination, PlayerSortKey, RankedPlayer, RankingPagination},
     patch::PatchPlayer,
 };
-use crate::{demon::MinimalDemon, nationality::Nationality, record::MinimalRecordD};
+use crate::{
+    demon::MinimalDemon,
+    nationality::Nationality,
+    record::MinimalRecordD,
+    score::{
+        lock_scores, score_giving_of_player, score_of_group, score_of_player, scores_per_list, scoring_fingerprint, store_fingerprint,
+        update_nation_scores, update_subdivision_scores, ScoreGiving,
+    },
+};
 use derive_more::Display;
 use pointercrate_core::{error::CoreError, etag::Taggable};
 use serde::{Deserialize, Serialize};
 use sqlx::PgConnection;
 use std::{
-    collections::hash_map::DefaultHasher,
+    collections::{hash_map::DefaultHasher, BTreeMap, HashMap},
     hash::{Hash, Hasher},
 };
 
+pub mod audit;
 pub mod claim;
+mod delete;
 mod get;
 mod paginate;
 mod patch;
@@ -42,9 +52,9 @@ pub struct Player {
     #[serde(flatten)]
     pub base: DatabasePlayer,
 
-    /// This [`Player`]'s score on the stats viewer
+    /// This [`Player`]'s score on the stats viewer of the list this object was retrieved for
     ///
-    /// This value is cached in the `score` column of the `players` table, and not computed on-demand!
+    /// This value is cached in the `player_scores` table, and not computed on-demand!
     /// Thus it needs to be updated on any event that can affect a player's score. These are
     /// - Record updates
     ///   * Record status updated (to approved, or from approved)
@@ -61,6 +71,9 @@ pub struct Player {
     pub score: f64,
     pub rank: Option<i64>,
     pub nationality: Option<Nationality>,
+
+    /// The id of the [`List`](crate::list::List) [`Player::score`] and [`Player::rank`] refer to
+    pub list: i32,
 }
 
 // `f64` does not implement hash. Most things in the pointercrate frontend only display score with an accuracy of two digits after the dot,
@@ -70,6 +83,7 @@ impl Hash for Player {
         self.base.hash(state);
         ((self.score * 100f64) as u64).hash(state);
         self.nationality.hash(state);
+        self.list.hash(state);
     }
 }
 
@@ -82,34 +96,181 @@ impl Taggable for FullPlayer {
 }
 
 impl DatabasePlayer {
-    /// Recomputes this player's score and updates it in the database.
-    pub async fn update_score(&self, connection: &mut PgConnection) -> Result<f64, CoreError> {
-        // No need to specially handle banned players - they have no approved records, so `score_of_player` will return 0
-        let new_score = sqlx::query!(
-            "UPDATE players SET score = coalesce(score_of_player($1), 0) WHERE id = $1 RETURNING score",
-            self.id
+    /// Recomputes this player's scores on all lists and updates them in the database.
+    ///
+    /// Returns the new scores, keyed by list id. Lists on which this player has no score giving
+    /// records are not included.
+    pub async fn update_score(&self, connection: &mut PgConnection) -> Result<HashMap<i32, f64>, CoreError> {
+        let scores = self.recompute_score(&mut *connection).await?;
+
+        refresh_player_ranks(connection).await?;
+
+        Ok(scores)
+    }
+
+    /// Like [`DatabasePlayer::update_score`], but does not refresh the `player_ranks` view
+    async fn recompute_score(&self, connection: &mut PgConnection) -> Result<HashMap<i32, f64>, CoreError> {
+        // No need to specially handle banned players - they have no approved records, so their score will be 0
+        lock_scores(&mut *connection).await?;
+
+        let (lists, scores) = scores_per_list(score_giving_of_player(self.id, &mut *connection).await?, |records| {
+            score_of_player(records)
+        });
+
+        sqlx::query!("DELETE FROM player_scores WHERE player = $1", self.id)
+            .execute(&mut *connection)
+            .await?;
+        sqlx::query!(
+            "INSERT INTO player_scores (player, list, score) SELECT $1, * FROM UNNEST($2::INTEGER[], $3::DOUBLE PRECISION[])",
+            self.id,
+            &lists,
+            &scores
         )
-        .fetch_one(&mut *connection)
+        .execute(&mut *connection)
         .await?;
 
-        sqlx::query!("UPDATE nationalities SET score = coalesce(score_of_nation(nationalities.iso_country_code), 0) FROM players WHERE players.id = $1 AND players.nationality = nationalities.iso_country_code", self.id).execute(&mut *connection).await?;
-        sqlx::query!("UPDATE subdivisions SET score = coalesce(score_of_subdivision(subdivisions.nation, subdivisions.iso_code), 0) FROM players WHERE players.id = $1 AND players.nationality = subdivisions.nation AND players.subdivision = subdivisions.iso_code", self.id).execute(&mut *connection).await?;
-        sqlx::query!("REFRESH MATERIALIZED VIEW CONCURRENTLY player_ranks;")
-            .execute(&mut *connection)
+        let row = sqlx::query!("SELECT nationality, subdivision FROM players WHERE id = $1", self.id)
+            .fetch_one(&mut *connection)
             .await?;
 
-        Ok(new_score.score)
+        if let Some(nation) = row.nationality {
+            update_nation_scores(&nation, &mut *connection).await?;
+
+            if let Some(subdivision) = row.subdivision {
+                update_subdivision_scores(&nation, &subdivision, &mut *connection).await?;
+            }
+        }
+
+        Ok(lists.into_iter().zip(scores).collect())
     }
 }
 
-pub async fn recompute_scores(connection: &mut PgConnection) -> Result<(), CoreError> {
-    sqlx::query!("SELECT recompute_player_scores();").execute(&mut *connection).await?;
-    sqlx::query!("SELECT recompute_nation_scores();").execute(&mut *connection).await?;
-    sqlx::query!("SELECT recompute_subdivision_scores();")
-        .execute(&mut *connection)
+/// The players whose scores need to be recomputed after a series of record modifications
+///
+/// Allows modifying many records without recomputing the holders' scores (and refreshing the
+/// `player_ranks` view) after every single modification.
+#[derive(Debug, Default)]
+pub struct ScoreUpdates(BTreeMap<i32, DatabasePlayer>);
+
+impl ScoreUpdates {
+    pub fn add(&mut self, player: &DatabasePlayer) {
+        self.0.entry(player.id).or_insert_with(|| player.clone());
+    }
+
+    /// Recomputes the scores of all affected players once, and then refreshes the `player_ranks`
+    /// view
+    pub async fn apply(self, connection: &mut PgConnection) -> Result<(), CoreError> {
+        if self.0.is_empty() {
+            return Ok(());
+        }
+
+        for player in self.0.values() {
+            player.recompute_score(&mut *connection).await?;
+        }
+
+        refresh_player_ranks(connection).await
+    }
+}
+
+async fn refresh_player_ranks(connection: &mut PgConnection) -> Result<(), CoreError> {
+    sqlx::query!("REFRESH MATERIALIZED VIEW CONCURRENTLY player_ranks;")
+        .execute(connection)
         .await?;
+
+    Ok(())
+}
+
+/// Recomputes the scores of all players, nations and subdivisions on all lists using the
+/// configured [`ScoringPolicy`](crate::score::ScoringPolicy), and stores the policy's fingerprint
+/// alongside them
+pub async fn recompute_scores(connection: &mut PgConnection) -> Result<(), CoreError> {
+    lock_scores(&mut *connection).await?;
+
+    let fingerprint = scoring_fingerprint();
+
+    let rows = sqlx::query!(
+        r#"SELECT player AS "player!", list AS "list!", position AS "position!", requirement AS "requirement!", progress::SMALLINT AS "progress!", players.nationality, players.subdivision
+           FROM score_giving INNER JOIN players ON players.id = player"#
+    )
+    .fetch_all(&mut *connection)
+    .await?;
+
+    let mut by_player = HashMap::<(i32, i32), Vec<ScoreGiving>>::new();
+    let mut by_nation = HashMap::<(String, i32), Vec<ScoreGiving>>::new();
+    let mut by_subdivision = HashMap::<(String, String, i32), Vec<ScoreGiving>>::new();
+
+    for row in rows {
+        let record = ScoreGiving {
+            list: row.list,
+            position: row.position,
+            requirement: row.requirement,
+            progress: row.progress,
+        };
+
+        if let Some(nation) = row.nationality {
+            if let Some(subdivision) = row.subdivision {
+                by_subdivision
+                    .entry((nation.clone(), subdivision, row.list))
+                    .or_default()
+                    .push(record.clone());
+            }
+            by_nation.entry((nation, row.list)).or_default().push(record.clone());
+        }
+        by_player.entry((row.player, row.list)).or_default().push(record);
+    }
+
+    let ((player_ids, player_lists), player_scores): ((Vec<_>, Vec<_>), Vec<_>) =
+        by_player.iter().map(|(&key, records)| (key, score_of_player(records))).unzip();
+    let ((nations, nation_lists), nation_scores): ((Vec<_>, Vec<_>), Vec<_>) = by_nation
+        .iter()
+        .map(|((nation, list), records)| ((nation.clone(), *list), score_of_group(records)))
+        .unzip();
+    let (subdivision_keys, subdivision_scores): (Vec<_>, Vec<_>) = by_subdivision
+        .iter()
+        .map(|(key, records)| (key.clone(), score_of_group(records)))
+        .unzip();
+    let mut subdivision_nations = Vec::new();
+    let mut subdivisions = Vec::new();
+    let mut subdivision_lists = Vec::new();
+
+    for (nation, subdivision, list) in subdivision_keys {
+        subdivision_nations.push(nation);
+        subdivisions.push(subdivision);
+        subdivision_lists.push(list);
+    }
+
+    // Players (nations, subdivisions) which no longer have any score giving records on some list simply have no row for it anymore
+    sqlx::query!("DELETE FROM player_scores").execute(&mut *connection).await?;
+    sqlx::query!(
+        "INSERT INTO player_scores (player, list, score) SELECT * FROM UNNEST($1::INTEGER[], $2::INTEGER[], $3::DOUBLE PRECISION[])",
+        &player_ids,
+        &player_lists,
+        &player_scores
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!("DELETE FROM nation_scores").execute(&mut *connection).await?;
+    sqlx::query!(
+        "INSERT INTO nation_scores (nation, list, score) SELECT * FROM UNNEST($1::VARCHAR[], $2::INTEGER[], $3::DOUBLE PRECISION[])",
+        &nations,
+        &nation_lists,
+        &nation_scores
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!("DELETE FROM subdivision_scores").execute(&mut *connection).await?;
+    sqlx::query!(
+        "INSERT INTO subdivision_scores (nation, subdivision, list, score) SELECT * FROM UNNEST($1::VARCHAR[], $2::VARCHAR[], $3::INTEGER[], $4::DOUBLE PRECISION[])",
+        &subdivision_nations,
+        &subdivisions,
+        &subdivision_lists,
+        &subdivision_scores
+    )
+    .execute(&mut *connection)
+    .await?;
     sqlx::query!("REFRESH MATERIALIZED VIEW CONCURRENTLY player_ranks;")
         .execute(&mut *connection)
         .await?;
+    store_fingerprint(fingerprint, connection).await?;
     Ok(())
 }
@@ -1,21 +1,68 @@
 use crate::{
+    list::DEFAULT_LIST,
     nationality::{Continent, Nationality, Subdivision},
     player::{DatabasePlayer, Player},
 };
 use futures::StreamExt;
 use pointercrate_core::{
     first_and_last,
-    pagination::{PageContext, Paginatable, PaginationParameters, PaginationQuery, __pagination_compat},
+    pagination::{
+        __pagination_compat, __sorted_pagination_compat, count_query, PageContext, Paginatable, PaginationParameters, PaginationQuery,
+        SortKey, SortParameters, SortValue, Sortable, SortedPaginationQuery,
+    },
     util::{non_nullable, nullable},
 };
 use serde::{Deserialize, Serialize};
-use sqlx::{postgres::PgConnection, Row};
+use sqlx::{
+    postgres::{PgArguments, PgConnection},
+    query::Query,
+    Postgres, Row,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PlayerSortKey {
+    #[default]
+    Id,
+    Name,
+    /// The score on the list being paginated (see [`PlayerPagination::list`])
+    Score,
+}
+
+impl SortKey for PlayerSortKey {
+    fn name(&self) -> &'static str {
+        match self {
+            PlayerSortKey::Id => "id",
+            PlayerSortKey::Name => "name",
+            PlayerSortKey::Score => "score",
+        }
+    }
+
+    fn column(&self) -> &'static str {
+        match self {
+            PlayerSortKey::Id => "players.id",
+            PlayerSortKey::Name => "players.name",
+            PlayerSortKey::Score => "COALESCE(player_scores.score, 0)",
+        }
+    }
+
+    fn sql_type(&self) -> &'static str {
+        match self {
+            PlayerSortKey::Id => "INTEGER",
+            PlayerSortKey::Name => "CITEXT",
+            PlayerSortKey::Score => "DOUBLE PRECISION",
+        }
+    }
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct PlayerPagination {
     #[serde(flatten)]
     pub params: PaginationParameters,
 
+    #[serde(flatten)]
+    pub sorting: SortParameters<PlayerSortKey>,
+
     #[serde(default, deserialize_with = "non_nullable")]
     name: Option<String>,
 
@@ -30,6 +77,10 @@ pub struct PlayerPagination {
 
     #[serde(default, deserialize_with = "nullable")]
     subdivision: Option<Option<String>>,
+
+    /// The list whose scores and ranks to include, defaulting to the [default list](DEFAULT_LIST)
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub list: Option<i32>,
 }
 
 impl PaginationQuery for PlayerPagination {
@@ -43,28 +94,63 @@ impl PaginationQuery for PlayerPagination {
             ..self.clone()
         }
     }
-}
 
-impl Paginatable<PlayerPagination> for Player {
-    first_and_last!("players");
+    fn preceding(&self) -> Option<Self> {
+        self.sorted_preceding()
+    }
 
-    async fn page(query: &PlayerPagination, connection: &mut PgConnection) -> Result<(Vec<Player>, PageContext), sqlx::Error> {
-        let order = query.params.order();
+    fn following(&self) -> Option<Self> {
+        self.sorted_following()
+    }
+}
 
-        let sql_query = format!(include_str!("../../sql/paginate_players_by_id.sql"), order);
+impl PlayerPagination {
+    fn sql(&self) -> String {
+        format!(
+            include_str!("../../sql/paginate_players_by_id.sql"),
+            cursor_conditions = self.sorting.cursor_conditions("players.id", 11),
+            order_by = self.sorting.order_by(&self.params, "players.id")
+        )
+    }
 
+    /// Binds the parameters of the given query, which is (derived from) the one returned by
+    /// [`PlayerPagination::sql`], returning at most `limit` rows (if given)
+    fn sql_query<'q>(&'q self, sql: &'q str, limit: Option<i32>) -> Query<'q, Postgres, PgArguments> {
         // FIXME(sqlx) once CITEXT is supported
-        let mut stream = sqlx::query(&sql_query)
-            .bind(query.params.before)
-            .bind(query.params.after)
-            .bind(query.name.as_deref())
-            .bind(query.name_contains.as_deref())
-            .bind(query.banned)
-            .bind(&query.nation)
-            .bind(query.nation == Some(None))
-            .bind(&query.subdivision)
-            .bind(query.params.limit + 1)
-            .fetch(connection);
+        let query = sqlx::query(sql)
+            .bind(self.params.before)
+            .bind(self.params.after)
+            .bind(self.name.as_deref())
+            .bind(self.name_contains.as_deref())
+            .bind(self.banned)
+            .bind(&self.nation)
+            .bind(self.nation == Some(None))
+            .bind(&self.subdivision)
+            .bind(limit)
+            .bind(self.list.unwrap_or(DEFAULT_LIST));
+
+        self.sorting.bind_cursors(query)
+    }
+}
+
+impl SortedPaginationQuery for PlayerPagination {
+    type Key = PlayerSortKey;
+
+    fn sorting(&self) -> &SortParameters<PlayerSortKey> {
+        &self.sorting
+    }
+
+    fn with_sorting(&self, sorting: SortParameters<PlayerSortKey>) -> Self {
+        Self { sorting, ..self.clone() }
+    }
+}
+
+impl Paginatable<PlayerPagination> for Player {
+    first_and_last!(PlayerPagination, "players");
+
+    async fn fetch(query: &PlayerPagination, connection: &mut PgConnection) -> Result<(Vec<Player>, PageContext), sqlx::Error> {
+        let sql = query.sql();
+        let mut stream = query.sql_query(&sql, Some(query.params.limit + 1)).fetch(connection);
 
         let mut players = Vec::new();
 
@@ -102,10 +188,20 @@ impl Paginatable<PlayerPagination> for Player {
                 score: row.get("score"),
                 rank: row.get("rank"),
                 nationality,
+                list: query.list.unwrap_or(DEFAULT_LIST),
             })
         }
 
-        Ok(__pagination_compat(&query.params, players))
+        Ok(__sorted_pagination_compat(&query.params, &query.sorting, players))
+    }
+
+    async fn count(query: &PlayerPagination, connection: &mut PgConnection) -> Result<Option<i64>, sqlx::Error> {
+        let query = query
+            .with_parameters(PaginationParameters::default())
+            .with_sorting(SortParameters::default());
+        let sql = count_query(&query.sql());
+
+        Ok(Some(query.sql_query(&sql, None).fetch_one(connection).await?.try_get(0)?))
     }
 
     fn pagination_id(&self) -> i32 {
@@ -113,6 +209,16 @@ impl Paginatable<PlayerPagination> for Player {
     }
 }
 
+impl Sortable<PlayerPagination> for Player {
+    fn sort_value(&self, key: PlayerSortKey) -> SortValue {
+        match key {
+            PlayerSortKey::Id => SortValue::Integer(self.base.id as i64),
+            PlayerSortKey::Name => SortValue::Text(self.base.name.clone()),
+            PlayerSortKey::Score => SortValue::Float(self.score),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct RankingPagination {
     #[serde(flatten)]
@@ -129,6 +235,10 @@ pub struct RankingPagination {
 
     #[serde(default, deserialize_with = "non_nullable")]
     name_contains: Option<String>,
+
+    /// The list whose ranking to paginate, defaulting to the [default list](DEFAULT_LIST)
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub list: Option<i32>,
 }
 
 impl PaginationQuery for RankingPagination {
@@ -153,15 +263,19 @@ pub struct RankedPlayer {
 }
 
 impl Paginatable<RankingPagination> for RankedPlayer {
-    async fn first_and_last(connection: &mut PgConnection) -> Result<Option<(i32, i32)>, sqlx::Error> {
-        Ok(sqlx::query!("SELECT COUNT(*) FROM players WHERE NOT banned AND score > 0.0")
-            .fetch_one(connection)
-            .await?
-            .count
-            .map(|max| (1, max as i32)))
+    async fn first_and_last(query: &RankingPagination, connection: &mut PgConnection) -> Result<Option<(i32, i32)>, sqlx::Error> {
+        // The indices are only unique within a list's ranking
+        Ok(sqlx::query!(
+            "SELECT COUNT(*) FROM player_ranks WHERE list = $1",
+            query.list.unwrap_or(DEFAULT_LIST)
+        )
+        .fetch_one(connection)
+        .await?
+        .count
+        .map(|max| (1, max as i32)))
     }
 
-    async fn page(query: &RankingPagination, connection: &mut PgConnection) -> Result<(Vec<RankedPlayer>, PageContext), sqlx::Error> {
+    async fn fetch(query: &RankingPagination, connection: &mut PgConnection) -> Result<(Vec<RankedPlayer>, PageContext), sqlx::Error> {
         let order = query.params.order();
 
         let sql_query = format!(include_str!("../../sql/paginate_player_ranking.sql"), order);
@@ -175,6 +289,7 @@ impl Paginatable<RankingPagination> for RankedPlayer {
             .bind(query.continent.as_ref().map(|c| c.to_sql()))
             .bind(&query.subdivision)
             .bind(query.params.limit + 1)
+            .bind(query.list.unwrap_or(DEFAULT_LIST))
             .fetch(connection);
 
         let mut players = Vec::new();
@@ -200,6 +315,7 @@ impl Paginatable<RankingPagination> for RankedPlayer {
                 score: row.get("score"),
                 rank: row.get("rank"),
                 nationality,
+                list: query.list.unwrap_or(DEFAULT_LIST),
             };
 
             players.push(RankedPlayer {
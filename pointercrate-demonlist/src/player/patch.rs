@@ -72,7 +72,9 @@ impl FullPlayer {
             self.set_name(name, connection).await?;
         }
 
-        self.player.score = self.player.base.update_score(connection).await?;
+        let scores = self.player.base.update_score(connection).await?;
+
+        self.player.score = scores.get(&self.player.list).copied().unwrap_or(0.0);
 
         Ok(self)
     }
@@ -219,6 +221,16 @@ impl FullPlayer {
 
         info!("Moved {} records from {} to {}", updated.rows_affected(), with, self);
 
+        // Remember the merge in the audit log, as otherwise it would only show up as a deletion of the second player
+        sqlx::query!(
+            "INSERT INTO player_merges (userid, id, merged, merged_name) (SELECT id, $1, $2, $3::text FROM active_user LIMIT 1)",
+            self.player.base.id,
+            with.id,
+            with.name
+        )
+        .execute(&mut *connection)
+        .await?;
+
         // Delete the second player
         sqlx::query!("DELETE FROM players WHERE id = $1", with.id)
             .execute(connection)
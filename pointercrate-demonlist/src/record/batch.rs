@@ -0,0 +1,74 @@
+use crate::{
+    config,
+    error::{DemonlistError, Result},
+    record::{FullRecord, PatchRecord},
+};
+use pointercrate_core::etag::Taggable;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// A single operation of a batch of record modifications
+///
+/// Since the operations are not sent as individual requests, each carries the value that would
+/// otherwise be sent in the `If-Match` header.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum RecordOperation {
+    Patch { id: i32, if_match: String, data: PatchRecord },
+    Delete { id: i32, if_match: String },
+}
+
+impl RecordOperation {
+    /// The id of the record this operation applies to
+    pub fn id(&self) -> i32 {
+        match self {
+            RecordOperation::Patch { id, .. } | RecordOperation::Delete { id, .. } => *id,
+        }
+    }
+
+    /// Ensures the given batch does not exceed the configured
+    /// [`max_batch_size`](config::max_batch_size) and contains at most one operation per record
+    ///
+    /// Each operation's `if_match` refers to the record as the client last saw it, which a second
+    /// operation on the same record could never match.
+    pub fn validate_batch(operations: &[RecordOperation]) -> Result<()> {
+        let max_size = config::max_batch_size()?;
+
+        if operations.len() > max_size {
+            return Err(DemonlistError::BatchTooLarge { max_size });
+        }
+
+        let mut seen = HashSet::new();
+
+        for operation in operations {
+            if !seen.insert(operation.id()) {
+                return Err(DemonlistError::DuplicateBatchRecord { record_id: operation.id() });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The outcome of a successful [`RecordOperation`]
+#[derive(Debug, Serialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum RecordOperationResult {
+    /// The record after the patch was applied, together with its new ETag
+    Patch {
+        record: Box<FullRecord>,
+        etag: String,
+    },
+    Delete {
+        id: i32,
+    },
+}
+
+impl RecordOperationResult {
+    pub fn patched(record: FullRecord) -> Self {
+        RecordOperationResult::Patch {
+            etag: record.etag_string(),
+            record: Box::new(record),
+        }
+    }
+}
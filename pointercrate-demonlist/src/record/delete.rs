@@ -1,14 +1,24 @@
-use crate::{error::Result, record::FullRecord};
+use crate::{error::Result, player::ScoreUpdates, record::FullRecord};
 use log::info;
 use sqlx::PgConnection;
 
 impl FullRecord {
     pub async fn delete(self, connection: &mut PgConnection) -> Result<()> {
+        let mut scores = ScoreUpdates::default();
+
+        self.delete_deferred(&mut scores, &mut *connection).await?;
+
+        Ok(scores.apply(connection).await?)
+    }
+
+    /// Like [`FullRecord::delete`], but instead of updating the holder's score, adds them to
+    /// `scores`
+    pub async fn delete_deferred(self, scores: &mut ScoreUpdates, connection: &mut PgConnection) -> Result<()> {
         info!("Deleting record {}", self);
 
-        FullRecord::delete_by_id(self.id, &mut *connection).await?;
+        FullRecord::delete_by_id(self.id, connection).await?;
 
-        self.player.update_score(connection).await?;
+        scores.add(&self.player);
 
         Ok(())
     }
@@ -6,6 +6,7 @@ use crate::{
     record::{FullRecord, MinimalRecordD, MinimalRecordP, RecordStatus},
     submitter::Submitter,
 };
+use chrono::{NaiveDate, NaiveDateTime};
 use futures::stream::StreamExt;
 use sqlx::{Error, PgConnection};
 
@@ -21,8 +22,13 @@ struct FetchedRecord {
     demon_id: i32,
     demon_name: String,
     position: i16,
+    list: i32,
     submitter_id: i32,
     submitter_banned: bool,
+    rejection_reason: Option<String>,
+    achieved_on: Option<NaiveDate>,
+    submitted_at: Option<NaiveDateTime>,
+    approved_at: Option<NaiveDateTime>,
 }
 
 impl FullRecord {
@@ -47,11 +53,16 @@ impl FullRecord {
                     id: row.demon_id,
                     position: row.position,
                     name: row.demon_name,
+                    list: row.list,
                 },
                 submitter: Some(Submitter {
                     id: row.submitter_id,
                     banned: row.submitter_banned,
                 }),
+                rejection_reason: row.rejection_reason,
+                achieved_on: row.achieved_on,
+                submitted_at: row.submitted_at,
+                approved_at: row.approved_at,
             }),
 
             Err(Error::RowNotFound) => Err(DemonlistError::RecordNotFound { record_id: id }),
@@ -63,8 +74,9 @@ impl FullRecord {
 pub async fn approved_records_by(player: &DatabasePlayer, connection: &mut PgConnection) -> Result<Vec<MinimalRecordD>> {
     let mut stream = sqlx::query!(
         r#"SELECT records.id, progress, CASE WHEN players.link_banned THEN NULL ELSE records.video::text END, demons.id AS demon_id, 
-         demons.name, demons.position FROM records INNER JOIN demons ON records.demon = demons.id INNER JOIN players ON players.id 
-         = $1 WHERE status_ = 'APPROVED' AND records.player = $1"#,
+         demons.name, demons.position, demons.list, records.achieved_on, record_timeline.approved_at AS "approved_at?" FROM records 
+         INNER JOIN demons ON records.demon = demons.id INNER JOIN players ON players.id = $1 INNER JOIN record_timeline ON 
+         record_timeline.id = records.id WHERE status_ = 'APPROVED' AND records.player = $1"#,
         player.id
     )
     .fetch(connection);
@@ -83,7 +95,10 @@ pub async fn approved_records_by(player: &DatabasePlayer, connection: &mut PgCon
                 id: row.demon_id,
                 position: row.position,
                 name: row.name,
+                list: row.list,
             },
+            achieved_on: row.achieved_on,
+            approved_at: row.approved_at,
         })
     }
 
@@ -100,13 +115,17 @@ pub async fn approved_records_on(demon: &MinimalDemon, connection: &mut PgConnec
         banned: bool,
         nation: Option<String>,
         iso_country_code: Option<String>,
+        achieved_on: Option<NaiveDate>,
+        approved_at: Option<NaiveDateTime>,
     }
 
     let mut stream = sqlx::query_as!(
         Fetched,
         r#"SELECT records.id, progress, CASE WHEN players.link_banned THEN NULL ELSE video::text END, players.id AS player_id, 
-         players.name, players.banned, nation::TEXT, iso_country_code::TEXT FROM records INNER JOIN players ON records.player = players.id LEFT OUTER JOIN nationalities ON nationality = iso_country_code WHERE status_ = 'APPROVED' AND 
-         records.demon = $1 ORDER BY progress DESC, id ASC"#,
+         players.name, players.banned, nation::TEXT, iso_country_code::TEXT, records.achieved_on, record_timeline.approved_at AS "approved_at?" 
+         FROM records INNER JOIN players ON records.player = players.id LEFT OUTER JOIN nationalities ON nationality = iso_country_code 
+         INNER JOIN record_timeline ON record_timeline.id = records.id WHERE status_ = 'APPROVED' AND records.demon = $1 
+         ORDER BY progress DESC, records.id ASC"#,
         demon.id
     )
     .fetch(connection);
@@ -134,6 +153,8 @@ pub async fn approved_records_on(demon: &MinimalDemon, connection: &mut PgConnec
                 }),
                 _ => None,
             },
+            achieved_on: row.achieved_on,
+            approved_at: row.approved_at,
         })
     }
 
@@ -22,12 +22,22 @@
 //!   the 'under consideration' status makes. A record under consideration IS NOT UNIQUE!
 
 pub use self::{
+    batch::{RecordOperation, RecordOperationResult},
     get::{approved_records_by, approved_records_on, submission_count},
-    paginate::RecordPagination,
+    paginate::{RecordPagination, RecordSortKey},
     patch::PatchRecord,
     post::Submission,
+    rejection::{rejection_reason_stats, RejectionReason, RejectionReasonCount},
+    track::TrackedSubmission,
 };
-use crate::{demon::MinimalDemon, error::Result, nationality::Nationality, player::DatabasePlayer, submitter::Submitter};
+use crate::{
+    demon::MinimalDemon,
+    error::{DemonlistError, Result},
+    nationality::Nationality,
+    player::DatabasePlayer,
+    submitter::Submitter,
+};
+use chrono::{Days, NaiveDate, NaiveDateTime, Utc};
 use derive_more::Display;
 use pointercrate_core::etag::Taggable;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
@@ -39,12 +49,15 @@ use std::{
 };
 
 pub mod audit;
+mod batch;
 mod delete;
 mod get;
 pub mod note;
 mod paginate;
 mod patch;
 mod post;
+mod rejection;
+mod track;
 
 #[derive(Debug, Eq, PartialEq, Clone, Copy, Hash, Default)]
 pub enum RecordStatus {
@@ -66,7 +79,7 @@ impl RecordStatus {
         .to_owned()
     }
 
-    fn from_sql(sql: &str) -> Self {
+    pub(crate) fn from_sql(sql: &str) -> Self {
         match sql {
             "SUBMITTED" => RecordStatus::Submitted,
             "APPROVED" => RecordStatus::Approved,
@@ -75,6 +88,12 @@ impl RecordStatus {
             _ => panic!("invalid record state: {}", sql),
         }
     }
+
+    /// Whether records with this status still need to be reviewed, e.g. are either 'submitted' or
+    /// 'under consideration'
+    pub fn awaits_review(self) -> bool {
+        matches!(self, RecordStatus::Submitted | RecordStatus::UnderConsideration)
+    }
 }
 
 impl Display for RecordStatus {
@@ -128,6 +147,21 @@ pub struct FullRecord {
     pub demon: MinimalDemon,
     pub submitter: Option<Submitter>,
     pub raw_footage: Option<String>,
+
+    /// The key of the reason this record was rejected for, if it is rejected and a reason was
+    /// given. See [`rejection_reasons`](crate::config::rejection_reasons)
+    pub rejection_reason: Option<String>,
+
+    /// The day this record was achieved on, if known
+    pub achieved_on: Option<NaiveDate>,
+
+    /// When this record was submitted, as derived from the audit log. `None` for records older
+    /// than the audit log
+    pub submitted_at: Option<NaiveDateTime>,
+
+    /// When this record was approved, as derived from the audit log. `None` if it is not approved,
+    /// or if it was approved before the audit log existed
+    pub approved_at: Option<NaiveDateTime>,
 }
 
 impl Taggable for FullRecord {
@@ -139,6 +173,9 @@ impl Taggable for FullRecord {
         self.status.hash(&mut hasher);
         self.player.id.hash(&mut hasher);
         self.demon.id.hash(&mut hasher);
+        self.rejection_reason.hash(&mut hasher);
+        self.achieved_on.hash(&mut hasher);
+        // timestamps are derived from the status -> no hash
         // notes have sub-endpoint -> no hash
         // submitter cannot be patched -> no hash
         // raw footage cannot be patched -> no hash
@@ -165,6 +202,8 @@ pub struct MinimalRecordD {
     pub video: Option<String>,
     pub status: RecordStatus,
     pub demon: MinimalDemon,
+    pub achieved_on: Option<NaiveDate>,
+    pub approved_at: Option<NaiveDateTime>,
 }
 
 #[derive(Debug, Hash, Serialize, Deserialize, Display, PartialEq, Eq)]
@@ -176,6 +215,8 @@ pub struct MinimalRecordP {
     pub status: RecordStatus,
     pub player: DatabasePlayer,
     pub nationality: Option<Nationality>,
+    pub achieved_on: Option<NaiveDate>,
+    pub approved_at: Option<NaiveDateTime>,
 }
 
 impl FullRecord {
@@ -188,4 +229,32 @@ impl FullRecord {
         .await?
         .was_modified)
     }
+
+    /// Derives [`FullRecord::submitted_at`] and [`FullRecord::approved_at`] anew from the audit
+    /// log
+    pub(crate) async fn refresh_timeline(&mut self, connection: &mut PgConnection) -> Result<()> {
+        let row = sqlx::query!(
+            r#"SELECT submitted_at AS "submitted_at?", approved_at AS "approved_at?" FROM record_timeline WHERE id = $1"#,
+            self.id
+        )
+        .fetch_one(connection)
+        .await?;
+
+        self.submitted_at = row.submitted_at;
+        self.approved_at = row.approved_at;
+
+        Ok(())
+    }
+}
+
+/// Checks that the given day a record was achieved on does not lie in the future
+///
+/// Allows for one day of leeway, since the submitter might live in a timezone that is already a day
+/// ahead of UTC.
+pub(crate) fn validate_achieved_on(achieved_on: NaiveDate) -> Result<()> {
+    if achieved_on > Utc::now().date_naive() + Days::new(1) {
+        return Err(DemonlistError::AchievedInFuture);
+    }
+
+    Ok(())
 }
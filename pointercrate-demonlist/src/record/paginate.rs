@@ -6,17 +6,58 @@ use crate::{
 use futures::StreamExt;
 use pointercrate_core::{
     first_and_last,
-    pagination::{PageContext, Paginatable, PaginationParameters, PaginationQuery, __pagination_compat},
+    pagination::{
+        __sorted_pagination_compat, count_query, PageContext, Paginatable, PaginationParameters, PaginationQuery, SortKey, SortParameters,
+        SortValue, Sortable, SortedPaginationQuery,
+    },
     util::{non_nullable, nullable},
 };
 use serde::{Deserialize, Serialize};
-use sqlx::{postgres::PgRow, PgConnection, Row};
+use sqlx::{
+    postgres::{PgArguments, PgRow},
+    query::Query,
+    PgConnection, Postgres, Row,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordSortKey {
+    #[default]
+    Id,
+    Progress,
+}
+
+impl SortKey for RecordSortKey {
+    fn name(&self) -> &'static str {
+        match self {
+            RecordSortKey::Id => "id",
+            RecordSortKey::Progress => "progress",
+        }
+    }
+
+    fn column(&self) -> &'static str {
+        match self {
+            RecordSortKey::Id => "records.id",
+            RecordSortKey::Progress => "progress",
+        }
+    }
+
+    fn sql_type(&self) -> &'static str {
+        match self {
+            RecordSortKey::Id => "INTEGER",
+            RecordSortKey::Progress => "SMALLINT",
+        }
+    }
+}
 
 #[derive(Clone, Debug, Serialize, Deserialize, Default)]
 pub struct RecordPagination {
     #[serde(flatten)]
     pub params: PaginationParameters,
 
+    #[serde(flatten)]
+    pub sorting: SortParameters<RecordSortKey>,
+
     progress: Option<i16>,
 
     #[serde(default, deserialize_with = "non_nullable")]
@@ -54,6 +95,9 @@ pub struct RecordPagination {
 
     #[serde(default, deserialize_with = "non_nullable")]
     pub submitter: Option<i32>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub list: Option<i32>,
 }
 
 impl PaginationQuery for RecordPagination {
@@ -67,34 +111,69 @@ impl PaginationQuery for RecordPagination {
             ..self.clone()
         }
     }
+
+    fn preceding(&self) -> Option<Self> {
+        self.sorted_preceding()
+    }
+
+    fn following(&self) -> Option<Self> {
+        self.sorted_following()
+    }
+}
+
+impl RecordPagination {
+    fn sql(&self) -> String {
+        format!(
+            include_str!("../../sql/paginate_records.sql"),
+            cursor_conditions = self.sorting.cursor_conditions("records.id", 18),
+            order_by = self.sorting.order_by(&self.params, "records.id")
+        )
+    }
+
+    /// Binds the parameters of the given query, which is (derived from) the one returned by
+    /// [`RecordPagination::sql`], returning at most `limit` rows (if given)
+    fn sql_query<'q>(&'q self, sql: &'q str, limit: Option<i32>) -> Query<'q, Postgres, PgArguments> {
+        let query = sqlx::query(sql)
+            .bind(self.params.before)
+            .bind(self.params.after)
+            .bind(self.progress)
+            .bind(self.progress_lt)
+            .bind(self.progress_gt)
+            .bind(self.demon_position)
+            .bind(self.demon_position_lt)
+            .bind(self.demon_position_gt)
+            .bind(self.status.map(|s| s.to_sql()))
+            .bind(self.demon.as_deref())
+            .bind(self.demon_id)
+            .bind(&self.video)
+            .bind(self.video == Some(None))
+            .bind(self.player)
+            .bind(self.submitter)
+            .bind(limit)
+            .bind(self.list);
+
+        self.sorting.bind_cursors(query)
+    }
+}
+
+impl SortedPaginationQuery for RecordPagination {
+    type Key = RecordSortKey;
+
+    fn sorting(&self) -> &SortParameters<RecordSortKey> {
+        &self.sorting
+    }
+
+    fn with_sorting(&self, sorting: SortParameters<RecordSortKey>) -> Self {
+        Self { sorting, ..self.clone() }
+    }
 }
 
 impl Paginatable<RecordPagination> for MinimalRecordPD {
-    first_and_last!("records");
-
-    async fn page(query: &RecordPagination, connection: &mut PgConnection) -> Result<(Vec<MinimalRecordPD>, PageContext), sqlx::Error> {
-        let order = query.params.order();
-
-        let sql_query = format!(include_str!("../../sql/paginate_records.sql"), order);
-
-        let mut stream = sqlx::query(&sql_query)
-            .bind(query.params.before)
-            .bind(query.params.after)
-            .bind(query.progress)
-            .bind(query.progress_lt)
-            .bind(query.progress_gt)
-            .bind(query.demon_position)
-            .bind(query.demon_position_lt)
-            .bind(query.demon_position_gt)
-            .bind(query.status.map(|s| s.to_sql()))
-            .bind(query.demon.as_deref())
-            .bind(query.demon_id)
-            .bind(&query.video)
-            .bind(query.video == Some(None))
-            .bind(query.player)
-            .bind(query.submitter)
-            .bind(query.params.limit + 1)
-            .fetch(&mut *connection);
+    first_and_last!(RecordPagination, "records");
+
+    async fn fetch(query: &RecordPagination, connection: &mut PgConnection) -> Result<(Vec<MinimalRecordPD>, PageContext), sqlx::Error> {
+        let sql = query.sql();
+        let mut stream = query.sql_query(&sql, Some(query.params.limit + 1)).fetch(&mut *connection);
 
         let mut records = Vec::new();
 
@@ -115,14 +194,33 @@ impl Paginatable<RecordPagination> for MinimalRecordPD {
                     id: row.try_get("demon_id")?,
                     position: row.try_get("position")?,
                     name: row.try_get("demon_name")?,
+                    list: row.try_get("list")?,
                 },
             })
         }
 
-        Ok(__pagination_compat(&query.params, records))
+        Ok(__sorted_pagination_compat(&query.params, &query.sorting, records))
+    }
+
+    async fn count(query: &RecordPagination, connection: &mut PgConnection) -> Result<Option<i64>, sqlx::Error> {
+        let query = query
+            .with_parameters(PaginationParameters::default())
+            .with_sorting(SortParameters::default());
+        let sql = count_query(&query.sql());
+
+        Ok(Some(query.sql_query(&sql, None).fetch_one(connection).await?.try_get(0)?))
     }
 
     fn pagination_id(&self) -> i32 {
         self.id
     }
 }
+
+impl Sortable<RecordPagination> for MinimalRecordPD {
+    fn sort_value(&self, key: RecordSortKey) -> SortValue {
+        match key {
+            RecordSortKey::Id => SortValue::Integer(self.id as i64),
+            RecordSortKey::Progress => SortValue::Integer(self.progress as i64),
+        }
+    }
+}
@@ -1,9 +1,12 @@
 use crate::{
+    config,
     demon::MinimalDemon,
     error::{DemonlistError, Result},
-    player::DatabasePlayer,
-    record::{FullRecord, RecordStatus},
+    player::{DatabasePlayer, ScoreUpdates},
+    record::{validate_achieved_on, FullRecord, RecordStatus},
+    video::VideoHosts,
 };
+use chrono::NaiveDate;
 use log::{info, warn};
 use pointercrate_core::{
     error::CoreError,
@@ -31,11 +34,31 @@ pub struct PatchRecord {
 
     #[serde(default, deserialize_with = "non_nullable")]
     demon_id: Option<i32>,
+
+    #[serde(default, deserialize_with = "nullable")]
+    rejection_reason: Option<Option<String>>,
+
+    #[serde(default, deserialize_with = "nullable")]
+    achieved_on: Option<Option<NaiveDate>>,
 }
 
 impl FullRecord {
     /// Must be called inside a transaction
-    pub async fn apply_patch(mut self, data: PatchRecord, connection: &mut PgConnection) -> Result<Self> {
+    pub async fn apply_patch(self, data: PatchRecord, video_hosts: &VideoHosts, connection: &mut PgConnection) -> Result<Self> {
+        let mut scores = ScoreUpdates::default();
+
+        let record = self.apply_patch_deferred(data, video_hosts, &mut scores, &mut *connection).await?;
+
+        scores.apply(connection).await?;
+
+        Ok(record)
+    }
+
+    /// Like [`FullRecord::apply_patch`], but instead of updating the scores of all players
+    /// affected by the patch, adds them to `scores`
+    pub async fn apply_patch_deferred(
+        mut self, data: PatchRecord, video_hosts: &VideoHosts, scores: &mut ScoreUpdates, connection: &mut PgConnection,
+    ) -> Result<Self> {
         info!("Applying patch {:?} for record {}", data, self);
 
         if let Some(progress) = data.progress {
@@ -45,18 +68,32 @@ impl FullRecord {
         if let Some(video) = data.video {
             match video {
                 None => self.delete_video(connection).await?,
-                Some(video) => self.set_video(video, connection).await?,
+                Some(video) => self.set_video(video, video_hosts, connection).await?,
             }
         }
 
+        // Only require a reason when a record gets rejected, so that records rejected before reasons were
+        // required can still be edited
+        let newly_rejected = data.status == Some(RecordStatus::Rejected) && self.status != RecordStatus::Rejected;
+
         if let Some(status) = data.status {
             self.set_status(status, connection).await?
         }
 
+        match data.rejection_reason {
+            Some(reason) => self.set_rejection_reason(reason, connection).await?,
+            None if newly_rejected && config::require_rejection_reason()? => return Err(DemonlistError::RejectionReasonRequired),
+            None => (),
+        }
+
+        if let Some(achieved_on) = data.achieved_on {
+            self.set_achieved_on(achieved_on, connection).await?;
+        }
+
         if let Some(player) = data.player {
             let player = DatabasePlayer::by_name_or_create(player.as_ref(), connection).await?;
 
-            self.set_player(player, connection).await?;
+            self.set_player_deferred(player, scores, connection).await?;
         }
 
         match (data.demon, data.demon_id) {
@@ -71,7 +108,7 @@ impl FullRecord {
 
         // Not all record update require recomputing scores (for example, changing status from "submitted" to "under consideration")
         // but the logic for correctly determining this is hard, and updating scores of individual players cheap, so we do not bother.
-        self.player.update_score(connection).await?;
+        scores.add(&self.player);
 
         Ok(self)
     }
@@ -198,8 +235,8 @@ impl FullRecord {
         Ok(())
     }
 
-    pub async fn set_video(&mut self, video: String, connection: &mut PgConnection) -> Result<()> {
-        let video = crate::video::validate(&video)?;
+    pub async fn set_video(&mut self, video: String, video_hosts: &VideoHosts, connection: &mut PgConnection) -> Result<()> {
+        let video = video_hosts.validate(&video)?;
 
         if Some(&video) == self.video.as_ref() {
             return Ok(());
@@ -246,6 +283,18 @@ impl FullRecord {
     ///
     /// If this record is approved, updates the score of the old holder.
     pub async fn set_player(&mut self, player: DatabasePlayer, connection: &mut PgConnection) -> Result<()> {
+        let mut scores = ScoreUpdates::default();
+
+        self.set_player_deferred(player, &mut scores, &mut *connection).await?;
+
+        Ok(scores.apply(connection).await?)
+    }
+
+    /// Like [`FullRecord::set_player`], but instead of updating the old holder's score, adds them
+    /// to `scores`
+    pub async fn set_player_deferred(
+        &mut self, player: DatabasePlayer, scores: &mut ScoreUpdates, connection: &mut PgConnection,
+    ) -> Result<()> {
         if player.banned && self.status != RecordStatus::Rejected {
             return Err(DemonlistError::PlayerBanned);
         }
@@ -258,13 +307,54 @@ impl FullRecord {
             .execute(&mut *connection)
             .await?;
 
-        self.player.update_score(connection).await?;
+        scores.add(&self.player);
 
         self.player = player;
 
         Ok(())
     }
 
+    /// Sets (or, if `None` is given, removes) the reason this record was rejected for
+    ///
+    /// The record must be rejected, and the reason must be one of the configured
+    /// [`rejection_reasons`](crate::config::rejection_reasons).
+    pub async fn set_rejection_reason(&mut self, reason: Option<String>, connection: &mut PgConnection) -> Result<()> {
+        if self.status != RecordStatus::Rejected {
+            return Err(DemonlistError::RecordNotRejected);
+        }
+
+        match reason {
+            Some(reason) => {
+                if !config::rejection_reasons()?.contains(&reason) {
+                    return Err(DemonlistError::UnknownRejectionReason { reason });
+                }
+
+                sqlx::query!(
+                    "INSERT INTO record_rejections (record, reason) VALUES ($1, $2) ON CONFLICT (record) DO UPDATE SET reason = $2",
+                    self.id,
+                    reason
+                )
+                .execute(connection)
+                .await?;
+
+                self.rejection_reason = Some(reason);
+            },
+            None => {
+                if config::require_rejection_reason()? {
+                    return Err(DemonlistError::RejectionReasonRequired);
+                }
+
+                sqlx::query!("DELETE FROM record_rejections WHERE record = $1", self.id)
+                    .execute(connection)
+                    .await?;
+
+                self.rejection_reason = None;
+            },
+        }
+
+        Ok(())
+    }
+
     /// Updates this record's status
     pub async fn set_status(&mut self, status: RecordStatus, connection: &mut PgConnection) -> Result<()> {
         // To uphold the invariants outlined in the module documentation, we need to do some preparations.
@@ -338,10 +428,41 @@ impl FullRecord {
             status.to_sql().to_string(),
             self.id
         )
-        .execute(connection)
+        .execute(&mut *connection)
         .await?;
 
+        // Once a record has been reviewed, whoever was reviewing it is done with it
+        if !status.awaits_review() {
+            sqlx::query!("DELETE FROM record_claims WHERE record = $1", self.id)
+                .execute(&mut *connection)
+                .await?;
+        }
+
+        if status != RecordStatus::Rejected && self.rejection_reason.is_some() {
+            sqlx::query!("DELETE FROM record_rejections WHERE record = $1", self.id)
+                .execute(&mut *connection)
+                .await?;
+
+            self.rejection_reason = None;
+        }
+
         self.status = status;
+        self.refresh_timeline(connection).await?;
+
+        Ok(())
+    }
+
+    /// Sets the day this record was achieved on, or removes it if `None` is given
+    pub async fn set_achieved_on(&mut self, achieved_on: Option<NaiveDate>, connection: &mut PgConnection) -> Result<()> {
+        if let Some(achieved_on) = achieved_on {
+            validate_achieved_on(achieved_on)?;
+        }
+
+        sqlx::query!("UPDATE records SET achieved_on = $1 WHERE id = $2", achieved_on, self.id)
+            .execute(connection)
+            .await?;
+
+        self.achieved_on = achieved_on;
 
         Ok(())
     }
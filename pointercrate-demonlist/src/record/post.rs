@@ -1,10 +1,13 @@
 use crate::{
     demon::MinimalDemon,
     error::{DemonlistError, Result},
+    list::List,
     player::{claim::PlayerClaim, DatabasePlayer},
-    record::{FullRecord, RecordStatus},
+    record::{validate_achieved_on, FullRecord, RecordStatus},
     submitter::Submitter,
+    video::VideoHosts,
 };
+use chrono::NaiveDate;
 use derive_more::Display;
 use log::debug;
 use serde::Deserialize;
@@ -27,6 +30,10 @@ pub struct Submission {
     /// An initial, submitter provided note for the demon.
     #[serde(default)]
     note: Option<String>,
+
+    /// The day the record was achieved on, if the submitter knows it
+    #[serde(default)]
+    achieved_on: Option<NaiveDate>,
 }
 
 #[derive(Debug)]
@@ -39,6 +46,7 @@ pub struct NormalizedSubmission {
     video: Option<String>,
     raw_footage: Option<String>,
     note: Option<String>,
+    achieved_on: Option<NaiveDate>,
 }
 
 #[derive(Debug)]
@@ -50,6 +58,7 @@ pub struct ValidatedSubmission {
     player: DatabasePlayer,
     demon: MinimalDemon,
     note: Option<String>,
+    achieved_on: Option<NaiveDate>,
 }
 
 impl Submission {
@@ -57,14 +66,19 @@ impl Submission {
         self.video.is_some()
     }
 
+    /// The id of the demon this submission is for
+    pub fn demon_id(&self) -> i32 {
+        self.demon
+    }
+
     pub fn status(&self) -> RecordStatus {
         self.status
     }
 
-    pub async fn normalize(self, connection: &mut PgConnection) -> Result<NormalizedSubmission> {
+    pub async fn normalize(self, video_hosts: &VideoHosts, connection: &mut PgConnection) -> Result<NormalizedSubmission> {
         // validate video
         let video = match self.video {
-            Some(ref video) => Some(crate::video::validate(video)?),
+            Some(ref video) => Some(video_hosts.validate(video)?),
             None => None,
         };
 
@@ -80,6 +94,7 @@ impl Submission {
             video,
             raw_footage: self.raw_footage,
             note: self.note,
+            achieved_on: self.achieved_on,
         })
     }
 }
@@ -95,14 +110,16 @@ impl NormalizedSubmission {
             return Err(DemonlistError::PlayerBanned);
         }
 
+        let list = List::by_id(self.demon.list, &mut *connection).await?;
+
         // Cannot submit records for the legacy list (it is possible to directly add them for list mods)
-        if self.demon.position > crate::config::extended_list_size() && self.status == RecordStatus::Submitted {
+        if self.demon.position > list.extended_list_size && self.status == RecordStatus::Submitted {
             return Err(DemonlistError::SubmitLegacy);
         }
 
         // Can only submit 100% records for the extended list (it is possible to directly add them for list
         // mods)
-        if self.demon.position > crate::config::list_size() && self.progress != 100 && self.status == RecordStatus::Submitted {
+        if self.demon.position > list.list_size && self.progress != 100 && self.status == RecordStatus::Submitted {
             return Err(DemonlistError::Non100Extended);
         }
 
@@ -113,6 +130,10 @@ impl NormalizedSubmission {
             return Err(DemonlistError::InvalidProgress { requirement });
         }
 
+        if let Some(achieved_on) = self.achieved_on {
+            validate_achieved_on(achieved_on)?;
+        }
+
         debug!("Submission is valid, checking for duplicates!");
 
         // Search for existing records. If a video exists, we also check if a record with
@@ -166,6 +187,7 @@ impl NormalizedSubmission {
             player: self.player,
             demon: self.demon,
             note: self.note,
+            achieved_on: self.achieved_on,
         })
     }
 }
@@ -173,13 +195,14 @@ impl NormalizedSubmission {
 impl ValidatedSubmission {
     pub async fn create(self, submitter: Submitter, connection: &mut PgConnection) -> Result<FullRecord> {
         let id = sqlx::query!(
-            "INSERT INTO records (progress, video, status_, player, submitter, demon, raw_footage) VALUES ($1, $2::TEXT, 'SUBMITTED', $3, $4, $5, $6) RETURNING id",
+            "INSERT INTO records (progress, video, status_, player, submitter, demon, raw_footage, achieved_on) VALUES ($1, $2::TEXT, 'SUBMITTED', $3, $4, $5, $6, $7) RETURNING id",
             self.progress,
             self.video,
             self.player.id,
             submitter.id,
             self.demon.id,
-            self.raw_footage
+            self.raw_footage,
+            self.achieved_on
         )
         .fetch_one(&mut *connection)
         .await?
@@ -194,6 +217,10 @@ impl ValidatedSubmission {
             player: self.player,
             demon: self.demon,
             submitter: Some(submitter),
+            rejection_reason: None,
+            achieved_on: self.achieved_on,
+            submitted_at: None,
+            approved_at: None,
         };
 
         // Dealing with different status and upholding their invariant is complicated, we should not
@@ -210,6 +237,8 @@ impl ValidatedSubmission {
             }
         }
 
+        record.refresh_timeline(&mut *connection).await?;
+
         if self.status != RecordStatus::Submitted {
             record.player.update_score(connection).await?;
         }
@@ -223,6 +252,7 @@ mod tests {
     use crate::{
         demon::MinimalDemon,
         error::DemonlistError,
+        list::DEFAULT_LIST,
         player::DatabasePlayer,
         record::{post::NormalizedSubmission, RecordStatus},
     };
@@ -241,11 +271,13 @@ mod tests {
                 id: 1,
                 position: 1,
                 name: "Bloodbath".to_string(),
+                list: DEFAULT_LIST,
             },
             status: RecordStatus::Submitted,
             video: None,
             raw_footage: None,
             note: None,
+            achieved_on: None,
         }
         .validate(&mut conn)
         .await;
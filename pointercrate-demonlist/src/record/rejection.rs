@@ -0,0 +1,54 @@
+use crate::{config, error::Result};
+use pointercrate_core::localization::tr;
+use serde::Serialize;
+use sqlx::PgConnection;
+
+/// One of the configured reasons reviewers can give when rejecting a record
+#[derive(Debug, Serialize)]
+pub struct RejectionReason {
+    pub key: String,
+
+    /// The description of this reason, in the language of the current request
+    pub description: String,
+}
+
+impl RejectionReason {
+    /// Gets the catalogue of all configured rejection reasons
+    ///
+    /// Needs to be called from a localized context
+    pub fn all() -> Result<Vec<RejectionReason>> {
+        Ok(config::rejection_reasons()?
+            .iter()
+            .map(|key| RejectionReason {
+                key: key.clone(),
+                description: tr(&format!("rejection-reason-{}", key)),
+            })
+            .collect())
+    }
+}
+
+/// How many of the rejected records were rejected for some reason
+#[derive(Debug, Serialize)]
+pub struct RejectionReasonCount {
+    /// The key of the reason, or `None` for records that were rejected without a reason
+    pub reason: Option<String>,
+    pub count: i64,
+}
+
+/// Counts how often each rejection reason was given, most frequent first
+///
+/// If a list is given, only records on that list are considered.
+pub async fn rejection_reason_stats(list: Option<i32>, connection: &mut PgConnection) -> Result<Vec<RejectionReasonCount>> {
+    Ok(sqlx::query_as!(
+        RejectionReasonCount,
+        r#"SELECT record_rejections.reason AS "reason?", COUNT(*) AS "count!" FROM records 
+           INNER JOIN demons ON demons.id = records.demon 
+           LEFT OUTER JOIN record_rejections ON record_rejections.record = records.id 
+           WHERE records.status_ = 'REJECTED' AND ($1::INTEGER IS NULL OR demons.list = $1) 
+           GROUP BY record_rejections.reason 
+           ORDER BY 2 DESC, 1"#,
+        list
+    )
+    .fetch_all(connection)
+    .await?)
+}
@@ -0,0 +1,85 @@
+use crate::{
+    demon::MinimalDemon,
+    error::{DemonlistError, Result},
+    player::DatabasePlayer,
+    record::{
+        note::{notes_on, Note},
+        FullRecord, RecordStatus,
+    },
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::PgConnection;
+
+/// The publicly visible state of a submission, as looked up via its tracking token
+///
+/// Deliberately does not contain the submitter or the raw footage, as anyone who knows the
+/// token can see this.
+#[derive(Debug, Serialize)]
+pub struct TrackedSubmission {
+    pub id: i32,
+    pub progress: i16,
+    pub video: Option<String>,
+    pub status: RecordStatus,
+    pub player: DatabasePlayer,
+    pub demon: MinimalDemon,
+    pub rejection_reason: Option<String>,
+
+    /// The public notes on this submission
+    pub notes: Vec<Note>,
+}
+
+impl TrackedSubmission {
+    /// Looks up the submission tracked by the given token
+    pub async fn by_token(token: &str, connection: &mut PgConnection) -> Result<TrackedSubmission> {
+        let record_id = sqlx::query!(
+            "SELECT record FROM submission_tracking WHERE token_hash = $1",
+            hash_tracking_token(token)
+        )
+        .fetch_optional(&mut *connection)
+        .await?
+        .ok_or(DemonlistError::TrackedSubmissionNotFound)?
+        .record;
+
+        let record = FullRecord::by_id(record_id, &mut *connection).await?;
+        let notes = notes_on(record_id, true, connection).await?;
+
+        Ok(TrackedSubmission {
+            id: record.id,
+            progress: record.progress,
+            video: record.video,
+            status: record.status,
+            player: record.player,
+            demon: record.demon,
+            rejection_reason: record.rejection_reason,
+            notes,
+        })
+    }
+}
+
+impl FullRecord {
+    /// Generates a new tracking token for this record
+    ///
+    /// The token is random and long enough to not be guessable. It is only ever returned to the
+    /// submitter, and cannot be retrieved again later, as only its hash is stored.
+    pub async fn create_tracking_token(&self, connection: &mut PgConnection) -> Result<String> {
+        let token = sqlx::query_scalar!(r#"SELECT replace(gen_random_uuid()::TEXT || gen_random_uuid()::TEXT, '-', '') AS "token!""#)
+            .fetch_one(&mut *connection)
+            .await?;
+
+        sqlx::query!(
+            "INSERT INTO submission_tracking (record, token_hash) VALUES ($1, $2)",
+            self.id,
+            hash_tracking_token(&token)
+        )
+        .execute(connection)
+        .await?;
+
+        Ok(token)
+    }
+}
+
+/// Hex encoded SHA-256 hash of a tracking token, which is what gets stored in the database
+fn hash_tracking_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
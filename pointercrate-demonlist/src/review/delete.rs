@@ -0,0 +1,16 @@
+use crate::{error::Result, review::RecordClaim};
+use sqlx::PgConnection;
+
+impl RecordClaim {
+    pub async fn release(self, connection: &mut PgConnection) -> Result<()> {
+        sqlx::query!(
+            "DELETE FROM record_claims WHERE record = $1 AND member_id = $2",
+            self.record_id,
+            self.claimed_by.id
+        )
+        .execute(connection)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,45 @@
+use crate::{
+    error::{DemonlistError, Result},
+    review::RecordClaim,
+};
+use pointercrate_core::audit::NamedId;
+use sqlx::PgConnection;
+
+impl RecordClaim {
+    /// Gets the active claim on the record with the given ID, if any
+    pub async fn active_on(record_id: i32, connection: &mut PgConnection) -> Result<Option<RecordClaim>> {
+        let row = sqlx::query!(
+            r#"SELECT record_claims.member_id, members.name, claimed_at, expires_at FROM record_claims INNER JOIN members ON
+             record_claims.member_id = members.member_id WHERE record = $1 AND expires_at > (NOW() AT TIME ZONE 'utc')"#,
+            record_id
+        )
+        .fetch_optional(connection)
+        .await?;
+
+        Ok(row.map(|row| RecordClaim {
+            record_id,
+            claimed_by: NamedId {
+                id: row.member_id,
+                name: Some(row.name),
+            },
+            claimed_at: row.claimed_at,
+            expires_at: row.expires_at,
+        }))
+    }
+
+    /// Ensures that the record with the given ID is not currently claimed by anyone but the user
+    /// with the given ID
+    pub async fn require_unclaimed_by_others(record_id: i32, user_id: i32, connection: &mut PgConnection) -> Result<()> {
+        match RecordClaim::active_on(record_id, connection).await? {
+            Some(claim) if claim.claimed_by.id != user_id => Err(claim.into_conflict()),
+            _ => Ok(()),
+        }
+    }
+
+    pub(super) fn into_conflict(self) -> DemonlistError {
+        DemonlistError::RecordClaimedByOther {
+            record_id: self.record_id,
+            claimed_by: self.claimed_by.name.unwrap_or_default(),
+        }
+    }
+}
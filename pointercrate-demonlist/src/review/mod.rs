@@ -0,0 +1,33 @@
+//! Module containing the review queue for records awaiting review (e.g. records that are either
+//! 'submitted' or 'under consideration')
+//!
+//! To prevent multiple reviewers from working on the same submission at once, a reviewer can
+//! _claim_ a record. A claim lasts for [`record_claim_minutes`](crate::config::record_claim_minutes),
+//! during which no one else can claim or modify the record. Claims are released once the record
+//! is approved or rejected. Reviewers are expected to explicitly release claims on records they
+//! decide not to review after all, but forgotten claims simply expire.
+
+pub use self::{
+    paginate::{QueuedRecord, ReviewQueuePagination, ReviewSortKey},
+    stats::{reviewer_stats, ReviewerStats},
+};
+use chrono::NaiveDateTime;
+use pointercrate_core::audit::NamedId;
+use serde::Serialize;
+
+mod delete;
+mod get;
+mod paginate;
+mod put;
+mod stats;
+
+/// An active claim of some reviewer on a record
+#[derive(Debug, Serialize, Clone)]
+pub struct RecordClaim {
+    #[serde(skip)]
+    pub record_id: i32,
+
+    pub claimed_by: NamedId,
+    pub claimed_at: NaiveDateTime,
+    pub expires_at: NaiveDateTime,
+}
@@ -0,0 +1,238 @@
+use crate::{
+    demon::MinimalDemon,
+    player::DatabasePlayer,
+    record::{MinimalRecordPD, RecordStatus},
+    review::RecordClaim,
+};
+use futures::StreamExt;
+use pointercrate_core::{
+    audit::NamedId,
+    first_and_last,
+    pagination::{
+        __sorted_pagination_compat, count_query, PageContext, Paginatable, PaginationParameters, PaginationQuery, SortKey, SortParameters,
+        SortValue, Sortable, SortedPaginationQuery,
+    },
+    util::non_nullable,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{
+    postgres::{PgArguments, PgRow},
+    query::Query,
+    PgConnection, Postgres, Row,
+};
+
+/// The orders in which the review queue can be worked through
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewSortKey {
+    /// Oldest submissions first (in ascending order)
+    #[default]
+    Age,
+
+    /// Submissions for the hardest demons first (in ascending order)
+    Position,
+
+    /// Submissions by the least trustworthy submitters first (in ascending order), see the
+    /// `submitter_trust` view
+    Trust,
+}
+
+impl SortKey for ReviewSortKey {
+    fn name(&self) -> &'static str {
+        match self {
+            ReviewSortKey::Age => "age",
+            ReviewSortKey::Position => "position",
+            ReviewSortKey::Trust => "trust",
+        }
+    }
+
+    fn column(&self) -> &'static str {
+        match self {
+            ReviewSortKey::Age => "records.id",
+            ReviewSortKey::Position => "demons.position",
+            ReviewSortKey::Trust => "submitter_trust.trust",
+        }
+    }
+
+    fn sql_type(&self) -> &'static str {
+        match self {
+            ReviewSortKey::Age => "INTEGER",
+            ReviewSortKey::Position => "SMALLINT",
+            ReviewSortKey::Trust => "DOUBLE PRECISION",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+pub struct ReviewQueuePagination {
+    #[serde(flatten)]
+    pub params: PaginationParameters,
+
+    #[serde(flatten)]
+    pub sorting: SortParameters<ReviewSortKey>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub status: Option<RecordStatus>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub list: Option<i32>,
+
+    /// Whether to only include records someone has (`true`) or no one has (`false`) claimed
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub claimed: Option<bool>,
+
+    /// Only include records claimed by the user with this ID
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub claimed_by: Option<i32>,
+}
+
+/// A record in the review queue, together with some information relevant for reviewing it
+#[derive(Debug, Serialize)]
+pub struct QueuedRecord {
+    #[serde(flatten)]
+    pub record: MinimalRecordPD,
+
+    /// The ID of the [`Submitter`](crate::submitter::Submitter) of this record
+    pub submitter: i32,
+
+    /// How trustworthy the submitter of this record is, between `0` and `1`
+    pub trust: f64,
+
+    /// The active claim on this record, if someone is currently reviewing it
+    pub claim: Option<RecordClaim>,
+}
+
+impl PaginationQuery for ReviewQueuePagination {
+    fn parameters(&self) -> PaginationParameters {
+        self.params
+    }
+
+    fn with_parameters(&self, parameters: PaginationParameters) -> Self {
+        Self {
+            params: parameters,
+            ..self.clone()
+        }
+    }
+
+    fn preceding(&self) -> Option<Self> {
+        self.sorted_preceding()
+    }
+
+    fn following(&self) -> Option<Self> {
+        self.sorted_following()
+    }
+}
+
+impl ReviewQueuePagination {
+    fn sql(&self) -> String {
+        format!(
+            include_str!("../../sql/paginate_review_queue.sql"),
+            cursor_conditions = self.sorting.cursor_conditions("records.id", 8),
+            order_by = self.sorting.order_by(&self.params, "records.id")
+        )
+    }
+
+    /// Binds the parameters of the given query, which is (derived from) the one returned by
+    /// [`ReviewQueuePagination::sql`], returning at most `limit` rows (if given)
+    fn sql_query<'q>(&'q self, sql: &'q str, limit: Option<i32>) -> Query<'q, Postgres, PgArguments> {
+        let query = sqlx::query(sql)
+            .bind(self.params.before)
+            .bind(self.params.after)
+            .bind(self.status.map(|s| s.to_sql()))
+            .bind(self.list)
+            .bind(self.claimed)
+            .bind(self.claimed_by)
+            .bind(limit);
+
+        self.sorting.bind_cursors(query)
+    }
+}
+
+impl SortedPaginationQuery for ReviewQueuePagination {
+    type Key = ReviewSortKey;
+
+    fn sorting(&self) -> &SortParameters<ReviewSortKey> {
+        &self.sorting
+    }
+
+    fn with_sorting(&self, sorting: SortParameters<ReviewSortKey>) -> Self {
+        Self { sorting, ..self.clone() }
+    }
+}
+
+impl Paginatable<ReviewQueuePagination> for QueuedRecord {
+    first_and_last!(ReviewQueuePagination, "records");
+
+    async fn fetch(query: &ReviewQueuePagination, connection: &mut PgConnection) -> Result<(Vec<QueuedRecord>, PageContext), sqlx::Error> {
+        let sql = query.sql();
+        let mut stream = query.sql_query(&sql, Some(query.params.limit + 1)).fetch(&mut *connection);
+
+        let mut records = Vec::new();
+
+        while let Some(row) = stream.next().await {
+            let row: PgRow = row?;
+            let id = row.try_get("id")?;
+
+            let claim = match row.try_get::<Option<i32>, _>("claimed_by")? {
+                Some(member_id) => Some(RecordClaim {
+                    record_id: id,
+                    claimed_by: NamedId {
+                        id: member_id,
+                        name: row.try_get("claimed_by_name")?,
+                    },
+                    claimed_at: row.try_get("claimed_at")?,
+                    expires_at: row.try_get("expires_at")?,
+                }),
+                None => None,
+            };
+
+            records.push(QueuedRecord {
+                record: MinimalRecordPD {
+                    id,
+                    progress: row.try_get("progress")?,
+                    video: row.try_get("video")?,
+                    status: RecordStatus::from_sql(&row.try_get::<String, _>("status")?),
+                    player: DatabasePlayer {
+                        id: row.try_get("player_id")?,
+                        name: row.try_get("player_name")?,
+                        banned: row.try_get("player_banned")?,
+                    },
+                    demon: MinimalDemon {
+                        id: row.try_get("demon_id")?,
+                        position: row.try_get("position")?,
+                        name: row.try_get("demon_name")?,
+                        list: row.try_get("list")?,
+                    },
+                },
+                submitter: row.try_get("submitter")?,
+                trust: row.try_get("trust")?,
+                claim,
+            })
+        }
+
+        Ok(__sorted_pagination_compat(&query.params, &query.sorting, records))
+    }
+
+    async fn count(query: &ReviewQueuePagination, connection: &mut PgConnection) -> Result<Option<i64>, sqlx::Error> {
+        let query = query
+            .with_parameters(PaginationParameters::default())
+            .with_sorting(SortParameters::default());
+        let sql = count_query(&query.sql());
+
+        Ok(Some(query.sql_query(&sql, None).fetch_one(connection).await?.try_get(0)?))
+    }
+
+    fn pagination_id(&self) -> i32 {
+        self.record.id
+    }
+}
+
+impl Sortable<ReviewQueuePagination> for QueuedRecord {
+    fn sort_value(&self, key: ReviewSortKey) -> SortValue {
+        match key {
+            ReviewSortKey::Age => SortValue::Integer(self.record.id as i64),
+            ReviewSortKey::Position => SortValue::Integer(self.record.demon.position as i64),
+            ReviewSortKey::Trust => SortValue::Float(self.trust),
+        }
+    }
+}
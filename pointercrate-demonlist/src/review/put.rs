@@ -0,0 +1,59 @@
+use crate::{
+    config,
+    error::{DemonlistError, Result},
+    record::FullRecord,
+    review::RecordClaim,
+};
+use log::info;
+use pointercrate_core::audit::NamedId;
+use pointercrate_user::User;
+use sqlx::PgConnection;
+
+impl FullRecord {
+    /// Claims this record for review by the given user
+    ///
+    /// If the user already claimed this record, their claim is extended instead. Fails if some
+    /// other user currently claims this record.
+    ///
+    /// Must be called inside a transaction
+    pub async fn claim_for_review(&self, reviewer: &User, connection: &mut PgConnection) -> Result<RecordClaim> {
+        if !self.status.awaits_review() {
+            return Err(DemonlistError::RecordAlreadyReviewed);
+        }
+
+        // Lock the record, so that concurrent claims cannot both succeed
+        sqlx::query!("SELECT id FROM records WHERE id = $1 FOR UPDATE", self.id)
+            .fetch_one(&mut *connection)
+            .await?;
+
+        let claimed_at = match RecordClaim::active_on(self.id, connection).await? {
+            Some(claim) if claim.claimed_by.id != reviewer.id => return Err(claim.into_conflict()),
+            Some(claim) => Some(claim.claimed_at),
+            None => None,
+        };
+
+        let row = sqlx::query!(
+            "INSERT INTO record_claims (record, member_id, claimed_at, expires_at) VALUES ($1, $2, COALESCE($3, NOW() AT TIME ZONE \
+             'utc'), (NOW() AT TIME ZONE 'utc') + make_interval(mins => $4)) ON CONFLICT (record) DO UPDATE SET member_id = \
+             EXCLUDED.member_id, claimed_at = EXCLUDED.claimed_at, expires_at = EXCLUDED.expires_at RETURNING claimed_at, expires_at",
+            self.id,
+            reviewer.id,
+            claimed_at,
+            config::record_claim_minutes()?
+        )
+        .fetch_one(connection)
+        .await?;
+
+        info!("{} claimed record {} until {}", reviewer, self, row.expires_at);
+
+        Ok(RecordClaim {
+            record_id: self.id,
+            claimed_by: NamedId {
+                id: reviewer.id,
+                name: Some(reviewer.name.clone()),
+            },
+            claimed_at: row.claimed_at,
+            expires_at: row.expires_at,
+        })
+    }
+}
@@ -0,0 +1,60 @@
+use crate::error::Result;
+use pointercrate_core::audit::NamedId;
+use serde::Serialize;
+use sqlx::PgConnection;
+
+/// How much reviewing some user has been doing recently
+#[derive(Debug, Serialize)]
+pub struct ReviewerStats {
+    pub reviewer: NamedId,
+
+    /// The number of records this user currently claims
+    pub claimed: i64,
+
+    /// The number of records this user changed the status of while they were awaiting review,
+    /// during the last 30 days
+    pub reviewed: i64,
+}
+
+/// Gets the [`ReviewerStats`] of all users who either currently claim records, or reviewed
+/// records during the last 30 days, ordered by how many records they reviewed
+///
+/// If a list is given, only records on that list are counted.
+pub async fn reviewer_stats(list: Option<i32>, connection: &mut PgConnection) -> Result<Vec<ReviewerStats>> {
+    let rows = sqlx::query!(
+        r#"SELECT member_id AS "member_id!", name AS "name!", claimed AS "claimed!", reviewed AS "reviewed!" FROM (
+             SELECT members.member_id, members.name,
+                    (SELECT COUNT(*) FROM record_claims
+                       INNER JOIN records ON records.id = record_claims.record
+                       INNER JOIN demons ON demons.id = records.demon
+                       WHERE record_claims.member_id = members.member_id
+                       AND expires_at > (NOW() AT TIME ZONE 'utc')
+                       AND ($1::INTEGER IS NULL OR demons.list = $1)) AS claimed,
+                    (SELECT COUNT(DISTINCT record_modifications.id) FROM record_modifications
+                       INNER JOIN records ON records.id = record_modifications.id
+                       INNER JOIN demons ON demons.id = records.demon
+                       WHERE userid = members.member_id
+                       AND record_modifications.status_ IN ('SUBMITTED', 'UNDER_CONSIDERATION')
+                       AND time > (NOW() AT TIME ZONE 'utc') - INTERVAL '30 days'
+                       AND ($1::INTEGER IS NULL OR demons.list = $1)) AS reviewed
+             FROM members
+           ) AS stats
+           WHERE claimed > 0 OR reviewed > 0
+           ORDER BY reviewed DESC, claimed DESC, name"#,
+        list
+    )
+    .fetch_all(connection)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| ReviewerStats {
+            reviewer: NamedId {
+                id: row.member_id,
+                name: Some(row.name),
+            },
+            claimed: row.claimed,
+            reviewed: row.reviewed,
+        })
+        .collect())
+}
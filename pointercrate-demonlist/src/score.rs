@@ -0,0 +1,441 @@
+//! Module containing the scoring policy that determines how many points a record is worth on the
+//! stats viewer.
+//!
+//! By default, pointercrate's own piecewise exponential curve is used (see
+//! [`PointercrateScoringPolicy`]). Lists that want to award points differently can implement
+//! [`ScoringPolicy`] and register their implementation via [`set_scoring_policy`] before launching
+//! the rocket. Instances hosting multiple lists can additionally give individual lists their own
+//! policy via [`set_list_scoring_policy`]. The policy is used both for the scores displayed next
+//! to records on demon pages and for the cached per-list scores stored in the `player_scores`,
+//! `nation_scores` and `subdivision_scores` tables.
+//!
+//! Since scores are cached in the database, changing the policy requires all of them to be
+//! recomputed. For this, whenever all scores are recomputed, a fingerprint of the policy used is
+//! stored in the database alongside them, and [`sync_scoring_policy`] schedules a
+//! [`RecomputeScores`] job if it no longer matches the configured policy.
+
+use log::info;
+use pointercrate_core::{
+    error::CoreError,
+    job::{self, Job},
+};
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, PoisonError, RwLock},
+};
+
+static SCORING_POLICIES: RwLock<ScoringPolicies> = RwLock::new(ScoringPolicies::new());
+
+/// A formula for computing how many points a record is worth
+pub trait ScoringPolicy: Send + Sync {
+    /// Computes the score a record with the given progress on a demon at the given position, with
+    /// the given record requirement, is worth.
+    ///
+    /// Records with progress below the requirement should be worth nothing.
+    fn score(&self, position: i16, requirement: i16, progress: i16) -> f64;
+
+    /// A value identifying this policy, used to detect when scores cached in the database were
+    /// computed using a different policy and need to be recomputed.
+    ///
+    /// The default implementation samples [`ScoringPolicy::score`] for a range of positions,
+    /// requirements and progress values, meaning it changes whenever the formula does. Policies
+    /// whose formula depends on demons past position 500 should override this.
+    fn fingerprint(&self) -> i64 {
+        // FNV-1a, since the fingerprint needs to be stable across compilations (which
+        // `DefaultHasher` does not guarantee).
+        let mut hash = 0xcbf29ce484222325u64;
+
+        for position in 1..=500 {
+            for requirement in (0..=100).step_by(10) {
+                for progress in (requirement..=100).step_by(5) {
+                    for byte in self.score(position, requirement, progress).to_bits().to_le_bytes() {
+                        hash ^= byte as u64;
+                        hash = hash.wrapping_mul(0x100000001b3);
+                    }
+                }
+            }
+        }
+
+        hash as i64
+    }
+}
+
+/// The scoring formula used by pointercrate itself
+///
+/// Only the first 150 demons award points. A 100% record on a demon is worth a fixed value
+/// decreasing with the demon's position. Records with less progress give at most a tenth of that,
+/// scaling exponentially between the record requirement and 100%.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PointercrateScoringPolicy;
+
+impl ScoringPolicy for PointercrateScoringPolicy {
+    fn score(&self, position: i16, requirement: i16, progress: i16) -> f64 {
+        if progress < requirement {
+            return 0.0;
+        }
+
+        let beaten_score = match position {
+            56..=150 => 1.039035131_f64 * ((185.7_f64 * (-0.02715_f64 * position as f64).exp()) + 14.84_f64),
+            36..=55 => 1.0371139743_f64 * ((212.61_f64 * 1.036_f64.powf(1_f64 - position as f64)) + 25.071_f64),
+            21..=35 => ((250_f64 - 83.389_f64) * (1.0099685_f64.powf(2_f64 - position as f64)) - 31.152_f64) * 1.0371139743_f64,
+            4..=20 => ((326.1_f64 * (-0.0871_f64 * position as f64).exp()) + 51.09_f64) * 1.037117142_f64,
+            1..=3 => (-18.2899079915_f64 * position as f64) + 368.2899079915_f64,
+            _ => 0_f64,
+        };
+
+        if progress != 100 {
+            (beaten_score * (5f64.powf((progress - requirement) as f64 / (100f64 - requirement as f64)))) / 10f64
+        } else {
+            beaten_score
+        }
+    }
+}
+
+/// The [`ScoringPolicy`]s configured for this pointercrate instance
+struct ScoringPolicies {
+    /// The policy used by all lists without a policy of their own. [`PointercrateScoringPolicy`]
+    /// if unset.
+    default: Option<Arc<dyn ScoringPolicy>>,
+
+    /// Policies of lists that award points differently from the default, by list id
+    lists: BTreeMap<i32, Arc<dyn ScoringPolicy>>,
+}
+
+impl ScoringPolicies {
+    const fn new() -> Self {
+        ScoringPolicies {
+            default: None,
+            lists: BTreeMap::new(),
+        }
+    }
+
+    fn default_policy(&self) -> Arc<dyn ScoringPolicy> {
+        self.default.clone().unwrap_or_else(|| Arc::new(PointercrateScoringPolicy))
+    }
+
+    fn policy_of(&self, list: i32) -> Arc<dyn ScoringPolicy> {
+        self.lists.get(&list).cloned().unwrap_or_else(|| self.default_policy())
+    }
+
+    /// Combines the fingerprints of all configured policies.
+    ///
+    /// Without any per-list policies, this is just the fingerprint of the default policy.
+    fn fingerprint(&self) -> i64 {
+        let mut fingerprint = self.default_policy().fingerprint();
+
+        for (list, policy) in &self.lists {
+            for value in [*list as i64, policy.fingerprint()] {
+                // FNV-1a, see ScoringPolicy::fingerprint
+                for byte in value.to_le_bytes() {
+                    fingerprint ^= byte as i64;
+                    fingerprint = fingerprint.wrapping_mul(0x100000001b3);
+                }
+            }
+        }
+
+        fingerprint
+    }
+}
+
+/// Sets the default [`ScoringPolicy`] used by this pointercrate instance for all lists without a
+/// policy of their own.
+///
+/// Should be called before any scores are computed (ideally before the rocket is launched).
+/// Calling it again replaces the previously set policy. If never called,
+/// [`PointercrateScoringPolicy`] is used.
+pub fn set_scoring_policy(policy: impl ScoringPolicy + 'static) {
+    SCORING_POLICIES.write().unwrap_or_else(PoisonError::into_inner).default = Some(Arc::new(policy));
+}
+
+/// Sets the [`ScoringPolicy`] used for records on the list with the given id, overriding the
+/// default policy for that list.
+///
+/// Like [`set_scoring_policy`], this should be called before any scores are computed.
+pub fn set_list_scoring_policy(list: i32, policy: impl ScoringPolicy + 'static) {
+    SCORING_POLICIES
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .lists
+        .insert(list, Arc::new(policy));
+}
+
+/// Gets the [`ScoringPolicy`] used for records on the list with the given id
+pub fn scoring_policy(list: i32) -> Arc<dyn ScoringPolicy> {
+    SCORING_POLICIES.read().unwrap_or_else(PoisonError::into_inner).policy_of(list)
+}
+
+/// Gets a value identifying the [`ScoringPolicy`]s currently configured for all lists, as stored
+/// in the database alongside the cached scores
+pub fn scoring_fingerprint() -> i64 {
+    SCORING_POLICIES.read().unwrap_or_else(PoisonError::into_inner).fingerprint()
+}
+
+/// Job for recomputing all cached scores, see [`recompute_scores`](crate::player::recompute_scores)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecomputeScores;
+
+impl Job for RecomputeScores {
+    const KIND: &'static str = "recompute_scores";
+
+    // Recomputing scores always recomputes all of them, so there is no point in queueing up
+    // multiple recomputations
+    fn dedup_key(&self) -> Option<String> {
+        Some("all".to_owned())
+    }
+}
+
+/// Schedules a recomputation of all cached scores if they were computed using a different
+/// [`ScoringPolicy`] than the one currently configured.
+///
+/// The stored fingerprint is only updated once the scores have actually been recomputed, so if the
+/// recomputation fails, it is scheduled again on the next call. Returns whether a recomputation was
+/// scheduled, which is not the case if one is already pending.
+pub async fn sync_scoring_policy(connection: &mut PgConnection) -> Result<bool, CoreError> {
+    let fingerprint = scoring_fingerprint();
+    let stored = sqlx::query_scalar!("SELECT fingerprint FROM scoring_policy")
+        .fetch_optional(&mut *connection)
+        .await?;
+
+    if stored == Some(fingerprint) {
+        return Ok(false);
+    }
+
+    info!(
+        "Scoring policy changed (fingerprint {:?} -> {}), scheduling recomputation of all scores",
+        stored, fingerprint
+    );
+
+    Ok(job::enqueue(&RecomputeScores, &mut *connection).await?.is_some())
+}
+
+/// Records that all cached scores were computed using the [`ScoringPolicy`]s with the given
+/// fingerprint
+pub(crate) async fn store_fingerprint(fingerprint: i64, connection: &mut PgConnection) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "INSERT INTO scoring_policy (fingerprint) VALUES ($1) ON CONFLICT (id) DO UPDATE SET fingerprint = EXCLUDED.fingerprint",
+        fingerprint
+    )
+    .execute(connection)
+    .await?;
+
+    Ok(())
+}
+
+/// A record (or verification) that gives points, as given by the `score_giving` view
+#[derive(Debug, Clone)]
+pub(crate) struct ScoreGiving {
+    pub list: i32,
+    pub position: i16,
+    pub requirement: i16,
+    pub progress: i16,
+}
+
+impl ScoreGiving {
+    fn score(&self) -> f64 {
+        scoring_policy(self.list).score(self.position, self.requirement, self.progress)
+    }
+}
+
+/// Sums up the scores of the given records
+pub(crate) fn score_of_player<'a>(records: impl IntoIterator<Item = &'a ScoreGiving>) -> f64 {
+    records.into_iter().map(ScoreGiving::score).sum()
+}
+
+/// Computes the score of a group of players (a nation or subdivision) from all their records.
+///
+/// For each demon, only the best record from within the group counts.
+pub(crate) fn score_of_group<'a>(records: impl IntoIterator<Item = &'a ScoreGiving>) -> f64 {
+    let mut best = HashMap::<i16, f64>::new();
+
+    for record in records {
+        let score = record.score();
+        let entry = best.entry(record.position).or_insert(score);
+
+        if *entry < score {
+            *entry = score;
+        }
+    }
+
+    best.values().sum()
+}
+
+/// Computes one score per list, using the given function to compute the score of all records
+/// on a single list.
+///
+/// Scores are returned as two vectors of list ids and scores, ready to be passed to postgres
+/// `UNNEST`.
+pub(crate) fn scores_per_list(records: Vec<ScoreGiving>, score_of: fn(&[ScoreGiving]) -> f64) -> (Vec<i32>, Vec<f64>) {
+    let mut by_list = HashMap::<i32, Vec<ScoreGiving>>::new();
+
+    for record in records {
+        by_list.entry(record.list).or_default().push(record);
+    }
+
+    by_list.iter().map(|(&list, records)| (list, score_of(records))).unzip()
+}
+
+pub(crate) async fn score_giving_of_player(player_id: i32, connection: &mut PgConnection) -> Result<Vec<ScoreGiving>, sqlx::Error> {
+    sqlx::query_as!(
+        ScoreGiving,
+        r#"SELECT list AS "list!", position AS "position!", requirement AS "requirement!", progress::SMALLINT AS "progress!" FROM score_giving WHERE player = $1"#,
+        player_id
+    )
+    .fetch_all(connection)
+    .await
+}
+
+/// Arbitrary key of the advisory lock held while updating cached scores
+const SCORES_LOCK: i64 = 0x5c0_5e5;
+
+/// Prevents concurrent transactions from updating the cached scores until the current one ends
+///
+/// Score updates delete and re-insert rows of the score tables, so two transactions updating
+/// overlapping scores (e.g. a full recomputation and an update of a single player) would otherwise
+/// conflict on insertion. Outside of a transaction, this does nothing.
+pub(crate) async fn lock_scores(connection: &mut PgConnection) -> Result<(), sqlx::Error> {
+    sqlx::query!("SELECT pg_advisory_xact_lock($1)", SCORES_LOCK)
+        .execute(connection)
+        .await?;
+
+    Ok(())
+}
+
+/// Recomputes the scores of the given nation on all lists and stores them in the database
+pub(crate) async fn update_nation_scores(iso_country_code: &str, connection: &mut PgConnection) -> Result<(), sqlx::Error> {
+    lock_scores(&mut *connection).await?;
+
+    let records = sqlx::query_as!(
+        ScoreGiving,
+        r#"SELECT list AS "list!", position AS "position!", requirement AS "requirement!", progress::SMALLINT AS "progress!" FROM score_giving INNER JOIN players ON players.id = player WHERE players.nationality = $1"#,
+        iso_country_code
+    )
+    .fetch_all(&mut *connection)
+    .await?;
+
+    let (lists, scores) = scores_per_list(records, |records| score_of_group(records));
+
+    sqlx::query!("DELETE FROM nation_scores WHERE nation = $1", iso_country_code)
+        .execute(&mut *connection)
+        .await?;
+    sqlx::query!(
+        "INSERT INTO nation_scores (nation, list, score) SELECT $1, * FROM UNNEST($2::INTEGER[], $3::DOUBLE PRECISION[])",
+        iso_country_code,
+        &lists,
+        &scores
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(())
+}
+
+/// Recomputes the scores of the given subdivision on all lists and stores them in the database
+pub(crate) async fn update_subdivision_scores(
+    iso_country_code: &str, iso_code: &str, connection: &mut PgConnection,
+) -> Result<(), sqlx::Error> {
+    lock_scores(&mut *connection).await?;
+
+    let records = sqlx::query_as!(
+        ScoreGiving,
+        r#"SELECT list AS "list!", position AS "position!", requirement AS "requirement!", progress::SMALLINT AS "progress!" FROM score_giving INNER JOIN players ON players.id = player WHERE players.nationality = $1 AND players.subdivision = $2"#,
+        iso_country_code,
+        iso_code
+    )
+    .fetch_all(&mut *connection)
+    .await?;
+
+    let (lists, scores) = scores_per_list(records, |records| score_of_group(records));
+
+    sqlx::query!(
+        "DELETE FROM subdivision_scores WHERE nation = $1 AND subdivision = $2",
+        iso_country_code,
+        iso_code
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!(
+        "INSERT INTO subdivision_scores (nation, subdivision, list, score) SELECT $1, $2, * FROM UNNEST($3::INTEGER[], $4::DOUBLE PRECISION[])",
+        iso_country_code,
+        iso_code,
+        &lists,
+        &scores
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sync_scoring_policy, PointercrateScoringPolicy, ScoringPolicies, ScoringPolicy};
+    use crate::player::recompute_scores;
+    use sqlx::{pool::PoolConnection, Postgres};
+    use std::sync::Arc;
+
+    struct FlatPolicy;
+
+    impl ScoringPolicy for FlatPolicy {
+        fn score(&self, position: i16, requirement: i16, progress: i16) -> f64 {
+            if position <= 10 && progress >= requirement {
+                10.0
+            } else {
+                0.0
+            }
+        }
+    }
+
+    #[test]
+    fn test_pointercrate_policy() {
+        let policy = PointercrateScoringPolicy;
+
+        assert_eq!(policy.score(1, 50, 100), 350.0);
+        assert_eq!(policy.score(1, 50, 49), 0.0);
+        assert_eq!(policy.score(1, 50, 50), 35.0);
+        assert_eq!(policy.score(151, 50, 100), 0.0);
+        assert!(policy.score(75, 50, 100) > policy.score(76, 50, 100));
+    }
+
+    #[test]
+    fn test_fingerprint_differs() {
+        assert_eq!(PointercrateScoringPolicy.fingerprint(), PointercrateScoringPolicy.fingerprint());
+        assert_ne!(PointercrateScoringPolicy.fingerprint(), FlatPolicy.fingerprint());
+    }
+
+    #[test]
+    fn test_per_list_policies() {
+        let mut policies = ScoringPolicies::new();
+        let default_fingerprint = PointercrateScoringPolicy.fingerprint();
+
+        assert_eq!(policies.policy_of(2).score(1, 50, 100), 350.0);
+        assert_eq!(policies.fingerprint(), default_fingerprint);
+
+        policies.lists.insert(2, Arc::new(FlatPolicy));
+
+        assert_eq!(policies.policy_of(1).score(1, 50, 100), 350.0);
+        assert_eq!(policies.policy_of(2).score(1, 50, 100), 10.0);
+        assert_ne!(policies.fingerprint(), default_fingerprint);
+
+        policies.default = Some(Arc::new(FlatPolicy));
+
+        assert_eq!(policies.policy_of(1).score(1, 50, 100), 10.0);
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_sync_scoring_policy(mut conn: PoolConnection<Postgres>) {
+        assert!(sync_scoring_policy(&mut conn).await.unwrap(), "Scores not recomputed on first sync");
+        assert!(
+            !sync_scoring_policy(&mut conn).await.unwrap(),
+            "Recomputation scheduled again while one was still pending"
+        );
+
+        recompute_scores(&mut conn).await.unwrap();
+
+        assert!(
+            !sync_scoring_policy(&mut conn).await.unwrap(),
+            "Scores recomputed despite unchanged policy"
+        );
+    }
+}
@@ -2,7 +2,7 @@ use crate::submitter::Submitter;
 use futures::StreamExt;
 use pointercrate_core::{
     first_and_last,
-    pagination::{PageContext, Paginatable, PaginationParameters, PaginationQuery, __pagination_compat},
+    pagination::{__pagination_compat, PageContext, Paginatable, PaginationParameters, PaginationQuery},
     util::non_nullable,
 };
 use serde::{Deserialize, Serialize};
@@ -31,9 +31,9 @@ impl PaginationQuery for SubmitterPagination {
 }
 
 impl Paginatable<SubmitterPagination> for Submitter {
-    first_and_last!("submitters", "submitter_id");
+    first_and_last!(SubmitterPagination, "submitters", "submitter_id");
 
-    async fn page(query: &SubmitterPagination, connection: &mut PgConnection) -> Result<(Vec<Submitter>, PageContext), sqlx::Error> {
+    async fn fetch(query: &SubmitterPagination, connection: &mut PgConnection) -> Result<(Vec<Submitter>, PageContext), sqlx::Error> {
         let order = query.params.order();
 
         let sql_query = format!("SELECT submitter_id, banned FROM submitters WHERE (submitter_id < $1 OR $1 IS NULL) AND (submitter_id > $2 OR $2 IS NULL) AND (banned = $3 OR $3 IS NULL) ORDER BY submitter_id {} LIMIT $4", order);
@@ -1,3 +1,9 @@
+//! Module for dealing with the sites videos of records and verifications can be hosted on
+//!
+//! Which hosts are accepted is determined by a [`VideoHosts`] registry. Its [`Default`]
+//! implementation contains all hosts pointercrate has historically supported, additional ones can
+//! be added by implementing [`VideoHost`].
+
 use crate::error::{DemonlistError, Result};
 use pointercrate_core::error::CoreError;
 use url::Url;
@@ -15,97 +21,373 @@ const EVERYPLAY_FORMAT: &str = "https://everyplay.com/videos/{video_id}' or'http
 const VIMEO_FORMAT: &str = "https://vimeo.com/{video_id}' or'https://www.vimeo.com/{video_id}";
 const BILIBILI_FORMAT: &str = "'https://www.bilibili.com/video/{video_id}' or'https://bilibili.com/video/{video_id}";
 
-pub fn validate(url: &str) -> Result<String> {
-    let url = Url::parse(url).map_err(|_| DemonlistError::MalformedVideoUrl)?;
+/// A site videos can be hosted on
+pub trait VideoHost: Send + Sync {
+    /// The name of this host as displayed to users, e.g. "YouTube"
+    fn name(&self) -> &str;
+
+    /// Whether URLs on the given domain point to this host
+    fn handles(&self, domain: &str) -> bool;
 
-    if !SCHEMES.contains(&url.scheme()) {
-        return Err(CoreError::InvalidUrlScheme.into());
+    /// Brings a URL pointing to a video on this host into its canonical form
+    ///
+    /// Only called for URLs whose domain this host [handles](VideoHost::handles), and which
+    /// already passed the host-independent checks (e.g. for the URL scheme). Canonical forms are
+    /// what gets stored in the database, meaning they are what the other methods of this trait get
+    /// passed, and what duplicate video detection operates on.
+    fn normalize(&self, url: &Url) -> Result<String>;
+
+    /// The URL of a player for the given video, suitable for embedding into an `iframe`
+    fn embed_url(&self, _video: &Url) -> Option<String> {
+        None
     }
 
-    if !url.username().is_empty() || url.password().is_some() {
-        return Err(CoreError::UrlAuthenticated.into());
+    /// The URL of a thumbnail image for the given video
+    fn thumbnail_url(&self, _video: &Url) -> Option<String> {
+        None
     }
+}
 
-    if let Some(host) = url.domain() {
-        match host {
-            "www.youtube.com" | "m.youtube.com" | "youtube.com" => {
-                if url.path() == "/watch" {
-                    if let Some(video_id) = url
-                        .query_pairs()
-                        .find_map(|(key, value)| if key == "v" { Some(value) } else { None })
-                    {
-                        return Ok(format!(
-                            "https://www.youtube.com/watch?v={}",
-                            video_id.chars().take(11).collect::<String>()
-                        ));
-                    }
-                }
+/// The set of [`VideoHost`]s a pointercrate instance accepts videos from
+///
+/// Managed in rocket state. If a host is registered for a domain more than once, the earliest
+/// registration wins.
+pub struct VideoHosts {
+    hosts: Vec<Box<dyn VideoHost>>,
+}
 
-                Err(CoreError::InvalidUrlFormat { expected: YOUTUBE_FORMAT }.into())
-            },
-            "youtu.be" => {
-                if let Some(path_segments) = url.path_segments() {
-                    match &path_segments.collect::<Vec<_>>()[..] {
-                        [video_id] => Ok(format!(
-                            "https://www.youtube.com/watch?v={}",
-                            video_id.chars().take(11).collect::<String>()
-                        )),
-                        _ => Err(CoreError::InvalidUrlFormat { expected: YOUTUBE_FORMAT }.into()),
-                    }
-                } else {
-                    Err(CoreError::InvalidUrlFormat { expected: YOUTUBE_FORMAT }.into())
-                }
-            },
-            "www.twitch.tv" | "twitch.tv" => {
-                if let Some(path_segments) = url.path_segments() {
-                    match &path_segments.collect::<Vec<_>>()[..] {
-                        ["videos", video_id] => Ok(format!("https://www.twitch.tv/videos/{}", video_id)),
-                        [_, "v", video_id] => Ok(format!("https://www.twitch.tv/videos/{}", video_id)),
-                        _ => Err(CoreError::InvalidUrlFormat { expected: TWITCH_FORMAT }.into()),
-                    }
-                } else {
-                    Err(CoreError::InvalidUrlFormat { expected: TWITCH_FORMAT }.into())
-                }
-            },
-            "everyplay.com" | "www.everyplay.com" => {
-                if let Some(path_segments) = url.path_segments() {
-                    match &path_segments.collect::<Vec<_>>()[..] {
-                        ["videos", video_id] => Ok(format!("https://everyplay.com/videos/{}", video_id)),
-                        _ => Err(CoreError::InvalidUrlFormat {
-                            expected: EVERYPLAY_FORMAT,
-                        }
-                        .into()),
-                    }
-                } else {
-                    Err(CoreError::InvalidUrlFormat {
-                        expected: EVERYPLAY_FORMAT,
-                    }
-                    .into())
-                }
-            },
-            "www.bilibili.com" | "bilibili.com" => {
-                if let Some(path_segments) = url.path_segments() {
-                    match &path_segments.collect::<Vec<_>>()[..] {
-                        ["video", video_id] => Ok(format!("https://www.bilibili.com/video/{}", video_id)),
-                        _ => Err(CoreError::InvalidUrlFormat { expected: BILIBILI_FORMAT }.into()),
-                    }
-                } else {
-                    Err(CoreError::InvalidUrlFormat { expected: BILIBILI_FORMAT }.into())
-                }
+impl Default for VideoHosts {
+    fn default() -> Self {
+        VideoHosts::empty()
+            .with(YouTube)
+            .with(Twitch)
+            .with(Everyplay)
+            .with(Vimeo)
+            .with(Bilibili)
+    }
+}
+
+impl VideoHosts {
+    /// A registry not accepting videos from anywhere
+    pub fn empty() -> Self {
+        VideoHosts { hosts: Vec::new() }
+    }
+
+    pub fn with(mut self, host: impl VideoHost + 'static) -> Self {
+        self.hosts.push(Box::new(host));
+        self
+    }
+
+    /// Validates the given video URL, returning it in the canonical form of the host it points to
+    pub fn validate(&self, url: &str) -> Result<String> {
+        let url = Url::parse(url).map_err(|_| DemonlistError::MalformedVideoUrl)?;
+
+        if !SCHEMES.contains(&url.scheme()) {
+            return Err(CoreError::InvalidUrlScheme.into());
+        }
+
+        if !url.username().is_empty() || url.password().is_some() {
+            return Err(CoreError::UrlAuthenticated.into());
+        }
+
+        let Some(domain) = url.domain() else {
+            return Err(CoreError::UnprocessableEntity.into());
+        };
+
+        self.hosts
+            .iter()
+            .find(|host| host.handles(domain))
+            .ok_or(DemonlistError::UnsupportedVideoHost)?
+            .normalize(&url)
+    }
+
+    /// Finds the host the given (already validated) video is on
+    ///
+    /// Returns [`None`] if the video is on a host no longer part of this registry.
+    fn host_of(&self, video: &str) -> Option<(&dyn VideoHost, Url)> {
+        let url = Url::parse(video).ok()?;
+        let host = self
+            .hosts
+            .iter()
+            .find(|host| url.domain().is_some_and(|domain| host.handles(domain)))?;
+
+        Some((host.as_ref(), url))
+    }
+
+    /// The display name of the host of the given video
+    ///
+    /// Falls back to the video's domain for hosts not part of this registry.
+    pub fn host_name<'a>(&'a self, video: &'a str) -> &'a str {
+        match self.host_of(video) {
+            Some((host, _)) => host.name(),
+            None => video.split("://").nth(1).and_then(|rest| rest.split('/').next()).unwrap_or(video),
+        }
+    }
+
+    pub fn embed_url(&self, video: &str) -> Option<String> {
+        let (host, url) = self.host_of(video)?;
+
+        host.embed_url(&url)
+    }
+
+    pub fn thumbnail_url(&self, video: &str) -> Option<String> {
+        let (host, url) = self.host_of(video)?;
+
+        host.thumbnail_url(&url)
+    }
+}
+
+fn path_segments(url: &Url) -> Vec<&str> {
+    url.path_segments().map(|segments| segments.collect()).unwrap_or_default()
+}
+
+pub struct YouTube;
+
+impl YouTube {
+    fn video_id(url: &Url) -> Option<String> {
+        url.query_pairs()
+            .find_map(|(key, value)| if key == "v" { Some(value.into_owned()) } else { None })
+    }
+}
+
+impl VideoHost for YouTube {
+    fn name(&self) -> &str {
+        "YouTube"
+    }
+
+    fn handles(&self, domain: &str) -> bool {
+        matches!(domain, "www.youtube.com" | "m.youtube.com" | "youtube.com" | "youtu.be")
+    }
+
+    fn normalize(&self, url: &Url) -> Result<String> {
+        let video_id = match url.domain() {
+            Some("youtu.be") => match &path_segments(url)[..] {
+                [video_id] => Some(video_id.to_string()),
+                _ => None,
             },
-            "vimeo.com" | "www.vimeo.com" => {
-                if let Some(path_segments) = url.path_segments() {
-                    match &path_segments.collect::<Vec<_>>()[..] {
-                        [video_id] => Ok(format!("https://vimeo.com/{}", video_id)),
-                        _ => Err(CoreError::InvalidUrlFormat { expected: VIMEO_FORMAT }.into()),
-                    }
-                } else {
-                    Err(CoreError::InvalidUrlFormat { expected: VIMEO_FORMAT }.into())
-                }
+            _ if url.path() == "/watch" => YouTube::video_id(url),
+            _ => None,
+        };
+
+        match video_id {
+            Some(video_id) => Ok(format!(
+                "https://www.youtube.com/watch?v={}",
+                video_id.chars().take(11).collect::<String>()
+            )),
+            None => Err(CoreError::InvalidUrlFormat { expected: YOUTUBE_FORMAT }.into()),
+        }
+    }
+
+    fn embed_url(&self, video: &Url) -> Option<String> {
+        Some(format!("https://www.youtube.com/embed/{}", YouTube::video_id(video)?))
+    }
+
+    fn thumbnail_url(&self, video: &Url) -> Option<String> {
+        Some(format!("https://i.ytimg.com/vi/{}/mqdefault.jpg", YouTube::video_id(video)?))
+    }
+}
+
+pub struct Twitch;
+
+impl VideoHost for Twitch {
+    fn name(&self) -> &str {
+        "Twitch"
+    }
+
+    fn handles(&self, domain: &str) -> bool {
+        matches!(domain, "www.twitch.tv" | "twitch.tv")
+    }
+
+    fn normalize(&self, url: &Url) -> Result<String> {
+        match &path_segments(url)[..] {
+            ["videos", video_id] | [_, "v", video_id] => Ok(format!("https://www.twitch.tv/videos/{}", video_id)),
+            _ => Err(CoreError::InvalidUrlFormat { expected: TWITCH_FORMAT }.into()),
+        }
+    }
+
+    fn embed_url(&self, video: &Url) -> Option<String> {
+        match &path_segments(video)[..] {
+            ["videos", video_id] => Some(format!("https://player.twitch.tv/?video={}&autoplay=false", video_id)),
+            _ => None,
+        }
+    }
+}
+
+/// Everyplay shut down in 2018, but some old records still link to it
+pub struct Everyplay;
+
+impl VideoHost for Everyplay {
+    fn name(&self) -> &str {
+        "Everyplay"
+    }
+
+    fn handles(&self, domain: &str) -> bool {
+        matches!(domain, "everyplay.com" | "www.everyplay.com")
+    }
+
+    fn normalize(&self, url: &Url) -> Result<String> {
+        match &path_segments(url)[..] {
+            ["videos", video_id] => Ok(format!("https://everyplay.com/videos/{}", video_id)),
+            _ => Err(CoreError::InvalidUrlFormat {
+                expected: EVERYPLAY_FORMAT,
+            }
+            .into()),
+        }
+    }
+}
+
+pub struct Vimeo;
+
+impl VideoHost for Vimeo {
+    fn name(&self) -> &str {
+        "Vimeo"
+    }
+
+    fn handles(&self, domain: &str) -> bool {
+        matches!(domain, "vimeo.com" | "www.vimeo.com")
+    }
+
+    fn normalize(&self, url: &Url) -> Result<String> {
+        match &path_segments(url)[..] {
+            [video_id] => Ok(format!("https://vimeo.com/{}", video_id)),
+            _ => Err(CoreError::InvalidUrlFormat { expected: VIMEO_FORMAT }.into()),
+        }
+    }
+
+    fn embed_url(&self, video: &Url) -> Option<String> {
+        match &path_segments(video)[..] {
+            [video_id] => Some(format!("https://player.vimeo.com/video/{}", video_id)),
+            _ => None,
+        }
+    }
+}
+
+pub struct Bilibili;
+
+impl VideoHost for Bilibili {
+    fn name(&self) -> &str {
+        "Bilibili"
+    }
+
+    fn handles(&self, domain: &str) -> bool {
+        matches!(domain, "www.bilibili.com" | "bilibili.com")
+    }
+
+    fn normalize(&self, url: &Url) -> Result<String> {
+        match &path_segments(url)[..] {
+            ["video", video_id] => Ok(format!("https://www.bilibili.com/video/{}", video_id)),
+            _ => Err(CoreError::InvalidUrlFormat { expected: BILIBILI_FORMAT }.into()),
+        }
+    }
+
+    fn embed_url(&self, video: &Url) -> Option<String> {
+        // Videos are identified either by their legacy numeric "av" id, or their newer "BV" id
+        match &path_segments(video)[..] {
+            ["video", video_id] => match video_id.strip_prefix("av") {
+                Some(aid) => Some(format!("https://player.bilibili.com/player.html?aid={}&autoplay=0", aid)),
+                None => Some(format!("https://player.bilibili.com/player.html?bvid={}&autoplay=0", video_id)),
             },
-            _ => Err(DemonlistError::UnsupportedVideoHost),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{VideoHost, VideoHosts};
+    use crate::error::{DemonlistError, Result};
+    use pointercrate_core::error::CoreError;
+    use url::Url;
+
+    struct Streamable;
+
+    impl VideoHost for Streamable {
+        fn name(&self) -> &str {
+            "Streamable"
+        }
+
+        fn handles(&self, domain: &str) -> bool {
+            domain == "streamable.com"
+        }
+
+        fn normalize(&self, url: &Url) -> Result<String> {
+            Ok(format!("https://streamable.com{}", url.path()))
+        }
+
+        fn embed_url(&self, video: &Url) -> Option<String> {
+            Some(format!("https://streamable.com/e{}", video.path()))
+        }
+    }
+
+    #[test]
+    fn test_normalize_builtin_hosts() {
+        let hosts = VideoHosts::default();
+
+        for (url, normalized) in [
+            ("https://youtu.be/dQw4w9WgXcQ", "https://www.youtube.com/watch?v=dQw4w9WgXcQ"),
+            (
+                "http://m.youtube.com/watch?v=dQw4w9WgXcQabc&t=5",
+                "https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+            ),
+            ("https://twitch.tv/someone/v/12345", "https://www.twitch.tv/videos/12345"),
+            ("https://www.vimeo.com/12345", "https://vimeo.com/12345"),
+            (
+                "https://bilibili.com/video/BV1xx411c7mD",
+                "https://www.bilibili.com/video/BV1xx411c7mD",
+            ),
+        ] {
+            assert_eq!(hosts.validate(url).unwrap(), normalized);
+        }
+
+        assert_eq!(
+            hosts.validate("ftp://youtu.be/dQw4w9WgXcQ"),
+            Err(CoreError::InvalidUrlScheme.into())
+        );
+        assert_eq!(
+            hosts.validate("https://streamable.com/abcdef"),
+            Err(DemonlistError::UnsupportedVideoHost)
+        );
+        assert!(matches!(
+            hosts.validate("https://www.youtube.com/channel/abc"),
+            Err(DemonlistError::Core(CoreError::InvalidUrlFormat { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_custom_host() {
+        let hosts = VideoHosts::default().with(Streamable);
+        let video = hosts.validate("https://streamable.com/abcdef").unwrap();
+
+        assert_eq!(hosts.host_name(&video), "Streamable");
+        assert_eq!(hosts.embed_url(&video).as_deref(), Some("https://streamable.com/e/abcdef"));
+        assert_eq!(hosts.thumbnail_url(&video), None);
+
+        // Videos on hosts that were removed from the registry are still displayed
+        assert_eq!(VideoHosts::empty().host_name(&video), "streamable.com");
+        assert_eq!(VideoHosts::empty().embed_url(&video), None);
+    }
+
+    #[test]
+    fn test_embeds() {
+        let hosts = VideoHosts::default();
+
+        for (video, embed) in [
+            (
+                "https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+                Some("https://www.youtube.com/embed/dQw4w9WgXcQ"),
+            ),
+            (
+                "https://www.twitch.tv/videos/12345",
+                Some("https://player.twitch.tv/?video=12345&autoplay=false"),
+            ),
+            ("https://vimeo.com/12345", Some("https://player.vimeo.com/video/12345")),
+            (
+                "https://www.bilibili.com/video/av170001",
+                Some("https://player.bilibili.com/player.html?aid=170001&autoplay=0"),
+            ),
+            ("https://everyplay.com/videos/12345", None),
+        ] {
+            assert_eq!(hosts.embed_url(video).as_deref(), embed);
         }
-    } else {
-        Err(CoreError::UnprocessableEntity.into())
     }
 }
@@ -0,0 +1,16 @@
+use crate::{error::Result, webhook::WebhookTarget};
+use log::info;
+use sqlx::PgConnection;
+
+impl WebhookTarget {
+    /// Deletes this webhook target, together with its delivery log
+    pub async fn delete(self, connection: &mut PgConnection) -> Result<()> {
+        info!("Deleting webhook target {}", self);
+
+        sqlx::query!("DELETE FROM webhook_targets WHERE id = $1", self.id)
+            .execute(connection)
+            .await?;
+
+        Ok(())
+    }
+}
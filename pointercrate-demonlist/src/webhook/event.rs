@@ -0,0 +1,43 @@
+use crate::{
+    demon::FullDemon,
+    player::{claim::PlayerClaim, DatabasePlayer},
+    record::FullRecord,
+    webhook::EventType,
+};
+use serde::Serialize;
+
+/// Something that happened on the list that webhook targets can be notified about
+///
+/// Serializes to an object of the form `{"event": "<event type>", "data": {...}}`.
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum Event<'a> {
+    RecordSubmitted(&'a FullRecord),
+    RecordApproved(&'a FullRecord),
+    RecordRejected(&'a FullRecord),
+    DemonAdded(&'a FullDemon),
+    DemonMoved {
+        demon: &'a FullDemon,
+        /// The position of the demon before it was moved. The new position is part of `demon`.
+        from: i16,
+    },
+    ClaimVerified {
+        claim: &'a PlayerClaim,
+        player: &'a DatabasePlayer,
+    },
+    PlayerBanned(&'a DatabasePlayer),
+}
+
+impl Event<'_> {
+    pub fn event_type(&self) -> EventType {
+        match self {
+            Event::RecordSubmitted(_) => EventType::RecordSubmitted,
+            Event::RecordApproved(_) => EventType::RecordApproved,
+            Event::RecordRejected(_) => EventType::RecordRejected,
+            Event::DemonAdded(_) => EventType::DemonAdded,
+            Event::DemonMoved { .. } => EventType::DemonMoved,
+            Event::ClaimVerified { .. } => EventType::ClaimVerified,
+            Event::PlayerBanned(_) => EventType::PlayerBanned,
+        }
+    }
+}
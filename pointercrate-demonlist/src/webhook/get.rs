@@ -0,0 +1,61 @@
+use crate::{
+    error::{DemonlistError, Result},
+    webhook::{EventType, PayloadFormat, WebhookTarget},
+};
+use futures::StreamExt;
+use sqlx::{Error, PgConnection};
+
+impl WebhookTarget {
+    pub async fn by_id(id: i32, connection: &mut PgConnection) -> Result<WebhookTarget> {
+        let result = sqlx::query!(
+            r#"SELECT url, format::TEXT AS "format!", events::TEXT[] AS "events!", secret FROM webhook_targets WHERE id = $1"#,
+            id
+        )
+        .fetch_one(connection)
+        .await;
+
+        match result {
+            Ok(row) => Ok(WebhookTarget {
+                id,
+                url: row.url,
+                format: PayloadFormat::from_sql(&row.format),
+                events: row.events.iter().map(|event| EventType::from_sql(event)).collect(),
+                secret: row.secret,
+            }),
+            Err(Error::RowNotFound) => Err(DemonlistError::WebhookTargetNotFound { id }),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Gets all webhook targets, ordered by id
+    pub async fn all(connection: &mut PgConnection) -> Result<Vec<WebhookTarget>> {
+        let mut stream = sqlx::query!(
+            r#"SELECT id, url, format::TEXT AS "format!", events::TEXT[] AS "events!", secret FROM webhook_targets ORDER BY id"#
+        )
+        .fetch(connection);
+        let mut targets = Vec::new();
+
+        while let Some(row) = stream.next().await {
+            let row = row?;
+
+            targets.push(WebhookTarget {
+                id: row.id,
+                url: row.url,
+                format: PayloadFormat::from_sql(&row.format),
+                events: row.events.iter().map(|event| EventType::from_sql(event)).collect(),
+                secret: row.secret,
+            })
+        }
+
+        Ok(targets)
+    }
+
+    /// Gets all webhook targets that want to be notified about the given type of event
+    pub async fn subscribed_to(event: EventType, connection: &mut PgConnection) -> Result<Vec<WebhookTarget>> {
+        Ok(WebhookTarget::all(connection)
+            .await?
+            .into_iter()
+            .filter(|target| target.events.contains(&event))
+            .collect())
+    }
+}
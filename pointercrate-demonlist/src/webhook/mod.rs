@@ -0,0 +1,152 @@
+//! Module for outbound webhooks
+//!
+//! Webhook targets are URLs that get notified whenever certain [`Event`]s happen on the list. Each
+//! target subscribes to a set of [`EventType`]s, and decides in which [`PayloadFormat`] it wants to
+//! receive them. Every delivery attempt is logged as a [`WebhookDelivery`].
+//!
+//! Actually sending out webhooks is up to the API layer.
+
+use chrono::NaiveDateTime;
+use derive_more::Display;
+use pointercrate_core::etag::Taggable;
+use serde::{Deserialize, Serialize};
+use std::hash::{Hash, Hasher};
+
+pub use event::Event;
+pub use paginate::WebhookDeliveryPagination;
+pub use patch::PatchWebhookTarget;
+pub use post::PostWebhookTarget;
+
+mod delete;
+mod event;
+mod get;
+mod paginate;
+mod patch;
+mod post;
+
+#[derive(Debug, Serialize, Deserialize, Display, Copy, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum EventType {
+    #[display("record_submitted")]
+    RecordSubmitted,
+    #[display("record_approved")]
+    RecordApproved,
+    #[display("record_rejected")]
+    RecordRejected,
+    #[display("demon_added")]
+    DemonAdded,
+    #[display("demon_moved")]
+    DemonMoved,
+    #[display("claim_verified")]
+    ClaimVerified,
+    #[display("player_banned")]
+    PlayerBanned,
+}
+
+impl EventType {
+    pub fn to_sql(self) -> String {
+        match self {
+            EventType::RecordSubmitted => "RECORD_SUBMITTED",
+            EventType::RecordApproved => "RECORD_APPROVED",
+            EventType::RecordRejected => "RECORD_REJECTED",
+            EventType::DemonAdded => "DEMON_ADDED",
+            EventType::DemonMoved => "DEMON_MOVED",
+            EventType::ClaimVerified => "CLAIM_VERIFIED",
+            EventType::PlayerBanned => "PLAYER_BANNED",
+        }
+        .to_owned()
+    }
+
+    fn from_sql(sql: &str) -> Self {
+        match sql {
+            "RECORD_SUBMITTED" => EventType::RecordSubmitted,
+            "RECORD_APPROVED" => EventType::RecordApproved,
+            "RECORD_REJECTED" => EventType::RecordRejected,
+            "DEMON_ADDED" => EventType::DemonAdded,
+            "DEMON_MOVED" => EventType::DemonMoved,
+            "CLAIM_VERIFIED" => EventType::ClaimVerified,
+            "PLAYER_BANNED" => EventType::PlayerBanned,
+            _ => panic!("invalid webhook event: {}", sql),
+        }
+    }
+}
+
+/// The format in which a [`WebhookTarget`] receives events
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PayloadFormat {
+    /// The [`Event`] serialized as JSON
+    #[default]
+    Json,
+
+    /// A message containing a single embed, suitable for Discord webhooks
+    Discord,
+
+    /// A message suitable for Slack incoming webhooks
+    Slack,
+}
+
+impl PayloadFormat {
+    pub fn to_sql(self) -> String {
+        match self {
+            PayloadFormat::Json => "JSON",
+            PayloadFormat::Discord => "DISCORD",
+            PayloadFormat::Slack => "SLACK",
+        }
+        .to_owned()
+    }
+
+    fn from_sql(sql: &str) -> Self {
+        match sql {
+            "JSON" => PayloadFormat::Json,
+            "DISCORD" => PayloadFormat::Discord,
+            "SLACK" => PayloadFormat::Slack,
+            _ => panic!("invalid webhook format: {}", sql),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Display, PartialEq, Eq)]
+#[display("{} (ID: {})", url, id)]
+pub struct WebhookTarget {
+    pub id: i32,
+    pub url: String,
+    pub format: PayloadFormat,
+    pub events: Vec<EventType>,
+
+    /// The key with which the payloads sent to this target are signed.
+    ///
+    /// Only revealed upon creation of the target
+    #[serde(skip)]
+    pub secret: String,
+}
+
+// The secret is never revealed after creation, so it should not influence the ETag either
+impl Hash for WebhookTarget {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+        self.url.hash(state);
+        self.format.hash(state);
+        self.events.hash(state);
+    }
+}
+
+impl Taggable for WebhookTarget {}
+
+#[derive(Debug, Serialize)]
+pub struct WebhookDelivery {
+    pub id: i32,
+    pub target: i32,
+    pub event: EventType,
+
+    /// The number of this attempt, starting at 1
+    pub attempt: i16,
+    pub time: NaiveDateTime,
+
+    /// The HTTP status code returned by the target, if any
+    pub status_code: Option<i16>,
+
+    /// Why this delivery attempt failed, if it did
+    pub error: Option<String>,
+    pub payload: String,
+}
@@ -0,0 +1,78 @@
+use crate::webhook::{EventType, WebhookDelivery};
+use futures::StreamExt;
+use pointercrate_core::{
+    first_and_last,
+    pagination::{__pagination_compat, PageContext, Paginatable, PaginationParameters, PaginationQuery},
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, Row};
+
+#[derive(Deserialize, Debug, Clone, Copy, Serialize)]
+pub struct WebhookDeliveryPagination {
+    #[serde(flatten)]
+    pub params: PaginationParameters,
+
+    /// The webhook target whose deliveries to paginate. Not part of the query string, but
+    /// determined by the endpoint's path.
+    #[serde(skip)]
+    pub target: i32,
+}
+
+impl PaginationQuery for WebhookDeliveryPagination {
+    fn parameters(&self) -> PaginationParameters {
+        self.params
+    }
+
+    fn with_parameters(&self, parameters: PaginationParameters) -> Self {
+        Self {
+            params: parameters,
+            ..*self
+        }
+    }
+}
+
+impl Paginatable<WebhookDeliveryPagination> for WebhookDelivery {
+    first_and_last!(WebhookDeliveryPagination, "webhook_deliveries");
+
+    async fn fetch(
+        query: &WebhookDeliveryPagination, connection: &mut PgConnection,
+    ) -> Result<(Vec<WebhookDelivery>, PageContext), sqlx::Error> {
+        let order = query.params.order();
+
+        let sql_query = format!(
+            "SELECT id, event::TEXT, attempt, time, status_code, error, payload FROM webhook_deliveries WHERE (id < $1 OR $1 IS NULL) AND \
+             (id > $2 OR $2 IS NULL) AND target = $3 ORDER BY id {} LIMIT $4",
+            order
+        );
+
+        let mut stream = sqlx::query(&sql_query)
+            .bind(query.params.before)
+            .bind(query.params.after)
+            .bind(query.target)
+            .bind(query.params.limit + 1)
+            .fetch(connection);
+
+        let mut deliveries = Vec::new();
+
+        while let Some(row) = stream.next().await {
+            let row = row?;
+
+            deliveries.push(WebhookDelivery {
+                id: row.get("id"),
+                target: query.target,
+                event: EventType::from_sql(row.get("event")),
+                attempt: row.get("attempt"),
+                time: row.get("time"),
+                status_code: row.get("status_code"),
+                error: row.get("error"),
+                payload: row.get("payload"),
+            })
+        }
+
+        Ok(__pagination_compat(&query.params, deliveries))
+    }
+
+    fn pagination_id(&self) -> i32 {
+        self.id
+    }
+}
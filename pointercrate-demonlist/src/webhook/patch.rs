@@ -0,0 +1,58 @@
+use crate::{
+    error::Result,
+    webhook::{post::validate_url, EventType, PayloadFormat, WebhookTarget},
+};
+use pointercrate_core::util::non_nullable;
+use serde::Deserialize;
+use sqlx::PgConnection;
+
+#[derive(Debug, Deserialize)]
+pub struct PatchWebhookTarget {
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub url: Option<String>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub format: Option<PayloadFormat>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub events: Option<Vec<EventType>>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub secret: Option<String>,
+}
+
+impl WebhookTarget {
+    pub async fn apply_patch(mut self, patch: PatchWebhookTarget, connection: &mut PgConnection) -> Result<Self> {
+        if let Some(url) = patch.url {
+            self.url = validate_url(&url)?;
+        }
+
+        if let Some(format) = patch.format {
+            self.format = format;
+        }
+
+        if let Some(events) = patch.events {
+            self.events = events;
+        }
+
+        if let Some(secret) = patch.secret {
+            self.secret = secret;
+        }
+
+        let events = self.events.iter().map(|event| event.to_sql()).collect::<Vec<_>>();
+
+        sqlx::query!(
+            "UPDATE webhook_targets SET url = $1, format = $2::TEXT::WEBHOOK_FORMAT, events = $3::TEXT[]::WEBHOOK_EVENT[], secret = $4 WHERE \
+             id = $5",
+            self.url,
+            self.format.to_sql(),
+            &events,
+            self.secret,
+            self.id
+        )
+        .execute(connection)
+        .await?;
+
+        Ok(self)
+    }
+}
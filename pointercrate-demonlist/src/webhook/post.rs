@@ -0,0 +1,82 @@
+use crate::{
+    error::{DemonlistError, Result},
+    webhook::{EventType, PayloadFormat, WebhookDelivery, WebhookTarget},
+};
+use log::info;
+use serde::Deserialize;
+use sqlx::PgConnection;
+use url::Url;
+
+#[derive(Debug, Deserialize)]
+pub struct PostWebhookTarget {
+    pub url: String,
+
+    #[serde(default)]
+    pub format: PayloadFormat,
+
+    pub events: Vec<EventType>,
+
+    /// The key with which to sign payloads. Randomly generated if not provided.
+    #[serde(default)]
+    pub secret: Option<String>,
+}
+
+impl WebhookTarget {
+    pub async fn create_from(data: PostWebhookTarget, connection: &mut PgConnection) -> Result<WebhookTarget> {
+        let url = validate_url(&data.url)?;
+        let events = data.events.iter().map(|event| event.to_sql()).collect::<Vec<_>>();
+
+        let row = sqlx::query!(
+            "INSERT INTO webhook_targets (url, format, events, secret) VALUES ($1, $2::TEXT::WEBHOOK_FORMAT, $3::TEXT[]::WEBHOOK_EVENT[], \
+             COALESCE($4, replace(gen_random_uuid()::TEXT || gen_random_uuid()::TEXT, '-', ''))) RETURNING id, secret",
+            url,
+            data.format.to_sql(),
+            &events,
+            data.secret
+        )
+        .fetch_one(connection)
+        .await?;
+
+        let target = WebhookTarget {
+            id: row.id,
+            url,
+            format: data.format,
+            events: data.events,
+            secret: row.secret,
+        };
+
+        info!("Created webhook target {}", target);
+
+        Ok(target)
+    }
+}
+
+impl WebhookDelivery {
+    /// Logs an attempt at delivering an event of the given type to the given target
+    pub async fn log(
+        target: i32, event: EventType, attempt: i16, status_code: Option<i16>, error: Option<String>, payload: &str,
+        connection: &mut PgConnection,
+    ) -> Result<()> {
+        sqlx::query!(
+            "INSERT INTO webhook_deliveries (target, event, attempt, status_code, error, payload) VALUES ($1, $2::TEXT::WEBHOOK_EVENT, $3, \
+             $4, $5, $6)",
+            target,
+            event.to_sql(),
+            attempt,
+            status_code,
+            error,
+            payload
+        )
+        .execute(connection)
+        .await?;
+
+        Ok(())
+    }
+}
+
+pub(super) fn validate_url(url: &str) -> Result<String> {
+    match Url::parse(url) {
+        Ok(url) if url.scheme() == "https" || url.scheme() == "http" => Ok(url.into()),
+        _ => Err(DemonlistError::InvalidWebhookUrl),
+    }
+}
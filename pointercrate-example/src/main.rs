@@ -2,7 +2,9 @@ use maud::html;
 use pointercrate_core::localization::LocalesLoader;
 use pointercrate_core::pool::PointercratePool;
 use pointercrate_core::{error::CoreError, localization::tr};
-use pointercrate_core_api::{error::ErrorResponder, maintenance::MaintenanceFairing, preferences::PreferenceManager};
+use pointercrate_core_api::{
+    error::ErrorResponder, maintenance::MaintenanceFairing, metrics::MetricsFairing, preferences::PreferenceManager,
+};
 use pointercrate_core_macros::localized_catcher;
 use pointercrate_core_pages::{
     footer::{Footer, FooterColumn, Link},
@@ -15,7 +17,8 @@ use pointercrate_demonlist_pages::account::{
     demons::DemonsTab, list_integration::ListIntegrationTab, players::PlayersPage, records::RecordsPage,
 };
 use pointercrate_user::MODERATOR;
-use pointercrate_user_pages::account::{profile::ProfileTab, users::UsersTab, AccountPageConfig};
+use pointercrate_user_api::auth::{catch_401, catch_403};
+use pointercrate_user_pages::account::{maintenance::MaintenanceTab, profile::ProfileTab, users::UsersTab, AccountPageConfig};
 use rocket::{async_trait, fs::FileServer, response::Redirect, serde, uri, Request};
 use std::net::IpAddr;
 use unic_langid::lang;
@@ -44,13 +47,6 @@ async fn catch_422() -> ErrorResponder {
     CoreError::UnprocessableEntity.into()
 }
 
-/// Failures from the authorization FromRequest implementations can return 401s
-#[localized_catcher]
-#[rocket::catch(401)]
-async fn catch_401() -> ErrorResponder {
-    CoreError::Unauthorized.into()
-}
-
 /// We do not have a home page, so have the website root simply redirect to the demonlist
 #[rocket::get("/")]
 fn home() -> Redirect {
@@ -88,11 +84,32 @@ impl GeolocationProvider for IpWhoIsGeolocationProvider {
     }
 }
 
+/// Loads and validates the configuration of all pointercrate components
+///
+/// Each component reads its own table of the `pointercrate.toml` configuration file, with
+/// environment variables taking precedence over settings from the file. Invalid configuration is
+/// reported here, so that the server does not start up only to fail later.
+fn load_configuration() {
+    let result = pointercrate_core::config::init()
+        .and_then(|_| pointercrate_demonlist::config::init())
+        .and_then(|_| pointercrate_demonlist_api::config::init())
+        .and_then(|_| pointercrate_user::config::init());
+
+    if let Err(err) = result {
+        eprintln!("Invalid configuration: {}", err);
+
+        std::process::exit(1);
+    }
+}
+
 #[rocket::launch]
 async fn rocket() -> _ {
-    // Load the configuration from your .env file
+    // Load the environment variables from your .env file
     dotenv::dotenv().unwrap();
 
+    // Load the configuration from your pointercrate.toml file (and the environment)
+    load_configuration();
+
     // Load the translation files
     LocalesLoader::load(&[
         "pointercrate-core-pages/static/ftl/",
@@ -104,7 +121,7 @@ async fn rocket() -> _ {
     .commit(DEFAULT_LOCALE);
 
     // Initialize a database connection pool to the database specified by the
-    // DATABASE_URL environment variable
+    // `database_url` setting (or DATABASE_URL environment variable)
     let pool = PointercratePool::init().await;
 
     // Set up the HTTP server
@@ -114,8 +131,9 @@ async fn rocket() -> _ {
         // Tell pointercrate's core components about navigation bar and footers, so that it knows how to render the website
         // We are passing is as a function pointer so the page can load it in a different language each time a page is rendered
         .manage(page_configuration as fn() -> PageConfiguration)
-        // Register our 404 catcher
-        .register("/", rocket::catchers![catch_401, catch_404, catch_422])
+        // Register our 404 catcher, as well as the catchers responding with the reason an
+        // authorization attempt failed (e.g. a missing two-factor code)
+        .register("/", rocket::catchers![catch_404, catch_422, catch_401, catch_403])
         // Register our home page
         .mount("/", rocket::routes![home]);
 
@@ -129,6 +147,14 @@ async fn rocket() -> _ {
     let mut permissions_manager = pointercrate_user::default_permissions_manager();
     permissions_manager.merge_with(pointercrate_demonlist::default_permissions_manager());
 
+    // Users holding the most powerful permissions can only use them once they have
+    // enabled two-factor authentication on their profile (accounts linked with an
+    // external provider are exempt, as the provider takes care of this). Remove these
+    // lines to make two-factor authentication entirely optional.
+    let permissions_manager = permissions_manager
+        .requires_two_factor(pointercrate_user::ADMINISTRATOR)
+        .requires_two_factor(LIST_ADMINISTRATOR);
+
     let rocket = rocket.manage(permissions_manager);
 
     // Define the preferences our website supports. Preferences are sent to us from
@@ -152,23 +178,57 @@ async fn rocket() -> _ {
         // Tab where website moderators can manage permissions. 
         // The vector below specified which permissions a user needs to have for the tab to be displayed.
         .with_page(UsersTab(vec![MODERATOR, LIST_ADMINISTRATOR]))
-        // Tab where list helpers can manage demons
-        .with_page(DemonsTab)
+        // Tab where website administrators can switch maintenance mode on and off, or schedule it
+        .with_page(MaintenanceTab)
+        // Tab where list moderators can manage demons of the default list (use `DemonsTab::for_list`
+        // to manage a different list instead)
+        .with_page(DemonsTab::default())
         // Tab where list helpers can manage players
         .with_page(PlayersPage)
-        // Tab where list helpers can manage records
-        .with_page(RecordsPage);
+        // Tab where list helpers can manage records of the default list (use `RecordsPage::for_list`
+        // to manage a different list instead)
+        .with_page(RecordsPage::default());
 
     let rocket = rocket.manage(account_page_config);
 
     // Changing `false` to `true` here will put your website into "maintenance mode", which will disable all mutating request handlers and always return 503 SERVICE UNAVAILABLE responses for non-GET requests.
+    // Otherwise, administrators can switch maintenance mode on and off (or schedule it) at runtime via the maintenance tab of the user area.
     let rocket = rocket.attach(MaintenanceFairing::new(false));
 
+    // Record request latencies, error codes, ratelimit hits and more, and expose them to prometheus at `/metrics`.
+    // Set the METRICS_TOKEN environment variable to require a bearer token for accessing the metrics.
+    let rocket = rocket.attach(MetricsFairing);
+
     // Register all the endpoints related to the demonlist to our server (this is
     // optional, but without registering the demonlist related endpoint your website
     // will just be User Account Simulator 2024).
+    //
+    // By default, records award points according to pointercrate's own scoring curve. If your
+    // list wants to award points differently, implement [`pointercrate_demonlist::score::ScoringPolicy`]
+    // and register it via `pointercrate_demonlist::score::set_scoring_policy(MyPolicy)` here (or use
+    // `set_list_scoring_policy(list_id, MyPolicy)` for a single list only). Whenever the policy
+    // changes, all cached scores are recomputed in the background after the next launch.
+    //
+    // Similarly, videos are accepted from YouTube, Twitch, Everyplay, Vimeo and Bilibili by default. To
+    // support other sites, implement [`pointercrate_demonlist::video::VideoHost`] for them and manage a
+    // custom registry, e.g. `rocket.manage(VideoHosts::default().with(Streamable))`.
     let rocket = pointercrate_demonlist_api::setup(rocket);
 
+    // `setup` mounts the default list at `/api/v2/demons/` and `/demonlist/`. To host additional
+    // lists (e.g. a challenge list), mount them via `mount_list`, for example
+    //
+    //     let rocket = pointercrate_demonlist_api::mount_list(rocket, ListSetup {
+    //         name: "challengelist",
+    //         api_base: "/api/v2/challenges/",
+    //         pages_base: "/challengelist/",
+    //         list_size: 50,
+    //         extended_list_size: 100,
+    //         permissions: CHALLENGELIST_PERMISSIONS,
+    //     });
+    //
+    // If the list uses dedicated permissions, remember to merge
+    // `CHALLENGELIST_PERMISSIONS.permissions_manager()` into the permissions manager above.
+
     // Register all the endpoints related to the user account system to our server
     let rocket = pointercrate_user_api::setup(rocket);
 
@@ -230,17 +290,18 @@ fn page_configuration() -> PageConfiguration {
         br;
         "<your website> and <your demonlist> are in no way affiliated with RobTopGamesAB ® or pointercrate.com"
     })
-    // Add a column with links for various list-related highlights
+    // Add a column with links for various list-related highlights (the list sizes are always
+    // available, as `load_configuration` already validated the configuration at startup)
     .with_column(FooterColumn::LinkList {
         heading: tr("footer-demonlist"),
         links: vec![
             Link::new("/demonlist/1/", tr("footer-demonlist.top-demon")),
             Link::new(
-                format!("/demonlist/{}/", pointercrate_demonlist::config::list_size() + 1),
+                format!("/demonlist/{}/", pointercrate_demonlist::config::list_size().expect("validated at startup") + 1),
                 tr("footer-demonlist.extended-list"),
             ),
             Link::new(
-                format!("/demonlist/{}/", pointercrate_demonlist::config::extended_list_size() + 1),
+                format!("/demonlist/{}/", pointercrate_demonlist::config::extended_list_size().expect("validated at startup") + 1),
                 tr("footer-demonlist.legacy-list"),
             ),
         ],
@@ -9,9 +9,13 @@ use dash_rs::{
     response::{parse_download_gj_level_response, parse_get_gj_levels_response},
 };
 use log::{debug, error, trace, warn};
-use pointercrate_core::ratelimits;
+use pointercrate_core::{
+    job::{self, Job},
+    metrics, ratelimits,
+};
 use pointercrate_demonlist::demon::Demon;
 use reqwest::{header::CONTENT_TYPE, Client};
+use serde::{Deserialize, Serialize};
 use sqlx::{Pool, Postgres};
 use std::{borrow::Cow, sync::Arc};
 
@@ -32,20 +36,48 @@ ratelimits! {
 
 pub type IntegrationLevel = Level<'static, LevelData<'static>, Option<NewgroundsSong<'static>>>;
 
+/// Job for re-querying the Geometry Dash servers for a demon's level data, see
+/// [`GeometryDashConnector::refresh_demon_data`]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RefreshDemonData {
+    pub name: String,
+    pub demon_id: i32,
+    pub level_id: Option<u64>,
+}
+
+impl Job for RefreshDemonData {
+    const KIND: &'static str = "refresh_demon_data";
+
+    fn dedup_key(&self) -> Option<String> {
+        Some(self.demon_id.to_string())
+    }
+
+    fn max_attempts(&self) -> i16 {
+        3
+    }
+}
+
 impl GeometryDashConnector {
     /// Attempts to pull the Geometry Dash level data for the given [`Demon`] from the database
     ///
     /// If the last time the data for this demon was sought on the Geometry Dash servers was over 24h ago,
-    /// re-query them for updated data.
+    /// re-query them for updated data (by enqueueing a [`RefreshDemonData`] job).
     pub async fn load_level_for_demon(&self, demon: &Demon) -> Option<IntegrationLevel> {
-        if self.ratelimits.throttle_throttle(demon.base.id).is_ok()
-            && self.ratelimits.throttle().is_ok()
-            && self.ratelimits.demon_refresh(demon.base.id).is_ok()
+        if self.ratelimits.throttle_throttle(demon.base.id).await.is_ok()
+            && self.ratelimits.throttle().await.is_ok()
+            && self.ratelimits.demon_refresh(demon.base.id).await.is_ok()
         {
-            tokio::spawn(
-                self.clone()
-                    .refresh_demon_data(demon.base.name.clone(), demon.base.id, demon.level_id),
-            );
+            let job = RefreshDemonData {
+                name: demon.base.name.clone(),
+                demon_id: demon.base.id,
+                level_id: demon.level_id,
+            };
+
+            if let Ok(mut connection) = self.pool.acquire().await {
+                if let Err(err) = job::enqueue(&job, &mut connection).await {
+                    error!("Failed to enqueue refresh of demon data for {}: {:?}", demon.base.id, err);
+                }
+            }
         }
 
         if let Some(level_id) = demon.level_id {
@@ -65,7 +97,12 @@ impl GeometryDashConnector {
         None
     }
 
-    pub async fn refresh_demon_data(self, name: String, demon_id: i32, level_id: Option<u64>) {
+    /// Queries the Geometry Dash servers for the level with the given name (or id, if known), and
+    /// stores it in the database
+    ///
+    /// Only fails if the Geometry Dash servers could not be reached, in which case retrying later
+    /// might help.
+    pub async fn refresh_demon_data(self, name: String, demon_id: i32, level_id: Option<u64>) -> Result<(), reqwest::Error> {
         debug!("Refreshing demon data for {} (id {})", name, demon_id);
 
         let levels_request = match level_id {
@@ -83,13 +120,17 @@ impl GeometryDashConnector {
             Some(level_id) => LevelsRequest::default().search(level_id.to_string()),
         };
 
-        let Ok(response) = self.make_request(levels_request.to_url(), levels_request.to_string()).await else {
-            return;
-        };
+        let response = self
+            .make_request("getGJLevels", levels_request.to_url(), levels_request.to_string())
+            .await?;
         let Ok(demons) = parse_get_gj_levels_response(&response)
-            .inspect_err(|err| warn!("[{}] Failed to parse getGJLevels response: {:?}", demon_id, err))
+            .inspect(|_| metrics::record_gd_request("getGJLevels", "success"))
+            .inspect_err(|err| {
+                warn!("[{}] Failed to parse getGJLevels response: {:?}", demon_id, err);
+                metrics::record_gd_request("getGJLevels", "parse_error");
+            })
         else {
-            return;
+            return Ok(());
         };
         let Some(mut hardest) = demons
             .into_iter()
@@ -98,7 +139,7 @@ impl GeometryDashConnector {
             .max_by(|x, y| x.difficulty.cmp(&y.difficulty))
         else {
             warn!("[{}] No demons found with name {}", demon_id, name);
-            return;
+            return Ok(());
         };
 
         if let Some(newgrounds_song) = &mut hardest.custom_song {
@@ -110,13 +151,15 @@ impl GeometryDashConnector {
         }
 
         let request = LevelRequest::new(hardest.level_id);
-        let Ok(response) = self.make_request(request.to_url(), request.to_string()).await else {
-            return;
-        };
+        let response = self.make_request("downloadGJLevel", request.to_url(), request.to_string()).await?;
         let Ok(mut level) = parse_download_gj_level_response(&response)
-            .inspect_err(|err| warn!("[{}] Failed to parse downloadGJLevel response: {:?}", demon_id, err))
+            .inspect(|_| metrics::record_gd_request("downloadGJLevel", "success"))
+            .inspect_err(|err| {
+                warn!("[{}] Failed to parse downloadGJLevel response: {:?}", demon_id, err);
+                metrics::record_gd_request("downloadGJLevel", "parse_error");
+            })
         else {
-            return;
+            return Ok(());
         };
 
         self.store_level(&level, level.creator, level.custom_song).await;
@@ -125,22 +168,33 @@ impl GeometryDashConnector {
         let _ = sqlx::query!("UPDATE demons SET level_id = $1 WHERE id = $2", level.level_id as i64, demon_id)
             .execute(&self.pool)
             .await;
+
+        Ok(())
     }
 
-    async fn make_request(&self, url: String, body: String) -> Result<String, reqwest::Error> {
+    /// Makes a request to the given endpoint of the Geometry Dash servers. Failures to reach the
+    /// servers are recorded in the metrics under the given endpoint name.
+    async fn make_request(&self, endpoint: &'static str, url: String, body: String) -> Result<String, reqwest::Error> {
         debug!("Making request to {} with body {}", url, body);
 
-        let response = self.http_client
+        let response = async {
+            self.http_client
             .post(url)
               // boomlings.com rejects any request with a User-Agent header set, so make sure reqwest doesn't "helpfully" add one
             .headers(HeaderMap::new())
             .body(body)
             .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
             .send()
+            .await?
+            .text()
             .await
-            .inspect_err(|err| warn!("Failed to make boomlings request: {:?}", err))?;
+        }
+        .await;
 
-        response.text().await
+        response.inspect_err(|err| {
+            warn!("Failed to make boomlings request: {:?}", err);
+            metrics::record_gd_request(endpoint, "request_error");
+        })
     }
 }
 
@@ -1,34 +1,88 @@
 use crate::{TestClient, TestRequest};
 use pointercrate_core::etag::Taggable;
 use pointercrate_core::localization::LocalesLoader;
-use pointercrate_core::{permission::PermissionsManager, pool::PointercratePool};
+use pointercrate_core::{
+    permission::{Permission, PermissionsManager},
+    pool::PointercratePool,
+};
 use pointercrate_core_api::preferences::PreferenceManager;
 use pointercrate_demonlist::demon::FullDemon;
 use pointercrate_demonlist::{
+    list::{ListPermissions, DEFAULT_LIST},
     player::{claim::PlayerClaim, FullPlayer},
     record::RecordStatus,
     submitter::Submitter,
+    video::VideoHosts,
     LIST_ADMINISTRATOR, LIST_HELPER, LIST_MODERATOR,
 };
+use pointercrate_demonlist_api::ListSetup;
 use pointercrate_user::auth::{AuthenticatedUser, PasswordOrBrowser};
 use pointercrate_user_pages::account::AccountPageConfig;
-use rocket::{http::Status, local::asynchronous::Client};
+use rocket::{http::Status, local::asynchronous::Client, Build, Rocket};
 use sqlx::{pool::PoolConnection, PgConnection, Pool, Postgres};
 use std::{net::IpAddr, str::FromStr};
 
+pub const CHALLENGE_HELPER: Permission = Permission::new("challenge-helper", 0x10);
+pub const CHALLENGE_MODERATOR: Permission = Permission::new("challenge-moderator", 0x20);
+pub const CHALLENGE_ADMINISTRATOR: Permission = Permission::new("challenge-administrator", 0x40);
+
 pub async fn setup_rocket(pool: Pool<Postgres>) -> (TestClient, PoolConnection<Postgres>) {
+    setup(rocket::build(), pool, None).await
+}
+
+/// Like [`setup_rocket`], but accepting videos from the given hosts instead of the default ones
+pub async fn setup_rocket_with_video_hosts(pool: Pool<Postgres>, video_hosts: VideoHosts) -> (TestClient, PoolConnection<Postgres>) {
+    setup(rocket::build().manage(video_hosts), pool, None).await
+}
+
+/// Like [`setup_rocket`], but additionally hosting a challenge list managed via the dedicated
+/// [`CHALLENGE_HELPER`], [`CHALLENGE_MODERATOR`] and [`CHALLENGE_ADMINISTRATOR`] permissions.
+///
+/// Returns the id of the challenge list.
+pub async fn setup_rocket_with_challenge_list(pool: Pool<Postgres>) -> (TestClient, PoolConnection<Postgres>, i32) {
+    let challenge_list = ListSetup {
+        name: "challengelist",
+        api_base: "/api/v2/challenges/",
+        pages_base: "/challengelist/",
+        list_size: 75,
+        extended_list_size: 150,
+        permissions: ListPermissions {
+            helper: CHALLENGE_HELPER,
+            moderator: CHALLENGE_MODERATOR,
+            administrator: CHALLENGE_ADMINISTRATOR,
+        },
+    };
+
+    let (client, mut connection) = setup(rocket::build(), pool, Some(challenge_list)).await;
+
+    let list_id = sqlx::query_scalar!("SELECT id FROM lists WHERE name = 'challengelist'")
+        .fetch_one(&mut *connection)
+        .await
+        .unwrap();
+
+    (client, connection, list_id)
+}
+
+async fn setup(rocket: Rocket<Build>, pool: Pool<Postgres>, additional_list: Option<ListSetup>) -> (TestClient, PoolConnection<Postgres>) {
     let _ = dotenv::dotenv();
 
     let mut connection = pool.acquire().await.unwrap();
 
-    let permissions = PermissionsManager::new(vec![LIST_HELPER, LIST_MODERATOR, LIST_ADMINISTRATOR])
+    let mut permissions = PermissionsManager::new(vec![LIST_HELPER, LIST_MODERATOR, LIST_ADMINISTRATOR])
         .assigns(LIST_ADMINISTRATOR, LIST_MODERATOR)
         .implies(LIST_ADMINISTRATOR, LIST_MODERATOR)
         .implies(LIST_MODERATOR, LIST_HELPER);
 
     LocalesLoader::empty();
 
-    let rocket = pointercrate_demonlist_api::setup(rocket::build().manage(PointercratePool::from(pool)))
+    let mut rocket = pointercrate_demonlist_api::setup(rocket.manage(PointercratePool::from(pool)));
+
+    if let Some(list) = additional_list {
+        permissions.merge_with(list.permissions.permissions_manager());
+        rocket = pointercrate_demonlist_api::mount_list(rocket, list);
+    }
+
+    let rocket = rocket
         .manage(permissions)
         .manage(AccountPageConfig::default())
         .manage(PreferenceManager::default().preference("locale", "en"));
@@ -38,19 +92,49 @@ pub async fn setup_rocket(pool: Pool<Postgres>) -> (TestClient, PoolConnection<P
         .await
         .unwrap();
 
-    (TestClient::new(Client::tracked(rocket).await.unwrap()), connection)
+    let client = TestClient::new(Client::tracked(rocket).await.unwrap());
+
+    wait_for_initial_score_recomputation(&mut connection).await;
+
+    (client, connection)
+}
+
+/// Waits for the job worker to finish recomputing all scores, which is scheduled on the first
+/// launch. Otherwise, it might change player scores (and thus etags) in the middle of a test.
+async fn wait_for_initial_score_recomputation(connection: &mut PgConnection) {
+    loop {
+        let pending = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM jobs WHERE kind = 'recompute_scores' AND status IN ('PENDING', 'RUNNING')) AS "pending!""#
+        )
+        .fetch_one(&mut *connection)
+        .await
+        .unwrap();
+
+        if !pending {
+            return;
+        }
+
+        rocket::tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
 }
 
 pub async fn add_demon(
     name: impl Into<String>, position: i16, requirement: i16, verifier_id: i32, publisher_id: i32, connection: &mut PgConnection,
+) -> i32 {
+    add_demon_on_list(DEFAULT_LIST, name, position, requirement, verifier_id, publisher_id, connection).await
+}
+
+pub async fn add_demon_on_list(
+    list: i32, name: impl Into<String>, position: i16, requirement: i16, verifier_id: i32, publisher_id: i32, connection: &mut PgConnection,
 ) -> i32 {
     sqlx::query!(
-        "INSERT INTO demons (name, position, requirement, verifier, publisher) VALUES ($1::TEXT::CITEXT, $2, $3, $4, $5) RETURNING id",
+        "INSERT INTO demons (name, position, requirement, verifier, publisher, list) VALUES ($1::TEXT::CITEXT, $2, $3, $4, $5, $6) RETURNING id",
         name.into(),
         position,
         requirement,
         verifier_id,
-        publisher_id
+        publisher_id,
+        list
     )
     .fetch_one(&mut *connection)
     .await
@@ -3,7 +3,7 @@
 use pointercrate_user::auth::{AuthenticatedUser, PasswordOrBrowser};
 
 use rocket::{
-    http::{Header, Status},
+    http::{ContentType, Cookie, Header, Status},
     local::asynchronous::{Client, LocalRequest, LocalResponse},
 };
 use serde::{de::DeserializeOwned, Serialize};
@@ -11,6 +11,7 @@ use serde::{de::DeserializeOwned, Serialize};
 use std::{collections::HashMap, fmt::Debug};
 
 pub mod demonlist;
+pub mod oauth;
 pub mod user;
 
 pub struct TestClient(Client);
@@ -32,6 +33,11 @@ impl TestClient {
         TestRequest::new(self.0.post(url.into()).json(body))
     }
 
+    /// Like [`TestClient::post`], but for bodies that are not JSON
+    pub fn post_raw(&self, url: impl Into<String>, content_type: ContentType, body: impl AsRef<[u8]>) -> TestRequest<'_> {
+        TestRequest::new(self.0.post(url.into()).header(content_type).body(body))
+    }
+
     pub fn patch(&self, url: impl Into<String>, body: &impl Serialize) -> TestRequest<'_> {
         TestRequest::new(self.0.patch(url.into()).json(body))
     }
@@ -67,6 +73,14 @@ impl<'c> TestRequest<'c> {
         self.header("Authorization", format!("Bearer {}", user.generate_programmatic_access_token()))
     }
 
+    /// Authorizes the request the way the website does, via an access token cookie and a CSRF token
+    pub fn authorize_as_browser(mut self, user: &AuthenticatedUser<PasswordOrBrowser>) -> Self {
+        let (access_token, csrf_token) = user.generate_token_pair().unwrap();
+
+        self.request = self.request.cookie(Cookie::new("access_token", access_token));
+        self.header("X-CSRF-TOKEN", csrf_token)
+    }
+
     pub fn expect_status(mut self, status: Status) -> Self {
         self.expected_status = status;
         self
@@ -130,3 +144,12 @@ impl<'c> TestRequest<'c> {
         response
     }
 }
+
+/// Extracts the URL of the link with the given relation from a pagination response's `Links` header
+pub fn link(links_header: &str, rel: &str) -> Option<String> {
+    links_header.split(',').find_map(|link| {
+        let (url, link_rel) = link.split_once("; rel=")?;
+
+        (link_rel == rel).then(|| url.trim_start_matches('<').trim_end_matches('>').to_string())
+    })
+}
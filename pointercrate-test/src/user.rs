@@ -4,31 +4,61 @@ use pointercrate_core::{
     permission::{Permission, PermissionsManager},
     pool::PointercratePool,
 };
-use pointercrate_core_api::preferences::PreferenceManager;
+use pointercrate_core_api::{maintenance::MaintenanceFairing, metrics::MetricsFairing, preferences::PreferenceManager};
 use pointercrate_user::{
-    auth::{legacy::Registration, AuthenticatedUser, PasswordOrBrowser},
+    auth::{legacy::Registration, oauth::OauthProvider, AuthenticatedUser, PasswordOrBrowser},
     ADMINISTRATOR, MODERATOR,
 };
+use pointercrate_user_api::oauth::OauthProviderStore;
 use pointercrate_user_pages::account::AccountPageConfig;
-use rocket::local::asynchronous::Client;
+use rocket::{local::asynchronous::Client, Build, Rocket};
 use sqlx::{pool::PoolConnection, PgConnection, Pool, Postgres};
 
+fn default_permissions() -> PermissionsManager {
+    PermissionsManager::new(vec![MODERATOR, ADMINISTRATOR])
+        .assigns(ADMINISTRATOR, MODERATOR)
+        .implies(ADMINISTRATOR, MODERATOR)
+}
+
 pub async fn setup_rocket(pool: Pool<Postgres>) -> (TestClient, PoolConnection<Postgres>) {
+    setup_rocket_with_permissions(pool, default_permissions()).await
+}
+
+/// Sets up a rocket instance where users can sign in with the given OAuth2 providers (see also
+/// [`MockIssuer`](crate::oauth::MockIssuer))
+pub async fn setup_rocket_with_oauth(pool: Pool<Postgres>, providers: Vec<OauthProvider>) -> (TestClient, PoolConnection<Postgres>) {
+    setup(
+        rocket::build().manage(OauthProviderStore::new(providers)),
+        pool,
+        default_permissions(),
+    )
+    .await
+}
+
+pub async fn setup_rocket_with_permissions(
+    pool: Pool<Postgres>, permissions: PermissionsManager,
+) -> (TestClient, PoolConnection<Postgres>) {
+    setup(rocket::build(), pool, permissions).await
+}
+
+async fn setup(rocket: Rocket<Build>, pool: Pool<Postgres>, permissions: PermissionsManager) -> (TestClient, PoolConnection<Postgres>) {
     let _ = dotenv::dotenv();
 
     let connection = pool.acquire().await.unwrap();
 
-    let permissions = PermissionsManager::new(vec![MODERATOR, ADMINISTRATOR])
-        .assigns(ADMINISTRATOR, MODERATOR)
-        .implies(ADMINISTRATOR, MODERATOR);
-
     LocalesLoader::empty();
 
-    let rocket = pointercrate_user_api::setup(rocket::build())
+    let rocket = pointercrate_user_api::setup(rocket)
         .manage(PointercratePool::from(pool))
         .manage(permissions)
         .manage(AccountPageConfig::default())
-        .manage(PreferenceManager::default().preference("locale", "en"));
+        .manage(PreferenceManager::default().preference("locale", "en"))
+        .register(
+            "/",
+            rocket::catchers![pointercrate_user_api::auth::catch_401, pointercrate_user_api::auth::catch_403],
+        )
+        .attach(MaintenanceFairing::default())
+        .attach(MetricsFairing);
 
     (TestClient::new(Client::tracked(rocket).await.unwrap()), connection)
 }
@@ -0,0 +1,33 @@
+use pointercrate_core_api::cache::ResponseCache;
+use sqlx::{Pool, Postgres};
+use std::time::Duration;
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_invalidation_reaches_other_instances(pool: Pool<Postgres>) {
+    let cache = ResponseCache::new(Duration::from_secs(60));
+    let other_cache = ResponseCache::new(Duration::from_secs(60));
+
+    rocket::tokio::spawn(other_cache.clone().listen(pool.clone()));
+
+    let compute = |value: i32| async move { Ok::<_, ()>(value) };
+
+    assert_eq!(other_cache.get_or_insert("key", &["tag"], compute(1)).await, Ok(1));
+    assert_eq!(other_cache.get_or_insert("key", &["tag"], compute(2)).await, Ok(1));
+
+    let mut connection = pool.acquire().await.unwrap();
+
+    // The listener might not be subscribed yet, so keep invalidating until the other instance
+    // receives it
+    for _ in 0..50 {
+        cache.invalidate(&["unrelated"], &mut connection).await.unwrap();
+        cache.invalidate(&["tag"], &mut connection).await.unwrap();
+
+        rocket::tokio::time::sleep(Duration::from_millis(100)).await;
+
+        if other_cache.get_or_insert("key", &["tag"], compute(3)).await == Ok(3) {
+            return;
+        }
+    }
+
+    panic!("Invalidation was never received by other instance");
+}
@@ -0,0 +1,243 @@
+use pointercrate_demonlist::{
+    archive::{ArchiveEntry, ArchiveImporter, ARCHIVE_VERSION},
+    player::{recompute_scores, DatabasePlayer},
+    record::RecordStatus,
+    LIST_ADMINISTRATOR, LIST_MODERATOR,
+};
+use rocket::http::{ContentType, Status};
+use serde_json::json;
+use sqlx::{Pool, Postgres};
+
+fn ndjson() -> ContentType {
+    ContentType::new("application", "x-ndjson")
+}
+
+fn parse(archive: &str) -> Vec<ArchiveEntry> {
+    archive.lines().map(|line| serde_json::from_str(line).unwrap()).collect()
+}
+
+/// The entries of an archive that are expected to survive an export/import round trip unchanged
+///
+/// The header contains the export time, and audit entries get new IDs during import (and the
+/// import itself is audited).
+fn data_entries(entries: &[ArchiveEntry]) -> Vec<ArchiveEntry> {
+    entries
+        .iter()
+        .filter(|entry| !matches!(entry, ArchiveEntry::Header(_) | ArchiveEntry::Audit(_) | ArchiveEntry::End(_)))
+        .cloned()
+        .collect()
+}
+
+fn header(version: u32) -> String {
+    json!({"type": "header", "version": version, "exported_at": "2025-10-28T09:00:00", "scoring_fingerprint": null}).to_string()
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_archive_round_trip(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+    let user = pointercrate_test::user::system_user_with_perms(LIST_ADMINISTRATOR, &mut connection).await;
+
+    let verifier = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let player = DatabasePlayer::by_name_or_create("stardust1972", &mut connection).await.unwrap();
+
+    let bloodbath = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, verifier.id, verifier.id, &mut connection).await;
+    let tartarus = pointercrate_test::demonlist::add_demon("Tartarus", 2, 60, verifier.id, verifier.id, &mut connection).await;
+
+    let record = pointercrate_test::demonlist::add_simple_record(100, player.id, bloodbath, RecordStatus::Approved, &mut connection).await;
+    pointercrate_test::demonlist::add_simple_record(70, player.id, tartarus, RecordStatus::Approved, &mut connection).await;
+    // Only approved records are archived
+    pointercrate_test::demonlist::add_simple_record(100, player.id, tartarus, RecordStatus::Rejected, &mut connection).await;
+
+    sqlx::query!("INSERT INTO record_notes (record, content) VALUES ($1, 'note')", record)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    recompute_scores(&mut connection).await.unwrap();
+
+    let archive = clnt
+        .get("/api/v1/archive/")
+        .authorize_as(&user)
+        .expect_header("Content-Type", "application/x-ndjson")
+        .execute()
+        .await
+        .into_string()
+        .await
+        .unwrap();
+    let exported = parse(&archive);
+
+    let records = exported.iter().filter(|entry| matches!(entry, ArchiveEntry::Record(_))).count();
+
+    assert_eq!(records, 2);
+    assert!(matches!(exported.last(), Some(ArchiveEntry::End(_))));
+
+    // Archives can only be imported into empty instances
+    clnt.post_raw("/api/v1/archive/", ndjson(), &archive)
+        .authorize_as(&user)
+        .expect_status(Status::Conflict)
+        .execute()
+        .await;
+
+    sqlx::query!("TRUNCATE players, record_notes CASCADE")
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    let summary: serde_json::Value = clnt
+        .post_raw("/api/v1/archive/", ndjson(), &archive)
+        .authorize_as(&user)
+        .get_result()
+        .await;
+
+    assert_eq!(summary["players"], 2);
+    assert_eq!(summary["demons"], 2);
+    assert_eq!(summary["records"], 2);
+    assert_eq!(summary["notes"], 1);
+
+    let reexported = clnt
+        .get("/api/v1/archive/")
+        .authorize_as(&user)
+        .execute()
+        .await
+        .into_string()
+        .await
+        .unwrap();
+
+    assert_eq!(data_entries(&exported), data_entries(&parse(&reexported)));
+
+    // IDs of newly created objects do not collide with imported ones
+    let new_player = DatabasePlayer::by_name_or_create("stardust1973", &mut connection).await.unwrap();
+
+    assert!(new_player.id > player.id);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_import_without_scores(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+    let user = pointercrate_test::user::system_user_with_perms(LIST_ADMINISTRATOR, &mut connection).await;
+
+    let verifier = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let player = DatabasePlayer::by_name_or_create("stardust1972", &mut connection).await.unwrap();
+    let bloodbath = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, verifier.id, verifier.id, &mut connection).await;
+
+    pointercrate_test::demonlist::add_simple_record(100, player.id, bloodbath, RecordStatus::Approved, &mut connection).await;
+    recompute_scores(&mut connection).await.unwrap();
+
+    let archive = clnt
+        .get("/api/v1/archive/")
+        .authorize_as(&user)
+        .execute()
+        .await
+        .into_string()
+        .await
+        .unwrap();
+
+    sqlx::query!("TRUNCATE players CASCADE").execute(&mut *connection).await.unwrap();
+
+    let mut importer = ArchiveImporter::new(&mut connection).await.unwrap();
+
+    for line in archive.lines() {
+        importer.import_line(line).await.unwrap();
+    }
+
+    let summary = importer.finish_without_scores().await.unwrap();
+
+    assert_eq!(summary.players, 2);
+    assert_eq!(summary.records, 1);
+
+    // Computing the scores is left to a scheduled recomputation
+    let scores = sqlx::query_scalar!(r#"SELECT COUNT(*) AS "count!" FROM player_scores"#)
+        .fetch_one(&mut *connection)
+        .await
+        .unwrap();
+
+    assert_eq!(scores, 0);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_import_requires_admin(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+    let user = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut connection).await;
+
+    clnt.get("/api/v1/archive/")
+        .authorize_as(&user)
+        .expect_status(Status::Forbidden)
+        .execute()
+        .await;
+    clnt.post_raw("/api/v1/archive/", ndjson(), header(ARCHIVE_VERSION))
+        .authorize_as(&user)
+        .expect_status(Status::Forbidden)
+        .execute()
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_import_invalid_archives(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+    let user = pointercrate_test::user::system_user_with_perms(LIST_ADMINISTRATOR, &mut connection).await;
+
+    let player = json!({"type": "player", "id": 1, "name": "stardust1971", "banned": false, "link_banned": false, "nationality": null, "subdivision": null, "scores": []});
+    let demon = json!({"type": "demon", "id": 1, "list": 1, "name": "Bloodbath", "position": 2, "requirement": 50, "video": null, "thumbnail": "", "verifier": 1, "publisher": 1, "level_id": null});
+    let list = json!({"type": "list", "id": 1, "name": "demonlist", "list_size": 75, "extended_list_size": 150});
+
+    let cases = [
+        // Not JSON
+        (format!("{}\nnot json", header(ARCHIVE_VERSION)), 42245),
+        // Truncated
+        (format!("{}\n{}", header(ARCHIVE_VERSION), list), 42245),
+        // Wrong entry count
+        (
+            format!("{}\n{}\n{{\"type\":\"end\",\"entries\":2}}", header(ARCHIVE_VERSION), list),
+            42245,
+        ),
+        // Out of order
+        (
+            format!(
+                "{}\n{}\n{}\n{{\"type\":\"end\",\"entries\":2}}",
+                header(ARCHIVE_VERSION),
+                player,
+                list
+            ),
+            42245,
+        ),
+        (
+            format!("{}\n{{\"type\":\"end\",\"entries\":0}}", header(ARCHIVE_VERSION + 1)),
+            42246,
+        ),
+        // Unknown verifier
+        (
+            format!(
+                "{}\n{}\n{}\n{{\"type\":\"end\",\"entries\":2}}",
+                header(ARCHIVE_VERSION),
+                list,
+                demon
+            ),
+            42247,
+        ),
+        // The list has no demon at position 1
+        (
+            format!(
+                "{}\n{}\n{}\n{}\n{{\"type\":\"end\",\"entries\":3}}",
+                header(ARCHIVE_VERSION),
+                list,
+                player,
+                demon
+            ),
+            42248,
+        ),
+    ];
+
+    for (archive, code) in cases {
+        let response: serde_json::Value = clnt
+            .post_raw("/api/v1/archive/", ndjson(), &archive)
+            .authorize_as(&user)
+            .expect_status(Status::UnprocessableEntity)
+            .get_result()
+            .await;
+
+        assert_eq!(response["code"], code, "{}", archive);
+    }
+
+    // Nothing of the failed imports was kept
+    assert!(ArchiveImporter::new(&mut connection).await.is_ok());
+}
@@ -0,0 +1,49 @@
+use pointercrate_core::etag::Taggable;
+use pointercrate_demonlist::{
+    demon::{Demon, FullDemon},
+    player::DatabasePlayer,
+    LIST_MODERATOR,
+};
+use sqlx::{Pool, Postgres};
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_listed_demons_cached_until_modified(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let user = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon_id = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 87, player.id, player.id, &mut connection).await;
+
+    let (demons, links) = clnt.get("/api/v2/demons/listed/").get_pagination_result::<Demon>().await;
+
+    assert_eq!(demons[0].base.name, "Bloodbath");
+
+    // Changes made without going through the API do not invalidate the cache
+    sqlx::query!("UPDATE demons SET name = 'Bloodlust' WHERE id = $1", demon_id)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    let (demons, cached_links) = clnt.get("/api/v2/demons/listed/").get_pagination_result::<Demon>().await;
+
+    assert_eq!(demons[0].base.name, "Bloodbath");
+    assert_eq!(links, cached_links);
+
+    // Different query strings are cached separately
+    let (demons, _) = clnt.get("/api/v2/demons/listed/?limit=1").get_pagination_result::<Demon>().await;
+
+    assert_eq!(demons[0].base.name, "Bloodlust");
+
+    let demon = FullDemon::by_id(demon_id, &mut connection).await.unwrap();
+
+    clnt.patch(format!("/api/v2/demons/{}/", demon_id), &serde_json::json!({"requirement": 90}))
+        .authorize_as(&user)
+        .header("If-Match", demon.etag_string())
+        .execute()
+        .await;
+
+    let (demons, _) = clnt.get("/api/v2/demons/listed/").get_pagination_result::<Demon>().await;
+
+    assert_eq!(demons[0].base.name, "Bloodlust");
+    assert_eq!(demons[0].requirement, 90);
+}
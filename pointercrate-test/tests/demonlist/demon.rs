@@ -1,12 +1,16 @@
-use pointercrate_core::pagination::PaginationParameters;
+use pointercrate_core::{etag::Taggable, pagination::PaginationParameters};
 use pointercrate_core_api::pagination::LinksBuilder;
 use pointercrate_demonlist::{
-    demon::{Demon, DemonPositionPagination},
-    player::DatabasePlayer,
-    LIST_MODERATOR,
+    demon::{Demon, DemonPositionPagination, FullDemon},
+    error::Result,
+    player::{DatabasePlayer, FullPlayer},
+    record::RecordStatus,
+    video::{VideoHost, VideoHosts},
+    LIST_ADMINISTRATOR, LIST_MODERATOR,
 };
 use rocket::http::Status;
 use sqlx::{Pool, Postgres};
+use url::Url;
 
 #[sqlx::test(migrations = "../migrations")]
 async fn test_add_demon_ratelimits(pool: Pool<Postgres>) {
@@ -24,13 +28,25 @@ async fn test_add_demon_ratelimits(pool: Pool<Postgres>) {
         .await;
 
     // second one should hit the "1 per minute" ratelimit
-    let result: serde_json::Value = clnt
+    let response = clnt
         .post("/api/v2/demons/", &demon)
         .authorize_as(&user)
         .expect_status(Status::TooManyRequests)
-        .get_result()
+        .expect_header("RateLimit-Limit", "1")
+        .expect_header("RateLimit-Remaining", "0")
+        .execute()
         .await;
 
+    let retry_after: u64 = response.headers().get_one("Retry-After").unwrap().parse().unwrap();
+
+    assert!(0 < retry_after && retry_after <= 60);
+    assert_eq!(
+        response.headers().get_one("RateLimit-Reset"),
+        Some(retry_after.to_string().as_str())
+    );
+
+    let result: serde_json::Value = serde_json::from_str(&response.into_string().await.unwrap()).unwrap();
+
     assert_eq!(result["code"].as_i64(), Some(42900))
 }
 
@@ -138,7 +154,7 @@ async fn test_demon_pagination(pool: Pool<Postgres>) {
 
     assert_eq!(links, expected.generate(&base).unwrap());
 
-    // Query an empty page by setting "before" and "after" to an empty range. Should result in a response with only "first" and "last" headers set
+    // Query an empty page by setting "before" and "after" to an empty range. Since there are demons on either side of the range, both "next" and "prev" headers should be set
     let base = DemonPositionPagination {
         params: PaginationParameters {
             before: Some(2),
@@ -154,7 +170,7 @@ async fn test_demon_pagination(pool: Pool<Postgres>) {
 
     assert_eq!(demons.len(), 0);
 
-    let expected = LinksBuilder::new(URL).with_first(0).with_last(4);
+    let expected = LinksBuilder::new(URL).with_first(0).with_last(4).with_next(1).with_previous(2);
 
     assert_eq!(links, expected.generate(&base).unwrap());
 
@@ -192,11 +208,139 @@ async fn test_demon_pagination(pool: Pool<Postgres>) {
     assert_eq!(demons[0].base.id, id2);
     assert_eq!(demons[1].base.id, id3);
 
-    let expected = LinksBuilder::new(URL)
-        .with_first(0)
-        .with_last(4)
-        .with_next(3) // FIXME: This `next` link should not have been returned (currently we always return a "next" link if a `before` parameter is set though)
-        .with_previous(2);
+    let expected = LinksBuilder::new(URL).with_first(0).with_last(4).with_previous(2);
 
     assert_eq!(links, expected.generate(&base).unwrap());
 }
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_delete_demon(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let administrator = pointercrate_test::user::system_user_with_perms(LIST_ADMINISTRATOR, &mut connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+
+    let id1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 100, player.id, player.id, &mut connection).await;
+    let id2 = pointercrate_test::demonlist::add_demon("Bloodbath 2", 2, 100, player.id, player.id, &mut connection).await;
+
+    pointercrate_test::demonlist::add_simple_record(100, player.id, id1, RecordStatus::Approved, &mut connection).await;
+
+    let demon: FullDemon = clnt
+        .get(format!("/api/v2/demons/{}/", id1))
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    clnt.delete(format!("/api/v2/demons/{}/", id1))
+        .authorize_as(&administrator)
+        .header("If-Match", "not the etag")
+        .expect_status(Status::PreconditionFailed)
+        .execute()
+        .await;
+
+    clnt.delete(format!("/api/v2/demons/{}/", id1))
+        .authorize_as(&administrator)
+        .header("If-Match", demon.etag_string())
+        .expect_status(Status::NoContent)
+        .execute()
+        .await;
+
+    clnt.get(format!("/api/v2/demons/{}/", id1))
+        .expect_status(Status::NotFound)
+        .execute()
+        .await;
+
+    // The gap left by the deleted demon is closed
+    let demon: FullDemon = clnt
+        .get(format!("/api/v2/demons/{}/", id2))
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(demon.demon.base.position, 1);
+
+    // Records on the deleted demon are gone
+    let player: FullPlayer = clnt
+        .get(format!("/api/v1/players/{}/", player.id))
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert!(player.records.is_empty(), "{:?}", player.records);
+
+    // The deletion is logged as such, not as a modification of the deleted demon
+    let modifications = sqlx::query_scalar!(r#"SELECT COUNT(*) AS "count!" FROM demon_modifications WHERE id = $1"#, id1)
+        .fetch_one(&mut *connection)
+        .await
+        .unwrap();
+
+    assert_eq!(modifications, 0);
+
+    let movements: Vec<serde_json::Value> = clnt
+        .get(format!("/api/v2/demons/{}/audit/movement/", id2))
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(
+        movements.last().unwrap()["reason"]["OtherDeletedAbove"]["other"]["name"],
+        "Bloodbath"
+    );
+}
+
+struct Streamable;
+
+impl VideoHost for Streamable {
+    fn name(&self) -> &str {
+        "Streamable"
+    }
+
+    fn handles(&self, domain: &str) -> bool {
+        matches!(domain, "streamable.com" | "www.streamable.com")
+    }
+
+    fn normalize(&self, url: &Url) -> Result<String> {
+        Ok(format!("https://streamable.com{}", url.path()))
+    }
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_unsupported_video_host(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let user = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut connection).await;
+
+    let response: serde_json::Value = clnt
+        .post(
+            "/api/v2/demons/",
+            &serde_json::json! {{"name": "Bloodbath", "requirement": 90, "position": 1, "verifier": "Riot", "publisher": "Riot", "creators": [], "video": "https://streamable.com/abcdef"}},
+        )
+        .authorize_as(&user)
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(response["code"], 42224);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_custom_video_host(pool: Pool<Postgres>) {
+    let (clnt, mut connection) =
+        pointercrate_test::demonlist::setup_rocket_with_video_hosts(pool, VideoHosts::default().with(Streamable)).await;
+
+    let user = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut connection).await;
+
+    let demon: FullDemon = clnt
+        .post(
+            "/api/v2/demons/",
+            &serde_json::json! {{"name": "Bloodbath", "requirement": 90, "position": 1, "verifier": "Riot", "publisher": "Riot", "creators": [], "video": "https://www.streamable.com/abcdef?t=3"}},
+        )
+        .authorize_as(&user)
+        .expect_status(Status::Created)
+        .get_success_result()
+        .await;
+
+    assert_eq!(demon.demon.video.as_deref(), Some("https://streamable.com/abcdef"));
+    // Streamable does not provide thumbnails, so the default one is used
+    assert_eq!(demon.demon.thumbnail, "https://i.ytimg.com/vi/zebrafishes/mqdefault.jpg");
+}
@@ -0,0 +1,173 @@
+use pointercrate_core::job::{self, Job, JobQueue};
+use pointercrate_demonlist::{player::DatabasePlayer, LIST_ADMINISTRATOR};
+use rocket::http::Status;
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Postgres};
+
+#[derive(Serialize, Deserialize)]
+struct TestJob {
+    key: String,
+    fail: bool,
+}
+
+impl Job for TestJob {
+    const KIND: &'static str = "test";
+
+    fn dedup_key(&self) -> Option<String> {
+        Some(self.key.clone())
+    }
+
+    fn max_attempts(&self) -> i16 {
+        2
+    }
+}
+
+async fn job_state(id: i32, pool: &Pool<Postgres>) -> (String, i16, Option<String>) {
+    let row = sqlx::query!(
+        r#"SELECT status::TEXT AS "status!", attempts, last_error FROM jobs WHERE id = $1"#,
+        id
+    )
+    .fetch_one(pool)
+    .await
+    .unwrap();
+
+    (row.status, row.attempts, row.last_error)
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_job_queue(pool: Pool<Postgres>) {
+    let mut connection = pool.acquire().await.unwrap();
+
+    let queue = JobQueue::new(pool.clone()).register(|job: TestJob, attempt| async move {
+        match job.fail {
+            true => Err(format!("attempt {} failed", attempt)),
+            false => Ok(()),
+        }
+    });
+
+    let succeeding = TestJob {
+        key: "a".to_string(),
+        fail: false,
+    };
+    let failing = TestJob {
+        key: "b".to_string(),
+        fail: true,
+    };
+
+    let succeeding_id = job::enqueue(&succeeding, &mut connection).await.unwrap().unwrap();
+    let failing_id = job::enqueue(&failing, &mut connection).await.unwrap().unwrap();
+
+    // Only one unfinished job per deduplication key
+    assert_eq!(job::enqueue(&succeeding, &mut connection).await.unwrap(), None);
+
+    assert!(queue.run_next().await.unwrap());
+    assert!(queue.run_next().await.unwrap());
+
+    assert_eq!(job_state(succeeding_id, &pool).await, ("SUCCEEDED".to_string(), 1, None));
+    assert_eq!(
+        job_state(failing_id, &pool).await,
+        ("PENDING".to_string(), 1, Some("attempt 1 failed".to_string()))
+    );
+
+    // The failed job is only retried after backing off
+    assert!(!queue.run_next().await.unwrap());
+
+    sqlx::query!(
+        "UPDATE jobs SET scheduled_at = scheduled_at - INTERVAL '1 hour' WHERE id = $1",
+        failing_id
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    assert!(queue.run_next().await.unwrap());
+    assert_eq!(
+        job_state(failing_id, &pool).await,
+        ("FAILED".to_string(), 2, Some("attempt 2 failed".to_string()))
+    );
+
+    // Finished jobs do not prevent new jobs with the same key
+    assert!(job::enqueue(&succeeding, &mut connection).await.unwrap().is_some());
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_abandoned_job_is_retried(pool: Pool<Postgres>) {
+    let mut connection = pool.acquire().await.unwrap();
+
+    let queue = JobQueue::new(pool.clone()).register(|_: TestJob, _| async { Ok::<(), String>(()) });
+
+    let id = job::enqueue(
+        &TestJob {
+            key: "a".to_string(),
+            fail: false,
+        },
+        &mut connection,
+    )
+    .await
+    .unwrap()
+    .unwrap();
+
+    // Simulate a worker that crashed while running the job
+    sqlx::query!(
+        "UPDATE jobs SET status = 'RUNNING', attempts = 1, locked_until = (NOW() AT TIME ZONE 'utc') - INTERVAL '1 minute' WHERE id = $1",
+        id
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    assert!(queue.run_next().await.unwrap());
+    assert_eq!(job_state(id, &pool).await, ("SUCCEEDED".to_string(), 2, None));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_list_jobs(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+    let user = pointercrate_test::user::system_user_with_perms(LIST_ADMINISTRATOR, &mut connection).await;
+
+    clnt.get("/api/v1/jobs/").expect_status(Status::Unauthorized).execute().await;
+
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player.id, player.id, &mut connection).await;
+
+    clnt.post(
+        "/api/v1/records/",
+        &serde_json::json!({"progress": 60, "demon": demon, "player": "stardust1971", "video": "https://youtube.com/watch?v=1234567890", "raw_footage": "https://pointercrate.com"}),
+    )
+    .expect_status(Status::Ok)
+    .execute()
+    .await;
+
+    // No worker handles jobs of this kind, so it stays pending
+    job::enqueue(
+        &TestJob {
+            key: "a".to_string(),
+            fail: false,
+        },
+        &mut connection,
+    )
+    .await
+    .unwrap();
+
+    let (jobs, _) = clnt
+        .get("/api/v1/jobs/")
+        .authorize_as(&user)
+        .get_pagination_result::<serde_json::Value>()
+        .await;
+
+    // The first launch also schedules computing all scores
+    let kinds = jobs.iter().map(|job| job["kind"].as_str().unwrap()).collect::<Vec<_>>();
+
+    assert_eq!(kinds, ["recompute_scores", "validate_submission", "test"]);
+    assert_eq!(jobs[1]["payload"]["video"], "https://www.youtube.com/watch?v=1234567890");
+    assert_eq!(jobs[2]["status"], "pending");
+
+    let (jobs, _) = clnt
+        .get("/api/v1/jobs/?kind=test&status=pending")
+        .authorize_as(&user)
+        .get_pagination_result::<serde_json::Value>()
+        .await;
+
+    assert_eq!(jobs.len(), 1);
+    assert_eq!(jobs[0]["payload"], serde_json::json!({"key": "a", "fail": false}));
+}
@@ -0,0 +1,177 @@
+use pointercrate_demonlist::{
+    list::DEFAULT_LIST,
+    player::{recompute_scores, DatabasePlayer},
+    record::RecordStatus,
+};
+use pointercrate_test::{
+    demonlist::{add_demon, add_demon_on_list, add_simple_record, CHALLENGE_HELPER},
+    link,
+    user::system_user_with_perms,
+};
+use rocket::http::Status;
+use sqlx::{Pool, Postgres};
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_list_helper_records(pool: Pool<Postgres>) {
+    let (clnt, mut connection, challenge_list) = pointercrate_test::demonlist::setup_rocket_with_challenge_list(pool).await;
+
+    let helper = system_user_with_perms(CHALLENGE_HELPER, &mut connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+
+    let demon = add_demon("Bloodbath", 1, 50, player.id, player.id, &mut connection).await;
+    let challenge = add_demon_on_list(challenge_list, "VSC", 1, 100, player.id, player.id, &mut connection).await;
+
+    let record = add_simple_record(100, player.id, demon, RecordStatus::Submitted, &mut connection).await;
+    let challenge_record = add_simple_record(100, player.id, challenge, RecordStatus::Submitted, &mut connection).await;
+
+    // Unapproved records are only visible on the helper's own list
+    clnt.get(format!("/api/v1/records/{}/", challenge_record))
+        .authorize_as(&helper)
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+    clnt.get(format!("/api/v1/records/{}/", record))
+        .authorize_as(&helper)
+        .expect_status(Status::NotFound)
+        .execute()
+        .await;
+
+    // The same goes for filtering by status
+    clnt.get(format!("/api/v1/records/?status=submitted&list={}", challenge_list))
+        .authorize_as(&helper)
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+    clnt.get(format!("/api/v1/records/?status=submitted&list={}", DEFAULT_LIST))
+        .authorize_as(&helper)
+        .expect_status(Status::Forbidden)
+        .execute()
+        .await;
+    clnt.get("/api/v1/records/?status=submitted")
+        .authorize_as(&helper)
+        .expect_status(Status::Forbidden)
+        .execute()
+        .await;
+
+    // ... and for notes
+    let note = serde_json::json! {{"content": "Checked raw footage"}};
+
+    clnt.post(format!("/api/v1/records/{}/notes/", challenge_record), &note)
+        .authorize_as(&helper)
+        .expect_status(Status::Created)
+        .execute()
+        .await;
+    clnt.post(format!("/api/v1/records/{}/notes/", record), &note)
+        .authorize_as(&helper)
+        .expect_status(Status::Forbidden)
+        .execute()
+        .await;
+    clnt.get(format!("/api/v1/records/{}/notes/", challenge_record))
+        .authorize_as(&helper)
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+    clnt.get(format!("/api/v1/records/{}/notes/", record))
+        .authorize_as(&helper)
+        .expect_status(Status::NotFound)
+        .execute()
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_list_helper_submissions(pool: Pool<Postgres>) {
+    let (clnt, mut connection, challenge_list) = pointercrate_test::demonlist::setup_rocket_with_challenge_list(pool).await;
+
+    let helper = system_user_with_perms(CHALLENGE_HELPER, &mut connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+
+    let demon = add_demon("Bloodbath", 1, 50, player.id, player.id, &mut connection).await;
+    let challenge = add_demon_on_list(challenge_list, "VSC", 1, 100, player.id, player.id, &mut connection).await;
+
+    // Helpers can directly add approved records to their own list only
+    clnt.post(
+        "/api/v1/records/",
+        &serde_json::json! {{"progress": 100, "demon": challenge, "player": "stardust1971", "status": "approved"}},
+    )
+    .authorize_as(&helper)
+    .expect_status(Status::Ok)
+    .execute()
+    .await;
+    clnt.post(
+        "/api/v1/records/",
+        &serde_json::json! {{"progress": 100, "demon": demon, "player": "stardust1971", "status": "approved"}},
+    )
+    .authorize_as(&helper)
+    .expect_status(Status::Forbidden)
+    .execute()
+    .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_list_helper_banned_players(pool: Pool<Postgres>) {
+    let (clnt, mut connection, challenge_list) = pointercrate_test::demonlist::setup_rocket_with_challenge_list(pool).await;
+
+    let helper = system_user_with_perms(CHALLENGE_HELPER, &mut connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+
+    sqlx::query!("UPDATE players SET banned = TRUE WHERE id = $1", player.id)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    let players: Vec<serde_json::Value> = clnt
+        .get(format!("/api/v1/players/?list={}", challenge_list))
+        .authorize_as(&helper)
+        .get_result()
+        .await;
+
+    assert_eq!(players.len(), 1, "Banned player hidden from helper of the list");
+
+    let players: Vec<serde_json::Value> = clnt
+        .get(format!("/api/v1/players/?list={}", DEFAULT_LIST))
+        .authorize_as(&helper)
+        .get_result()
+        .await;
+
+    assert!(players.is_empty(), "Banned player visible to helper of a different list");
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_ranking_links_per_list(pool: Pool<Postgres>) {
+    let (clnt, mut connection, challenge_list) = pointercrate_test::demonlist::setup_rocket_with_challenge_list(pool).await;
+
+    let verifier = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon = add_demon("Bloodbath", 1, 50, verifier.id, verifier.id, &mut connection).await;
+    let challenge = add_demon_on_list(challenge_list, "VSC", 1, 100, verifier.id, verifier.id, &mut connection).await;
+
+    add_simple_record(100, verifier.id, demon, RecordStatus::Approved, &mut connection).await;
+
+    for name in ["Bob", "aeon", "Carol"] {
+        let player = DatabasePlayer::by_name_or_create(name, &mut connection).await.unwrap();
+
+        add_simple_record(100, player.id, challenge, RecordStatus::Approved, &mut connection).await;
+    }
+
+    recompute_scores(&mut connection).await.unwrap();
+
+    // The last link of each list's ranking lies just past that list's last rank. The verifier is
+    // ranked on both lists.
+    for (list, ranked) in [(DEFAULT_LIST, 1), (challenge_list, 4)] {
+        let (players, links) = clnt
+            .get(format!("/api/v1/players/ranking/?list={}", list))
+            .get_pagination_result::<serde_json::Value>()
+            .await;
+
+        assert_eq!(players.len(), ranked);
+
+        let last = link(&links, "last").unwrap();
+        let before = format!("before={}", ranked + 1);
+
+        assert!(
+            last.split(['?', '&']).any(|param| param == before),
+            "{} does not contain {}",
+            last,
+            before
+        );
+    }
+}
@@ -1,4 +1,10 @@
+mod archive;
+mod cache;
 mod demon;
+mod job;
+mod list;
 mod nationality;
 mod player;
 mod record;
+mod review;
+mod webhook;
@@ -1,8 +1,9 @@
+use pointercrate_core::etag::Taggable;
 use pointercrate_demonlist::record::RecordStatus;
 use pointercrate_demonlist::{
     nationality::{Nationality, Subdivision},
     player::{DatabasePlayer, FullPlayer, Player},
-    LIST_HELPER, LIST_MODERATOR,
+    LIST_ADMINISTRATOR, LIST_HELPER, LIST_MODERATOR,
 };
 use rocket::http::Status;
 use serde_json::json;
@@ -69,6 +70,39 @@ async fn test_list_helper_pagination(pool: Pool<Postgres>) {
     assert_eq!(json[1].base.id, unbanned.id);
 }
 
+#[sqlx::test(migrations = "../migrations")]
+async fn test_pagination_sorted_by_name(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    create_players(&mut connection).await;
+    DatabasePlayer::by_name_or_create("Bob", &mut connection).await.unwrap();
+    DatabasePlayer::by_name_or_create("aeon", &mut connection).await.unwrap();
+    let user = pointercrate_test::user::system_user_with_perms(LIST_HELPER, &mut connection).await;
+
+    let mut next = Some("/api/v1/players/?sort=name&limit=1".to_string());
+    let mut names = Vec::new();
+
+    while let Some(url) = next {
+        let (players, links) = client.get(url).authorize_as(&user).get_pagination_result::<Player>().await;
+
+        names.extend(players.into_iter().map(|player| player.base.name));
+        next = pointercrate_test::link(&links, "next");
+    }
+
+    // Player names are compared case-insensitively
+    assert_eq!(names, ["aeon", "Bob", "stardust1971", "stardust1972"]);
+
+    let (players, _) = client
+        .get("/api/v1/players/?sort=name&order=desc&limit=2")
+        .authorize_as(&user)
+        .get_pagination_result::<Player>()
+        .await;
+
+    assert_eq!(players.len(), 2);
+    assert_eq!(players[0].base.name, "stardust1972");
+    assert_eq!(players[1].base.name, "stardust1971");
+}
+
 #[sqlx::test(migrations = "../migrations")]
 async fn test_patch_player_nationality(pool: Pool<Postgres>) {
     let (client, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
@@ -371,3 +405,103 @@ async fn test_player_merge(pool: Pool<Postgres>) {
         .execute()
         .await;
 }
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_delete_player(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+    let moderator = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut connection).await;
+
+    let with_record = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let orphaned = DatabasePlayer::by_name_or_create("stardust1972", &mut connection).await.unwrap();
+
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 87, with_record.id, with_record.id, &mut connection).await;
+
+    pointercrate_test::demonlist::add_simple_record(90, with_record.id, demon, RecordStatus::Rejected, &mut connection).await;
+    pointercrate_test::demonlist::put_claim(moderator.user().id, orphaned.id, true, false, &mut connection).await;
+
+    let player: FullPlayer = client
+        .get(format!("/api/v1/players/{}/", with_record.id))
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    // Even rejected records prevent deletion
+    let result: serde_json::Value = client
+        .delete(format!("/api/v1/players/{}/", with_record.id))
+        .authorize_as(&moderator)
+        .header("If-Match", player.etag_string())
+        .expect_status(Status::Conflict)
+        .get_result()
+        .await;
+
+    assert_eq!(result["code"], 40909);
+
+    let player: FullPlayer = client
+        .get(format!("/api/v1/players/{}/", orphaned.id))
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    client
+        .delete(format!("/api/v1/players/{}/", orphaned.id))
+        .authorize_as(&moderator)
+        .header("If-Match", player.etag_string())
+        .expect_status(Status::NoContent)
+        .execute()
+        .await;
+
+    client
+        .get(format!("/api/v1/players/{}/", orphaned.id))
+        .expect_status(Status::NotFound)
+        .execute()
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_player_audit_log(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+    let administrator = pointercrate_test::user::system_user_with_perms(LIST_ADMINISTRATOR, &mut connection).await;
+
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let player2 = DatabasePlayer::by_name_or_create("stardust1972", &mut connection).await.unwrap();
+
+    client
+        .patch_player(player2.id, &administrator, json! {{"banned": true}})
+        .await
+        .execute()
+        .await;
+
+    // Renaming player2 to player1's name merges player1 into player2
+    client
+        .patch_player(player2.id, &administrator, json! {{"name": "stardust1971"}})
+        .await
+        .execute()
+        .await;
+
+    let log: Vec<serde_json::Value> = client
+        .get(format!("/api/v1/players/{}/audit/", player2.id))
+        .authorize_as(&administrator)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(log.len(), 4);
+    assert_eq!(log[0]["type"], "Addition");
+    assert_eq!(log[1]["type"]["Modification"]["banned"], false);
+    assert_eq!(log[2]["type"]["Modification"]["merged"]["id"], player1.id);
+    assert_eq!(log[2]["type"]["Modification"]["merged"]["name"], "stardust1971");
+    assert_eq!(log[3]["type"]["Modification"]["name"], "stardust1972");
+
+    let log: Vec<serde_json::Value> = client
+        .get(format!("/api/v1/players/{}/audit/", player1.id))
+        .authorize_as(&administrator)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(log.len(), 4);
+    assert_eq!(log[0]["type"], "Addition");
+    assert_eq!(log[1]["type"]["Modification"]["merged_into"]["id"], player2.id);
+    assert_eq!(log[2]["type"]["Modification"]["name"], "stardust1971");
+    assert_eq!(log[3]["type"], "Deletion");
+}
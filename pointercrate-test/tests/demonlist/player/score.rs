@@ -2,6 +2,7 @@
 
 use pointercrate_core::etag::Taggable;
 use pointercrate_demonlist::{
+    list::DEFAULT_LIST,
     player::{DatabasePlayer, FullPlayer},
     record::FullRecord,
     LIST_MODERATOR,
@@ -70,18 +71,23 @@ pub async fn test_verifications_give_score(pool: Pool<Postgres>) {
 }
 
 async fn nationality_score(iso_country_code: &str, connection: &mut PgConnection) -> f64 {
-    sqlx::query!("SELECT score FROM nationalities WHERE iso_country_code = $1", iso_country_code)
-        .fetch_one(&mut *connection)
-        .await
-        .unwrap()
-        .score
+    sqlx::query!(
+        r#"SELECT COALESCE((SELECT score FROM nation_scores WHERE nation = $1 AND list = $2), 0) AS "score!""#,
+        iso_country_code,
+        DEFAULT_LIST
+    )
+    .fetch_one(&mut *connection)
+    .await
+    .unwrap()
+    .score
 }
 
 async fn subdivision_score(nation: &str, iso_code: &str, connection: &mut PgConnection) -> f64 {
     sqlx::query!(
-        "SELECT score FROM subdivisions WHERE nation = $1 AND iso_code = $2",
+        r#"SELECT COALESCE((SELECT score FROM subdivision_scores WHERE nation = $1 AND subdivision = $2 AND list = $3), 0) AS "score!""#,
         nation,
-        iso_code
+        iso_code,
+        DEFAULT_LIST
     )
     .fetch_one(&mut *connection)
     .await
@@ -140,9 +146,10 @@ pub async fn test_extended_progress_records_give_no_score(pool: Pool<Postgres>)
 
     for position in 1..=(list_size + 1) {
         last_demon_id = sqlx::query!(
-            "INSERT INTO demons (name, position, requirement, verifier, publisher) VALUES ('Bloodbath', $2, 98, $1, $1) RETURNING id",
+            "INSERT INTO demons (name, position, requirement, verifier, publisher, list) VALUES ('Bloodbath', $2, 98, $1, $1, $3) RETURNING id",
             player.id,
-            position
+            position,
+            DEFAULT_LIST
         )
         .fetch_one(&mut *connection)
         .await
@@ -180,9 +187,10 @@ pub async fn test_score_resets_if_last_record_removed(pool: Pool<Postgres>) {
 
     for position in 1..=list_size {
         last_demon_id = sqlx::query!(
-            "INSERT INTO demons (name, position, requirement, verifier, publisher) VALUES ('Bloodbath', $2, 98, $1, $1) RETURNING id",
+            "INSERT INTO demons (name, position, requirement, verifier, publisher, list) VALUES ('Bloodbath', $2, 98, $1, $1, $3) RETURNING id",
             player.id,
-            position
+            position,
+            DEFAULT_LIST
         )
         .fetch_one(&mut *connection)
         .await
@@ -1,4 +1,4 @@
-use pointercrate_core::error::PointercrateError;
+use pointercrate_core::error::{CoreError, PointercrateError};
 use pointercrate_core::etag::Taggable;
 use pointercrate_demonlist::{
     error::DemonlistError,
@@ -6,7 +6,8 @@ use pointercrate_demonlist::{
     record::{note::Note, FullRecord, RecordStatus},
     LIST_HELPER, LIST_MODERATOR,
 };
-use pointercrate_test::{demonlist::add_simple_record, user::system_user_with_perms};
+use pointercrate_test::{demonlist::add_simple_record, link, user::system_user_with_perms};
+use pointercrate_user::auth::{AuthenticatedUser, PasswordOrBrowser};
 use rocket::http::Status;
 use sqlx::{PgConnection, Pool, Postgres};
 
@@ -79,6 +80,93 @@ async fn paginate_records_with_verified_claim_wrong_player(pool: Pool<Postgres>)
     assert_eq!(json.len(), 0);
 }
 
+#[sqlx::test(migrations = "../migrations")]
+async fn paginate_records_sorted_by_progress(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let (_, r1, r2, r3) = setup_pagination_tests(&mut connection).await;
+    let helper = system_user_with_perms(LIST_HELPER, &mut connection).await;
+
+    let mut next = Some("/api/v1/records/?sort=progress&order=desc&limit=1".to_string());
+    let mut ids = Vec::new();
+
+    // Ties in progress are broken by id, in the requested order
+    while let Some(url) = next {
+        let (records, links) = clnt
+            .get(url)
+            .authorize_as(&helper)
+            .get_pagination_result::<serde_json::Value>()
+            .await;
+
+        ids.extend(records.iter().map(|record| record["id"].as_i64().unwrap() as i32));
+        next = link(&links, "next");
+    }
+
+    assert_eq!(ids, [r3, r1, r2]);
+
+    let (records, links) = clnt
+        .get("/api/v1/records/?sort=progress&limit=1")
+        .authorize_as(&helper)
+        .get_pagination_result::<serde_json::Value>()
+        .await;
+
+    assert_eq!(records[0]["id"].as_i64(), Some(r2 as i64));
+    assert!(link(&links, "prev").is_none());
+
+    let (records, links) = clnt
+        .get(link(&links, "next").unwrap())
+        .authorize_as(&helper)
+        .get_pagination_result::<serde_json::Value>()
+        .await;
+
+    assert_eq!(records[0]["id"].as_i64(), Some(r1 as i64));
+
+    // Going back from the second page yields the first one again
+    let (records, _) = clnt
+        .get(link(&links, "prev").unwrap())
+        .authorize_as(&helper)
+        .get_pagination_result::<serde_json::Value>()
+        .await;
+
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0]["id"].as_i64(), Some(r2 as i64));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn paginate_records_invalid_cursor(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    setup_pagination_tests(&mut connection).await;
+    let helper = system_user_with_perms(LIST_HELPER, &mut connection).await;
+
+    let (_, links) = clnt
+        .get("/api/v1/records/?sort=progress&limit=1")
+        .authorize_as(&helper)
+        .get_pagination_result::<serde_json::Value>()
+        .await;
+
+    // A cursor into the progress order cannot be used to paginate by id
+    let next = link(&links, "next").unwrap().replace("sort=progress&", "");
+
+    let error: serde_json::Value = clnt
+        .get(next)
+        .authorize_as(&helper)
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(error["code"], CoreError::InvalidPaginationCursor.error_code());
+
+    let error: serde_json::Value = clnt
+        .get("/api/v1/records/?sort=progress&after=1")
+        .authorize_as(&helper)
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(error["code"], CoreError::MutuallyExclusive.error_code());
+}
+
 async fn setup_pagination_tests(connection: &mut PgConnection) -> (i32, i32, i32, i32) {
     let player1 = DatabasePlayer::by_name_or_create("stardust1971", connection).await.unwrap();
     let player2 = DatabasePlayer::by_name_or_create("stardust1972", connection).await.unwrap();
@@ -269,3 +357,380 @@ async fn test_record_deletion_updates_player_score(pool: Pool<Postgres>) {
 
     assert_eq!(player.player.score, 0.0f64, "Deleting approved record failed to lower player score");
 }
+
+async fn etag_of(clnt: &pointercrate_test::TestClient, record_id: i32, user: &AuthenticatedUser<PasswordOrBrowser>) -> String {
+    clnt.get(format!("/api/v1/records/{}/", record_id))
+        .authorize_as(user)
+        .expect_status(Status::Ok)
+        .get_success_result::<FullRecord>()
+        .await
+        .etag_string()
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_batch_moderation(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let moderator = system_user_with_perms(LIST_MODERATOR, &mut connection).await;
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let player2 = DatabasePlayer::by_name_or_create("stardust1972", &mut connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id, player1.id, &mut connection).await;
+    let demon2 = pointercrate_test::demonlist::add_demon("Bloodlust", 2, 50, player1.id, player1.id, &mut connection).await;
+
+    let approve1 = add_simple_record(100, player1.id, demon1, RecordStatus::Submitted, &mut connection).await;
+    let approve2 = add_simple_record(100, player1.id, demon2, RecordStatus::Submitted, &mut connection).await;
+    let reject = add_simple_record(100, player2.id, demon1, RecordStatus::Submitted, &mut connection).await;
+    let delete = add_simple_record(100, player2.id, demon2, RecordStatus::Approved, &mut connection).await;
+
+    let operations = serde_json::json!([
+        {"op": "patch", "id": approve1, "if_match": etag_of(&clnt, approve1, &moderator).await, "data": {"status": "approved"}},
+        {"op": "patch", "id": approve2, "if_match": etag_of(&clnt, approve2, &moderator).await, "data": {"status": "approved"}},
+        {"op": "patch", "id": reject, "if_match": etag_of(&clnt, reject, &moderator).await, "data": {"status": "rejected"}},
+        {"op": "delete", "id": delete, "if_match": etag_of(&clnt, delete, &moderator).await},
+    ]);
+
+    let results: Vec<serde_json::Value> = clnt
+        .post("/api/v1/records/batch/", &operations)
+        .authorize_as(&moderator)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(results.len(), 4);
+    assert_eq!(results[0]["record"]["status"], "approved");
+    assert_eq!(results[2]["record"]["status"], "rejected");
+    assert_eq!(results[3], serde_json::json!({"op": "delete", "id": delete}));
+
+    // The returned ETags can be used for further modifications
+    assert_eq!(results[0]["etag"], etag_of(&clnt, approve1, &moderator).await);
+
+    // Only players with a non-zero score are ranked
+    assert_eq!(rank_of(player1.id, &mut connection).await, Some(1));
+    assert_eq!(rank_of(player2.id, &mut connection).await, None);
+}
+
+async fn rank_of(player_id: i32, connection: &mut PgConnection) -> Option<i64> {
+    sqlx::query_scalar("SELECT rank FROM player_ranks WHERE id = $1")
+        .bind(player_id)
+        .fetch_optional(connection)
+        .await
+        .unwrap()
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_batch_failure_applies_nothing(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper = system_user_with_perms(LIST_HELPER, &mut connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player.id, player.id, &mut connection).await;
+    let demon2 = pointercrate_test::demonlist::add_demon("Bloodlust", 2, 50, player.id, player.id, &mut connection).await;
+
+    let submitted = add_simple_record(100, player.id, demon1, RecordStatus::Submitted, &mut connection).await;
+    let approved = add_simple_record(100, player.id, demon2, RecordStatus::Approved, &mut connection).await;
+    let etag = etag_of(&clnt, submitted, &helper).await;
+
+    let error: serde_json::Value = clnt
+        .post(
+            "/api/v1/records/batch/",
+            &serde_json::json!([
+                {"op": "patch", "id": submitted, "if_match": etag, "data": {"status": "rejected"}},
+                {"op": "patch", "id": approved, "if_match": "W/\"1;1\"", "data": {"progress": 60}},
+            ]),
+        )
+        .authorize_as(&helper)
+        .expect_status(Status::PreconditionFailed)
+        .get_result()
+        .await;
+
+    assert_eq!(error["code"], CoreError::PreconditionFailed.error_code());
+    assert_eq!(error["data"]["index"], 1);
+
+    // Helpers cannot delete approved records
+    let error: serde_json::Value = clnt
+        .post(
+            "/api/v1/records/batch/",
+            &serde_json::json!([
+                {"op": "patch", "id": submitted, "if_match": etag, "data": {"status": "rejected"}},
+                {"op": "delete", "id": approved, "if_match": etag_of(&clnt, approved, &helper).await},
+            ]),
+        )
+        .authorize_as(&helper)
+        .expect_status(Status::Forbidden)
+        .get_result()
+        .await;
+
+    assert_eq!(error["data"]["index"], 1);
+
+    // The patch preceding the failed operation was rolled back
+    assert_eq!(etag_of(&clnt, submitted, &helper).await, etag);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_batch_validation(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper = system_user_with_perms(LIST_HELPER, &mut connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player.id, player.id, &mut connection).await;
+
+    let record = add_simple_record(100, player.id, demon, RecordStatus::Submitted, &mut connection).await;
+    let etag = etag_of(&clnt, record, &helper).await;
+
+    let max_size = pointercrate_demonlist::config::max_batch_size().unwrap();
+    let operations: Vec<_> = (1..=max_size as i32 + 1)
+        .map(|id| serde_json::json!({"op": "delete", "id": id, "if_match": "W/\"1;1\""}))
+        .collect();
+
+    let error: serde_json::Value = clnt
+        .post("/api/v1/records/batch/", &operations)
+        .authorize_as(&helper)
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(error["code"], DemonlistError::BatchTooLarge { max_size }.error_code());
+
+    // The second operation's precondition could only ever match a state the client has not seen
+    let error: serde_json::Value = clnt
+        .post(
+            "/api/v1/records/batch/",
+            &serde_json::json!([
+                {"op": "patch", "id": record, "if_match": etag, "data": {"progress": 60}},
+                {"op": "patch", "id": record, "if_match": etag, "data": {"status": "approved"}},
+            ]),
+        )
+        .authorize_as(&helper)
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(
+        error["code"],
+        DemonlistError::DuplicateBatchRecord { record_id: record }.error_code()
+    );
+    assert_eq!(etag_of(&clnt, record, &helper).await, etag);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_submission_tracking(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper = system_user_with_perms(LIST_HELPER, &mut connection).await;
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id, player1.id, &mut connection).await;
+
+    let submission = serde_json::json! {{"progress": 60, "demon": demon1, "player": "stardust1971", "video": "https://youtube.com/watch?v=1234567890", "raw_footage": "https://pointercrate.com", "note": "Please look at this"}};
+
+    let response = clnt.post("/api/v1/records/", &submission).expect_status(Status::Ok).execute().await;
+    let token = response
+        .headers()
+        .get_one("X-TRACKING-TOKEN")
+        .expect("'X-TRACKING-TOKEN' header to be set on submissions")
+        .to_owned();
+    let record: serde_json::Value = serde_json::from_str(&response.into_string().await.unwrap()).unwrap();
+    let record_id = record["data"]["id"].as_i64().unwrap() as i32;
+
+    assert!(token.len() >= 32);
+
+    let tracked: serde_json::Value = clnt
+        .get(format!("/api/v1/records/track/{}/", token))
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(tracked["id"].as_i64(), Some(record_id as i64));
+    assert_eq!(tracked["status"], "submitted");
+    assert!(tracked.get("submitter").is_none());
+    assert!(tracked.get("raw_footage").is_none());
+    // The submitter provided note is not public
+    assert_eq!(tracked["notes"].as_array().map(Vec::len), Some(0));
+
+    for (content, is_public) in [("Missing clicks", true), ("Looks fishy", false)] {
+        clnt.post(
+            format!("/api/v1/records/{}/notes/", record_id),
+            &serde_json::json! {{"content": content, "is_public": is_public}},
+        )
+        .authorize_as(&helper)
+        .expect_status(Status::Created)
+        .execute()
+        .await;
+    }
+
+    let record: FullRecord = clnt
+        .get(format!("/api/v1/records/{}/", record_id))
+        .authorize_as(&helper)
+        .get_success_result()
+        .await;
+
+    clnt.patch(
+        format!("/api/v1/records/{}/", record_id),
+        &serde_json::json!({"status": "rejected", "rejection_reason": "no-clicks"}),
+    )
+    .authorize_as(&helper)
+    .header("If-Match", record.etag_string())
+    .expect_status(Status::Ok)
+    .execute()
+    .await;
+
+    let tracked: serde_json::Value = clnt
+        .get(format!("/api/v1/records/track/{}/", token))
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(tracked["status"], "rejected");
+    assert_eq!(tracked["rejection_reason"], "no-clicks");
+
+    let notes = tracked["notes"].as_array().unwrap();
+
+    assert_eq!(notes.len(), 1);
+    assert_eq!(notes[0]["content"], "Missing clicks");
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_track_unknown_submission(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id, player1.id, &mut connection).await;
+    let record = add_simple_record(100, player1.id, demon1, RecordStatus::Submitted, &mut connection).await;
+
+    // Records cannot be looked up by their id
+    let json: serde_json::Value = clnt
+        .get(format!("/api/v1/records/track/{}/", record))
+        .expect_status(Status::NotFound)
+        .get_result()
+        .await;
+
+    assert_eq!(json["code"].as_i64(), Some(40401));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_record_timeline(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper = system_user_with_perms(LIST_HELPER, &mut connection).await;
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id, player1.id, &mut connection).await;
+
+    let submission = serde_json::json! {{"progress": 100, "demon": demon1, "player": "stardust1971", "video": "https://youtube.com/watch?v=1234567890", "raw_footage": "https://pointercrate.com", "achieved_on": "2024-01-05"}};
+
+    let record: FullRecord = clnt
+        .post("/api/v1/records/", &submission)
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(record.achieved_on.map(|date| date.to_string()).as_deref(), Some("2024-01-05"));
+    assert!(record.submitted_at.is_some());
+    assert_eq!(record.approved_at, None);
+
+    let approved: serde_json::Value = clnt
+        .patch(
+            format!("/api/v1/records/{}/", record.id),
+            &serde_json::json!({"status": "approved"}),
+        )
+        .authorize_as(&helper)
+        .header("If-Match", record.etag_string())
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+    let approved: FullRecord = serde_json::from_value(approved["data"].clone()).unwrap();
+
+    assert!(approved.approved_at.is_some());
+    assert!(approved.approved_at >= approved.submitted_at);
+
+    // The timeline is also part of the records listed on demons and players
+    let demon: serde_json::Value = clnt.get(format!("/api/v2/demons/{}/", demon1)).get_result().await;
+    let listed = &demon["data"]["records"][0];
+
+    assert_eq!(listed["achieved_on"], "2024-01-05");
+    assert_eq!(listed["approved_at"], serde_json::to_value(approved.approved_at).unwrap());
+
+    let player: FullPlayer = clnt.get(format!("/api/v1/players/{}/", player1.id)).get_success_result().await;
+
+    assert_eq!(player.records[0].achieved_on, record.achieved_on);
+    assert_eq!(player.records[0].approved_at, approved.approved_at);
+
+    // Moving a record out of 'approved' means it no longer has been approved
+    let record: FullRecord = clnt
+        .patch(
+            format!("/api/v1/records/{}/", record.id),
+            &serde_json::json!({"status": "under consideration"}),
+        )
+        .authorize_as(&helper)
+        .header("If-Match", approved.etag_string())
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(record.approved_at, None);
+    assert_eq!(record.submitted_at, approved.submitted_at);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_achieved_on_validation(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper = system_user_with_perms(LIST_HELPER, &mut connection).await;
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id, player1.id, &mut connection).await;
+
+    let future = "2999-01-01";
+    let submission = serde_json::json! {{"progress": 100, "demon": demon1, "player": "stardust1971", "video": "https://youtube.com/watch?v=1234567890", "raw_footage": "https://pointercrate.com", "achieved_on": future}};
+
+    let json: serde_json::Value = clnt
+        .post("/api/v1/records/", &submission)
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(json["code"].as_i64(), Some(42254));
+
+    let record = add_simple_record(100, player1.id, demon1, RecordStatus::Submitted, &mut connection).await;
+    let record: FullRecord = clnt
+        .get(format!("/api/v1/records/{}/", record))
+        .authorize_as(&helper)
+        .get_success_result()
+        .await;
+
+    let json: serde_json::Value = clnt
+        .patch(
+            format!("/api/v1/records/{}/", record.id),
+            &serde_json::json!({ "achieved_on": future }),
+        )
+        .authorize_as(&helper)
+        .header("If-Match", record.etag_string())
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(json["code"].as_i64(), Some(42254));
+
+    let record: FullRecord = clnt
+        .patch(
+            format!("/api/v1/records/{}/", record.id),
+            &serde_json::json!({"achieved_on": "2024-01-05"}),
+        )
+        .authorize_as(&helper)
+        .header("If-Match", record.etag_string())
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(record.achieved_on.map(|date| date.to_string()).as_deref(), Some("2024-01-05"));
+
+    let record: FullRecord = clnt
+        .patch(
+            format!("/api/v1/records/{}/", record.id),
+            &serde_json::json!({ "achieved_on": null }),
+        )
+        .authorize_as(&helper)
+        .header("If-Match", record.etag_string())
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(record.achieved_on, None);
+}
@@ -0,0 +1,457 @@
+use pointercrate_core::{error::PointercrateError, etag::Taggable};
+use pointercrate_demonlist::{
+    error::DemonlistError,
+    player::DatabasePlayer,
+    record::{FullRecord, RecordStatus},
+    submitter::Submitter,
+    LIST_HELPER,
+};
+use pointercrate_test::{demonlist::add_simple_record, user::system_user_with_perms, TestClient};
+use pointercrate_user::auth::{legacy::Registration, AuthenticatedUser, PasswordOrBrowser};
+use rocket::http::Status;
+use sqlx::{PgConnection, Pool, Postgres};
+use std::{net::IpAddr, str::FromStr};
+
+async fn add_second_helper(connection: &mut PgConnection) -> AuthenticatedUser<PasswordOrBrowser> {
+    let user = AuthenticatedUser::register(
+        Registration {
+            name: "Bob".to_string(),
+            password: "bad password".to_string(),
+        },
+        &mut *connection,
+    )
+    .await
+    .unwrap();
+
+    sqlx::query("UPDATE members SET permissions = $2::INTEGER::BIT(16) WHERE member_id = $1")
+        .bind(user.user().id)
+        .bind(LIST_HELPER.bit() as i16)
+        .execute(connection)
+        .await
+        .unwrap();
+
+    user
+}
+
+async fn set_status(clnt: &TestClient, record_id: i32, status: &str, user: &AuthenticatedUser<PasswordOrBrowser>, expected: Status) {
+    patch_record(clnt, record_id, serde_json::json!({ "status": status }), user, expected).await;
+}
+
+async fn patch_record(
+    clnt: &TestClient, record_id: i32, patch: serde_json::Value, user: &AuthenticatedUser<PasswordOrBrowser>, expected: Status,
+) -> serde_json::Value {
+    let record: FullRecord = clnt
+        .get(format!("/api/v1/records/{}/", record_id))
+        .authorize_as(user)
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    clnt.patch(format!("/api/v1/records/{}/", record_id), &patch)
+        .authorize_as(user)
+        .header("If-Match", record.etag_string())
+        .expect_status(expected)
+        .get_result()
+        .await
+}
+
+/// Sets up a submission for a demon at position 1, and one for a demon at position 2, returning
+/// their IDs in that order
+async fn setup_submissions(connection: &mut PgConnection) -> (i32, i32) {
+    let player = DatabasePlayer::by_name_or_create("stardust1971", connection).await.unwrap();
+
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player.id, player.id, connection).await;
+    let demon2 = pointercrate_test::demonlist::add_demon("Bloodlust", 2, 50, player.id, player.id, connection).await;
+
+    // Submit for the easier demon first, so that ordering by age and by position differ
+    let r2 = add_simple_record(100, player.id, demon2, RecordStatus::Submitted, connection).await;
+    let r1 = add_simple_record(100, player.id, demon1, RecordStatus::UnderConsideration, connection).await;
+
+    (r1, r2)
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_claim_conflicts(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let (record, _) = setup_submissions(&mut connection).await;
+    let patrick = system_user_with_perms(LIST_HELPER, &mut connection).await;
+    let bob = add_second_helper(&mut connection).await;
+
+    let claim: serde_json::Value = clnt
+        .put(format!("/api/v1/records/{}/claim/", record))
+        .authorize_as(&patrick)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(claim["claimed_by"]["name"], "Patrick");
+
+    let error: serde_json::Value = clnt
+        .put(format!("/api/v1/records/{}/claim/", record))
+        .authorize_as(&bob)
+        .expect_status(Status::Conflict)
+        .get_result()
+        .await;
+
+    assert_eq!(
+        error["code"],
+        DemonlistError::RecordClaimedByOther {
+            record_id: record,
+            claimed_by: "Patrick".to_string()
+        }
+        .error_code()
+    );
+
+    // Claiming again extends the claim, but keeps when it was made
+    let extended: serde_json::Value = clnt
+        .put(format!("/api/v1/records/{}/claim/", record))
+        .authorize_as(&patrick)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(extended["claimed_at"], claim["claimed_at"]);
+
+    set_status(&clnt, record, "rejected", &bob, Status::Conflict).await;
+
+    set_status(&clnt, record, "approved", &patrick, Status::Ok).await;
+
+    // Reviewing the record releases the claim
+    clnt.get(format!("/api/v1/records/{}/claim/", record))
+        .authorize_as(&bob)
+        .expect_status(Status::NotFound)
+        .execute()
+        .await;
+
+    let error: serde_json::Value = clnt
+        .put(format!("/api/v1/records/{}/claim/", record))
+        .authorize_as(&bob)
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(error["code"], DemonlistError::RecordAlreadyReviewed.error_code());
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_claim_release_and_expiry(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let (record, _) = setup_submissions(&mut connection).await;
+    let patrick = system_user_with_perms(LIST_HELPER, &mut connection).await;
+    let bob = add_second_helper(&mut connection).await;
+
+    clnt.put(format!("/api/v1/records/{}/claim/", record))
+        .authorize_as(&patrick)
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    // Only moderators can release other people's claims
+    clnt.delete(format!("/api/v1/records/{}/claim/", record))
+        .authorize_as(&bob)
+        .expect_status(Status::Forbidden)
+        .execute()
+        .await;
+
+    clnt.delete(format!("/api/v1/records/{}/claim/", record))
+        .authorize_as(&patrick)
+        .expect_status(Status::NoContent)
+        .execute()
+        .await;
+
+    clnt.delete(format!("/api/v1/records/{}/claim/", record))
+        .authorize_as(&patrick)
+        .expect_status(Status::NotFound)
+        .execute()
+        .await;
+
+    clnt.put(format!("/api/v1/records/{}/claim/", record))
+        .authorize_as(&patrick)
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    sqlx::query("UPDATE record_claims SET expires_at = (NOW() AT TIME ZONE 'utc') - INTERVAL '1 minute'")
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    let claim: serde_json::Value = clnt
+        .put(format!("/api/v1/records/{}/claim/", record))
+        .authorize_as(&bob)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(claim["claimed_by"]["name"], "Bob");
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_review_queue_requires_helper(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let (record, _) = setup_submissions(&mut connection).await;
+    let user = pointercrate_test::user::add_normal_user(&mut connection).await;
+
+    clnt.get("/api/v1/records/queue/")
+        .authorize_as(&user)
+        .expect_status(Status::Forbidden)
+        .execute()
+        .await;
+
+    clnt.put(format!("/api/v1/records/{}/claim/", record))
+        .authorize_as(&user)
+        .expect_status(Status::Forbidden)
+        .execute()
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_review_queue_order(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let (r1, r2) = setup_submissions(&mut connection).await;
+    let helper = system_user_with_perms(LIST_HELPER, &mut connection).await;
+
+    let queue_ids = |records: Vec<serde_json::Value>| {
+        records
+            .iter()
+            .map(|record| record["id"].as_i64().unwrap() as i32)
+            .collect::<Vec<_>>()
+    };
+
+    let (records, _) = clnt
+        .get("/api/v1/records/queue/")
+        .authorize_as(&helper)
+        .get_pagination_result::<serde_json::Value>()
+        .await;
+
+    assert_eq!(queue_ids(records), [r2, r1]);
+
+    let (records, _) = clnt
+        .get("/api/v1/records/queue/?sort=position")
+        .authorize_as(&helper)
+        .get_pagination_result::<serde_json::Value>()
+        .await;
+
+    assert_eq!(queue_ids(records), [r1, r2]);
+
+    // Move the submission for the easier demon to a submitter who has had a record rejected before
+    let untrusted = Submitter::create_submitter(IpAddr::from_str("127.0.0.2").unwrap(), &mut connection)
+        .await
+        .unwrap();
+    let player = DatabasePlayer::by_name_or_create("stardust1972", &mut connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodborne", 3, 50, player.id, player.id, &mut connection).await;
+    let rejected = add_simple_record(100, player.id, demon, RecordStatus::Rejected, &mut connection).await;
+
+    sqlx::query("UPDATE records SET submitter = $1 WHERE id = $2 OR id = $3")
+        .bind(untrusted.id)
+        .bind(r2)
+        .bind(rejected)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    let (records, _) = clnt
+        .get("/api/v1/records/queue/?sort=trust")
+        .authorize_as(&helper)
+        .get_pagination_result::<serde_json::Value>()
+        .await;
+
+    assert_eq!(records[0]["id"].as_i64(), Some(r2 as i64));
+    assert!(records[0]["trust"].as_f64() < records[1]["trust"].as_f64());
+
+    // Reviewed records are not in the queue
+    assert_eq!(records.len(), 2);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_review_queue_claims(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let (r1, r2) = setup_submissions(&mut connection).await;
+    let patrick = system_user_with_perms(LIST_HELPER, &mut connection).await;
+    let bob = add_second_helper(&mut connection).await;
+
+    clnt.put(format!("/api/v1/records/{}/claim/", r1))
+        .authorize_as(&patrick)
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    let (records, _) = clnt
+        .get("/api/v1/records/queue/?claimed=false")
+        .authorize_as(&bob)
+        .get_pagination_result::<serde_json::Value>()
+        .await;
+
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0]["id"].as_i64(), Some(r2 as i64));
+    assert!(records[0]["claim"].is_null());
+
+    let (records, _) = clnt
+        .get(format!("/api/v1/records/queue/?claimed_by={}", patrick.user().id))
+        .authorize_as(&bob)
+        .get_pagination_result::<serde_json::Value>()
+        .await;
+
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0]["claim"]["claimed_by"]["name"], "Patrick");
+
+    set_status(&clnt, r2, "rejected", &bob, Status::Ok).await;
+
+    let stats: Vec<serde_json::Value> = clnt
+        .get("/api/v1/records/queue/reviewers/")
+        .authorize_as(&bob)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(stats.len(), 2);
+    assert_eq!(stats[0]["reviewer"]["name"], "Bob");
+    assert_eq!(stats[0]["reviewed"], 1);
+    assert_eq!(stats[0]["claimed"], 0);
+    assert_eq!(stats[1]["reviewer"]["name"], "Patrick");
+    assert_eq!(stats[1]["reviewed"], 0);
+    assert_eq!(stats[1]["claimed"], 1);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_rejection_reasons(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let (record, _) = setup_submissions(&mut connection).await;
+    let helper = system_user_with_perms(LIST_HELPER, &mut connection).await;
+
+    let error = patch_record(
+        &clnt,
+        record,
+        serde_json::json!({"status": "rejected", "rejection_reason": "too-good"}),
+        &helper,
+        Status::UnprocessableEntity,
+    )
+    .await;
+
+    assert_eq!(
+        error["code"],
+        DemonlistError::UnknownRejectionReason {
+            reason: "too-good".to_string()
+        }
+        .error_code()
+    );
+
+    let error = patch_record(
+        &clnt,
+        record,
+        serde_json::json!({"rejection_reason": "no-clicks"}),
+        &helper,
+        Status::UnprocessableEntity,
+    )
+    .await;
+
+    assert_eq!(error["code"], DemonlistError::RecordNotRejected.error_code());
+
+    let rejected = patch_record(
+        &clnt,
+        record,
+        serde_json::json!({"status": "rejected", "rejection_reason": "no-clicks"}),
+        &helper,
+        Status::Ok,
+    )
+    .await;
+
+    assert_eq!(rejected["data"]["rejection_reason"], "no-clicks");
+
+    // Un-rejecting a record discards the reason
+    let approved = patch_record(&clnt, record, serde_json::json!({"status": "approved"}), &helper, Status::Ok).await;
+
+    assert!(approved["data"]["rejection_reason"].is_null());
+
+    let reasons: Vec<serde_json::Value> = clnt
+        .get("/api/v1/records/rejection-reasons/")
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert!(reasons.iter().any(|reason| reason["key"] == "no-clicks"));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_rejection_reason_visible_to_claimant(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let (record, _) = setup_submissions(&mut connection).await;
+    let helper = add_second_helper(&mut connection).await;
+    let claimant = pointercrate_test::user::add_normal_user(&mut connection).await;
+    let player = DatabasePlayer::by_name("stardust1971", &mut connection).await.unwrap();
+
+    patch_record(
+        &clnt,
+        record,
+        serde_json::json!({"status": "rejected", "rejection_reason": "cheat-indicator"}),
+        &helper,
+        Status::Ok,
+    )
+    .await;
+
+    clnt.get(format!("/api/v1/records/{}/", record))
+        .authorize_as(&claimant)
+        .expect_status(Status::NotFound)
+        .execute()
+        .await;
+
+    pointercrate_test::demonlist::put_claim(claimant.user().id, player.id, true, false, &mut connection).await;
+
+    let seen: FullRecord = clnt
+        .get(format!("/api/v1/records/{}/", record))
+        .authorize_as(&claimant)
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(seen.rejection_reason.as_deref(), Some("cheat-indicator"));
+    assert!(seen.submitter.is_none());
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_rejection_reason_stats(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let (r1, r2) = setup_submissions(&mut connection).await;
+    let helper = add_second_helper(&mut connection).await;
+
+    patch_record(
+        &clnt,
+        r1,
+        serde_json::json!({"status": "rejected", "rejection_reason": "duplicate"}),
+        &helper,
+        Status::Ok,
+    )
+    .await;
+    set_status(&clnt, r2, "rejected", &helper, Status::Ok).await;
+
+    let stats: Vec<serde_json::Value> = clnt
+        .get("/api/v1/records/rejection-reasons/stats/")
+        .authorize_as(&helper)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(
+        stats,
+        [
+            serde_json::json!({"reason": "duplicate", "count": 1}),
+            serde_json::json!({"reason": null, "count": 1})
+        ]
+    );
+
+    let user = pointercrate_test::user::add_normal_user(&mut connection).await;
+
+    clnt.get("/api/v1/records/rejection-reasons/stats/")
+        .authorize_as(&user)
+        .expect_status(Status::Forbidden)
+        .execute()
+        .await;
+}
@@ -0,0 +1,169 @@
+use pointercrate_core::etag::Taggable;
+use pointercrate_demonlist::LIST_ADMINISTRATOR;
+use rocket::{
+    http::Status,
+    tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+        time::{sleep, timeout, Duration},
+    },
+};
+use serde_json::json;
+use sqlx::{Pool, Postgres};
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_manage_webhook_targets(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+    let user = pointercrate_test::user::system_user_with_perms(LIST_ADMINISTRATOR, &mut connection).await;
+
+    let result: serde_json::Value = clnt
+        .post(
+            "/api/v1/webhooks/",
+            &json!({"url": "ftp://example.com", "events": ["record_submitted"]}),
+        )
+        .authorize_as(&user)
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(result["code"], 42236);
+
+    let created: serde_json::Value = clnt
+        .post(
+            "/api/v1/webhooks/",
+            &json!({"url": "https://example.com/hook", "format": "discord", "events": ["record_submitted", "demon_added"]}),
+        )
+        .authorize_as(&user)
+        .expect_status(Status::Created)
+        .get_result()
+        .await;
+
+    let id = created["id"].as_i64().unwrap();
+
+    assert_eq!(created["format"], "discord");
+    assert!(!created["secret"].as_str().unwrap().is_empty());
+
+    // The secret is only revealed upon creation
+    let target: serde_json::Value = clnt
+        .get(format!("/api/v1/webhooks/{}/", id))
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert!(target.get("secret").is_none());
+
+    let response = clnt
+        .get(format!("/api/v1/webhooks/{}/", id))
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+    let etag = response.headers().get_one("ETag").unwrap().to_string();
+
+    let patched: serde_json::Value = clnt
+        .patch(format!("/api/v1/webhooks/{}/", id), &json!({"events": ["player_banned"]}))
+        .authorize_as(&user)
+        .header("If-Match", etag)
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(patched["events"], json!(["player_banned"]));
+
+    let targets: Vec<serde_json::Value> = clnt
+        .get("/api/v1/webhooks/")
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(targets.len(), 1);
+
+    let target = pointercrate_demonlist::webhook::WebhookTarget::by_id(id as i32, &mut connection)
+        .await
+        .unwrap();
+
+    clnt.delete(format!("/api/v1/webhooks/{}/", id))
+        .authorize_as(&user)
+        .header("If-Match", target.etag_string())
+        .expect_status(Status::NoContent)
+        .execute()
+        .await;
+
+    clnt.get(format!("/api/v1/webhooks/{}/", id))
+        .authorize_as(&user)
+        .expect_status(Status::NotFound)
+        .execute()
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_webhook_delivery(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+    let user = pointercrate_test::user::system_user_with_perms(LIST_ADMINISTRATOR, &mut connection).await;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+
+    let created: serde_json::Value = clnt
+        .post(
+            "/api/v1/webhooks/",
+            &json!({"url": url, "events": ["demon_added"], "secret": "hunter2"}),
+        )
+        .authorize_as(&user)
+        .expect_status(Status::Created)
+        .get_result()
+        .await;
+
+    clnt.add_demon(&user, "Bloodbath", 1, 87, "Riot", "Riot").await;
+
+    let request = timeout(Duration::from_secs(10), async {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        let mut buffer = [0u8; 4096];
+
+        // Good enough for a test: read until the JSON body is complete
+        while !request.ends_with(b"}") {
+            let read = stream.read(&mut buffer).await.unwrap();
+            assert_ne!(read, 0, "connection closed before request was complete");
+            request.extend_from_slice(&buffer[..read]);
+        }
+
+        stream.write_all(b"HTTP/1.1 204 No Content\r\n\r\n").await.unwrap();
+
+        String::from_utf8(request).unwrap()
+    })
+    .await
+    .expect("webhook was not delivered");
+
+    let request = request.to_lowercase();
+
+    assert!(request.starts_with("post /hook"));
+    assert!(request.contains("x-pointercrate-event: demon_added"));
+    assert!(request.contains("x-pointercrate-signature: sha256="));
+    assert!(request.contains(r#""event":"demon_added""#));
+
+    // The delivery is logged asynchronously after the response was received
+    let mut deliveries = Vec::new();
+
+    for _ in 0..50 {
+        deliveries = clnt
+            .get(format!("/api/v1/webhooks/{}/deliveries/", created["id"]))
+            .authorize_as(&user)
+            .expect_status(Status::Ok)
+            .get_result::<Vec<serde_json::Value>>()
+            .await;
+
+        if !deliveries.is_empty() {
+            break;
+        }
+
+        sleep(Duration::from_millis(100)).await;
+    }
+
+    assert_eq!(deliveries.len(), 1);
+    assert_eq!(deliveries[0]["attempt"], 1);
+    assert_eq!(deliveries[0]["status_code"], 204);
+    assert_eq!(deliveries[0]["error"], serde_json::Value::Null);
+}
@@ -1,2 +1,6 @@
+mod cache;
 mod demonlist;
+mod pagination;
+mod pool;
+mod ratelimits;
 mod user;
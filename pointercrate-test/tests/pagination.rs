@@ -0,0 +1,176 @@
+//! Runs the generic pagination invariants from `pointercrate-core-api` against every
+//! [`Paginatable`](pointercrate_core::pagination::Paginatable) implementation
+
+use pointercrate_core::job::{JobPagination, QueuedJob};
+use pointercrate_core_api::pagination::testing::{assert_pagination_invariants, assert_sorted_pagination_invariants};
+use pointercrate_demonlist::{
+    demon::{Demon, DemonIdPagination, DemonPositionPagination},
+    list::DEFAULT_LIST,
+    nationality::{Nationality, NationalityRankingPagination, RankedNation},
+    player::{
+        claim::{ListedClaim, PlayerClaimPagination},
+        recompute_scores, DatabasePlayer, Player, PlayerPagination, RankedPlayer, RankingPagination,
+    },
+    record::{MinimalRecordPD, RecordPagination, RecordStatus},
+    review::{QueuedRecord, ReviewQueuePagination},
+    submitter::{Submitter, SubmitterPagination},
+    webhook::{WebhookDelivery, WebhookDeliveryPagination},
+};
+use pointercrate_user::{
+    auth::{legacy::Registration, AuthenticatedUser},
+    User, UserPagination,
+};
+use serde::de::DeserializeOwned;
+use sqlx::{PgConnection, Pool, Postgres};
+use std::{net::IpAddr, str::FromStr};
+
+fn query<Q: DeserializeOwned>(query_string: &str) -> Q {
+    serde_urlencoded::from_str(query_string).unwrap()
+}
+
+async fn register(names: [&str; 3], connection: &mut PgConnection) -> Vec<i32> {
+    let mut ids = Vec::new();
+
+    for name in names {
+        let user = AuthenticatedUser::register(
+            Registration {
+                name: name.to_string(),
+                password: "bad password".to_string(),
+            },
+            &mut *connection,
+        )
+        .await
+        .unwrap();
+
+        ids.push(user.user().id);
+    }
+
+    ids
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_demonlist_pagination_invariants(pool: Pool<Postgres>) {
+    let (_, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let mut players = Vec::new();
+
+    for (name, iso_country_code, nation) in [
+        ("stardust1971", "DE", "Germany"),
+        ("Bob", "FR", "France"),
+        ("aeon", "GB", "United Kingdom"),
+    ] {
+        let player = DatabasePlayer::by_name_or_create(name, &mut connection).await.unwrap();
+        let mut player = Player::by_id(player.id, &mut connection).await.unwrap();
+
+        player
+            .set_nationality(
+                Some(Nationality {
+                    iso_country_code: iso_country_code.into(),
+                    nation: nation.into(),
+                    subdivision: None,
+                }),
+                &mut connection,
+            )
+            .await
+            .unwrap();
+
+        players.push(player.base.id);
+    }
+
+    let mut demons = Vec::new();
+
+    for (position, name) in ["Bloodbath", "Bloodlust", "Sonic Wave"].into_iter().enumerate() {
+        demons.push(pointercrate_test::demonlist::add_demon(name, position as i16 + 1, 50, players[0], players[0], &mut connection).await);
+    }
+
+    for (&player, progress) in players.iter().zip([100, 70, 100]) {
+        for &demon in &demons {
+            pointercrate_test::demonlist::add_simple_record(progress, player, demon, RecordStatus::Approved, &mut connection).await;
+        }
+    }
+
+    for (&player, &demon) in players.iter().zip(&demons) {
+        pointercrate_test::demonlist::add_simple_record(90, player, demon, RecordStatus::Submitted, &mut connection).await;
+    }
+
+    // The rankings only contain players and nations with cached scores
+    recompute_scores(&mut connection).await.unwrap();
+
+    for (user, &player) in register(["Carol", "Dave", "Erin"], &mut connection).await.into_iter().zip(&players) {
+        pointercrate_test::demonlist::put_claim(user, player, false, false, &mut connection).await;
+    }
+
+    for ip in ["127.0.0.2", "127.0.0.3"] {
+        Submitter::create_submitter(IpAddr::from_str(ip).unwrap(), &mut connection)
+            .await
+            .unwrap();
+    }
+
+    let targets: Vec<i32> =
+        sqlx::query_scalar("INSERT INTO webhook_targets (url, events, secret) VALUES ('https://example.com', '{}', ''), ('https://example.org', '{}', '') RETURNING id")
+            .fetch_all(&mut *connection)
+            .await
+            .unwrap();
+
+    for target in [targets[0], targets[1], targets[0], targets[0]] {
+        sqlx::query("INSERT INTO webhook_deliveries (target, event, attempt, payload) VALUES ($1, 'RECORD_SUBMITTED', 1, '{}')")
+            .bind(target)
+            .execute(&mut *connection)
+            .await
+            .unwrap();
+    }
+
+    // Scheduled in the future, so that they are not picked up while paginating
+    for kind in ["first", "second", "third"] {
+        sqlx::query("INSERT INTO jobs (kind, payload, max_attempts, scheduled_at) VALUES ($1, '{}', 1, NOW() + INTERVAL '1 day')")
+            .bind(kind)
+            .execute(&mut *connection)
+            .await
+            .unwrap();
+    }
+
+    // The list is determined by where the demon endpoints are mounted
+    let mut by_id: DemonIdPagination = query("");
+    let mut by_position: DemonPositionPagination = query("");
+
+    by_id.list = Some(DEFAULT_LIST);
+    by_position.list = Some(DEFAULT_LIST);
+
+    assert_pagination_invariants::<_, Demon>(by_id, &mut connection).await;
+    assert_pagination_invariants::<_, Demon>(by_position, &mut connection).await;
+
+    for query_string in ["", "sort=name", "sort=score&order=desc"] {
+        assert_sorted_pagination_invariants::<PlayerPagination, Player>(query(query_string), &mut connection).await;
+    }
+
+    for query_string in ["", "sort=progress", "sort=progress&order=desc&demon_position__gt=1"] {
+        assert_sorted_pagination_invariants::<RecordPagination, MinimalRecordPD>(query(query_string), &mut connection).await;
+    }
+
+    for query_string in ["", "sort=position&order=desc", "sort=trust"] {
+        assert_sorted_pagination_invariants::<ReviewQueuePagination, QueuedRecord>(query(query_string), &mut connection).await;
+    }
+
+    assert_pagination_invariants::<RankingPagination, RankedPlayer>(query(""), &mut connection).await;
+    assert_pagination_invariants::<NationalityRankingPagination, RankedNation>(query(""), &mut connection).await;
+    assert_pagination_invariants::<PlayerClaimPagination, ListedClaim>(query(""), &mut connection).await;
+    assert_pagination_invariants::<SubmitterPagination, Submitter>(query(""), &mut connection).await;
+    assert_pagination_invariants::<JobPagination, QueuedJob>(query(""), &mut connection).await;
+
+    let mut deliveries: WebhookDeliveryPagination = query("");
+
+    deliveries.target = targets[0];
+
+    assert_pagination_invariants::<_, WebhookDelivery>(deliveries, &mut connection).await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_user_pagination_invariants(pool: Pool<Postgres>) {
+    let (_, mut connection) = pointercrate_test::user::setup_rocket(pool).await;
+
+    register(["Bob", "alice", "Carol"], &mut connection).await;
+
+    for query_string in ["", "sort=name", "sort=name&order=desc"] {
+        assert_sorted_pagination_invariants::<UserPagination, User>(query(query_string), &mut connection).await;
+    }
+}
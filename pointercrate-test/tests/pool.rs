@@ -0,0 +1,45 @@
+use pointercrate_core::pool::PointercratePool;
+use sqlx::{postgres::PgPoolOptions, PgConnection, Pool, Postgres};
+use std::time::Duration;
+
+async fn current_database(connection: &mut PgConnection) -> String {
+    sqlx::query_scalar!(r#"SELECT current_database() AS "name!""#)
+        .fetch_one(connection)
+        .await
+        .unwrap()
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_read_connection_uses_replica(pool: Pool<Postgres>) {
+    // sqlx::test gives us a freshly created database, so we can use the database from DATABASE_URL
+    // as a stand-in for a replica and tell the two apart by name
+    let replica = PgPoolOptions::new().connect_lazy(&std::env::var("DATABASE_URL").unwrap()).unwrap();
+    let replica_database = current_database(&mut replica.acquire().await.unwrap()).await;
+
+    let pool = PointercratePool::from(pool).with_replica(replica);
+
+    let primary_database = current_database(&mut pool.connection().await.unwrap()).await;
+
+    assert_ne!(primary_database, replica_database);
+    assert_eq!(current_database(&mut pool.read_connection().await.unwrap()).await, replica_database);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_read_connection_falls_back_to_primary(pool: Pool<Postgres>) {
+    let replica = PgPoolOptions::new()
+        .acquire_timeout(Duration::from_secs(1))
+        .connect_lazy("postgresql://pointercrate@localhost:1/pointercrate")
+        .unwrap();
+
+    let pool = PointercratePool::from(pool).with_replica(replica);
+
+    let primary_database = current_database(&mut pool.connection().await.unwrap()).await;
+
+    assert_eq!(current_database(&mut pool.read_connection().await.unwrap()).await, primary_database);
+
+    // The replica is now considered unhealthy, so we do not even try it again
+    let start = std::time::Instant::now();
+
+    assert_eq!(current_database(&mut pool.read_connection().await.unwrap()).await, primary_database);
+    assert!(start.elapsed() < Duration::from_secs(1));
+}
@@ -0,0 +1,25 @@
+use pointercrate_core::ratelimits::{PostgresStore, Quota, RatelimitStore};
+use sqlx::{Pool, Postgres};
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_postgres_ratelimit_store(pool: Pool<Postgres>) {
+    let store = PostgresStore::new(pool.clone());
+    let quota = Quota::new(2, 60);
+
+    assert!(store.check("test", "a".to_string(), quota).await.is_ok());
+    assert!(store.check("test", "a".to_string(), quota).await.is_ok());
+
+    let retry_after = store.check("test", "a".to_string(), quota).await.unwrap_err();
+
+    assert!(retry_after.as_secs() <= 30 && retry_after.as_secs() >= 29);
+
+    // Different keys are limited separately
+    assert!(store.check("test", "b".to_string(), quota).await.is_ok());
+
+    // The limits persist across stores, e.g. after a restart or on a different instance
+    let store = PostgresStore::new(pool);
+
+    assert!(store.check("test", "a".to_string(), quota).await.is_err());
+    assert!(store.check("test", "b".to_string(), quota).await.is_ok());
+    assert!(store.check("test", "b".to_string(), quota).await.is_err());
+}
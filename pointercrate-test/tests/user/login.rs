@@ -116,10 +116,13 @@ pub async fn test_no_login_if_google_account_linked(pool: Pool<Postgres>) {
         .execute()
         .await;
 
-    sqlx::query!("UPDATE members SET google_account_id='1' WHERE member_id=$1", user.user().id)
-        .execute(&mut *connection)
-        .await
-        .unwrap();
+    sqlx::query!(
+        "INSERT INTO oauth_identities (member_id, provider, subject) VALUES ($1, 'google', '1')",
+        user.user().id
+    )
+    .execute(&mut *connection)
+    .await
+    .unwrap();
 
     client
         .post("/api/v1/auth/", &())
@@ -0,0 +1,257 @@
+use pointercrate_user::{
+    auth::{legacy::Registration, AuthenticatedUser},
+    ADMINISTRATOR,
+};
+use rocket::http::Status;
+use sqlx::{Pool, Postgres};
+
+const ADMIN_BASIC_AUTH: &str = "Basic UGF0cmljazpiYWQgcGFzc3dvcmQ=";
+const BOB_BASIC_AUTH: &str = "Basic Qm9iOmJhZCBwYXNzd29yZA==";
+const ADMIN_WRONG_PASSWORD_BASIC_AUTH: &str = "Basic UGF0cmljazp3cm9uZyBwYXNzd29yZA==";
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_maintenance_settings_require_administrator(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::user::setup_rocket(pool).await;
+
+    let user = pointercrate_test::user::add_normal_user(&mut connection).await;
+
+    client
+        .get("/api/v1/maintenance/")
+        .authorize_as(&user)
+        .expect_status(Status::Forbidden)
+        .execute()
+        .await;
+
+    client
+        .patch("/api/v1/maintenance/", &serde_json::json!({"enabled": true}))
+        .authorize_as(&user)
+        .expect_status(Status::Forbidden)
+        .execute()
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_toggle_maintenance(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::user::setup_rocket(pool).await;
+
+    let admin = pointercrate_test::user::system_user_with_perms(ADMINISTRATOR, &mut connection).await;
+
+    let status: serde_json::Value = client
+        .get("/api/v1/maintenance/")
+        .authorize_as(&admin)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(status["active"], false);
+
+    let status: serde_json::Value = client
+        .patch(
+            "/api/v1/maintenance/",
+            &serde_json::json!({"enabled": true, "messages": {"en": "Back soon!", "ru": ""}}),
+        )
+        .authorize_as(&admin)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(status["active"], true);
+    assert_eq!(status["messages"], serde_json::json!({"en": "Back soon!"}));
+
+    let response: serde_json::Value = client
+        .post("/api/v1/auth/", &())
+        .header("Authorization", ADMIN_BASIC_AUTH)
+        .header("X-Real-IP", "127.0.0.1")
+        .expect_status(Status::ServiceUnavailable)
+        .get_result()
+        .await;
+
+    assert_eq!(response["code"], 50301);
+
+    // Administrators can always switch maintenance mode off again
+    client
+        .patch("/api/v1/maintenance/", &serde_json::json!({"enabled": false}))
+        .authorize_as(&admin)
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    client
+        .post("/api/v1/auth/", &())
+        .header("Authorization", ADMIN_BASIC_AUTH)
+        .header("X-Real-IP", "127.0.0.1")
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_maintenance_allowlist(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::user::setup_rocket(pool).await;
+
+    let admin = pointercrate_test::user::system_user_with_perms(ADMINISTRATOR, &mut connection).await;
+
+    AuthenticatedUser::register(
+        Registration {
+            name: "Bob".to_string(),
+            password: "bad password".to_string(),
+        },
+        &mut connection,
+    )
+    .await
+    .unwrap();
+
+    client
+        .patch(
+            "/api/v1/maintenance/",
+            &serde_json::json!({"enabled": true, "allowlist": ["/api/v1/auth/"]}),
+        )
+        .authorize_as(&admin)
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    client
+        .post("/api/v1/auth/", &())
+        .header("Authorization", ADMIN_BASIC_AUTH)
+        .header("X-Real-IP", "127.0.0.1")
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    // Only administrators are exempt
+    client
+        .post("/api/v1/auth/", &())
+        .header("Authorization", BOB_BASIC_AUTH)
+        .header("X-Real-IP", "127.0.0.1")
+        .expect_status(Status::ServiceUnavailable)
+        .execute()
+        .await;
+
+    // Exemptions are only granted for valid credentials
+    client
+        .post("/api/v1/auth/", &())
+        .header("Authorization", ADMIN_WRONG_PASSWORD_BASIC_AUTH)
+        .header("X-Real-IP", "127.0.0.1")
+        .expect_status(Status::ServiceUnavailable)
+        .execute()
+        .await;
+
+    // Routes not on the allowlist are read-only for administrators too
+    client
+        .post("/api/v1/auth/invalidate/", &())
+        .header("Authorization", ADMIN_BASIC_AUTH)
+        .expect_status(Status::ServiceUnavailable)
+        .execute()
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_maintenance_exemption_on_anonymous_route(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::user::setup_rocket(pool).await;
+
+    let admin = pointercrate_test::user::system_user_with_perms(ADMINISTRATOR, &mut connection).await;
+
+    client
+        .patch(
+            "/api/v1/maintenance/",
+            &serde_json::json!({"enabled": true, "allowlist": ["/api/v1/auth/register/"]}),
+        )
+        .authorize_as(&admin)
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    // The endpoint does not look at the credentials at all, so the exemption must not rely on it
+    // rejecting invalid ones
+    client
+        .post(
+            "/api/v1/auth/register/",
+            &Registration {
+                name: "Bob".to_string(),
+                password: "bad password".to_string(),
+            },
+        )
+        .header("Authorization", ADMIN_WRONG_PASSWORD_BASIC_AUTH)
+        .header("X-Real-IP", "127.0.0.1")
+        .expect_status(Status::ServiceUnavailable)
+        .execute()
+        .await;
+
+    client
+        .post(
+            "/api/v1/auth/register/",
+            &Registration {
+                name: "Bob".to_string(),
+                password: "bad password".to_string(),
+            },
+        )
+        .header("Authorization", ADMIN_BASIC_AUTH)
+        .header("X-Real-IP", "127.0.0.1")
+        .expect_status(Status::Created)
+        .execute()
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_scheduled_maintenance(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::user::setup_rocket(pool).await;
+
+    let admin = pointercrate_test::user::system_user_with_perms(ADMINISTRATOR, &mut connection).await;
+
+    for (body, code) in [
+        (
+            serde_json::json!({"starts_at": "2030-01-01T12:00:00", "ends_at": "2030-01-01T10:00:00"}),
+            42242,
+        ),
+        (serde_json::json!({"allowlist": ["login"]}), 42243),
+    ] {
+        let response: serde_json::Value = client
+            .patch("/api/v1/maintenance/", &body)
+            .authorize_as(&admin)
+            .expect_status(Status::UnprocessableEntity)
+            .get_result()
+            .await;
+
+        assert_eq!(response["code"], code);
+    }
+
+    let status: serde_json::Value = client
+        .patch(
+            "/api/v1/maintenance/",
+            &serde_json::json!({"starts_at": "2999-01-01T10:00:00", "ends_at": "2999-01-01T12:00:00"}),
+        )
+        .authorize_as(&admin)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(status["active"], false);
+
+    let status: serde_json::Value = client
+        .patch("/api/v1/maintenance/", &serde_json::json!({"starts_at": "2000-01-01T10:00:00"}))
+        .authorize_as(&admin)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(status["active"], true);
+
+    client
+        .post("/api/v1/auth/", &())
+        .header("Authorization", ADMIN_BASIC_AUTH)
+        .header("X-Real-IP", "127.0.0.1")
+        .expect_status(Status::ServiceUnavailable)
+        .execute()
+        .await;
+
+    // Windows that are over no longer have any effect
+    let status: serde_json::Value = client
+        .patch("/api/v1/maintenance/", &serde_json::json!({"ends_at": "2000-01-01T12:00:00"}))
+        .authorize_as(&admin)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(status["active"], false);
+}
@@ -1,2 +1,7 @@
 mod login;
+mod maintenance;
+mod metrics;
+mod oauth;
 mod register;
+mod token;
+mod totp;
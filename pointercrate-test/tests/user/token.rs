@@ -0,0 +1,227 @@
+use rocket::http::Status;
+use sqlx::{Pool, Postgres};
+
+const BASIC_AUTH: &str = "Basic UGF0cmljazpiYWQgcGFzc3dvcmQ=";
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_create_scoped_token(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::user::setup_rocket(pool.clone()).await;
+
+    let user = pointercrate_test::user::add_normal_user(&mut connection).await;
+
+    for (body, code) in [
+        (serde_json::json!({"name": "bot", "scopes": ["auth:fly"]}), 42240),
+        (serde_json::json!({"name": "bot", "scopes": []}), 42240),
+        (serde_json::json!({"name": " bot", "scopes": ["auth:read"]}), 42239),
+        (serde_json::json!({"name": "bot", "scopes": ["auth:read"], "lifetime": 0}), 42241),
+    ] {
+        let response: serde_json::Value = client
+            .post("/api/v1/auth/tokens/", &body)
+            .header("Authorization", BASIC_AUTH)
+            .expect_status(Status::UnprocessableEntity)
+            .get_result()
+            .await;
+
+        assert_eq!(response["code"], code);
+    }
+
+    // Managing tokens requires password (or browser) authentication
+    client
+        .post("/api/v1/auth/tokens/", &serde_json::json!({"name": "bot", "scopes": ["auth:read"]}))
+        .authorize_as(&user)
+        .expect_status(Status::Unauthorized)
+        .execute()
+        .await;
+
+    let response: serde_json::Value = client
+        .post("/api/v1/auth/tokens/", &serde_json::json!({"name": "bot", "scopes": ["auth:read"]}))
+        .header("Authorization", BASIC_AUTH)
+        .expect_status(Status::Created)
+        .get_result()
+        .await;
+
+    assert_eq!(response["data"]["name"], "bot");
+    assert_eq!(response["data"]["scopes"], serde_json::json!(["auth:read"]));
+    assert!(response["data"]["last_used_at"].is_null());
+    assert!(response["token"].is_string());
+
+    let response: serde_json::Value = client
+        .post(
+            "/api/v1/auth/tokens/",
+            &serde_json::json!({"name": "bot", "scopes": ["records:create"]}),
+        )
+        .header("Authorization", BASIC_AUTH)
+        .expect_status(Status::Conflict)
+        .get_result()
+        .await;
+
+    assert_eq!(response["code"], 40910);
+
+    let tokens: Vec<serde_json::Value> = client.get("/api/v1/auth/tokens/").authorize_as(&user).get_result().await;
+
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(tokens[0]["name"], "bot");
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_scoped_token_usage(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::user::setup_rocket(pool.clone()).await;
+
+    let user = pointercrate_test::user::add_normal_user(&mut connection).await;
+    let user_id = user.user().id;
+
+    let response: serde_json::Value = client
+        .post("/api/v1/auth/tokens/", &serde_json::json!({"name": "bot", "scopes": ["auth:read"]}))
+        .header("Authorization", BASIC_AUTH)
+        .expect_status(Status::Created)
+        .get_result()
+        .await;
+
+    let token_id = response["data"]["id"].as_i64().unwrap();
+    let bearer = format!("Bearer {}", response["token"].as_str().unwrap());
+
+    client
+        .get("/api/v1/auth/me/")
+        .header("Authorization", bearer.clone())
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    let response: serde_json::Value = client
+        .get(format!("/api/v1/users/{}/", user_id))
+        .header("Authorization", bearer.clone())
+        .expect_status(Status::Forbidden)
+        .get_result()
+        .await;
+
+    assert_eq!(response["code"], 40310);
+    assert_eq!(response["data"]["required"], "users:read");
+
+    let last_used_at = sqlx::query_scalar!("SELECT last_used_at FROM api_tokens WHERE id = $1", token_id as i32)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+    assert!(last_used_at.is_some());
+
+    // Extending the scopes applies to existing access tokens
+    let etag = client
+        .get(format!("/api/v1/auth/tokens/{}/", token_id))
+        .authorize_as(&user)
+        .execute()
+        .await
+        .headers()
+        .get_one("etag")
+        .unwrap()
+        .to_string();
+
+    let response: serde_json::Value = client
+        .patch(
+            format!("/api/v1/auth/tokens/{}/", token_id),
+            &serde_json::json!({"scopes": ["auth:read", "users:read"]}),
+        )
+        .header("Authorization", BASIC_AUTH)
+        .header("If-Match", etag)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(response["data"]["scopes"], serde_json::json!(["auth:read", "users:read"]));
+
+    // Now the request fails for lack of permissions instead
+    let response: serde_json::Value = client
+        .get(format!("/api/v1/users/{}/", user_id))
+        .header("Authorization", bearer.clone())
+        .expect_status(Status::NotFound)
+        .get_result()
+        .await;
+
+    assert_eq!(response["code"], 40401);
+
+    // Expired tokens cannot be used anymore
+    sqlx::query!(
+        "UPDATE api_tokens SET expires_at = (NOW() AT TIME ZONE 'utc') - INTERVAL '1 minute' WHERE id = $1",
+        token_id as i32
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    client
+        .get("/api/v1/auth/me/")
+        .header("Authorization", bearer)
+        .expect_status(Status::Unauthorized)
+        .execute()
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_revoke_scoped_token(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::user::setup_rocket(pool).await;
+
+    let user = pointercrate_test::user::add_normal_user(&mut connection).await;
+
+    let mut tokens = Vec::new();
+
+    for name in ["first", "second"] {
+        let response: serde_json::Value = client
+            .post("/api/v1/auth/tokens/", &serde_json::json!({"name": name, "scopes": ["*:read"]}))
+            .header("Authorization", BASIC_AUTH)
+            .expect_status(Status::Created)
+            .get_result()
+            .await;
+
+        tokens.push((
+            response["data"]["id"].as_i64().unwrap(),
+            format!("Bearer {}", response["token"].as_str().unwrap()),
+        ));
+    }
+
+    let etag = client
+        .get(format!("/api/v1/auth/tokens/{}/", tokens[0].0))
+        .authorize_as(&user)
+        .execute()
+        .await
+        .headers()
+        .get_one("etag")
+        .unwrap()
+        .to_string();
+
+    client
+        .delete(format!("/api/v1/auth/tokens/{}/", tokens[0].0))
+        .header("Authorization", BASIC_AUTH)
+        .header("If-Match", etag)
+        .expect_status(Status::NoContent)
+        .execute()
+        .await;
+
+    // Only the deleted token is revoked, and the account's other sessions remain valid
+    client
+        .get("/api/v1/auth/me/")
+        .header("Authorization", tokens[0].1.clone())
+        .expect_status(Status::Unauthorized)
+        .execute()
+        .await;
+
+    client
+        .get("/api/v1/auth/me/")
+        .header("Authorization", tokens[1].1.clone())
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    client
+        .get("/api/v1/auth/me/")
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    // Deleted tokens are gone for good
+    client
+        .get(format!("/api/v1/auth/tokens/{}/", tokens[0].0))
+        .authorize_as(&user)
+        .expect_status(Status::NotFound)
+        .execute()
+        .await;
+}
@@ -0,0 +1,226 @@
+use pointercrate_core::{etag::Taggable, permission::PermissionsManager};
+use pointercrate_user::{User, ADMINISTRATOR, MODERATOR};
+use rocket::http::Status;
+use sqlx::{Pool, Postgres};
+use std::time::{SystemTime, UNIX_EPOCH};
+use totp_rs::{Algorithm, Secret, TOTP};
+
+const BASIC_AUTH: &str = "Basic UGF0cmljazpiYWQgcGFzc3dvcmQ=";
+
+// base32 encoding of "12345678901234567890"
+const SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+/// Generates the code for the time step `offset` steps away from the current one
+///
+/// Each code is only accepted once, so tests making multiple authenticated requests need codes from
+/// different (increasing) steps. Codes from the previous and next step are accepted to account for
+/// clock drift.
+fn code_at(offset: i64) -> String {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+
+    TOTP::new(Algorithm::SHA1, 6, 1, 30, Secret::Encoded(SECRET.to_string()).to_bytes().unwrap())
+        .unwrap()
+        .generate(now.saturating_add_signed(offset * 30))
+}
+
+async fn enable_totp(client: &pointercrate_test::TestClient, user: &User) -> Vec<String> {
+    let response: serde_json::Value = client
+        .patch(
+            "/api/v1/auth/me/",
+            &serde_json::json!({"totp": {"secret": SECRET, "code": code_at(-1)}}),
+        )
+        .header("Authorization", BASIC_AUTH)
+        .header("If-Match", user.etag_string())
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    serde_json::from_value(response["recovery_codes"].clone()).unwrap()
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_enable_totp(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::user::setup_rocket(pool).await;
+
+    let user = pointercrate_test::user::add_normal_user(&mut connection).await;
+
+    let response: serde_json::Value = client
+        .patch(
+            "/api/v1/auth/me/",
+            &serde_json::json!({"totp": {"secret": SECRET, "code": "abcdef"}}),
+        )
+        .header("Authorization", BASIC_AUTH)
+        .header("If-Match", user.user().etag_string())
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(response["code"], 42238);
+
+    let recovery_codes = enable_totp(&client, user.user()).await;
+
+    assert_eq!(recovery_codes.len(), 10);
+
+    // Password alone is no longer sufficient
+    let response: serde_json::Value = client
+        .post("/api/v1/auth/invalidate/", &())
+        .header("Authorization", BASIC_AUTH)
+        .expect_status(Status::Unauthorized)
+        .get_result()
+        .await;
+
+    assert_eq!(response["code"], 40101);
+
+    client
+        .post("/api/v1/auth/invalidate/", &())
+        .header("Authorization", BASIC_AUTH)
+        .header("X-TOTP-CODE", code_at(0))
+        .expect_status(Status::NoContent)
+        .execute()
+        .await;
+
+    // Codes cannot be replayed, neither are codes from earlier steps accepted after a later one was used
+    for offset in [-1, 0] {
+        let response: serde_json::Value = client
+            .post("/api/v1/auth/invalidate/", &())
+            .header("Authorization", BASIC_AUTH)
+            .header("X-TOTP-CODE", code_at(offset))
+            .expect_status(Status::Unauthorized)
+            .get_result()
+            .await;
+
+        assert_eq!(response["code"], 40101);
+    }
+
+    // Recovery codes can be used exactly once
+    client
+        .post("/api/v1/auth/invalidate/", &())
+        .header("Authorization", BASIC_AUTH)
+        .header("X-TOTP-CODE", recovery_codes[0].to_uppercase())
+        .expect_status(Status::NoContent)
+        .execute()
+        .await;
+
+    client
+        .post("/api/v1/auth/invalidate/", &())
+        .header("Authorization", BASIC_AUTH)
+        .header("X-TOTP-CODE", recovery_codes[0].clone())
+        .expect_status(Status::Unauthorized)
+        .execute()
+        .await;
+
+    client
+        .patch("/api/v1/auth/me/", &serde_json::json!({"totp": null}))
+        .header("Authorization", BASIC_AUTH)
+        .header("X-TOTP-CODE", code_at(1))
+        .header("If-Match", user.user().etag_string())
+        .expect_status(Status::NoContent)
+        .execute()
+        .await;
+
+    client
+        .post("/api/v1/auth/invalidate/", &())
+        .header("Authorization", BASIC_AUTH)
+        .expect_status(Status::NoContent)
+        .execute()
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_enable_totp_requires_password(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::user::setup_rocket(pool).await;
+
+    let user = pointercrate_test::user::add_normal_user(&mut connection).await;
+
+    // A session cookie alone is not enough to change the second factor
+    client
+        .patch(
+            "/api/v1/auth/me/",
+            &serde_json::json!({"totp": {"secret": SECRET, "code": code_at(0)}}),
+        )
+        .authorize_as_browser(&user)
+        .header("If-Match", user.user().etag_string())
+        .expect_status(Status::Unauthorized)
+        .execute()
+        .await;
+
+    // The session does suffice for changes not related to authentication
+    client
+        .patch("/api/v1/auth/me/", &serde_json::json!({"display_name": "Patrick"}))
+        .authorize_as_browser(&user)
+        .header("If-Match", user.user().etag_string())
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    let user = User::by_id(user.user().id, &mut connection).await.unwrap();
+
+    assert_eq!(enable_totp(&client, &user).await.len(), 10);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_two_factor_required_for_permission(pool: Pool<Postgres>) {
+    let permissions = PermissionsManager::new(vec![MODERATOR, ADMINISTRATOR])
+        .assigns(ADMINISTRATOR, MODERATOR)
+        .implies(ADMINISTRATOR, MODERATOR)
+        .requires_two_factor(ADMINISTRATOR);
+
+    let (client, mut connection) = pointercrate_test::user::setup_rocket_with_permissions(pool, permissions).await;
+
+    let user = pointercrate_test::user::system_user_with_perms(ADMINISTRATOR, &mut connection).await;
+    let user_url = format!("/api/v1/users/{}/", user.user().id);
+    // The permissions were changed after registration, so the etag of `user` is outdated
+    let etag = User::by_id(user.user().id, &mut connection).await.unwrap().etag_string();
+
+    let response: serde_json::Value = client
+        .delete(user_url.clone())
+        .authorize_as(&user)
+        .header("If-Match", etag.clone())
+        .expect_status(Status::Forbidden)
+        .get_result()
+        .await;
+
+    assert_eq!(response["code"], 40309);
+
+    enable_totp(&client, &User::by_id(user.user().id, &mut connection).await.unwrap()).await;
+
+    // Now the permission check passes, and the request fails for a different reason
+    let response: serde_json::Value = client
+        .delete(user_url)
+        .authorize_as(&user)
+        .header("If-Match", etag)
+        .expect_status(Status::Forbidden)
+        .get_result()
+        .await;
+
+    assert_eq!(response["code"], 40302);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_totp_code_usable_during_maintenance(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::user::setup_rocket(pool).await;
+
+    let admin = pointercrate_test::user::system_user_with_perms(ADMINISTRATOR, &mut connection).await;
+
+    enable_totp(&client, &User::by_id(admin.user().id, &mut connection).await.unwrap()).await;
+
+    client
+        .patch(
+            "/api/v1/maintenance/",
+            &serde_json::json!({"enabled": true, "allowlist": ["/api/v1/auth/"]}),
+        )
+        .authorize_as(&admin)
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    // Checking for the maintenance exemption must not use up the code
+    client
+        .post("/api/v1/auth/", &())
+        .header("Authorization", BASIC_AUTH)
+        .header("X-TOTP-CODE", code_at(0))
+        .header("X-Real-IP", "127.0.0.1")
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+}
@@ -1,13 +1,19 @@
 use base64::{engine::general_purpose::STANDARD, Engine};
 use log::warn;
+use pointercrate_core::maintenance::MaintenanceSettings;
 use pointercrate_core::{
     error::CoreError,
     permission::{Permission, PermissionsManager},
     pool::{audit_connection, PointercratePool},
 };
-use pointercrate_core_api::error::IntoOutcome2;
+use pointercrate_core_api::error::{ErrorResponder, IntoOutcome2};
+use pointercrate_core_api::maintenance::MaintenanceExemption;
 use pointercrate_core_api::{tryo_result, tryo_state};
-use pointercrate_user::auth::{AccessClaims, ApiToken, AuthenticatedUser, NonMutating, PasswordOrBrowser};
+use pointercrate_core_macros::localized_catcher;
+use pointercrate_user::{
+    auth::{token::ScopedToken, AccessClaims, ApiToken, AuthenticatedUser, NonMutating, PasswordOrBrowser},
+    ADMINISTRATOR,
+};
 use rocket::{
     http::{Method, Status},
     request::{FromRequest, Outcome},
@@ -16,6 +22,106 @@ use rocket::{
 use sqlx::{Postgres, Transaction};
 use std::collections::HashSet;
 
+/// The error an authorization request guard failed with, stored in the request local cache so that
+/// [`catch_401`] and [`catch_403`] can respond with it
+struct GuardError(Option<CoreError>);
+
+fn remember_guard_error(request: &Request<'_>, error: CoreError) -> CoreError {
+    request.local_cache(|| GuardError(Some(error.clone())));
+
+    error
+}
+
+/// Failures from the authorization FromRequest implementations can return 401s. Responds with the
+/// error that caused the failure if it is known (e.g. to let clients differentiate between wrong
+/// credentials and a missing second factor), and a generic 401 error otherwise.
+///
+/// Like all catchers, this needs to be registered by the application (see `pointercrate-example`).
+#[localized_catcher]
+#[rocket::catch(401)]
+pub async fn catch_401(request: &Request<'_>) -> ErrorResponder {
+    match request.local_cache(|| GuardError(None)).0 {
+        Some(ref error) => error.clone().into(),
+        None => CoreError::Unauthorized.into(),
+    }
+}
+
+/// Like [`catch_401`], but for authorization failures due to an access token lacking the required
+/// scope
+#[localized_catcher]
+#[rocket::catch(403)]
+pub async fn catch_403(request: &Request<'_>) -> ErrorResponder {
+    match request.local_cache(|| GuardError(None)).0 {
+        Some(ref error) => error.clone().into(),
+        None => CoreError::Forbidden.into(),
+    }
+}
+
+/// Lets administrators make mutating requests to allowlisted routes (and to the maintenance
+/// settings themselves, so that maintenance mode can be switched off again) while maintenance mode
+/// is active
+pub(crate) struct AdministratorExemption;
+
+#[rocket::async_trait]
+impl MaintenanceExemption for AdministratorExemption {
+    async fn is_exempt(&self, request: &Request<'_>, settings: &MaintenanceSettings) -> bool {
+        let path = request.uri().path();
+
+        if !settings.allows(path.as_str()) && !path.starts_with("/api/v1/maintenance") {
+            return false;
+        }
+
+        claims_administrator(request).await.unwrap_or(false)
+    }
+}
+
+/// Whether the request is authenticated as an administrator
+///
+/// All credentials are verified just like the authorization request guards would, as handlers
+/// accepting anonymous requests simply ignore invalid ones. Two-factor codes can only be used once,
+/// so basic authentication is verified inside a transaction that is rolled back again, leaving the
+/// code for the handler's request guards to verify.
+async fn claims_administrator(request: &Request<'_>) -> Option<bool> {
+    let pool = request.rocket().state::<PointercratePool>()?;
+    let permissions = request.rocket().state::<PermissionsManager>()?;
+
+    let mut connection = pool.connection().await.ok()?;
+
+    for authorization in request.headers().get("Authorization") {
+        match authorization.split(' ').collect::<Vec<_>>()[..] {
+            ["Bearer", token] => {
+                let access_claims = AccessClaims::decode(token).ok()?;
+                let user = AuthenticatedUser::by_id(access_claims.id().ok()?, &mut connection).await.ok()?;
+                let authenticated_user = match access_claims.is_scoped() {
+                    true => user.validate_scoped_api_access(access_claims, &mut connection).await.ok()?,
+                    false => user.validate_api_access(access_claims).ok()?,
+                };
+
+                return Some(require_permission(&authenticated_user, permissions, ADMINISTRATOR).is_ok());
+            },
+            ["Basic", basic_auth] => {
+                let decoded = String::from_utf8(STANDARD.decode(basic_auth).ok()?).ok()?;
+                let (username, password) = decoded.split_once(':')?;
+                let totp_code = request.headers().get_one("X-TOTP-CODE");
+
+                // Dropping the transaction rolls it back
+                let mut transaction = pool.transaction().await.ok()?;
+                let user = AuthenticatedUser::by_name(username, &mut transaction).await.ok()?;
+                let authenticated = user.verify_credentials(password, totp_code, &mut transaction).await.ok()?;
+
+                return Some(require_permission(&authenticated, permissions, ADMINISTRATOR).is_ok());
+            },
+            _ => (),
+        }
+    }
+
+    let access_claims = AccessClaims::decode(request.cookies().get("access_token")?.value()).ok()?;
+    let user = AuthenticatedUser::by_id(access_claims.id().ok()?, &mut connection).await.ok()?;
+    let authenticated_for_get = user.validate_cookie_claims(access_claims).ok()?;
+
+    Some(require_permission(&authenticated_for_get, permissions, ADMINISTRATOR).is_ok())
+}
+
 #[allow(non_upper_case_globals)]
 pub struct Auth<A> {
     pub user: AuthenticatedUser<A>,
@@ -29,9 +135,7 @@ impl<A> Auth<A> {
     }
 
     pub fn require_permission(&self, permission: Permission) -> Result<(), CoreError> {
-        self.permissions.require_permission(self.user.user().permissions, permission)?;
-
-        Ok(())
+        require_permission(&self.user, &self.permissions, permission)
     }
 
     pub fn has_permission(&self, permission: Permission) -> bool {
@@ -39,10 +143,32 @@ impl<A> Auth<A> {
     }
 
     pub fn assignable_permissions(&self) -> HashSet<Permission> {
+        if require_two_factor(&self.user, &self.permissions).is_err() {
+            return HashSet::new();
+        }
+
         self.permissions.assignable_by_bits(self.user.user().permissions)
     }
 }
 
+fn require_permission<A>(user: &AuthenticatedUser<A>, permissions: &PermissionsManager, permission: Permission) -> Result<(), CoreError> {
+    permissions.require_permission(user.user().permissions, permission)?;
+    require_two_factor(user, permissions)
+}
+
+/// Users holding a permission that requires two-factor authentication cannot use any of their
+/// permissions until they enabled it
+fn require_two_factor<A>(user: &AuthenticatedUser<A>, permissions: &PermissionsManager) -> Result<(), CoreError> {
+    if user.has_two_factor() {
+        return Ok(());
+    }
+
+    match permissions.two_factor_required_by_bits(user.user().permissions) {
+        Some(required) => Err(CoreError::TwoFactorNotEnabled { required }),
+        None => Ok(()),
+    }
+}
+
 #[rocket::async_trait]
 impl<'r> FromRequest<'r> for Auth<NonMutating> {
     type Error = CoreError;
@@ -90,7 +216,21 @@ impl<'r> FromRequest<'r> for Auth<ApiToken> {
             if let ["Bearer", token] = authorization.split(' ').collect::<Vec<_>>()[..] {
                 let access_claims = tryo_result!(AccessClaims::decode(token));
                 let user = tryo_result!(AuthenticatedUser::by_id(tryo_result!(access_claims.id()), &mut connection).await);
-                let authenticated_user = tryo_result!(user.validate_api_access(access_claims));
+                let authenticated_user = match access_claims.is_scoped() {
+                    true => tryo_result!(user.validate_scoped_api_access(access_claims, &mut connection).await),
+                    false => tryo_result!(user.validate_api_access(access_claims)),
+                };
+
+                tryo_result!(authenticated_user
+                    .authorize_request(request.method().as_str(), request.uri().path().as_str())
+                    .map_err(|err| remember_guard_error(request, err)));
+
+                if let Some(token_id) = authenticated_user.scoped_token_id() {
+                    // Outside of the request's transaction, which is not committed for GET requests
+                    let mut usage_connection = tryo_result!(pool.connection().await);
+
+                    tryo_result!(ScopedToken::record_usage(token_id, &mut usage_connection).await);
+                }
 
                 tryo_result!(audit_connection(&mut connection, authenticated_user.user().id).await);
 
@@ -154,8 +294,12 @@ impl<'r> FromRequest<'r> for Auth<PasswordOrBrowser> {
                     }));
 
                 if let [username, password] = &decoded.splitn(2, ':').collect::<Vec<_>>()[..] {
+                    let totp_code = request.headers().get_one("X-TOTP-CODE");
                     let user = tryo_result!(AuthenticatedUser::by_name(username, &mut connection).await);
-                    let authenticated = tryo_result!(user.verify_password(password));
+                    let authenticated = tryo_result!(user
+                        .verify_credentials(password, totp_code, &mut connection)
+                        .await
+                        .map_err(|err| remember_guard_error(request, err)));
 
                     tryo_result!(audit_connection(&mut connection, authenticated.user().id).await);
 
@@ -14,7 +14,7 @@ use pointercrate_user::{
 use rocket::{
     http::Status,
     serde::json::{serde_json, Json},
-    State,
+    Either, State,
 };
 use std::net::IpAddr;
 
@@ -35,14 +35,14 @@ pub async fn register(
 
     let mut connection = pool.transaction().await.map_err(UserError::from)?;
 
-    ratelimits.soft_registrations(ip)?;
+    ratelimits.soft_registrations(ip).await?;
 
     LegacyAuthenticatedUser::validate_password(&body.password)?;
     User::validate_name(&body.name)?;
 
     let user = AuthenticatedUser::register(body.0, &mut connection).await?;
 
-    ratelimits.registrations(ip)?;
+    ratelimits.registrations(ip).await?;
 
     connection.commit().await.map_err(UserError::from)?;
 
@@ -56,16 +56,21 @@ pub async fn register(
 pub async fn login(
     auth: std::result::Result<Auth<PasswordOrBrowser>, CoreError>, ip: IpAddr, ratelimits: &State<UserRatelimits>,
 ) -> Result<Response2<Json<serde_json::Value>>> {
-    ratelimits.login_attempts(ip)?;
+    ratelimits.login_attempts(ip).await?;
     let auth = auth?;
 
-    Ok(Response2::json(serde_json::json! {
+    let response = Response2::json(serde_json::json! {
         {
             "data": auth.user.user(),
             "token": auth.user.generate_programmatic_access_token()
         }
     })
-    .with_header("etag", auth.user.user().etag_string()))
+    .with_header("etag", auth.user.user().etag_string());
+
+    // Logging in might have used up a recovery code
+    auth.commit().await?;
+
+    Ok(response)
 }
 
 #[localized]
@@ -87,19 +92,40 @@ pub async fn get_me(auth: Auth<ApiToken>) -> Tagged<User> {
 #[rocket::patch("/me/", data = "<patch>")]
 pub async fn patch_me(
     mut auth: Auth<PasswordOrBrowser>, patch: Json<PatchMe>, pred: Precondition,
-) -> Result<std::result::Result<Tagged<User>, Status>> {
+) -> Result<std::result::Result<Either<Tagged<User>, Response2<Json<serde_json::Value>>>, Status>> {
     pred.require_etag_match(auth.user.user())?;
 
-    let changes_password = patch.changes_password();
+    // Neither change is visible in the user object, so a tagged response would be a 304
+    let no_content = patch.changes_password() || patch.disables_totp();
+
+    // Needs to happen before applying the patch, as that consumes the user. Should enabling two-factor
+    // authentication fail, the transaction is rolled back, and the codes are never stored.
+    let recovery_codes = match patch.enables_totp() {
+        true => Some(auth.user.regenerate_recovery_codes(&mut auth.connection).await?),
+        false => None,
+    };
 
     let updated_user = auth.user.apply_patch(patch.0, &mut auth.connection).await?;
 
     auth.connection.commit().await.map_err(UserError::from)?;
 
-    if changes_password {
+    // Recovery codes are only ever shown once, so they take precedence over everything else
+    if let Some(recovery_codes) = recovery_codes {
+        let etag = updated_user.etag_string();
+
+        Ok(Ok(Either::Right(
+            Response2::json(serde_json::json! {
+                {
+                    "data": updated_user,
+                    "recovery_codes": recovery_codes
+                }
+            })
+            .with_header("etag", etag),
+        )))
+    } else if no_content {
         Ok(Err(Status::NoContent))
     } else {
-        Ok(Ok(Tagged(updated_user)))
+        Ok(Ok(Either::Left(Tagged(updated_user))))
     }
 }
 
@@ -0,0 +1,38 @@
+use crate::auth::Auth;
+use pointercrate_core::maintenance::{MaintenanceSettings, PatchMaintenanceSettings};
+use pointercrate_core_api::{
+    error::Result,
+    maintenance::{MaintenanceMode, MaintenanceStatus},
+};
+use pointercrate_core_macros::localized;
+use pointercrate_user::{auth::ApiToken, ADMINISTRATOR};
+use rocket::{serde::json::Json, State};
+
+#[localized]
+#[rocket::get("/")]
+pub async fn get(auth: Auth<ApiToken>, maintenance: &State<MaintenanceMode>) -> Result<Json<MaintenanceStatus>> {
+    auth.require_permission(ADMINISTRATOR)?;
+
+    Ok(Json(maintenance.status()))
+}
+
+#[localized]
+#[rocket::patch("/", data = "<patch>")]
+pub async fn patch(
+    mut auth: Auth<ApiToken>, patch: Json<PatchMaintenanceSettings>, maintenance: &State<MaintenanceMode>,
+) -> Result<Json<MaintenanceStatus>> {
+    auth.require_permission(ADMINISTRATOR)?;
+
+    // Work off of the settings currently in the database instead of the cached ones, in case they
+    // were changed from a different server instance in the meantime
+    let settings = MaintenanceSettings::load(&mut auth.connection)
+        .await?
+        .apply_patch(patch.0, &mut auth.connection)
+        .await?;
+
+    auth.commit().await?;
+
+    maintenance.update(settings);
+
+    Ok(Json(maintenance.status()))
+}
@@ -1,2 +1,4 @@
 pub(crate) mod auth;
+pub(crate) mod maintenance;
+pub(crate) mod token;
 pub(crate) mod user;
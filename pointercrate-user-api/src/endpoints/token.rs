@@ -0,0 +1,83 @@
+use crate::auth::Auth;
+use pointercrate_core_api::{
+    error::Result,
+    etag::{Precondition, TaggableExt, Tagged},
+    response::Response2,
+};
+use pointercrate_core_macros::localized;
+use pointercrate_user::{
+    auth::{
+        token::{PatchScopedToken, PostScopedToken, ScopedToken},
+        ApiToken, PasswordOrBrowser,
+    },
+    error::UserError,
+};
+use rocket::{
+    http::Status,
+    serde::json::{serde_json, Json},
+};
+
+#[localized]
+#[rocket::get("/")]
+pub async fn list(mut auth: Auth<ApiToken>) -> Result<Json<Vec<ScopedToken>>> {
+    Ok(Json(ScopedToken::all_of(auth.user.user().id, &mut auth.connection).await?))
+}
+
+#[localized]
+#[rocket::post("/", data = "<data>")]
+pub async fn post(mut auth: Auth<PasswordOrBrowser>, data: Json<PostScopedToken>) -> Result<Response2<Json<serde_json::Value>>> {
+    let (token, access_token) = auth.user.create_scoped_token(data.0, &mut auth.connection).await?;
+
+    auth.connection.commit().await.map_err(UserError::from)?;
+
+    let location = format!("/api/v1/auth/tokens/{}/", token.id);
+
+    // The access token is only ever returned right after creation
+    Ok(Response2::json(serde_json::json! {
+        {
+            "data": token,
+            "token": access_token
+        }
+    })
+    .status(Status::Created)
+    .with_header("Location", location))
+}
+
+#[localized]
+#[rocket::get("/<token_id>/")]
+pub async fn get(token_id: i32, mut auth: Auth<ApiToken>) -> Result<Tagged<ScopedToken>> {
+    Ok(Tagged(
+        ScopedToken::by_id(token_id, auth.user.user().id, &mut auth.connection).await?,
+    ))
+}
+
+#[localized]
+#[rocket::patch("/<token_id>/", data = "<patch>")]
+pub async fn patch(
+    token_id: i32, mut auth: Auth<PasswordOrBrowser>, precondition: Precondition, patch: Json<PatchScopedToken>,
+) -> Result<Tagged<ScopedToken>> {
+    let member_id = auth.user.user().id;
+
+    let token = ScopedToken::by_id(token_id, member_id, &mut auth.connection)
+        .await?
+        .require_match(precondition)?
+        .apply_patch(member_id, patch.0, &mut auth.connection)
+        .await?;
+
+    auth.connection.commit().await.map_err(UserError::from)?;
+
+    Ok(Tagged(token))
+}
+
+#[localized]
+#[rocket::delete("/<token_id>/")]
+pub async fn delete(token_id: i32, mut auth: Auth<PasswordOrBrowser>, precondition: Precondition) -> Result<Status> {
+    let token = ScopedToken::by_id(token_id, auth.user.user().id, &mut auth.connection).await?;
+
+    precondition.require_etag_match(&token)?;
+
+    token.delete(&mut auth.connection).await?;
+    auth.connection.commit().await.map_err(UserError::from)?;
+
+    Ok(Status::NoContent)
+}
@@ -3,7 +3,7 @@ use pointercrate_core::error::CoreError;
 use pointercrate_core_api::{
     error::Result,
     etag::{Precondition, Tagged},
-    pagination::pagination_response,
+    pagination::sorted_pagination_response,
     query::Query,
     response::Response2,
 };
@@ -35,7 +35,7 @@ pub async fn paginate(mut auth: Auth<ApiToken>, data: Query<UserPagination>) ->
         }
     }
 
-    Ok(pagination_response("/api/v1/users/", pagination, &mut auth.connection).await?)
+    Ok(sorted_pagination_response("/api/v1/users/", pagination, &mut auth.connection).await?)
 }
 
 #[localized]
@@ -1,18 +1,18 @@
 use crate::ratelimits::UserRatelimits;
 
-use rocket::{Build, Rocket};
+use pointercrate_core::{pool::PointercratePool, ratelimits::configured_store};
+use pointercrate_core_api::maintenance::MaintenanceExemption;
+use rocket::{fairing::AdHoc, Build, Rocket};
 
 pub mod auth;
 mod endpoints;
 #[cfg(feature = "oauth2")]
-mod oauth;
+pub mod oauth;
 mod pages;
 mod ratelimits;
 
 #[allow(unused_mut)]
 pub fn setup(mut rocket: Rocket<Build>) -> Rocket<Build> {
-    let ratelimits = UserRatelimits::new();
-
     let mut auth_routes = rocket::routes![
         endpoints::auth::login,
         endpoints::auth::invalidate,
@@ -32,16 +32,33 @@ pub fn setup(mut rocket: Rocket<Build>) -> Rocket<Build> {
     #[cfg(feature = "legacy_accounts")]
     page_routes.extend(rocket::routes![pages::register]);
     #[cfg(feature = "oauth2")]
-    auth_routes.extend(rocket::routes![pages::google_oauth_login, pages::google_oauth_register]);
+    auth_routes.extend(rocket::routes![pages::oauth_login, pages::oauth_register]);
+    #[cfg(feature = "oauth2")]
+    page_routes.extend(rocket::routes![pages::oauth_authorize, pages::oauth_callback]);
 
     #[cfg(feature = "oauth2")]
     {
-        rocket = rocket.manage(oauth::GoogleCertificateStore::default());
+        rocket = rocket.attach(AdHoc::try_on_ignite("OAuth2 Providers", setup_oauth_providers));
     }
 
     rocket
-        .manage(ratelimits)
+        .attach(AdHoc::try_on_ignite("User Ratelimits", setup_ratelimits))
+        .manage(Box::new(auth::AdministratorExemption) as Box<dyn MaintenanceExemption>)
         .mount("/api/v1/auth/", auth_routes)
+        .mount(
+            "/api/v1/auth/tokens/",
+            rocket::routes![
+                endpoints::token::list,
+                endpoints::token::post,
+                endpoints::token::get,
+                endpoints::token::patch,
+                endpoints::token::delete
+            ],
+        )
+        .mount(
+            "/api/v1/maintenance/",
+            rocket::routes![endpoints::maintenance::get, endpoints::maintenance::patch],
+        )
         .mount(
             "/api/v1/users/",
             rocket::routes![
@@ -53,3 +70,44 @@ pub fn setup(mut rocket: Rocket<Build>) -> Rocket<Build> {
         )
         .mount("/", page_routes)
 }
+
+async fn setup_ratelimits(rocket: Rocket<Build>) -> Result<Rocket<Build>, Rocket<Build>> {
+    let Some(pool) = rocket.state::<PointercratePool>() else {
+        log::error!("No database pool registered, cannot set up ratelimits");
+
+        return Err(rocket);
+    };
+
+    let store = match configured_store(pool.clone_inner()) {
+        Ok(store) => store,
+        Err(err) => {
+            log::error!("Cannot set up ratelimits: {}", err);
+
+            return Err(rocket);
+        },
+    };
+
+    let ratelimits = UserRatelimits::with_store(store);
+
+    Ok(rocket.manage(ratelimits))
+}
+
+/// Manages an [`OauthProviderStore`](oauth::OauthProviderStore) for the providers configured in the
+/// `[user]` table, unless one was managed explicitly already
+#[cfg(feature = "oauth2")]
+async fn setup_oauth_providers(rocket: Rocket<Build>) -> Result<Rocket<Build>, Rocket<Build>> {
+    if rocket.state::<oauth::OauthProviderStore>().is_some() {
+        return Ok(rocket);
+    }
+
+    let providers = match pointercrate_user::config::oauth_providers() {
+        Ok(providers) => providers.to_vec(),
+        Err(err) => {
+            log::error!("Cannot set up OAuth2 providers: {}", err);
+
+            return Err(rocket);
+        },
+    };
+
+    Ok(rocket.manage(oauth::OauthProviderStore::new(providers)))
+}
@@ -1,95 +1,257 @@
-use std::sync::Arc;
+use std::collections::HashMap;
 
 use chrono::{Duration, Utc};
-use pointercrate_core::error::log_internal_server_error;
-use pointercrate_user::auth::oauth::{GoogleCertificateDatabase, UnvalidatedOauthCredential, ValidatedGoogleCredentials};
-use reqwest::Client;
+use pointercrate_core::error::{log_internal_server_error, CoreError};
+use pointercrate_user::{
+    auth::oauth::{
+        AuthorizationState, KeySet, OauthIdentity, OauthProvider, ProviderEndpoints, ProviderMetadata, TokenResponse,
+        UnvalidatedOauthCredential, Userinfo,
+    },
+    error::UserError,
+};
+use reqwest::{header::HeaderMap, Client};
 use rocket::tokio::sync::RwLock;
 
-pub const GOOGLE_CERT_ENDPOINT: &'static str = "https://www.googleapis.com/oauth2/v3/certs";
+/// How long to cache signing keys if the provider does not tell us (one hour)
+const DEFAULT_KEY_LIFETIME: i64 = 3600;
 
-#[derive(Default)]
-pub struct GoogleCertificateStore {
-    db: Arc<RwLock<GoogleCertificateDatabase>>,
+/// The configured OAuth2 providers, together with their discovered metadata and cached signing keys
+pub struct OauthProviderStore {
+    client: Client,
+    providers: HashMap<String, ProviderState>,
+}
+
+struct ProviderState {
+    provider: OauthProvider,
+    metadata: RwLock<Option<ProviderMetadata>>,
+    keys: RwLock<KeySet>,
 }
 
 #[allow(dead_code)] /* fields exist specifically for Debug output */
 #[derive(Debug)]
-pub enum CertificateRefreshError {
+pub enum ProviderError {
     Reqwest(reqwest::Error),
     MalformedCacheControlHeader(String),
-    MissingCacheControlHeader,
+    IssuerMismatch { expected: String, actual: Option<String> },
+    MissingJwksUri,
 }
 
-impl From<reqwest::Error> for CertificateRefreshError {
+impl From<reqwest::Error> for ProviderError {
     fn from(value: reqwest::Error) -> Self {
-        CertificateRefreshError::Reqwest(value)
+        ProviderError::Reqwest(value)
     }
 }
 
-impl GoogleCertificateStore {
-    pub async fn validate(&self, creds: UnvalidatedOauthCredential) -> Option<ValidatedGoogleCredentials> {
-        self.db.read().await.validate_credentials(creds)
-    }
+fn max_age(headers: &HeaderMap) -> Result<Option<i64>, ProviderError> {
+    let Some(cc_header) = headers.get("Cache-Control") else {
+        return Ok(None);
+    };
+    let cc_header = cc_header
+        .to_str()
+        .map_err(|_| ProviderError::MalformedCacheControlHeader("unparsable".to_string()))?;
 
-    pub async fn validate_with_refresh(&self, creds: UnvalidatedOauthCredential) -> Option<ValidatedGoogleCredentials> {
-        if self.needs_refresh().await {
-            self.refresh()
-                .await
-                .inspect_err(|err| log_internal_server_error(format!("Failed to retrieve signing certificates from Google! {:?}", err)))
-                .ok()?;
+    let Some(max_age_directive) = cc_header.split(',').find(|directive| directive.trim().starts_with("max-age")) else {
+        return Ok(None);
+    };
+
+    let mut parts = max_age_directive.split('=');
+    _ = parts.next(); /* Split<'_, T> implements FusedIterator, so can ignore None handling here */
+    let age_str = parts
+        .next()
+        .ok_or_else(|| ProviderError::MalformedCacheControlHeader(max_age_directive.to_string()))?;
+
+    age_str
+        .trim()
+        .parse()
+        .map(Some)
+        .map_err(|_| ProviderError::MalformedCacheControlHeader(age_str.to_string()))
+}
+
+impl OauthProviderStore {
+    pub fn new(providers: Vec<OauthProvider>) -> Self {
+        OauthProviderStore {
+            client: Client::builder()
+                .user_agent("pointercrate")
+                .build()
+                .expect("failed to initialize HTTP client"),
+            providers: providers
+                .into_iter()
+                .map(|provider| {
+                    (
+                        provider.id.clone(),
+                        ProviderState {
+                            provider,
+                            metadata: RwLock::new(None),
+                            keys: RwLock::new(KeySet::default()),
+                        },
+                    )
+                })
+                .collect(),
         }
+    }
+
+    fn state(&self, provider: &str) -> Result<&ProviderState, UserError> {
+        self.providers.get(provider).ok_or_else(|| UserError::OauthProviderNotFound {
+            provider: provider.to_string(),
+        })
+    }
 
-        self.validate(creds).await
+    pub fn provider(&self, provider: &str) -> Result<&OauthProvider, UserError> {
+        self.state(provider).map(|state| &state.provider)
     }
 
-    pub async fn needs_refresh(&self) -> bool {
-        self.db.read().await.needs_refresh()
+    /// Gets the metadata of the given provider, performing discovery if needed
+    pub async fn metadata(&self, provider: &str) -> Result<ProviderMetadata, UserError> {
+        let state = self.state(provider)?;
+
+        state.metadata(&self.client).await.map_err(|err| {
+            CoreError::internal_server_error(format!("Failed to retrieve metadata of oauth provider {}: {:?}", provider, err)).into()
+        })
+    }
+
+    /// Validates an ID token issued by the given provider, without a nonce (e.g. one obtained via
+    /// Google's one tap sign-in)
+    pub async fn validate(&self, provider: &str, credential: &UnvalidatedOauthCredential) -> Result<Option<OauthIdentity>, UserError> {
+        let state = self.state(provider)?;
+
+        Ok(state.validate_id_token(&self.client, credential.credential(), None).await)
+    }
+
+    /// Completes an authorization code flow by exchanging the code the provider redirected back
+    /// with, and retrieving the identity of the user that authorized us
+    pub async fn exchange_code(
+        &self, provider: &str, code: &str, authorization: &AuthorizationState,
+    ) -> Result<Option<OauthIdentity>, UserError> {
+        let state = self.state(provider)?;
+
+        Ok(state
+            .exchange_code(&self.client, code, authorization)
+            .await
+            .inspect_err(|err| log::warn!("Failed to exchange authorization code at {}: {:?}", provider, err))
+            .ok()
+            .flatten())
     }
+}
+
+impl ProviderState {
+    async fn metadata(&self, client: &Client) -> Result<ProviderMetadata, ProviderError> {
+        let issuer = match self.provider.endpoints {
+            ProviderEndpoints::Static(ref metadata) => return Ok(metadata.clone()),
+            ProviderEndpoints::Discover { ref issuer } => issuer,
+        };
 
-    pub async fn refresh(&self) -> Result<(), CertificateRefreshError> {
-        let mut guard = self.db.write().await;
+        if let Some(ref metadata) = *self.metadata.read().await {
+            return Ok(metadata.clone());
+        }
+
+        let mut guard = self.metadata.write().await;
+
+        // Some other task might have done the discovery while we were waiting for the lock
+        if let Some(ref metadata) = *guard {
+            return Ok(metadata.clone());
+        }
+
+        let discovery_url = self.provider.endpoints.discovery_url().unwrap_or_default();
+        let metadata = client
+            .get(discovery_url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<ProviderMetadata>()
+            .await?;
+
+        // OpenID Connect Discovery 1.0, Section 4.3
+        if metadata.issuer.as_deref().map(|iss| iss.trim_end_matches('/')) != Some(issuer.trim_end_matches('/')) {
+            return Err(ProviderError::IssuerMismatch {
+                expected: issuer.clone(),
+                actual: metadata.issuer,
+            });
+        }
+
+        *guard = Some(metadata.clone());
+
+        Ok(metadata)
+    }
+
+    async fn refresh_keys(&self, client: &Client, metadata: &ProviderMetadata, id_token: &str) -> Result<(), ProviderError> {
+        let mut guard = self.keys.write().await;
 
         // Between calling needs_refresh and us taking the write lock, did the need
         // for a refresh potentially go away (e.g. because some other task did the refresh in the meantime)?
-        if !guard.needs_refresh() {
+        if !guard.needs_refresh(id_token) {
             return Ok(());
         }
 
-        let client = Client::new();
+        let jwks_uri = metadata.jwks_uri.as_ref().ok_or(ProviderError::MissingJwksUri)?;
 
         let request_time = Utc::now();
-        let response = client.get(GOOGLE_CERT_ENDPOINT).send().await?;
+        let response = client.get(jwks_uri).send().await?.error_for_status()?;
 
-        let cc_header = response
-            .headers()
-            .get("Cache-Control")
-            .ok_or(CertificateRefreshError::MissingCacheControlHeader)?;
-        let cc_header = cc_header
-            .to_str()
-            .map_err(|_| CertificateRefreshError::MalformedCacheControlHeader("unparsable".to_string()))?;
+        let age = max_age(response.headers())?.unwrap_or(DEFAULT_KEY_LIFETIME);
 
-        let max_age_directive = cc_header
-            .split(',')
-            .find(|directive| directive.trim().starts_with("max-age"))
-            .ok_or_else(|| CertificateRefreshError::MalformedCacheControlHeader(cc_header.to_string()))?;
+        let mut keys = response.json::<KeySet>().await?;
+        keys.fetched_at = Some(request_time);
+        keys.expiry = Some(request_time + Duration::seconds(age));
 
-        let mut parts = max_age_directive.split('=');
-        _ = parts.next(); /* Split<'_, T> implements FusedIterator, so can ignore None handling here */
-        let age_str = parts
-            .next()
-            .ok_or_else(|| CertificateRefreshError::MalformedCacheControlHeader(max_age_directive.to_string()))?;
-        let age = age_str
-            .parse()
-            .map_err(|_| CertificateRefreshError::MalformedCacheControlHeader(age_str.to_string()))?;
+        *guard = keys;
 
-        let expiry = request_time + Duration::seconds(age);
+        Ok(())
+    }
 
-        let mut new_db = response.json::<GoogleCertificateDatabase>().await?;
-        new_db.expiry = Some(expiry);
+    async fn validate_id_token(&self, client: &Client, id_token: &str, nonce: Option<&str>) -> Option<OauthIdentity> {
+        let metadata = self
+            .metadata(client)
+            .await
+            .inspect_err(|err| log_internal_server_error(format!("Failed to retrieve metadata of {}! {:?}", self.provider.id, err)))
+            .ok()?;
 
-        *guard = new_db;
+        if self.keys.read().await.needs_refresh(id_token) {
+            self.refresh_keys(client, &metadata, id_token)
+                .await
+                .inspect_err(|err| log_internal_server_error(format!("Failed to retrieve signing keys of {}! {:?}", self.provider.id, err)))
+                .ok()?;
+        }
 
-        Ok(())
+        self.keys.read().await.validate_id_token(&self.provider, &metadata, id_token, nonce)
+    }
+
+    async fn exchange_code(
+        &self, client: &Client, code: &str, authorization: &AuthorizationState,
+    ) -> Result<Option<OauthIdentity>, ProviderError> {
+        let metadata = self.metadata(client).await?;
+
+        let tokens = client
+            .post(&metadata.token_endpoint)
+            .header("Accept", "application/json")
+            .form(&self.provider.token_request(code, authorization))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<TokenResponse>()
+            .await?;
+
+        if self.provider.is_openid_connect() {
+            let Some(id_token) = tokens.id_token else {
+                return Ok(None);
+            };
+
+            return Ok(self.validate_id_token(client, &id_token, Some(authorization.nonce())).await);
+        }
+
+        let Some(ref userinfo_endpoint) = metadata.userinfo_endpoint else {
+            return Ok(None);
+        };
+
+        let userinfo = client
+            .get(userinfo_endpoint)
+            .bearer_auth(&tokens.access_token)
+            .header("Accept", "application/json")
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Userinfo>()
+            .await?;
+
+        Ok(self.provider.identity_from_userinfo(userinfo))
     }
 }
@@ -24,10 +24,15 @@ use pointercrate_user::auth::legacy::{LegacyAuthenticatedUser, Registration};
 
 #[cfg(feature = "oauth2")]
 use {
-    crate::oauth::GoogleCertificateStore,
-    pointercrate_user::auth::oauth::{OauthRegistration, UnvalidatedOauthCredential},
+    crate::oauth::OauthProviderStore,
+    pointercrate_user::auth::oauth::{AuthorizationState, OauthIdentity, OauthRegistration, UnvalidatedOauthCredential},
+    rocket::response::content::RawHtml,
 };
 
+/// Name of the cookie holding the [`AuthorizationState`] of an ongoing authorization code flow
+#[cfg(feature = "oauth2")]
+const OAUTH_STATE_COOKIE: &str = "oauth_state";
+
 fn build_cookies(user: &AuthenticatedUser<PasswordOrBrowser>, cookies: &CookieJar<'_>) -> pointercrate_user::error::Result<()> {
     let (access_token, csrf_token) = user.generate_token_pair()?;
 
@@ -63,12 +68,15 @@ pub async fn login_page(auth: Option<Auth<NonMutating>>) -> Result<Redirect, Pag
 pub async fn login(
     auth: Result<Auth<PasswordOrBrowser>, CoreError>, ip: IpAddr, ratelimits: &State<UserRatelimits>, cookies: &CookieJar<'_>,
 ) -> pointercrate_core_api::error::Result<Status> {
-    ratelimits.login_attempts(ip)?;
+    ratelimits.login_attempts(ip).await?;
 
     let auth = auth?;
 
     build_cookies(&auth.user, cookies)?;
 
+    // Logging in might have used up a recovery code
+    auth.commit().await?;
+
     Ok(Status::NoContent)
 }
 
@@ -87,12 +95,12 @@ pub async fn register(
 ) -> pointercrate_core_api::error::Result<Status> {
     let mut connection = pool.transaction().await.map_err(UserError::from)?;
 
-    ratelimits.soft_registrations(ip)?;
+    ratelimits.soft_registrations(ip).await?;
 
     LegacyAuthenticatedUser::validate_password(&registration.password)?;
     User::validate_name(&registration.name)?;
 
-    ratelimits.registrations(ip)?;
+    ratelimits.registrations(ip).await?;
 
     let user = AuthenticatedUser::register(registration.0, &mut connection).await?;
 
@@ -122,29 +130,115 @@ pub async fn logout(_auth: Auth<NonMutating>, cookies: &CookieJar<'_>) -> Redire
     Redirect::to(rocket::uri!(login_page))
 }
 
+/// Redirects to the given location once the returned page has loaded
+///
+/// Our session cookies are `SameSite=Strict`, meaning browsers do not send them along when following
+/// an HTTP redirect at the end of a navigation that started at an external OAuth2 provider.
+#[cfg(feature = "oauth2")]
+fn same_site_redirect(location: &str) -> RawHtml<String> {
+    RawHtml(format!(
+        r#"<!DOCTYPE html><html><head><meta http-equiv="refresh" content="0; url={0}"></head><body><a href="{0}">{0}</a></body></html>"#,
+        location
+    ))
+}
+
+/// Starts an authorization code flow with the given provider. If the user is already signed in,
+/// the identity they authorize us for will be linked to their account.
 #[cfg(feature = "oauth2")]
 #[localized]
-#[rocket::post("/oauth/google/", data = "<payload>")]
-pub async fn google_oauth_login(
-    payload: Json<UnvalidatedOauthCredential>, auth: Option<Auth<PasswordOrBrowser>>, key_store: &State<GoogleCertificateStore>,
-    pool: &State<PointercratePool>, cookies: &rocket::http::CookieJar<'_>,
-) -> pointercrate_core_api::error::Result<Status> {
-    let validated_credentials = key_store.validate_with_refresh(payload.0).await.ok_or(CoreError::Unauthorized)?;
+#[rocket::get("/oauth/<provider>/")]
+pub async fn oauth_authorize(
+    provider: &str, auth: Option<Auth<NonMutating>>, providers: &State<OauthProviderStore>, cookies: &CookieJar<'_>,
+) -> pointercrate_core_api::error::Result<Redirect> {
+    let metadata = providers.metadata(provider).await?;
+    let request = providers
+        .provider(provider)?
+        .authorization_request(&metadata, auth.as_ref().map(|auth| &auth.user))?;
+
+    // Needs to be sent along when the provider redirects back to us, which is a cross-site navigation
+    let cookie = Cookie::build((OAUTH_STATE_COOKIE, request.state))
+        .http_only(true)
+        .same_site(SameSite::Lax)
+        .secure(!cfg!(debug_assertions))
+        .max_age(rocket::time::Duration::minutes(15))
+        .path("/oauth/");
+
+    cookies.add(cookie);
+
+    Ok(Redirect::to(request.url))
+}
+
+#[cfg(feature = "oauth2")]
+#[localized]
+#[rocket::get("/oauth/<provider>/callback/?<code>&<state>")]
+pub async fn oauth_callback(
+    provider: &str, code: Option<&str>, state: &str, providers: &State<OauthProviderStore>, pool: &State<PointercratePool>,
+    cookies: &CookieJar<'_>,
+) -> pointercrate_core_api::error::Result<RawHtml<String>> {
+    let stored = cookies.get(OAUTH_STATE_COOKIE).ok_or(CoreError::Unauthorized)?;
+    let authorization = AuthorizationState::validate(stored.value(), provider, state)?;
+
+    cookies.remove(Cookie::build(OAUTH_STATE_COOKIE).path("/oauth/"));
+
+    // The user declined to authorize us at the provider
+    let Some(code) = code else {
+        return Ok(same_site_redirect(&rocket::uri!(login_page).to_string()));
+    };
+
+    let identity = providers
+        .exchange_code(provider, code, &authorization)
+        .await?
+        .ok_or(CoreError::Unauthorized)?;
+
+    let mut connection = pool.transaction().await.map_err(UserError::from)?;
+
+    let user = match authorization.link_target() {
+        Some(member_id) => {
+            let mut user = AuthenticatedUser::by_id(member_id, &mut connection)
+                .await?
+                .validate_link_intent(&authorization)?;
+
+            user.link_oauth_identity(&identity, &mut connection).await?;
+            user
+        },
+        None => match AuthenticatedUser::by_oauth_identity(&identity, &mut connection).await {
+            Ok(user) => user,
+            // No account linked to this identity yet, so have the user pick a name for a new one
+            Err(UserError::Core(CoreError::Unauthorized)) => {
+                return Ok(same_site_redirect(&format!(
+                    "/register/#provider={}&ticket={}",
+                    provider,
+                    identity.registration_ticket()
+                )))
+            },
+            Err(err) => return Err(err.into()),
+        },
+    };
 
-    let maybe_linked_user = AuthenticatedUser::by_validated_google_creds(&validated_credentials, &mut *pool.connection().await?).await;
+    connection.commit().await.map_err(UserError::from)?;
+
+    build_cookies(&user, cookies)?;
+
+    Ok(same_site_redirect(&rocket::uri!(account_page).to_string()))
+}
+
+/// Signs in using an ID token issued by the given provider, or links the identity to the current
+/// account if already signed in
+#[cfg(feature = "oauth2")]
+#[localized]
+#[rocket::post("/oauth/<provider>/", data = "<payload>")]
+pub async fn oauth_login(
+    provider: &str, payload: Json<UnvalidatedOauthCredential>, auth: Option<Auth<PasswordOrBrowser>>,
+    providers: &State<OauthProviderStore>, pool: &State<PointercratePool>, cookies: &CookieJar<'_>,
+) -> pointercrate_core_api::error::Result<Status> {
+    let identity = providers.validate(provider, &payload.0).await?.ok_or(CoreError::Unauthorized)?;
 
     let authenticated_user = match auth {
-        None => maybe_linked_user?,
+        None => AuthenticatedUser::by_oauth_identity(&identity, &mut *pool.connection().await?).await?,
         Some(mut signed_in_user) => {
-            // Unauthorized = No linked account found. But in the flow that is supposed to establish the link,
-            // that is exactly what we need.
-            if !matches!(maybe_linked_user, Err(UserError::Core(CoreError::Unauthorized))) {
-                return Err(CoreError::Unauthorized.into());
-            }
-
             signed_in_user
                 .user
-                .link_google_account(&validated_credentials, &mut signed_in_user.connection)
+                .link_oauth_identity(&identity, &mut signed_in_user.connection)
                 .await?;
             signed_in_user.connection.commit().await.map_err(UserError::from)?;
             signed_in_user.user
@@ -156,21 +250,30 @@ pub async fn google_oauth_login(
     Ok(Status::NoContent)
 }
 
+/// Registers a new account for an identity at the given provider. The credential is either an ID
+/// token issued by the provider, or the registration ticket handed out at the end of an
+/// authorization code flow.
 #[cfg(feature = "oauth2")]
 #[localized]
-#[rocket::post("/oauth/google/register/", data = "<payload>")]
-pub async fn google_oauth_register(
-    payload: Json<OauthRegistration>, key_store: &State<GoogleCertificateStore>, ip: IpAddr, pool: &State<PointercratePool>,
-    cookies: &rocket::http::CookieJar<'_>,
+#[rocket::post("/oauth/<provider>/register/", data = "<payload>")]
+pub async fn oauth_register(
+    provider: &str, payload: Json<OauthRegistration>, providers: &State<OauthProviderStore>, ip: IpAddr, pool: &State<PointercratePool>,
+    cookies: &CookieJar<'_>,
 ) -> pointercrate_core_api::error::Result<Status> {
     let OauthRegistration { credential, username } = payload.0;
-    let validated_credentials = key_store.validate_with_refresh(credential).await.ok_or(CoreError::Unauthorized)?;
+
+    providers.provider(provider)?;
+
+    let identity = match OauthIdentity::from_registration_ticket(provider, &credential) {
+        Some(identity) => identity,
+        None => providers.validate(provider, &credential).await?.ok_or(CoreError::Unauthorized)?,
+    };
 
     let mut connection = pool.transaction().await.map_err(UserError::from)?;
 
     User::validate_name(&username)?;
 
-    let user = AuthenticatedUser::register_oauth(username, validated_credentials, &mut connection).await?;
+    let user = AuthenticatedUser::register_oauth(username, identity, &mut connection).await?;
 
     connection.commit().await.map_err(UserError::from)?;
 
@@ -0,0 +1,126 @@
+use crate::account::AccountPageTab;
+use maud::{html, Markup, PreEscaped};
+use pointercrate_core::{
+    localization::{tr, LocaleConfiguration},
+    maintenance::MaintenanceSettings,
+    permission::PermissionsManager,
+};
+use pointercrate_user::{
+    auth::{AuthenticatedUser, NonMutating},
+    ADMINISTRATOR,
+};
+use sqlx::PgConnection;
+
+pub struct MaintenanceTab;
+
+#[async_trait::async_trait]
+impl AccountPageTab for MaintenanceTab {
+    fn should_display_for(&self, permissions_we_have: u16, permissions: &PermissionsManager) -> bool {
+        permissions.require_permission(permissions_we_have, ADMINISTRATOR).is_ok()
+    }
+
+    fn initialization_script(&self) -> String {
+        "/static/user/js/account/maintenance.js".into()
+    }
+
+    fn tab_id(&self) -> u8 {
+        8
+    }
+
+    fn tab(&self) -> Markup {
+        html! {
+            b {
+                (tr("maintenance"))
+            }
+            (PreEscaped("&nbsp;&nbsp;"))
+            i class = "fa fa-wrench fa-2x" aria-hidden="true" {}
+        }
+    }
+
+    async fn content(
+        &self, _user: &AuthenticatedUser<NonMutating>, _permissions: &PermissionsManager, connection: &mut PgConnection,
+    ) -> Markup {
+        let settings = MaintenanceSettings::load(connection).await.unwrap_or_default();
+        // The format expected by datetime-local inputs
+        let starts_at = settings.starts_at.map(|time| time.format("%Y-%m-%dT%H:%M").to_string());
+        let ends_at = settings.ends_at.map(|time| time.format("%Y-%m-%dT%H:%M").to_string());
+
+        html! {
+            div.left {
+                div.panel.fade {
+                    h2.underlined.pad {
+                        (tr("maintenance-settings"))
+                    }
+                    p {
+                        (tr("maintenance-settings.info"))
+                    }
+                    p.info-yellow #maintenance-status {
+                        @if settings.is_active() {
+                            (tr("maintenance-settings.status-active"))
+                        } @else if settings.is_upcoming() {
+                            (tr("maintenance-settings.status-upcoming"))
+                        } @else {
+                            (tr("maintenance-settings.status-inactive"))
+                        }
+                    }
+                    form.flex.col.pad #maintenance-form novalidate = "" {
+                        p.info-red.output {}
+                        p.info-green.output {}
+
+                        label.cb-container.form-input #maintenance-enabled for = "enabled" {
+                            i {
+                                (tr("maintenance-enabled"))
+                            }
+                            @if settings.enabled {
+                                input type = "checkbox" name = "enabled" checked = "";
+                            }
+                            @else {
+                                input type = "checkbox" name = "enabled";
+                            }
+                            span.checkmark {}
+                        }
+                        span.form-input #maintenance-starts-at {
+                            label for = "starts_at" { (tr("maintenance-starts-at")) }
+                            input name = "starts_at" type = "datetime-local" value = [starts_at];
+                            p.error {}
+                        }
+                        span.form-input #maintenance-ends-at {
+                            label for = "ends_at" { (tr("maintenance-ends-at")) }
+                            input name = "ends_at" type = "datetime-local" value = [ends_at];
+                            p.error {}
+                        }
+                        @for locale in LocaleConfiguration::get().locales() {
+                            @let language = locale.language.as_str();
+
+                            span.form-input.maintenance-message #{"maintenance-message-" (language)} data-language = (language) {
+                                label for = (format!("message-{}", language)) { (tr("maintenance-message")) " (" (locale) ")" }
+                                textarea name = (format!("message-{}", language)) style = "width: 100%" {
+                                    (settings.messages.get(language).map(String::as_str).unwrap_or_default())
+                                }
+                                p.error {}
+                            }
+                        }
+                        span.form-input #maintenance-allowlist {
+                            label for = "allowlist" { (tr("maintenance-allowlist")) }
+                            textarea name = "allowlist" style = "width: 100%" placeholder = "/login/" {
+                                (settings.allowlist.join("\n"))
+                            }
+                            p.error {}
+                        }
+                        input.button.blue.hover type = "submit" style = "margin: 15px auto 0px;" value=(tr("maintenance-settings.submit"));
+                    }
+                }
+            }
+            div.right {
+                div.panel.fade {
+                    h2.underlined.pad {
+                        (tr("maintenance-allowlist"))
+                    }
+                    p {
+                        (tr("maintenance-allowlist.info"))
+                    }
+                }
+            }
+        }
+    }
+}
@@ -7,6 +7,7 @@ use pointercrate_core_pages::{
 use pointercrate_user::auth::{AuthenticatedUser, NonMutating};
 use sqlx::PgConnection;
 
+pub mod maintenance;
 pub mod profile;
 pub mod users;
 
@@ -1,15 +1,11 @@
-use crate::account::AccountPageTab;
-use maud::{html, Markup, PreEscaped};
-use pointercrate_core::{
-    localization::{task_lang, tr},
-    permission::PermissionsManager,
-    trp,
-};
-use pointercrate_core_pages::head::Script;
-use pointercrate_user::{
-    auth::{AuthenticatedUser, NonMutating},
-    config,
+use crate::{
+    account::AccountPageTab,
+    oauth::{linked_accounts, linked_providers, oauth_providers},
 };
+use maud::{html, Markup, PreEscaped};
+use pointercrate_core::{error::PointercrateError, localization::tr, permission::PermissionsManager, trp};
+use pointercrate_core_pages::error::ErrorFragment;
+use pointercrate_user::auth::{AuthenticatedUser, NonMutating};
 use sqlx::PgConnection;
 
 pub struct ProfileTab;
@@ -24,14 +20,6 @@ impl AccountPageTab for ProfileTab {
         "/static/user/js/account/profile.js".into()
     }
 
-    fn additional_scripts(&self) -> Vec<Script> {
-        if cfg!(feature = "oauth2") {
-            vec![Script::r#async("https://accounts.google.com/gsi/client")]
-        } else {
-            Vec::new()
-        }
-    }
-
     fn tab_id(&self) -> u8 {
         1
     }
@@ -47,14 +35,25 @@ impl AccountPageTab for ProfileTab {
     }
 
     async fn content(
-        &self, authenticated_user: &AuthenticatedUser<NonMutating>, permissions: &PermissionsManager, _connection: &mut PgConnection,
+        &self, authenticated_user: &AuthenticatedUser<NonMutating>, permissions: &PermissionsManager, connection: &mut PgConnection,
     ) -> Markup {
         let user = authenticated_user.user();
 
         let permissions = permissions.bits_to_permissions(user.permissions);
         let permission_string = permissions.iter().map(|perm| tr(perm.text_id())).collect::<Vec<_>>().join(", ");
 
-        let lang = task_lang();
+        let providers = oauth_providers();
+        let linked = match linked_providers(user.id, connection).await {
+            Ok(linked) => linked,
+            Err(err) => {
+                return ErrorFragment {
+                    status: err.status_code(),
+                    reason: "Internal Server Error".to_string(),
+                    message: err.to_string(),
+                }
+                .body()
+            },
+        };
 
         html! {
             div.left {
@@ -132,7 +131,7 @@ impl AccountPageTab for ProfileTab {
                         (tr("profile-logout.button"))
                     }
                 }
-                @if cfg!(feature = "oauth2") && authenticated_user.is_legacy() {
+                @if !providers.is_empty() {
                     div.panel.fade {
                         h2.underlined.pad {
                             (tr("profile-oauth"))
@@ -140,16 +139,32 @@ impl AccountPageTab for ProfileTab {
                         p {
                             (tr("profile-oauth.info"))
                         }
-                        div #g_id_onload
-                            data-ux_mode="popup"
-                            data-auto_select="true"
-                            data-itp_support="true"
-                            data-client_id=(config::google_client_id())
-                            data-callback="googleOauthCallback" {}
-
-                        script src=(format!("https://accounts.google.com/gsi/client?hl={}", &lang)) async {}
-                        div .g_id_signin data-text="continue_with" style="margin: 10px 0px" data-locale=(lang) {}
-                        p.error #g-signin-error style="text-align: left" {}
+                        (linked_accounts(&providers, &linked))
+                    }
+                }
+                @if authenticated_user.is_legacy() {
+                    div.panel.fade {
+                        h2.underlined.pad {
+                            (tr("profile-totp"))
+                        }
+                        @if authenticated_user.has_two_factor() {
+                            p {
+                                (tr("profile-totp.info-enabled"))
+                            }
+                            input.button.red.hover #disable-totp type = "button" style = "margin: 15px auto 0px;" value=(tr("profile-totp.disable"));
+                        } @else {
+                            p {
+                                (tr("profile-totp.info-disabled"))
+                            }
+                            input.button.blue.hover #enable-totp type = "button" style = "margin: 15px auto 0px;" value=(tr("profile-totp.enable"));
+                        }
+                        div.overlined.pad #recovery-codes-area style = "display: none" {
+                            b {(tr("profile-totp.recovery-codes-header")) }
+                            p {
+                                (tr("profile-totp.recovery-codes-info"))
+                            }
+                            textarea #recovery-codes readonly="" style = "resize: none; width: 100%; margin-top: 8px; min-height:175px" {}
+                        }
                     }
                 }
                 div.panel.fade {
@@ -184,7 +199,12 @@ impl AccountPageTab for ProfileTab {
             (edit_display_name_dialog())
             (edit_youtube_link_dialog())
             @if authenticated_user.is_legacy() {
-                (change_password_dialog())
+                (change_password_dialog(authenticated_user.has_two_factor()))
+                @if authenticated_user.has_two_factor() {
+                    (disable_totp_dialog())
+                } @else {
+                    (enable_totp_dialog())
+                }
             }
             (delete_account_dialog())
         }
@@ -237,7 +257,7 @@ fn edit_youtube_link_dialog() -> Markup {
     }
 }
 
-fn change_password_dialog() -> Markup {
+fn change_password_dialog(has_two_factor: bool) -> Markup {
     html! {
         div.overlay.closable {
             div.dialog #edit-pw-dialog {
@@ -266,6 +286,13 @@ fn change_password_dialog() -> Markup {
                         input type = "password" minlength = "10" required = "";
                         p.error {}
                     }
+                    @if has_two_factor {
+                        span.form-input #auth-totp {
+                            label {(tr("auth-totp")) }
+                            input type = "text" autocomplete = "one-time-code" required = "";
+                            p.error {}
+                        }
+                    }
                     input.button.blue.hover type = "submit" style = "margin: 15px auto 0px;" value=(tr("profile-change-password.dialog-submit"));
                 }
             }
@@ -273,6 +300,73 @@ fn change_password_dialog() -> Markup {
     }
 }
 
+fn enable_totp_dialog() -> Markup {
+    html! {
+        div.overlay.closable {
+            div.dialog #enable-totp-dialog {
+                span.plus.cross.hover {}
+                h2.underlined.pad {
+                    (tr("profile-totp.enable-dialog-header"))
+                }
+                p {
+                    (tr("profile-totp.enable-dialog-info"))
+                }
+                p {
+                    b {(tr("profile-totp.enable-dialog-secret")) " "}
+                    code #totp-secret {}
+                }
+                p {
+                    a.link #totp-uri {(tr("profile-totp.enable-dialog-link"))}
+                }
+                form.flex.col novalidate = "" {
+                    p.info-red.output {}
+                    span.form-input #enable-totp-pw {
+                        label {(tr("auth-password")) }
+                        input type = "password" minlength = "10" required = "";
+                        p.error {}
+                    }
+                    span.form-input #enable-totp-code {
+                        label {(tr("auth-totp")) }
+                        input type = "text" autocomplete = "one-time-code" required = "";
+                        p.error {}
+                    }
+                    input.button.blue.hover type = "submit" style = "margin: 15px auto 0px;" value=(tr("profile-totp.enable-dialog-submit"));
+                }
+            }
+        }
+    }
+}
+
+fn disable_totp_dialog() -> Markup {
+    html! {
+        div.overlay.closable {
+            div.dialog #disable-totp-dialog {
+                span.plus.cross.hover {}
+                h2.underlined.pad {
+                    (tr("profile-totp.disable-dialog-header"))
+                }
+                p {
+                    (tr("profile-totp.disable-dialog-info"))
+                }
+                form.flex.col novalidate = "" {
+                    p.info-red.output {}
+                    span.form-input #disable-totp-pw {
+                        label {(tr("auth-password")) }
+                        input type = "password" minlength = "10" required = "";
+                        p.error {}
+                    }
+                    span.form-input #disable-totp-code {
+                        label {(tr("auth-totp")) }
+                        input type = "text" autocomplete = "one-time-code" required = "";
+                        p.error {}
+                    }
+                    input.button.red.hover type = "submit" style = "margin: 15px auto 0px;" value=(tr("profile-totp.disable-dialog-submit"));
+                }
+            }
+        }
+    }
+}
+
 fn delete_account_dialog() -> Markup {
     html! {
         div.overlay.closable {
@@ -1,10 +1,10 @@
+use crate::oauth::{oauth_buttons, oauth_providers};
 use maud::{html, Markup};
-use pointercrate_core::localization::{task_lang, tr};
+use pointercrate_core::localization::tr;
 use pointercrate_core_pages::{head::HeadLike, trp_html, PageFragment};
-use pointercrate_user::config;
 
 pub fn login_page() -> PageFragment {
-    let mut frag = PageFragment::new(
+    PageFragment::new(
         "Pointercrate - Login",
         "Log in to an existing pointercrate account or register for a new one!",
     )
@@ -12,17 +12,11 @@ pub fn login_page() -> PageFragment {
     .module("/static/core/js/modules/form.js")
     .module("/static/core/js/modules/tab.js")
     .stylesheet("/static/user/css/login.css")
-    .body(login_page_body());
-
-    if cfg!(feature = "oauth2") {
-        frag = frag.async_script("https://accounts.google.com/gsi/client");
-    }
-
-    frag
+    .body(login_page_body())
 }
 
 fn login_page_body() -> Markup {
-    let lang = task_lang();
+    let providers = oauth_providers();
 
     html! {
         div.center #login style="display: flex; align-items: center; justify-content: center; height: calc(100% - 70px)" { // 70px = height of nav bar
@@ -32,20 +26,11 @@ fn login_page_body() -> Markup {
                         (tr("login"))
                     }
 
-                    @if cfg!(feature = "oauth2") {
+                    @if !providers.is_empty() {
                         p {
                             (tr("login.oauth-info"))
                         }
-                        div #g_id_onload
-                            data-ux_mode="popup"
-                            data-auto_select="true"
-                            data-itp_support="true"
-                            data-client_id=(config::google_client_id())
-                            data-callback="googleOauthCallback" {}
-
-                        script src=(format!("https://accounts.google.com/gsi/client?hl={}", &lang)) async {}
-                        div .g_id_signin data-text="continue_with" style="margin: 10px 0px" data-locale=(lang) {}
-                        p.error #g-signin-error style="text-align: left" {}
+                        (oauth_buttons(&providers))
 
                         p.or style="text-size: small; margin: 0px" { (tr("login.methods-separator")) }
                     }
@@ -66,6 +51,12 @@ fn login_page_body() -> Markup {
                             input required = "" type = "password" name = "password" minlength = "10";
                             p.error {}
                         }
+                        // Only shown once the server tells us that the account has two-factor authentication enabled
+                        span.form-input #login-totp style = "display: none" {
+                            label for = "totp" {(tr("auth-totp")) }
+                            input type = "text" name = "totp" autocomplete = "one-time-code";
+                            p.error {}
+                        }
                         input.button.blue.hover type = "submit" style = "margin: 15px auto 0px;" value = (tr("login.submit"));
                     }
                 }
@@ -0,0 +1,72 @@
+use maud::{html, Markup};
+use pointercrate_core::{localization::tr, trp};
+use pointercrate_user::error::UserError;
+use sqlx::PgConnection;
+
+#[cfg(feature = "oauth2")]
+use pointercrate_user::{auth::oauth::LinkedIdentity, config};
+
+/// The ids and display names of the configured OAuth2 providers
+///
+/// Empty if the configuration is invalid, which would have prevented the rocket from launching
+/// already.
+pub(crate) fn oauth_providers() -> Vec<(&'static str, &'static str)> {
+    #[cfg(feature = "oauth2")]
+    return config::oauth_providers()
+        .unwrap_or_default()
+        .iter()
+        .map(|provider| (provider.id.as_str(), provider.display_name.as_str()))
+        .collect();
+
+    #[cfg(not(feature = "oauth2"))]
+    Vec::new()
+}
+
+/// Buttons starting the authorization code flow with each of the given providers
+pub(crate) fn oauth_buttons(providers: &[(&str, &str)]) -> Markup {
+    html! {
+        div.flex.col style="margin: 10px 0px" {
+            @for (id, name) in providers {
+                a.button.blue.hover href=(format!("/oauth/{}/", id)) style="margin: 5px 0px" {
+                    (trp!("oauth-continue", "provider" = name))
+                }
+            }
+        }
+    }
+}
+
+/// Lists the configured providers, showing which ones are linked to the current account and
+/// offering to link the others
+pub(crate) fn linked_accounts(providers: &[(&str, &str)], linked: &[String]) -> Markup {
+    html! {
+        @for (id, name) in providers {
+            @if linked.iter().any(|provider| provider == id) {
+                p {
+                    b { (name) ": " }
+                    (tr("profile-oauth.linked"))
+                }
+            } @else {
+                a.button.blue.hover href=(format!("/oauth/{}/", id)) style="margin: 5px 0px; display: inline-block" {
+                    (trp!("oauth-link", "provider" = name))
+                }
+            }
+        }
+    }
+}
+
+/// The ids of the providers the given account has linked identities at
+pub(crate) async fn linked_providers(member_id: i32, connection: &mut PgConnection) -> Result<Vec<String>, UserError> {
+    #[cfg(feature = "oauth2")]
+    return Ok(LinkedIdentity::all_of(member_id, connection)
+        .await?
+        .into_iter()
+        .map(|identity| identity.provider)
+        .collect());
+
+    #[cfg(not(feature = "oauth2"))]
+    {
+        let _ = (member_id, connection);
+
+        Ok(Vec::new())
+    }
+}
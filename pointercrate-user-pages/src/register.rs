@@ -1,27 +1,20 @@
+use crate::oauth::{oauth_buttons, oauth_providers};
 use maud::{html, Markup};
-use pointercrate_core::localization::task_lang;
 use pointercrate_core::localization::tr;
 use pointercrate_core_pages::head::HeadLike;
 use pointercrate_core_pages::{trp_html, PageFragment};
-use pointercrate_user::config;
 
 pub fn registration_page() -> PageFragment {
-    let mut frag = PageFragment::new("Pointercrate - Registration", "Register for a new pointercrate account!")
+    PageFragment::new("Pointercrate - Registration", "Register for a new pointercrate account!")
         .module("/static/user/js/register.js")
         .module("/static/core/js/modules/form.js")
         .module("/static/core/js/modules/tab.js")
         .stylesheet("/static/user/css/login.css")
-        .body(register_page_body());
-
-    if cfg!(feature = "oauth2") {
-        frag = frag.async_script("https://accounts.google.com/gsi/client");
-    }
-
-    frag
+        .body(register_page_body())
 }
 
 fn register_page_body() -> Markup {
-    let lang = task_lang();
+    let providers = oauth_providers();
 
     html! {
         div.center #register style="display: flex; align-items: center; justify-content: center; height: calc(100% - 70px)" { // 70px = height of nav bar
@@ -33,16 +26,8 @@ fn register_page_body() -> Markup {
                     p {
                         (tr("register.info"))
                     }
-                    @if cfg!(feature = "oauth2") {
-                        div #g_id_onload
-                            data-ux_mode="popup"
-                            data-auto_select="true"
-                            data-itp_support="true"
-                            data-client_id=(config::google_client_id())
-                            data-callback="googleOauthRegisterCallback" {}
-
-                        script src=(format!("https://accounts.google.com/gsi/client?hl={}", &lang)) async {}
-                        div .g_id_signin data-text="signup_with" style="margin: 10px 0px" {}
+                    @if !providers.is_empty() {
+                        (oauth_buttons(&providers))
                         @if cfg!(feature = "legacy_accounts") {
                             p.or style="text-size: small; margin: 0px" { (tr("login.methods-separator")) }
                         }
@@ -4,11 +4,21 @@ error-user-patchself = You cannot modify your own account via this endpoint. Use
 error-user-permissionnotassignable = You cannot assign the following permissions: { $non-assignable }
 error-user-usernotfound = No user with id { $user-id } found
 error-user-usernotfoundname = No user with name { $user-name } found
+error-user-apitokennotfound = You have no access token with id { $token-id }
+error-user-oauthprovidernotfound = No login provider with id '{ $provider }' is configured
 error-user-nametaken = The chosen username is already taken
+error-user-tokennametaken = You already have an access token with this name
+error-user-oauthidentitytaken = This { $provider } account is already linked to a different pointercrate account
+error-user-oauthprovideralreadylinked = Your account is already linked with a { $provider } account
 error-user-invalidusername = Invalid display or username! The name must be at least 3 characters long and not start/end with a space
 error-user-invalidpassword = Invalid password! The password must be at least 10 characters long
 error-user-notyoutube = The given URL is no YouTube URL
 error-user-nonlegacyaccount = The given operation (change password) is invalid on non-legacy account, as password login is not supported for these
+error-user-invalidtotpsecret = Invalid TOTP secret! The secret must be base32 encoded and at least 128 bits long
+error-user-invalidtotpcode = The given code does not match the TOTP secret. Please check that your authenticator app is set up correctly
+error-user-invalidtokenname = Invalid token name! The name must be between 1 and 64 characters long and not start/end with a space
+error-user-invalidscope = Invalid scope "{ $scope }"! Scopes must have the form "<resource>:<read|create|write>", and at least one scope is required
+error-user-invalidtokenlifetime = Invalid token lifetime! Access tokens must expire within one year
 
 error-user-ratelimit-registration = Too many registrations!
 error-user-ratelimit-soft-registration = Too many failed registration attempts!
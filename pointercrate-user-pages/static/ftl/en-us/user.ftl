@@ -21,6 +21,8 @@ auth-password = Password:
     .validator-tooshort = Password too short. It needs to be at least 10 characters long.
 auth-repeatpassword = Repeat Password:
     .validator-notmatching = Passwords don't match
+auth-totp = Two-factor code:
+    .validator-valuemissing = Code required
 
 ## Login/registration forms
 #
@@ -29,7 +31,7 @@ auth-repeatpassword = Repeat Password:
 # in the .redirect attributes
 #
 login = Sign In
-    .oauth-info = If you have linked your pointercrate account with a Google account, you must sign in via Google oauth by clicking the button below:
+    .oauth-info = If you have linked your pointercrate account with an external account, you must sign in with it by clicking the corresponding button below:
 
     .methods-separator = otherwise
 
@@ -37,6 +39,7 @@ login = Sign In
     .submit = Sign In
 
     .error-invalidcredentials = Invalid credentials
+    .error-invalidtotp = Invalid two-factor code
 
     .redirect = Already have a pointercrate account? { $redirect-link } instead.
     .redirect-link = Sign in
@@ -48,6 +51,9 @@ register = Sign Up
     .redirect = Don't have a pointercrate account yet? { $redirect-link } for one!
     .redirect-link = Sign up
 
+oauth-continue = Continue with { $provider }
+oauth-link = Link { $provider } account
+
 register-oauth = Pick your username:
     .submit = Sign up!
 
@@ -141,5 +147,44 @@ profile-invalidate-tokens = Invalidate tokens
     .info = If one of your access tokens ever got leaked, you can invalidate them here. Invalidating will cause all access tokens to your account to stop functioning. This includes the one stored inside the browser currently, meaning you'll have to log in again after this action!
     .button = Invalidate all access tokens
 
-profile-oauth = Link With Google
-    .info = Enable signing in to your pointercrate account via Google oauth. More secure than password login, and avoids account lock-outs due to forgotten passwords. Linking a Google account is irreversible, and you cannot change the linked Google account later on!
+profile-oauth = Linked Accounts
+    .info = Enable signing in to your pointercrate account via an external account. More secure than password login, and avoids account lock-outs due to forgotten passwords. Linking an account is irreversible, and once linked, you can no longer sign in using your password!
+    .linked = Linked
+
+profile-totp = Two-Factor Authentication
+    .info-disabled = Protect your account by requiring a code from an authenticator app (such as Google Authenticator or Aegis) in addition to your password when signing in.
+    .info-enabled = Two-factor authentication is enabled for your account. Signing in requires a code from your authenticator app, or one of your recovery codes.
+    .enable = Enable two-factor authentication
+    .disable = Disable two-factor authentication
+
+    .enable-dialog-header = Enable Two-Factor Authentication
+    .enable-dialog-info = Add the secret below to your authenticator app, either by entering it manually or by opening the link on your phone. Then enter your password and the code shown by the app to confirm the setup.
+    .enable-dialog-secret = Secret:
+    .enable-dialog-link = Open in authenticator app
+    .enable-dialog-submit = Enable
+
+    .disable-dialog-header = Disable Two-Factor Authentication
+    .disable-dialog-info = To disable two-factor authentication, re-enter your password and a two-factor code below.
+    .disable-dialog-submit = Disable
+
+    .recovery-codes-header = Your recovery codes
+    .recovery-codes-info = Each of these codes can be used once instead of a two-factor code, in case you lose access to your authenticator app. Store them somewhere safe, they will not be shown again!
+
+## Maintenance tab
+maintenance = Maintenance
+
+maintenance-settings = Maintenance Mode
+    .info = While maintenance mode is active, the website is read-only: all changes (such as record submissions) are rejected. Maintenance mode can either be switched on right away, or be scheduled for a specific time window. All times are in UTC.
+    .status-active = Maintenance mode is currently active.
+    .status-upcoming = Maintenance mode is scheduled.
+    .status-inactive = Maintenance mode is currently inactive.
+    .submit = Save
+    .edit-success = Successfully updated maintenance settings!
+
+maintenance-enabled = Enable maintenance mode now
+maintenance-starts-at = Scheduled start (UTC):
+maintenance-ends-at = Scheduled end (UTC):
+maintenance-message = Banner message
+
+maintenance-allowlist = Allowlisted routes
+    .info = Administrators can still make changes via these routes while maintenance mode is active, one route per line. Routes ending in '*' match all routes starting with the given prefix, e.g. '/api/v1/auth/*'. The maintenance settings themselves can always be changed.
@@ -4,11 +4,21 @@ error-user-patchself = Вы не можете изменять свой акка
 error-user-permissionnotassignable = Вы не можете назначать следующие права: { $non-assignable }
 error-user-usernotfound = Пользователь с id { $user-id } не был найден
 error-user-usernotfoundname = Пользователь с именем { $user-name } не был найден
+error-user-apitokennotfound = У вас нет токена доступа с id { $token-id }
+error-user-oauthprovidernotfound = Провайдер входа с id '{ $provider }' не настроен
 error-user-nametaken = Выбранный никнейм уже занят
+error-user-tokennametaken = У вас уже есть токен доступа с таким названием
+error-user-oauthidentitytaken = Этот аккаунт { $provider } уже связан с другим аккаунтом pointercrate
+error-user-oauthprovideralreadylinked = Ваш аккаунт уже связан с аккаунтом { $provider }
 error-user-invalidusername = Неправильное отображаемое имя или логин! Имя должно быть как минимум 3 символа в длину и не начинаться либо заканчиваться пробелом
 error-user-invalidpassword = Неверный пароль! Пароль должен быть минимум 10 символов в длину
 error-user-notyoutube = Данная ссылка не является YouTube-ссылкой
 error-user-nonlegacyaccount = Данная операция (изменение пароля) не является валидной на новом типе аккаунтов, так как вход по паролю для них не поддерживается
+error-user-invalidtotpsecret = Неверный секрет TOTP! Секрет должен быть закодирован в base32 и иметь длину не менее 128 бит
+error-user-invalidtotpcode = Указанный код не соответствует секрету TOTP. Пожалуйста, проверьте, правильно ли настроено ваше приложение-аутентификатор
+error-user-invalidtokenname = Неверное название токена! Название должно быть от 1 до 64 символов в длину и не начинаться либо заканчиваться пробелом
+error-user-invalidscope = Неверная область доступа "{ $scope }"! Области доступа должны иметь вид "<ресурс>:<read|create|write>", и требуется хотя бы одна область
+error-user-invalidtokenlifetime = Неверный срок действия токена! Токены доступа должны истекать в течение одного года
 
 error-user-ratelimit-registration = Слишком много попыток регистрации!
 error-user-ratelimit-soft-registration = Слишком много проваленных попыток регистрации!
@@ -21,6 +21,8 @@ auth-password = Пароль:
     .validator-tooshort = Пароль слишком короткий. Он должен быть как минимум 10 символов в длину.
 auth-repeatpassword = Повторите пароль:
     .validator-notmatching = Пароли не совпадают
+auth-totp = Код двухфакторной аутентификации:
+    .validator-valuemissing = Требуется код
 
 ## Login/registration forms
 #
@@ -29,7 +31,7 @@ auth-repeatpassword = Повторите пароль:
 # in the .redirect attributes
 #
 login = Вход
-    .oauth-info = Если вы связали свой аккаунт pointercrate с аккаунтом Google, вы должны войти через Google, нажав на кнопку ниже:
+    .oauth-info = Если вы связали свой аккаунт pointercrate с внешним аккаунтом, вы должны войти через него, нажав на соответствующую кнопку ниже:
 
     .methods-separator = либо
 
@@ -37,6 +39,7 @@ login = Вход
     .submit = Войти
 
     .error-invalidcredentials = Неверные данные
+    .error-invalidtotp = Неверный код двухфакторной аутентификации
 
     .redirect = Уже есть аккаунт pointercrate? { $redirect-link } в него.
     .redirect-link = Войдите
@@ -48,6 +51,9 @@ register = Регистрация
     .redirect = Еще нет аккаунта pointercrate? { $redirect-link } его!
     .redirect-link = Зарегистрируйте
 
+oauth-continue = Продолжить через { $provider }
+oauth-link = Связать аккаунт { $provider }
+
 register-oauth = Выберите ник:
     .submit = Зарегистрироваться!
 
@@ -141,5 +147,44 @@ profile-invalidate-tokens = Отключить токены
     .info = Если один из ваших токенов доступа был слит в сеть, вы можете отключить их здесь. Отключение приведет к потере функциональности всех токенов доступа, связанных с вашим аккаунтом. Это включает в себя хранящийся в браузере на данный момент токен, что означает необходимость повторной авторизации после выполнения этого действия!
     .button = Отключить все токены доступа
 
-profile-oauth = Связь с Google
-    .info = Здесь проходит включение возможности входа в ваш аккаунт pointercrate через Google. Эта опция более защищена, чем традиционный метод входа, и предотвращает потерю доступа к аккаунту из-за забытого пароля. Связь с аккаунтом Google отменить нельзя, и вы не можете поменять связанный аккаунт Google на другой!
+profile-oauth = Связанные аккаунты
+    .info = Здесь проходит включение возможности входа в ваш аккаунт pointercrate через внешний аккаунт. Эта опция более защищена, чем традиционный метод входа, и предотвращает потерю доступа к аккаунту из-за забытого пароля. Связь с аккаунтом отменить нельзя, и после неё вход по паролю станет невозможен!
+    .linked = Связан
+
+profile-totp = Двухфакторная аутентификация
+    .info-disabled = Защитите свой аккаунт, требуя при входе помимо пароля код из приложения-аутентификатора (например, Google Authenticator или Aegis).
+    .info-enabled = Для вашего аккаунта включена двухфакторная аутентификация. Для входа требуется код из приложения-аутентификатора или один из ваших кодов восстановления.
+    .enable = Включить двухфакторную аутентификацию
+    .disable = Отключить двухфакторную аутентификацию
+
+    .enable-dialog-header = Включение двухфакторной аутентификации
+    .enable-dialog-info = Добавьте секрет ниже в своё приложение-аутентификатор, введя его вручную или открыв ссылку на телефоне. Затем введите ваш пароль и показанный приложением код для подтверждения.
+    .enable-dialog-secret = Секрет:
+    .enable-dialog-link = Открыть в приложении-аутентификаторе
+    .enable-dialog-submit = Включить
+
+    .disable-dialog-header = Отключение двухфакторной аутентификации
+    .disable-dialog-info = Для отключения двухфакторной аутентификации повторно введите ваш пароль и код двухфакторной аутентификации ниже.
+    .disable-dialog-submit = Отключить
+
+    .recovery-codes-header = Ваши коды восстановления
+    .recovery-codes-info = Каждый из этих кодов можно один раз использовать вместо кода двухфакторной аутентификации, если вы потеряете доступ к приложению-аутентификатору. Сохраните их в надёжном месте, они больше не будут показаны!
+
+## Maintenance tab
+maintenance = Обслуживание
+
+maintenance-settings = Режим обслуживания
+    .info = Пока активен режим обслуживания, сайт доступен только для чтения: все изменения (например, отправка рекордов) отклоняются. Режим обслуживания можно включить сразу или запланировать на определённый промежуток времени. Всё время указано в UTC.
+    .status-active = Режим обслуживания сейчас активен.
+    .status-upcoming = Режим обслуживания запланирован.
+    .status-inactive = Режим обслуживания сейчас неактивен.
+    .submit = Сохранить
+    .edit-success = Настройки обслуживания успешно обновлены!
+
+maintenance-enabled = Включить режим обслуживания сейчас
+maintenance-starts-at = Запланированное начало (UTC):
+maintenance-ends-at = Запланированный конец (UTC):
+maintenance-message = Сообщение баннера
+
+maintenance-allowlist = Разрешённые пути
+    .info = Администраторы могут вносить изменения через эти пути, пока активен режим обслуживания, по одному пути на строку. Пути, оканчивающиеся на '*', охватывают все пути с данным префиксом, например '/api/v1/auth/*'. Настройки обслуживания можно изменить всегда.
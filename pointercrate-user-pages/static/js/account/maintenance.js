@@ -0,0 +1,64 @@
+"use strict";
+
+import { displayError, patch, Form } from "/static/core/js/modules/form.js";
+import { tr } from "/static/core/js/modules/localization.js";
+
+// datetime-local inputs omit the seconds, but the server expects them
+function toDateTime(value) {
+  if (value === null) return null;
+
+  return value.length == 16 ? value + ":00" : value;
+}
+
+function setupMaintenanceForm() {
+  let maintenanceForm = new Form(document.getElementById("maintenance-form"));
+  let status = document.getElementById("maintenance-status");
+
+  maintenanceForm.onSubmit(() => {
+    let messages = {};
+
+    for (let input of maintenanceForm.inputs) {
+      if (input.span.classList.contains("maintenance-message") && input.value !== null) {
+        messages[input.span.dataset.language] = input.value;
+      }
+    }
+
+    let allowlist = maintenanceForm.input("maintenance-allowlist").value || "";
+
+    patch(
+      "/api/v1/maintenance/",
+      {},
+      {
+        enabled: maintenanceForm.input("maintenance-enabled").value,
+        starts_at: toDateTime(maintenanceForm.input("maintenance-starts-at").value),
+        ends_at: toDateTime(maintenanceForm.input("maintenance-ends-at").value),
+        messages: messages,
+        allowlist: allowlist
+          .split("\n")
+          .map((route) => route.trim())
+          .filter((route) => route !== ""),
+      }
+    )
+      .then((response) => {
+        let settings = response.data;
+
+        if (settings.active) {
+          status.innerText = tr("user", "user", "maintenance-settings.status-active");
+        } else if (
+          settings.starts_at !== null &&
+          new Date(settings.starts_at + "Z") > new Date()
+        ) {
+          status.innerText = tr("user", "user", "maintenance-settings.status-upcoming");
+        } else {
+          status.innerText = tr("user", "user", "maintenance-settings.status-inactive");
+        }
+
+        maintenanceForm.setSuccess(tr("user", "user", "maintenance-settings.edit-success"));
+      })
+      .catch(displayError(maintenanceForm));
+  });
+}
+
+export function initialize() {
+  setupMaintenanceForm();
+}
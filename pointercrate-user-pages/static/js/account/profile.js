@@ -6,6 +6,7 @@ import {
   EditorBackend,
   Form,
   Output,
+  patch,
   post,
   setupEditorDialog,
   setupFormDialogEditor,
@@ -31,10 +32,11 @@ function setupGetAccessToken() {
 }
 
 class ProfileEditorBackend extends EditorBackend {
-  constructor(passwordInput) {
+  constructor(passwordInput, totpInput) {
     super();
 
     this._pw = passwordInput;
+    this._totp = totpInput;
     this._displayName = document.getElementById("profile-display-name");
     this._youtube = document.getElementById("profile-youtube-channel");
   }
@@ -48,6 +50,7 @@ class ProfileEditorBackend extends EditorBackend {
     if (this._pw)
       headers["Authorization"] =
         "Basic " + btoa(window.username + ":" + this._pw.value);
+    if (this._totp) headers["X-TOTP-CODE"] = this._totp.value;
     return headers;
   }
 
@@ -76,14 +79,14 @@ function setupEditAccount() {
   let output = new Output(document.getElementById("things"));
 
   setupFormDialogEditor(
-    new ProfileEditorBackend(null),
+    new ProfileEditorBackend(null, null),
     "edit-dn-dialog",
     "display-name-pen",
     output
   );
 
   let editYoutubeForm = setupFormDialogEditor(
-    new ProfileEditorBackend(null),
+    new ProfileEditorBackend(null, null),
     "edit-yt-dialog",
     "youtube-pen",
     output
@@ -101,7 +104,10 @@ function setupEditAccount() {
 
   if (document.getElementById("change-password")) {
     let changePasswordForm = setupFormDialogEditor(
-      new ProfileEditorBackend(document.querySelector("#auth-pw input")), // not pretty, but oh well
+      new ProfileEditorBackend(
+        document.querySelector("#auth-pw input"), // not pretty, but oh well
+        document.querySelector("#auth-totp input")
+      ),
       "edit-pw-dialog",
       "change-password",
       output
@@ -144,6 +150,7 @@ function setupEditAccount() {
     });
 
     changePasswordForm.addErrorOverride(40100, "auth-pw");
+    changePasswordForm.addErrorOverride(40101, "auth-totp");
   }
 
   var deleteAccountDialog = document.getElementById("delete-acc-dialog");
@@ -163,6 +170,147 @@ function setupEditAccount() {
   });
 }
 
+// RFC 4648 base32, as expected by authenticator apps
+function base32Encode(bytes) {
+  const alphabet = "ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+  let result = "";
+  let buffer = 0;
+  let bits = 0;
+
+  for (let byte of bytes) {
+    buffer = (buffer << 8) | byte;
+    bits += 8;
+
+    while (bits >= 5) {
+      result += alphabet[(buffer >>> (bits - 5)) & 31];
+      bits -= 5;
+    }
+  }
+
+  if (bits > 0) result += alphabet[(buffer << (5 - bits)) & 31];
+
+  return result;
+}
+
+function setupEnableTotp() {
+  let enableButton = document.getElementById("enable-totp");
+
+  if (!enableButton) return;
+
+  let enableDialog = document.getElementById("enable-totp-dialog");
+  let enableForm = new Form(enableDialog.getElementsByTagName("form")[0]);
+  let password = enableForm.input("enable-totp-pw");
+  let code = enableForm.input("enable-totp-code");
+  let secret = null;
+
+  enableForm.addValidators({
+    "enable-totp-pw": {
+      [tr(
+        "user",
+        "user",
+        "profile-change-password.authenticate-validator-valuemissing"
+      )]: valueMissing,
+      [tr(
+        "user",
+        "user",
+        "profile-change-password.authenticate-validator-tooshort"
+      )]: tooShort,
+    },
+    "enable-totp-code": {
+      [tr("user", "user", "auth-totp.validator-valuemissing")]: valueMissing,
+    },
+  });
+  enableForm.addErrorOverride(40100, "enable-totp-pw");
+  enableForm.addErrorOverride(42238, "enable-totp-code");
+
+  enableButton.addEventListener("click", () => {
+    // 160 bit secret, the length recommended by RFC 4226
+    secret = base32Encode(crypto.getRandomValues(new Uint8Array(20)));
+
+    document.getElementById("totp-secret").innerText = secret;
+    document.getElementById("totp-uri").href =
+      "otpauth://totp/" +
+      encodeURIComponent("pointercrate:" + window.username) +
+      "?secret=" +
+      secret +
+      "&issuer=pointercrate";
+
+    $(enableDialog.parentElement).show();
+  });
+
+  enableForm.onSubmit(() => {
+    patch(
+      "/api/v1/auth/me/",
+      {
+        "If-Match": window.etag,
+        Authorization: "Basic " + btoa(window.username + ":" + password.value),
+      },
+      { totp: { secret: secret, code: code.value } }
+    )
+      .then((response) => {
+        window.etag = response.headers["etag"];
+
+        $(enableDialog.parentElement).hide();
+
+        enableButton.style.display = "none";
+        document.getElementById("recovery-codes").value =
+          response.data.recovery_codes.join("\n");
+        document.getElementById("recovery-codes-area").style.display = "block";
+      })
+      .catch(displayError(enableForm));
+  });
+}
+
+function setupDisableTotp() {
+  let disableButton = document.getElementById("disable-totp");
+
+  if (!disableButton) return;
+
+  let disableDialog = document.getElementById("disable-totp-dialog");
+  let disableForm = new Form(disableDialog.getElementsByTagName("form")[0]);
+  let password = disableForm.input("disable-totp-pw");
+  let code = disableForm.input("disable-totp-code");
+
+  disableForm.addValidators({
+    "disable-totp-pw": {
+      [tr(
+        "user",
+        "user",
+        "profile-change-password.authenticate-validator-valuemissing"
+      )]: valueMissing,
+      [tr(
+        "user",
+        "user",
+        "profile-change-password.authenticate-validator-tooshort"
+      )]: tooShort,
+    },
+    "disable-totp-code": {
+      [tr("user", "user", "auth-totp.validator-valuemissing")]: valueMissing,
+    },
+  });
+  disableForm.addErrorOverride(40100, "disable-totp-pw");
+  disableForm.addErrorOverride(40101, "disable-totp-code");
+
+  disableButton.addEventListener("click", () => {
+    $(disableDialog.parentElement).show();
+  });
+
+  disableForm.onSubmit(() => {
+    patch(
+      "/api/v1/auth/me/",
+      {
+        "If-Match": window.etag,
+        Authorization: "Basic " + btoa(window.username + ":" + password.value),
+        "X-TOTP-CODE": code.value,
+      },
+      { totp: null }
+    )
+      .then(() => window.location.reload())
+      .catch(displayError(disableForm));
+  });
+}
+
 function setupInvalidateToken() {
   var htmlInvalidateForm = document.getElementById("invalidate-form");
   var invalidateForm = new Form(htmlInvalidateForm);
@@ -174,21 +322,10 @@ function setupInvalidateToken() {
   });
 }
 
-function googleOauthCallback(response) {
-  let error = document.getElementById("g-signin-error");
-
-  post("/api/v1/auth/oauth/google/", {}, response)
-    .then(() => window.location.reload())
-    .catch((response) => {
-      error.innerText = response.data.message;
-      error.style.display = "block";
-    });
-}
-
-window.googleOauthCallback = googleOauthCallback;
-
 export function initialize() {
   setupGetAccessToken();
   setupEditAccount();
+  setupEnableTotp();
+  setupDisableTotp();
   setupInvalidateToken();
 }
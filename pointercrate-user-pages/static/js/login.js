@@ -11,6 +11,7 @@ function initializeLoginForm() {
 
   var loginUsername = loginForm.input("login-username");
   var loginPassword = loginForm.input("login-password");
+  var loginTotp = loginForm.input("login-totp");
 
   loginUsername.addValidator(
     valueMissing,
@@ -32,16 +33,34 @@ function initializeLoginForm() {
   );
 
   loginForm.onSubmit(function (event) {
-    post("/login/", {
+    let headers = {
       Authorization:
         "Basic " + btoa(loginUsername.value + ":" + loginPassword.value),
-    })
+    };
+
+    if (loginTotp.value) headers["X-TOTP-CODE"] = loginTotp.value;
+
+    post("/login/", headers)
       .then((response) => {
         window.location = "/account/";
       })
       .catch((response) => {
         console.log(response);
-        if (response.status === 401) {
+        if (response.data && response.data.code === 40101) {
+          let totpInput = document.getElementById("login-totp");
+
+          if (totpInput.style.display === "none") {
+            // First time we learn that this account uses two-factor authentication
+            totpInput.style.display = "block";
+            loginForm.setError(response.data.message);
+          } else {
+            loginTotp.errorText = tr(
+              "user",
+              "user",
+              "login.error-invalidtotp"
+            );
+          }
+        } else if (response.status === 401) {
           loginPassword.errorText = tr(
             "user",
             "user",
@@ -54,19 +73,6 @@ function initializeLoginForm() {
   });
 }
 
-function googleOauthCallback(response) {
-  let error = document.getElementById("g-signin-error");
-
-  post("/api/v1/auth/oauth/google/", {}, response)
-    .then(() => (window.location = "/account/"))
-    .catch((response) => {
-      error.innerText = response.data.message;
-      error.style.display = "block";
-    });
-}
-
-window.googleOauthCallback = googleOauthCallback;
-
 $(window).on("load", function () {
   initializeLoginForm();
 });
@@ -65,21 +65,31 @@ function intializeRegisterForm() {
   });
 }
 
-function googleOauthRegisterCallback(response) {
+// At the end of the authorization flow with an external provider, we get redirected here if
+// the external account is not linked to any pointercrate account yet
+function initializeOauthRegistration() {
+  let params = new URLSearchParams(window.location.hash.substring(1));
+  let provider = params.get("provider");
+  let ticket = params.get("ticket");
+
+  if (!provider || !ticket) return;
+
+  // Do not keep the ticket around in the browser history
+  history.replaceState(null, "", window.location.pathname);
+
   let dialog = new FormDialog("oauth-registration-pick-username");
   dialog.form.addErrorOverride(40902, "oauth-username");
   dialog.form.onSubmit(() => {
     let formData = dialog.form.serialize();
-    formData["credential"] = response["credential"];
-    post("/api/v1/auth/oauth/google/register/", {}, formData)
+    formData["credential"] = ticket;
+    post("/api/v1/auth/oauth/" + provider + "/register/", {}, formData)
       .then(() => (window.location = "/account/"))
       .catch(displayError(dialog.form));
   });
   dialog.open();
 }
 
-window.googleOauthRegisterCallback = googleOauthRegisterCallback;
-
 $(document).ready(function () {
+  initializeOauthRegistration();
   intializeRegisterForm();
 });
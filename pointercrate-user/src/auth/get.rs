@@ -10,7 +10,7 @@ use super::NoAuth;
 impl AuthenticatedUser<NoAuth> {
     pub async fn by_id(id: i32, connection: &mut PgConnection) -> Result<Self> {
         let row = sqlx::query!(
-            r#"SELECT member_id, members.name, permissions::integer, display_name, youtube_channel::text, password_hash, generation, google_account_id FROM members WHERE member_id = $1"#,
+            r#"SELECT member_id, members.name, permissions::integer, display_name, youtube_channel::text, password_hash, generation, totp_secret, EXISTS(SELECT 1 FROM oauth_identities WHERE oauth_identities.member_id = members.member_id) AS "oauth!" FROM members WHERE member_id = $1"#,
             id
         )
         .fetch_one(connection)
@@ -22,13 +22,15 @@ impl AuthenticatedUser<NoAuth> {
             Ok(row) => {
                 let user = construct_from_row!(row);
 
-                let auth_type = match row.google_account_id {
-                    Some(_) => AuthenticationType::oauth(user),
-                    None => AuthenticationType::legacy(
+                let auth_type = if row.oauth {
+                    AuthenticationType::oauth(user)
+                } else {
+                    AuthenticationType::legacy_with_totp(
                         user,
                         row.password_hash
                             .ok_or_else(|| CoreError::internal_server_error("Non-oauth user without password in database!"))?,
-                    ),
+                        row.totp_secret,
+                    )
                 };
 
                 Ok(AuthenticatedUser {
@@ -42,7 +44,7 @@ impl AuthenticatedUser<NoAuth> {
 
     pub async fn by_name(name: &str, connection: &mut PgConnection) -> Result<Self> {
         let row = sqlx::query!(
-            r#"SELECT member_id, members.name, permissions::integer, display_name, youtube_channel::text, password_hash, generation, google_account_id FROM members WHERE members.name = $1"#,
+            r#"SELECT member_id, members.name, permissions::integer, display_name, youtube_channel::text, password_hash, generation, totp_secret, EXISTS(SELECT 1 FROM oauth_identities WHERE oauth_identities.member_id = members.member_id) AS "oauth!" FROM members WHERE members.name = $1"#,
             name.to_string()
         )
         .fetch_one(connection)
@@ -54,13 +56,15 @@ impl AuthenticatedUser<NoAuth> {
             Ok(row) => {
                 let user = construct_from_row!(row);
 
-                let auth_type = match row.google_account_id {
-                    Some(_) => AuthenticationType::oauth(user),
-                    None => AuthenticationType::legacy(
+                let auth_type = if row.oauth {
+                    AuthenticationType::oauth(user)
+                } else {
+                    AuthenticationType::legacy_with_totp(
                         user,
                         row.password_hash
                             .ok_or_else(|| CoreError::internal_server_error("Non-oauth user without password in database!"))?,
-                    ),
+                        row.totp_secret,
+                    )
                 };
 
                 Ok(AuthenticatedUser {
@@ -6,13 +6,18 @@ use super::AuthenticationType;
 
 #[cfg(feature = "legacy_accounts")]
 pub use post::Registration;
+pub use totp::TotpEnrolment;
 
 mod patch;
 mod post;
+mod totp;
 
 pub struct LegacyAuthenticatedUser {
     user: User,
     password_hash: String,
+
+    /// The base32 encoded TOTP secret, if this user enabled two-factor authentication
+    totp_secret: Option<String>,
 }
 
 impl LegacyAuthenticatedUser {
@@ -57,6 +62,14 @@ impl LegacyAuthenticatedUser {
 
 impl AuthenticationType {
     pub fn legacy(user: User, password_hash: String) -> Self {
-        AuthenticationType::Legacy(LegacyAuthenticatedUser { user, password_hash })
+        Self::legacy_with_totp(user, password_hash, None)
+    }
+
+    pub(in crate::auth) fn legacy_with_totp(user: User, password_hash: String, totp_secret: Option<String>) -> Self {
+        AuthenticationType::Legacy(LegacyAuthenticatedUser {
+            user,
+            password_hash,
+            totp_secret,
+        })
     }
 }
@@ -0,0 +1,252 @@
+//! Two-factor authentication for legacy accounts via time-based one-time passwords (RFC 6238)
+//!
+//! Enabling two-factor authentication also generates a set of single-use recovery codes, which can
+//! be supplied instead of a TOTP code in case the user lost access to their authenticator app.
+
+use super::LegacyAuthenticatedUser;
+use crate::error::{Result, UserError};
+use pointercrate_core::{error::CoreError, util::csprng_u64};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use sqlx::PgConnection;
+use std::time::{SystemTime, UNIX_EPOCH};
+use totp_rs::{Algorithm, Secret, TOTP};
+
+/// How many recovery codes are generated when enabling two-factor authentication
+const RECOVERY_CODE_COUNT: usize = 10;
+
+/// Request to enable two-factor authentication for an account
+#[derive(Deserialize)]
+pub struct TotpEnrolment {
+    /// The base32 encoded secret shared with the user's authenticator app
+    pub secret: String,
+
+    /// A code generated by the authenticator app, proving that it was set up with `secret`
+    pub code: String,
+}
+
+/// How many steps before and after the current one codes are accepted from, to account for clock
+/// drift
+const TOTP_SKEW: u64 = 1;
+
+/// Constructs a TOTP generator with the parameters all common authenticator apps default to
+/// (SHA-1, 6 digits, 30 second steps)
+///
+/// The generator itself only accepts codes from exactly the given step, clock drift is handled by
+/// [`check_totp`].
+fn totp(secret: &str) -> Option<TOTP> {
+    let secret = Secret::Encoded(secret.to_string()).to_bytes().ok()?;
+
+    TOTP::new(Algorithm::SHA1, 6, 0, 30, secret).ok()
+}
+
+/// Checks the given code against the current time step and its [`TOTP_SKEW`] neighbours, returning
+/// the step the code was generated for
+///
+/// The step is needed to reject codes that were already used once, see [`accept_totp_step`].
+fn check_totp(totp: &TOTP, code: &str) -> Option<i64> {
+    // The only possible error is the system clock being set to before the unix epoch
+    let current = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs() / totp.step;
+
+    (current.saturating_sub(TOTP_SKEW)..=current + TOTP_SKEW)
+        .find(|step| totp.check(code.trim(), step * totp.step))
+        .and_then(|step| i64::try_from(step).ok())
+}
+
+/// Records `step` as the last time step a TOTP code was accepted for the given user
+///
+/// Returns `false` if a code from this step, or a later one, was already accepted before, meaning
+/// the code at hand is being replayed.
+async fn accept_totp_step(member_id: i32, step: i64, connection: &mut PgConnection) -> std::result::Result<bool, sqlx::Error> {
+    let updated = sqlx::query!(
+        "UPDATE members SET totp_last_step = $1 WHERE member_id = $2 AND (totp_last_step IS NULL OR totp_last_step < $1)",
+        step,
+        member_id
+    )
+    .execute(connection)
+    .await?
+    .rows_affected();
+
+    Ok(updated != 0)
+}
+
+/// Hashes a recovery code, ignoring any formatting (dashes, whitespace, capitalization) the user
+/// might have added while noting it down
+fn hash_recovery_code(code: &str) -> String {
+    let normalized = code
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect::<String>();
+
+    Sha256::digest(normalized.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+impl LegacyAuthenticatedUser {
+    pub fn has_totp(&self) -> bool {
+        self.totp_secret.is_some()
+    }
+
+    /// Verifies the given second factor, which is either a code generated from the user's TOTP
+    /// secret, or one of their recovery codes. Used recovery codes are invalidated.
+    ///
+    /// Always succeeds if the user did not enable two-factor authentication.
+    pub(in crate::auth) async fn verify_totp(
+        &self, code: Option<&str>, connection: &mut PgConnection,
+    ) -> std::result::Result<(), CoreError> {
+        let Some(ref secret) = self.totp_secret else {
+            return Ok(());
+        };
+
+        let Some(code) = code else {
+            log::debug!("No second factor provided for account {}", self.user);
+
+            return Err(CoreError::TwoFactorRequired);
+        };
+
+        let totp =
+            totp(secret).ok_or_else(|| CoreError::internal_server_error(format!("Invalid TOTP secret for account {}", self.user)))?;
+
+        if let Some(step) = check_totp(&totp, code) {
+            if !accept_totp_step(self.user.id, step, connection).await? {
+                log::warn!("Rejecting reused TOTP code for account {}", self.user);
+
+                return Err(CoreError::TwoFactorRequired);
+            }
+
+            log::debug!("TOTP code correct, proceeding");
+
+            return Ok(());
+        }
+
+        let consumed = sqlx::query!(
+            "DELETE FROM totp_recovery_codes WHERE member_id = $1 AND code_hash = $2",
+            self.user.id,
+            hash_recovery_code(code)
+        )
+        .execute(connection)
+        .await?
+        .rows_affected();
+
+        if consumed == 0 {
+            log::warn!("Wrong second factor for account {}", self.user);
+
+            return Err(CoreError::TwoFactorRequired);
+        }
+
+        log::info!("Account {} authenticated using a recovery code", self.user);
+
+        Ok(())
+    }
+
+    pub(in crate::auth) async fn enable_totp(&mut self, enrolment: TotpEnrolment, connection: &mut PgConnection) -> Result<()> {
+        let totp = totp(&enrolment.secret).ok_or(UserError::InvalidTotpSecret)?;
+
+        let step = check_totp(&totp, &enrolment.code).ok_or(UserError::InvalidTotpCode)?;
+
+        log::info!("Enabling two-factor authentication for account {}", self.user);
+
+        // The enrolment code counts as used, so it cannot be replayed to log in afterwards
+        sqlx::query!(
+            "UPDATE members SET totp_secret = $1, totp_last_step = $2 WHERE member_id = $3",
+            enrolment.secret,
+            step,
+            self.user.id
+        )
+        .execute(connection)
+        .await?;
+
+        self.totp_secret = Some(enrolment.secret);
+
+        Ok(())
+    }
+
+    pub(in crate::auth) async fn disable_totp(&mut self, connection: &mut PgConnection) -> Result<()> {
+        log::info!("Disabling two-factor authentication for account {}", self.user);
+
+        sqlx::query!(
+            "UPDATE members SET totp_secret = NULL, totp_last_step = NULL WHERE member_id = $1",
+            self.user.id
+        )
+        .execute(&mut *connection)
+        .await?;
+        sqlx::query!("DELETE FROM totp_recovery_codes WHERE member_id = $1", self.user.id)
+            .execute(connection)
+            .await?;
+
+        self.totp_secret = None;
+
+        Ok(())
+    }
+
+    /// Replaces all recovery codes of this user with newly generated ones, which are returned
+    ///
+    /// Only the hashes of the codes are stored, meaning this is the only time they can be shown to
+    /// the user.
+    pub(in crate::auth) async fn regenerate_recovery_codes(&self, connection: &mut PgConnection) -> Result<Vec<String>> {
+        sqlx::query!("DELETE FROM totp_recovery_codes WHERE member_id = $1", self.user.id)
+            .execute(&mut *connection)
+            .await?;
+
+        let mut codes = Vec::with_capacity(RECOVERY_CODE_COUNT);
+
+        for _ in 0..RECOVERY_CODE_COUNT {
+            // 40 bits of entropy, formatted as "xxxxx-xxxxx"
+            let random = format!("{:010x}", csprng_u64()? & 0xff_ffff_ffff);
+            let code = format!("{}-{}", &random[..5], &random[5..]);
+
+            sqlx::query!(
+                "INSERT INTO totp_recovery_codes (member_id, code_hash) VALUES ($1, $2)",
+                self.user.id,
+                hash_recovery_code(&code)
+            )
+            .execute(&mut *connection)
+            .await?;
+
+            codes.push(code);
+        }
+
+        Ok(codes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_totp, hash_recovery_code, totp};
+
+    // base32 encoding of "12345678901234567890", the secret used by the test vectors of RFC 6238
+    const RFC_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+    #[test]
+    fn test_rfc6238_vectors() {
+        let totp = totp(RFC_SECRET).unwrap();
+
+        // The RFC lists 8 digit codes, of which the last 6 are used here
+        assert_eq!(totp.generate(59), "287082");
+        assert_eq!(totp.generate(1111111109), "081804");
+        assert_eq!(totp.generate(2000000000), "279037");
+    }
+
+    #[test]
+    fn test_current_code_accepted() {
+        let totp = totp(RFC_SECRET).unwrap();
+
+        assert!(check_totp(&totp, &totp.generate_current().unwrap()).is_some());
+        assert!(check_totp(&totp, "not a code").is_none());
+    }
+
+    #[test]
+    fn test_short_secret_rejected() {
+        assert!(totp("GEZDGNBVGY3TQOJQ").is_none());
+        assert!(totp("not base32!").is_none());
+    }
+
+    #[test]
+    fn test_recovery_code_formatting_ignored() {
+        assert_eq!(hash_recovery_code("abcde-12345"), hash_recovery_code(" ABCDE 12345 "));
+        assert_ne!(hash_recovery_code("abcde-12345"), hash_recovery_code("abcde-12346"));
+    }
+}
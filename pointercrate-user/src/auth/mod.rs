@@ -3,7 +3,9 @@
 //! Pointercrate makes use of three authentication methods, although the first one can be considered deprecated:
 //! 1. HTTP Basic Auth/"Password based auth": Authenticating with a password is considered the
 //!    highest level of authentication, and allows all administrative account actions  (e.g. changing password,
-//!    deleting account). However, it does not allow API requests
+//!    deleting account). However, it does not allow API requests. If the account has two-factor authentication
+//!    enabled, a TOTP or recovery code needs to be provided in addition to the password (see
+//!    [`AuthenticatedUser::verify_credentials`])
 //! 2. Browser based auth: When logging into pointercrate from a browser, two cookies will be set: `access_token`
 //!    and `csrf_token`. The `access_token` cookie should be more accurately names `session_token`, as that's
 //!    what it really is. The `csrf_token` allows CSRF prevention using the signed double-submit cookie pattern,
@@ -16,6 +18,7 @@
 //!    - non-`GET`: These are authenticated using the session token, which is validated using the csrf token.
 //!      Browser-based auth allows both administrative account actions (except changing password) and API access
 //! 3. HTTP Bearer Auth: Authenticating using a bearer token allows API access, but does not allow user account actions.
+//!    Tokens can be restricted to specific endpoints by creating them as a [`ScopedToken`](token::ScopedToken).
 //!
 //! See [`AuthenticatedUser`] for implementation details.
 
@@ -26,6 +29,7 @@ use legacy::LegacyAuthenticatedUser;
 use pointercrate_core::error::Result;
 use pointercrate_core::{error::CoreError, util::csprng_u64};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sqlx::PgConnection;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 mod delete;
@@ -34,6 +38,7 @@ pub mod legacy;
 pub mod oauth;
 mod patch;
 mod post;
+pub mod token;
 
 /// Indicates that no authentication has occurred yet
 ///
@@ -42,8 +47,12 @@ pub struct NoAuth;
 
 /// Indicates that authentication using a bearer token from an `Authorization` header has taken place
 ///
-/// See also [`AuthenticatedUser::validate_api_access`]
-pub struct ApiToken;
+/// If the token was a [`ScopedToken`](token::ScopedToken), the requests this authentication is
+/// valid for are restricted (see [`AuthenticatedUser::authorize_request`]).
+///
+/// See also [`AuthenticatedUser::validate_api_access`] and
+/// [`AuthenticatedUser::validate_scoped_api_access`]
+pub struct ApiToken(Option<token::TokenGrant>);
 
 /// Indicates that authentication using an `access_token` Cookie occurred
 ///
@@ -102,6 +111,10 @@ pub struct AccessClaims {
     /// A generation ID that allows token invalidation. Tokens are only accepted if the generation
     /// ID in the token matches the current generation id on the [`AuthenticatedUser`]
     gen: i64,
+
+    /// The id of the [`ScopedToken`](token::ScopedToken) this is an access token for, if any
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    token: Option<i32>,
 }
 
 impl AccessClaims {
@@ -116,6 +129,12 @@ impl AccessClaims {
     pub fn id(&self) -> Result<i32> {
         self.sub.parse().map_err(|_| CoreError::Unauthorized)
     }
+
+    /// Whether this is an access token for a [`ScopedToken`](token::ScopedToken), which needs
+    /// to be validated using [`AuthenticatedUser::validate_scoped_api_access`]
+    pub fn is_scoped(&self) -> bool {
+        self.token.is_some()
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -131,13 +150,13 @@ pub fn generate_jwt<C: Serialize>(claims: &C) -> String {
     jsonwebtoken::encode(
         &jsonwebtoken::Header::default(),
         &claims,
-        &EncodingKey::from_secret(&config::secret()),
+        &EncodingKey::from_secret(config::secret()),
     )
     .unwrap()
 }
 
 pub fn decode_jwt<C: DeserializeOwned>(jwt: &str, validation: &Validation) -> Result<C> {
-    jsonwebtoken::decode::<C>(jwt, &DecodingKey::from_secret(&config::secret()), validation)
+    jsonwebtoken::decode::<C>(jwt, &DecodingKey::from_secret(config::secret()), validation)
         .map_err(|_| CoreError::Unauthorized)
         .map(|token_data| token_data.claims)
 }
@@ -171,17 +190,28 @@ impl<Auth> AuthenticatedUser<Auth> {
     pub fn auth_type(&self) -> &AuthenticationType {
         &self.auth_type
     }
+
+    /// Whether logging into this account requires a second factor
+    ///
+    /// OAuth accounts leave this to their identity provider, and thus always count as having a
+    /// second factor.
+    pub fn has_two_factor(&self) -> bool {
+        match &self.auth_type {
+            AuthenticationType::Legacy(legacy) => legacy.has_totp(),
+            AuthenticationType::Oauth2(_) => true,
+        }
+    }
 }
 
 impl AuthenticatedUser<NoAuth> {
     pub fn validate_api_access(self, claims: AccessClaims) -> Result<AuthenticatedUser<ApiToken>> {
-        if claims.gen != self.gen || claims.id()? != self.user().id || claims.session_uuid.is_some() {
+        if claims.gen != self.gen || claims.id()? != self.user().id || claims.session_uuid.is_some() || claims.is_scoped() {
             Err(CoreError::Unauthorized)
         } else {
             Ok(AuthenticatedUser {
                 gen: self.gen,
                 auth_type: self.auth_type,
-                auth_artifact: ApiToken,
+                auth_artifact: ApiToken(None),
             })
         }
     }
@@ -198,8 +228,16 @@ impl AuthenticatedUser<NoAuth> {
         }
     }
 
+    /// Verifies the given password, failing for accounts with two-factor authentication enabled
+    ///
+    /// See [`AuthenticatedUser::verify_credentials`] for also verifying a second factor.
     pub fn verify_password(self, password: &str) -> Result<AuthenticatedUser<PasswordOrBrowser>> {
         match &self.auth_type {
+            AuthenticationType::Legacy(legacy) if legacy.has_totp() => {
+                legacy.verify(password)?;
+
+                return Err(CoreError::TwoFactorRequired);
+            },
             AuthenticationType::Legacy(legacy) => legacy.verify(password)?,
             _ => return Err(CoreError::Unauthorized),
         }
@@ -210,6 +248,26 @@ impl AuthenticatedUser<NoAuth> {
             auth_artifact: PasswordOrBrowser(true),
         })
     }
+
+    /// Verifies the given password and, if the account has two-factor authentication enabled, the
+    /// given TOTP or recovery code
+    pub async fn verify_credentials(
+        self, password: &str, totp_code: Option<&str>, connection: &mut PgConnection,
+    ) -> Result<AuthenticatedUser<PasswordOrBrowser>> {
+        match &self.auth_type {
+            AuthenticationType::Legacy(legacy) => {
+                legacy.verify(password)?;
+                legacy.verify_totp(totp_code, connection).await?;
+            },
+            _ => return Err(CoreError::Unauthorized),
+        }
+
+        Ok(AuthenticatedUser {
+            auth_type: self.auth_type,
+            gen: self.gen,
+            auth_artifact: PasswordOrBrowser(true),
+        })
+    }
 }
 
 impl AuthenticatedUser<NonMutating> {
@@ -239,6 +297,7 @@ impl AuthenticatedUser<PasswordOrBrowser> {
             sub: self.user().id.to_string(),
             session_uuid: Some(session_uuid),
             gen: self.gen,
+            token: None,
         };
 
         let csrf_claims = CSRFClaims {
@@ -262,6 +321,7 @@ impl AuthenticatedUser<PasswordOrBrowser> {
             sub: self.user().id.to_string(),
             session_uuid: None,
             gen: self.gen,
+            token: None,
         })
     }
 
@@ -272,7 +332,7 @@ impl AuthenticatedUser<PasswordOrBrowser> {
             Ok(AuthenticatedUser {
                 gen: self.gen,
                 auth_type: self.auth_type,
-                auth_artifact: ApiToken,
+                auth_artifact: ApiToken(None),
             })
         }
     }
@@ -281,6 +341,7 @@ impl AuthenticatedUser<PasswordOrBrowser> {
 #[cfg(test)]
 mod tests {
     use crate::auth::{AccessClaims, AuthenticatedUser, User};
+    use pointercrate_core::error::CoreError;
 
     use super::{AuthenticationType, NoAuth};
 
@@ -325,6 +386,32 @@ mod tests {
         assert!(make_patrick().verify_password("bad password with suffix").is_err());
     }
 
+    #[test]
+    fn test_password_with_two_factor() {
+        let patrick = || {
+            let AuthenticationType::Legacy(legacy) = make_patrick().auth_type else {
+                unreachable!()
+            };
+
+            AuthenticatedUser {
+                auth_type: AuthenticationType::legacy_with_totp(
+                    legacy.into_user(),
+                    bcrypt::hash("bad password", bcrypt::DEFAULT_COST).unwrap(),
+                    Some("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ".to_string()),
+                ),
+                gen: 0,
+                auth_artifact: NoAuth,
+            }
+        };
+
+        assert!(patrick().has_two_factor());
+        assert!(!make_patrick().has_two_factor());
+
+        // The password alone is no longer sufficient
+        assert_eq!(patrick().verify_password("bad password").err(), Some(CoreError::TwoFactorRequired));
+        assert_eq!(patrick().verify_password("lksafdölksad").err(), Some(CoreError::Unauthorized));
+    }
+
     #[test]
     fn test_token_pair() {
         let (patricks_access_token, patricks_csrf_token) = make_patrick()
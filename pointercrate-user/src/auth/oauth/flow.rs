@@ -0,0 +1,387 @@
+use crate::auth::{
+    decode_jwt, generate_jwt,
+    oauth::{OauthIdentity, OauthProvider, ProviderMetadata},
+    AuthenticatedUser, NoAuth, PasswordOrBrowser,
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{jwk::Jwk, Algorithm, DecodingKey, Validation};
+use pointercrate_core::{error::CoreError, util::csprng_u64};
+use serde::{de::IgnoredAny, Deserialize, Deserializer, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use url::Url;
+
+const STATE_AUDIENCE: &str = "pointercrate-oauth-state";
+
+/// How long users have to complete the authorization at the provider (15 minutes)
+const STATE_LIFETIME: u64 = 15 * 60;
+
+/// Minimum time between two key set refreshes caused by ID tokens signed with unknown keys
+const UNKNOWN_KEY_REFRESH_INTERVAL: i64 = 5 * 60;
+
+pub(super) fn random_token() -> Result<String, CoreError> {
+    let mut bytes = Vec::with_capacity(32);
+
+    for _ in 0..4 {
+        bytes.extend(csprng_u64()?.to_le_bytes());
+    }
+
+    Ok(URL_SAFE_NO_PAD.encode(bytes))
+}
+
+pub(super) fn unix_time() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
+
+/// The state of an ongoing authorization code flow, stored (signed) in a cookie while the user
+/// authorizes us at the provider
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AuthorizationState {
+    aud: String,
+    exp: u64,
+    provider: String,
+
+    /// The `state` parameter, which the provider needs to redirect back with
+    state: String,
+
+    /// The PKCE code verifier
+    verifier: String,
+
+    /// The nonce that needs to be included in the ID token
+    nonce: String,
+
+    /// The account the identity should be linked to, if the flow was started while signed in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    link: Option<LinkIntent>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LinkIntent {
+    member_id: i32,
+    gen: i64,
+}
+
+pub struct AuthorizationRequest {
+    /// The URL of the provider's authorization endpoint to redirect the user to
+    pub url: String,
+
+    /// The signed [`AuthorizationState`], to be kept until the provider redirects back
+    pub state: String,
+}
+
+impl OauthProvider {
+    /// Starts an authorization code flow with this provider. If a user is given, the identity
+    /// will be linked to their account once the flow completes
+    pub fn authorization_request<Auth>(
+        &self, metadata: &ProviderMetadata, link_to: Option<&AuthenticatedUser<Auth>>,
+    ) -> Result<AuthorizationRequest, CoreError> {
+        let state = AuthorizationState {
+            aud: STATE_AUDIENCE.to_string(),
+            exp: unix_time() + STATE_LIFETIME,
+            provider: self.id.clone(),
+            state: random_token()?,
+            verifier: random_token()?,
+            nonce: random_token()?,
+            link: link_to.map(|user| LinkIntent {
+                member_id: user.user().id,
+                gen: user.gen,
+            }),
+        };
+
+        let challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(state.verifier.as_bytes()));
+        let scope = self.scopes.join(" ");
+
+        let mut params = vec![
+            ("response_type", "code"),
+            ("client_id", &self.client_id),
+            ("redirect_uri", &self.redirect_uri),
+            ("state", &state.state),
+            ("code_challenge", &challenge),
+            ("code_challenge_method", "S256"),
+        ];
+
+        if !scope.is_empty() {
+            params.push(("scope", &scope));
+        }
+
+        if self.is_openid_connect() {
+            params.push(("nonce", &state.nonce));
+        }
+
+        let url = Url::parse_with_params(&metadata.authorization_endpoint, &params)
+            .map_err(|_| CoreError::internal_server_error(format!("Invalid authorization endpoint for oauth provider {}", self.id)))?;
+
+        Ok(AuthorizationRequest {
+            url: url.into(),
+            state: generate_jwt(&state),
+        })
+    }
+
+    /// The form parameters for exchanging the given authorization code at this provider's token
+    /// endpoint
+    pub fn token_request(&self, code: &str, state: &AuthorizationState) -> Vec<(&'static str, String)> {
+        let mut params = vec![
+            ("grant_type", "authorization_code".to_string()),
+            ("code", code.to_string()),
+            ("redirect_uri", self.redirect_uri.clone()),
+            ("client_id", self.client_id.clone()),
+            ("code_verifier", state.verifier.clone()),
+        ];
+
+        if let Some(ref secret) = self.client_secret {
+            params.push(("client_secret", secret.clone()));
+        }
+
+        params
+    }
+
+    /// Extracts the user's identity from the response of this provider's userinfo endpoint
+    pub fn identity_from_userinfo(&self, mut userinfo: Userinfo) -> Option<OauthIdentity> {
+        let subject = match userinfo.0.remove(&self.subject_claim)? {
+            ClaimValue::String(subject) => subject,
+            ClaimValue::Integer(subject) => subject.to_string(),
+            ClaimValue::Other(_) => return None,
+        };
+
+        if subject.is_empty() {
+            return None;
+        }
+
+        Some(OauthIdentity {
+            provider: self.id.clone(),
+            subject,
+        })
+    }
+}
+
+impl AuthorizationState {
+    /// Decodes the state stored when the flow was started, and checks that the `state` parameter
+    /// the provider redirected back with matches it
+    pub fn validate(stored: &str, provider: &str, state: &str) -> Result<AuthorizationState, CoreError> {
+        let mut validation = Validation::default();
+        validation.set_audience(&[STATE_AUDIENCE]);
+
+        let stored: AuthorizationState = decode_jwt(stored, &validation)?;
+
+        if stored.provider != provider || stored.state != state {
+            return Err(CoreError::Unauthorized);
+        }
+
+        Ok(stored)
+    }
+
+    pub fn nonce(&self) -> &str {
+        &self.nonce
+    }
+
+    /// The id of the account the identity should be linked to, if any
+    pub fn link_target(&self) -> Option<i32> {
+        self.link.as_ref().map(|link| link.member_id)
+    }
+}
+
+impl AuthenticatedUser<NoAuth> {
+    /// Authenticates the user that started an authorization code flow for linking a new identity
+    /// to their account. Fails if the account's tokens were invalidated in the meantime.
+    pub fn validate_link_intent(self, state: &AuthorizationState) -> Result<AuthenticatedUser<PasswordOrBrowser>, CoreError> {
+        match state.link {
+            Some(ref link) if link.member_id == self.user().id && link.gen == self.gen => Ok(AuthenticatedUser {
+                gen: self.gen,
+                auth_type: self.auth_type,
+                auth_artifact: PasswordOrBrowser(false),
+            }),
+            _ => Err(CoreError::Unauthorized),
+        }
+    }
+}
+
+/// A successful response from a provider's token endpoint
+#[derive(Deserialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+
+    #[serde(default)]
+    pub id_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ClaimValue {
+    String(String),
+    Integer(u64),
+    Other(IgnoredAny),
+}
+
+/// A response from a provider's userinfo endpoint
+#[derive(Deserialize)]
+pub struct Userinfo(HashMap<String, ClaimValue>);
+
+#[derive(Deserialize)]
+struct IdTokenClaims {
+    sub: String,
+
+    #[serde(default)]
+    nonce: Option<String>,
+}
+
+/// A provider's JSON Web Key Set, used to validate ID tokens
+#[derive(Default, Deserialize)]
+pub struct KeySet {
+    #[serde(deserialize_with = "supported_keys")]
+    keys: Vec<Jwk>,
+
+    /// When these keys were retrieved
+    #[serde(skip)]
+    pub fetched_at: Option<DateTime<Utc>>,
+
+    /// When these keys become stale, as indicated by the provider
+    #[serde(skip)]
+    pub expiry: Option<DateTime<Utc>>,
+}
+
+/// Skips keys we cannot use (e.g. because they are encryption keys of some type we don't know),
+/// instead of rejecting the entire key set
+fn supported_keys<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Jwk>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum MaybeJwk {
+        Jwk(Box<Jwk>),
+        Other(IgnoredAny),
+    }
+
+    Ok(Vec::<MaybeJwk>::deserialize(deserializer)?
+        .into_iter()
+        .filter_map(|key| match key {
+            MaybeJwk::Jwk(jwk) => Some(*jwk),
+            MaybeJwk::Other(_) => None,
+        })
+        .collect())
+}
+
+impl KeySet {
+    fn key_for(&self, kid: Option<&str>) -> Option<&Jwk> {
+        match kid {
+            Some(kid) => self.keys.iter().find(|key| key.common.key_id.as_deref() == Some(kid)),
+            None if self.keys.len() == 1 => self.keys.first(),
+            None => None,
+        }
+    }
+
+    /// Whether these keys need to be retrieved again before validating the given ID token
+    ///
+    /// This is the case if they are stale, or if the token was signed with a key we do not know
+    /// (as providers regularly rotate their keys). To prevent tokens with made up key ids from
+    /// causing a refresh on every request, the latter only causes a refresh every five minutes.
+    pub fn needs_refresh(&self, id_token: &str) -> bool {
+        let (Some(fetched_at), Some(expiry)) = (self.fetched_at, self.expiry) else {
+            return true;
+        };
+
+        let now = Utc::now();
+
+        if now >= expiry {
+            return true;
+        }
+
+        let Ok(header) = jsonwebtoken::decode_header(id_token) else {
+            return false;
+        };
+
+        self.key_for(header.kid.as_deref()).is_none() && now - fetched_at >= Duration::seconds(UNKNOWN_KEY_REFRESH_INTERVAL)
+    }
+
+    /// Validates an ID token issued by the given provider. If a nonce is given, the token must
+    /// contain it.
+    pub fn validate_id_token(
+        &self, provider: &OauthProvider, metadata: &ProviderMetadata, id_token: &str, nonce: Option<&str>,
+    ) -> Option<OauthIdentity> {
+        let issuer = metadata.issuer.as_ref()?;
+        let header = jsonwebtoken::decode_header(id_token).ok()?;
+
+        // Only accept asymmetric algorithms. jsonwebtoken ensures that the key matches the algorithm
+        use Algorithm::*;
+        if !matches!(header.alg, RS256 | RS384 | RS512 | PS256 | PS384 | PS512 | ES256 | ES384 | EdDSA) {
+            return None;
+        }
+
+        let key = self.key_for(header.kid.as_deref())?;
+
+        let mut issuers = vec![issuer.clone()];
+        issuers.extend(provider.issuer_aliases.iter().cloned());
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&issuers);
+        validation.set_audience(&[&provider.client_id]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+        let claims: IdTokenClaims = jsonwebtoken::decode(id_token, &DecodingKey::from_jwk(key).ok()?, &validation)
+            .map(|data| data.claims)
+            .inspect_err(|err| {
+                use jsonwebtoken::errors::ErrorKind::*;
+
+                match err.kind() {
+                    // With these, we don't run into any danger of accidentally logging credentials
+                    InvalidToken
+                    | InvalidSignature
+                    | InvalidEcdsaKey
+                    | InvalidRsaKey(_)
+                    | RsaFailedSigning
+                    | InvalidAlgorithmName
+                    | InvalidKeyFormat
+                    | MissingRequiredClaim(_)
+                    | ExpiredSignature
+                    | InvalidIssuer
+                    | InvalidAudience
+                    | InvalidSubject
+                    | ImmatureSignature
+                    | InvalidAlgorithm
+                    | MissingAlgorithm => log::warn!("Failure to validate ID token allegedly issued by {}: {:?}", provider.id, err),
+                    // All others, better be on the safe side and not log the actual error
+                    _ => log::warn!("Failure to parse/validate ID token allegedly issued by {}", provider.id),
+                }
+            })
+            .ok()?;
+
+        if nonce.is_some() && claims.nonce.as_deref() != nonce {
+            log::warn!("ID token allegedly issued by {} has invalid nonce", provider.id);
+
+            return None;
+        }
+
+        Some(OauthIdentity {
+            provider: provider.id.clone(),
+            subject: claims.sub,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClaimValue, Userinfo};
+    use crate::auth::oauth::OauthProvider;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_identity_from_userinfo() {
+        let github = OauthProvider::github("client".into(), "http://localhost");
+
+        let userinfo = Userinfo(HashMap::from([
+            ("id".to_string(), ClaimValue::Integer(1234)),
+            ("login".to_string(), ClaimValue::String("stadust".into())),
+        ]));
+        let identity = github.identity_from_userinfo(userinfo).unwrap();
+
+        assert_eq!(identity.provider(), "github");
+        assert_eq!(identity.subject(), "1234");
+
+        let userinfo = Userinfo(HashMap::from([("login".to_string(), ClaimValue::String("stadust".into()))]));
+
+        assert!(github.identity_from_userinfo(userinfo).is_none());
+    }
+}
@@ -1,3 +1,5 @@
+use chrono::NaiveDateTime;
+use futures::StreamExt;
 use pointercrate_core::error::CoreError;
 use sqlx::{Error, PgConnection};
 
@@ -5,13 +7,44 @@ use crate::auth::{AuthenticatedUser, AuthenticationType, PasswordOrBrowser};
 use crate::Result;
 use crate::User;
 
-use super::ValidatedGoogleCredentials;
+use super::OauthIdentity;
+
+/// An identity at some OAuth2 provider that is linked to a pointercrate account
+pub struct LinkedIdentity {
+    /// The id of the provider
+    pub provider: String,
+    pub linked_at: NaiveDateTime,
+}
+
+impl LinkedIdentity {
+    /// Gets all identities linked to the given account, ordered by when they were linked
+    pub async fn all_of(member_id: i32, connection: &mut PgConnection) -> Result<Vec<LinkedIdentity>> {
+        let mut stream = sqlx::query!(
+            "SELECT provider, linked_at FROM oauth_identities WHERE member_id = $1 ORDER BY id",
+            member_id
+        )
+        .fetch(connection);
+        let mut identities = Vec::new();
+
+        while let Some(row) = stream.next().await {
+            let row = row?;
+
+            identities.push(LinkedIdentity {
+                provider: row.provider,
+                linked_at: row.linked_at,
+            })
+        }
+
+        Ok(identities)
+    }
+}
 
 impl AuthenticatedUser<PasswordOrBrowser> {
-    pub async fn by_validated_google_creds(creds: &ValidatedGoogleCredentials, connection: &mut PgConnection) -> Result<Self> {
+    pub async fn by_oauth_identity(identity: &OauthIdentity, connection: &mut PgConnection) -> Result<Self> {
         let row = sqlx::query!(
-            r#"SELECT member_id, members.name, permissions::integer, display_name, youtube_channel::text, password_hash, generation, google_account_id FROM members WHERE google_account_id = $1"#,
-            creds.google_account_id()
+            r#"SELECT members.member_id, members.name, permissions::integer, display_name, youtube_channel::text, generation FROM members INNER JOIN oauth_identities ON oauth_identities.member_id = members.member_id WHERE provider = $1 AND subject = $2"#,
+            identity.provider,
+            identity.subject
         )
         .fetch_one(connection)
         .await;
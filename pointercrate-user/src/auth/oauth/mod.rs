@@ -1,12 +1,27 @@
+//! Module for signing in with external OAuth2/OpenID Connect providers
+//!
+//! Accounts can link (at most) one identity per configured [`OauthProvider`]. Signing in happens
+//! either via the authorization code flow (with PKCE) started at `/oauth/<provider>/`, or by
+//! directly submitting an ID token obtained by some other means (e.g. Google's one tap sign-in).
+
+#[cfg(feature = "oauth2")]
+mod flow;
 #[cfg(feature = "oauth2")]
 mod get;
 #[cfg(feature = "oauth2")]
 mod patch;
 #[cfg(feature = "oauth2")]
 mod post;
+#[cfg(feature = "oauth2")]
+mod provider;
 
 #[cfg(feature = "oauth2")]
-pub use post::{GoogleCertificateDatabase, OauthRegistration, UnvalidatedOauthCredential, ValidatedGoogleCredentials};
+pub use self::{
+    flow::{AuthorizationRequest, AuthorizationState, KeySet, TokenResponse, Userinfo},
+    get::LinkedIdentity,
+    post::{OauthRegistration, UnvalidatedOauthCredential},
+    provider::{OauthProvider, ProviderEndpoints, ProviderMetadata},
+};
 
 use crate::User;
 
@@ -31,3 +46,23 @@ impl AuthenticationType {
         AuthenticationType::Oauth2(OA2AuthenticatedUser { user })
     }
 }
+
+/// An identity at an [`OauthProvider`] whose ownership has been verified (either by validating an
+/// ID token, or by querying the provider's userinfo endpoint with an access token we obtained)
+#[cfg(feature = "oauth2")]
+#[derive(Debug)]
+pub struct OauthIdentity {
+    provider: String,
+    subject: String,
+}
+
+#[cfg(feature = "oauth2")]
+impl OauthIdentity {
+    pub fn provider(&self) -> &str {
+        &self.provider
+    }
+
+    pub fn subject(&self) -> &str {
+        &self.subject
+    }
+}
@@ -1,20 +1,63 @@
 use sqlx::PgConnection;
 
-use crate::auth::legacy::LegacyAuthenticatedUser;
+use crate::auth::{AuthenticatedUser, AuthenticationType, PasswordOrBrowser};
+use crate::error::UserError;
 use crate::Result;
 
-use super::ValidatedGoogleCredentials;
+use super::OauthIdentity;
+
+impl AuthenticatedUser<PasswordOrBrowser> {
+    /// Links the given identity to this account, allowing it to be used for signing in
+    ///
+    /// Linking the first identity to a legacy account turns it into an oauth account, meaning that
+    /// password login is no longer possible.
+    pub async fn link_oauth_identity(&mut self, identity: &OauthIdentity, connection: &mut PgConnection) -> Result<()> {
+        let existing = sqlx::query!(
+            "SELECT member_id, provider, subject FROM oauth_identities WHERE member_id = $1 OR (provider = $2 AND subject = $3)",
+            self.user().id,
+            identity.provider,
+            identity.subject
+        )
+        .fetch_all(&mut *connection)
+        .await?;
+
+        for row in existing {
+            if row.member_id != self.user().id {
+                return Err(UserError::OauthIdentityTaken {
+                    provider: identity.provider.clone(),
+                });
+            }
+
+            if row.provider == identity.provider {
+                // Re-linking the same identity is a no-op
+                if row.subject == identity.subject {
+                    return Ok(());
+                }
+
+                return Err(UserError::OauthProviderAlreadyLinked {
+                    provider: identity.provider.clone(),
+                });
+            }
+        }
+
+        log::info!("Linking {} identity to account of {}", identity.provider, self.user());
 
-impl LegacyAuthenticatedUser {
-    pub async fn set_linked_google_account(&mut self, creds: &ValidatedGoogleCredentials, connection: &mut PgConnection) -> Result<()> {
         sqlx::query!(
-            "UPDATE members SET google_account_id = $1 WHERE member_id = $2",
-            creds.google_account_id(),
-            self.user().id
+            "INSERT INTO oauth_identities (member_id, provider, subject) VALUES ($1, $2, $3)",
+            self.user().id,
+            identity.provider,
+            identity.subject
         )
-        .execute(connection)
+        .execute(&mut *connection)
         .await?;
 
+        if let AuthenticationType::Legacy(legacy) = &self.auth_type {
+            self.auth_type = AuthenticationType::oauth(legacy.user().clone());
+
+            // Sessions established via password login are no longer valid
+            self.increment_generation_id(connection).await?;
+        }
+
         Ok(())
     }
 }
@@ -1,18 +1,37 @@
-use crate::auth::{AuthenticatedUser, AuthenticationType, PasswordOrBrowser};
+use crate::auth::{
+    decode_jwt, generate_jwt,
+    oauth::{flow::unix_time, OauthIdentity},
+    AuthenticatedUser, AuthenticationType, PasswordOrBrowser,
+};
 use crate::error::UserError;
 use crate::Result;
-use crate::{config, User};
-use chrono::{DateTime, Utc};
-use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use crate::User;
+use jsonwebtoken::Validation;
 use pointercrate_core::error::CoreError;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use sqlx::PgConnection;
 
+const REGISTRATION_AUDIENCE: &str = "pointercrate-oauth-registration";
+
+/// How long after completing an authorization flow users have to pick a username (15 minutes)
+const REGISTRATION_LIFETIME: u64 = 15 * 60;
+
+/// A credential proving ownership of some identity at an OAuth2 provider
+///
+/// Either an ID token issued by the provider, or a registration ticket obtained at the end of an
+/// authorization code flow for an identity not yet linked to any account (see
+/// [`OauthIdentity::registration_ticket`]).
 #[derive(Debug, Deserialize)]
 pub struct UnvalidatedOauthCredential {
     credential: String,
 }
 
+impl UnvalidatedOauthCredential {
+    pub fn credential(&self) -> &str {
+        &self.credential
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct OauthRegistration {
     #[serde(flatten)]
@@ -20,87 +39,52 @@ pub struct OauthRegistration {
     pub username: String,
 }
 
-#[derive(Deserialize)]
-pub struct ValidatedGoogleCredentials {
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RegistrationClaims {
+    aud: String,
+    exp: u64,
+    provider: String,
     sub: String,
 }
 
-impl ValidatedGoogleCredentials {
-    pub fn google_account_id(&self) -> &str {
-        self.sub.as_ref()
+impl OauthIdentity {
+    /// Generates a short-lived ticket allowing registration of a new account for this identity
+    pub fn registration_ticket(&self) -> String {
+        generate_jwt(&RegistrationClaims {
+            aud: REGISTRATION_AUDIENCE.to_string(),
+            exp: unix_time() + REGISTRATION_LIFETIME,
+            provider: self.provider.clone(),
+            sub: self.subject.clone(),
+        })
     }
-}
 
-#[derive(Deserialize)]
-pub struct SigningKey {
-    e: String,
-    n: String,
-    kid: String,
-    alg: Algorithm,
-}
+    /// Validates a ticket generated by [`OauthIdentity::registration_ticket`] for an identity at
+    /// the given provider
+    pub fn from_registration_ticket(provider: &str, credential: &UnvalidatedOauthCredential) -> Option<OauthIdentity> {
+        let mut validation = Validation::default();
+        validation.set_audience(&[REGISTRATION_AUDIENCE]);
 
-#[derive(Default, Deserialize)]
-pub struct GoogleCertificateDatabase {
-    pub keys: Vec<SigningKey>,
+        let claims: RegistrationClaims = decode_jwt(&credential.credential, &validation).ok()?;
 
-    #[serde(default)]
-    pub expiry: Option<DateTime<Utc>>,
-}
-
-impl GoogleCertificateDatabase {
-    pub fn needs_refresh(&self) -> bool {
-        match self.expiry {
-            None => true,
-            Some(expiry) => Utc::now() >= expiry,
+        if claims.provider != provider {
+            return None;
         }
-    }
-
-    pub fn validate_credentials(&self, creds: UnvalidatedOauthCredential) -> Option<ValidatedGoogleCredentials> {
-        let header = jsonwebtoken::decode_header(&creds.credential).ok()?;
-        let key = self.keys.iter().find(|key| Some(key.kid.as_ref()) == header.kid.as_deref())?;
 
-        let mut validation = Validation::new(key.alg);
-        validation.set_issuer(&["accounts.google.com", "https://accounts.google.com"]);
-        validation.set_audience(&[config::google_client_id()]);
-        validation.required_spec_claims.extend(["iss".to_string(), "aud".to_string()]);
-
-        jsonwebtoken::decode(
-            &creds.credential,
-            &DecodingKey::from_rsa_components(&key.n, &key.e).ok()?,
-            &validation,
-        )
-        .map(|data| data.claims)
-        .inspect_err(|err| {
-            use jsonwebtoken::errors::ErrorKind::*;
-
-            match err.kind() {
-                // With these, we don't run into any danger of accidentally logging credentials
-                InvalidToken
-                | InvalidSignature
-                | InvalidEcdsaKey
-                | InvalidRsaKey(_)
-                | RsaFailedSigning
-                | InvalidAlgorithmName
-                | InvalidKeyFormat
-                | MissingRequiredClaim(_)
-                | ExpiredSignature
-                | InvalidIssuer
-                | InvalidAudience
-                | InvalidSubject
-                | ImmatureSignature
-                | InvalidAlgorithm
-                | MissingAlgorithm => log::warn!("Failure to validate credentials allegedly received from google: {:?}", err),
-                // All others, better be on the safe side and not log the actual error
-                _ => log::warn!("Failure to parse/validate credentials allegedly received from google"),
-            }
+        Some(OauthIdentity {
+            provider: claims.provider,
+            subject: claims.sub,
         })
-        .ok()
     }
 }
 
 impl AuthenticatedUser<PasswordOrBrowser> {
-    pub async fn register_oauth(username: String, credentials: ValidatedGoogleCredentials, connection: &mut PgConnection) -> Result<Self> {
-        log::info!("Attempting oauth registration of new user under name {}", username);
+    pub async fn register_oauth(username: String, identity: OauthIdentity, connection: &mut PgConnection) -> Result<Self> {
+        log::info!(
+            "Attempting oauth registration of new user under name {} via {}",
+            username,
+            identity.provider
+        );
 
         match User::by_name(&username, connection).await {
             Ok(_) => return Err(UserError::NameTaken),
@@ -108,20 +92,29 @@ impl AuthenticatedUser<PasswordOrBrowser> {
             Err(err) => return Err(err),
         }
 
-        match AuthenticatedUser::by_validated_google_creds(&credentials, connection).await {
-            Ok(_) => return Err(CoreError::Unauthorized.into()),
+        match AuthenticatedUser::by_oauth_identity(&identity, connection).await {
+            Ok(_) => {
+                return Err(UserError::OauthIdentityTaken {
+                    provider: identity.provider,
+                })
+            },
             Err(UserError::Core(CoreError::Unauthorized)) => {},
             Err(err) => return Err(err),
         }
 
-        let id = sqlx::query!(
-            "INSERT INTO members (name, google_account_id) VALUES ($1, $2) RETURNING member_id",
-            &username,
-            credentials.sub
+        let id = sqlx::query!("INSERT INTO members (name) VALUES ($1) RETURNING member_id", &username)
+            .fetch_one(&mut *connection)
+            .await?
+            .member_id;
+
+        sqlx::query!(
+            "INSERT INTO oauth_identities (member_id, provider, subject) VALUES ($1, $2, $3)",
+            id,
+            identity.provider,
+            identity.subject
         )
-        .fetch_one(connection)
-        .await?
-        .member_id;
+        .execute(connection)
+        .await?;
 
         Ok(AuthenticatedUser {
             gen: 0,
@@ -0,0 +1,182 @@
+use crate::config::OauthProviderConfig;
+use serde::Deserialize;
+
+/// An external OAuth2 (or OpenID Connect) identity provider users can sign in with
+///
+/// Providers are configured in the `[user]` configuration table, see
+/// [`UserConfig::oauth`](crate::config::UserConfig::oauth).
+#[derive(Debug, Clone)]
+pub struct OauthProvider {
+    /// The id of this provider, used in URLs and when storing linked identities (e.g. `google`)
+    pub id: String,
+
+    /// The name of this provider as shown to users
+    pub display_name: String,
+
+    pub client_id: String,
+
+    /// The client secret, if this provider requires one in addition to PKCE
+    pub client_secret: Option<String>,
+
+    /// The URI the provider redirects back to after authorization. Must point to the
+    /// `/oauth/<provider>/callback/` endpoint.
+    pub redirect_uri: String,
+
+    pub scopes: Vec<String>,
+
+    pub endpoints: ProviderEndpoints,
+
+    /// Additional values of the `iss` claim accepted in ID tokens, apart from the issuer from the
+    /// provider's metadata (Google issues ID tokens both with and without `https://` prefix)
+    pub issuer_aliases: Vec<String>,
+
+    /// The field of the userinfo response that identifies the user, for providers that do not
+    /// issue ID tokens
+    pub subject_claim: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum ProviderEndpoints {
+    /// An OpenID Connect provider, whose metadata is retrieved from
+    /// `<issuer>/.well-known/openid-configuration`
+    Discover { issuer: String },
+
+    /// A plain OAuth2 provider without support for discovery (e.g. GitHub)
+    Static(ProviderMetadata),
+}
+
+/// The subset of an OpenID Connect provider's metadata we need
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProviderMetadata {
+    #[serde(default)]
+    pub issuer: Option<String>,
+
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+
+    #[serde(default)]
+    pub userinfo_endpoint: Option<String>,
+
+    #[serde(default)]
+    pub jwks_uri: Option<String>,
+}
+
+impl ProviderEndpoints {
+    pub fn discovery_url(&self) -> Option<String> {
+        match self {
+            ProviderEndpoints::Discover { issuer } => Some(format!("{}/.well-known/openid-configuration", issuer.trim_end_matches('/'))),
+            ProviderEndpoints::Static(_) => None,
+        }
+    }
+}
+
+impl OauthProvider {
+    /// Constructs a generic OpenID Connect provider, using the `openid` scope
+    pub fn openid_connect(id: &str, display_name: &str, issuer: &str, client_id: String, redirect_base: &str) -> Self {
+        OauthProvider {
+            id: id.to_string(),
+            display_name: display_name.to_string(),
+            client_id,
+            client_secret: None,
+            redirect_uri: format!("{}/oauth/{}/callback/", redirect_base.trim_end_matches('/'), id),
+            scopes: vec!["openid".to_string()],
+            endpoints: ProviderEndpoints::Discover {
+                issuer: issuer.to_string(),
+            },
+            issuer_aliases: Vec::new(),
+            subject_claim: "sub".to_string(),
+        }
+    }
+
+    pub fn google(client_id: String, redirect_base: &str) -> Self {
+        OauthProvider {
+            issuer_aliases: vec!["accounts.google.com".to_string()],
+            ..OauthProvider::openid_connect("google", "Google", "https://accounts.google.com", client_id, redirect_base)
+        }
+    }
+
+    pub fn discord(client_id: String, redirect_base: &str) -> Self {
+        OauthProvider {
+            scopes: vec!["identify".to_string()],
+            endpoints: ProviderEndpoints::Static(ProviderMetadata {
+                issuer: None,
+                authorization_endpoint: "https://discord.com/oauth2/authorize".to_string(),
+                token_endpoint: "https://discord.com/api/oauth2/token".to_string(),
+                userinfo_endpoint: Some("https://discord.com/api/users/@me".to_string()),
+                jwks_uri: None,
+            }),
+            subject_claim: "id".to_string(),
+            ..OauthProvider::openid_connect("discord", "Discord", "", client_id, redirect_base)
+        }
+    }
+
+    pub fn github(client_id: String, redirect_base: &str) -> Self {
+        OauthProvider {
+            scopes: Vec::new(),
+            endpoints: ProviderEndpoints::Static(ProviderMetadata {
+                issuer: None,
+                authorization_endpoint: "https://github.com/login/oauth/authorize".to_string(),
+                token_endpoint: "https://github.com/login/oauth/access_token".to_string(),
+                userinfo_endpoint: Some("https://api.github.com/user".to_string()),
+                jwks_uri: None,
+            }),
+            subject_claim: "id".to_string(),
+            ..OauthProvider::openid_connect("github", "GitHub", "", client_id, redirect_base)
+        }
+    }
+
+    /// Constructs the provider configured in the `[user.oauth.<id>]` table
+    ///
+    /// The configuration must have been validated, meaning that `issuer` is set unless `id` is one of
+    /// the known providers.
+    pub(crate) fn from_config(id: &str, config: &OauthProviderConfig, redirect_base: &str) -> OauthProvider {
+        let client_id = config.client_id.clone();
+
+        let mut provider = match id {
+            "google" => OauthProvider::google(client_id, redirect_base),
+            "discord" => OauthProvider::discord(client_id, redirect_base),
+            "github" => OauthProvider::github(client_id, redirect_base),
+            _ => OauthProvider::openid_connect(id, id, config.issuer.as_deref().unwrap_or_default(), client_id, redirect_base),
+        };
+
+        provider.client_secret = config.client_secret.clone();
+
+        if let Some(name) = &config.name {
+            provider.display_name = name.clone();
+        }
+
+        if let Some(scopes) = &config.scopes {
+            provider.scopes = scopes.clone();
+        }
+
+        provider
+    }
+
+    /// Whether ID tokens issued by this provider should be validated instead of querying the
+    /// userinfo endpoint
+    pub fn is_openid_connect(&self) -> bool {
+        self.scopes.iter().any(|scope| scope == "openid")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{OauthProvider, ProviderEndpoints};
+
+    #[test]
+    fn test_discovery_url() {
+        let provider = OauthProvider::openid_connect("mock", "Mock", "http://localhost:1234/", "client".into(), "http://localhost");
+
+        assert_eq!(
+            provider.endpoints.discovery_url().as_deref(),
+            Some("http://localhost:1234/.well-known/openid-configuration")
+        );
+        assert_eq!(provider.redirect_uri, "http://localhost/oauth/mock/callback/");
+        assert!(provider.is_openid_connect());
+
+        let github = OauthProvider::github("client".into(), "http://localhost");
+
+        assert!(matches!(github.endpoints, ProviderEndpoints::Static(_)));
+        assert!(!github.is_openid_connect());
+    }
+}
@@ -12,7 +12,7 @@ use serde::Deserialize;
 use sqlx::PgConnection;
 use std::fmt::{Debug, Formatter};
 
-use super::{AuthenticationType, PasswordOrBrowser};
+use super::{legacy::TotpEnrolment, AuthenticationType, NoAuth, PasswordOrBrowser};
 
 #[derive(Deserialize, Default)]
 pub struct PatchMe {
@@ -24,15 +24,28 @@ pub struct PatchMe {
 
     #[serde(default, deserialize_with = "nullable")]
     pub(super) youtube_channel: Option<Option<String>>,
+
+    /// Enables two-factor authentication if set to a [`TotpEnrolment`], disables it if set to
+    /// `null`
+    #[serde(default, deserialize_with = "nullable")]
+    pub(super) totp: Option<Option<TotpEnrolment>>,
 }
 
 impl PatchMe {
     pub fn changes_password(&self) -> bool {
         self.password.is_some()
     }
+
+    pub fn enables_totp(&self) -> bool {
+        matches!(self.totp, Some(Some(_)))
+    }
+
+    pub fn disables_totp(&self) -> bool {
+        matches!(self.totp, Some(None))
+    }
 }
 
-// manual debug impl to ensure that the password field is never printed anywhere
+// manual debug impl to ensure that the password and TOTP secret are never printed anywhere
 impl Debug for PatchMe {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("PatchMe")
@@ -52,6 +65,17 @@ impl AuthenticatedUser<PasswordOrBrowser> {
             self.set_password(password, connection).await?;
         }
 
+        // Otherwise, a stolen session cookie would be enough to add or remove a second factor
+        if patch.totp.is_some() && !self.auth_artifact.is_password() {
+            return Err(CoreError::Unauthorized.into());
+        }
+
+        match patch.totp {
+            Some(Some(enrolment)) => self.enable_totp(enrolment, connection).await?,
+            Some(None) => self.disable_totp(connection).await?,
+            None => (),
+        }
+
         self.into_user()
             .apply_patch(
                 PatchUser {
@@ -64,6 +88,42 @@ impl AuthenticatedUser<PasswordOrBrowser> {
             .await
     }
 
+    async fn enable_totp(&mut self, enrolment: TotpEnrolment, connection: &mut PgConnection) -> Result<()> {
+        match &mut self.auth_type {
+            AuthenticationType::Legacy(legacy) => legacy.enable_totp(enrolment, connection).await,
+            _ => Err(UserError::NonLegacyAccount),
+        }
+    }
+
+    async fn disable_totp(&mut self, connection: &mut PgConnection) -> Result<()> {
+        match &mut self.auth_type {
+            AuthenticationType::Legacy(legacy) => legacy.disable_totp(connection).await,
+            _ => Err(UserError::NonLegacyAccount),
+        }
+    }
+
+    /// Generates a new set of recovery codes for this account, invalidating all previous ones
+    pub async fn regenerate_recovery_codes(&self, connection: &mut PgConnection) -> Result<Vec<String>> {
+        match &self.auth_type {
+            AuthenticationType::Legacy(legacy) => legacy.regenerate_recovery_codes(connection).await,
+            _ => Err(UserError::NonLegacyAccount),
+        }
+    }
+}
+
+impl AuthenticatedUser<NoAuth> {
+    /// Sets a new password for this account without requiring the current one
+    ///
+    /// Meant for instance operators restoring access to an account whose owner they verified by
+    /// other means. Like any password change, this invalidates all access tokens.
+    pub async fn reset_password(mut self, password: String, connection: &mut PgConnection) -> Result<()> {
+        log::warn!("Resetting password of user {}", self.user());
+
+        self.set_password(password, connection).await
+    }
+}
+
+impl<Auth> AuthenticatedUser<Auth> {
     async fn set_password(&mut self, password: String, connection: &mut PgConnection) -> Result<()> {
         match &mut self.auth_type {
             AuthenticationType::Legacy(legacy) => legacy.set_password(password, connection).await?,
@@ -86,18 +146,6 @@ impl AuthenticatedUser<PasswordOrBrowser> {
 
         Ok(())
     }
-
-    #[cfg(feature = "oauth2")]
-    pub async fn link_google_account(
-        &mut self, creds: &super::oauth::ValidatedGoogleCredentials, connection: &mut PgConnection,
-    ) -> Result<()> {
-        match &mut self.auth_type {
-            AuthenticationType::Legacy(legacy) => legacy.set_linked_google_account(creds, connection).await?,
-            _ => return Err(CoreError::Unauthorized.into()),
-        }
-
-        self.increment_generation_id(connection).await
-    }
 }
 
 #[cfg(test)]
@@ -133,4 +181,38 @@ mod tests {
 
         assert!(patrick.validate_api_access(AccessClaims::decode(&token).unwrap()).is_err());
     }
+
+    #[cfg(feature = "legacy_accounts")]
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_reset_password(mut conn: sqlx::pool::PoolConnection<sqlx::Postgres>) {
+        use crate::auth::{legacy::Registration, AccessClaims, AuthenticatedUser};
+
+        let patrick = AuthenticatedUser::register(
+            Registration {
+                name: "Patrick".to_string(),
+                password: "bad password".to_string(),
+            },
+            &mut conn,
+        )
+        .await
+        .unwrap();
+
+        let token = patrick.generate_programmatic_access_token();
+        let patricks_id = patrick.user().id;
+
+        AuthenticatedUser::by_id(patricks_id, &mut conn)
+            .await
+            .unwrap()
+            .reset_password("worse password".into(), &mut conn)
+            .await
+            .unwrap();
+
+        let patrick = AuthenticatedUser::by_id(patricks_id, &mut conn).await.unwrap();
+
+        assert!(patrick.validate_api_access(AccessClaims::decode(&token).unwrap()).is_err());
+
+        let patrick = AuthenticatedUser::by_id(patricks_id, &mut conn).await.unwrap();
+
+        assert!(patrick.verify_password("worse password").is_ok());
+    }
 }
@@ -0,0 +1,16 @@
+use crate::{auth::token::ScopedToken, error::Result};
+use log::info;
+use sqlx::PgConnection;
+
+impl ScopedToken {
+    /// Deletes this token, immediately revoking all access tokens issued for it
+    pub async fn delete(self, connection: &mut PgConnection) -> Result<()> {
+        info!("Deleting scoped token {}", self);
+
+        sqlx::query!("DELETE FROM api_tokens WHERE id = $1", self.id)
+            .execute(connection)
+            .await?;
+
+        Ok(())
+    }
+}
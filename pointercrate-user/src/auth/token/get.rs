@@ -0,0 +1,123 @@
+use crate::{
+    auth::{
+        token::{Scope, ScopedToken, TokenGrant},
+        AccessClaims, ApiToken, AuthenticatedUser, NoAuth,
+    },
+    error::{Result, UserError},
+};
+use futures::StreamExt;
+use pointercrate_core::error::CoreError;
+use sqlx::{Error, PgConnection};
+
+fn parse_stored_scopes(scopes: &[String]) -> std::result::Result<Vec<Scope>, CoreError> {
+    scopes
+        .iter()
+        .map(|scope| {
+            scope
+                .parse()
+                .map_err(|_| CoreError::internal_server_error(format!("Invalid scope '{}' in database", scope)))
+        })
+        .collect()
+}
+
+impl ScopedToken {
+    /// Gets the scoped token with the given id, if it belongs to the given user
+    pub async fn by_id(id: i32, member_id: i32, connection: &mut PgConnection) -> Result<ScopedToken> {
+        let result = sqlx::query!(
+            "SELECT name, scopes, created_at, expires_at, last_used_at FROM api_tokens WHERE id = $1 AND member_id = $2",
+            id,
+            member_id
+        )
+        .fetch_one(connection)
+        .await;
+
+        match result {
+            Ok(row) => Ok(ScopedToken {
+                id,
+                name: row.name,
+                scopes: parse_stored_scopes(&row.scopes)?,
+                created_at: row.created_at,
+                expires_at: row.expires_at,
+                last_used_at: row.last_used_at,
+            }),
+            Err(Error::RowNotFound) => Err(UserError::ApiTokenNotFound { token_id: id }),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Gets all scoped tokens of the given user (including expired ones), ordered by id
+    pub async fn all_of(member_id: i32, connection: &mut PgConnection) -> Result<Vec<ScopedToken>> {
+        let mut stream = sqlx::query!(
+            "SELECT id, name, scopes, created_at, expires_at, last_used_at FROM api_tokens WHERE member_id = $1 ORDER BY id",
+            member_id
+        )
+        .fetch(connection);
+        let mut tokens = Vec::new();
+
+        while let Some(row) = stream.next().await {
+            let row = row?;
+
+            tokens.push(ScopedToken {
+                id: row.id,
+                name: row.name,
+                scopes: parse_stored_scopes(&row.scopes)?,
+                created_at: row.created_at,
+                expires_at: row.expires_at,
+                last_used_at: row.last_used_at,
+            })
+        }
+
+        Ok(tokens)
+    }
+
+    /// Updates the time the scoped token with the given id was last used, unless that already
+    /// happened within the last minute
+    ///
+    /// Should not happen in the transaction of the request the token was used for, as that might
+    /// never be committed (e.g. for `GET` requests).
+    pub async fn record_usage(id: i32, connection: &mut PgConnection) -> std::result::Result<(), CoreError> {
+        sqlx::query!(
+            "UPDATE api_tokens SET last_used_at = (NOW() AT TIME ZONE 'utc') WHERE id = $1 AND (last_used_at IS NULL OR last_used_at < \
+             (NOW() AT TIME ZONE 'utc') - INTERVAL '1 minute')",
+            id
+        )
+        .execute(connection)
+        .await?;
+
+        Ok(())
+    }
+}
+
+impl AuthenticatedUser<NoAuth> {
+    /// Validates an access token for a [`ScopedToken`], which must neither have been deleted nor
+    /// have expired
+    pub async fn validate_scoped_api_access(
+        self, claims: AccessClaims, connection: &mut PgConnection,
+    ) -> std::result::Result<AuthenticatedUser<ApiToken>, CoreError> {
+        let Some(token_id) = claims.token else {
+            return Err(CoreError::Unauthorized);
+        };
+
+        if claims.gen != self.gen || claims.id()? != self.user().id || claims.session_uuid.is_some() {
+            return Err(CoreError::Unauthorized);
+        }
+
+        let scopes = sqlx::query_scalar!(
+            "SELECT scopes FROM api_tokens WHERE id = $1 AND member_id = $2 AND expires_at > (NOW() AT TIME ZONE 'utc')",
+            token_id,
+            self.user().id
+        )
+        .fetch_optional(connection)
+        .await?
+        .ok_or(CoreError::Unauthorized)?;
+
+        Ok(AuthenticatedUser {
+            gen: self.gen,
+            auth_type: self.auth_type,
+            auth_artifact: ApiToken(Some(TokenGrant {
+                id: token_id,
+                scopes: parse_stored_scopes(&scopes)?,
+            })),
+        })
+    }
+}
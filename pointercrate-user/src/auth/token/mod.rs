@@ -0,0 +1,260 @@
+//! Module for named, scoped access tokens
+//!
+//! Next to the single programmatic access token handed out on login (see
+//! [`AuthenticatedUser::generate_programmatic_access_token`]), which carries all of a user's
+//! permissions, users can create any number of [`ScopedToken`]s. Each of these is restricted to a
+//! set of [`Scope`]s, expires after a fixed lifetime and can be revoked individually by deleting it.
+//! Invalidating all of a user's tokens by incrementing their generation ID (see
+//! [`AuthenticatedUser::invalidate_all_tokens`]) also invalidates their scoped tokens.
+//!
+//! Note that scopes only ever restrict which endpoints a token can be used for. Whether the user is
+//! allowed to perform the request at all is still decided by their permissions.
+
+use crate::{
+    auth::{ApiToken, AuthenticatedUser},
+    error::{Result, UserError},
+};
+use chrono::NaiveDateTime;
+use derive_more::Display;
+use pointercrate_core::{error::CoreError, etag::Taggable};
+use serde::{Serialize, Serializer};
+use std::{
+    hash::{Hash, Hasher},
+    str::FromStr,
+};
+
+pub use patch::PatchScopedToken;
+pub use post::PostScopedToken;
+
+mod delete;
+mod get;
+mod patch;
+mod post;
+
+/// The kind of access to a resource a [`Scope`] grants
+#[derive(Debug, Display, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Access {
+    /// Allows `GET` requests
+    #[display("read")]
+    Read,
+
+    /// Allows `POST` requests, e.g. submitting records
+    #[display("create")]
+    Create,
+
+    /// Allows requests with any method
+    #[display("write")]
+    Write,
+}
+
+impl Access {
+    /// The kind of access a request with the given HTTP method requires
+    pub fn of_method(method: &str) -> Access {
+        match method {
+            "GET" | "HEAD" | "OPTIONS" => Access::Read,
+            "POST" => Access::Create,
+            _ => Access::Write,
+        }
+    }
+
+    fn includes(self, other: Access) -> bool {
+        self == Access::Write || self == other
+    }
+}
+
+/// Grants some kind of [`Access`] to a resource, written as `<resource>:<access>`
+///
+/// The resource of an API endpoint is the first path segment after the API version, e.g. `records`
+/// for `/api/v1/records/42/`. The resource `*` matches all endpoints.
+#[derive(Debug, Display, Clone, PartialEq, Eq, Hash)]
+#[display("{}:{}", resource.as_deref().unwrap_or("*"), access)]
+pub struct Scope {
+    /// The resource this scope grants access to, or [`None`] for all resources
+    resource: Option<String>,
+    access: Access,
+}
+
+impl Scope {
+    /// Whether this scope allows a request with the given method to the given path
+    pub fn allows(&self, method: &str, path: &str) -> bool {
+        let resource_matches = match self.resource {
+            None => true,
+            Some(ref resource) => resource_of(path) == Some(resource),
+        };
+
+        resource_matches && self.access.includes(Access::of_method(method))
+    }
+
+    /// The most specific scope that allows a request with the given method to the given path
+    pub fn required_for(method: &str, path: &str) -> Scope {
+        Scope {
+            resource: resource_of(path).map(ToString::to_string),
+            access: Access::of_method(method),
+        }
+    }
+}
+
+impl FromStr for Scope {
+    type Err = UserError;
+
+    fn from_str(scope: &str) -> Result<Self> {
+        let invalid = || UserError::InvalidScope { scope: scope.to_string() };
+
+        let (resource, access) = scope.split_once(':').ok_or_else(invalid)?;
+
+        let resource = match resource {
+            "*" => None,
+            _ if !resource.is_empty() && resource.chars().all(|c| c.is_ascii_lowercase() || c == '_') => Some(resource.to_string()),
+            _ => return Err(invalid()),
+        };
+
+        let access = match access {
+            "read" => Access::Read,
+            "create" => Access::Create,
+            "write" => Access::Write,
+            _ => return Err(invalid()),
+        };
+
+        Ok(Scope { resource, access })
+    }
+}
+
+impl Serialize for Scope {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Extracts the resource an API request targets from its path (see [`Scope`])
+fn resource_of(path: &str) -> Option<&str> {
+    let mut segments = path.trim_start_matches('/').split('/');
+
+    match (segments.next(), segments.next(), segments.next()) {
+        (Some("api"), Some(version), Some(resource)) if version.starts_with('v') && !resource.is_empty() => Some(resource),
+        _ => None,
+    }
+}
+
+fn parse_scopes(scopes: &[String]) -> Result<Vec<Scope>> {
+    if scopes.is_empty() {
+        return Err(UserError::InvalidScope { scope: String::new() });
+    }
+
+    scopes.iter().map(|scope| scope.parse()).collect()
+}
+
+/// A named access token, restricted to a set of [`Scope`]s
+#[derive(Debug, Serialize, Display, PartialEq, Eq)]
+#[display("{} (ID: {})", name, id)]
+pub struct ScopedToken {
+    pub id: i32,
+    pub name: String,
+    pub scopes: Vec<Scope>,
+    pub created_at: NaiveDateTime,
+    pub expires_at: NaiveDateTime,
+
+    /// When this token was last used to authenticate a request. Only updated once per minute.
+    pub last_used_at: Option<NaiveDateTime>,
+}
+
+// Using a token should not invalidate the etag of the token itself
+impl Hash for ScopedToken {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+        self.name.hash(state);
+        self.scopes.hash(state);
+        self.created_at.hash(state);
+        self.expires_at.hash(state);
+    }
+}
+
+impl Taggable for ScopedToken {}
+
+/// The [`ScopedToken`] an [`ApiToken`] authentication happened with
+pub(super) struct TokenGrant {
+    id: i32,
+    scopes: Vec<Scope>,
+}
+
+impl AuthenticatedUser<ApiToken> {
+    /// The id of the [`ScopedToken`] used for authentication, if any
+    pub fn scoped_token_id(&self) -> Option<i32> {
+        self.auth_artifact.0.as_ref().map(|grant| grant.id)
+    }
+
+    /// Ensures that the token this user authenticated with may be used for a request with the given
+    /// method to the given path
+    ///
+    /// Only [`ScopedToken`]s are restricted, all other forms of authentication allow every request.
+    pub fn authorize_request(&self, method: &str, path: &str) -> std::result::Result<(), CoreError> {
+        match self.auth_artifact.0 {
+            Some(ref grant) if !grant.scopes.iter().any(|scope| scope.allows(method, path)) => Err(CoreError::MissingScope {
+                required: Scope::required_for(method, path).to_string(),
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Access, Scope};
+    use crate::error::UserError;
+
+    fn scope(scope: &str) -> Scope {
+        scope.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_scope() {
+        assert_eq!(
+            scope("records:read"),
+            Scope {
+                resource: Some("records".to_string()),
+                access: Access::Read
+            }
+        );
+        assert_eq!(
+            scope("*:write"),
+            Scope {
+                resource: None,
+                access: Access::Write
+            }
+        );
+        assert_eq!(scope("webhooks:create").to_string(), "webhooks:create");
+
+        for invalid in ["records", "records:delete", ":read", "Records:read", "records/1:read"] {
+            assert_eq!(
+                invalid.parse::<Scope>(),
+                Err(UserError::InvalidScope {
+                    scope: invalid.to_string()
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn test_scope_allows() {
+        assert!(scope("records:read").allows("GET", "/api/v1/records/42/"));
+        assert!(scope("records:read").allows("GET", "/api/v2/records/"));
+        assert!(!scope("records:read").allows("POST", "/api/v1/records/"));
+        assert!(!scope("records:read").allows("GET", "/api/v1/players/"));
+
+        assert!(scope("records:create").allows("POST", "/api/v1/records/"));
+        assert!(!scope("records:create").allows("PATCH", "/api/v1/records/42/"));
+
+        assert!(scope("records:write").allows("DELETE", "/api/v1/records/42/"));
+        assert!(scope("records:write").allows("GET", "/api/v1/records/42/"));
+
+        // Only the wildcard scope covers non-API endpoints
+        assert!(!scope("records:read").allows("GET", "/demonlist/"));
+        assert!(scope("*:read").allows("GET", "/demonlist/"));
+        assert!(scope("*:read").allows("GET", "/api/v1/players/"));
+    }
+
+    #[test]
+    fn test_required_scope() {
+        assert_eq!(Scope::required_for("PATCH", "/api/v1/demons/1/").to_string(), "demons:write");
+        assert_eq!(Scope::required_for("GET", "/account/").to_string(), "*:read");
+    }
+}
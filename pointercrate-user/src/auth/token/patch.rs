@@ -0,0 +1,51 @@
+use crate::{
+    auth::token::{
+        parse_scopes,
+        post::{name_taken, validate_name},
+        ScopedToken,
+    },
+    error::{Result, UserError},
+};
+use pointercrate_core::util::non_nullable;
+use serde::Deserialize;
+use sqlx::PgConnection;
+
+#[derive(Debug, Deserialize)]
+pub struct PatchScopedToken {
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub name: Option<String>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub scopes: Option<Vec<String>>,
+}
+
+impl ScopedToken {
+    /// Renames this token and/or changes its scopes. Changes to the scopes apply to the token
+    /// immediately.
+    pub async fn apply_patch(mut self, member_id: i32, patch: PatchScopedToken, connection: &mut PgConnection) -> Result<Self> {
+        if let Some(name) = patch.name {
+            validate_name(&name)?;
+
+            if name_taken(&name, member_id, Some(self.id), &mut *connection).await? {
+                return Err(UserError::TokenNameTaken);
+            }
+
+            self.name = name;
+        }
+
+        if let Some(scopes) = patch.scopes {
+            self.scopes = parse_scopes(&scopes)?;
+        }
+
+        sqlx::query!(
+            "UPDATE api_tokens SET name = $1, scopes = $2 WHERE id = $3",
+            self.name,
+            &self.scopes.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            self.id
+        )
+        .execute(connection)
+        .await?;
+
+        Ok(self)
+    }
+}
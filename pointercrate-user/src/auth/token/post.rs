@@ -0,0 +1,100 @@
+use crate::{
+    auth::{
+        generate_jwt,
+        token::{parse_scopes, ScopedToken},
+        AccessClaims, AuthenticatedUser, PasswordOrBrowser,
+    },
+    error::{Result, UserError},
+};
+use log::info;
+use serde::Deserialize;
+use sqlx::PgConnection;
+
+/// Lifetime of scoped tokens for which none was explicitly requested (90 days)
+const DEFAULT_LIFETIME: u32 = 90 * 24 * 3600;
+
+/// Maximum lifetime of scoped tokens (one year)
+const MAX_LIFETIME: u32 = 365 * 24 * 3600;
+
+#[derive(Debug, Deserialize)]
+pub struct PostScopedToken {
+    pub name: String,
+
+    /// The scopes of the new token, in the form `<resource>:<access>`
+    pub scopes: Vec<String>,
+
+    /// After how many seconds the token expires
+    #[serde(default)]
+    pub lifetime: Option<u32>,
+}
+
+pub(super) fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.chars().count() > 64 || name.trim() != name {
+        return Err(UserError::InvalidTokenName);
+    }
+
+    Ok(())
+}
+
+pub(super) async fn name_taken(name: &str, member_id: i32, except: Option<i32>, connection: &mut PgConnection) -> Result<bool> {
+    Ok(sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM api_tokens WHERE member_id = $1 AND name = $2 AND id IS DISTINCT FROM $3) AS "taken!""#,
+        member_id,
+        name,
+        except
+    )
+    .fetch_one(connection)
+    .await?)
+}
+
+impl AuthenticatedUser<PasswordOrBrowser> {
+    /// Creates a new [`ScopedToken`] for this user, returning it together with the access token
+    /// that can be used to authenticate as it
+    ///
+    /// The access token is not stored, meaning this is the only time it can be shown to the user.
+    pub async fn create_scoped_token(&self, data: PostScopedToken, connection: &mut PgConnection) -> Result<(ScopedToken, String)> {
+        validate_name(&data.name)?;
+
+        let scopes = parse_scopes(&data.scopes)?;
+        let lifetime = data.lifetime.unwrap_or(DEFAULT_LIFETIME);
+
+        if lifetime == 0 || lifetime > MAX_LIFETIME {
+            return Err(UserError::InvalidTokenLifetime);
+        }
+
+        if name_taken(&data.name, self.user().id, None, &mut *connection).await? {
+            return Err(UserError::TokenNameTaken);
+        }
+
+        let row = sqlx::query!(
+            "INSERT INTO api_tokens (member_id, name, scopes, expires_at) VALUES ($1, $2, $3, (NOW() AT TIME ZONE 'utc') + \
+             make_interval(secs => $4)) RETURNING id, created_at, expires_at",
+            self.user().id,
+            data.name,
+            &scopes.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            lifetime as f64
+        )
+        .fetch_one(connection)
+        .await?;
+
+        let token = ScopedToken {
+            id: row.id,
+            name: data.name,
+            scopes,
+            created_at: row.created_at,
+            expires_at: row.expires_at,
+            last_used_at: None,
+        };
+
+        info!("User {} created scoped token {}", self.user(), token);
+
+        let access_token = generate_jwt(&AccessClaims {
+            sub: self.user().id.to_string(),
+            session_uuid: None,
+            gen: self.gen,
+            token: Some(token.id),
+        });
+
+        Ok((token, access_token))
+    }
+}
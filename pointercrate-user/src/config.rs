@@ -1,12 +1,213 @@
-use std::{fs::File, io::Read};
+use pointercrate_core::config::{env, override_from_env, ConfigError, ConfigSection, Section};
+use serde::Deserialize;
+use std::{collections::BTreeMap, path::PathBuf, sync::OnceLock};
 
-use pointercrate_core::util::from_env_or_default;
+#[cfg(feature = "oauth2")]
+use crate::auth::oauth::OauthProvider;
 
-pub(crate) fn secret() -> Vec<u8> {
-    let path: String = from_env_or_default("SECRET_FILE", ".secret".into());
+static USER: Section<UserConfig> = Section::new();
 
-    match File::open(path) {
-        Ok(file) => file.bytes().collect::<Result<Vec<u8>, _>>().unwrap(),
+/// The providers whose endpoints are known, and therefore do not need an issuer to be configured
+const KNOWN_OAUTH_PROVIDERS: [&str; 3] = ["google", "discord", "github"];
+
+/// The `[user]` configuration table
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct UserConfig {
+    /// Path to the file containing the key used to sign access tokens (environment variable
+    /// `SECRET_FILE`)
+    pub secret_file: PathBuf,
+
+    /// The URL this instance is reachable at, from which the redirect URIs of the OAuth2 providers
+    /// are derived as `<oauth_redirect_base>/oauth/<id>/callback/`. Required if any providers are
+    /// configured (environment variable `OAUTH_REDIRECT_BASE`).
+    pub oauth_redirect_base: Option<String>,
+
+    /// The external OAuth2 providers users can sign in with, by id (environment variable
+    /// `OAUTH_PROVIDERS`, a comma separated list of ids). Only used with the `oauth2` feature.
+    pub oauth: BTreeMap<String, OauthProviderConfig>,
+}
+
+/// A `[user.oauth.<id>]` table
+///
+/// The providers `google`, `discord` and `github` are known, all others need an `issuer` supporting
+/// OpenID Connect discovery. Each setting can be given via the environment variable
+/// `OAUTH_<ID>_<SETTING>`, e.g. `OAUTH_GOOGLE_CLIENT_ID`.
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct OauthProviderConfig {
+    pub client_id: String,
+
+    /// The client secret, if the provider requires one in addition to PKCE
+    pub client_secret: Option<String>,
+
+    /// The OpenID Connect issuer of a provider that is not known
+    pub issuer: Option<String>,
+
+    /// Overwrites the name of the provider shown to users
+    pub name: Option<String>,
+
+    /// Overwrites the requested scopes (space separated in the environment variable)
+    pub scopes: Option<Vec<String>>,
+}
+
+impl Default for UserConfig {
+    fn default() -> Self {
+        UserConfig {
+            secret_file: ".secret".into(),
+            oauth_redirect_base: None,
+            oauth: BTreeMap::new(),
+        }
+    }
+}
+
+fn oauth_env_key(provider: &str, setting: &str) -> String {
+    format!("OAUTH_{}_{}", provider.to_uppercase().replace('-', "_"), setting)
+}
+
+impl OauthProviderConfig {
+    fn apply_env(&mut self, id: &str) -> Result<(), ConfigError> {
+        override_from_env(&mut self.client_id, &oauth_env_key(id, "CLIENT_ID"))?;
+
+        if let Some(secret) = env(&oauth_env_key(id, "CLIENT_SECRET"))? {
+            self.client_secret = Some(secret);
+        }
+
+        if let Some(issuer) = env(&oauth_env_key(id, "ISSUER"))? {
+            self.issuer = Some(issuer);
+        }
+
+        if let Some(name) = env(&oauth_env_key(id, "NAME"))? {
+            self.name = Some(name);
+        }
+
+        if let Some(scopes) = env::<String>(&oauth_env_key(id, "SCOPES"))? {
+            self.scopes = Some(scopes.split_whitespace().map(str::to_string).collect());
+        }
+
+        // Legacy configuration, from when google was the only supported provider
+        if id == "google" && self.client_id.is_empty() {
+            override_from_env(&mut self.client_id, "GOOGLE_CLIENT_ID")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl ConfigSection for UserConfig {
+    const NAME: &'static str = "user";
+
+    fn apply_env(&mut self) -> Result<(), ConfigError> {
+        override_from_env(&mut self.secret_file, "SECRET_FILE")?;
+
+        if let Some(redirect_base) = env("OAUTH_REDIRECT_BASE")? {
+            self.oauth_redirect_base = Some(redirect_base);
+        }
+
+        match env::<String>("OAUTH_PROVIDERS")? {
+            Some(ids) => {
+                for id in ids.split(',').map(str::trim).filter(|id| !id.is_empty()) {
+                    self.oauth.entry(id.to_string()).or_default();
+                }
+            },
+            // For backwards compatibility, a set GOOGLE_CLIENT_ID on its own configures google
+            None if self.oauth.is_empty() && std::env::var("GOOGLE_CLIENT_ID").is_ok() => {
+                self.oauth.insert("google".to_string(), OauthProviderConfig::default());
+            },
+            None => (),
+        }
+
+        for (id, provider) in &mut self.oauth {
+            provider.apply_env(id)?;
+        }
+
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        let invalid = |setting: &'static str, message: String| ConfigError::Invalid {
+            section: Self::NAME,
+            setting,
+            message,
+        };
+
+        if self.oauth.is_empty() {
+            return Ok(());
+        }
+
+        match &self.oauth_redirect_base {
+            None => {
+                return Err(ConfigError::Missing {
+                    section: Self::NAME,
+                    setting: "oauth_redirect_base",
+                    variable: "OAUTH_REDIRECT_BASE",
+                })
+            },
+            Some(redirect_base) => match url::Url::parse(redirect_base) {
+                Ok(url) if url.scheme() == "http" || url.scheme() == "https" => (),
+                _ => {
+                    return Err(invalid(
+                        "oauth_redirect_base",
+                        format!("'{}' is not a valid http(s) URL", redirect_base),
+                    ))
+                },
+            },
+        }
+
+        for (id, provider) in &self.oauth {
+            // The id is part of URLs and environment variable names
+            if id.is_empty() || !id.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-') {
+                return Err(invalid(
+                    "oauth",
+                    format!("provider id '{}' must only consist of lowercase letters, digits and dashes", id),
+                ));
+            }
+
+            if provider.client_id.is_empty() {
+                return Err(invalid(
+                    "oauth",
+                    format!(
+                        "provider '{}' has no client_id (environment variable {})",
+                        id,
+                        oauth_env_key(id, "CLIENT_ID")
+                    ),
+                ));
+            }
+
+            if provider.issuer.is_none() && !KNOWN_OAUTH_PROVIDERS.contains(&id.as_str()) {
+                return Err(invalid(
+                    "oauth",
+                    format!(
+                        "provider '{}' is not known and needs an issuer (environment variable {})",
+                        id,
+                        oauth_env_key(id, "ISSUER")
+                    ),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Loads and validates the `[user]` configuration table, and reads the secret
+pub fn init() -> Result<(), ConfigError> {
+    USER.init()?;
+
+    load_secret().map(|_| ())
+}
+
+fn load_secret() -> Result<&'static [u8], ConfigError> {
+    static SECRET: OnceLock<Vec<u8>> = OnceLock::new();
+
+    if let Some(secret) = SECRET.get() {
+        return Ok(secret);
+    }
+
+    let path = &USER.get()?.secret_file;
+
+    let secret = match std::fs::read(path) {
+        Ok(secret) => secret,
         Err(err) if cfg!(debug_assertions) => {
             // needed for integration tests/CI
             log::error!(
@@ -16,10 +217,100 @@ pub(crate) fn secret() -> Vec<u8> {
 
             vec![0x0; 64]
         },
-        Err(err) => panic!("Unable to open secret file: {:?}", err),
+        Err(source) => {
+            return Err(ConfigError::Io {
+                path: path.clone(),
+                source,
+            })
+        },
+    };
+
+    Ok(SECRET.get_or_init(|| secret))
+}
+
+pub(crate) fn secret() -> &'static [u8] {
+    load_secret().unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// The OAuth2 providers configured in the `[user]` table, see [`UserConfig::oauth`]
+#[cfg(feature = "oauth2")]
+pub fn oauth_providers() -> Result<&'static [OauthProvider], ConfigError> {
+    static PROVIDERS: OnceLock<Vec<OauthProvider>> = OnceLock::new();
+
+    if let Some(providers) = PROVIDERS.get() {
+        return Ok(providers);
     }
+
+    let config = USER.get()?;
+
+    // Validation guarantees the redirect base to be set if there are any providers
+    let redirect_base = config.oauth_redirect_base.as_deref().unwrap_or_default();
+
+    let providers = config
+        .oauth
+        .iter()
+        .map(|(id, provider)| OauthProvider::from_config(id, provider, redirect_base))
+        .collect();
+
+    Ok(PROVIDERS.get_or_init(|| providers))
 }
 
-pub fn google_client_id() -> String {
-    std::env::var("GOOGLE_CLIENT_ID").expect("GOOGLE_CLIENT_ID is not set")
+#[cfg(test)]
+mod tests {
+    use super::{OauthProviderConfig, UserConfig};
+    use pointercrate_core::config::{ConfigError, ConfigSection};
+
+    fn with_provider(id: &str, provider: OauthProviderConfig) -> UserConfig {
+        let mut config = UserConfig {
+            oauth_redirect_base: Some("https://pointercrate.com".to_string()),
+            ..Default::default()
+        };
+
+        config.oauth.insert(id.to_string(), provider);
+        config
+    }
+
+    #[test]
+    fn test_validate_oauth() {
+        let google = OauthProviderConfig {
+            client_id: "client".to_string(),
+            ..Default::default()
+        };
+
+        assert!(UserConfig::default().validate().is_ok());
+        assert!(with_provider("google", google.clone()).validate().is_ok());
+
+        let mut config = with_provider("google", google.clone());
+        config.oauth_redirect_base = None;
+
+        assert!(matches!(config.validate(), Err(ConfigError::Missing { .. })));
+
+        config.oauth_redirect_base = Some("pointercrate.com".to_string());
+
+        assert!(matches!(config.validate(), Err(ConfigError::Invalid { .. })));
+
+        let without_client_id = OauthProviderConfig::default();
+
+        assert!(matches!(
+            with_provider("google", without_client_id).validate(),
+            Err(ConfigError::Invalid { .. })
+        ));
+
+        // Unknown providers need to support OpenID Connect discovery
+        assert!(matches!(
+            with_provider("gitlab", google.clone()).validate(),
+            Err(ConfigError::Invalid { .. })
+        ));
+
+        let gitlab = OauthProviderConfig {
+            issuer: Some("https://gitlab.com".to_string()),
+            ..google
+        };
+
+        assert!(with_provider("gitlab", gitlab.clone()).validate().is_ok());
+        assert!(matches!(
+            with_provider("GitLab", gitlab).validate(),
+            Err(ConfigError::Invalid { .. })
+        ));
+    }
 }
@@ -39,11 +39,47 @@ pub enum UserError {
         user_name: String,
     },
 
+    /// `404 NOT FOUND` error returned if the current user has no access token with the given id
+    ///
+    /// Error Code `40401`
+    ApiTokenNotFound {
+        token_id: i32,
+    },
+
+    /// `404 NOT FOUND` error returned if no OAuth2 provider with the given id is configured
+    ///
+    /// Error Code `40401`
+    OauthProviderNotFound {
+        provider: String,
+    },
+
     /// `409 CONFLICT` error returned if a user tries to register with a name that's already taken
     ///
     /// Error Code `40902`
     NameTaken,
 
+    /// `409 CONFLICT` error returned if a user tries to create an access token with the same name
+    /// as one of their existing tokens
+    ///
+    /// Error Code `40910`
+    TokenNameTaken,
+
+    /// `409 CONFLICT` error returned if a user tries to link an identity at an OAuth2 provider that
+    /// is already linked to a different pointercrate account
+    ///
+    /// Error Code `40911`
+    OauthIdentityTaken {
+        provider: String,
+    },
+
+    /// `409 CONFLICT` error returned if a user tries to link a second identity at an OAuth2
+    /// provider they already have a linked identity with
+    ///
+    /// Error Code `40912`
+    OauthProviderAlreadyLinked {
+        provider: String,
+    },
+
     /// `422 UNPROCESSABLE ENTITIY` variant returned if the username provided during registration
     /// is either shorter than 3 letters of contains trailing or leading whitespaces
     ///
@@ -67,6 +103,39 @@ pub enum UserError {
     ///
     /// Error Code `42234`
     NonLegacyAccount,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if the TOTP secret provided when enabling
+    /// two-factor authentication is not valid base32, or shorter than 128 bits
+    ///
+    /// Error Code `42237`
+    InvalidTotpSecret,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if the code provided when enabling two-factor
+    /// authentication was not generated from the provided secret (e.g. because the authenticator
+    /// app was not set up correctly)
+    ///
+    /// Error Code `42238`
+    InvalidTotpCode,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if the name of an access token is empty, longer
+    /// than 64 characters, or starts/ends with whitespace
+    ///
+    /// Error Code `42239`
+    InvalidTokenName,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if an access token is requested with a scope
+    /// that is not of the form `<resource>:<read|create|write>`, or with no scopes at all
+    ///
+    /// Error Code `42240`
+    InvalidScope {
+        scope: String,
+    },
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if an access token is requested with a lifetime
+    /// of zero, or a lifetime longer than one year
+    ///
+    /// Error Code `42241`
+    InvalidTokenLifetime,
 }
 
 impl std::error::Error for UserError {}
@@ -90,11 +159,28 @@ impl PointercrateError for UserError {
             PermissionNotAssignable { .. } => 40305,
             UserNotFound { .. } => 40401,
             UserNotFoundName { .. } => 40401,
+            ApiTokenNotFound { .. } => 40401,
+            OauthProviderNotFound { .. } => 40401,
             NameTaken => 40902,
+            TokenNameTaken => 40910,
+            OauthIdentityTaken { .. } => 40911,
+            OauthProviderAlreadyLinked { .. } => 40912,
             InvalidUsername => 42202,
             InvalidPassword => 42204,
             NotYouTube => 42226,
             NonLegacyAccount => 42234,
+            InvalidTotpSecret => 42237,
+            InvalidTotpCode => 42238,
+            InvalidTokenName => 42239,
+            InvalidScope { .. } => 42240,
+            InvalidTokenLifetime => 42241,
+        }
+    }
+
+    fn headers(&self) -> Vec<(&'static str, String)> {
+        match self {
+            UserError::Core(core) => core.headers(),
+            _ => Vec::new(),
         }
     }
 }
@@ -121,11 +207,21 @@ impl Display for UserError {
                 ),
                 UserError::UserNotFound { user_id } => trp!("error-user-usernotfound", "user-id" = user_id),
                 UserError::UserNotFoundName { user_name } => trp!("error-user-usernotfoundname", "user-name" = user_name),
+                UserError::ApiTokenNotFound { token_id } => trp!("error-user-apitokennotfound", "token-id" = token_id),
+                UserError::OauthProviderNotFound { provider } => trp!("error-user-oauthprovidernotfound", "provider" = provider),
                 UserError::NameTaken => tr("error-user-nametaken"),
+                UserError::TokenNameTaken => tr("error-user-tokennametaken"),
+                UserError::OauthIdentityTaken { provider } => trp!("error-user-oauthidentitytaken", "provider" = provider),
+                UserError::OauthProviderAlreadyLinked { provider } => trp!("error-user-oauthprovideralreadylinked", "provider" = provider),
                 UserError::InvalidUsername => tr("error-user-invalidusername"),
                 UserError::InvalidPassword => tr("error-user-invalidpassword"),
                 UserError::NotYouTube => tr("error-user-notyoutube"),
                 UserError::NonLegacyAccount => tr("error-user-nonlegacyaccount"),
+                UserError::InvalidTotpSecret => tr("error-user-invalidtotpsecret"),
+                UserError::InvalidTotpCode => tr("error-user-invalidtotpcode"),
+                UserError::InvalidTokenName => tr("error-user-invalidtokenname"),
+                UserError::InvalidScope { scope } => trp!("error-user-invalidscope", "scope" = scope),
+                UserError::InvalidTokenLifetime => tr("error-user-invalidtokenlifetime"),
             }
         )
     }
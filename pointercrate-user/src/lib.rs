@@ -5,7 +5,10 @@
 //! * Modifying other people's accounts (assign permissions, change offensive names, etc)
 //! * Querying account information
 
-pub use self::{paginate::UserPagination, patch::PatchUser};
+pub use self::{
+    paginate::{UserPagination, UserSortKey},
+    patch::PatchUser,
+};
 use crate::error::{Result, UserError};
 use pointercrate_core::{
     etag::Taggable,
@@ -37,7 +40,7 @@ pub fn default_permissions_manager() -> PermissionsManager {
 }
 
 /// Model representing a user in the database
-#[derive(Debug, Serialize, Hash, Eq, PartialEq)]
+#[derive(Debug, Clone, Serialize, Hash, Eq, PartialEq)]
 pub struct User {
     /// The [`User`]'s unique ID. This is used to identify users and cannot be changed.
     pub id: i32,
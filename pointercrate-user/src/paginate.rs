@@ -2,18 +2,59 @@ use crate::{error::Result, User};
 use futures::StreamExt;
 use pointercrate_core::{
     first_and_last,
-    pagination::{PageContext, Paginatable, PaginationParameters, PaginationQuery, __pagination_compat},
+    pagination::{
+        __sorted_pagination_compat, count_query, PageContext, Paginatable, PaginationParameters, PaginationQuery, SortKey, SortParameters,
+        SortValue, Sortable, SortedPaginationQuery,
+    },
     permission::Permission,
     util::{non_nullable, nullable},
 };
 use serde::{Deserialize, Serialize};
-use sqlx::{postgres::PgRow, PgConnection, Row};
+use sqlx::{
+    postgres::{PgArguments, PgRow},
+    query::Query,
+    PgConnection, Postgres, Row,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum UserSortKey {
+    #[default]
+    Id,
+    Name,
+}
+
+impl SortKey for UserSortKey {
+    fn name(&self) -> &'static str {
+        match self {
+            UserSortKey::Id => "id",
+            UserSortKey::Name => "name",
+        }
+    }
+
+    fn column(&self) -> &'static str {
+        match self {
+            UserSortKey::Id => "member_id",
+            UserSortKey::Name => "name",
+        }
+    }
+
+    fn sql_type(&self) -> &'static str {
+        match self {
+            UserSortKey::Id => "INTEGER",
+            UserSortKey::Name => "TEXT",
+        }
+    }
+}
 
 #[derive(Deserialize, Debug, Clone, Serialize)]
 pub struct UserPagination {
     #[serde(flatten)]
     pub params: PaginationParameters,
 
+    #[serde(flatten)]
+    pub sorting: SortParameters<UserSortKey>,
+
     #[serde(default, deserialize_with = "non_nullable")]
     pub name: Option<String>,
 
@@ -41,27 +82,61 @@ impl PaginationQuery for UserPagination {
             ..self.clone()
         }
     }
+
+    fn preceding(&self) -> Option<Self> {
+        self.sorted_preceding()
+    }
+
+    fn following(&self) -> Option<Self> {
+        self.sorted_following()
+    }
 }
 
-impl Paginatable<UserPagination> for User {
-    first_and_last!("members", "member_id");
+impl UserPagination {
+    fn sql(&self) -> String {
+        format!(
+            include_str!("../sql/paginate_users.sql"),
+            cursor_conditions = self.sorting.cursor_conditions("member_id", 10),
+            order_by = self.sorting.order_by(&self.params, "member_id")
+        )
+    }
+
+    /// Binds the parameters of the given query, which is (derived from) the one returned by
+    /// [`UserPagination::sql`], returning at most `limit` rows (if given)
+    fn sql_query<'q>(&'q self, sql: &'q str, limit: Option<i32>) -> Query<'q, Postgres, PgArguments> {
+        let query = sqlx::query(sql)
+            .bind(self.params.before)
+            .bind(self.params.after)
+            .bind(self.name.as_ref())
+            .bind(self.display_name.as_ref())
+            .bind(self.display_name == Some(None))
+            .bind(self.has_permissions.map(|p| p as i32))
+            .bind(self.any_permissions.map(|p| p as i32))
+            .bind(self.name_contains.as_ref())
+            .bind(limit);
+
+        self.sorting.bind_cursors(query)
+    }
+}
 
-    async fn page(query: &UserPagination, connection: &mut PgConnection) -> std::result::Result<(Vec<User>, PageContext), sqlx::Error> {
-        let order = query.params.order();
+impl SortedPaginationQuery for UserPagination {
+    type Key = UserSortKey;
 
-        let sql_query = format!(include_str!("../sql/paginate_users.sql"), order);
+    fn sorting(&self) -> &SortParameters<UserSortKey> {
+        &self.sorting
+    }
 
-        let mut stream = sqlx::query(&sql_query)
-            .bind(query.params.before)
-            .bind(query.params.after)
-            .bind(query.name.as_ref())
-            .bind(query.display_name.as_ref())
-            .bind(query.display_name == Some(None))
-            .bind(query.has_permissions.map(|p| p as i32))
-            .bind(query.any_permissions.map(|p| p as i32))
-            .bind(query.name_contains.as_ref())
-            .bind(query.params.limit + 1)
-            .fetch(connection);
+    fn with_sorting(&self, sorting: SortParameters<UserSortKey>) -> Self {
+        Self { sorting, ..self.clone() }
+    }
+}
+
+impl Paginatable<UserPagination> for User {
+    first_and_last!(UserPagination, "members", "member_id");
+
+    async fn fetch(query: &UserPagination, connection: &mut PgConnection) -> std::result::Result<(Vec<User>, PageContext), sqlx::Error> {
+        let sql = query.sql();
+        let mut stream = query.sql_query(&sql, Some(query.params.limit + 1)).fetch(connection);
 
         let mut users = Vec::new();
 
@@ -79,7 +154,16 @@ impl Paginatable<UserPagination> for User {
             })
         }
 
-        Ok(__pagination_compat(&query.params, users))
+        Ok(__sorted_pagination_compat(&query.params, &query.sorting, users))
+    }
+
+    async fn count(query: &UserPagination, connection: &mut PgConnection) -> std::result::Result<Option<i64>, sqlx::Error> {
+        let query = query
+            .with_parameters(PaginationParameters::default())
+            .with_sorting(SortParameters::default());
+        let sql = count_query(&query.sql());
+
+        Ok(Some(query.sql_query(&sql, None).fetch_one(connection).await?.try_get(0)?))
     }
 
     fn pagination_id(&self) -> i32 {
@@ -87,6 +171,15 @@ impl Paginatable<UserPagination> for User {
     }
 }
 
+impl Sortable<UserPagination> for User {
+    fn sort_value(&self, key: UserSortKey) -> SortValue {
+        match key {
+            UserSortKey::Id => SortValue::Integer(self.id as i64),
+            UserSortKey::Name => SortValue::Text(self.name.clone()),
+        }
+    }
+}
+
 impl User {
     pub async fn by_permission(permission: Permission, connection: &mut PgConnection) -> Result<Vec<User>> {
         User::by_permissions(permission.bit(), connection).await